@@ -3,29 +3,40 @@ use parser::CatchParams;
 use utils::*;
 
 use syntax::codemap::{Span};
-use syntax::ast::{MetaItem, Ident, TyKind};
+use syntax::ast::{MetaItem, Ident, Ty, TyKind};
 use syntax::ext::base::{Annotatable, ExtCtxt};
 use syntax::tokenstream::TokenTree;
 use syntax::parse::token;
 
 const ERR_PARAM: &'static str = "__err";
 const REQ_PARAM: &'static str = "__req";
+const CTX_PARAM: &'static str = "__ctx";
+
+// Whether `ty` is a path to a type named `CatchContext`.
+fn is_catch_context(ty: &Ty) -> bool {
+    match ty.node {
+        TyKind::Path(_, ref path) => path.segments.last()
+            .map_or(false, |segment| segment.ident.name == "CatchContext"),
+        _ => false
+    }
+}
 
 trait CatchGenerateExt {
-    fn generate_fn_arguments(&self, &ExtCtxt, Ident, Ident) -> Vec<TokenTree>;
+    fn generate_fn_arguments(&self, &ExtCtxt, Ident, Ident, Ident) -> Vec<TokenTree>;
+    fn takes_context(&self) -> bool;
 }
 
 impl CatchGenerateExt for CatchParams {
-    fn generate_fn_arguments(&self, ecx: &ExtCtxt, err: Ident, req: Ident)
+    fn generate_fn_arguments(&self, ecx: &ExtCtxt, err: Ident, req: Ident, ctx: Ident)
             -> Vec<TokenTree> {
-        let arg_help = "error catchers can take either a `rocket::Error`, \
-                      `rocket::Request` type, or both.";
+        let arg_help = "error catchers can take any of a `rocket::Error`, \
+                      `rocket::Request`, and `rocket::request::CatchContext` type.";
 
         // Retrieve the params from the user's handler and check the number.
         let input_args = &self.annotated_fn.decl().inputs;
-        if input_args.len() > 2 {
+        if input_args.len() > 3 {
             let sp = self.annotated_fn.span();
-            ecx.struct_span_err(sp, "error catchers can have at most 2 arguments")
+            ecx.struct_span_err(sp, "error catchers can have at most 3 arguments")
                 .help(arg_help).emit()
         }
 
@@ -33,6 +44,7 @@ impl CatchGenerateExt for CatchParams {
         let args = input_args.iter().map(|arg| &arg.ty).filter_map(|ty| {
             match ty.node {
                 TyKind::Rptr(..) => Some(req),
+                TyKind::Path(..) if is_catch_context(ty) => Some(ctx),
                 TyKind::Path(..) => Some(err),
                 _ => {
                     ecx.struct_span_err(ty.span, "unknown error catcher argument")
@@ -46,6 +58,10 @@ impl CatchGenerateExt for CatchParams {
 
         sep_by_tok(ecx, &args, token::Comma)
     }
+
+    fn takes_context(&self) -> bool {
+        self.annotated_fn.decl().inputs.iter().any(|arg| is_catch_context(&arg.ty))
+    }
 }
 
 pub fn catch_decorator(
@@ -65,13 +81,21 @@ pub fn catch_decorator(
     let code = catch.code.node;
     let err_ident = Ident::from_str(ERR_PARAM);
     let req_ident = Ident::from_str(REQ_PARAM);
-    let fn_arguments = catch.generate_fn_arguments(ecx, err_ident, req_ident);
+    let ctx_ident = Ident::from_str(CTX_PARAM);
+    let fn_arguments = catch.generate_fn_arguments(ecx, err_ident, req_ident, ctx_ident);
+
+    // Retrieve the catch context only if the user's catcher asks for it.
+    let ctx_stmt = match catch.takes_context() {
+        true => quote_stmt!(ecx, let $ctx_ident = $req_ident.catch_context();),
+        false => None
+    };
 
     // Push the Rocket generated catch function.
     emit_item(&mut output, quote_item!(ecx,
         fn $catch_fn_name<'_b>($err_ident: ::rocket::Error,
                                $req_ident: &'_b ::rocket::Request)
                                -> ::rocket::response::Result<'_b> {
+            $ctx_stmt
             let user_response = $user_fn_name($fn_arguments);
             let response = ::rocket::response::Responder::respond_to(user_response,
                                                                      $req_ident)?;
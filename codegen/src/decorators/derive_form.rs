@@ -16,6 +16,7 @@ use syntax_ext::deriving::generic::{StaticStruct, Substructure, TraitDef, ty};
 use syntax_ext::deriving::generic::combine_substructure as c_s;
 
 use utils::{strip_ty_lifetimes, is_valid_ident, SpanExt};
+use super::derive_validate::{extract_validators, validation_stmts};
 
 static ONLY_STRUCTS_ERR: &'static str = "`FromForm` can only be derived for \
     structures with named fields.";
@@ -63,7 +64,7 @@ pub fn from_form_derive(
     };
 
     // The error type in the derived implementation.
-    let error_type = ty::Ty::Literal(ty::Path::new_(vec!["rocket", "Error"],
+    let error_type = ty::Ty::Literal(ty::Path::new_(vec!["rocket", "request", "FormErrors"],
                                                     None, vec![], ty::PathKind::Global));
 
     let trait_def = TraitDef {
@@ -135,59 +136,128 @@ fn is_valid_field_name(name: &str) -> bool {
         .all(|c| (c >= ' ' && c <= '~') && c != '&' && c != '=' && c != '?')
 }
 
-pub fn extract_field_ident_name(ecx: &ExtCtxt, struct_field: &StructField)
-        -> (Ident, String, Span) {
+/// The parsed `#[form(..)]` attribute of a single struct field.
+pub struct FormField {
+    pub ident: Ident,
+    pub name: String,
+    pub span: Span,
+    pub transforms: Vec<(String, Span)>,
+}
+
+const FORM_ATTR_HELP: &'static str =
+    r#"the `form` attribute must have the form: #[form(field = "..", transform = "..")]"#;
+
+pub fn extract_field_ident_name(ecx: &ExtCtxt, struct_field: &StructField) -> FormField {
     let ident = match struct_field.ident {
         Some(ident) => ident,
         None => ecx.span_fatal(struct_field.span, ONLY_STRUCTS_ERR)
     };
 
+    let mut field = FormField {
+        ident: ident,
+        name: ident.to_string(),
+        span: struct_field.span,
+        transforms: vec![],
+    };
+
     let field_attrs: Vec<_> = struct_field.attrs.iter()
         .filter(|attr| attr.check_name("form"))
         .collect();
 
-    let default = |ident: Ident| (ident, ident.to_string(), struct_field.span);
     if field_attrs.len() == 0 {
-        return default(ident);
+        return field;
     } else if field_attrs.len() > 1 {
         ecx.span_err(struct_field.span, "only a single #[form(..)] \
             attribute can be applied to a given struct field at a time");
-        return default(ident);
+        return field;
     }
 
     let field_attr = field_attrs[0];
     ::syntax::attr::mark_known(&field_attr);
-    if !field_attr.meta_item_list().map_or(false, |l| l.len() == 1) {
+    let items = field_attr.meta_item_list().unwrap_or_else(Vec::new);
+
+    if items.is_empty() {
         ecx.struct_span_err(field_attr.span, "incorrect use of attribute")
-            .help(r#"the `form` attribute must have the form: #[form(field = "..")]"#)
+            .help(FORM_ATTR_HELP)
             .emit();
-        return default(ident);
+        return field;
     }
 
-    let inner_item = &field_attr.meta_item_list().unwrap()[0];
-    if !inner_item.check_name("field") {
-        ecx.struct_span_err(inner_item.span, "invalid `form` attribute contents")
-            .help(r#"only the 'field' key is supported: #[form(field = "..")]"#)
-            .emit();
-        return default(ident);
-    }
+    let mut seen = vec![];
+    for inner_item in &items {
+        let key = match inner_item.name().map(|n| n.as_str().to_string()) {
+            Some(key) if key == "field" || key == "transform" => key,
+            _ => {
+                ecx.struct_span_err(inner_item.span, "invalid `form` attribute contents")
+                    .help(r#"only the 'field' and 'transform' keys are supported"#)
+                    .emit();
+                continue;
+            }
+        };
 
-    if !inner_item.is_value_str() {
-        ecx.struct_span_err(inner_item.span, "invalid `field` in attribute")
-            .help(r#"the `form` attribute must have the form: #[form(field = "..")]"#)
-            .emit();
-        return default(ident);
-    }
+        if seen.contains(&key) {
+            ecx.struct_span_err(field_attr.span, "incorrect use of attribute")
+                .help(FORM_ATTR_HELP)
+                .emit();
+            return field;
+        }
 
-    let name = inner_item.value_str().unwrap().as_str().to_string();
-    let sp = inner_item.span.shorten_upto(name.len() + 2);
-    if !is_valid_field_name(&name) {
-        ecx.struct_span_err(sp, "invalid form field name")
-            .help("field names must be visible ASCII without '&', '=', or '?'")
-            .emit();
+        let value = match inner_item.value_str() {
+            Some(value) => value.as_str().to_string(),
+            None => {
+                ecx.struct_span_err(inner_item.span, &format!("invalid `{}` in attribute", key))
+                    .help(FORM_ATTR_HELP)
+                    .emit();
+                continue;
+            }
+        };
+
+        let sp = inner_item.span.shorten_upto(value.len() + 2);
+        let is_field = key == "field";
+        seen.push(key);
+
+        if is_field {
+            if !is_valid_field_name(&value) {
+                ecx.struct_span_err(sp, "invalid form field name")
+                    .help("field names must be visible ASCII without '&', '=', or '?'")
+                    .emit();
+            }
+
+            field.name = value;
+            field.span = sp;
+        } else {
+            for transform in value.split(',').map(|t| t.trim()) {
+                let valid = !transform.is_empty() && transform.split("::")
+                    .all(|segment| is_valid_ident(segment));
+
+                if !valid {
+                    ecx.struct_span_err(sp, "invalid transformer name")
+                        .help("transformers are builtin names like `trim` or \
+                              paths to functions of type `fn(String) -> String`")
+                        .emit();
+                    continue;
+                }
+
+                field.transforms.push((transform.to_string(), sp));
+            }
+        }
     }
 
-    (ident, name, sp)
+    field
+}
+
+/// Builtin transformers, in `rocket::request::transform`.
+const BUILTIN_TRANSFORMS: &'static [&'static str] = &["trim", "lowercase", "uppercase"];
+
+fn transform_path(cx: &ExtCtxt, name: &str, sp: Span) -> P<Expr> {
+    let path = if BUILTIN_TRANSFORMS.contains(&name) {
+        let segments = vec!["rocket", "request", "transform", name];
+        cx.path_global(sp, segments.into_iter().map(Ident::from_str).collect())
+    } else {
+        cx.path(sp, name.split("::").map(Ident::from_str).collect())
+    };
+
+    cx.expr_path(path)
 }
 
 fn from_form_substructure(cx: &mut ExtCtxt, trait_span: Span, substr: &Substructure) -> P<Expr> {
@@ -216,33 +286,44 @@ fn from_form_substructure(cx: &mut ExtCtxt, trait_span: Span, substr: &Substruct
     let mut names = HashMap::new();
     let mut fields_info = vec![];
     for field in fields {
-        let (ident, name, span) = extract_field_ident_name(cx, field);
+        let form_field = extract_field_ident_name(cx, field);
         let stripped_ty = strip_ty_lifetimes(field.ty.clone());
 
-        if let Some(sp) = names.get(&name).map(|sp| *sp) {
-            cx.struct_span_err(span, "field with duplicate name")
+        if let Some(sp) = names.get(&form_field.name).map(|sp| *sp) {
+            cx.struct_span_err(form_field.span, "field with duplicate name")
                 .span_note(sp, "original was declared here")
                 .emit();
         } else {
-            names.insert(name.clone(), span);
+            names.insert(form_field.name.clone(), form_field.span);
         }
 
-        fields_info.push((ident, stripped_ty, name));
+        let transforms: Vec<_> = form_field.transforms.iter()
+            .map(|&(ref name, sp)| {
+                let path = transform_path(cx, name, sp);
+                quote_tokens!(cx, $path as fn(::std::string::String) -> ::std::string::String,)
+            })
+            .collect();
+
+        let validators = extract_validators(cx, &field.attrs);
+        fields_info.push((form_field.ident, stripped_ty, form_field.name, transforms, validators));
     }
 
-    debug!("Fields, types, attrs: {:?}", fields_info);
+    debug!("Fields, types, attrs: {:?}", fields_info.iter()
+           .map(|&(ref i, ref t, ref n, ref tr, _)| (i, t, n, tr))
+           .collect::<Vec<_>>());
     let mut stmts = Vec::new();
 
-    // The thing to do when we wish to exit with an error.
-    let return_err_stmt = quote_tokens!(cx,
-        return Err(::rocket::Error::BadParse)
-    );
+    // Every error encountered is recorded here so that all of them, not just
+    // the first, can be reported.
+    stmts.push(quote_stmt!(cx,
+        let mut __errors = ::rocket::request::FormErrors::new();
+    ).unwrap());
 
     // Generate the let bindings for parameters that will be unwrapped and
     // placed into the final struct. They start out as `None` and are changed
     // to Some when a parse completes, or some default value if the parse was
     // unsuccessful and default() returns Some.
-    for &(ref ident, ref ty, _) in &fields_info {
+    for &(ref ident, ref ty, _, _, _) in &fields_info {
         stmts.push(quote_stmt!(cx,
             let mut $ident: ::std::option::Option<$ty> = None;
         ).unwrap());
@@ -251,16 +332,27 @@ fn from_form_substructure(cx: &mut ExtCtxt, trait_span: Span, substr: &Substruct
     // Generating an arm for each struct field. This matches against the key and
     // tries to parse the value according to the type.
     let mut arms = vec![];
-    for &(ref ident, _, ref name) in &fields_info {
+    for &(ref ident, _, ref name, ref transforms, _) in &fields_info {
+        // When there are transformers, the value is decoded, transformed, and
+        // re-encoded before being handed to `FromFormValue`.
+        let value = if transforms.is_empty() {
+            quote_tokens!(cx, let __r = ::rocket::http::RawStr::from_str(__v);)
+        } else {
+            quote_tokens!(cx,
+                let __t = ::rocket::request::transform::apply(__v, &[$transforms]);
+                let __r = ::rocket::http::RawStr::from_str(&__t);
+            )
+        };
+
         arms.push(quote_tokens!(cx,
             $name => {
-                let __r = ::rocket::http::RawStr::from_str(__v);
-                $ident = match ::rocket::request::FromFormValue::from_form_value(__r) {
-                    Ok(__v) => Some(__v),
+                $value
+                match ::rocket::request::FromFormValue::from_form_value(__r) {
+                    Ok(__v) => $ident = Some(__v),
                     Err(__e) => {
                         println!("    => Error parsing form val '{}': {:?}",
                                  $name, __e);
-                        $return_err_stmt
+                        __errors.push(::rocket::request::FormError::invalid($name, __r.as_str()));
                     }
                 };
             },
@@ -279,7 +371,7 @@ fn from_form_substructure(cx: &mut ExtCtxt, trait_span: Span, substr: &Substruct
                     if $strict_arg && __k != "_method" {
                         println!("    => {}={} has no matching field in struct.",
                                  __k, __v);
-                        $return_err_stmt
+                        __errors.push(::rocket::request::FormError::unexpected(__k.as_str(), __v.as_str()));
                     }
                 }
            };
@@ -287,15 +379,34 @@ fn from_form_substructure(cx: &mut ExtCtxt, trait_span: Span, substr: &Substruct
     ).unwrap());
 
     // This looks complicated but just generates the boolean condition checking
-    // that each parameter actually is Some() or has a default value.
+    // that each parameter actually is Some() or has a default value. Fields
+    // that failed to parse already have an error recorded.
     let mut failure_conditions = vec![];
 
-    for &(ref ident, ref ty, _) in (&fields_info).iter() {
+    for &(ref ident, ref ty, ref name, _, _) in (&fields_info).iter() {
         failure_conditions.push(quote_tokens!(cx,
             if $ident.is_none() &&
-                <$ty as ::rocket::request::FromFormValue>::default().is_none() {
+                <$ty as ::rocket::request::FromFormValue>::default().is_none() &&
+                __errors.field($name).next().is_none() {
                 println!("    => '{}' did not parse.", stringify!($ident));
-                $return_err_stmt;
+                __errors.push(::rocket::request::FormError::missing($name));
+            }
+        ));
+    }
+
+    // Run the validators of every field that parsed, so that validation errors
+    // are reported alongside parse errors.
+    let mut validations = vec![];
+    for &(ref ident, _, ref name, _, ref validators) in &fields_info {
+        if validators.is_empty() {
+            continue;
+        }
+
+        let value = quote_expr!(cx, __f);
+        let stmts = validation_stmts(cx, name, &value, validators);
+        validations.push(quote_tokens!(cx,
+            if let Some(ref __f) = $ident {
+                $stmts
             }
         ));
     }
@@ -303,7 +414,7 @@ fn from_form_substructure(cx: &mut ExtCtxt, trait_span: Span, substr: &Substruct
     // The fields of the struct, which are just the let bindings declared above
     // or the default value.
     let mut result_fields = vec![];
-    for &(ref ident, ref ty, _) in &fields_info {
+    for &(ref ident, ref ty, _, _, _) in &fields_info {
         result_fields.push(quote_tokens!(cx,
             $ident: $ident.unwrap_or_else(||
                 <$ty as ::rocket::request::FromFormValue>::default().unwrap()
@@ -316,6 +427,11 @@ fn from_form_substructure(cx: &mut ExtCtxt, trait_span: Span, substr: &Substruct
     let self_ident = substr.type_ident;
     let final_block = quote_block!(cx, {
         $failure_conditions
+        $validations
+
+        if !__errors.is_empty() {
+            return Err(__errors);
+        }
 
         Ok($self_ident { $result_fields })
     });
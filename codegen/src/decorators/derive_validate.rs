@@ -0,0 +1,282 @@
+#![allow(unused_imports)] // FIXME: Why is this coming from quote_tokens?
+
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ast::{Expr, MetaItem, Attribute, LitKind, NestedMetaItem, MetaItemKind};
+use syntax::codemap::Span;
+use syntax::ext::build::AstBuilder;
+use syntax::symbol::Symbol;
+use syntax::tokenstream::TokenTree;
+use syntax::ptr::P;
+
+use syntax_ext::deriving::generic::MethodDef;
+use syntax_ext::deriving::generic::{Struct, Substructure, TraitDef, ty};
+use syntax_ext::deriving::generic::combine_substructure as c_s;
+
+static ONLY_STRUCTS_ERR: &'static str = "`Validate` can only be derived for \
+    structures with named fields.";
+
+const VALIDATE_ATTR_HELP: &'static str = "supported validators are: \
+    `length(min = N, max = N)`, `range(min = N, max = N)`, `regex = \"..\"`, \
+    and `email`";
+
+/// A single validator from a `#[validate(..)]` attribute.
+pub enum Validator {
+    Length(Option<usize>, Option<usize>),
+    Range(Option<f64>, Option<f64>),
+    Regex(String),
+    Email,
+}
+
+fn bound_usize(ecx: &ExtCtxt, item: &NestedMetaItem) -> Option<usize> {
+    match item.meta_item().map(|mi| &mi.node) {
+        Some(&MetaItemKind::NameValue(ref lit)) => match lit.node {
+            LitKind::Int(n, _) => return Some(n as usize),
+            _ => {}
+        },
+        _ => {}
+    }
+
+    ecx.struct_span_err(item.span, "invalid `length` bound")
+        .help("bounds must be non-negative integers: `length(min = 3)`")
+        .emit();
+    None
+}
+
+fn bound_f64(ecx: &ExtCtxt, item: &NestedMetaItem) -> Option<f64> {
+    match item.meta_item().map(|mi| &mi.node) {
+        Some(&MetaItemKind::NameValue(ref lit)) => match lit.node {
+            LitKind::Int(n, _) => return Some(n as f64),
+            LitKind::Float(ref s, _) | LitKind::FloatUnsuffixed(ref s) => {
+                if let Ok(n) = s.as_str().parse() {
+                    return Some(n);
+                }
+            }
+            // Meta items can't contain negative literals; allow them as strings.
+            LitKind::Str(ref s, _) => {
+                if let Ok(n) = s.as_str().parse() {
+                    return Some(n);
+                }
+            }
+            _ => {}
+        },
+        _ => {}
+    }
+
+    ecx.struct_span_err(item.span, "invalid `range` bound")
+        .help("bounds must be numbers: `range(min = 1, max = 10)` or `range(min = \"-1\")`")
+        .emit();
+    None
+}
+
+fn parse_validator(ecx: &ExtCtxt, item: &NestedMetaItem) -> Option<Validator> {
+    let meta_item = match item.meta_item() {
+        Some(meta_item) => meta_item,
+        None => {
+            ecx.struct_span_err(item.span, "invalid validator")
+                .help(VALIDATE_ATTR_HELP)
+                .emit();
+            return None;
+        }
+    };
+
+    let name = meta_item.name().as_str().to_string();
+    match (name.as_str(), &meta_item.node) {
+        ("email", &MetaItemKind::Word) => Some(Validator::Email),
+        ("regex", &MetaItemKind::NameValue(ref lit)) => match lit.node {
+            LitKind::Str(ref s, _) => Some(Validator::Regex(s.as_str().to_string())),
+            _ => {
+                ecx.span_err(item.span, "`regex` must be a string literal");
+                None
+            }
+        },
+        ("length", &MetaItemKind::List(ref bounds))
+            | ("range", &MetaItemKind::List(ref bounds)) => {
+            let (mut min_item, mut max_item) = (None, None);
+            for bound in bounds {
+                if bound.check_name("min") && min_item.is_none() {
+                    min_item = Some(bound);
+                } else if bound.check_name("max") && max_item.is_none() {
+                    max_item = Some(bound);
+                } else {
+                    ecx.struct_span_err(bound.span, "invalid bound")
+                        .help("only one `min` and one `max` bound may be given")
+                        .emit();
+                }
+            }
+
+            if min_item.is_none() && max_item.is_none() {
+                ecx.struct_span_err(item.span, "missing bounds")
+                    .help("at least one of `min` or `max` must be given")
+                    .emit();
+                return None;
+            }
+
+            if name == "length" {
+                let min = min_item.and_then(|i| bound_usize(ecx, i));
+                let max = max_item.and_then(|i| bound_usize(ecx, i));
+                Some(Validator::Length(min, max))
+            } else {
+                let min = min_item.and_then(|i| bound_f64(ecx, i));
+                let max = max_item.and_then(|i| bound_f64(ecx, i));
+                Some(Validator::Range(min, max))
+            }
+        }
+        _ => {
+            ecx.struct_span_err(item.span, "invalid validator")
+                .help(VALIDATE_ATTR_HELP)
+                .emit();
+            None
+        }
+    }
+}
+
+/// Parses and marks as known all of the `#[validate(..)]` attributes in
+/// `attrs`.
+pub fn extract_validators(ecx: &ExtCtxt, attrs: &[Attribute]) -> Vec<(Validator, Span)> {
+    let mut validators = vec![];
+    for attr in attrs.iter().filter(|attr| attr.check_name("validate")) {
+        ::syntax::attr::mark_known(attr);
+        let items = attr.meta_item_list().unwrap_or_else(Vec::new);
+        if items.is_empty() {
+            ecx.struct_span_err(attr.span, "incorrect use of attribute")
+                .help(VALIDATE_ATTR_HELP)
+                .emit();
+        }
+
+        for item in &items {
+            if let Some(validator) = parse_validator(ecx, item) {
+                validators.push((validator, item.span));
+            }
+        }
+    }
+
+    validators
+}
+
+/// Generates statements that run each of `validators` against `value`, an
+/// expression of reference type, pushing any errors into `__errors`.
+pub fn validation_stmts(
+    cx: &ExtCtxt,
+    name: &str,
+    value: &P<Expr>,
+    validators: &[(Validator, Span)]
+) -> Vec<TokenTree> {
+    fn option_usize(cx: &ExtCtxt, sp: Span, n: Option<usize>) -> P<Expr> {
+        n.map_or_else(|| cx.expr_none(sp), |n| cx.expr_some(sp, cx.expr_usize(sp, n)))
+    }
+
+    fn option_f64(cx: &ExtCtxt, sp: Span, n: Option<f64>) -> P<Expr> {
+        n.map_or_else(|| cx.expr_none(sp), |n| {
+            let lit = LitKind::FloatUnsuffixed(Symbol::intern(&format!("{:?}", n)));
+            cx.expr_some(sp, cx.expr_lit(sp, lit))
+        })
+    }
+
+    let mut stmts = vec![];
+    for &(ref validator, sp) in validators {
+        let value = value.clone();
+        let check = match *validator {
+            Validator::Length(min, max) => {
+                let (min, max) = (option_usize(cx, sp, min), option_usize(cx, sp, max));
+                quote_expr!(cx, ::rocket::request::validate::length($name, $value, $min, $max))
+            }
+            Validator::Range(min, max) => {
+                let (min, max) = (option_f64(cx, sp, min), option_f64(cx, sp, max));
+                quote_expr!(cx, ::rocket::request::validate::range($name, $value, $min, $max))
+            }
+            Validator::Regex(ref pattern) => {
+                let pattern = pattern.as_str();
+                quote_expr!(cx, ::rocket::request::validate::regex($name, $value, $pattern))
+            }
+            Validator::Email => {
+                quote_expr!(cx, ::rocket::request::validate::email($name, $value))
+            }
+        };
+
+        stmts.extend(quote_tokens!(cx,
+            if let Err(__e) = $check {
+                __errors.push(__e);
+            }
+        ));
+    }
+
+    stmts
+}
+
+pub fn validate_derive(
+    ecx: &mut ExtCtxt,
+    span: Span,
+    meta_item: &MetaItem,
+    annotated: &Annotatable,
+    push: &mut FnMut(Annotatable)
+) {
+    let errors_type = ty::Ty::Literal(ty::Path::new_(vec!["rocket", "request", "FormErrors"],
+                                                     None, vec![], ty::PathKind::Global));
+
+    let trait_def = TraitDef {
+        is_unsafe: false,
+        supports_unions: false,
+        span: span,
+        attributes: vec![],
+        path: ty::Path::new_(
+            vec!["rocket", "request", "Validate"],
+            None,
+            vec![],
+            ty::PathKind::Global,
+        ),
+        additional_bounds: Vec::new(),
+        generics: ty::LifetimeBounds::empty(),
+        methods: vec![
+            MethodDef {
+                name: "validate",
+                generics: ty::LifetimeBounds::empty(),
+                explicit_self: ty::borrowed_explicit_self(),
+                args: vec![],
+                ret_ty: ty::Literal(ty::Path::new_(
+                    vec!["result", "Result"],
+                    None,
+                    vec![Box::new(ty::Ty::Tuple(vec![])), Box::new(errors_type)],
+                    ty::PathKind::Std,
+                )),
+                attributes: vec![],
+                is_unsafe: false,
+                combine_substructure: c_s(Box::new(validate_substructure)),
+                unify_fieldless_variants: false,
+            }
+        ],
+        associated_types: vec![],
+    };
+
+    trait_def.expand(ecx, meta_item, annotated, push);
+}
+
+fn validate_substructure(cx: &mut ExtCtxt, trait_span: Span, substr: &Substructure) -> P<Expr> {
+    let fields = match *substr.fields {
+        Struct(_, ref fields) => fields,
+        _ => cx.span_fatal(trait_span, ONLY_STRUCTS_ERR)
+    };
+
+    let mut stmts = vec![];
+    for field in fields {
+        let name = match field.name {
+            Some(ident) => ident.to_string(),
+            None => cx.span_fatal(field.span, ONLY_STRUCTS_ERR)
+        };
+
+        let validators = extract_validators(cx, field.attrs);
+        let self_ = field.self_.clone();
+        let value = quote_expr!(cx, &$self_);
+        stmts.extend(validation_stmts(cx, &name, &value, &validators));
+    }
+
+    quote_expr!(cx, {
+        let mut __errors = ::rocket::request::FormErrors::new();
+        $stmts
+
+        if !__errors.is_empty() {
+            return Err(__errors);
+        }
+
+        Ok(())
+    })
+}
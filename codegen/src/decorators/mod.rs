@@ -1,8 +1,10 @@
 mod route;
 mod catch;
 mod derive_form;
+mod derive_validate;
 
 pub use self::route::*;
 pub use self::catch::*;
 pub use self::derive_form::*;
+pub use self::derive_validate::*;
 
@@ -3,12 +3,12 @@ use std::fmt::Display;
 
 use ::{ROUTE_STRUCT_PREFIX, ROUTE_FN_PREFIX, PARAM_PREFIX, URI_INFO_MACRO_PREFIX};
 use ::{ROUTE_ATTR, ROUTE_INFO_ATTR};
-use parser::{Param, RouteParams};
+use parser::{KVSpanned, Param, RouteParams};
 use utils::*;
 
 use syntax::codemap::{Span, Spanned, dummy_spanned};
 use syntax::tokenstream::TokenTree;
-use syntax::ast::{Arg, Ident, Item, Stmt, Expr, MetaItem, Path};
+use syntax::ast::{Arg, Ident, Item, Stmt, Expr, MetaItem, Path, Ty, TyKind};
 use syntax::ext::base::{Annotatable, ExtCtxt};
 use syntax::ext::build::AstBuilder;
 use syntax::parse::token;
@@ -40,7 +40,53 @@ fn media_type_to_expr(ecx: &ExtCtxt, ct: Option<MediaType>) -> Option<P<Expr>> {
     })
 }
 
+// Whether `ty` is syntactically an `Option<T>`.
+fn is_option_ty(ty: &P<Ty>) -> bool {
+    match ty.node {
+        TyKind::Path(_, ref path) => path.segments.last()
+            .map_or(false, |segment| segment.ident.name == "Option"),
+        _ => false
+    }
+}
+
+// An expression describing the guard error `e` via `Debug`, if it can be.
+fn describe_error_expr(ecx: &ExtCtxt) -> P<Expr> {
+    quote_expr!(ecx, {
+        #[allow(unused_imports)]
+        use ::rocket::request::{DescribeDebug, DescribeOther};
+        (&::rocket::request::Describe(&e))._rocket_describe()
+    })
+}
+
+// A statement recording in `__req` that the guard of `kind` for the argument
+// `name` of type `ty` failed with `status` and the described `error`.
+fn record_failure_stmt(
+    ecx: &ExtCtxt,
+    kind: &str,
+    name: Ident,
+    ty: &P<Ty>,
+    status: P<Expr>,
+    error: P<Expr>
+) -> Stmt {
+    let kind = Ident::from_str(kind);
+    quote_stmt!(ecx,
+        __req._record_guard_failure(::rocket::request::GuardFailure {
+            kind: ::rocket::request::GuardKind::$kind,
+            name: stringify!($name),
+            ty: stringify!($ty),
+            status: $status,
+            error: $error,
+        });
+    ).expect("guard failure statement")
+}
+
 impl RouteParams {
+    fn default_for(&self, ident: &Ident) -> Option<&str> {
+        self.defaults.iter()
+            .find(|default| &*ident.name.as_str() == default.key())
+            .map(|default| default.value().as_str())
+    }
+
     fn missing_declared_err<T: Display>(&self, ecx: &ExtCtxt, arg: &Spanned<T>) {
         let (fn_span, fn_name) = (self.annotated_fn.span(), self.annotated_fn.ident());
         ecx.struct_span_err(arg.span, &format!("unused dynamic parameter: `{}`", arg.node))
@@ -63,8 +109,12 @@ impl RouteParams {
         }
 
         let arg = arg.unwrap();
-        let name = arg.ident().expect("form param identifier").prepend(PARAM_PREFIX);
+        let original = arg.ident().expect("form param identifier");
+        let name = original.prepend(PARAM_PREFIX);
         let ty = strip_ty_lifetimes(arg.ty.clone());
+        let status = quote_expr!(ecx, ::rocket::http::Status::BadRequest);
+        let record = record_failure_stmt(ecx, "Query", *original, &ty, status,
+                                         quote_expr!(ecx, None));
         Some(quote_stmt!(ecx,
             #[allow(non_snake_case)]
             let $name: $ty = {
@@ -78,6 +128,7 @@ impl RouteParams {
 
                 if !items.exhaust() {
                     println!("    => The query string {:?} is malformed.", $form_string);
+                    $record
                     return ::rocket::Outcome::Failure(::rocket::http::Status::BadRequest);
                 }
 
@@ -97,8 +148,11 @@ impl RouteParams {
         }
 
         let arg = arg.unwrap();
-        let name = arg.ident().expect("form param identifier").prepend(PARAM_PREFIX);
+        let original = arg.ident().expect("form param identifier");
+        let name = original.prepend(PARAM_PREFIX);
         let ty = strip_ty_lifetimes(arg.ty.clone());
+        let record = record_failure_stmt(ecx, "Data", *original, &ty,
+                                         quote_expr!(ecx, code), describe_error_expr(ecx));
         Some(quote_stmt!(ecx,
             #[allow(non_snake_case, unreachable_patterns)]
             let $name: $ty =
@@ -106,13 +160,25 @@ impl RouteParams {
                     ::rocket::Outcome::Success(d) => d,
                     ::rocket::Outcome::Forward(d) =>
                         return ::rocket::Outcome::Forward(d),
-                    ::rocket::Outcome::Failure((code, _)) => {
+                    ::rocket::Outcome::Failure((code, e)) => {
+                        $record
                         return ::rocket::Outcome::Failure(code);
                     }
                 };
         ).expect("data statement"))
     }
 
+    // Answers automatic `HEAD` requests from the `head_length` function, if
+    // there is one, once all of the guards have succeeded.
+    fn generate_head_statement(&self, ecx: &ExtCtxt) -> Option<Stmt> {
+        self.head_length.as_ref()?;
+        quote_stmt!(ecx,
+            if let Some(__outcome) = ::rocket::handler::Outcome::_from_head_length(__req) {
+                return __outcome;
+            }
+        )
+    }
+
     fn generate_query_statement(&self, ecx: &ExtCtxt) -> Option<Stmt> {
         let param = self.query_param.as_ref();
         let expr = quote_expr!(ecx,
@@ -147,17 +213,53 @@ impl RouteParams {
             // Note: the `None` case shouldn't happen if a route is matched.
             let ident = param.ident().prepend(PARAM_PREFIX);
             let expr = match *param {
-                Param::Single(_) => quote_expr!(ecx, match __req.get_param_str($i) {
+                Param::Single(..) => quote_expr!(ecx, match __req.get_param_str($i) {
                     Some(s) => <$ty as ::rocket::request::FromParam>::from_param(s),
                     None => return ::rocket::Outcome::Forward(__data)
                 }),
+                Param::Optional(..) => {
+                    let missing = match self.default_for(param.ident()) {
+                        Some(default) => quote_expr!(ecx,
+                            <$ty as ::rocket::request::FromParam>::from_param(
+                                ::rocket::http::RawStr::from_str($default))),
+                        None if is_option_ty(&ty) => quote_expr!(ecx, Ok(None)),
+                        None => {
+                            let msg = format!("optional parameter `{}` must have a \
+                                default or be an `Option`", param.ident());
+                            ecx.struct_span_err(param.inner().span, &msg)
+                                .help(&format!("add a default with `#[default({} = \"..\")]`",
+                                               param.ident()))
+                                .emit();
+                            continue;
+                        }
+                    };
+
+                    quote_expr!(ecx, match __req.get_param_str($i) {
+                        Some(s) => <$ty as ::rocket::request::FromParam>::from_param(s),
+                        None => $missing
+                    })
+                }
                 Param::Many(_) => quote_expr!(ecx, match __req.get_raw_segments($i) {
                     Some(s) => <$ty as ::rocket::request::FromSegments>::from_segments(s),
                     None => return ::rocket::Outcome::Forward(__data)
                 }),
             };
 
+            // A constrained parameter has already been matched against its
+            // constraint by the router, so a parse failure is a client error.
             let original_ident = param.ident();
+            let (failure, record) = match param.constraint() {
+                Some(_) => {
+                    let status = quote_expr!(ecx, ::rocket::http::Status::BadRequest);
+                    let record = record_failure_stmt(ecx, "Param", *original_ident, &ty,
+                                                     status, describe_error_expr(ecx));
+                    let failure = quote_expr!(ecx,
+                        ::rocket::Outcome::Failure(::rocket::http::Status::BadRequest));
+                    (failure, Some(record))
+                }
+                None => (quote_expr!(ecx, ::rocket::Outcome::Forward(__data)), None)
+            };
+
             fn_param_statements.push(quote_stmt!(ecx,
                 #[allow(non_snake_case, unreachable_patterns)]
                 let $ident: $ty = match $expr {
@@ -165,12 +267,29 @@ impl RouteParams {
                     Err(e) => {
                         println!("    => Failed to parse '{}': {:?}",
                                  stringify!($original_ident), e);
-                        return ::rocket::Outcome::Forward(__data)
+                        $record
+                        return $failure
                     }
                 };
             ).expect("declared param parsing statement"));
         }
 
+        // Every default must be for an optional parameter.
+        for default in &self.defaults {
+            let key = default.key().as_str();
+            let names_default = |param: &Param| match *param {
+                Param::Optional(ref ident, _) => &*ident.node.name.as_str() == key,
+                _ => false
+            };
+
+            if !params.iter().any(names_default) {
+                let msg = format!("`{}` is not an optional parameter", default.key());
+                ecx.struct_span_err(default.key.span, &msg)
+                    .help(r#"defaults apply to optional path parameters like "<page?>""#)
+                    .emit();
+            }
+        }
+
         // A from_request parameter is one that isn't declared, data, or query.
         let from_request = |a: &&Arg| {
             if let Some(name) = a.name() {
@@ -189,8 +308,11 @@ impl RouteParams {
         // Generate the code for `from_request` parameters.
         let all = &self.annotated_fn.decl().inputs;
         for arg in all.iter().filter(from_request) {
-            let ident = arg.ident().unwrap().prepend(PARAM_PREFIX);
+            let original = arg.ident().unwrap();
+            let ident = original.prepend(PARAM_PREFIX);
             let ty = strip_ty_lifetimes(arg.ty.clone());
+            let record = record_failure_stmt(ecx, "Request", *original, &ty,
+                                             quote_expr!(ecx, code), describe_error_expr(ecx));
             fn_param_statements.push(quote_stmt!(ecx,
                 #[allow(non_snake_case, unreachable_patterns)]
                 let $ident: $ty = match
@@ -198,7 +320,8 @@ impl RouteParams {
                     ::rocket::Outcome::Success(v) => v,
                     ::rocket::Outcome::Forward(_) =>
                         return ::rocket::Outcome::Forward(__data),
-                    ::rocket::Outcome::Failure((code, _)) => {
+                    ::rocket::Outcome::Failure((code, e)) => {
+                        $record
                         return ::rocket::Outcome::Failure(code)
                     },
                 };
@@ -260,15 +383,32 @@ impl RouteParams {
         ).expect("consistent uri macro item")
     }
 
-    fn explode(&self, ecx: &ExtCtxt) -> (LocalInternedString, &str, Path, P<Expr>, P<Expr>) {
+    fn explode(&self, ecx: &ExtCtxt)
+        -> (LocalInternedString, &str, Path, P<Expr>, P<Expr>, bool, P<Expr>, P<Expr>, P<Expr>,
+            P<Expr>, P<Expr>, P<Expr>, P<Expr>)
+    {
         let name = self.annotated_fn.ident().name.as_str();
         let path = &self.uri.node.as_str();
         let method = method_to_path(ecx, self.method.node);
         let format = self.format.as_ref().map(|kv| kv.value().clone());
         let media_type = option_as_expr(ecx, &media_type_to_expr(ecx, format));
         let rank = option_as_expr(ecx, &self.rank);
+        let coalesce = self.coalesce.as_ref().map_or(false, |kv| *kv.value());
+        let head_length = option_as_expr(ecx, &self.head_length);
+        let version = self.version.as_ref().map(|kv| kv.value().as_str());
+        let version = option_as_expr(ecx, &version);
+        let response_limit = option_as_expr(ecx, &self.response_limit);
+        let timeout = option_as_expr(ecx, &self.timeout);
+        let as_str = |kv: &Option<KVSpanned<String>>| {
+            let value = kv.as_ref().map(|kv| kv.value().as_str());
+            option_as_expr(ecx, &value)
+        };
+
+        let (deprecated_since, sunset) = (as_str(&self.deprecated_since), as_str(&self.sunset));
+        let successor = as_str(&self.successor);
 
-        (name, path, method, media_type, rank)
+        (name, path, method, media_type, rank, coalesce, head_length, version, response_limit,
+         timeout, deprecated_since, sunset, successor)
     }
 }
 
@@ -288,6 +428,7 @@ fn generic_route_decorator(known_method: Option<Spanned<Method>>,
     let param_statements = route.generate_param_statements(ecx);
     let query_statement = route.generate_query_statement(ecx);
     let data_statement = route.generate_data_statement(ecx);
+    let head_statement = route.generate_head_statement(ecx);
     let fn_arguments = route.generate_fn_arguments(ecx);
     let uri_macro = route.generate_uri_macro(ecx);
 
@@ -303,6 +444,8 @@ fn generic_route_decorator(known_method: Option<Spanned<Method>>,
              $param_statements
              $query_statement
              $data_statement
+             __req._mark_guards_complete();
+             $head_statement
              let responder = $user_fn_name($fn_arguments);
             ::rocket::handler::Outcome::from(__req, responder)
         }
@@ -311,7 +454,8 @@ fn generic_route_decorator(known_method: Option<Spanned<Method>>,
     // Generate and emit the static route info that uses the just generated
     // function as its handler. A proper Rocket route will be created from this.
     let struct_name = user_fn_name.prepend(ROUTE_STRUCT_PREFIX);
-    let (name, path, method, media_type, rank) = route.explode(ecx);
+    let (name, path, method, media_type, rank, coalesce, head_length, version, response_limit,
+         timeout, deprecated_since, sunset, successor) = route.explode(ecx);
     let static_route_info_item =  quote_item!(ecx,
         /// Rocket code generated static route information structure.
         #[allow(non_upper_case_globals)]
@@ -323,6 +467,14 @@ fn generic_route_decorator(known_method: Option<Spanned<Method>>,
                 handler: $route_fn_name,
                 format: $media_type,
                 rank: $rank,
+                coalesce: $coalesce,
+                head_length: $head_length,
+                version: $version,
+                response_limit: $response_limit,
+                timeout: $timeout,
+                deprecated_since: $deprecated_since,
+                sunset: $sunset,
+                successor: $successor,
             };
     ).expect("static route info");
 
@@ -44,15 +44,53 @@
 //! kv_param := 'rank' '=' INTEGER
 //!           | 'format' '=' STRING
 //!           | 'data' '=' DYNAMIC_PARAM
-//!
+//!           | 'version' '=' STRING
+//!           | 'response_limit' '=' INTEGER
+//!           | 'timeout' '=' INTEGER
+//!           | 'deprecated_since' '=' DATE
+//!           | 'sunset' '=' DATE
+//!           | 'successor' '=' STRING
+//!
+//! DATE := a 'YYYY-MM-DD' date (string literal)
 //! INTEGER := isize, as defined by Rust
 //! STRING := UTF-8 string literal, as defined by Rust
 //! IDENT := valid identifier, as defined by Rust
 //!
 //! URI_SEG := valid HTTP URI Segment
-//! DYNAMIC_PARAM := '<' IDENT '..'? '>' (string literal)
+//! DYNAMIC_PARAM := '<' IDENT '..'? '>'
+//!                | '<' IDENT '?'? (':' CONSTRAINT)? '>'
+//!                (string literal)
+//! CONSTRAINT := a segment constraint, as defined by `rocket::http::uri::Constraint`
 //! </pre>
 //!
+//! A dynamic parameter with a trailing `?`, such as `<page?>`, is _optional_:
+//! the route matches whether or not the segment is present. Optional
+//! parameters must be the final segments in a path. The handler argument for
+//! an optional parameter must either be an `Option<T>`, which is `None` when
+//! the segment is missing, or have a default. Defaults are given as raw
+//! strings, parsed with `FromParam`, in a `default` attribute:
+//!
+//!     #[get("/archive/<year?>/<page?>")]
+//!     #[default(page = "1")]
+//!     fn archive(year: Option<u16>, page: usize) -> T { ... }
+//!
+//! A route with a `version`, such as `version = "v2"`, only matches requests
+//! that negotiate that API version; see `rocket::versioning`.
+//!
+//! A route with a `response_limit`, such as `response_limit = 1048576`, fails
+//! with a `500` if its response body is larger than the limit in bytes. The
+//! limit overrides the `response` limit in the configuration.
+//!
+//! A route with a `timeout`, such as `timeout = 5000`, may take at most that
+//! many milliseconds from the start of its handler to the end of its response
+//! body. Handlers observe the deadline through a `rocket::request::CancelToken`
+//! guard; see its documentation for how the timeout is enforced.
+//!
+//! A route with a `deprecated_since`, `sunset`, or `successor` is deprecated:
+//! its responses carry `Deprecation`, `Sunset`, and `Link` headers describing
+//! when it was deprecated, when it will be removed, and the URI that replaces
+//! it. See `rocket::versioning::Deprecation`.
+//!
 //! Note that the **route** attribute takes a method as its first argument,
 //! while the remaining do not. That is, **route** looks like:
 //!
@@ -77,6 +115,9 @@
 //! This crate implements the following custom derives:
 //!
 //!   * **FromForm**
+//!   * **Validate**
+//!   * **UriDisplay**
+//!   * **FromUriParam**
 //!
 //! ### `FromForm`
 //!
@@ -92,9 +133,15 @@
 //! accepts one field attribute: `form`, with the following syntax:
 //!
 //! <pre>
-//! form := 'field' '=' '"' IDENT '"'
+//! form := form_param (',' form_param)?
+//!
+//! form_param := 'field' '=' '"' IDENT '"'
+//!             | 'transform' '=' '"' TRANSFORM (',' TRANSFORM)* '"'
+//!
+//! TRANSFORM := 'trim' | 'lowercase' | 'uppercase' | PATH
 //!
 //! IDENT := valid identifier, as defined by Rust
+//! PATH := valid path to a function of type `fn(String) -> String`
 //! </pre>
 //!
 //! When applied, the attribute looks as follows:
@@ -110,14 +157,109 @@
 //! implementation parses a form whose field names match the field names of the
 //! structure on which the derive was applied. Each field's value is parsed with
 //! the [`FromFormValue`] implementation of the field's type. The `FromForm`
-//! implementation succeeds only when all of the field parses succeed.
+//! implementation succeeds only when all of the field parses succeed. Its
+//! error type is [`FormErrors`], which contains an error for every field that
+//! was missing or failed to parse.
 //!
 //! The `form` field attribute can be used to direct that a different incoming
 //! field name is expected. In this case, the attribute's field name is used
 //! instead of the structure's field name when parsing a form.
 //!
+//! The `transform` parameter lists transformers that are applied, in order, to
+//! the decoded field value before it is parsed. See [`transform`] for details.
+//!
+//!     #[derive(FromForm)]
+//!     struct MyStruct {
+//!         #[form(field = "e-mail", transform = "trim, lowercase")]
+//!         email: String
+//!     }
+//!
+//! Fields may additionally be annotated with any number of `validate`
+//! attributes, with the following syntax:
+//!
+//! <pre>
+//! validate := validator (',' validator)*
+//!
+//! validator := 'length' '(' bounds ')'
+//!            | 'range' '(' bounds ')'
+//!            | 'regex' '=' STRING
+//!            | 'email'
+//!
+//! bounds := bound (',' bound)?
+//! bound := ('min' | 'max') '=' NUMBER
+//! </pre>
+//!
+//! Each validator is run against the parsed value of the field. Validation
+//! failures are reported in the [`FormErrors`] alongside parse errors. See
+//! [`validate`] for details on each validator.
+//!
+//!     #[derive(FromForm)]
+//!     struct Signup {
+//!         #[validate(length(min = 3, max = 32))]
+//!         username: String,
+//!         #[validate(email)]
+//!         email: String,
+//!     }
+//!
+//! ### `Validate`
+//!
+//! The [`Validate`] derive can be applied to structures with named fields. It
+//! accepts the same `validate` field attributes as the `FromForm` derive and
+//! generates an implementation of the [`Validate`] trait that runs every
+//! validator, returning all failures as [`FormErrors`]. This allows data parsed
+//! by other guards, such as JSON, to be validated declaratively:
+//!
+//!     #[derive(Deserialize, Validate)]
+//!     struct NewComment {
+//!         #[validate(length(min = 1, max = 280))]
+//!         body: String,
+//!     }
+//!
 //! [`FromForm`]: /rocket/request/trait.FromForm.html
 //! [`FromFormValue`]: /rocket/request/trait.FromFormValue.html
+//! [`FormErrors`]: /rocket/request/struct.FormErrors.html
+//! [`transform`]: /rocket/request/transform/index.html
+//! [`validate`]: /rocket/request/validate/index.html
+//! [`Validate`]: /rocket/request/trait.Validate.html
+//!
+//! ### `UriDisplay` and `FromUriParam`
+//!
+//! The [`UriDisplay`] derive can be applied to structures and enums so that
+//! they can be used as URI parameters in `uri!`. Tuple structures and variants
+//! with a single field render as that field, making them suitable for path
+//! segments. Structures and variants with named fields render as a query
+//! string of the form `name=value&other=value`. Unit variants render as the
+//! variant's name. Every rendered field's type is required to implement
+//! [`UriDisplay`].
+//!
+//! The [`FromUriParam`] derive can be applied to structures without type
+//! parameters. It allows a structure with a single field to be created from a
+//! value of that field's type and a structure with several fields to be created
+//! from a tuple of values convertible via `Into` into each field's type, in
+//! declaration order:
+//!
+//!     #[derive(FromForm, UriDisplay, FromUriParam)]
+//!     struct User {
+//!         name: String,
+//!         #[uri(rename = "nick")]
+//!         nickname: String,
+//!     }
+//!
+//!     // uri!(search: query = ("Robert", "Bob")) => "/search?name=Robert&nick=Bob"
+//!
+//! Both derives accept one field attribute, and `UriDisplay` also accepts it on
+//! variants: `uri`, with the following syntax:
+//!
+//! <pre>
+//! uri := 'rename' '=' STRING
+//!      | 'skip'
+//!
+//! STRING := an uncooked string literal, as defined by Rust (example: `"hi"`)
+//! </pre>
+//!
+//! A `rename` changes the name of a named field or unit variant in the
+//! rendered URI. A skipped field is not rendered and, for `FromUriParam`, is
+//! initialized with `Default::default()`.
 //!
 //! ## Procedural Macros
 //!
@@ -126,6 +268,7 @@
 //!   * **routes**
 //!   * **catchers**
 //!   * **uri**
+//!   * **embed_dir**
 //!
 //! The syntax for `routes!` and `catchers!` is defined as:
 //!
@@ -210,6 +353,38 @@
 //! [`FromUriParam`]: /rocket/http/uri/trait.FromUriParam.html
 //! [`UriDisplay`]: /rocket/http/uri/trait.UriDisplay.html
 //!
+//! ### Embedded Assets: `embed_dir!`
+//!
+//! The `embed_dir!` macro compiles every file in a directory, recursively,
+//! into the binary. It returns an [`EmbeddedDir`] whose files respond with
+//! their contents, an `ETag`, and a `Content-Type` derived from their
+//! extension. The directory is relative to the root of the crate, the
+//! directory containing its `Cargo.toml`:
+//!
+//! ```rust,ignore
+//! static ASSETS: EmbeddedDir = embed_dir!("static", gzip);
+//!
+//! #[get("/<path..>", rank = 5)]
+//! fn assets(path: PathBuf) -> Option<&'static EmbeddedFile> {
+//!     ASSETS.get(path)
+//! }
+//! ```
+//!
+//! The grammar for the `embed_dir!` macro is as follows:
+//!
+//! <pre>
+//! embed_dir := STRING (',' 'gzip')?
+//!
+//! STRING := an uncooked string literal, as defined by Rust (example: `"hi"`)
+//! </pre>
+//!
+//! With the `gzip` option, a gzip compressed copy of each file that
+//! compression makes smaller is also embedded and served to clients that
+//! accept it. Changes to embedded files cause the crate to be rebuilt, but
+//! files added to the directory are only picked up by the next rebuild.
+//!
+//! [`EmbeddedDir`]: /rocket/response/struct.EmbeddedDir.html
+//!
 //! # Debugging Codegen
 //!
 //! When the `ROCKET_CODEGEN_DEBUG` environment variable is set, this crate logs
@@ -229,6 +404,7 @@ extern crate syntax_pos;
 extern crate rustc_plugin;
 extern crate rocket;
 extern crate indexmap;
+extern crate flate2;
 
 #[macro_use] mod utils;
 mod parser;
@@ -288,11 +464,13 @@ pub fn plugin_registrar(reg: &mut Registry) {
         "routes" => routes,
         "catchers" => catchers,
         "uri" => uri,
-        "rocket_internal_uri" => uri_internal
+        "rocket_internal_uri" => uri_internal,
+        "embed_dir" => embed_dir
     );
 
     register_derives!(reg,
-        "derive_FromForm" => from_form_derive
+        "derive_FromForm" => from_form_derive,
+        "derive_Validate" => validate_derive
     );
 
     register_decorators!(reg,
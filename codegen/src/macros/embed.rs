@@ -0,0 +1,153 @@
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::hash::{Hash, Hasher};
+use std::collections::hash_map::DefaultHasher;
+
+use syntax::codemap::Span;
+use syntax::tokenstream::TokenTree;
+use syntax::ext::base::{DummyResult, ExtCtxt, MacEager, MacResult};
+use syntax::parse::token::{self, Token};
+use syntax::symbol::Symbol;
+use syntax::ast::Expr;
+use syntax::ptr::P;
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+
+use utils::{sep_by_tok, ParserExt};
+
+/// A file in an embedded directory.
+struct Asset {
+    /// The path of the file relative to the directory, separated by `/`.
+    path: String,
+    /// The absolute path of the file.
+    absolute: PathBuf,
+}
+
+// Collects every file in `dir`, recursively, in a stable order.
+fn collect_assets(root: &Path, dir: &Path, assets: &mut Vec<Asset>) -> io::Result<()> {
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    paths.sort();
+    for path in paths {
+        if fs::metadata(&path)?.is_dir() {
+            collect_assets(root, &path, assets)?;
+            continue;
+        }
+
+        let relative = path.strip_prefix(root).expect("asset is in the root");
+        let components: Vec<_> = relative.components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+
+        assets.push(Asset { path: components.join("/"), absolute: path.clone() });
+    }
+
+    Ok(())
+}
+
+// Returns a byte string literal token containing `bytes`.
+fn byte_str_lit(sp: Span, bytes: &[u8]) -> TokenTree {
+    let escaped: String = bytes.iter()
+        .flat_map(|&b| ::std::ascii::escape_default(b))
+        .map(|b| b as char)
+        .collect();
+
+    TokenTree::Token(sp, Token::Literal(token::Lit::ByteStr(Symbol::intern(&escaped)), None))
+}
+
+// Returns the gzip compressed `bytes` if compressing makes them smaller.
+fn gzipped(bytes: &[u8]) -> io::Result<Option<Vec<u8>>> {
+    let mut encoder = GzEncoder::new(vec![], Compression::best());
+    encoder.write_all(bytes)?;
+    let compressed = encoder.finish()?;
+    Ok(match compressed.len() < bytes.len() {
+        true => Some(compressed),
+        false => None
+    })
+}
+
+fn asset_expr(ecx: &ExtCtxt, sp: Span, asset: &Asset, gzip: bool) -> io::Result<P<Expr>> {
+    let bytes = fs::read(&asset.absolute)?;
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    let etag = format!("{:x}-{:x}", bytes.len(), hasher.finish());
+
+    let gzipped = match gzip {
+        true => gzipped(&bytes)?.map(|compressed| byte_str_lit(sp, &compressed)),
+        false => None
+    };
+
+    // `include_bytes!` makes the compiler rebuild the crate when a file changes.
+    let path = &*asset.path;
+    let absolute = asset.absolute.to_string_lossy().into_owned();
+    let gzipped = match gzipped {
+        Some(lit) => quote_expr!(ecx, Some($lit as &'static [u8])),
+        None => quote_expr!(ecx, None)
+    };
+
+    Ok(quote_expr!(ecx, ::rocket::response::EmbeddedFile {
+        path: $path,
+        bytes: include_bytes!($absolute),
+        gzipped: $gzipped,
+        etag: $etag,
+    }))
+}
+
+pub fn embed_dir(
+    ecx: &mut ExtCtxt,
+    sp: Span,
+    args: &[TokenTree],
+) -> Box<MacResult + 'static> {
+    let mut parser = ecx.new_parser_from_tts(args);
+    let (dir, _) = try_parse!(sp, parser.parse_str_lit());
+
+    // Parse the options, if any.
+    let mut gzip = false;
+    while parser.eat(&Token::Comma) && parser.token != Token::Eof {
+        let option = try_parse!(sp, parser.parse_ident());
+        if option.name == "gzip" {
+            gzip = true;
+        } else {
+            ecx.struct_span_err(parser.prev_span, "unknown `embed_dir!` option")
+                .help("the only option is `gzip`")
+                .emit();
+
+            return DummyResult::expr(sp);
+        }
+    }
+
+    try_parse!(sp, parser.expect(&Token::Eof));
+
+    // Directories are relative to the root of the crate being compiled.
+    let root = env::var("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("."))
+        .join(&*dir.as_str());
+
+    let mut assets = vec![];
+    if let Err(e) = collect_assets(&root, &root, &mut assets) {
+        ecx.span_err(sp, &format!("failed to read directory {}: {}", root.display(), e));
+        return DummyResult::expr(sp);
+    }
+
+    let mut exprs = vec![];
+    for asset in &assets {
+        match asset_expr(ecx, sp, asset, gzip) {
+            Ok(expr) => exprs.push(expr),
+            Err(e) => {
+                ecx.span_err(sp, &format!("failed to embed {}: {}", asset.path, e));
+                return DummyResult::expr(sp);
+            }
+        }
+    }
+
+    let files = sep_by_tok(ecx, &exprs, Token::Comma);
+    MacEager::expr(quote_expr!(ecx, ::rocket::response::EmbeddedDir {
+        files: &[$files],
+    }))
+}
@@ -1,4 +1,5 @@
 mod uri;
+mod embed;
 
 use {ROUTE_STRUCT_PREFIX, CATCH_STRUCT_PREFIX};
 use utils::{sep_by_tok, ParserExt, IdentExt};
@@ -11,6 +12,7 @@ use syntax::parse::token::Token;
 use syntax::ptr::P;
 
 pub use self::uri::{uri, uri_internal};
+pub use self::embed::embed_dir;
 
 #[inline]
 pub fn prefix_path(prefix: &str, path: &mut Path) {
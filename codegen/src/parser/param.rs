@@ -3,23 +3,38 @@ use syntax::codemap::{Span, Spanned};
 use syntax::ext::base::ExtCtxt;
 use utils::SpanExt;
 use syntax::parse::PResult;
+use rocket::http::uri::Constraint;
 
 #[derive(Debug)]
 pub enum Param {
-    Single(Spanned<Ident>),
+    /// A single segment parameter with its constraint, if any.
+    Single(Spanned<Ident>, Option<String>),
+    /// An optional trailing segment parameter with its constraint, if any.
+    Optional(Spanned<Ident>, Option<String>),
     Many(Spanned<Ident>),
 }
 
 impl Param {
     pub fn inner(&self) -> &Spanned<Ident> {
         match *self {
-            Param::Single(ref ident) | Param::Many(ref ident) => ident,
+            Param::Single(ref ident, _) | Param::Optional(ref ident, _) => ident,
+            Param::Many(ref ident) => ident,
         }
     }
 
     pub fn ident(&self) -> &Ident {
         match *self {
-            Param::Single(ref ident) | Param::Many(ref ident) => &ident.node,
+            Param::Single(ref ident, _) | Param::Optional(ref ident, _) => &ident.node,
+            Param::Many(ref ident) => &ident.node,
+        }
+    }
+
+    pub fn constraint(&self) -> Option<&str> {
+        match *self {
+            Param::Single(_, ref constraint) | Param::Optional(_, ref constraint) => {
+                constraint.as_ref().map(|c| c.as_str())
+            }
+            Param::Many(_) => None,
         }
     }
 
@@ -55,11 +70,15 @@ impl Param {
             string = &string[(end + 1)..];
             span = span.trim_left(end + 1);
 
-            let spanned_ident = param_span.wrap(Ident::from_str(param));
+            let (param, constraint) = Constraint::split_param(param);
+            let constraint = constraint.map(|c| c.to_string());
             if is_many {
-                params.push(Param::Many(spanned_ident))
+                params.push(Param::Many(param_span.wrap(Ident::from_str(param))))
+            } else if param.ends_with('?') {
+                let ident = Ident::from_str(&param[..(param.len() - 1)]);
+                params.push(Param::Optional(param_span.wrap(ident), constraint))
             } else {
-                params.push(Param::Single(spanned_ident))
+                params.push(Param::Single(param_span.wrap(Ident::from_str(param)), constraint))
             }
         }
     }
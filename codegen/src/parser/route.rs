@@ -27,6 +27,15 @@ pub struct RouteParams {
     pub query_param: Option<Spanned<Ident>>,
     pub format: Option<KVSpanned<MediaType>>,
     pub rank: Option<KVSpanned<isize>>,
+    pub coalesce: Option<KVSpanned<bool>>,
+    pub head_length: Option<KVSpanned<Ident>>,
+    pub version: Option<KVSpanned<String>>,
+    pub response_limit: Option<KVSpanned<u64>>,
+    pub timeout: Option<KVSpanned<u64>>,
+    pub deprecated_since: Option<KVSpanned<String>>,
+    pub sunset: Option<KVSpanned<String>>,
+    pub successor: Option<KVSpanned<String>>,
+    pub defaults: Vec<KVSpanned<String>>,
 }
 
 impl RouteParams {
@@ -77,6 +86,9 @@ impl RouteParams {
         // Parse all of the optional parameters.
         let mut seen_keys = HashSet::new();
         let (mut rank, mut data, mut format) = Default::default();
+        let (mut coalesce, mut head_length, mut version) = (None, None, None);
+        let (mut response_limit, mut timeout) = (None, None);
+        let (mut deprecated_since, mut sunset, mut successor) = (None, None, None);
         for param in &attr_params[1..] {
             let kv_opt = kv_from_nested(param);
             if kv_opt.is_none() {
@@ -89,6 +101,16 @@ impl RouteParams {
                 "rank" => rank = parse_opt(ecx, &kv, parse_rank),
                 "data" => data = parse_opt(ecx, &kv, parse_data),
                 "format" => format = parse_opt(ecx, &kv, parse_format),
+                "coalesce" => coalesce = parse_opt(ecx, &kv, parse_coalesce),
+                "head_length" => head_length = parse_opt(ecx, &kv, parse_head_length),
+                "version" => version = parse_opt(ecx, &kv, parse_version),
+                "response_limit" => {
+                    response_limit = parse_opt(ecx, &kv, parse_response_limit)
+                }
+                "timeout" => timeout = parse_opt(ecx, &kv, parse_timeout),
+                "deprecated_since" => deprecated_since = parse_opt(ecx, &kv, parse_date),
+                "sunset" => sunset = parse_opt(ecx, &kv, parse_date),
+                "successor" => successor = parse_opt(ecx, &kv, parse_successor),
                 _ => {
                     let msg = format!("'{}' is not a known parameter", kv.key());
                     ecx.span_err(kv.span, &msg);
@@ -116,6 +138,25 @@ impl RouteParams {
             }
         }
 
+        // Sanity check: only `GET` requests can be coalesced.
+        if let Some(ref coalesce) = coalesce {
+            if *coalesce.value() && method.node != Method::Get {
+                ecx.struct_span_err(coalesce.span, "only `GET` routes can be coalesced")
+                    .note(&format!("this route's method is '{}'", method.node))
+                    .emit();
+            }
+        }
+
+        // Sanity check: only `GET` routes answer automatic `HEAD` requests.
+        if let Some(ref head_length) = head_length {
+            if method.node != Method::Get {
+                ecx.struct_span_err(head_length.span, "`head_length` can only be \
+                        used with `GET` routes")
+                    .note(&format!("this route's method is '{}'", method.node))
+                    .emit();
+            }
+        }
+
         RouteParams {
             method: method,
             uri: uri,
@@ -123,11 +164,73 @@ impl RouteParams {
             query_param: query,
             format: format,
             rank: rank,
+            coalesce: coalesce,
+            head_length: head_length,
+            version: version,
+            response_limit: response_limit,
+            timeout: timeout,
+            deprecated_since: deprecated_since,
+            sunset: sunset,
+            successor: successor,
+            defaults: parse_defaults(ecx, annotated),
             annotated_fn: function,
         }
     }
 }
 
+/// Parses and marks as known the `#[default(name = "value", ..)]` attributes
+/// on the annotated function, which supply the raw values of missing optional
+/// path segments.
+fn parse_defaults(ecx: &ExtCtxt, annotated: &Annotatable) -> Vec<KVSpanned<String>> {
+    let attrs = match *annotated {
+        Annotatable::Item(ref item) => &item.attrs,
+        _ => return vec![]
+    };
+
+    let mut defaults: Vec<KVSpanned<String>> = vec![];
+    for attr in attrs.iter().filter(|attr| attr.check_name("default")) {
+        ::syntax::attr::mark_used(attr);
+        ::syntax::attr::mark_known(attr);
+        let items = attr.meta_item_list().unwrap_or_else(Vec::new);
+        if items.is_empty() {
+            ecx.struct_span_err(attr.span, "incorrect use of attribute")
+                .help(r#"defaults have the form: #[default(name = "value")]"#)
+                .emit();
+        }
+
+        for item in &items {
+            let kv = match kv_from_nested(item) {
+                Some(kv) => kv,
+                None => {
+                    ecx.span_err(item.span(), "expected key = value");
+                    continue;
+                }
+            };
+
+            let value = match *kv.value() {
+                LitKind::Str(ref s, _) => s.as_str().to_string(),
+                _ => {
+                    ecx.struct_span_err(kv.span, "default values must be strings")
+                        .help("the string is parsed by the parameter's `FromParam` \
+                              implementation, e.g: page = \"1\"")
+                        .emit();
+                    continue;
+                }
+            };
+
+            if defaults.iter().any(|d| d.key() == kv.key()) {
+                let msg = format!("a default for `{}` was already given", kv.key());
+                ecx.span_err(kv.span, &msg);
+                continue;
+            }
+
+            defaults.push(kv.map_ref(|_| value.clone()));
+        }
+    }
+
+    defaults
+}
+
 fn is_valid_method(method: Method) -> bool {
     use rocket::http::Method::*;
     match method {
@@ -256,6 +359,140 @@ fn parse_rank(ecx: &ExtCtxt, kv: &KVSpanned<LitKind>) -> isize {
     -1
 }
 
+fn parse_coalesce(ecx: &ExtCtxt, kv: &KVSpanned<LitKind>) -> bool {
+    if let LitKind::Bool(b) = *kv.value() {
+        return b;
+    }
+
+    ecx.struct_span_err(kv.span, r#"`coalesce` value must be a bool"#)
+        .help(r#"coalesce, if specified, must be a key-value pair where
+              the key is `coalesce` and the value is a boolean.
+              e.g: coalesce = true"#)
+        .emit();
+
+    false
+}
+
+fn parse_head_length(ecx: &ExtCtxt, kv: &KVSpanned<LitKind>) -> Ident {
+    if let LitKind::Str(ref s, _) = *kv.value() {
+        if is_valid_ident(&s.as_str()) {
+            return Ident::from_str(&s.as_str());
+        }
+    }
+
+    ecx.struct_span_err(kv.span, r#"`head_length` value must be a function name"#)
+        .help(r#"head_length, if specified, must be a key-value pair where
+              the key is `head_length` and the value is a string naming a
+              function of type `fn(&Request) -> Option<u64>`.
+              e.g: head_length = "report_len""#)
+        .emit();
+
+    Ident::from_str("unknown")
+}
+
+fn parse_version(ecx: &ExtCtxt, kv: &KVSpanned<LitKind>) -> String {
+    if let LitKind::Str(ref s, _) = *kv.value() {
+        let version = s.as_str();
+        let valid = |c: char| c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_';
+        if !version.is_empty() && version.chars().all(valid) {
+            return version.to_string();
+        }
+    }
+
+    ecx.struct_span_err(kv.span, r#"`version` value must be a version name"#)
+        .help(r#"version, if specified, must be a key-value pair where
+              the key is `version` and the value is a non-empty string of
+              alphanumerics, `.`, `-`, and `_`. e.g: version = "v2""#)
+        .emit();
+
+    "unknown".to_string()
+}
+
+fn parse_response_limit(ecx: &ExtCtxt, kv: &KVSpanned<LitKind>) -> u64 {
+    if let LitKind::Int(n, _) = *kv.value() {
+        if n <= u64::max_value() as u128 {
+            return n as u64;
+        }
+
+        let msg = format!("response limit must be at most {}", u64::max_value());
+        ecx.span_err(kv.value.span, msg.as_str());
+    } else {
+        ecx.struct_span_err(kv.span, r#"`response_limit` value must be an int"#)
+            .help(r#"response_limit, if specified, must be a key-value pair where
+                  the key is `response_limit` and the value is the maximum
+                  size of the response body in bytes.
+                  e.g: response_limit = 1048576"#)
+            .emit();
+    }
+
+    u64::max_value()
+}
+
+fn parse_timeout(ecx: &ExtCtxt, kv: &KVSpanned<LitKind>) -> u64 {
+    if let LitKind::Int(n, _) = *kv.value() {
+        if n > 0 && n <= u64::max_value() as u128 {
+            return n as u64;
+        }
+
+        let msg = format!("timeout must be between 1 and {}", u64::max_value());
+        ecx.span_err(kv.value.span, msg.as_str());
+    } else {
+        ecx.struct_span_err(kv.span, r#"`timeout` value must be an int"#)
+            .help(r#"timeout, if specified, must be a key-value pair where
+                  the key is `timeout` and the value is the maximum time a
+                  request may take in milliseconds. e.g: timeout = 5000"#)
+            .emit();
+    }
+
+    u64::max_value()
+}
+
+fn parse_date(ecx: &ExtCtxt, kv: &KVSpanned<LitKind>) -> String {
+    if let LitKind::Str(ref s, _) = *kv.value() {
+        let date = s.as_str();
+        let parts: Vec<_> = date.split('-').collect();
+        let numeric = |part: &str, len: usize| {
+            part.len() == len && part.chars().all(|c| c.is_ascii_digit())
+        };
+
+        if parts.len() == 3 && numeric(parts[0], 4) && numeric(parts[1], 2)
+            && numeric(parts[2], 2)
+        {
+            let (month, day) = (parts[1].parse::<u8>().unwrap(), parts[2].parse::<u8>().unwrap());
+            if month >= 1 && month <= 12 && day >= 1 && day <= 31 {
+                return date.to_string();
+            }
+        }
+    }
+
+    let key = kv.key().as_str();
+    ecx.struct_span_err(kv.span, &format!("`{}` value must be a date", key))
+        .help(&format!("{}, if specified, must be a key-value pair where
+              the key is `{}` and the value is a date of the form
+              YYYY-MM-DD. e.g: {} = \"2019-01-01\"", key, key, key))
+        .emit();
+
+    "unknown".to_string()
+}
+
+fn parse_successor(ecx: &ExtCtxt, kv: &KVSpanned<LitKind>) -> String {
+    if let LitKind::Str(ref s, _) = *kv.value() {
+        let uri = s.as_str();
+        let valid = |c: char| !c.is_whitespace() && c != '<' && c != '>';
+        if !uri.is_empty() && uri.chars().all(valid) {
+            return uri.to_string();
+        }
+    }
+
+    ecx.struct_span_err(kv.span, r#"`successor` value must be a URI"#)
+        .help(r#"successor, if specified, must be a key-value pair where
+              the key is `successor` and the value is the URI of the route
+              that replaces this one. e.g: successor = "/v2/users""#)
+        .emit();
+
+    "unknown".to_string()
+}
+
 fn parse_format(ecx: &ExtCtxt, kv: &KVSpanned<LitKind>) -> MediaType {
     if let LitKind::Str(ref s, _) = *kv.value() {
         if let Some(ct) = MediaType::parse_flexible(&s.as_str()) {
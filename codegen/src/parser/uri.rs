@@ -2,7 +2,7 @@ use syntax::ast::*;
 use syntax::codemap::{Span, Spanned, dummy_spanned};
 use syntax::ext::base::ExtCtxt;
 
-use rocket::http::uri::Uri;
+use rocket::http::uri::{Uri, Constraint};
 use super::route::param_to_ident;
 use utils::{span, SpanExt, is_valid_ident};
 
@@ -28,9 +28,21 @@ fn valid_path(ecx: &ExtCtxt, uri: &Uri, sp: Span) -> bool {
     false
 }
 
+// If `segment` contains exactly one parameter and static text, returns the
+// static prefix, the parameter without its brackets, and the static suffix.
+fn split_partial(segment: &str) -> Option<(&str, &str, &str)> {
+    let (start, end) = (segment.find('<')?, segment.find('>')?);
+    if start > end || segment.rfind('<') != Some(start) || segment.rfind('>') != Some(end) {
+        return None;
+    }
+
+    Some((&segment[..start], &segment[(start + 1)..end], &segment[(end + 1)..]))
+}
+
 fn valid_segments(ecx: &ExtCtxt, uri: &Uri, sp: Span) -> bool {
     let mut validated = true;
     let mut segments_span = None;
+    let mut optional_span = None;
     for segment in uri.segments() {
         // We add one to the index to account for the '/'.
         let index = segment.as_ptr() as usize - uri.path().as_ptr() as usize;
@@ -54,6 +66,47 @@ fn valid_segments(ecx: &ExtCtxt, uri: &Uri, sp: Span) -> bool {
                 param = &param[..(param.len() - 2)];
             }
 
+            let (mut name, constraint) = Constraint::split_param(param);
+            if name.ends_with('?') {
+                name = &name[..(name.len() - 1)];
+                if segments_span.is_some() {
+                    ecx.struct_span_err(span, "segments params cannot be optional")
+                        .help("a segments param already matches zero or more segments")
+                        .emit();
+                    validated = false;
+                    continue;
+                }
+
+                optional_span = Some(span);
+            } else if let Some(optional_span) = optional_span {
+                ecx.struct_span_err(span, "required param after an optional param")
+                    .help("optional params must be the final segments in a path")
+                    .span_note(optional_span, "optional param is here")
+                    .emit();
+                validated = false;
+                continue;
+            }
+
+            if let Some(constraint) = constraint {
+                if segments_span.is_some() {
+                    ecx.struct_span_err(span, "segments params cannot be constrained")
+                        .help("constraints apply to single segment params like '<id:int>'")
+                        .emit();
+                    validated = false;
+                    continue;
+                }
+
+                if let Err(e) = Constraint::parse(constraint) {
+                    ecx.struct_span_err(span, "invalid parameter constraint")
+                        .note(&e)
+                        .help("use a named constraint like 'int' or a class like '[a-z-]+'")
+                        .emit();
+                    validated = false;
+                    continue;
+                }
+            }
+
+            let param = name;
             if param.is_empty() {
                 ecx.span_err(span, "parameters cannot be empty");
             } else if !is_valid_ident(param) {
@@ -68,6 +121,36 @@ fn valid_segments(ecx: &ExtCtxt, uri: &Uri, sp: Span) -> bool {
                 continue
             }
 
+            validated = false;
+        } else if let Some(optional_span) = optional_span {
+            ecx.struct_span_err(span, "static segment after an optional param")
+                .help("optional params must be the final segments in a path")
+                .span_note(optional_span, "optional param is here")
+                .emit();
+
+            validated = false;
+        } else if let Some((prefix, param, suffix)) = split_partial(segment) {
+            // A single parameter with static text around it: `report.<ext>`.
+            let statics_valid = Uri::percent_encode(prefix) == prefix
+                && Uri::percent_encode(suffix) == suffix;
+
+            if !statics_valid {
+                ecx.span_err(span, "segment contains invalid characters");
+            } else if param.ends_with("..") || param.contains(':') || param.ends_with('?') {
+                ecx.struct_span_err(span, "invalid parameter in a partial segment")
+                    .help("only plain parameters like 'report.<ext>' can be \
+                          combined with static text in a segment")
+                    .emit();
+            } else if param.is_empty() {
+                ecx.span_err(span, "parameters cannot be empty");
+            } else if !is_valid_ident(param) || param == "_" {
+                ecx.struct_span_err(span, "parameter names must be valid identifiers")
+                    .note(&format!("{:?} is not a valid identifier", param))
+                    .emit();
+            } else {
+                continue
+            }
+
             validated = false;
         } else if segment.starts_with("<") {
             if segment[1..].contains("<") || segment.contains(">") {
@@ -102,8 +185,9 @@ pub fn validate_uri(ecx: &ExtCtxt,
                     sp: Span)
                     -> (Spanned<Uri<'static>>, Option<Spanned<Ident>>) {
     let uri = Uri::from(string.to_string());
-    let query_param = string.find('?')
-        .map(|i| span(&string[(i + 1)..], sp.trim_left(i + 1)))
+    let query_param = uri.query()
+        .map(|query| (string.len() - query.len(), query))
+        .map(|(i, query)| span(&string[i..(i + query.len())], sp.trim_left(i)))
         .and_then(|spanned_q_param| param_to_ident(ecx, spanned_q_param));
 
     if valid_segments(ecx, &uri, sp) && valid_path(ecx, &uri, sp) {
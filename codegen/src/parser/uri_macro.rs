@@ -255,9 +255,20 @@ impl InternalUriParams {
     }
 
     pub fn uri_fmt_string(&self) -> String {
-        self.uri.node
-            .replace('<', "{")
-            .replace("..>", "}")
-            .replace('>', "}")
+        // Replace each `<name>`, `<name..>`, `<name?>`, or `<name:constraint>`
+        // with `{name}`, escaping any braces in the static parts of the path.
+        let mut string = String::with_capacity(self.uri.node.len());
+        let mut rest = self.uri.node.as_str();
+        while let Some(start) = rest.find('<') {
+            string.push_str(&rest[..start].replace('{', "{{").replace('}', "}}"));
+            let end = rest[start..].find('>').map_or(rest.len(), |i| start + i);
+            let param = rest[(start + 1)..end].trim_right_matches("..");
+            let name = param.split(':').next().unwrap_or(param).trim_right_matches('?');
+            string.push_str(&format!("{{{}}}", name));
+            rest = &rest[::std::cmp::min(end + 1, rest.len())..];
+        }
+
+        string.push_str(&rest.replace('{', "{{").replace('}', "}}"));
+        string
     }
 }
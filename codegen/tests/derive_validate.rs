@@ -0,0 +1,51 @@
+#![feature(plugin, decl_macro, custom_derive)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::request::{Validate, FormErrorKind};
+
+#[derive(Validate)]
+struct Comment {
+    #[validate(length(min = 1, max = 10))]
+    body: String,
+    #[validate(range(min = "-1", max = 1.5))]
+    score: f32,
+    #[validate(email)]
+    author: Option<String>,
+    #[validate(length(max = 2))]
+    tags: Vec<String>,
+}
+
+#[test]
+fn valid_struct_passes() {
+    let comment = Comment {
+        body: "hello".into(),
+        score: -0.5,
+        author: None,
+        tags: vec!["a".into()],
+    };
+
+    assert!(comment.validate().is_ok());
+}
+
+#[test]
+fn every_failure_is_reported() {
+    let comment = Comment {
+        body: "".into(),
+        score: 2.0,
+        author: Some("nobody".into()),
+        tags: vec!["a".into(), "b".into(), "c".into()],
+    };
+
+    let errors = comment.validate().unwrap_err();
+    let kinds: Vec<_> = errors.iter().map(|e| (e.name(), e.kind())).collect();
+    assert_eq!(kinds, vec![
+        ("body", FormErrorKind::TooShort),
+        ("score", FormErrorKind::TooLarge),
+        ("author", FormErrorKind::Email),
+        ("tags", FormErrorKind::TooLong),
+    ]);
+
+    assert_eq!(errors[1].param("max"), Some("1.5"));
+}
@@ -0,0 +1,56 @@
+#![feature(plugin, decl_macro, custom_derive)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::request::{FromForm, FormItems};
+
+fn digits(value: String) -> String {
+    value.chars().filter(|c| c.is_digit(10)).collect()
+}
+
+mod normalize {
+    pub fn squash(value: String) -> String {
+        value.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+}
+
+#[derive(Debug, PartialEq, FromForm)]
+struct Form {
+    #[form(transform = "trim, lowercase")]
+    email: String,
+    #[form(field = "tel", transform = "digits")]
+    phone: u64,
+    #[form(transform = "normalize::squash,uppercase")]
+    name: String,
+    raw: String,
+}
+
+fn parse<'f, T: FromForm<'f>>(string: &'f str) -> Option<T> {
+    let mut items = FormItems::from(string);
+    let result = T::from_form(items.by_ref(), true);
+    if !items.exhaust() {
+        panic!("Invalid form input.");
+    }
+
+    result.ok()
+}
+
+#[test]
+fn test_transforms() {
+    let form: Option<Form> = parse("email=%20Bob%40Example.COM+&tel=(555)+123-4567\
+                                    &name=+ada++lovelace&raw=+A%2BB+");
+
+    assert_eq!(form, Some(Form {
+        email: "bob@example.com".into(),
+        phone: 5551234567,
+        name: "ADA LOVELACE".into(),
+        raw: " A+B ".into(),
+    }));
+}
+
+#[test]
+fn test_transformed_value_is_reencoded() {
+    let form: Option<Form> = parse("email=a%2Bb%25c&tel=1&name=x&raw=y");
+    assert_eq!(form.map(|f| f.email), Some("a+b%c".into()));
+}
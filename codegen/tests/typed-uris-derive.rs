@@ -0,0 +1,86 @@
+#![feature(plugin, decl_macro, custom_derive)]
+#![plugin(rocket_codegen)]
+#![allow(dead_code, unused_variables)]
+
+#[macro_use] extern crate rocket;
+
+use rocket::http::RawStr;
+use rocket::http::uri::{Uri, UriDisplay};
+use rocket::request::FromParam;
+
+#[derive(UriDisplay, FromUriParam)]
+struct Name(String);
+
+impl<'r> FromParam<'r> for Name {
+    type Error = &'r RawStr;
+
+    fn from_param(param: &'r RawStr) -> Result<Self, Self::Error> {
+        param.percent_decode().map(|s| Name(s.into_owned())).map_err(|_| param)
+    }
+}
+
+#[derive(FromForm, UriDisplay, FromUriParam)]
+struct Search<'r> {
+    #[uri(rename = "q")]
+    query: &'r RawStr,
+    page: usize,
+    #[uri(skip)]
+    cached: Option<bool>,
+}
+
+#[derive(UriDisplay)]
+enum Filter {
+    #[uri(rename = "all")]
+    Everything,
+    Recent,
+    Tagged(String),
+    Range { from: u32, #[uri(rename = "until")] to: u32 },
+}
+
+#[derive(UriDisplay)]
+struct Wrapper<T>(T);
+
+#[get("/hello/<name>")]
+fn hello(name: Name) -> &'static str { "" }
+
+#[get("/search/<name>?<search>")]
+fn search<'r>(name: Name, search: Search<'r>) -> &'static str { "" }
+
+macro assert_uri_eq($($uri:expr => $expected:expr,)+) {
+    $(assert_eq!($uri, Uri::from($expected));)+
+}
+
+macro assert_display_eq($($value:expr => $expected:expr,)+) {
+    $(assert_eq!(format!("{}", &$value as &UriDisplay), $expected);)+
+}
+
+#[test]
+fn check_derived_path_param() {
+    assert_uri_eq! {
+        uri!(hello: Name("Bob".into())) => "/hello/Bob",
+        uri!(hello: name = Name("Bob Jr".into())) => "/hello/Bob%20Jr",
+        uri!(hello: String::from("Bob")) => "/hello/Bob",
+    }
+}
+
+#[test]
+fn check_derived_query_param() {
+    let search = Search { query: "a b".into(), page: 2, cached: Some(true) };
+    assert_uri_eq! {
+        uri!(search: String::from("x"), &search) => "/search/x?q=a%20b&page=2",
+        uri!(search: name = Name("x".into()), search = ("rust", 3usize))
+            => "/search/x?q=rust&page=3",
+    }
+}
+
+#[test]
+fn check_derived_enum_and_generics() {
+    assert_display_eq! {
+        Filter::Everything => "all",
+        Filter::Recent => "Recent",
+        Filter::Tagged("a/b".into()) => "a%2Fb",
+        Filter::Range { from: 1, to: 10 } => "from=1&until=10",
+        Wrapper(Filter::Recent) => "Recent",
+        Wrapper(12) => "12",
+    }
+}
@@ -10,6 +10,7 @@ mod parser;
 mod spanned;
 mod ext;
 mod codegen_ext;
+mod uri_display;
 
 use parser::Result as PResult;
 use proc_macro::{Span, TokenStream};
@@ -101,3 +102,19 @@ pub fn derive_from_form_value(input: TokenStream) -> TokenStream {
         TokenStream::empty()
     })
 }
+
+#[proc_macro_derive(UriDisplay, attributes(uri))]
+pub fn derive_uri_display(input: TokenStream) -> TokenStream {
+    uri_display::real_derive_uri_display(input).unwrap_or_else(|diag| {
+        diag.emit();
+        TokenStream::empty()
+    })
+}
+
+#[proc_macro_derive(FromUriParam, attributes(uri))]
+pub fn derive_from_uri_param(input: TokenStream) -> TokenStream {
+    uri_display::real_derive_from_uri_param(input).unwrap_or_else(|diag| {
+        diag.emit();
+        TokenStream::empty()
+    })
+}
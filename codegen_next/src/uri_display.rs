@@ -0,0 +1,252 @@
+use proc_macro::{Span, TokenStream};
+use quote::Tokens;
+
+use parser::Result as PResult;
+use spanned::Spanned;
+use ext::*;
+use syn::*;
+
+const ATTR_SYNTAX: &str = "malformed attribute: expected `#[uri(rename = \"name\")]` \
+    or `#[uri(skip)]`";
+const RENAME_UNNAMED: &str = "`rename` is only supported on named fields and variants";
+const SKIP_VARIANT: &str = "variants cannot be skipped";
+const MANY_UNNAMED: &str = "tuple structs and variants with more than one \
+    unskipped field cannot derive `UriDisplay`";
+const NO_UNIONS: &str = "unions cannot derive `UriDisplay`";
+const FROM_PARAM_ONLY_STRUCTS: &str = "`FromUriParam` can only be derived for structs";
+const FROM_PARAM_NO_GENERICS: &str = "structs with type parameters cannot derive `FromUriParam`";
+const FROM_PARAM_NO_FIELDS: &str = "structs without unskipped fields cannot derive `FromUriParam`";
+
+/// The parsed contents of all `#[uri(..)]` attributes on an item.
+#[derive(Default)]
+struct UriAttrs {
+    rename: Option<String>,
+    skip: bool,
+}
+
+fn uri_attrs(attrs: &[Attribute]) -> PResult<UriAttrs> {
+    let mut result = UriAttrs::default();
+    for attr in attrs {
+        let list = match attr.interpret_meta() {
+            Some(Meta::List(list)) => list,
+            Some(meta) => {
+                if meta.name() == "uri" {
+                    return Err(attr.span().error(ATTR_SYNTAX));
+                }
+
+                continue
+            }
+            None => continue
+        };
+
+        if list.ident != "uri" {
+            continue
+        }
+
+        for nested in list.nested.iter() {
+            match *nested {
+                NestedMeta::Meta(Meta::Word(ref word)) if *word == "skip" => {
+                    result.skip = true;
+                }
+                NestedMeta::Meta(Meta::NameValue(ref pair)) if pair.ident == "rename" => {
+                    match pair.lit {
+                        Lit::Str(ref string) => result.rename = Some(string.value()),
+                        _ => return Err(pair.lit.span().error("expected a string literal"))
+                    }
+                }
+                _ => return Err(nested.span().error(ATTR_SYNTAX))
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Returns a pattern matching `path` with `fields` that binds every unskipped
+/// field by reference along with the body that writes those fields to `f`.
+fn display_arm(path: Tokens, fields: &Fields) -> PResult<(Tokens, Tokens)> {
+    let mut patterns = vec![];
+    let mut writes = vec![];
+    for (i, field) in fields.iter().enumerate() {
+        let attrs = uri_attrs(&field.attrs)?;
+        let binding = Ident::from(format!("__uri_field_{}", i));
+        match field.ident {
+            Some(ident) => {
+                if attrs.skip {
+                    continue;
+                }
+
+                let key = attrs.rename.unwrap_or_else(|| ident.to_string());
+                if !writes.is_empty() {
+                    writes.push(quote!(f.write_str("&")?;));
+                }
+
+                patterns.push(quote!(#ident: ref #binding));
+                writes.push(quote! {
+                    ::rocket::http::uri::UriDisplay::fmt(#key, f)?;
+                    f.write_str("=")?;
+                    ::rocket::http::uri::UriDisplay::fmt(#binding, f)?;
+                });
+            }
+            None => {
+                if attrs.rename.is_some() {
+                    return Err(field.span().error(RENAME_UNNAMED));
+                } else if attrs.skip {
+                    patterns.push(quote!(_));
+                    continue;
+                } else if !writes.is_empty() {
+                    return Err(field.span().error(MANY_UNNAMED));
+                }
+
+                patterns.push(quote!(ref #binding));
+                writes.push(quote!(::rocket::http::uri::UriDisplay::fmt(#binding, f)?;));
+            }
+        }
+    }
+
+    let pattern = match *fields {
+        Fields::Named(_) => quote!(#path { #(#patterns,)* .. }),
+        Fields::Unnamed(_) => quote!(#path(#(#patterns),*)),
+        Fields::Unit => quote!(#path),
+    };
+
+    Ok((pattern, quote!({ #(#writes)* Ok(()) })))
+}
+
+pub fn real_derive_uri_display(input: TokenStream) -> PResult<TokenStream> {
+    // Parse the input `TokenStream` as a `syn::DeriveInput`, an AST.
+    let input: DeriveInput = syn::parse(input).map_err(|e| {
+        Span::call_site().error(format!("error: failed to parse input: {:?}", e))
+    })?;
+
+    // Build one match arm per variant or a single arm for a struct.
+    let name = input.ident;
+    let mut arms = vec![];
+    match input.data {
+        Data::Struct(ref data) => {
+            let (pattern, body) = display_arm(quote!(#name), &data.fields)?;
+            arms.push(quote!(#pattern => #body));
+        }
+        Data::Enum(ref data) => {
+            for variant in data.variants.iter() {
+                let attrs = uri_attrs(&variant.attrs)?;
+                if attrs.skip {
+                    return Err(variant.span().error(SKIP_VARIANT));
+                }
+
+                let ident = variant.ident;
+                let path = quote!(#name::#ident);
+                if variant.fields.is_unit() {
+                    let value = attrs.rename.unwrap_or_else(|| ident.to_string());
+                    arms.push(quote!(#path => ::rocket::http::uri::UriDisplay::fmt(#value, f)));
+                } else {
+                    let (pattern, body) = display_arm(path, &variant.fields)?;
+                    arms.push(quote!(#pattern => #body));
+                }
+            }
+        }
+        Data::Union(_) => return Err(input.span().error(NO_UNIONS))
+    }
+
+    // Every type parameter must itself be displayable.
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let predicates = where_clause.into_iter().flat_map(|clause| clause.predicates.iter());
+    let type_params = input.generics.params.iter().filter_map(|param| match *param {
+        GenericParam::Type(ref ty) => Some(ty.ident),
+        _ => None
+    });
+
+    // Generate the implementation.
+    Ok(quote! {
+        impl #impl_generics ::rocket::http::uri::UriDisplay for #name #ty_generics
+            where #(#predicates,)* #(#type_params: ::rocket::http::uri::UriDisplay,)*
+        {
+            #[allow(unreachable_code, unused_variables)]
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                match *self {
+                    #(#arms,)*
+                }
+            }
+        }
+    }.into())
+}
+
+pub fn real_derive_from_uri_param(input: TokenStream) -> PResult<TokenStream> {
+    // Parse the input `TokenStream` as a `syn::DeriveInput`, an AST.
+    let input: DeriveInput = syn::parse(input).map_err(|e| {
+        Span::call_site().error(format!("error: failed to parse input: {:?}", e))
+    })?;
+
+    // This derive only works for structs without type parameters.
+    let has_type_params = input.generics.params.iter().any(|param| match *param {
+        GenericParam::Type(_) => true,
+        _ => false
+    });
+
+    if has_type_params {
+        return Err(input.generics.span().error(FROM_PARAM_NO_GENERICS));
+    }
+
+    let input_span = input.span();
+    let name = input.ident;
+    let generics = input.generics;
+    let data = input.data.into_struct().ok_or_else(|| input_span.error(FROM_PARAM_ONLY_STRUCTS))?;
+
+    // Partition the fields into those taken from the parameter and the rest.
+    let mut param_types = vec![];
+    let mut initializers = vec![];
+    for field_member in data.fields.to_field_members() {
+        let (field, member) = (field_member.field, field_member.member);
+        if uri_attrs(&field.attrs)?.skip {
+            initializers.push(quote!(#member: ::std::default::Default::default()));
+            continue;
+        }
+
+        let arg = Ident::from(format!("__uri_arg_{}", param_types.len()));
+        initializers.push(quote!(#member: #arg.into()));
+        param_types.push((arg, &field.ty));
+    }
+
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
+    let lifetimes = generics.params.iter().filter(|param| match **param {
+        GenericParam::Lifetime(_) => true,
+        _ => false
+    });
+
+    // A single field is taken by value as its own type; several fields are
+    // taken as a tuple of values convertible into the fields' types.
+    let (extra_params, param_type, destructure) = match param_types.len() {
+        0 => return Err(input_span.error(FROM_PARAM_NO_FIELDS)),
+        1 => {
+            let (arg, ty) = param_types[0];
+            (vec![], quote!(#ty), quote!(let #arg = param;))
+        }
+        _ => {
+            let args: Vec<_> = param_types.iter().map(|&(arg, _)| arg).collect();
+            let generic_args: Vec<_> = (0..args.len())
+                .map(|i| Ident::from(format!("__UriArg{}", i)))
+                .collect();
+
+            let bounds = generic_args.iter().zip(param_types.iter())
+                .map(|(generic, &(_, ty))| quote!(#generic: ::std::convert::Into<#ty>))
+                .collect();
+
+            (bounds, quote!((#(#generic_args),*)), quote!(let (#(#args),*) = param;))
+        }
+    };
+
+    // Generate the implementation.
+    Ok(quote! {
+        impl<#(#lifetimes,)* #(#extra_params),*> ::rocket::http::uri::FromUriParam<#param_type>
+            for #name #ty_generics #where_clause
+        {
+            type Target = #name #ty_generics;
+
+            #[allow(unused_variables)]
+            fn from_uri_param(param: #param_type) -> Self::Target {
+                #destructure
+                #name { #(#initializers),* }
+            }
+        }
+    }.into())
+}
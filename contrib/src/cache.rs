@@ -0,0 +1,535 @@
+//! An in-memory response cache fairing with a programmatic invalidation API.
+//!
+//! The [`ResponseCache`] fairing stores successful responses to `GET` requests
+//! in memory and serves identical requests from memory until the entry
+//! expires, without running the route's handler. Entries are keyed by the
+//! request's method, URI (including the query string), and the values of a
+//! configurable set of request headers. The cache is bounded both by number of
+//! entries and by total body size; when either bound is exceeded, the oldest
+//! entries are evicted first.
+//!
+//! Responses are only cached if they have a status of `200 OK`, don't set
+//! cookies, and don't carry a `Cache-Control` header with a `no-store`,
+//! `no-cache`, or `private` directive.
+//!
+//! When Rocket's compression is configured, the cache also stores the
+//! compressed bodies of an entry, one per content coding, as they are first
+//! requested. A request for a coding that is already stored skips both the
+//! handler and compression. Compressed bodies count towards the cache's size
+//! bound. Since the cache compresses responses itself, it should be attached
+//! after any fairing that inspects or modifies response bodies.
+//!
+//! The cache records its activity in Rocket's [metrics]: `cache.hits`,
+//! `cache.compressed_hits` (hits served with a stored compressed body),
+//! `cache.misses`, and `cache.bytes`, the total size of the cached bodies.
+//!
+//! When a [`DevReload`] fairing is attached, every entry is removed once it
+//! observes a change to the watched assets or templates.
+//!
+//! # Usage
+//!
+//! To use, add the `cache` feature to the `rocket_contrib` dependencies
+//! section of your `Cargo.toml`:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "*"
+//! default-features = false
+//! features = ["cache"]
+//! ```
+//!
+//! Then attach the fairing. A [`CacheHandle`] is managed by the fairing and
+//! can be retrieved in any handler via `State` to invalidate entries after a
+//! write:
+//!
+//! ```rust,ignore
+//! use std::time::Duration;
+//! use rocket::State;
+//! use rocket_contrib::cache::{ResponseCache, CacheHandle};
+//!
+//! #[get("/users/<id>")]
+//! fn user(id: usize) -> String { /* expensive */ }
+//!
+//! #[put("/users/<id>", data = "<user>")]
+//! fn update(id: usize, user: String, cache: State<CacheHandle>) -> &'static str {
+//!     /* write */
+//!     cache.invalidate(&format!("/users/{}", id));
+//!     "updated"
+//! }
+//!
+//! fn main() {
+//!     let cache = ResponseCache::new()
+//!         .ttl(Duration::from_secs(30))
+//!         .max_entries(512)
+//!         .vary("Accept");
+//!
+//!     rocket::ignite()
+//!         .attach(cache)
+//!         .mount("/", routes![user, update])
+//!         .launch();
+//! }
+//! ```
+//!
+//! [`ResponseCache`]: /rocket_contrib/cache/struct.ResponseCache.html
+//! [`CacheHandle`]: /rocket_contrib/cache/struct.CacheHandle.html
+//! [metrics]: /rocket/metrics/index.html
+//! [`DevReload`]: /rocket/dev/struct.DevReload.html
+
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rocket::{Rocket, Request, Response, Data, Route, State};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::handler::Outcome;
+use rocket::http::{Method, Header, Status};
+use rocket::metrics::{Metrics, Counter};
+use rocket::compression;
+use rocket::dev::ReloadHandle;
+
+/// The name given to the routes the cache mounts to serve hits.
+const CACHE_ROUTE_NAME: &str = "rocket_contrib::cache";
+
+/// A fairing that caches responses to `GET` requests in memory.
+///
+/// See the [module level documentation](/rocket_contrib/cache/index.html) for
+/// usage details.
+pub struct ResponseCache {
+    ttl: Duration,
+    max_entries: usize,
+    max_bytes: usize,
+    vary: Vec<String>,
+    handle: CacheHandle,
+}
+
+/// A handle to the entries of a [`ResponseCache`], used to invalidate entries
+/// programmatically.
+///
+/// A `CacheHandle` is managed by the `ResponseCache` fairing when it is
+/// attached; retrieve it with `State<CacheHandle>`. Clones of a handle refer
+/// to the same cache.
+///
+/// [`ResponseCache`]: /rocket_contrib/cache/struct.ResponseCache.html
+#[derive(Clone)]
+pub struct CacheHandle {
+    inner: Arc<Mutex<Entries>>,
+}
+
+struct Entry {
+    uri: String,
+    status: Status,
+    headers: Vec<Header<'static>>,
+    body: Vec<u8>,
+    /// The compressed bodies, keyed by content coding.
+    variants: HashMap<&'static str, Vec<u8>>,
+    stored: Instant,
+}
+
+impl Entry {
+    // The total size of the entry's bodies.
+    fn size(&self) -> usize {
+        self.body.len() + self.variants.values().map(|body| body.len()).sum::<usize>()
+    }
+}
+
+/// The metrics the cache records in Rocket's registry.
+struct CacheMetrics {
+    hits: Counter,
+    compressed_hits: Counter,
+    misses: Counter,
+    bytes: Counter,
+}
+
+impl CacheMetrics {
+    fn new(metrics: &Metrics) -> CacheMetrics {
+        CacheMetrics {
+            hits: metrics.counter("cache.hits"),
+            compressed_hits: metrics.counter("cache.compressed_hits"),
+            misses: metrics.counter("cache.misses"),
+            bytes: metrics.counter("cache.bytes"),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Entries {
+    map: HashMap<String, Entry>,
+    bytes: usize,
+    metrics: Option<CacheMetrics>,
+    /// The development reload generation the entries were stored at.
+    generation: u64,
+}
+
+impl Entries {
+    fn set_bytes(&mut self, bytes: usize) {
+        self.bytes = bytes;
+        if let Some(ref metrics) = self.metrics {
+            metrics.bytes.set(bytes);
+        }
+    }
+
+    fn record<F: Fn(&CacheMetrics) -> &Counter>(&self, counter: F) {
+        if let Some(ref metrics) = self.metrics {
+            counter(metrics).incr();
+        }
+    }
+
+    fn remove(&mut self, key: &str) -> bool {
+        match self.map.remove(key) {
+            Some(entry) => {
+                let bytes = self.bytes - entry.size();
+                self.set_bytes(bytes);
+                true
+            }
+            None => false
+        }
+    }
+
+    fn remove_where<F: Fn(&Entry) -> bool>(&mut self, f: F) -> usize {
+        let keys: Vec<String> = self.map.iter()
+            .filter(|&(_, entry)| f(entry))
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for key in &keys {
+            self.remove(key);
+        }
+
+        keys.len()
+    }
+
+    fn evict_oldest(&mut self) {
+        let oldest = self.map.iter()
+            .min_by_key(|&(_, entry)| entry.stored)
+            .map(|(key, _)| key.clone());
+
+        if let Some(key) = oldest {
+            self.remove(&key);
+        }
+    }
+}
+
+impl ResponseCache {
+    /// Returns a new `ResponseCache` with a TTL of 60 seconds, a maximum of
+    /// 1024 entries, a maximum of 64MiB of cached bodies, and no varying
+    /// headers.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::cache::ResponseCache;
+    ///
+    /// # if false {
+    /// rocket::ignite().attach(ResponseCache::new());
+    /// # }
+    /// ```
+    pub fn new() -> ResponseCache {
+        ResponseCache {
+            ttl: Duration::from_secs(60),
+            max_entries: 1024,
+            max_bytes: 64 * 1024 * 1024,
+            vary: vec![],
+            handle: CacheHandle { inner: Arc::new(Mutex::new(Entries::default())) },
+        }
+    }
+
+    /// Sets the time-to-live of cached entries to `ttl`.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets the maximum number of cached entries to `max`.
+    pub fn max_entries(mut self, max: usize) -> Self {
+        self.max_entries = max;
+        self
+    }
+
+    /// Sets the maximum total size, in bytes, of cached bodies to `max`.
+    /// Responses with bodies larger than `max` are never cached.
+    pub fn max_bytes(mut self, max: usize) -> Self {
+        self.max_bytes = max;
+        self
+    }
+
+    /// Adds the request header `name` to the cache key. Requests that differ
+    /// in the value of `name` are cached separately.
+    pub fn vary(mut self, name: &str) -> Self {
+        self.vary.push(name.to_string());
+        self
+    }
+
+    /// Returns a handle to this cache's entries. The same handle is managed by
+    /// Rocket when the fairing is attached.
+    pub fn handle(&self) -> CacheHandle {
+        self.handle.clone()
+    }
+
+    fn key(&self, request: &Request) -> String {
+        let mut key = format!("{} {}", request.method(), request.uri());
+        for name in &self.vary {
+            key.push('\0');
+            key.push_str(name);
+            for value in request.headers().get(name) {
+                key.push(':');
+                key.push_str(value);
+            }
+        }
+
+        key
+    }
+
+    fn is_cacheable(request: &Request, response: &Response) -> bool {
+        if request.method() != Method::Get || response.status() != Status::Ok {
+            return false;
+        }
+
+        if request.route().map_or(false, |r| r.name == Some(CACHE_ROUTE_NAME)) {
+            return false;
+        }
+
+        let uncacheable = ["no-store", "no-cache", "private"];
+        let forbids_caching = response.headers().get("Cache-Control")
+            .flat_map(|value| value.split(','))
+            .any(|directive| uncacheable.contains(&directive.trim()));
+
+        !forbids_caching && !response.headers().contains("Set-Cookie")
+    }
+
+    fn lookup<'r>(request: &'r Request, data: Data) -> Outcome<'r> {
+        let cache = match request.guard::<State<ResponseCache>>() {
+            ::rocket::Outcome::Success(cache) => cache.inner(),
+            _ => return Outcome::forward(data)
+        };
+
+        let key = cache.key(request);
+        let coding = compression::preferred_coding(request);
+        let mut entries = cache.handle.inner.lock().expect("cache lock");
+
+        // Entries may be stale once a development reload observes a change.
+        if let ::rocket::Outcome::Success(reload) = request.guard::<State<ReloadHandle>>() {
+            let generation = reload.generation();
+            if entries.generation != generation {
+                entries.map.clear();
+                entries.set_bytes(0);
+                entries.generation = generation;
+            }
+        }
+        let (response, expired) = match entries.map.get(&key) {
+            Some(entry) if entry.stored.elapsed() < cache.ttl => {
+                let mut response = Response::build();
+                response.status(entry.status);
+                for header in &entry.headers {
+                    response.header_adjoin(header.clone());
+                }
+
+                let age = entry.stored.elapsed().as_secs().to_string();
+                response.raw_header("Age", age);
+                match coding.and_then(|coding| entry.variants.get(coding).map(|b| (coding, b))) {
+                    Some((coding, body)) => {
+                        response.raw_header("Content-Encoding", coding);
+                        response.raw_header_adjoin("Vary", "Accept-Encoding");
+                        response.sized_body(Cursor::new(body.clone()));
+                    }
+                    None => {
+                        response.sized_body(Cursor::new(entry.body.clone()));
+                    }
+                }
+
+                (Some(response.finalize()), false)
+            }
+            Some(_) => (None, true),
+            None => (None, false)
+        };
+
+        if expired {
+            entries.remove(&key);
+        }
+
+        match response {
+            Some(response) => {
+                entries.record(|m| &m.hits);
+                if response.headers().contains("Content-Encoding") {
+                    entries.record(|m| &m.compressed_hits);
+                }
+
+                Outcome::Success(response)
+            }
+            None => {
+                entries.record(|m| &m.misses);
+                Outcome::forward(data)
+            }
+        }
+    }
+}
+
+impl Fairing for ResponseCache {
+    fn info(&self) -> Info {
+        Info { name: "Response Cache", kind: Kind::Attach | Kind::Response }
+    }
+
+    fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        // These routes are tried before any other: they respond on a cache hit
+        // and forward on a miss.
+        let mut routes = vec![];
+        let lookups = [("/<path..>", isize::min_value()), ("/", isize::min_value() + 1)];
+        for &(path, rank) in &lookups {
+            let mut route = Route::ranked(rank, Method::Get, path, ResponseCache::lookup);
+            route.name = Some(CACHE_ROUTE_NAME);
+            routes.push(route);
+        }
+
+        let cache = ResponseCache {
+            ttl: self.ttl,
+            max_entries: self.max_entries,
+            max_bytes: self.max_bytes,
+            vary: self.vary.clone(),
+            handle: self.handle.clone(),
+        };
+
+        let metrics = CacheMetrics::new(rocket.metrics());
+        self.handle.inner.lock().expect("cache lock").metrics = Some(metrics);
+
+        Ok(rocket.manage(cache).manage(self.handle.clone()).mount("/", routes))
+    }
+
+    fn on_response(&self, request: &Request, response: &mut Response) {
+        // A response served from the cache may lack the client's coding.
+        if request.route().map_or(false, |r| r.name == Some(CACHE_ROUTE_NAME)) {
+            self.store_variant(request, response);
+            return;
+        }
+
+        if !ResponseCache::is_cacheable(request, response) {
+            return;
+        }
+
+        let body = match response.body_bytes() {
+            Some(body) => body,
+            None => return
+        };
+
+        response.set_sized_body(Cursor::new(body.clone()));
+        if body.len() > self.max_bytes {
+            return;
+        }
+
+        let entry = Entry {
+            uri: request.uri().path().to_string(),
+            status: response.status(),
+            headers: response.headers().iter()
+                .map(|h| Header::new(h.name().to_string(), h.value().to_string()))
+                .collect(),
+            body: body,
+            variants: HashMap::new(),
+            stored: Instant::now(),
+        };
+
+        // Borrows of `entries` must end before the variant is stored.
+        {
+            let key = self.key(request);
+            let mut entries = self.handle.inner.lock().expect("cache lock");
+            entries.remove(&key);
+            while !entries.map.is_empty() && (entries.map.len() >= self.max_entries
+                    || entries.bytes + entry.body.len() > self.max_bytes) {
+                entries.evict_oldest();
+            }
+
+            if self.max_entries > 0 {
+                let bytes = entries.bytes + entry.body.len();
+                entries.set_bytes(bytes);
+                entries.map.insert(key, entry);
+            }
+        }
+
+        self.store_variant(request, response);
+    }
+}
+
+impl ResponseCache {
+    /// Compresses `response`, whose uncompressed body is cached, as Rocket
+    /// would and stores the compressed body with the cache entry for `request`
+    /// if it fits within the size bound.
+    fn store_variant(&self, request: &Request, response: &mut Response) {
+        let coding = match compression::compress(request, response) {
+            Some(coding) => coding,
+            None => return
+        };
+
+        let body = match response.body_bytes() {
+            Some(body) => body,
+            None => return
+        };
+
+        response.set_sized_body(Cursor::new(body.clone()));
+        let key = self.key(request);
+        let mut entries = self.handle.inner.lock().expect("cache lock");
+        let fits = entries.bytes + body.len() <= self.max_bytes;
+        let stored = match entries.map.get_mut(&key) {
+            Some(entry) if fits && !entry.variants.contains_key(coding) => {
+                entry.variants.insert(coding, body.clone());
+                true
+            }
+            _ => false
+        };
+
+        if stored {
+            let bytes = entries.bytes + body.len();
+            entries.set_bytes(bytes);
+        }
+    }
+}
+
+impl CacheHandle {
+    /// Removes all cached entries for requests to the path `path`, regardless
+    /// of query string or varying headers. Returns the number of entries
+    /// removed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::cache::ResponseCache;
+    ///
+    /// let handle = ResponseCache::new().handle();
+    /// assert_eq!(handle.invalidate("/users/1"), 0);
+    /// ```
+    pub fn invalidate(&self, path: &str) -> usize {
+        let mut entries = self.inner.lock().expect("cache lock");
+        entries.remove_where(|entry| entry.uri == path)
+    }
+
+    /// Removes all cached entries for requests to paths beginning with
+    /// `prefix`. Returns the number of entries removed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::cache::ResponseCache;
+    ///
+    /// let handle = ResponseCache::new().handle();
+    /// assert_eq!(handle.invalidate_prefix("/users/"), 0);
+    /// ```
+    pub fn invalidate_prefix(&self, prefix: &str) -> usize {
+        let mut entries = self.inner.lock().expect("cache lock");
+        entries.remove_where(|entry| entry.uri.starts_with(prefix))
+    }
+
+    /// Removes every cached entry.
+    pub fn clear(&self) {
+        let mut entries = self.inner.lock().expect("cache lock");
+        entries.map.clear();
+        entries.set_bytes(0);
+    }
+
+    /// Returns the number of cached entries, including those that have expired
+    /// but have not yet been removed.
+    pub fn len(&self) -> usize {
+        self.inner.lock().expect("cache lock").map.len()
+    }
+
+    /// Returns `true` if there are no cached entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
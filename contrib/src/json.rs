@@ -1,10 +1,10 @@
 use std::ops::{Deref, DerefMut};
-use std::io::Read;
+use std::io::{self, Read};
 
-use rocket::outcome::{Outcome, IntoOutcome};
-use rocket::request::Request;
+use rocket::outcome::Outcome;
+use rocket::request::{Request, FlashMessage, FormContext, FormErrors, Validate};
 use rocket::data::{self, Data, FromData};
-use rocket::response::{self, Responder, content};
+use rocket::response::{self, Responder, Flash, FlashEntry, content};
 use rocket::http::Status;
 
 use serde::Serialize;
@@ -14,20 +14,25 @@ use serde_json;
 
 pub use serde_json::error::Error as SerdeError;
 
-/// Like [`from_reader`] but eagerly reads the content of the reader to a string
-/// and delegates to `from_str`.
+/// Like [`from_reader`] but eagerly reads the content of the reader and
+/// decodes it as text, as configured for `request`, before delegating to
+/// `from_str`. Returns the error along with the status to fail with.
 ///
 /// [`from_reader`]: https://docs.serde.rs/serde_json/fn.from_reader.html
-fn from_reader_eager<R, T>(mut reader: R) -> serde_json::Result<T>
+fn from_reader_eager<R, T>(request: &Request, mut reader: R) -> Result<T, (Status, SerdeError)>
     where R: Read, T: DeserializeOwned
 {
-    let mut s = String::with_capacity(512);
-    if let Err(io_err) = reader.read_to_string(&mut s) {
-        // Error::io is private to serde_json. Do not use outside of Rocket.
-        return Err(SerdeError::io(io_err));
+    // Error::io is private to serde_json. Do not use outside of Rocket.
+    let mut bytes = Vec::with_capacity(512);
+    if let Err(io_err) = reader.read_to_end(&mut bytes) {
+        return Err((Status::BadRequest, SerdeError::io(io_err)));
     }
 
-    serde_json::from_str(&s)
+    let s = data::decode_text(request, bytes).map_err(|e| {
+        (e.status(), SerdeError::io(io::Error::new(io::ErrorKind::InvalidData, e)))
+    })?;
+
+    serde_json::from_str(&s).map_err(|e| (Status::BadRequest, e))
 }
 
 /// The JSON type: implements `FromData` and `Responder`, allowing you to easily
@@ -113,10 +118,89 @@ impl<T: DeserializeOwned> FromData for Json<T> {
         }
 
         let size_limit = request.limits().get("json").unwrap_or(LIMIT);
-        from_reader_eager(data.open().take(size_limit))
-            .map(|val| Json(val))
-            .map_err(|e| { error_!("Couldn't parse JSON body: {:?}", e); e })
-            .into_outcome(Status::BadRequest)
+        match from_reader_eager(request, data.open().take(size_limit)) {
+            Ok(val) => Outcome::Success(Json(val)),
+            Err((status, e)) => {
+                error_!("Couldn't parse JSON body: {:?}", e);
+                Outcome::Failure((status, e))
+            }
+        }
+    }
+}
+
+/// A data guard adapter that validates the value parsed by another guard.
+///
+/// `Valid<Json<T>>` parses the request body as `Json<T>` and then validates
+/// the value with its [`Validate`] implementation, which is typically derived
+/// using `validate` field attributes. All validation failures are aggregated
+/// into a single `422 Unprocessable Entity` failure.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #[derive(Deserialize, Validate)]
+/// struct NewComment {
+///     #[validate(length(min = 1, max = 280))]
+///     body: String,
+/// }
+///
+/// #[post("/comments", format = "json", data = "<comment>")]
+/// fn new(comment: Valid<Json<NewComment>>) -> String {
+///     let Valid(Json(comment)) = comment;
+///     comment.body
+/// }
+/// ```
+///
+/// [`Validate`]: /rocket/request/trait.Validate.html
+#[derive(Debug)]
+pub struct Valid<T>(pub T);
+
+impl<T> Valid<T> {
+    /// Consumes the adapter and returns the wrapped guard.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Valid<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+/// The error of a `Valid<Json<T>>` data guard.
+#[derive(Debug)]
+pub enum JsonValidationError {
+    /// The body could not be parsed as JSON. Fails with `400 Bad Request`.
+    Parse(SerdeError),
+    /// The value failed validation. Fails with `422 Unprocessable Entity`.
+    Invalid(FormErrors),
+}
+
+impl<T: DeserializeOwned + Validate> FromData for Valid<Json<T>> {
+    type Error = JsonValidationError;
+
+    fn from_data(request: &Request, data: Data) -> data::Outcome<Self, Self::Error> {
+        let json = match Json::<T>::from_data(request, data) {
+            Outcome::Success(json) => json,
+            Outcome::Failure((status, e)) => {
+                return Outcome::Failure((status, JsonValidationError::Parse(e)));
+            }
+            Outcome::Forward(data) => return Outcome::Forward(data)
+        };
+
+        match json.validate() {
+            Ok(()) => Outcome::Success(Valid(json)),
+            Err(errors) => {
+                error_!("JSON failed validation: {}", errors);
+                let error = JsonValidationError::Invalid(errors);
+                Outcome::Failure((Status::UnprocessableEntity, error))
+            }
+        }
     }
 }
 
@@ -232,6 +316,105 @@ impl<'a> Responder<'a> for JsonValue {
     }
 }
 
+impl<'a, 'r> From<&'a FlashMessage<'a, 'r>> for JsonValue {
+    /// Converts the messages of a flash into an array of JSON objects with
+    /// `name`, `msg`, and `payload` fields, suitable for a JSON response or a
+    /// template context. Payloads that are valid JSON are embedded as JSON;
+    /// other payloads are embedded as strings.
+    fn from(flash: &'a FlashMessage<'a, 'r>) -> JsonValue {
+        let messages = flash.messages().iter().map(|m| {
+            let payload = m.payload().map(|p| {
+                serde_json::from_str(p)
+                    .unwrap_or_else(|_| serde_json::Value::String(p.to_string()))
+            });
+
+            let mut object = serde_json::Map::new();
+            object.insert("name".into(), m.name().into());
+            object.insert("msg".into(), m.msg().into());
+            object.insert("payload".into(), payload.unwrap_or(serde_json::Value::Null));
+            serde_json::Value::Object(object)
+        });
+
+        JsonValue(serde_json::Value::Array(messages.collect()))
+    }
+}
+
+impl<'a> From<&'a FormContext> for JsonValue {
+    /// Converts a form context into a JSON object with two fields: `values`,
+    /// mapping each submitted field name to the first value submitted for it,
+    /// and `errors`, mapping each field name to an array of error messages.
+    /// The result can be passed directly to a template to re-render a form.
+    fn from(context: &'a FormContext) -> JsonValue {
+        let mut values = serde_json::Map::new();
+        for (name, value) in context.fields() {
+            if !values.contains_key(name) {
+                values.insert(name.into(), value.into());
+            }
+        }
+
+        let mut errors = serde_json::Map::new();
+        for error in context.errors() {
+            let entry = errors.entry(error.name())
+                .or_insert_with(|| serde_json::Value::Array(vec![]));
+
+            if let serde_json::Value::Array(ref mut messages) = *entry {
+                messages.push(error.to_string().into());
+            }
+        }
+
+        let mut object = serde_json::Map::new();
+        object.insert("values".into(), serde_json::Value::Object(values));
+        object.insert("errors".into(), serde_json::Value::Object(errors));
+        JsonValue(serde_json::Value::Object(object))
+    }
+}
+
+/// Typed JSON payloads for [`Flash`] messages.
+///
+/// # Example
+///
+/// ```rust
+/// # extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use std::collections::HashMap;
+/// use rocket::response::{Flash, Redirect};
+/// use rocket_contrib::FlashJson;
+///
+/// # fn main() {
+/// let mut created = HashMap::new();
+/// created.insert("id", 17);
+///
+/// # #[allow(unused_variables)]
+/// let flash = Flash::success(Redirect::to("/"), "Created.").with_json(&created);
+/// # }
+/// ```
+///
+/// [`Flash`]: /rocket/response/struct.Flash.html
+pub trait FlashJson {
+    /// Sets the payload of the most recently added message to `payload`
+    /// serialized as JSON. If serialization fails, no payload is set.
+    fn with_json<T: Serialize>(self, payload: &T) -> Self;
+}
+
+impl<'r, R: Responder<'r>> FlashJson for Flash<R> {
+    fn with_json<T: Serialize>(self, payload: &T) -> Self {
+        match serde_json::to_string(payload) {
+            Ok(json) => self.with_payload(json),
+            Err(e) => {
+                warn_!("Flash payload failed to serialize: {}", e);
+                self
+            }
+        }
+    }
+}
+
+/// Deserializes the JSON payload of the flash message `entry` into a `T`.
+/// Returns `None` if the message has no payload or the payload doesn't
+/// deserialize into a `T`.
+pub fn flash_payload<T: DeserializeOwned>(entry: &FlashEntry) -> Option<T> {
+    entry.payload().and_then(|p| serde_json::from_str(p).ok())
+}
+
 /// A macro to create ad-hoc JSON serializable values using JSON syntax.
 ///
 /// # Usage
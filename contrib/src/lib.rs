@@ -21,6 +21,10 @@
 //! * [handlebars_templates](struct.Template.html)
 //! * [tera_templates](struct.Template.html)
 //! * [uuid](struct.Uuid.html)
+//! * [s3](s3/index.html)
+//! * [cache](cache/index.html)
+//! * [http_signatures](signatures/index.html)
+//! * [chunked_uploads](uploads/index.html)
 //!
 //! The recommend way to include features from this crate via Cargo in your
 //! project is by adding a `[dependencies.rocket_contrib]` section to your
@@ -61,7 +65,9 @@ pub extern crate tera;
 pub mod json;
 
 #[cfg(feature = "json")]
-pub use json::{Json, SerdeError, JsonValue};
+pub use json::{Json, SerdeError, JsonValue, FlashJson, flash_payload};
+#[cfg(feature = "json")]
+pub use json::{Valid, JsonValidationError};
 
 #[cfg(feature = "msgpack")]
 #[doc(hidden)]
@@ -81,3 +87,15 @@ mod uuid;
 
 #[cfg(feature = "uuid")]
 pub use uuid::{Uuid, UuidParseError};
+
+#[cfg(feature = "s3")]
+pub mod s3;
+
+#[cfg(feature = "cache")]
+pub mod cache;
+
+#[cfg(feature = "http_signatures")]
+pub mod signatures;
+
+#[cfg(feature = "chunked_uploads")]
+pub mod uploads;
@@ -0,0 +1,549 @@
+//! Streaming uploads of request bodies to S3-compatible object stores.
+//!
+//! The [`Bucket`] type streams a request's [`Data`] directly to an S3
+//! compatible endpoint (Amazon S3, MinIO, Ceph, and so on) without first
+//! writing it to disk or buffering it in its entirety. Bodies no larger than a
+//! single part are uploaded with one `PUT`; larger bodies are uploaded with a
+//! multipart upload, buffering at most one part in memory at a time. All
+//! requests are signed with AWS Signature Version 4.
+//!
+//! # Usage
+//!
+//! To use, add the `s3` feature to the `rocket_contrib` dependencies section of
+//! your `Cargo.toml`:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "*"
+//! default-features = false
+//! features = ["s3"]
+//! ```
+//!
+//! Then, configure the bucket in `Rocket.toml` under the `s3` extra:
+//!
+//! ```toml
+//! [global.s3]
+//! endpoint = "https://s3.us-east-1.amazonaws.com"
+//! region = "us-east-1"
+//! bucket = "uploads"
+//! access_key = "AKIDEXAMPLE"
+//! secret_key = "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY"
+//! ```
+//!
+//! attach the fairing returned by [`Bucket::fairing()`], and retrieve the
+//! `Bucket` in handlers via `State`:
+//!
+//! ```rust,ignore
+//! use rocket::{Data, State};
+//! use rocket_contrib::s3::{Bucket, Upload, S3Error};
+//!
+//! #[post("/upload/<name>", data = "<data>")]
+//! fn upload(bucket: State<Bucket>, name: String, data: Data) -> Result<String, S3Error> {
+//!     let upload = bucket.upload(&name, data, None)?;
+//!     Ok(format!("stored {} ({} bytes, etag {})", upload.key, upload.size, upload.etag))
+//! }
+//!
+//! fn main() {
+//!     rocket::ignite()
+//!         .attach(Bucket::fairing())
+//!         .mount("/", routes![upload])
+//!         .launch();
+//! }
+//! ```
+//!
+//! [`Bucket`]: /rocket_contrib/s3/struct.Bucket.html
+//! [`Bucket::fairing()`]: /rocket_contrib/s3/struct.Bucket.html#method.fairing
+//! [`Data`]: /rocket/data/struct.Data.html
+
+extern crate hyper;
+extern crate hyper_sync_rustls;
+extern crate sha2;
+extern crate hmac;
+extern crate time;
+
+use std::fmt;
+use std::io::{self, Read};
+
+use rocket::Data;
+use rocket::config::Config;
+use rocket::fairing::{Fairing, AdHoc};
+use rocket::http::{ContentType, Status};
+use rocket::request::Request;
+use rocket::response::{self, Responder};
+
+use self::hyper::Client;
+use self::hyper::client::Body;
+use self::hyper::header::Headers;
+use self::hyper::method::Method;
+use self::hyper::net::HttpsConnector;
+use self::hyper::Url;
+use self::hyper_sync_rustls::TlsClient;
+use self::sha2::{Sha256, Digest};
+use self::hmac::{Hmac, Mac};
+
+/// The default size, in bytes, of each part of a multipart upload: 8MiB.
+const DEFAULT_PART_SIZE: usize = 8 * 1024 * 1024;
+
+/// The minimum part size S3 accepts for all but the last part: 5MiB.
+const MIN_PART_SIZE: usize = 5 * 1024 * 1024;
+
+/// An S3-compatible bucket that request bodies can be streamed to.
+///
+/// A `Bucket` can be created directly via [`Bucket::new()`] or from the `s3`
+/// configuration extra via [`Bucket::fairing()`].
+///
+/// [`Bucket::new()`]: #method.new
+/// [`Bucket::fairing()`]: #method.fairing
+pub struct Bucket {
+    endpoint: Url,
+    region: String,
+    name: String,
+    access_key: String,
+    secret_key: String,
+    part_size: usize,
+    client: Client,
+}
+
+/// The result of a successful upload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Upload {
+    /// The key of the stored object.
+    pub key: String,
+    /// The entity tag the store assigned to the object.
+    pub etag: String,
+    /// The size of the object in bytes.
+    pub size: u64,
+}
+
+/// An error that occurs while uploading to an S3-compatible store.
+///
+/// When used as a `Responder`, the error is logged and a `502 Bad Gateway` is
+/// returned for errors originating at the store and a `500 Internal Server
+/// Error` for all others.
+#[derive(Debug)]
+pub enum S3Error {
+    /// An I/O error occurred while reading the request body.
+    Io(io::Error),
+    /// An error occurred while communicating with the store.
+    Http(hyper::Error),
+    /// The store responded with a non-success status code and a body.
+    Status(u16, String),
+    /// The store's response could not be understood.
+    BadResponse(&'static str),
+}
+
+impl From<io::Error> for S3Error {
+    fn from(error: io::Error) -> S3Error {
+        S3Error::Io(error)
+    }
+}
+
+impl From<hyper::Error> for S3Error {
+    fn from(error: hyper::Error) -> S3Error {
+        S3Error::Http(error)
+    }
+}
+
+impl fmt::Display for S3Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            S3Error::Io(ref e) => write!(f, "i/o error: {}", e),
+            S3Error::Http(ref e) => write!(f, "http error: {}", e),
+            S3Error::Status(code, ref body) => write!(f, "store responded {}: {}", code, body),
+            S3Error::BadResponse(reason) => write!(f, "bad response from store: {}", reason),
+        }
+    }
+}
+
+impl ::std::error::Error for S3Error {
+    fn description(&self) -> &str {
+        match *self {
+            S3Error::Io(..) => "i/o error while reading the body",
+            S3Error::Http(..) => "http error while communicating with the store",
+            S3Error::Status(..) => "store responded with a non-success status",
+            S3Error::BadResponse(..) => "store responded with an unexpected response",
+        }
+    }
+}
+
+impl<'r> Responder<'r> for S3Error {
+    fn respond_to(self, _: &Request) -> response::Result<'r> {
+        error_!("S3 upload failed: {}", self);
+        match self {
+            S3Error::Io(_) => Err(Status::InternalServerError),
+            _ => Err(Status::BadGateway),
+        }
+    }
+}
+
+impl Bucket {
+    /// Creates a new `Bucket` named `name` at the S3-compatible `endpoint` in
+    /// `region`, authenticating with `access_key` and `secret_key`. Objects are
+    /// addressed path-style: `{endpoint}/{name}/{key}`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `endpoint` is not a valid URL.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::s3::Bucket;
+    ///
+    /// let bucket = Bucket::new("https://s3.us-east-1.amazonaws.com", "us-east-1",
+    ///     "uploads", "AKIDEXAMPLE", "secret");
+    ///
+    /// assert!(bucket.is_ok());
+    /// ```
+    pub fn new(
+        endpoint: &str,
+        region: &str,
+        name: &str,
+        access_key: &str,
+        secret_key: &str
+    ) -> Result<Bucket, S3Error> {
+        let endpoint = Url::parse(endpoint)
+            .map_err(|_| S3Error::BadResponse("invalid endpoint URL"))?;
+
+        let client = Client::with_connector(HttpsConnector::new(TlsClient::new()));
+        Ok(Bucket {
+            endpoint: endpoint,
+            region: region.to_string(),
+            name: name.to_string(),
+            access_key: access_key.to_string(),
+            secret_key: secret_key.to_string(),
+            part_size: DEFAULT_PART_SIZE,
+            client: client,
+        })
+    }
+
+    /// Sets the size of each part of a multipart upload to `size` bytes. This
+    /// is also the largest body that is uploaded with a single request. Sizes
+    /// below the 5MiB minimum allowed by S3 are raised to the minimum.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::s3::Bucket;
+    ///
+    /// let bucket = Bucket::new("https://s3.example.com", "us-east-1",
+    ///     "uploads", "AKIDEXAMPLE", "secret").unwrap()
+    ///     .part_size(16 * 1024 * 1024);
+    /// ```
+    pub fn part_size(mut self, size: usize) -> Self {
+        self.part_size = ::std::cmp::max(size, MIN_PART_SIZE);
+        self
+    }
+
+    /// Returns a fairing that reads the `s3` configuration extra and manages
+    /// the resulting `Bucket`. The extra must be a table with string values
+    /// for `endpoint`, `region`, `bucket`, `access_key`, and `secret_key` and,
+    /// optionally, an integer `part_size`. If the configuration is missing or
+    /// invalid, an error is logged and the fairing fails to attach.
+    pub fn fairing() -> impl Fairing {
+        AdHoc::on_attach(|rocket| {
+            match Bucket::from_config(rocket.config()) {
+                Ok(bucket) => Ok(rocket.manage(bucket)),
+                Err(e) => {
+                    error!("Invalid 's3' configuration: {}", e);
+                    Err(rocket)
+                }
+            }
+        })
+    }
+
+    fn from_config(config: &Config) -> Result<Bucket, String> {
+        let table = config.get_table("s3").map_err(|e| e.to_string())?;
+        let get = |key: &str| -> Result<&str, String> {
+            table.get(key).and_then(|v| v.as_str())
+                .ok_or_else(|| format!("missing or non-string `{}`", key))
+        };
+
+        let bucket = Bucket::new(get("endpoint")?, get("region")?, get("bucket")?,
+            get("access_key")?, get("secret_key")?).map_err(|e| e.to_string())?;
+
+        match table.get("part_size").map(|v| v.as_integer()) {
+            Some(Some(n)) if n > 0 => Ok(bucket.part_size(n as usize)),
+            Some(_) => Err("`part_size` must be a positive integer".into()),
+            None => Ok(bucket)
+        }
+    }
+
+    /// Streams `data` to the object `key` in this bucket with the given
+    /// `content_type`, returning the key, entity tag, and size of the stored
+    /// object.
+    ///
+    /// At most one part, as configured via [`part_size()`](#method.part_size),
+    /// is held in memory at a time. If a multipart upload fails midway, it is
+    /// aborted so that the store discards the parts uploaded thus far.
+    pub fn upload(
+        &self,
+        key: &str,
+        data: Data,
+        content_type: Option<&ContentType>
+    ) -> Result<Upload, S3Error> {
+        let mut stream = data.open();
+        let mut part = Vec::with_capacity(self.part_size);
+        let first = fill(&mut stream, &mut part, self.part_size)?;
+        let content_type = content_type.map(|ct| ct.to_string());
+
+        // The whole body fit into one part: upload it with a single request.
+        if first < self.part_size {
+            let response = self.send(Method::Put, key, &[], &part, content_type.as_ref())?;
+            return Ok(Upload {
+                key: key.to_string(),
+                etag: header_value(&response.0, "ETag")?,
+                size: first as u64,
+            });
+        }
+
+        let init = self.send(Method::Post, key, &[("uploads", "")], &[], content_type.as_ref())?;
+        let upload_id = xml_tag(&init.1, "UploadId")
+            .ok_or(S3Error::BadResponse("missing UploadId"))?;
+
+        match self.upload_parts(key, &upload_id, &mut stream, part) {
+            Ok(upload) => Ok(upload),
+            Err(e) => {
+                warn_!("Aborting multipart upload of '{}'.", key);
+                let query = [("uploadId", upload_id.as_str())];
+                if let Err(abort_err) = self.send(Method::Delete, key, &query, &[], None) {
+                    error_!("Failed to abort multipart upload: {}", abort_err);
+                }
+
+                Err(e)
+            }
+        }
+    }
+
+    fn upload_parts<R: Read>(
+        &self,
+        key: &str,
+        upload_id: &str,
+        stream: &mut R,
+        mut part: Vec<u8>
+    ) -> Result<Upload, S3Error> {
+        let mut etags = vec![];
+        let mut size = part.len() as u64;
+        loop {
+            let number = (etags.len() + 1).to_string();
+            let query = [("partNumber", number.as_str()), ("uploadId", upload_id)];
+            let response = self.send(Method::Put, key, &query, &part, None)?;
+            etags.push(header_value(&response.0, "ETag")?);
+
+            part.clear();
+            let n = fill(stream, &mut part, self.part_size)?;
+            if n == 0 {
+                break;
+            }
+
+            size += n as u64;
+        }
+
+        let mut body = String::from("<CompleteMultipartUpload>");
+        for (i, etag) in etags.iter().enumerate() {
+            body.push_str(&format!("<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                                   i + 1, etag));
+        }
+
+        body.push_str("</CompleteMultipartUpload>");
+        let query = [("uploadId", upload_id)];
+        let response = self.send(Method::Post, key, &query, body.as_bytes(), None)?;
+
+        // S3 may report an error in the body of a `200` response.
+        let etag = xml_tag(&response.1, "ETag")
+            .ok_or_else(|| S3Error::Status(200, response.1.clone()))?;
+
+        Ok(Upload { key: key.to_string(), etag: etag.replace("&quot;", "\""), size: size })
+    }
+
+    /// Sends a signed request to the store, returning the response's headers
+    /// and body if the response was successful.
+    fn send(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, &str)],
+        payload: &[u8],
+        content_type: Option<&String>
+    ) -> Result<(Headers, String), S3Error> {
+        let path = format!("/{}/{}", self.name, key);
+        let mut query: Vec<(String, String)> = query.iter()
+            .map(|&(k, v)| (uri_encode(k, true), uri_encode(v, true)))
+            .collect();
+
+        query.sort();
+        let query_string = query.iter()
+            .map(|&(ref k, ref v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let mut url = self.endpoint.clone();
+        url.set_path(&uri_encode(&path, false));
+        url.set_query(if query_string.is_empty() { None } else { Some(&query_string) });
+
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(S3Error::BadResponse("endpoint has no host")),
+        };
+
+        let now = time::now_utc();
+        let amz_date = now.strftime("%Y%m%dT%H%M%SZ").expect("amz date").to_string();
+        let payload_hash = hex(&Sha256::digest(payload));
+
+        let mut signed: Vec<(&str, String)> = vec![
+            ("host", host.clone()),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+
+        if let Some(ct) = content_type {
+            signed.push(("content-type", ct.clone()));
+        }
+
+        signed.sort();
+        let authorization = self.authorization(&method, &uri_encode(&path, false),
+            &query_string, &signed, &payload_hash, &amz_date);
+
+        let mut headers = Headers::new();
+        for &(name, ref value) in &signed {
+            headers.set_raw(name.to_string(), vec![value.as_bytes().to_vec()]);
+        }
+
+        headers.set_raw("Authorization", vec![authorization.into_bytes()]);
+
+        let mut response = self.client.request(method, url)
+            .headers(headers)
+            .body(Body::BufBody(payload, payload.len()))
+            .send()?;
+
+        let mut body = String::new();
+        response.read_to_string(&mut body)?;
+        if !response.status.is_success() {
+            return Err(S3Error::Status(response.status.to_u16(), body));
+        }
+
+        Ok((response.headers.clone(), body))
+    }
+
+    /// Computes the value of the `Authorization` header for a request per AWS
+    /// Signature Version 4. `headers` must be sorted by name.
+    fn authorization(
+        &self,
+        method: &Method,
+        path: &str,
+        query: &str,
+        headers: &[(&str, String)],
+        payload_hash: &str,
+        amz_date: &str
+    ) -> String {
+        let date = &amz_date[..8];
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let signed_headers = headers.iter()
+            .map(|&(name, _)| name)
+            .collect::<Vec<_>>()
+            .join(";");
+
+        let canonical_headers: String = headers.iter()
+            .map(|&(name, ref value)| format!("{}:{}\n", name, value.trim()))
+            .collect();
+
+        let canonical_request = format!("{}\n{}\n{}\n{}\n{}\n{}",
+            method, path, query, canonical_headers, signed_headers, payload_hash);
+
+        let string_to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date, scope, hex(&Sha256::digest(canonical_request.as_bytes())));
+
+        let secret = format!("AWS4{}", self.secret_key);
+        let key = hmac(secret.as_bytes(), date.as_bytes());
+        let key = hmac(&key, self.region.as_bytes());
+        let key = hmac(&key, b"s3");
+        let key = hmac(&key, b"aws4_request");
+        let signature = hex(&hmac(&key, string_to_sign.as_bytes()));
+
+        format!("AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                self.access_key, scope, signed_headers, signature)
+    }
+}
+
+/// Reads from `stream` into `buf` until `buf` contains `max` bytes or the
+/// stream is exhausted. Returns the number of bytes read.
+fn fill<R: Read>(stream: &mut R, buf: &mut Vec<u8>, max: usize) -> io::Result<usize> {
+    let start = buf.len();
+    stream.take((max - start) as u64).read_to_end(buf)?;
+    Ok(buf.len() - start)
+}
+
+fn header_value(headers: &Headers, name: &str) -> Result<String, S3Error> {
+    headers.get_raw(name)
+        .and_then(|values| values.get(0))
+        .and_then(|value| ::std::str::from_utf8(value).ok())
+        .map(|value| value.to_string())
+        .ok_or(S3Error::BadResponse("missing header in response"))
+}
+
+/// Returns the text of the first `<tag>` element in `xml`. The store's
+/// responses are small and flat, so a full XML parser isn't warranted.
+fn xml_tag(xml: &str, tag: &str) -> Option<String> {
+    let (open, close) = (format!("<{}>", tag), format!("</{}>", tag));
+    let start = xml.find(&open)? + open.len();
+    let end = xml[start..].find(&close)? + start;
+    Some(xml[start..end].to_string())
+}
+
+/// Percent-encodes `string` as required by SigV4: every byte except the
+/// unreserved characters is encoded. If `encode_slash` is `false`, `/` is
+/// left as is, as in paths.
+fn uri_encode(string: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(string.len());
+    for byte in string.bytes() {
+        match byte {
+            b'A'...b'Z' | b'a'...b'z' | b'0'...b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new(key).expect("HMAC accepts keys of any size");
+    mac.input(data);
+    mac.result().code().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{uri_encode, xml_tag, hex, hmac};
+
+    #[test]
+    fn test_uri_encode() {
+        assert_eq!(uri_encode("/bucket/a b+c.txt", false), "/bucket/a%20b%2Bc.txt");
+        assert_eq!(uri_encode("a/b", true), "a%2Fb");
+        assert_eq!(uri_encode("AZaz09-_.~", true), "AZaz09-_.~");
+    }
+
+    #[test]
+    fn test_xml_tag() {
+        let xml = "<InitiateMultipartUploadResult><UploadId>abc</UploadId>\
+                   </InitiateMultipartUploadResult>";
+
+        assert_eq!(xml_tag(xml, "UploadId"), Some("abc".into()));
+        assert_eq!(xml_tag(xml, "ETag"), None);
+    }
+
+    #[test]
+    fn test_hmac() {
+        // RFC 4231, test case 2.
+        let mac = hmac(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(hex(&mac),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+    }
+}
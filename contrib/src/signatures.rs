@@ -0,0 +1,598 @@
+//! Verification of HTTP message signatures for service-to-service
+//! authentication.
+//!
+//! This module implements verification of request signatures in the style of
+//! RFC 9421, _HTTP Message Signatures_. A signing client computes a signature
+//! over a set of _covered components_ of the request, such as its method, path,
+//! and selected headers, and sends it in the `Signature` header, along with a
+//! description of what was signed in the `Signature-Input` header:
+//!
+//! ```text
+//! Signature-Input: sig1=("@method" "@path" "content-digest");created=1618884473;keyid="svc-a"
+//! Signature: sig1=:K2qGT5srn2OGbOIDzQ6kYT+ruaycnDAAUpKv+ePFfD0=:
+//! ```
+//!
+//! Unlike mutual TLS, signatures survive TLS termination at a load balancer or
+//! proxy in front of the application.
+//!
+//! # Usage
+//!
+//! To use, add the `http_signatures` feature to the `rocket_contrib`
+//! dependencies section of your `Cargo.toml`:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "*"
+//! default-features = false
+//! features = ["http_signatures"]
+//! ```
+//!
+//! Then attach a [`SignatureVerifier`], which resolves key identifiers to
+//! [`VerificationKey`]s, and add a [`Signed`] request guard to each route that
+//! must only be invoked with a valid signature:
+//!
+//! ```rust,ignore
+//! use rocket_contrib::signatures::{SignatureVerifier, Signed, HmacSha256Key};
+//!
+//! #[post("/internal/jobs", data = "<job>")]
+//! fn enqueue(signed: Signed, job: String) -> String {
+//!     format!("queued by {}", signed.key_id())
+//! }
+//!
+//! fn main() {
+//!     let verifier = SignatureVerifier::new(|key_id| match key_id {
+//!         "svc-a" => Some(HmacSha256Key::new(b"secret shared with svc-a")),
+//!         _ => None
+//!     }).require("content-digest");
+//!
+//!     rocket::ignite()
+//!         .attach(verifier)
+//!         .mount("/", routes![enqueue])
+//!         .launch();
+//! }
+//! ```
+//!
+//! Covering the `content-digest` (or `digest`) header binds the signature to
+//! the body; enable Rocket's `verify_checksums` configuration parameter to
+//! ensure the body matches the digest.
+//!
+//! [`SignatureVerifier`]: /rocket_contrib/signatures/struct.SignatureVerifier.html
+//! [`VerificationKey`]: /rocket_contrib/signatures/trait.VerificationKey.html
+//! [`Signed`]: /rocket_contrib/signatures/struct.Signed.html
+
+extern crate sha2;
+extern crate hmac;
+extern crate base64;
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rocket::{Rocket, Request, State, Outcome};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::request::{self, FromRequest};
+use rocket::http::Status;
+
+use self::sha2::Sha256;
+use self::hmac::{Hmac, Mac};
+
+/// A key that can verify signatures created by a particular signer.
+pub trait VerificationKey: Send + Sync + 'static {
+    /// The name of the algorithm this key verifies, as it appears in the `alg`
+    /// signature parameter, for instance, `hmac-sha256`.
+    fn algorithm(&self) -> &str;
+
+    /// Returns `true` if `signature` is a valid signature of `message`.
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool;
+}
+
+/// A shared secret used to verify `hmac-sha256` signatures.
+pub struct HmacSha256Key(Vec<u8>);
+
+impl HmacSha256Key {
+    /// Returns a new key with the secret `secret`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::signatures::HmacSha256Key;
+    ///
+    /// let key = HmacSha256Key::new(b"a shared secret");
+    /// ```
+    pub fn new(secret: &[u8]) -> HmacSha256Key {
+        HmacSha256Key(secret.to_vec())
+    }
+}
+
+impl VerificationKey for HmacSha256Key {
+    fn algorithm(&self) -> &str {
+        "hmac-sha256"
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        let mut mac = Hmac::<Sha256>::new(&self.0).expect("HMAC accepts keys of any size");
+        mac.input(message);
+        mac.verify(signature).is_ok()
+    }
+}
+
+/// The reason a request's signature couldn't be verified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureError {
+    /// The request has no `Signature` or `Signature-Input` header.
+    Missing,
+    /// The signature headers couldn't be parsed.
+    Malformed,
+    /// The key identified by the `keyid` parameter is unknown.
+    UnknownKey(String),
+    /// The `alg` parameter doesn't match the algorithm of the key.
+    AlgorithmMismatch,
+    /// A required component isn't covered by the signature.
+    Uncovered(String),
+    /// A covered component isn't present in the request or isn't supported.
+    MissingComponent(String),
+    /// The signature's creation time is outside of the allowed clock skew or
+    /// the signature has expired.
+    Expired,
+    /// The signature doesn't match the request.
+    Invalid,
+    /// No `SignatureVerifier` fairing is attached.
+    NotConfigured,
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SignatureError::Missing => write!(f, "missing signature"),
+            SignatureError::Malformed => write!(f, "malformed signature headers"),
+            SignatureError::UnknownKey(ref id) => write!(f, "unknown key '{}'", id),
+            SignatureError::AlgorithmMismatch => write!(f, "signature algorithm mismatch"),
+            SignatureError::Uncovered(ref c) => write!(f, "'{}' is not signed", c),
+            SignatureError::MissingComponent(ref c) => write!(f, "missing component '{}'", c),
+            SignatureError::Expired => write!(f, "signature expired"),
+            SignatureError::Invalid => write!(f, "invalid signature"),
+            SignatureError::NotConfigured => write!(f, "signature verifier not attached"),
+        }
+    }
+}
+
+type KeyResolver = Fn(&str) -> Option<Box<VerificationKey>> + Send + Sync + 'static;
+
+struct Inner {
+    resolver: Box<KeyResolver>,
+    required: Vec<String>,
+    max_skew: u64,
+}
+
+/// A fairing that configures verification of HTTP message signatures.
+///
+/// Attaching a `SignatureVerifier` makes it available to the [`Signed`]
+/// request guard, which performs the verification. By default, only `@method`
+/// and `@path` must be covered by signatures, and signatures may be at most
+/// five minutes old.
+///
+/// [`Signed`]: /rocket_contrib/signatures/struct.Signed.html
+#[derive(Clone)]
+pub struct SignatureVerifier {
+    inner: Arc<Inner>,
+}
+
+impl SignatureVerifier {
+    /// Returns a new `SignatureVerifier` that calls `resolver` with the value
+    /// of a signature's `keyid` parameter to find the key to verify it with.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::signatures::{SignatureVerifier, HmacSha256Key};
+    ///
+    /// let verifier = SignatureVerifier::new(|key_id| match key_id {
+    ///     "svc-a" => Some(HmacSha256Key::new(b"secret")),
+    ///     _ => None
+    /// });
+    /// ```
+    pub fn new<K, F>(resolver: F) -> SignatureVerifier
+        where K: VerificationKey, F: Fn(&str) -> Option<K> + Send + Sync + 'static
+    {
+        let resolver = move |id: &str| {
+            resolver(id).map(|key| Box::new(key) as Box<VerificationKey>)
+        };
+
+        SignatureVerifier {
+            inner: Arc::new(Inner {
+                resolver: Box::new(resolver),
+                required: vec!["@method".into(), "@path".into()],
+                max_skew: 300,
+            })
+        }
+    }
+
+    fn inner_mut(&mut self) -> &mut Inner {
+        Arc::get_mut(&mut self.inner).expect("unshared verifier while building")
+    }
+
+    /// Requires signatures to cover `component`: either a derived component,
+    /// such as `@authority`, or the lowercase name of a header.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::signatures::{SignatureVerifier, HmacSha256Key};
+    ///
+    /// let verifier = SignatureVerifier::new(|_| Some(HmacSha256Key::new(b"secret")))
+    ///     .require("@authority")
+    ///     .require("content-digest");
+    /// ```
+    pub fn require(mut self, component: &str) -> Self {
+        self.inner_mut().required.push(component.to_lowercase());
+        self
+    }
+
+    /// Sets the maximum difference, in seconds, between a signature's `created`
+    /// time and the server's clock.
+    pub fn max_skew(mut self, seconds: u64) -> Self {
+        self.inner_mut().max_skew = seconds;
+        self
+    }
+
+    /// Verifies the signatures of `request`, returning the label and key
+    /// identifier of the first signature that verifies. Each signature
+    /// described in `Signature-Input` is paired with the member of `Signature`
+    /// with the same label. If none verifies, the first error is returned.
+    fn verify(&self, request: &Request) -> Result<Signed, SignatureError> {
+        let headers = request.headers();
+        let (inputs, signatures) = match (headers.get_one("Signature-Input"),
+                                          headers.get_one("Signature")) {
+            (Some(inputs), Some(signatures)) => (inputs, signatures),
+            _ => return Err(SignatureError::Missing)
+        };
+
+        let signatures = dictionary(signatures);
+        let mut error = None;
+        for (label, raw_params) in dictionary(inputs) {
+            let signature = signatures.iter()
+                .find(|&&(l, _)| l == label)
+                .map(|&(_, value)| value);
+
+            match self.verify_signature(request, label, raw_params, signature) {
+                Ok(signed) => return Ok(signed),
+                Err(e) => error = error.or(Some(e)),
+            }
+        }
+
+        Err(error.unwrap_or(SignatureError::Malformed))
+    }
+
+    /// Verifies the signature labeled `label` with the parameters `raw_params`
+    /// and the raw value `signature` from the `Signature` header, if any.
+    fn verify_signature(
+        &self,
+        request: &Request,
+        label: &str,
+        raw_params: &str,
+        signature: Option<&str>
+    ) -> Result<Signed, SignatureError> {
+        let signature = signature
+            .and_then(|value| {
+                let value = value.trim();
+                if value.len() >= 2 && value.starts_with(':') && value.ends_with(':') {
+                    base64::decode(&value[1..value.len() - 1]).ok()
+                } else {
+                    None
+                }
+            })
+            .ok_or(SignatureError::Malformed)?;
+
+        let input = SignatureInput::parse(raw_params).ok_or(SignatureError::Malformed)?;
+        for required in &self.inner.required {
+            if !input.components.contains(required) {
+                return Err(SignatureError::Uncovered(required.clone()));
+            }
+        }
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+
+        // `created` is chosen by the client: guard against overflow.
+        let created = input.created.ok_or(SignatureError::Malformed)?;
+        let skew = now.checked_sub(created).and_then(|skew| skew.checked_abs());
+        if skew.map_or(true, |skew| skew as u64 > self.inner.max_skew) {
+            return Err(SignatureError::Expired);
+        }
+
+        if input.expires.map_or(false, |expires| now > expires) {
+            return Err(SignatureError::Expired);
+        }
+
+        let key_id = input.key_id.clone().ok_or(SignatureError::Malformed)?;
+        let key = (self.inner.resolver)(&key_id)
+            .ok_or_else(|| SignatureError::UnknownKey(key_id.clone()))?;
+
+        if input.algorithm.as_ref().map_or(false, |alg| alg != key.algorithm()) {
+            return Err(SignatureError::AlgorithmMismatch);
+        }
+
+        let mut base = String::new();
+        for component in &input.components {
+            let value = component_value(request, component)
+                .ok_or_else(|| SignatureError::MissingComponent(component.clone()))?;
+
+            base.push_str(&format!("\"{}\": {}\n", component, value));
+        }
+
+        base.push_str(&format!("\"@signature-params\": {}", raw_params.trim()));
+        if !key.verify(base.as_bytes(), &signature) {
+            return Err(SignatureError::Invalid);
+        }
+
+        Ok(Signed { label: label.to_string(), key_id: key_id })
+    }
+}
+
+impl Fairing for SignatureVerifier {
+    fn info(&self) -> Info {
+        Info { name: "HTTP Signature Verifier", kind: Kind::Attach }
+    }
+
+    fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        Ok(rocket.manage(self.clone()))
+    }
+}
+
+/// Request guard for requests carrying a valid HTTP message signature.
+///
+/// The guard fails with `401 Unauthorized` and a [`SignatureError`] if the
+/// request's signature is missing or doesn't verify, and with `500 Internal
+/// Server Error` if no [`SignatureVerifier`] is attached.
+///
+/// [`SignatureError`]: /rocket_contrib/signatures/enum.SignatureError.html
+/// [`SignatureVerifier`]: /rocket_contrib/signatures/struct.SignatureVerifier.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signed {
+    label: String,
+    key_id: String,
+}
+
+impl Signed {
+    /// The label of the verified signature, for instance, `sig1`.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// The identifier of the key that verified the signature, and thus the
+    /// identity of the signer.
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for Signed {
+    type Error = SignatureError;
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        let verifier = match request.guard::<State<SignatureVerifier>>() {
+            Outcome::Success(verifier) => verifier,
+            _ => {
+                error_!("`Signed` guard used without attaching `SignatureVerifier`.");
+                return Outcome::Failure((Status::InternalServerError,
+                                         SignatureError::NotConfigured));
+            }
+        };
+
+        match verifier.verify(request) {
+            Ok(signed) => Outcome::Success(signed),
+            Err(e) => {
+                warn_!("Rejecting request signature: {}.", e);
+                Outcome::Failure((Status::Unauthorized, e))
+            }
+        }
+    }
+}
+
+/// The parsed parameters of a signature in `Signature-Input`.
+#[derive(Debug, Default, PartialEq)]
+struct SignatureInput {
+    components: Vec<String>,
+    created: Option<i64>,
+    expires: Option<i64>,
+    key_id: Option<String>,
+    algorithm: Option<String>,
+}
+
+impl SignatureInput {
+    /// Parses an inner list with parameters: `("@method" "@path");created=1`.
+    fn parse(raw: &str) -> Option<SignatureInput> {
+        let raw = raw.trim();
+        if !raw.starts_with('(') {
+            return None;
+        }
+
+        let end = raw.find(')')?;
+        let mut input = SignatureInput::default();
+        for item in raw[1..end].split_whitespace() {
+            if item.len() < 2 || !item.starts_with('"') || !item.ends_with('"') {
+                return None;
+            }
+
+            input.components.push(item[1..item.len() - 1].to_lowercase());
+        }
+
+        for param in raw[end + 1..].split(';').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+            let mut parts = param.splitn(2, '=');
+            let (name, value) = (parts.next()?, parts.next()?.trim_matches('"'));
+            match name {
+                "created" => input.created = Some(value.parse().ok()?),
+                "expires" => input.expires = Some(value.parse().ok()?),
+                "keyid" => input.key_id = Some(value.to_string()),
+                "alg" => input.algorithm = Some(value.to_string()),
+                _ => { /* unknown parameters are signed but otherwise ignored */ }
+            }
+        }
+
+        Some(input)
+    }
+}
+
+/// Splits a structured field dictionary into `(key, raw value)` pairs. Commas
+/// within parentheses or quotes don't separate members.
+fn dictionary(value: &str) -> Vec<(&str, &str)> {
+    fn member(raw: &str) -> Option<(&str, &str)> {
+        let mut parts = raw.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) => Some((key.trim(), value)),
+            _ => None
+        }
+    }
+
+    let mut members = vec![];
+    let (mut depth, mut quoted, mut start) = (0, false, 0);
+    for (i, c) in value.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted => depth -= 1,
+            ',' if !quoted && depth == 0 => {
+                members.extend(member(&value[start..i]));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    members.extend(member(&value[start..]));
+    members
+}
+
+/// Returns the canonical value of the covered component `name` of `request`.
+fn component_value(request: &Request, name: &str) -> Option<String> {
+    let uri = request.uri();
+    match name {
+        "@method" => Some(request.method().as_str().to_string()),
+        "@path" => Some(uri.path().to_string()),
+        "@query" => Some(format!("?{}", uri.query().unwrap_or(""))),
+        "@request-target" => Some(uri.as_str().to_string()),
+        "@authority" => request.headers().get_one("Host").map(|h| h.to_lowercase()),
+        _ if name.starts_with('@') => None,
+        _ => {
+            let values: Vec<_> = request.headers().get(name).map(|v| v.trim()).collect();
+            if values.is_empty() { None } else { Some(values.join(", ")) }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use super::{dictionary, SignatureInput, SignatureVerifier, SignatureError, HmacSha256Key};
+    use super::sha2::Sha256;
+    use super::hmac::{Hmac, Mac};
+    use super::base64;
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::Header;
+
+    const SECRET: &[u8] = b"secret shared with svc-a";
+
+    fn verifier() -> SignatureVerifier {
+        SignatureVerifier::new(|key_id| match key_id {
+            "svc-a" => Some(HmacSha256Key::new(SECRET)),
+            _ => None
+        }).require("x-job")
+    }
+
+    fn now() -> i64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64
+    }
+
+    /// Returns the `Signature-Input` and `Signature` members labeled `label`
+    /// for a `POST /jobs` request with `X-Job: 7`, created at `created`.
+    fn sign(label: &str, key_id: &str, created: i64) -> (String, String) {
+        let params = format!("(\"@method\" \"@path\" \"x-job\");created={};keyid=\"{}\"",
+                             created, key_id);
+
+        let base = format!("\"@method\": POST\n\"@path\": /jobs\n\"x-job\": 7\n\
+                            \"@signature-params\": {}", params);
+
+        let mut mac = Hmac::<Sha256>::new(SECRET).unwrap();
+        mac.input(base.as_bytes());
+        let signature = base64::encode(&mac.result().code());
+        (format!("{}={}", label, params), format!("{}=:{}:", label, signature))
+    }
+
+    fn verify(job: &str, input: &str, signature: &str) -> Result<String, SignatureError> {
+        let client = Client::new(rocket::ignite()).unwrap();
+        let request = client.post("/jobs")
+            .header(Header::new("X-Job", job.to_string()))
+            .header(Header::new("Signature-Input", input.to_string()))
+            .header(Header::new("Signature", signature.to_string()));
+
+        verifier().verify(request.inner()).map(|signed| signed.label().to_string())
+    }
+
+    #[test]
+    fn test_valid_signature() {
+        let (input, signature) = sign("sig1", "svc-a", now());
+        assert_eq!(verify("7", &input, &signature), Ok("sig1".into()));
+    }
+
+    #[test]
+    fn test_tampered_component() {
+        let (input, signature) = sign("sig1", "svc-a", now());
+        assert_eq!(verify("8", &input, &signature), Err(SignatureError::Invalid));
+    }
+
+    #[test]
+    fn test_created_out_of_range() {
+        let (input, signature) = sign("sig1", "svc-a", now() - 3600);
+        assert_eq!(verify("7", &input, &signature), Err(SignatureError::Expired));
+
+        let (input, signature) = sign("sig1", "svc-a", now() + 3600);
+        assert_eq!(verify("7", &input, &signature), Err(SignatureError::Expired));
+
+        for &created in &[i64::min_value(), i64::max_value()] {
+            let (input, signature) = sign("sig1", "svc-a", created);
+            assert_eq!(verify("7", &input, &signature), Err(SignatureError::Expired));
+        }
+    }
+
+    #[test]
+    fn test_unknown_key() {
+        let (input, signature) = sign("sig1", "svc-b", now());
+        let expected = Err(SignatureError::UnknownKey("svc-b".into()));
+        assert_eq!(verify("7", &input, &signature), expected);
+    }
+
+    #[test]
+    fn test_signatures_are_matched_by_label() {
+        let (bad_input, bad_signature) = sign("sig1", "svc-b", now());
+        let (input, signature) = sign("sig2", "svc-a", now());
+        let inputs = format!("{}, {}", bad_input, input);
+        let signatures = format!("{}, {}", signature, bad_signature);
+        assert_eq!(verify("7", &inputs, &signatures), Ok("sig2".into()));
+
+        let (input, _) = sign("sig1", "svc-a", now());
+        let (_, signature) = sign("sig2", "svc-a", now());
+        assert_eq!(verify("7", &input, &signature), Err(SignatureError::Malformed));
+    }
+
+    #[test]
+    fn test_dictionary() {
+        let members = dictionary("a=(\"x\" \"y\");k=\"1,2\", b=:AAA=:");
+        assert_eq!(members, vec![("a", "(\"x\" \"y\");k=\"1,2\""), ("b", ":AAA=:")]);
+    }
+
+    #[test]
+    fn test_signature_input() {
+        let raw = "(\"@method\" \"Content-Digest\");created=1618884473;keyid=\"k\";alg=\"hmac-sha256\"";
+        let input = SignatureInput::parse(raw).unwrap();
+        assert_eq!(input.components, vec!["@method", "content-digest"]);
+        assert_eq!(input.created, Some(1618884473));
+        assert_eq!(input.key_id, Some("k".into()));
+        assert_eq!(input.algorithm, Some("hmac-sha256".into()));
+
+        assert!(SignatureInput::parse("\"@method\";created=1").is_none());
+        assert!(SignatureInput::parse("(\"@method\");created=soon").is_none());
+    }
+}
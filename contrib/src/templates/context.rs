@@ -1,10 +1,12 @@
 use std::path::{Path, PathBuf};
 use std::collections::HashMap;
+use std::sync::{Mutex, RwLock, RwLockReadGuard};
 
 use super::{Engines, TemplateInfo};
 use super::glob;
 
 use rocket::http::ContentType;
+use rocket::dev::ReloadHandle;
 
 pub struct Context {
     /// The root of the template directory.
@@ -51,6 +53,56 @@ impl Context {
     }
 }
 
+/// Manages the template context, reloading it from its root when a
+/// `DevReload` fairing observes a change.
+pub struct ContextManager {
+    context: RwLock<Context>,
+    /// The reload generation the context was loaded at.
+    generation: Mutex<u64>,
+    customize: Box<Fn(&mut Engines) + Send + Sync>,
+}
+
+impl ContextManager {
+    pub fn new<F>(context: Context, customize: F) -> ContextManager
+        where F: Fn(&mut Engines) + Send + Sync + 'static
+    {
+        ContextManager {
+            context: RwLock::new(context),
+            generation: Mutex::new(0),
+            customize: Box::new(customize),
+        }
+    }
+
+    /// Returns the current context, first reloading it if `reload` has
+    /// observed a change since it was loaded.
+    pub fn context(&self, reload: Option<&ReloadHandle>) -> RwLockReadGuard<Context> {
+        if let Some(handle) = reload {
+            let mut generation = self.generation.lock().expect("template generation lock");
+            let current = handle.generation();
+            if *generation != current {
+                *generation = current;
+                self.reload();
+            }
+        }
+
+        self.context.read().expect("template context lock")
+    }
+
+    /// Reloads the context. If the templates fail to load, the existing
+    /// context is kept.
+    fn reload(&self) {
+        let root = self.context.read().expect("template context lock").root.clone();
+        match Context::initialize(root) {
+            Some(mut context) => {
+                (self.customize)(&mut context.engines);
+                *self.context.write().expect("template context lock") = context;
+                info_!("Reloaded templates.");
+            }
+            None => warn_!("Failed to reload templates: using the previous templates."),
+        }
+    }
+}
+
 /// Removes the file path's extension or does nothing if there is none.
 fn remove_extension<P: AsRef<Path>>(path: P) -> PathBuf {
     let path = path.as_ref();
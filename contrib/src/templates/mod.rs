@@ -10,13 +10,14 @@ mod context;
 pub use self::engine::Engines;
 
 use self::engine::Engine;
-use self::context::Context;
+use self::context::{Context, ContextManager};
 use self::serde::Serialize;
-use self::serde_json::{Value, to_value};
+use self::serde_json::{Value, Map, to_value};
 use self::glob::glob;
 
 use std::borrow::Cow;
 use std::path::PathBuf;
+use std::sync::Mutex;
 
 use rocket::{Rocket, State};
 use rocket::request::Request;
@@ -24,6 +25,7 @@ use rocket::fairing::{Fairing, AdHoc};
 use rocket::response::{self, Content, Responder};
 use rocket::http::{ContentType, Status};
 use rocket::config::ConfigError;
+use rocket::dev::ReloadHandle;
 
 const DEFAULT_TEMPLATE_DIR: &'static str = "templates";
 
@@ -68,6 +70,12 @@ const DEFAULT_TEMPLATE_DIR: &'static str = "templates";
 /// [attached](/rocket/struct.Rocket.html#method.attach) to the running Rocket
 /// instance. Failure to do so will result in an error.
 ///
+/// Templates are discovered once, when the fairing is attached. During
+/// development, attach a [`DevReload`] fairing as well: templates are then
+/// discovered again on the first render after the template directory changes.
+///
+/// [`DevReload`]: /rocket/dev/struct.DevReload.html
+///
 /// Templates are rendered with the `render` method. The method takes in the
 /// name of a template and a context to render the template with. The context
 /// can be any type that implements `Serialize` from
@@ -140,6 +148,9 @@ pub struct TemplateInfo {
     data_type: ContentType
 }
 
+/// Values injected into the template context of a request's templates.
+struct Injected(Mutex<Map<String, Value>>);
+
 impl Template {
     /// Returns a fairing that intializes and maintains templating state.
     ///
@@ -218,7 +229,7 @@ impl Template {
             match Context::initialize(template_root) {
                 Some(mut ctxt) => {
                     f(&mut ctxt.engines);
-                    Ok(rocket.manage(ctxt))
+                    Ok(rocket.manage(ContextManager::new(ctxt, f)))
                 }
                 None => Err(rocket)
             }
@@ -248,6 +259,71 @@ impl Template {
         Template { name: name.into(), value: to_value(context).ok() }
     }
 
+    /// Contributes the value `value` under the key `key` to the template
+    /// context of every `Template` rendered in response to `request`.
+    ///
+    /// This allows request guards and request fairings to provide values that
+    /// most templates need, such as the current user, a CSRF token, or flash
+    /// messages, without every handler having to add them to its context
+    /// explicitly. When a `Template` responds, injected values are merged into
+    /// its context if the context is a map or a `struct`; values set by the
+    /// handler take precedence over injected values with the same key.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// use rocket::fairing::AdHoc;
+    /// use rocket_contrib::Template;
+    ///
+    /// fn main() {
+    ///     rocket::ignite()
+    ///         .attach(Template::fairing())
+    ///         .attach(AdHoc::on_request(|request, _| {
+    ///             let path = request.uri().path().to_string();
+    ///             Template::inject(request, "current_path", path);
+    ///         }));
+    ///         // ...
+    /// }
+    /// ```
+    pub fn inject<S: Into<String>, V: Serialize>(request: &Request, key: S, value: V) {
+        let (key, value) = (key.into(), to_value(value));
+        match value {
+            Ok(value) => {
+                let injected = request.local_cache(|| Injected(Mutex::new(Map::new())));
+                injected.0.lock().expect("injected context lock").insert(key, value);
+            }
+            Err(e) => warn_!("Injected template value '{}' failed to serialize: {}", key, e)
+        }
+    }
+
+    /// Merges the values injected for `request` into this template's context,
+    /// without overwriting values set by the handler.
+    fn merge_injected(&mut self, request: &Request) {
+        let injected = request.local_cache(|| Injected(Mutex::new(Map::new())));
+        let injected = injected.0.lock().expect("injected context lock");
+        if injected.is_empty() {
+            return;
+        }
+
+        // A unit context, `()`, serializes to `null`: treat it as empty.
+        if self.value == Some(Value::Null) {
+            self.value = Some(Value::Object(Map::new()));
+        }
+
+        match self.value {
+            Some(Value::Object(ref mut map)) => {
+                for (key, value) in injected.iter() {
+                    if !map.contains_key(key) {
+                        map.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+            _ => warn_!("Template context isn't a map: ignoring injected values."),
+        }
+    }
+
     /// Render the template named `name` with the context `context` into a
     /// `String`. This method should **not** be used in any running Rocket
     /// application. This method should only be used during testing to
@@ -287,8 +363,8 @@ impl Template {
     pub fn show<S, C>(rocket: &Rocket, name: S, context: C) -> Option<String>
         where S: Into<Cow<'static, str>>, C: Serialize
     {
-        let ctxt = match rocket.state::<Context>() {
-            Some(ctxt) => ctxt,
+        let manager = match rocket.state::<ContextManager>() {
+            Some(manager) => manager,
             None => {
                 warn!("Uninitialized template context: missing fairing.");
                 info!("To use templates, you must attach `Template::fairing()`.");
@@ -296,6 +372,7 @@ impl Template {
                 return None;
             }
         };
+        let ctxt = manager.context(rocket.state::<ReloadHandle>());
         Template::render(name, context).finalize(&ctxt).ok().map(|v| v.0)
     }
 
@@ -328,14 +405,17 @@ impl Template {
 /// extension and a fixed-size body containing the rendered template. If
 /// rendering fails, an `Err` of `Status::InternalServerError` is returned.
 impl Responder<'static> for Template {
-    fn respond_to(self, req: &Request) -> response::Result<'static> {
-        let ctxt = req.guard::<State<Context>>().succeeded().ok_or_else(|| {
+    fn respond_to(mut self, req: &Request) -> response::Result<'static> {
+        let manager = req.guard::<State<ContextManager>>().succeeded().ok_or_else(|| {
             error_!("Uninitialized template context: missing fairing.");
             info_!("To use templates, you must attach `Template::fairing()`.");
             info_!("See the `Template` documentation for more information.");
             Status::InternalServerError
         })?;
 
+        self.merge_injected(req);
+        let reload = req.guard::<State<ReloadHandle>>().succeeded();
+        let ctxt = manager.context(reload.as_ref().map(|handle| handle.inner()));
         let (render, content_type) = self.finalize(&ctxt)?;
         Content(content_type, render).respond_to(req)
     }
@@ -0,0 +1,503 @@
+//! A kit for accepting large uploads in numbered, individually verified
+//! chunks.
+//!
+//! Clients on flaky connections, mobile clients in particular, often can't
+//! complete a large upload in a single request. With chunked uploads, a client
+//! instead:
+//!
+//!   1. creates an upload and receives its [`UploadId`],
+//!   2. sends the file in numbered chunks, each with an optional `Digest`
+//!      header, retrying individual chunks as necessary,
+//!   3. queries the upload's [`UploadStatus`] after reconnecting to learn which
+//!      chunks are still missing, and
+//!   4. finalizes the upload, which concatenates the spooled chunks into a
+//!      single file.
+//!
+//! The [`ChunkedUploads`] managed state spools chunks to a directory on disk;
+//! the application mounts its own routes and calls into it. The [`UploadId`]
+//! parameter type and the [`ChunkDigest`] request guard parse the pieces of a
+//! chunk request, and [`UploadError`] responds with the appropriate status.
+//!
+//! # Usage
+//!
+//! To use, add the `chunked_uploads` feature to the `rocket_contrib`
+//! dependencies section of your `Cargo.toml`:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "*"
+//! default-features = false
+//! features = ["chunked_uploads"]
+//! ```
+//!
+//! Then manage a `ChunkedUploads` and mount routes that use it:
+//!
+//! ```rust,ignore
+//! use rocket::{State, Data};
+//! use rocket_contrib::uploads::*;
+//!
+//! #[post("/uploads")]
+//! fn create(uploads: State<ChunkedUploads>) -> Result<String, UploadError> {
+//!     uploads.create().map(|id| id.to_string())
+//! }
+//!
+//! #[put("/uploads/<id>/<index>", data = "<chunk>")]
+//! fn chunk(
+//!     id: UploadId, index: u32, digest: Option<ChunkDigest>,
+//!     chunk: Data, uploads: State<ChunkedUploads>
+//! ) -> Result<(), UploadError> {
+//!     uploads.write_chunk(&id, index, chunk, digest.as_ref()).map(|_| ())
+//! }
+//!
+//! #[get("/uploads/<id>")]
+//! fn status(id: UploadId, uploads: State<ChunkedUploads>) -> Result<UploadStatus, UploadError> {
+//!     uploads.status(&id)
+//! }
+//!
+//! #[post("/uploads/<id>/finalize?<total>")]
+//! fn finalize(id: UploadId, total: u32, uploads: State<ChunkedUploads>) -> Result<String, UploadError> {
+//!     let upload = uploads.finalize(&id, total)?;
+//!     upload.persist_to(format!("/srv/files/{}", id))?;
+//!     Ok(upload.digest().to_string())
+//! }
+//!
+//! fn main() {
+//!     rocket::ignite()
+//!         .manage(ChunkedUploads::new("/tmp/uploads"))
+//!         .mount("/", routes![create, chunk, status, finalize])
+//!         .launch();
+//! }
+//! ```
+//!
+//! [`UploadId`]: /rocket_contrib/uploads/struct.UploadId.html
+//! [`UploadStatus`]: /rocket_contrib/uploads/struct.UploadStatus.html
+//! [`ChunkedUploads`]: /rocket_contrib/uploads/struct.ChunkedUploads.html
+//! [`ChunkDigest`]: /rocket_contrib/uploads/struct.ChunkDigest.html
+//! [`UploadError`]: /rocket_contrib/uploads/enum.UploadError.html
+
+extern crate sha2;
+extern crate base64;
+
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read, Write, Cursor};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rocket::{Request, Data, Outcome, Response};
+use rocket::error::ErrorCode;
+use rocket::request::{self, FromRequest, FromParam};
+use rocket::response::{self, Responder};
+use rocket::http::{Status, RawStr};
+use rocket::http::uncased::uncased_eq;
+
+use self::sha2::{Sha256, Digest};
+
+/// The length, in hex digits, of an upload identifier.
+const ID_LEN: usize = 32;
+
+/// A counter mixed into upload identifiers to keep them unique.
+static UPLOAD_COUNTER: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// The identifier of a chunked upload: 32 lowercase hex digits.
+///
+/// `UploadId` implements [`FromParam`], rejecting any parameter that isn't a
+/// well-formed identifier, so it is always safe to use in a path.
+///
+/// [`FromParam`]: /rocket/request/trait.FromParam.html
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UploadId(String);
+
+impl UploadId {
+    fn generate() -> UploadId {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let mut hasher = Sha256::default();
+        hasher.input(format!("{}.{}", now.as_secs(), now.subsec_nanos()).as_bytes());
+        hasher.input(format!(".{}", ::std::process::id()).as_bytes());
+        hasher.input(format!(".{}", UPLOAD_COUNTER.fetch_add(1, Ordering::SeqCst)).as_bytes());
+
+        let hex: String = hasher.result().iter().map(|b| format!("{:02x}", b)).collect();
+        UploadId(hex[..ID_LEN].to_string())
+    }
+
+    /// Returns the identifier as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for UploadId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<'a> FromParam<'a> for UploadId {
+    type Error = &'a RawStr;
+
+    fn from_param(param: &'a RawStr) -> Result<UploadId, &'a RawStr> {
+        let valid = param.len() == ID_LEN
+            && param.chars().all(|c| c.is_digit(16) && !c.is_uppercase());
+
+        match valid {
+            true => Ok(UploadId(param.to_string())),
+            false => Err(param)
+        }
+    }
+}
+
+/// Request guard for the SHA-256 digest of a chunk, sent in a `Digest:
+/// SHA-256=<base64>` header.
+///
+/// The guard forwards if the request has no SHA-256 digest, so it is typically
+/// used as `Option<ChunkDigest>`, and fails with `400 Bad Request` if the
+/// digest is malformed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkDigest(Vec<u8>);
+
+impl<'a, 'r> FromRequest<'a, 'r> for ChunkDigest {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        for value in request.headers().get("Digest") {
+            for instance in value.split(',') {
+                let mut parts = instance.trim().splitn(2, '=');
+                match (parts.next(), parts.next()) {
+                    (Some(name), Some(encoded)) if uncased_eq(name.trim(), "SHA-256") => {
+                        return match base64::decode(encoded.trim()) {
+                            Ok(ref bytes) if bytes.len() == 32 => {
+                                Outcome::Success(ChunkDigest(bytes.clone()))
+                            }
+                            _ => Outcome::Failure((Status::BadRequest, ()))
+                        };
+                    }
+                    _ => continue
+                }
+            }
+        }
+
+        Outcome::Forward(())
+    }
+}
+
+/// An error that occurred while handling a chunked upload.
+///
+/// `UploadError` implements `Responder`, failing with a status that reflects
+/// the error.
+#[derive(Debug)]
+pub enum UploadError {
+    /// There is no upload with the given identifier. Responds with `404 Not
+    /// Found`.
+    NotFound,
+    /// The chunk is larger than the maximum chunk size. Responds with `413
+    /// Payload Too Large`.
+    TooLarge,
+    /// The chunk doesn't match its digest. Responds with `422 Unprocessable
+    /// Entity`.
+    DigestMismatch,
+    /// The upload can't be finalized because the listed chunks are missing.
+    /// Responds with `409 Conflict`.
+    Incomplete(Vec<u32>),
+    /// An I/O error occurred. Responds with `500 Internal Server Error`.
+    Io(io::Error),
+}
+
+impl From<io::Error> for UploadError {
+    fn from(error: io::Error) -> UploadError {
+        UploadError::Io(error)
+    }
+}
+
+impl fmt::Display for UploadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            UploadError::NotFound => write!(f, "unknown upload"),
+            UploadError::TooLarge => write!(f, "chunk is too large"),
+            UploadError::DigestMismatch => write!(f, "chunk does not match its digest"),
+            UploadError::Incomplete(ref missing) => {
+                write!(f, "{} chunk(s) missing", missing.len())
+            }
+            UploadError::Io(ref e) => write!(f, "i/o error: {}", e),
+        }
+    }
+}
+
+impl ErrorCode for UploadError {
+    fn code(&self) -> &'static str {
+        match *self {
+            UploadError::NotFound => "upload.not_found",
+            UploadError::TooLarge => "upload.too_large",
+            UploadError::DigestMismatch => "upload.digest_mismatch",
+            UploadError::Incomplete(_) => "upload.incomplete",
+            UploadError::Io(_) => "upload.io",
+        }
+    }
+
+    fn params(&self) -> Vec<(&'static str, String)> {
+        match *self {
+            UploadError::Incomplete(ref missing) => {
+                let list = missing.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+                vec![("count", missing.len().to_string()), ("missing", list.join(", "))]
+            }
+            UploadError::Io(ref e) => vec![("error", e.to_string())],
+            _ => vec![]
+        }
+    }
+
+    fn english(&self) -> &'static str {
+        match *self {
+            UploadError::NotFound => "The upload does not exist.",
+            UploadError::TooLarge => "The chunk is too large.",
+            UploadError::DigestMismatch => "The chunk does not match its digest.",
+            UploadError::Incomplete(_) => "The upload is missing {count} chunk(s): {missing}.",
+            UploadError::Io(_) => "The upload could not be stored.",
+        }
+    }
+}
+
+impl<'r> Responder<'r> for UploadError {
+    fn respond_to(self, _: &Request) -> response::Result<'r> {
+        warn_!("Chunked upload failed: {}.", self);
+        Err(match self {
+            UploadError::NotFound => Status::NotFound,
+            UploadError::TooLarge => Status::PayloadTooLarge,
+            UploadError::DigestMismatch => Status::UnprocessableEntity,
+            UploadError::Incomplete(_) => Status::Conflict,
+            UploadError::Io(_) => Status::InternalServerError,
+        })
+    }
+}
+
+/// The chunks of an upload received so far.
+///
+/// As a `Responder`, an `UploadStatus` responds with the comma-separated list
+/// of received chunk indices as `text/plain` and the total number of bytes
+/// received in the `Upload-Received-Bytes` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadStatus {
+    /// The indices of the chunks received, in ascending order.
+    pub chunks: Vec<u32>,
+    /// The total size of the chunks received, in bytes.
+    pub bytes: u64,
+}
+
+impl<'r> Responder<'r> for UploadStatus {
+    fn respond_to(self, _: &Request) -> response::Result<'r> {
+        let list = self.chunks.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+        Response::build()
+            .raw_header("Content-Type", "text/plain; charset=utf-8")
+            .raw_header("Upload-Received-Bytes", self.bytes.to_string())
+            .sized_body(Cursor::new(list.join(",")))
+            .ok()
+    }
+}
+
+/// A finalized upload: the concatenation of all of its chunks, spooled to a
+/// file.
+#[derive(Debug)]
+pub struct CompletedUpload {
+    path: PathBuf,
+    size: u64,
+    digest: String,
+}
+
+impl CompletedUpload {
+    /// The path to the spooled file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The size of the upload in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// The SHA-256 digest of the upload, as it would appear in a `Digest`
+    /// header: `SHA-256=<base64>`.
+    pub fn digest(&self) -> &str {
+        &self.digest
+    }
+
+    /// Moves the spooled file to `path`, which must be on the same filesystem
+    /// as the spool directory.
+    pub fn persist_to<P: AsRef<Path>>(self, path: P) -> io::Result<()> {
+        fs::rename(&self.path, path)
+    }
+}
+
+/// Managed state that spools the chunks of uploads to disk and assembles
+/// them.
+pub struct ChunkedUploads {
+    dir: PathBuf,
+    max_chunk_size: u64,
+}
+
+impl ChunkedUploads {
+    /// Returns a `ChunkedUploads` that spools uploads to `dir`, which is
+    /// created if it does not exist, and accepts chunks of up to 8MiB.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::uploads::ChunkedUploads;
+    ///
+    /// let uploads = ChunkedUploads::new("/tmp/uploads");
+    /// ```
+    pub fn new<P: AsRef<Path>>(dir: P) -> ChunkedUploads {
+        ChunkedUploads { dir: dir.as_ref().to_path_buf(), max_chunk_size: 8 * 1024 * 1024 }
+    }
+
+    /// Sets the maximum size of a chunk to `size` bytes.
+    pub fn max_chunk_size(mut self, size: u64) -> Self {
+        self.max_chunk_size = size;
+        self
+    }
+
+    fn upload_dir(&self, id: &UploadId) -> Result<PathBuf, UploadError> {
+        let dir = self.dir.join(id.as_str());
+        match dir.is_dir() {
+            true => Ok(dir),
+            false => Err(UploadError::NotFound)
+        }
+    }
+
+    /// Creates a new upload and returns its identifier.
+    pub fn create(&self) -> Result<UploadId, UploadError> {
+        let id = UploadId::generate();
+        fs::create_dir_all(self.dir.join(id.as_str()))?;
+        Ok(id)
+    }
+
+    /// Spools `data` as the chunk with index `index` of the upload `id`,
+    /// replacing any previously received chunk with the same index. If
+    /// `digest` is `Some`, the chunk must match it. Returns the size of the
+    /// chunk.
+    pub fn write_chunk(
+        &self,
+        id: &UploadId,
+        index: u32,
+        data: Data,
+        digest: Option<&ChunkDigest>
+    ) -> Result<u64, UploadError> {
+        let dir = self.upload_dir(id)?;
+        let partial = dir.join(format!("{}.part", index));
+        let (size, computed) = {
+            let mut file = File::create(&partial)?;
+            let mut stream = data.open().take(self.max_chunk_size + 1);
+            let mut hasher = Sha256::default();
+            let mut buffer = [0u8; 8192];
+            let mut size = 0u64;
+            loop {
+                let n = stream.read(&mut buffer)?;
+                if n == 0 {
+                    break;
+                }
+
+                hasher.input(&buffer[..n]);
+                file.write_all(&buffer[..n])?;
+                size += n as u64;
+            }
+
+            (size, hasher.result().to_vec())
+        };
+
+        let result = if size > self.max_chunk_size {
+            Err(UploadError::TooLarge)
+        } else if digest.map_or(false, |digest| digest.0 != computed) {
+            Err(UploadError::DigestMismatch)
+        } else {
+            fs::rename(&partial, dir.join(format!("{}.chunk", index)))?;
+            return Ok(size);
+        };
+
+        fs::remove_file(&partial)?;
+        result
+    }
+
+    /// Returns the chunks of the upload `id` received so far.
+    pub fn status(&self, id: &UploadId) -> Result<UploadStatus, UploadError> {
+        let mut status = UploadStatus { chunks: vec![], bytes: 0 };
+        for entry in fs::read_dir(self.upload_dir(id)?)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let index = name.to_str()
+                .and_then(|name| match name.ends_with(".chunk") {
+                    true => name[..name.len() - ".chunk".len()].parse().ok(),
+                    false => None
+                });
+
+            if let Some(index) = index {
+                status.chunks.push(index);
+                status.bytes += entry.metadata()?.len();
+            }
+        }
+
+        status.chunks.sort();
+        Ok(status)
+    }
+
+    /// Finalizes the upload `id`, which must consist of the chunks `0` through
+    /// `total - 1`, by concatenating its chunks into a single file.
+    pub fn finalize(&self, id: &UploadId, total: u32) -> Result<CompletedUpload, UploadError> {
+        let dir = self.upload_dir(id)?;
+        let missing: Vec<u32> = (0..total)
+            .filter(|i| !dir.join(format!("{}.chunk", i)).is_file())
+            .collect();
+
+        if !missing.is_empty() {
+            return Err(UploadError::Incomplete(missing));
+        }
+
+        let path = self.dir.join(format!("{}.complete", id));
+        let mut output = File::create(&path)?;
+        let mut hasher = Sha256::default();
+        let mut size = 0;
+        let mut buffer = [0u8; 8192];
+        for i in 0..total {
+            let mut chunk = File::open(dir.join(format!("{}.chunk", i)))?;
+            loop {
+                let n = chunk.read(&mut buffer)?;
+                if n == 0 {
+                    break;
+                }
+
+                hasher.input(&buffer[..n]);
+                output.write_all(&buffer[..n])?;
+                size += n as u64;
+            }
+        }
+
+        output.sync_all()?;
+        fs::remove_dir_all(&dir)?;
+        Ok(CompletedUpload {
+            path: path,
+            size: size,
+            digest: format!("SHA-256={}", base64::encode(&hasher.result())),
+        })
+    }
+
+    /// Discards the upload `id` and all of its chunks.
+    pub fn abort(&self, id: &UploadId) -> Result<(), UploadError> {
+        fs::remove_dir_all(self.upload_dir(id)?)?;
+        Ok(())
+    }
+
+    /// Discards all uploads that haven't received a chunk in `age`, returning
+    /// the number of uploads discarded. Call this periodically to reclaim the
+    /// space used by abandoned uploads.
+    pub fn purge_older_than(&self, age: Duration) -> io::Result<usize> {
+        let mut purged = 0;
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            let idle = metadata.modified()?.elapsed().unwrap_or_default();
+            if metadata.is_dir() && idle > age {
+                fs::remove_dir_all(entry.path())?;
+                purged += 1;
+            }
+        }
+
+        Ok(purged)
+    }
+}
@@ -0,0 +1,65 @@
+extern crate rocket;
+extern crate rocket_contrib;
+
+#[cfg(feature = "cache")]
+mod cache_tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use rocket::{self, Request, Data, Route};
+    use rocket::handler::Outcome;
+    use rocket::http::{Method, Status};
+    use rocket::local::Client;
+    use rocket_contrib::cache::ResponseCache;
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn counted<'r>(req: &'r Request, _: Data) -> Outcome<'r> {
+        let calls = CALLS.fetch_add(1, Ordering::SeqCst) + 1;
+        Outcome::from(req, calls.to_string())
+    }
+
+    #[test]
+    fn caches_and_invalidates() {
+        let cache = ResponseCache::new();
+        let handle = cache.handle();
+        let route = Route::new(Method::Get, "/count", counted);
+        let client = Client::new(rocket::ignite().attach(cache).mount("/", vec![route]))
+            .unwrap();
+
+        let mut first = client.get("/count").dispatch();
+        assert_eq!(first.status(), Status::Ok);
+        let first_body = first.body_string();
+
+        let mut second = client.get("/count").dispatch();
+        assert_eq!(second.body_string(), first_body);
+        assert!(second.headers().get_one("Age").is_some());
+        assert_eq!(handle.len(), 1);
+
+        assert_eq!(handle.invalidate_prefix("/co"), 1);
+        assert!(handle.is_empty());
+
+        let mut third = client.get("/count").dispatch();
+        assert_ne!(third.body_string(), first_body);
+    }
+
+    fn hello<'r>(req: &'r Request, _: Data) -> Outcome<'r> {
+        Outcome::from(req, "Hello, cache!")
+    }
+
+    #[test]
+    fn records_hits_and_misses() {
+        let route = Route::new(Method::Get, "/hello", hello);
+        let rocket = rocket::ignite().attach(ResponseCache::new()).mount("/", vec![route]);
+        let client = Client::new(rocket).unwrap();
+
+        client.get("/hello").dispatch();
+        client.get("/hello").dispatch();
+        client.get("/hello").dispatch();
+
+        let metrics = client.rocket().metrics();
+        assert_eq!(metrics.get("cache.misses"), Some(1));
+        assert_eq!(metrics.get("cache.hits"), Some(2));
+        assert_eq!(metrics.get("cache.compressed_hits"), Some(0));
+        assert_eq!(metrics.get("cache.bytes"), Some("Hello, cache!".len()));
+    }
+}
@@ -49,7 +49,11 @@ fn main() {
             print_version_err(&*version, &*date);
             panic!("Aborting compilation due to incompatible compiler.")
         }
+
+        // Plugins are only compatible when built by the same compiler.
+        println!("cargo:rustc-env=ROCKET_RUSTC_VERSION={} ({})", version, date);
     } else {
+        println!("cargo:rustc-env=ROCKET_RUSTC_VERSION=unknown");
         println!("cargo:warning={}", "Rocket was unable to check rustc compatibility.");
         println!("cargo:warning={}", "Build may fail due to incompatible rustc version.");
     }
@@ -0,0 +1,33 @@
+#![no_main]
+#[macro_use] extern crate libfuzzer_sys;
+extern crate rocket;
+extern crate hyper;
+
+use rocket::http::Header;
+
+// Inputs are a header's name and value separated by the first `:`, as in the
+// seeds in `corpus/header_serialization`.
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+    let (name, value) = match input.find(':') {
+        Some(i) => (&input[..i], &input[(i + 1)..]),
+        None => (&*input, "")
+    };
+
+    let header = match Header::try_new(name, value) {
+        Ok(header) => header,
+        Err(_) => return
+    };
+
+    // A header that's accepted must be written as exactly one header line,
+    // both by Rocket and by Hyper, which writes out responses.
+    let line = header.to_string();
+    assert!(!line.contains('\r') && !line.contains('\n'));
+
+    let mut headers = hyper::header::Headers::new();
+    headers.append_raw(header.name().to_string(), header.value().as_bytes().to_vec());
+    let written = headers.to_string();
+    assert!(written.ends_with("\r\n"));
+    assert_eq!(written.matches('\r').count(), 1);
+    assert_eq!(written.matches('\n').count(), 1);
+});
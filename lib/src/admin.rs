@@ -0,0 +1,373 @@
+//! A local-only control channel for administering a running application.
+//!
+//! When configured via [`Rocket::admin()`], Rocket listens for administrative
+//! commands on a Unix domain socket or on a port bound to the loopback
+//! interface, separately from the application's own routes. The channel is
+//! never reachable through the application's listener, and every session
+//! must first authenticate with the configured token. On Unix, the socket
+//! file is additionally made accessible to its owner only.
+//!
+//! # Protocol
+//!
+//! Sessions are line-based. A session begins with `auth <token>`; any other
+//! first line, or a wrong token, ends the session. Each following line is a
+//! command. The reply to every line begins with `ok` or `error: <reason>`, may
+//! continue with further lines, and ends with an empty line.
+//!
+//!   * **`routes`**: lists the mounted routes as `<method> <uri> <rank>`,
+//!     followed by `deprecated` for deprecated routes.
+//!   * **`log [critical|normal|debug]`**: shows or changes the log level.
+//!   * **`maintenance [on|off]`**: shows or toggles maintenance mode. In
+//!     maintenance mode, every request is answered with `503 Service
+//!     Unavailable`.
+//!   * **`reload-tls`**: reloads the TLS certificates and key from their
+//!     files; see [`tls`](/rocket/tls/index.html).
+//!   * **`stats`**: shows connection and worker statistics, and whether the
+//!     application is ready, as `<name> <value>` lines.
+//!   * **`quit`**: ends the session.
+//!
+//! For example, with `socat`:
+//!
+//! ```text
+//! $ socat - UNIX-CONNECT:/run/app/admin.sock
+//! auth s3cr3t
+//! ok
+//!
+//! maintenance on
+//! ok
+//! maintenance on
+//!
+//! ```
+//!
+//! # Example
+//!
+//! ```rust
+//! use rocket::admin::Admin;
+//!
+//! # if false {
+//! rocket::ignite()
+//!     .admin(Admin::loopback(8001, "s3cr3t"))
+//!     .launch();
+//! # }
+//! ```
+//!
+//! [`Rocket::admin()`]: /rocket/struct.Rocket.html#method.admin
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, Ipv4Addr};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use std::thread;
+
+#[cfg(unix)] use std::path::{Path, PathBuf};
+
+use logger::{self, LoggingLevel};
+use metrics::Connections;
+use tls::TlsReloader;
+use security::{Secret, constant_time_eq};
+
+/// How long a session may stay silent before it is closed.
+const SESSION_TIMEOUT_SECS: u64 = 60;
+
+/// Where the admin channel listens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdminAddress {
+    /// A TCP port on `127.0.0.1`.
+    Loopback(u16),
+    /// A Unix domain socket at the given path.
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl fmt::Display for AdminAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AdminAddress::Loopback(port) => write!(f, "127.0.0.1:{}", port),
+            #[cfg(unix)]
+            AdminAddress::Unix(ref path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// Admin channel configuration: an address and an authentication token.
+///
+/// See the [module level documentation](/rocket/admin/index.html) for the
+/// protocol.
+pub struct Admin {
+    address: AdminAddress,
+    token: Secret<String>,
+}
+
+impl Admin {
+    /// Returns a configuration listening on `port` of the loopback interface,
+    /// authenticating sessions with `token`.
+    pub fn loopback<T: Into<String>>(port: u16, token: T) -> Admin {
+        Admin { address: AdminAddress::Loopback(port), token: Secret::new(token.into()) }
+    }
+
+    /// Returns a configuration listening on a Unix domain socket at `path`,
+    /// authenticating sessions with `token`. Any existing file at `path` is
+    /// replaced when Rocket launches.
+    #[cfg(unix)]
+    pub fn unix<P: AsRef<Path>, T: Into<String>>(path: P, token: T) -> Admin {
+        let address = AdminAddress::Unix(path.as_ref().to_path_buf());
+        Admin { address: address, token: Secret::new(token.into()) }
+    }
+
+    /// Returns the address `self` listens on.
+    #[inline(always)]
+    pub fn address(&self) -> &AdminAddress {
+        &self.address
+    }
+
+    /// Binds the admin channel and serves it with `control` on a new thread.
+    pub(crate) fn start(self, control: Control) -> io::Result<()> {
+        let token = self.token;
+        let spawner = thread::Builder::new().name("rocket-admin".into());
+        match self.address {
+            AdminAddress::Loopback(port) => {
+                let listener = TcpListener::bind((Ipv4Addr::new(127, 0, 0, 1), port))?;
+                spawner.spawn(move || for stream in listener.incoming() {
+                    let stream = match stream {
+                        Ok(stream) => stream,
+                        Err(e) => { error_!("Admin connection failed: {}", e); continue }
+                    };
+
+                    let timeout = Some(Duration::from_secs(SESSION_TIMEOUT_SECS));
+                    let _ = stream.set_read_timeout(timeout);
+                    match stream.try_clone() {
+                        Ok(reader) => session(&token, &control, BufReader::new(reader), stream),
+                        Err(e) => error_!("Admin connection failed: {}", e)
+                    }
+                })?;
+            }
+            #[cfg(unix)]
+            AdminAddress::Unix(path) => {
+                use std::fs;
+                use std::os::unix::fs::PermissionsExt;
+                use std::os::unix::net::UnixListener;
+
+                let _ = fs::remove_file(&path);
+                let listener = UnixListener::bind(&path)?;
+                fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+                spawner.spawn(move || for stream in listener.incoming() {
+                    let stream = match stream {
+                        Ok(stream) => stream,
+                        Err(e) => { error_!("Admin connection failed: {}", e); continue }
+                    };
+
+                    let timeout = Some(Duration::from_secs(SESSION_TIMEOUT_SECS));
+                    let _ = stream.set_read_timeout(timeout);
+                    match stream.try_clone() {
+                        Ok(reader) => session(&token, &control, BufReader::new(reader), stream),
+                        Err(e) => error_!("Admin connection failed: {}", e)
+                    }
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Admin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Admin").field("address", &self.address).finish()
+    }
+}
+
+/// The runtime state of a launched application that admin commands inspect
+/// and change.
+pub(crate) struct Control {
+    pub(crate) routes: Vec<String>,
+    pub(crate) workers: u16,
+    pub(crate) connections: Arc<Connections>,
+    pub(crate) maintenance: Arc<AtomicBool>,
+    pub(crate) ready: Arc<AtomicBool>,
+    pub(crate) tls: TlsReloader,
+}
+
+impl Control {
+    /// Executes `command`, returning its output, with a trailing newline if it
+    /// is not empty, or the reason it failed.
+    fn execute(&self, command: &str) -> Result<String, String> {
+        let mut words = command.split_whitespace();
+        let (name, argument) = (words.next().unwrap_or(""), words.next());
+        if words.next().is_some() {
+            return Err("too many arguments".into());
+        }
+
+        match (name, argument) {
+            ("routes", None) => {
+                Ok(self.routes.iter().map(|route| format!("{}\n", route)).collect())
+            }
+            ("log", None) => Ok(format!("{}\n", logger::level())),
+            ("log", Some(level)) => {
+                let level: LoggingLevel = level.parse().map_err(|e| format!("expected {}", e))?;
+                logger::set_level(level);
+                warn_!("Log level changed to {} via the admin channel.", level);
+                Ok(format!("{}\n", level))
+            }
+            ("maintenance", None) => {
+                let on = self.maintenance.load(Ordering::Acquire);
+                Ok(format!("maintenance {}\n", if on { "on" } else { "off" }))
+            }
+            ("maintenance", Some(state)) => {
+                match state {
+                    "on" => self.maintenance.store(true, Ordering::Release),
+                    "off" => self.maintenance.store(false, Ordering::Release),
+                    _ => return Err("expected `on` or `off`".into()),
+                }
+
+                warn_!("Maintenance mode turned {} via the admin channel.", state);
+                Ok(format!("maintenance {}\n", state))
+            }
+            ("reload-tls", None) => {
+                self.tls.reload().map_err(|e| format!("TLS reload failed: {}", e))?;
+                warn_!("TLS certificates reloaded via the admin channel.");
+                Ok(String::new())
+            }
+            ("stats", None) => {
+                let stats = self.connections.stats();
+                let ready = self.ready.load(Ordering::Acquire);
+                Ok(format!("connections.open {}\nconnections.idle {}\nconnections.active {}\n\
+                            workers {}\nready {}\n", stats.open, stats.idle, stats.active,
+                           self.workers, ready))
+            }
+            ("", None) => Err("empty command".into()),
+            ("routes", Some(_)) | ("reload-tls", Some(_)) | ("stats", Some(_)) => {
+                Err(format!("`{}` takes no arguments", name))
+            }
+            _ => Err(format!("unknown command `{}`", name))
+        }
+    }
+}
+
+/// Serves one session authenticated by `token`, reading lines from `reader`
+/// and writing replies to `writer`, until the client quits, disconnects, or
+/// goes silent.
+fn session<R: BufRead, W: Write>(
+    token: &Secret<String>,
+    control: &Control,
+    reader: R,
+    mut writer: W
+) {
+    let mut lines = reader.lines();
+    let authenticated = match lines.next() {
+        Some(Ok(line)) => {
+            let mut words = line.trim().splitn(2, ' ');
+            match (words.next(), words.next()) {
+                (Some("auth"), Some(given)) => {
+                    constant_time_eq(given.trim().as_bytes(), token.expose().as_bytes())
+                }
+                _ => false
+            }
+        }
+        _ => false
+    };
+
+    if !authenticated {
+        warn_!("Rejected an unauthenticated admin session.");
+        let _ = writer.write_all(b"error: unauthorized\n\n");
+        return;
+    }
+
+    if writer.write_all(b"ok\n\n").is_err() {
+        return;
+    }
+
+    for line in lines {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => return
+        };
+
+        if line.trim() == "quit" {
+            let _ = writer.write_all(b"ok\n\n");
+            return;
+        }
+
+        let reply = match control.execute(line.trim()) {
+            Ok(output) => format!("ok\n{}\n", output),
+            Err(e) => format!("error: {}\n\n", e),
+        };
+
+        if writer.write_all(reply.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use super::Control;
+    use metrics::{Metrics, Connections};
+    use security::Secret;
+    use tls::TlsReloader;
+
+    fn control() -> Control {
+        Control {
+            routes: vec!["GET /".into(), "POST /login".into()],
+            workers: 4,
+            connections: Arc::new(Connections::new(&Metrics::new(), None, None, None)),
+            maintenance: Arc::new(AtomicBool::new(false)),
+            ready: Arc::new(AtomicBool::new(true)),
+            tls: TlsReloader::new(),
+        }
+    }
+
+    fn session(control: &Control, input: &str) -> String {
+        let mut output = vec![];
+        let token = Secret::new("t0ken".to_string());
+        super::session(&token, control, Cursor::new(input.as_bytes()), &mut output);
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_session_requires_token() {
+        let control = control();
+        assert_eq!(session(&control, "routes\n"), "error: unauthorized\n\n");
+        assert_eq!(session(&control, "auth guess\nroutes\n"), "error: unauthorized\n\n");
+        assert_eq!(session(&control, "auth t0ken\nquit\nroutes\n"), "ok\n\nok\n\n");
+    }
+
+    #[test]
+    fn test_commands() {
+        let control = control();
+        let output = session(&control, "auth t0ken\nroutes\nstats\nfly\n");
+        assert_eq!(output, "ok\n\nok\nGET /\nPOST /login\n\n\
+                            ok\nconnections.open 0\nconnections.idle 0\n\
+                            connections.active 0\nworkers 4\nready true\n\n\
+                            error: unknown command `fly`\n\n");
+    }
+
+    #[test]
+    fn test_maintenance_toggle() {
+        let control = control();
+        assert_eq!(control.execute("maintenance on"), Ok("maintenance on\n".into()));
+        assert!(control.maintenance.load(Ordering::Acquire));
+        assert_eq!(control.execute("maintenance"), Ok("maintenance on\n".into()));
+        assert_eq!(control.execute("maintenance off"), Ok("maintenance off\n".into()));
+        assert!(control.execute("maintenance maybe").is_err());
+        assert!(!control.maintenance.load(Ordering::Acquire));
+    }
+
+    #[test]
+    fn test_reload_tls_requires_tls() {
+        let control = control();
+        let output = session(&control, "auth t0ken
+reload-tls
+");
+        assert_eq!(output, "ok
+
+error: TLS reload failed: TLS isn't being served
+
+");
+    }
+}
@@ -0,0 +1,260 @@
+//! Throttled alerts for server error responses.
+//!
+//! An application that pages or notifies someone when it fails shouldn't have
+//! to parse its own logs to find out. An [`ErrorHook`], registered via
+//! [`Rocket::on_error_response()`], is called with an [`ErrorAlert`] for every
+//! response with a `5xx` status: the status, a summary of the request and the
+//! route that handled it, and, when a guard failed the request, the chain of
+//! errors that led to the failure.
+//!
+//! A failing route tends to fail every request it handles, so alerts are
+//! throttled per route: by default, at most one alert is delivered per route
+//! per minute. Alerts that are throttled are counted, and the count is
+//! delivered with the route's next alert as [`ErrorAlert::suppressed`].
+//! Responses to requests that no route handled, such as those rejected in
+//! maintenance mode, share one throttle.
+//!
+//! The hook is called on the worker thread that handled the request, after
+//! the response fairings have run and before the response is written. Hooks
+//! that do slow work, such as sending a page over the network, should hand
+//! the alert off to another thread.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use rocket::alert::ErrorHook;
+//!
+//! # if false {
+//! rocket::ignite()
+//!     .on_error_response(ErrorHook::new(|alert| {
+//!         eprintln!("ALERT: {}", alert);
+//!     }).throttle(5, Duration::from_secs(10 * 60)))
+//!     .launch();
+//! # }
+//! ```
+//!
+//! [`ErrorHook`]: /rocket/alert/struct.ErrorHook.html
+//! [`ErrorAlert`]: /rocket/alert/struct.ErrorAlert.html
+//! [`ErrorAlert::suppressed`]: /rocket/alert/struct.ErrorAlert.html#structfield.suppressed
+//! [`Rocket::on_error_response()`]: /rocket/struct.Rocket.html#method.on_error_response
+
+use std::fmt;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use request::Request;
+use response::Response;
+use metrics::Timings;
+use http::{Method, Status, StatusClass};
+
+/// The throttle key shared by responses to requests that no route handled.
+const UNROUTED: &str = "<unrouted>";
+
+/// A server error response, as delivered to an
+/// [`ErrorHook`](/rocket/alert/struct.ErrorHook.html).
+#[derive(Debug, Clone)]
+pub struct ErrorAlert {
+    /// The status of the response.
+    pub status: Status,
+    /// The method of the request.
+    pub method: Method,
+    /// The URI of the request.
+    pub uri: String,
+    /// The route that handled the request, as its method and path, or `None`
+    /// if no route did.
+    pub route: Option<String>,
+    /// The name of the route's handler, if it was declared with a route
+    /// attribute.
+    pub route_name: Option<&'static str>,
+    /// The IP address of the client, as determined by
+    /// [`Request::client_ip()`](/rocket/struct.Request.html#method.client_ip).
+    pub client_ip: Option<IpAddr>,
+    /// The errors that led to the response, outermost first: the guard that
+    /// failed the request followed by the guard's error. Empty if the cause is
+    /// unknown, as when a handler returned the error status itself.
+    pub error_chain: Vec<String>,
+    /// The number of alerts for the same route that were throttled since the
+    /// last alert for the route was delivered.
+    pub suppressed: u64,
+    /// The request's timings.
+    pub timings: Timings,
+}
+
+impl fmt::Display for ErrorAlert {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} for {} {}", self.status, self.method, self.uri)?;
+        if let Some(ref route) = self.route {
+            write!(f, " (route {})", route)?;
+        }
+
+        for error in &self.error_chain {
+            write!(f, ": {}", error)?;
+        }
+
+        if self.suppressed > 0 {
+            write!(f, " [{} more suppressed]", self.suppressed)?;
+        }
+
+        Ok(())
+    }
+}
+
+// The alerts delivered for one route in the current throttle window.
+struct Window {
+    start: Instant,
+    delivered: u32,
+    suppressed: u64,
+}
+
+/// A hook called with an [`ErrorAlert`] for `5xx` responses, throttled per
+/// route. See the [module level documentation](/rocket/alert/index.html) for
+/// details.
+///
+/// [`ErrorAlert`]: /rocket/alert/struct.ErrorAlert.html
+pub struct ErrorHook {
+    hook: Box<Fn(&ErrorAlert) + Send + Sync>,
+    max: u32,
+    window: Duration,
+    routes: Mutex<HashMap<String, Window>>,
+}
+
+impl ErrorHook {
+    /// Returns an `ErrorHook` that calls `hook` with each alert, delivering at
+    /// most one alert per route per minute.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::alert::ErrorHook;
+    ///
+    /// let hook = ErrorHook::new(|alert| eprintln!("{}", alert));
+    /// ```
+    pub fn new<F>(hook: F) -> ErrorHook
+        where F: Fn(&ErrorAlert) + Send + Sync + 'static
+    {
+        ErrorHook {
+            hook: Box::new(hook),
+            max: 1,
+            window: Duration::from_secs(60),
+            routes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the throttle: at most `max` alerts are delivered per route in
+    /// each `window`. A `max` of `0` suppresses every alert.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use rocket::alert::ErrorHook;
+    ///
+    /// // At most 3 alerts per route every 5 minutes.
+    /// let hook = ErrorHook::new(|alert| eprintln!("{}", alert))
+    ///     .throttle(3, Duration::from_secs(5 * 60));
+    /// ```
+    pub fn throttle(mut self, max: u32, window: Duration) -> Self {
+        self.max = max;
+        self.window = window;
+        self
+    }
+
+    /// Returns the number of alerts suppressed for the throttle `key` if an
+    /// alert for it should be delivered now, and `None` otherwise.
+    fn admit(&self, key: &str) -> Option<u64> {
+        let now = Instant::now();
+        let mut routes = self.routes.lock().expect("error hook lock");
+        let window = routes.entry(key.to_string())
+            .or_insert(Window { start: now, delivered: 0, suppressed: 0 });
+
+        if now.duration_since(window.start) >= self.window {
+            window.start = now;
+            window.delivered = 0;
+        }
+
+        if window.delivered >= self.max {
+            window.suppressed += 1;
+            return None;
+        }
+
+        window.delivered += 1;
+        Some(::std::mem::replace(&mut window.suppressed, 0))
+    }
+
+    /// Calls the hook if `response` is a server error and the route that
+    /// handled `request` hasn't exhausted its throttle.
+    pub(crate) fn handle(&self, request: &Request, response: &Response) {
+        let status = response.status();
+        if status.class() != StatusClass::ServerError {
+            return;
+        }
+
+        let route = request.route();
+        let path = route.map(|route| format!("{} {}", route.method, route.uri));
+        let suppressed = match self.admit(path.as_ref().map(|p| p.as_str()).unwrap_or(UNROUTED)) {
+            Some(suppressed) => suppressed,
+            None => return
+        };
+
+        let mut error_chain = vec![];
+        if let Some(failure) = request.catch_context().guard_failure() {
+            error_chain.push(format!("{} `{}: {}` failed with {}",
+                                     failure.kind, failure.name, failure.ty, failure.status));
+            error_chain.extend(failure.error.clone());
+        }
+
+        (self.hook)(&ErrorAlert {
+            status: status,
+            method: request.method(),
+            uri: request.uri().as_str().to_string(),
+            route: path,
+            route_name: route.and_then(|route| route.name),
+            client_ip: request.client_ip(),
+            error_chain: error_chain,
+            suppressed: suppressed,
+            timings: request.timings(),
+        });
+    }
+}
+
+impl fmt::Debug for ErrorHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ErrorHook")
+            .field("max", &self.max)
+            .field("window", &self.window)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::thread;
+
+    use super::ErrorHook;
+
+    #[test]
+    fn test_throttle_per_route() {
+        let hook = ErrorHook::new(|_| ()).throttle(2, Duration::from_millis(200));
+        assert_eq!(hook.admit("GET /a"), Some(0));
+        assert_eq!(hook.admit("GET /a"), Some(0));
+        assert_eq!(hook.admit("GET /a"), None);
+        assert_eq!(hook.admit("GET /a"), None);
+        assert_eq!(hook.admit("GET /b"), Some(0));
+
+        thread::sleep(Duration::from_millis(250));
+        assert_eq!(hook.admit("GET /a"), Some(2));
+        assert_eq!(hook.admit("GET /a"), Some(0));
+        assert_eq!(hook.admit("GET /a"), None);
+    }
+
+    #[test]
+    fn test_zero_max_suppresses_everything() {
+        let hook = ErrorHook::new(|_| ()).throttle(0, Duration::from_secs(60));
+        assert_eq!(hook.admit("GET /a"), None);
+        assert_eq!(hook.admit("GET /a"), None);
+    }
+}
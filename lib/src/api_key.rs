@@ -0,0 +1,439 @@
+//! Request guards for typed, scoped API keys.
+//!
+//! The [`ApiKey`] request guard authenticates a request by the API key it
+//! presents, either as a bearer token in the `Authorization` header or in the
+//! `X-API-Key` header, and authorizes it by the scopes the key was granted.
+//! The scope a route requires is part of the guard's type: a route with an
+//! `ApiKey<Admin>` guard only runs for keys granted the `Admin` scope.
+//!
+//! The guard fails with `401 Unauthorized` if the request presents no key or
+//! an unknown key and with `403 Forbidden` if the key is known but lacks the
+//! required scope.
+//!
+//! # Keys
+//!
+//! Keys are looked up by the [`KeyResolver`] of the [`ApiKeys`] managed by
+//! the application. The [`StaticKeys`] resolver holds a fixed set of keys,
+//! usually read from the `api_keys` configuration table, which maps a name
+//! for each key to the key and its scopes. The name identifies the key in
+//! logs and handlers without revealing it:
+//!
+//! ```toml
+//! [global.api_keys]
+//! ci = { key = "KOIoMzuxnqt0nxvBnX3R6Y6UmXUKAtns", scopes = ["read"] }
+//! ops = { key = "yvP3yDsw8hOxuMB0OsYJgovK5fhEqf1p", scopes = ["read", "admin"] }
+//! ```
+//!
+//! The [`ApiKeys::fairing()`] fairing reads the keys from the configuration
+//! and manages them. Alternatively, an `ApiKeys` with a custom resolver, for
+//! instance one backed by a database, can be managed directly.
+//!
+//! A key granted the scope `*` has every scope. Keys should be compared in
+//! constant time to avoid leaking them via timing; `StaticKeys` does so.
+//!
+//! # Example
+//!
+//! ```rust
+//! # #![feature(plugin, decl_macro)]
+//! # #![plugin(rocket_codegen)]
+//! # extern crate rocket;
+//! use rocket::api_key::{ApiKey, ApiKeys, Scope};
+//!
+//! struct Admin;
+//!
+//! impl Scope for Admin {
+//!     const NAME: &'static str = "admin";
+//! }
+//!
+//! #[get("/status")]
+//! fn status(key: ApiKey) -> String {
+//!     format!("Hello, {}!", key.name())
+//! }
+//!
+//! #[post("/shutdown")]
+//! fn shutdown(key: ApiKey<Admin>) -> String {
+//!     format!("Shutting down at the request of {}.", key.name())
+//! }
+//!
+//! fn main() {
+//! # if false {
+//!     rocket::ignite()
+//!         .attach(ApiKeys::fairing())
+//!         .mount("/", routes![status, shutdown])
+//!         .launch();
+//! # }
+//! }
+//! ```
+//!
+//! [`ApiKey`]: /rocket/api_key/struct.ApiKey.html
+//! [`ApiKeys`]: /rocket/api_key/struct.ApiKeys.html
+//! [`ApiKeys::fairing()`]: /rocket/api_key/struct.ApiKeys.html#method.fairing
+//! [`KeyResolver`]: /rocket/api_key/trait.KeyResolver.html
+//! [`StaticKeys`]: /rocket/api_key/struct.StaticKeys.html
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use request::{Request, FromRequest, Outcome, State};
+use outcome::Outcome::*;
+use config::{Config, ConfigError};
+use fairing::{Fairing, AdHoc};
+use http::Status;
+use security::{Secret, constant_time_eq};
+
+/// Trait implemented by types that name an API key scope.
+///
+/// See the [`ApiKey`](/rocket/api_key/struct.ApiKey.html) request guard.
+pub trait Scope {
+    /// The name of the scope as granted to keys.
+    const NAME: &'static str;
+}
+
+/// The scope every valid key has. The default scope of an
+/// [`ApiKey`](/rocket/api_key/struct.ApiKey.html) guard.
+pub struct Any;
+
+impl Scope for Any {
+    const NAME: &'static str = "*";
+}
+
+/// The name and scopes of a valid API key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyInfo {
+    name: String,
+    scopes: Vec<String>,
+}
+
+impl KeyInfo {
+    /// Returns the information for the key named `name` with the scopes
+    /// `scopes`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::api_key::KeyInfo;
+    ///
+    /// let info = KeyInfo::new("ci", vec!["read".into()]);
+    /// assert!(info.has_scope("read"));
+    /// assert!(!info.has_scope("write"));
+    /// ```
+    pub fn new<S: Into<String>>(name: S, scopes: Vec<String>) -> KeyInfo {
+        KeyInfo { name: name.into(), scopes: scopes }
+    }
+
+    /// Returns the name of the key.
+    #[inline(always)]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the scopes granted to the key.
+    #[inline(always)]
+    pub fn scopes(&self) -> &[String] {
+        &self.scopes
+    }
+
+    /// Returns `true` if the key has the scope `scope`: if `scope` is `*`, if
+    /// the key was granted `scope`, or if the key was granted `*`.
+    pub fn has_scope(&self, scope: &str) -> bool {
+        scope == Any::NAME || self.scopes.iter().any(|s| s == Any::NAME || s == scope)
+    }
+}
+
+/// Trait implemented by types that look up API keys.
+///
+/// Implementations should compare keys in constant time, for instance with
+/// [`constant_time_eq()`](/rocket/security/fn.constant_time_eq.html), or look
+/// them up by a cryptographic hash of the key.
+pub trait KeyResolver: Send + Sync + 'static {
+    /// Returns the information for `key` or `None` if `key` isn't valid.
+    fn resolve(&self, key: &str) -> Option<KeyInfo>;
+}
+
+/// A fixed set of API keys, compared in constant time.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::api_key::{StaticKeys, KeyResolver};
+///
+/// let keys = StaticKeys::new()
+///     .key("ci", "KOIoMzuxnqt0nxvBnX3R6Y6UmXUKAtns", &["read"])
+///     .key("ops", "yvP3yDsw8hOxuMB0OsYJgovK5fhEqf1p", &["read", "admin"]);
+///
+/// let info = keys.resolve("KOIoMzuxnqt0nxvBnX3R6Y6UmXUKAtns").unwrap();
+/// assert_eq!(info.name(), "ci");
+/// assert!(keys.resolve("guess").is_none());
+/// ```
+#[derive(Default)]
+pub struct StaticKeys {
+    keys: Vec<(Secret<String>, KeyInfo)>,
+}
+
+impl StaticKeys {
+    /// Returns an empty set of keys.
+    #[inline(always)]
+    pub fn new() -> StaticKeys {
+        StaticKeys { keys: vec![] }
+    }
+
+    /// Adds the key `key`, named `name`, with the scopes `scopes`.
+    pub fn key<N, K>(mut self, name: N, key: K, scopes: &[&str]) -> StaticKeys
+        where N: Into<String>, K: Into<String>
+    {
+        let scopes = scopes.iter().map(|s| s.to_string()).collect();
+        self.keys.push((Secret::new(key.into()), KeyInfo::new(name, scopes)));
+        self
+    }
+
+    /// Reads the keys in the `api_keys` table of `config`. If there is no such
+    /// table, the set of keys is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `api_keys` isn't a table of tables, each with a
+    /// string `key` and an array of strings `scopes`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use rocket::config::{Config, Environment, Value};
+    /// use rocket::api_key::{StaticKeys, KeyResolver};
+    ///
+    /// let mut ci = HashMap::new();
+    /// ci.insert("key".to_string(), Value::from("secret-key"));
+    /// ci.insert("scopes".to_string(), Value::from(vec!["read"]));
+    ///
+    /// let mut keys = HashMap::new();
+    /// keys.insert("ci".to_string(), ci);
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .extra("api_keys", keys)
+    ///     .unwrap();
+    ///
+    /// let keys = StaticKeys::from_config(&config).unwrap();
+    /// assert_eq!(keys.resolve("secret-key").unwrap().name(), "ci");
+    /// ```
+    pub fn from_config(config: &Config) -> Result<StaticKeys, ConfigError> {
+        let table = match config.get_table("api_keys") {
+            Ok(table) => table,
+            Err(ConfigError::NotFound) => return Ok(StaticKeys::new()),
+            Err(e) => return Err(e)
+        };
+
+        let mut keys = StaticKeys::new();
+        for (name, value) in table {
+            let entry = format!("api_keys.{}", name);
+            let expect = "a table with a string `key` and an array of string `scopes`";
+            let bad_type = |actual| config.bad_type(&entry, actual, expect);
+
+            let entry = value.as_table().ok_or_else(|| bad_type(value.type_str()))?;
+            let key = match entry.get("key") {
+                Some(key) => key.as_str().ok_or_else(|| bad_type(key.type_str()))?,
+                None => return Err(bad_type("a table without a `key`"))
+            };
+
+            let mut scopes = vec![];
+            if let Some(value) = entry.get("scopes") {
+                let array = value.as_array().ok_or_else(|| bad_type(value.type_str()))?;
+                for scope in array {
+                    scopes.push(scope.as_str().ok_or_else(|| bad_type(scope.type_str()))?);
+                }
+            }
+
+            keys = keys.key(name.as_str(), key, &scopes);
+        }
+
+        Ok(keys)
+    }
+}
+
+impl KeyResolver for StaticKeys {
+    fn resolve(&self, key: &str) -> Option<KeyInfo> {
+        // Compare against every key so that the time taken doesn't reveal
+        // which key, if any, matched.
+        let mut found = None;
+        for &(ref candidate, ref info) in &self.keys {
+            if constant_time_eq(candidate.expose().as_bytes(), key.as_bytes()) {
+                found = Some(info);
+            }
+        }
+
+        found.cloned()
+    }
+}
+
+impl fmt::Debug for StaticKeys {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let infos: Vec<_> = self.keys.iter().map(|&(_, ref info)| info).collect();
+        f.debug_struct("StaticKeys").field("keys", &infos).finish()
+    }
+}
+
+/// The API keys of an application, as managed state.
+///
+/// The [`ApiKey`] request guard requires an `ApiKeys` to be managed. Use
+/// [`ApiKeys::fairing()`] to manage the keys in the `api_keys` configuration
+/// table or manage an `ApiKeys` with a custom [`KeyResolver`] directly:
+///
+/// ```rust
+/// use rocket::api_key::{ApiKeys, KeyResolver, KeyInfo};
+///
+/// struct Database;
+///
+/// impl KeyResolver for Database {
+///     fn resolve(&self, key: &str) -> Option<KeyInfo> {
+///         /* look up the key in the database */
+///         # None
+///     }
+/// }
+///
+/// # if false {
+/// rocket::ignite()
+///     .manage(ApiKeys::new(Database))
+///     .launch();
+/// # }
+/// ```
+///
+/// [`ApiKey`]: /rocket/api_key/struct.ApiKey.html
+/// [`ApiKeys::fairing()`]: #method.fairing
+/// [`KeyResolver`]: /rocket/api_key/trait.KeyResolver.html
+pub struct ApiKeys {
+    resolver: Box<KeyResolver>,
+}
+
+impl ApiKeys {
+    /// Returns `ApiKeys` that looks up keys with `resolver`.
+    #[inline]
+    pub fn new<R: KeyResolver>(resolver: R) -> ApiKeys {
+        ApiKeys { resolver: Box::new(resolver) }
+    }
+
+    /// Returns a fairing that reads the keys in the `api_keys` configuration
+    /// table with [`StaticKeys::from_config()`] and manages them. Launch is
+    /// aborted if the table is malformed.
+    ///
+    /// [`StaticKeys::from_config()`]: /rocket/api_key/struct.StaticKeys.html#method.from_config
+    pub fn fairing() -> impl Fairing {
+        AdHoc::on_attach(|rocket| {
+            match StaticKeys::from_config(rocket.config()) {
+                Ok(keys) => Ok(rocket.manage(ApiKeys::new(keys))),
+                Err(e) => {
+                    e.pretty_print();
+                    Err(rocket)
+                }
+            }
+        })
+    }
+
+    /// Returns the information for `key` or `None` if `key` isn't valid.
+    #[inline(always)]
+    pub fn resolve(&self, key: &str) -> Option<KeyInfo> {
+        self.resolver.resolve(key)
+    }
+}
+
+/// The reason an [`ApiKey`](/rocket/api_key/struct.ApiKey.html) guard failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiKeyError {
+    /// The request didn't present a key. The status is `401 Unauthorized`.
+    Missing,
+    /// The request presented an unknown key. The status is `401
+    /// Unauthorized`.
+    Invalid,
+    /// The key lacks the required scope. The status is `403 Forbidden`.
+    Forbidden,
+    /// No `ApiKeys` is managed. The status is `500 Internal Server Error`.
+    Unconfigured,
+}
+
+/// Request guard for a valid API key with the scope `S`.
+///
+/// The key is read from an `Authorization: Bearer <key>` header or, if there
+/// is none, from an `X-API-Key` header. It is looked up via the managed
+/// [`ApiKeys`]. The guard fails with:
+///
+///   * `401 Unauthorized` if the request presents no key or an unknown key
+///   * `403 Forbidden` if the key doesn't have the scope `S`
+///   * `500 Internal Server Error` if no `ApiKeys` is managed
+///
+/// With the default scope, [`Any`], every valid key is accepted. See the
+/// [module level documentation](/rocket/api_key/index.html) for an example.
+///
+/// [`ApiKeys`]: /rocket/api_key/struct.ApiKeys.html
+/// [`Any`]: /rocket/api_key/struct.Any.html
+pub struct ApiKey<S: Scope = Any> {
+    info: KeyInfo,
+    _scope: PhantomData<S>,
+}
+
+impl<S: Scope> ApiKey<S> {
+    /// Returns the name of the key.
+    #[inline(always)]
+    pub fn name(&self) -> &str {
+        self.info.name()
+    }
+
+    /// Returns the information for the key, including all of its scopes.
+    #[inline(always)]
+    pub fn info(&self) -> &KeyInfo {
+        &self.info
+    }
+}
+
+impl<S: Scope> fmt::Debug for ApiKey<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ApiKey<{}>({})", S::NAME, self.info.name())
+    }
+}
+
+// Returns the key presented by `request`, if any.
+fn presented_key<'a>(request: &'a Request) -> Option<&'a str> {
+    let bearer = request.headers().get("Authorization")
+        .filter_map(|value| {
+            let mut parts = value.trim().splitn(2, ' ');
+            match (parts.next(), parts.next()) {
+                (Some(scheme), Some(key)) if scheme.eq_ignore_ascii_case("Bearer") => {
+                    Some(key.trim())
+                }
+                _ => None
+            }
+        })
+        .next();
+
+    bearer.or_else(|| request.headers().get_one("X-API-Key").map(|key| key.trim()))
+        .filter(|key| !key.is_empty())
+}
+
+impl<'a, 'r, S: Scope> FromRequest<'a, 'r> for ApiKey<S> {
+    type Error = ApiKeyError;
+
+    fn from_request(request: &'a Request<'r>) -> Outcome<Self, Self::Error> {
+        let keys = match request.guard::<State<ApiKeys>>() {
+            Success(keys) => keys,
+            _ => {
+                error_!("An `ApiKey` guard was used, but no `ApiKeys` is managed.");
+                info_!("Attach `ApiKeys::fairing()` or manage an `ApiKeys`.");
+                return Failure((Status::InternalServerError, ApiKeyError::Unconfigured));
+            }
+        };
+
+        let key = match presented_key(request) {
+            Some(key) => key,
+            None => return Failure((Status::Unauthorized, ApiKeyError::Missing))
+        };
+
+        let info = match keys.resolve(key) {
+            Some(info) => info,
+            None => return Failure((Status::Unauthorized, ApiKeyError::Invalid))
+        };
+
+        if !info.has_scope(S::NAME) {
+            info_!("API key '{}' lacks the '{}' scope.", info.name(), S::NAME);
+            return Failure((Status::Forbidden, ApiKeyError::Forbidden));
+        }
+
+        Success(ApiKey { info: info, _scope: PhantomData })
+    }
+}
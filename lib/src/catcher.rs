@@ -59,8 +59,13 @@ use yansi::Color::*;
 /// }
 /// ```
 ///
-/// A function decorated with `catch` can take in 0, 1, or 2 parameters:
-/// `Error`, `&Request`, or both, as desired.
+/// A function decorated with `catch` can take in up to 3 parameters: `Error`,
+/// `&Request`, and [`CatchContext`], in any combination, as desired. The
+/// `CatchContext` describes the route and guard, if any, that failed the
+/// request.
+///
+/// [`CatchContext`]: /rocket/request/struct.CatchContext.html
+#[derive(Clone)]
 pub struct Catcher {
     /// The HTTP status code to match against.
     pub code: u16,
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex, Condvar};
+use std::time::Instant;
+
+use request::Request;
+use response::Response;
+use router::Route;
+use handler;
+use outcome::Outcome;
+use data::Data;
+use http::{Header, HeaderMap, Status};
+
+/// The request headers whose values are part of a coalescing key. Requests
+/// that differ in any of these may receive different responses, so they never
+/// share a handler execution. Responses may name further headers in `Vary`.
+const VARY_HEADERS: &[&str] = &[
+    "Accept", "Accept-Encoding", "Accept-Language", "Authorization", "Cookie"
+];
+
+/// A fully buffered response that can be replayed to any number of requests.
+/// `varies` holds the leader's values of the request headers named in the
+/// response's `Vary` header.
+#[derive(Clone)]
+struct SharedResponse {
+    status: Status,
+    headers: Vec<Header<'static>>,
+    body: Option<Vec<u8>>,
+    varies: Vec<(String, Vec<String>)>,
+}
+
+impl SharedResponse {
+    /// Buffers `response` to the leader's `request` if it may be shared with
+    /// other requests: if it has a status of `200 OK`, doesn't set cookies,
+    /// doesn't forbid caching, and doesn't vary on `*`. Otherwise, returns
+    /// `None`, leaving `response` untouched.
+    fn share(request: &Request, response: &mut Response) -> Option<SharedResponse> {
+        if response.status() != Status::Ok || response.headers().contains("Set-Cookie") {
+            return None;
+        }
+
+        let uncacheable = ["no-store", "no-cache", "private"];
+        let forbids_caching = response.headers().get("Cache-Control")
+            .flat_map(|value| value.split(','))
+            .any(|directive| uncacheable.contains(&directive.trim()));
+
+        let vary: Vec<String> = response.headers().get("Vary")
+            .flat_map(|value| value.split(','))
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect();
+
+        if forbids_caching || vary.iter().any(|name| name == "*") {
+            return None;
+        }
+
+        Some(SharedResponse {
+            status: response.status(),
+            headers: response.headers().iter()
+                .map(|h| Header::new(h.name().to_string(), h.value().to_string()))
+                .collect(),
+            body: response.body_bytes(),
+            varies: vary.into_iter()
+                .map(|name| {
+                    let values = request.headers().get(&name).map(String::from).collect();
+                    (name, values)
+                })
+                .collect(),
+        })
+    }
+
+    /// Returns `true` if a request with `headers` has the same values as the
+    /// leader's request for every header the response varies on.
+    fn matches(&self, headers: &HeaderMap) -> bool {
+        self.varies.iter().all(|&(ref name, ref values)| {
+            headers.get(name).eq(values.iter().map(|v| v.as_str()))
+        })
+    }
+
+    fn to_response<'r>(&self) -> Response<'r> {
+        let mut builder = Response::build();
+        builder.status(self.status);
+        for header in &self.headers {
+            builder.header_adjoin(header.clone());
+        }
+
+        if let Some(ref body) = self.body {
+            builder.sized_body(Cursor::new(body.clone()));
+        }
+
+        builder.finalize()
+    }
+}
+
+/// A handler execution in progress. `result` is `None` until the leader
+/// finishes, then `Some(None)` if the leader didn't produce a shareable
+/// response or `Some(Some(response))` if it did.
+struct Flight {
+    result: Mutex<Option<Option<SharedResponse>>>,
+    done: Condvar,
+}
+
+/// Marks a flight as finished, waking all waiting requests, when dropped. This
+/// ensures followers aren't left waiting if the leader's handler panics.
+struct Landing<'c> {
+    coalescer: &'c Coalescer,
+    key: String,
+    flight: Arc<Flight>,
+    response: Option<SharedResponse>,
+}
+
+impl<'c> Drop for Landing<'c> {
+    fn drop(&mut self) {
+        self.coalescer.inflight.lock().expect("coalescer lock").remove(&self.key);
+        *self.flight.result.lock().expect("flight lock") = Some(self.response.take());
+        self.flight.done.notify_all();
+    }
+}
+
+/// Coordinates "single-flight" execution of coalesced routes: the first
+/// request for a given key runs the handler while identical requests that
+/// arrive in the meantime wait for, and then share, its response.
+pub(crate) struct Coalescer {
+    inflight: Mutex<HashMap<String, Arc<Flight>>>,
+}
+
+impl Coalescer {
+    pub(crate) fn new() -> Coalescer {
+        Coalescer { inflight: Mutex::new(HashMap::new()) }
+    }
+
+    /// The key for `request` to `route`: the route, the normalized URI, and
+    /// the values of the headers the response may vary on.
+    fn key(route: &Route, request: &Request) -> String {
+        let mut key = format!("{} {} {}", route.method, route.uri, request.uri().normalized());
+        for name in VARY_HEADERS {
+            key.push('\0');
+            key.push_str(name);
+            for value in request.headers().get(name) {
+                key.push(':');
+                key.push_str(value);
+            }
+        }
+
+        key
+    }
+
+    /// Dispatches `request` to `route`, sharing the handler's response with
+    /// all identical requests that arrive while it runs. If the handler
+    /// forwards, fails, or produces a response that can't be shared, waiting
+    /// requests run the handler themselves. So does a waiting request whose
+    /// deadline passes before the handler completes.
+    pub(crate) fn handle<'r>(
+        &self,
+        route: &Route,
+        request: &'r Request,
+        data: Data
+    ) -> handler::Outcome<'r> {
+        let key = Coalescer::key(route, request);
+        let (flight, leader) = {
+            let mut inflight = self.inflight.lock().expect("coalescer lock");
+            match inflight.get(&key).cloned() {
+                Some(flight) => (flight, false),
+                None => {
+                    let flight = Arc::new(Flight {
+                        result: Mutex::new(None),
+                        done: Condvar::new(),
+                    });
+
+                    inflight.insert(key.clone(), flight.clone());
+                    (flight, true)
+                }
+            }
+        };
+
+        if !leader {
+            info_!("Waiting on coalesced request.");
+            let deadline = request.deadline();
+            let shared = {
+                let mut result = flight.result.lock().expect("flight lock");
+                while result.is_none() {
+                    result = match deadline {
+                        Some(deadline) => {
+                            let now = Instant::now();
+                            if now >= deadline {
+                                warn_!("Coalesced request timed out waiting for its leader.");
+                                break;
+                            }
+
+                            flight.done.wait_timeout(result, deadline - now)
+                                .expect("flight wait").0
+                        }
+                        None => flight.done.wait(result).expect("flight wait")
+                    };
+                }
+
+                result.clone().and_then(|shared| shared)
+            };
+
+            return match shared {
+                Some(ref shared) if shared.matches(request.headers()) => {
+                    Outcome::Success(shared.to_response())
+                }
+                _ => (route.handler)(request, data)
+            };
+        }
+
+        let mut landing = Landing {
+            coalescer: self,
+            key: key,
+            flight: flight,
+            response: None
+        };
+
+        match (route.handler)(request, data) {
+            Outcome::Success(mut response) => {
+                match SharedResponse::share(request, &mut response) {
+                    Some(shared) => {
+                        let response = shared.to_response();
+                        landing.response = Some(shared);
+                        Outcome::Success(response)
+                    }
+                    None => Outcome::Success(response)
+                }
+            }
+            outcome => outcome
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::SharedResponse;
+    use {Rocket, Request, Response, Config};
+    use http::{Header, Method, Status};
+
+    fn share(request: &Request, mut response: Response) -> Option<SharedResponse> {
+        SharedResponse::share(request, &mut response)
+    }
+
+    #[test]
+    fn test_only_cacheable_responses_are_shared() {
+        let rocket = Rocket::custom(Config::development().unwrap(), true);
+        let request = Request::new(&rocket, Method::Get, "/");
+        let ok = || Response::build().sized_body(Cursor::new("hi")).finalize();
+
+        let shared = share(&request, ok()).expect("shared");
+        assert_eq!(shared.body, Some(b"hi".to_vec()));
+
+        let not_found = Response::build().status(Status::NotFound).finalize();
+        assert!(share(&request, not_found).is_none());
+
+        let mut response = ok();
+        response.set_raw_header("Set-Cookie", "session=1");
+        assert!(share(&request, response).is_none());
+
+        let mut response = ok();
+        response.set_raw_header("Cache-Control", "max-age=60, private");
+        assert!(share(&request, response).is_none());
+
+        let mut response = ok();
+        response.set_raw_header("Vary", "*");
+        assert!(share(&request, response).is_none());
+    }
+
+    #[test]
+    fn test_shared_response_honors_vary() {
+        let rocket = Rocket::custom(Config::development().unwrap(), true);
+        let mut leader = Request::new(&rocket, Method::Get, "/");
+        leader.add_header(Header::new("X-Api-Key", "a"));
+
+        let mut response = Response::build().raw_header("Vary", "X-Api-Key").finalize();
+        let shared = SharedResponse::share(&leader, &mut response).expect("shared");
+        assert!(shared.matches(leader.headers()));
+
+        let mut follower = Request::new(&rocket, Method::Get, "/");
+        assert!(!shared.matches(follower.headers()));
+
+        follower.add_header(Header::new("X-Api-Key", "b"));
+        assert!(!shared.matches(follower.headers()));
+
+        follower.replace_header(Header::new("X-Api-Key", "a"));
+        assert!(shared.matches(follower.headers()));
+    }
+}
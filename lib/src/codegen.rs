@@ -1,4 +1,4 @@
-use handler::{Handler, ErrorHandler};
+use handler::{Handler, HeadLength, ErrorHandler};
 use http::{Method, MediaType};
 
 pub struct StaticRouteInfo {
@@ -8,6 +8,14 @@ pub struct StaticRouteInfo {
     pub format: Option<MediaType>,
     pub handler: Handler,
     pub rank: Option<isize>,
+    pub coalesce: bool,
+    pub head_length: Option<HeadLength>,
+    pub version: Option<&'static str>,
+    pub response_limit: Option<u64>,
+    pub timeout: Option<u64>,
+    pub deprecated_since: Option<&'static str>,
+    pub sunset: Option<&'static str>,
+    pub successor: Option<&'static str>,
 }
 
 pub struct StaticCatchInfo {
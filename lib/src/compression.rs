@@ -0,0 +1,326 @@
+//! Content codings for compressing response bodies and decompressing request
+//! bodies.
+//!
+//! When compression is configured, Rocket compresses response bodies after all
+//! response fairings have run. See [`CompressionConfig`] for the behavior and
+//! its configuration. The functions in this module give fairings access to
+//! the same negotiation and compression, for instance to store compressed
+//! bodies in a cache. A response that already has a `Content-Encoding` is
+//! never compressed again.
+//!
+//! [`CompressionConfig`]: /rocket/config/struct.CompressionConfig.html
+
+use std::io::{self, Read};
+
+use config::CompressionConfig;
+use request::Request;
+use request::negotiation;
+use response::{Response, Body};
+use data::Data;
+use http::Status;
+
+/// The default maximum size of a decompressed request body.
+const DEFAULT_DECOMPRESSED_LIMIT: u64 = 1024 * 1024;
+
+/// The size of the buffers used by the brotli encoder and decoder.
+#[cfg(feature = "brotli")]
+const BROTLI_BUFFER_SIZE: usize = 4096;
+
+/// A content coding Rocket can compress and decompress bodies with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Coding {
+    Brotli,
+    Zstd,
+    Gzip,
+}
+
+/// Every coding, in order of preference.
+const CODINGS: [Coding; 3] = [Coding::Brotli, Coding::Zstd, Coding::Gzip];
+
+impl Coding {
+    /// The coding's token in `Accept-Encoding` and `Content-Encoding`.
+    fn token(self) -> &'static str {
+        match self {
+            Coding::Brotli => "br",
+            Coding::Zstd => "zstd",
+            Coding::Gzip => "gzip",
+        }
+    }
+
+    /// Returns the level `config` sets for `self` or `None` if `self` is
+    /// disabled or wasn't compiled in.
+    fn level(self, config: &CompressionConfig) -> Option<i32> {
+        match self {
+            Coding::Brotli if cfg!(feature = "brotli") => {
+                config.brotli_quality().map(|quality| quality as i32)
+            }
+            Coding::Zstd if cfg!(feature = "zstd") => config.zstd_level(),
+            Coding::Gzip if cfg!(feature = "gzip") => config.gzip_level().map(|l| l as i32),
+            _ => None
+        }
+    }
+
+    /// Compresses `bytes` at `level`.
+    #[allow(unused_variables, unreachable_patterns)]
+    fn encode(self, level: i32, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            #[cfg(feature = "brotli")]
+            Coding::Brotli => {
+                use std::io::Write;
+
+                let mut encoded = vec![];
+                {
+                    // The encoder finishes the stream when it's dropped.
+                    let mut encoder = ::brotli::CompressorWriter::new(&mut encoded,
+                        BROTLI_BUFFER_SIZE, level as u32, 22);
+                    encoder.write_all(bytes)?;
+                }
+
+                Ok(encoded)
+            }
+            #[cfg(feature = "zstd")]
+            Coding::Zstd => ::zstd::stream::encode_all(bytes, level),
+            #[cfg(feature = "gzip")]
+            Coding::Gzip => {
+                use std::io::Write;
+
+                let level = ::flate2::Compression::new(level as u32);
+                let mut encoder = ::flate2::write::GzEncoder::new(vec![], level);
+                encoder.write_all(bytes)?;
+                encoder.finish()
+            }
+            _ => Err(io::Error::new(io::ErrorKind::Other, "unsupported content coding"))
+        }
+    }
+
+    /// Returns a reader of the decompressed contents of `reader`.
+    #[allow(unreachable_patterns)]
+    fn decoder<R: Read + 'static>(self, reader: R) -> io::Result<Box<Read>> {
+        match self {
+            #[cfg(feature = "brotli")]
+            Coding::Brotli => Ok(Box::new(::brotli::Decompressor::new(reader, BROTLI_BUFFER_SIZE))),
+            #[cfg(feature = "zstd")]
+            Coding::Zstd => Ok(Box::new(::zstd::stream::Decoder::new(reader)?)),
+            #[cfg(feature = "gzip")]
+            Coding::Gzip => Ok(Box::new(::flate2::read::GzDecoder::new(reader))),
+            _ => Err(io::Error::new(io::ErrorKind::Other, "unsupported content coding"))
+        }
+    }
+}
+
+/// Returns the enabled coding, with its level, that the client making
+/// `request` prefers according to its Accept-Encoding headers, or `None` if
+/// it prefers an uncompressed response.
+///
+/// Clients that send no Accept-Encoding header get uncompressed responses.
+/// Otherwise, a coding is chosen only if its quality is nonzero and at least
+/// that of `identity`; among codings of equal quality, the first in `CODINGS`
+/// wins.
+fn preferred(config: &CompressionConfig, request: &Request) -> Option<(Coding, i32)> {
+    if !request.headers().contains("Accept-Encoding") {
+        return None;
+    }
+
+    let quality = |token| {
+        negotiation::encoding_quality(request.headers().get("Accept-Encoding"), token)
+    };
+    let identity = quality("identity");
+    let mut preferred: Option<(Coding, i32, f32)> = None;
+    for &coding in CODINGS.iter() {
+        let level = match coding.level(config) {
+            Some(level) => level,
+            None => continue
+        };
+
+        let q = quality(coding.token());
+        if q > 0.0 && q >= identity && q > preferred.map_or(0.0, |(_, _, best)| best) {
+            preferred = Some((coding, level, q));
+        }
+    }
+
+    preferred.map(|(coding, level, _)| (coding, level))
+}
+
+/// Whether the body of `response` is worth compressing: its status permits a
+/// body and its media type isn't compressed already.
+fn is_compressible(response: &Response) -> bool {
+    match response.status().code {
+        100...199 | 204 | 206 | 304 => return false,
+        _ => {}
+    }
+
+    let content_type = match response.content_type() {
+        Some(content_type) => content_type,
+        None => return true
+    };
+
+    match (content_type.top().as_str(), content_type.sub().as_str()) {
+        ("image", "svg+xml") => true,
+        ("image", _) | ("audio", _) | ("video", _) | ("font", "woff") | ("font", "woff2") => false,
+        ("application", sub) => match sub {
+            "zip" | "gzip" | "x-gzip" | "zstd" | "x-bzip2" | "x-7z-compressed" | "pdf" => false,
+            _ => true
+        },
+        _ => true
+    }
+}
+
+/// Returns the content coding, as in `br`, that a compressible response to
+/// `request` would be compressed with, or `None` if compression isn't
+/// configured or the client prefers uncompressed responses.
+///
+/// # Example
+///
+/// ```rust
+/// # use rocket::Request;
+/// # use rocket::http::Method;
+/// use rocket::compression;
+///
+/// # Request::example(Method::Get, "/uri", |request| {
+/// // Compression isn't configured by default.
+/// assert_eq!(compression::preferred_coding(request), None);
+/// # });
+/// ```
+pub fn preferred_coding(request: &Request) -> Option<&'static str> {
+    let config = request.config().compression.as_ref()?;
+    preferred(config, request).map(|(coding, _)| coding.token())
+}
+
+/// Compresses the body of `response` with the enabled coding the client making
+/// `request` prefers, returning the coding, or `None` if the body wasn't
+/// compressed. Only sized bodies of at least the configured minimum size that
+/// don't already have a `Content-Encoding` are compressed. The buffered body
+/// counts towards the `memory` limit of `request`.
+///
+/// If compression is configured, a `Vary: Accept-Encoding` header is added to
+/// every compressible response, whether or not it is compressed. If
+/// compressing fails, the response is replaced with a `500`.
+pub fn compress(request: &Request, response: &mut Response) -> Option<&'static str> {
+    let config = request.config().compression.as_ref()?;
+    if response.headers().contains("Content-Encoding") || !is_compressible(response) {
+        return None;
+    }
+
+    // Whether or not it's compressed, the response depends on Accept-Encoding.
+    response.adjoin_raw_header("Vary", "Accept-Encoding");
+    let (coding, level) = preferred(config, request)?;
+
+    let mut bytes = vec![];
+    let result = match response.body() {
+        Some(Body::Sized(body, size)) if size >= config.min_body_size() as u64 => {
+            body.read_to_end(&mut bytes)
+        }
+        _ => return None
+    };
+
+    let encoded = result
+        .and_then(|_| {
+            request.reserve_memory(bytes.len() as u64)
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))
+        })
+        .and_then(|_| coding.encode(level, &bytes));
+
+    match encoded {
+        Ok(encoded) => {
+            response.set_raw_header("Content-Encoding", coding.token());
+            response.set_sized_body(io::Cursor::new(encoded));
+            Some(coding.token())
+        }
+        Err(e) => {
+            error_!("Failed to compress response body: {}.", e);
+            response.set_status(Status::InternalServerError);
+            response.set_sized_body(io::Cursor::new(Vec::new()));
+            None
+        }
+    }
+}
+
+/// Decompresses the body in `data` if it's compressed with an enabled coding,
+/// reading at most `limit` compressed and `limit` decompressed bytes. The
+/// compressed body is read to the end before it's decoded so that it's fully
+/// verified against its checksum, if it has one. The returned `Data` replaces
+/// `data`. Returns the status to reject the request with if the body can't be
+/// decompressed.
+pub(crate) fn decompress(
+    config: &CompressionConfig,
+    request: &mut Request,
+    data: Data,
+    limit: Option<u64>
+) -> (Data, Option<Status>) {
+    let coding = {
+        let encoding = match request.headers().get_one("Content-Encoding") {
+            Some(encoding) => encoding.trim(),
+            None => return (data, None)
+        };
+
+        let enabled = CODINGS.iter().find(|coding| {
+            coding.level(config).is_some() && encoding.eq_ignore_ascii_case(coding.token())
+        });
+
+        match enabled {
+            Some(&coding) => coding,
+            None => return (data, None)
+        }
+    };
+
+    let limit = limit.unwrap_or(DEFAULT_DECOMPRESSED_LIMIT);
+    let mut encoded = vec![];
+    if let Err(e) = data.open().take(limit + 1).read_to_end(&mut encoded) {
+        warn_!("Compressed request body could not be read: {}.", e);
+        return (Data::local(vec![]), Some(Status::BadRequest));
+    }
+
+    if encoded.len() as u64 > limit {
+        warn_!("Compressed body exceeds the decompressed limit of {} bytes.", limit);
+        return (Data::local(vec![]), Some(Status::PayloadTooLarge));
+    }
+
+    let mut decoded = vec![];
+    let result = coding.decoder(io::Cursor::new(encoded))
+        .and_then(|decoder| decoder.take(limit + 1).read_to_end(&mut decoded));
+
+    if let Err(e) = result {
+        warn_!("Request body could not be decompressed: {}.", e);
+        return (Data::local(vec![]), Some(Status::BadRequest));
+    }
+
+    if decoded.len() as u64 > limit {
+        warn_!("Decompressed body exceeds the decompressed limit of {} bytes.", limit);
+        return (Data::local(vec![]), Some(Status::PayloadTooLarge));
+    }
+
+    if request.reserve_memory(decoded.len() as u64).is_err() {
+        return (Data::local(vec![]), Some(Status::PayloadTooLarge));
+    }
+
+    request.remove_header("Content-Encoding");
+    (Data::local(decoded), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::{Coding, CODINGS};
+    use config::CompressionConfig;
+
+    #[test]
+    fn test_round_trip() {
+        let config = CompressionConfig::default();
+        let body = "Hello, world! ".repeat(100);
+        for &coding in CODINGS.iter() {
+            let level = match coding.level(&config) {
+                Some(level) => level,
+                None => continue
+            };
+
+            let mut decoded = String::new();
+            let encoded = coding.encode(level, body.as_bytes()).expect("encoded");
+            assert!(encoded.len() < body.len());
+
+            let mut decoder = coding.decoder(::std::io::Cursor::new(encoded)).expect("decoder");
+            decoder.read_to_string(&mut decoded).expect("decoded");
+            assert_eq!(decoded, body);
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use std::{env, fmt};
+use std::error::Error;
+use std::path::PathBuf;
+
+use config::{Result, ConfigBuilder, ConfigError, Environment, RocketConfig};
+use config::CONFIG_FILENAME;
+use config::toml_ext::parse_simple_toml_value;
+
+/// The error returned when command-line arguments can't be parsed or don't
+/// select a valid configuration.
+#[derive(Debug, PartialEq)]
+pub enum ArgsError {
+    /// Help was requested with `--help` or `-h`.
+    Help,
+    /// The argument isn't a known flag or a `name=value` pair.
+    ///
+    /// Parameters: (argument)
+    Unknown(String),
+    /// The flag requires a value but none was given.
+    ///
+    /// Parameters: (flag)
+    MissingValue(&'static str),
+    /// The value of a flag or parameter is invalid.
+    ///
+    /// Parameters: (flag or parameter, value, reason)
+    BadValue(String, String, String),
+    /// The configuration selected by the arguments couldn't be read.
+    ///
+    /// Parameters: (error)
+    Config(ConfigError),
+}
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ArgsError::Help => write!(f, "help was requested"),
+            ArgsError::Unknown(ref arg) => write!(f, "unexpected argument '{}'", arg),
+            ArgsError::MissingValue(flag) => write!(f, "'{}' requires a value", flag),
+            ArgsError::BadValue(ref name, ref value, ref reason) => {
+                write!(f, "invalid value '{}' for '{}': {}", value, name, reason)
+            }
+            ArgsError::Config(ref error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for ArgsError {
+    fn description(&self) -> &str {
+        match *self {
+            ArgsError::Help => "help was requested",
+            ArgsError::Unknown(..) => "unexpected argument",
+            ArgsError::MissingValue(..) => "a flag requires a value",
+            ArgsError::BadValue(..) => "invalid value",
+            ArgsError::Config(ref error) => error.description(),
+        }
+    }
+}
+
+impl From<ConfigError> for ArgsError {
+    fn from(error: ConfigError) -> ArgsError {
+        ArgsError::Config(error)
+    }
+}
+
+/// Launch configuration parsed from command-line arguments.
+///
+/// The following flags are recognized. A flag's value can be passed as the
+/// next argument, as in `--port 8000`, or after an `=`, as in `--port=8000`.
+///
+///   * **`--config`**, **`-c`** _path_: reads the configuration from the file
+///     at _path_ instead of searching for `Rocket.toml`.
+///   * **`--env`**, **`-e`** _environment_: selects the environment, overriding
+///     `ROCKET_ENV`.
+///   * **`--address`**, **`-a`** _address_: sets the `address` parameter.
+///   * **`--port`**, **`-p`** _port_: sets the `port` parameter.
+///   * **`--help`**, **`-h`**: requests the usage message.
+///
+/// Any other argument must be a `name=value` pair, which sets the parameter
+/// `name`, standard or extra, to `value`. Values are parsed as TOML, exactly
+/// as the values of `ROCKET_{PARAM}` environment variables are. Command-line
+/// arguments take precedence over all other configuration methods.
+///
+/// Most applications use [`Rocket::from_args()`] instead of this type
+/// directly.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::config::{Args, Environment};
+///
+/// let args = Args::parse(vec!["--env", "staging", "-p", "9000", "motd=hello"]).unwrap();
+/// assert_eq!(args.environment, Some(Environment::Staging));
+/// assert_eq!(args.port, Some(9000));
+/// assert_eq!(args.params, vec![("motd".to_string(), "hello".to_string())]);
+/// ```
+///
+/// [`Rocket::from_args()`]: /rocket/struct.Rocket.html#method.from_args
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Args {
+    /// The path to the configuration file, if given with `--config`.
+    pub config: Option<PathBuf>,
+    /// The environment, if given with `--env`.
+    pub environment: Option<Environment>,
+    /// The address, if given with `--address`.
+    pub address: Option<String>,
+    /// The port, if given with `--port`.
+    pub port: Option<u16>,
+    /// The `name=value` parameters, as (name, raw value) pairs, in order.
+    pub params: Vec<(String, String)>,
+}
+
+impl Args {
+    /// Parses `args`, which shouldn't include the name of the program.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ArgsError::Help` if help was requested and an error describing
+    /// the first invalid argument otherwise.
+    pub fn parse<I, S>(args: I) -> ::std::result::Result<Args, ArgsError>
+        where I: IntoIterator<Item = S>, S: Into<String>
+    {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter().map(|arg| arg.into());
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match (arg.starts_with('-'), arg.find('=')) {
+                (true, Some(i)) => (&arg[..i], Some(arg[(i + 1)..].to_string())),
+                _ => (&arg[..], None)
+            };
+
+            let name = match flag {
+                "--help" | "-h" => return Err(ArgsError::Help),
+                "--config" | "-c" => "--config",
+                "--env" | "-e" => "--env",
+                "--address" | "-a" => "--address",
+                "--port" | "-p" => "--port",
+                _ if flag.starts_with('-') => return Err(ArgsError::Unknown(arg.clone())),
+                _ => {
+                    parsed.params.push(Args::param(&arg)?);
+                    continue;
+                }
+            };
+
+            let value = match inline.or_else(|| args.next()) {
+                Some(value) => value,
+                None => return Err(ArgsError::MissingValue(name))
+            };
+
+            let bad_value = |reason: String| {
+                ArgsError::BadValue(name.to_string(), value.clone(), reason)
+            };
+
+            match name {
+                "--config" => parsed.config = Some(PathBuf::from(&value)),
+                "--env" => {
+                    let reason = || bad_value("invalid environment name".into());
+                    parsed.environment = Some(value.parse().map_err(|_| reason())?);
+                }
+                "--address" => parsed.address = Some(value.clone()),
+                _ => parsed.port = Some(value.parse().map_err(|e| bad_value(format!("{}", e)))?),
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// Parses a `name=value` parameter.
+    fn param(arg: &str) -> ::std::result::Result<(String, String), ArgsError> {
+        let (name, value) = match arg.find('=') {
+            Some(i) if i > 0 => (arg[..i].trim().to_lowercase(), &arg[(i + 1)..]),
+            _ => return Err(ArgsError::Unknown(arg.into()))
+        };
+
+        if let Err(e) = parse_simple_toml_value(value) {
+            return Err(ArgsError::BadValue(name, value.into(), e));
+        }
+
+        Ok((name, value.into()))
+    }
+
+    /// Returns the usage message for a program named `program`.
+    pub fn usage(program: &str) -> String {
+        format!("Usage: {} [OPTIONS] [NAME=VALUE]...\n\n\
+            Options:\n    \
+            -c, --config <PATH>       read the configuration from PATH\n    \
+            -e, --env <ENVIRONMENT>   select the configuration environment\n    \
+            -a, --address <ADDRESS>   listen on ADDRESS\n    \
+            -p, --port <PORT>         listen on PORT\n    \
+            -h, --help                print this message\n\n\
+            NAME=VALUE sets the configuration parameter NAME to the TOML VALUE.",
+            program)
+    }
+
+    /// Returns a builder for the configuration these arguments select, with
+    /// the parameters they set applied.
+    ///
+    /// The configuration is read from the `--config` file, if given, and
+    /// otherwise from `Rocket.toml`, or the defaults if there is none, exactly
+    /// as [`rocket::ignite()`] would. `ROCKET_{PARAM}` environment variables
+    /// apply to both. The environment is the `--env` environment, if given,
+    /// and the active environment otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error reading the configuration, `BadEnv` if the selected
+    /// environment isn't declared, and a `NotFound` error if the `--config`
+    /// file doesn't exist. Parameters set with the arguments are applied when
+    /// the builder is finalized: values of the wrong type cause finalizing to
+    /// fail with `BadEnvVal`.
+    ///
+    /// [`rocket::ignite()`]: /rocket/fn.ignite.html
+    pub fn builder(&self) -> Result<ConfigBuilder> {
+        let mut rocket_config = match self.config {
+            Some(ref path) => {
+                let cwd = env::current_dir().map_err(|_| ConfigError::BadCWD)?;
+                RocketConfig::read_file(&cwd.join(path))?
+            }
+            None => match RocketConfig::read() {
+                Err(ConfigError::NotFound) => {
+                    let cwd = env::current_dir().map_err(|_| ConfigError::BadCWD)?;
+                    let default = cwd.join(&format!(".{}.{}", "default", CONFIG_FILENAME));
+                    RocketConfig::active_default(&default)?
+                }
+                result => result?
+            }
+        };
+
+        if let Some(ref env) = self.environment {
+            rocket_config.active_env = env.clone();
+            rocket_config.check_active()?;
+        }
+
+        let mut builder = ConfigBuilder::from_base(rocket_config.into_active());
+        if let Some(ref address) = self.address {
+            builder = builder.address(address.clone());
+        }
+
+        if let Some(port) = self.port {
+            builder = builder.port(port);
+        }
+
+        builder.env_vars.extend(self.params.iter().cloned());
+        Ok(builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{Args, ArgsError};
+    use config::Environment;
+
+    #[test]
+    fn test_parse_args() {
+        let args = Args::parse(vec!["-c", "app.toml", "--env=prod", "--address", "0.0.0.0",
+                                    "--port=80", "workers=4", "motd=\"hi there\""]).unwrap();
+
+        assert_eq!(args, Args {
+            config: Some(PathBuf::from("app.toml")),
+            environment: Some(Environment::Production),
+            address: Some("0.0.0.0".into()),
+            port: Some(80),
+            params: vec![("workers".into(), "4".into()), ("motd".into(), "\"hi there\"".into())],
+        });
+
+        assert_eq!(Args::parse(Vec::<String>::new()).unwrap(), Args::default());
+    }
+
+    #[test]
+    fn test_bad_args() {
+        assert_eq!(Args::parse(vec!["-p", "80", "--help"]), Err(ArgsError::Help));
+        assert_eq!(Args::parse(vec!["--verbose"]), Err(ArgsError::Unknown("--verbose".into())));
+        assert_eq!(Args::parse(vec!["workers"]), Err(ArgsError::Unknown("workers".into())));
+        assert_eq!(Args::parse(vec!["=4"]), Err(ArgsError::Unknown("=4".into())));
+        assert_eq!(Args::parse(vec!["--port"]), Err(ArgsError::MissingValue("--port")));
+        assert!(Args::parse(vec!["--port", "http"]).is_err());
+        assert!(Args::parse(vec!["--port=65536"]).is_err());
+        assert!(Args::parse(vec!["-e", "testing"]).is_ok());
+        assert!(Args::parse(vec!["-e", ""]).is_err());
+        assert!(Args::parse(vec!["list=[1, 2"]).is_err());
+    }
+}
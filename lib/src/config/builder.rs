@@ -1,7 +1,14 @@
 use std::collections::HashMap;
+use std::env;
+use std::fs::File;
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
 
-use config::{Result, Config, Value, Environment, Limits, LoggingLevel};
+use config::{Result, Config, Value, Environment, Limits, HeaderPolicy, Hsts, LoggingLevel};
+use config::{CookieLimits, CompressionConfig, MutualTls};
+use config::{ConfigError, RocketConfig, env_vars, set_from_env};
+use data::{ChecksumAlgorithm, TextEncoding};
+use http::CookieCipher;
 
 /// Structure following the builder pattern for building `Config` structures.
 #[derive(Clone)]
@@ -16,18 +23,98 @@ pub struct ConfigBuilder {
     pub workers: u16,
     /// Keep-alive timeout in seconds or None if disabled.
     pub keep_alive: Option<u32>,
+    /// Maximum number of idle keep-alive connections or None if unbounded.
+    pub max_idle_connections: Option<u32>,
+    /// Maximum number of open connections or None if unbounded.
+    pub max_connections: Option<u32>,
+    /// Maximum number of requests served on one connection or None if
+    /// unbounded.
+    pub max_requests_per_connection: Option<u32>,
+    /// The length of the queue of pending connections or None for the system
+    /// default.
+    pub backlog: Option<u32>,
+    /// Maximum number of open connections from one IP address or None if
+    /// unbounded.
+    pub max_connections_per_ip: Option<u32>,
+    /// The IP networks exempt from `max_connections_per_ip`.
+    pub connection_limit_exempt: Vec<String>,
+    /// Maximum size, in bytes, of a request's head.
+    pub max_header_size: u32,
+    /// Maximum number of headers in a request.
+    pub max_header_count: u32,
     /// How much information to log.
     pub log_level: LoggingLevel,
     /// The secret key.
     pub secret_key: Option<String>,
     /// TLS configuration (path to certificates file, path to private key file).
     pub tls: Option<(String, String)>,
+    /// In-memory TLS configuration (certificates, private key, and optionally
+    /// the certificate store, all in PEM format).
+    pub tls_bytes: Option<(Vec<u8>, Vec<u8>, Option<Vec<u8>>)>,
+    /// Certificates served by SNI hostname (hostname, path to certificates
+    /// file, path to private key file).
+    pub tls_sni: Vec<(String, String, String)>,
+    /// Path to the certificate store client certificates are verified against.
+    pub cert_store_path: Option<String>,
+    /// Path to the DER-encoded OCSP response stapled to TLS handshakes.
+    pub tls_ocsp_response_path: Option<String>,
+    /// Whether TLS clients must authenticate with a certificate.
+    pub mutual_tls: MutualTls,
+    /// Number of seconds new TLS connections have to complete the handshake
+    /// or None if unbounded.
+    pub tls_handshake_timeout: Option<u32>,
+    /// Number of seconds between rereads of the stapled OCSP response or None
+    /// if it's only read at launch.
+    pub tls_ocsp_refresh: Option<u32>,
+    /// The protocols advertised to TLS clients via ALPN.
+    pub tls_alpn: Vec<String>,
+    /// The names of the TLS protocol versions clients may negotiate.
+    pub tls_versions: Vec<String>,
+    /// The names of the TLS cipher suites clients may negotiate.
+    pub tls_ciphersuites: Vec<String>,
+    /// The SHA-256 fingerprints, in hexadecimal, of the client certificates
+    /// `AllowedClient` accepts.
+    pub mtls_allowed_fingerprints: Vec<String>,
     /// Size limits.
     pub limits: Limits,
+    /// Whether to verify request bodies against `Content-MD5`/`Digest`.
+    pub verify_checksums: bool,
+    /// The value of the `Server` header or None if the header is omitted.
+    pub server_name: Option<String>,
+    /// Policy for scrubbing response headers.
+    pub header_policy: HeaderPolicy,
+    /// The configured state of each feature toggle.
+    pub features: HashMap<String, bool>,
+    /// The hosts requests may be sent to or empty if any host is allowed.
+    pub allowed_hosts: Vec<String>,
+    /// The HSTS policy sent over TLS or None if no policy is sent.
+    pub hsts: Option<Hsts>,
+    /// Whether plaintext requests asking to be upgraded are redirected to HTTPS.
+    pub upgrade_insecure_requests: bool,
+    /// The port HTTPS is served on.
+    pub https_port: u16,
+    /// The IP networks of proxies trusted to report the request's scheme.
+    pub trusted_proxies: Vec<String>,
+    /// The algorithm used to digest response bodies or None if they aren't.
+    pub response_digest: Option<ChecksumAlgorithm>,
+    /// Limits on the number and size of request and response cookies.
+    pub cookie_limits: CookieLimits,
+    /// The algorithm private cookies are encrypted with.
+    pub private_cookie_cipher: CookieCipher,
+    /// Compression settings or None if bodies aren't compressed.
+    pub compression: Option<CompressionConfig>,
+    /// How text request bodies are decoded.
+    pub text_encoding: Option<TextEncoding>,
     /// Any extra parameters that aren't part of Rocket's config.
     pub extras: HashMap<String, Value>,
+    /// Parameters read from environment variables, as (name, raw value) pairs,
+    /// applied over all other parameters.
+    pub env_vars: Vec<(String, String)>,
     /// The root directory of this config.
     pub root: PathBuf,
+    /// The configuration read from a TOML document, if any, which carries the
+    /// secret key and TLS settings that have no builder representation.
+    base: Option<Config>,
 }
 
 impl ConfigBuilder {
@@ -59,22 +146,155 @@ impl ConfigBuilder {
         let config = Config::new(environment)
             .expect("ConfigBuilder::new(): couldn't get current directory.");
 
-        let root_dir = PathBuf::from(config.root());
+        ConfigBuilder::from_config(&config)
+    }
+
+    /// Returns a builder whose parameters are those of `config`.
+    fn from_config(config: &Config) -> ConfigBuilder {
         ConfigBuilder {
-            environment: config.environment,
-            address: config.address,
+            environment: config.environment.clone(),
+            address: config.address.clone(),
             port: config.port,
             workers: config.workers,
             keep_alive: config.keep_alive,
+            max_idle_connections: config.max_idle_connections,
+            max_connections: config.max_connections,
+            max_requests_per_connection: config.max_requests_per_connection,
+            backlog: config.backlog,
+            max_connections_per_ip: config.max_connections_per_ip,
+            connection_limit_exempt: config.connection_limit_exempt.clone(),
+            max_header_size: config.max_header_size,
+            max_header_count: config.max_header_count,
             log_level: config.log_level,
             secret_key: None,
             tls: None,
-            limits: config.limits,
-            extras: config.extras,
-            root: root_dir,
+            tls_bytes: None,
+            tls_sni: vec![],
+            cert_store_path: None,
+            tls_ocsp_response_path: None,
+            mutual_tls: config.mutual_tls,
+            tls_handshake_timeout: config.tls_handshake_timeout,
+            tls_ocsp_refresh: config.tls_ocsp_refresh,
+            tls_alpn: config.tls_alpn.clone(),
+            tls_versions: config.tls_versions.clone(),
+            tls_ciphersuites: config.tls_ciphersuites.clone(),
+            mtls_allowed_fingerprints: config.mtls_allowed_fingerprints.iter()
+                .map(|digest| digest.iter().map(|b| format!("{:02x}", b)).collect())
+                .collect(),
+            limits: config.limits.clone(),
+            verify_checksums: config.verify_checksums,
+            server_name: config.server_name.clone(),
+            header_policy: config.header_policy.clone(),
+            features: config.features.clone(),
+            allowed_hosts: config.allowed_hosts.clone(),
+            hsts: config.hsts,
+            upgrade_insecure_requests: config.upgrade_insecure_requests,
+            https_port: config.https_port,
+            trusted_proxies: config.trusted_proxies.clone(),
+            response_digest: config.response_digest,
+            cookie_limits: config.cookie_limits,
+            private_cookie_cipher: config.private_cookie_cipher,
+            compression: config.compression,
+            text_encoding: config.text_encoding,
+            extras: config.extras.clone(),
+            env_vars: vec![],
+            root: PathBuf::from(config.root()),
+            base: None,
         }
     }
 
+    /// Creates a `ConfigBuilder` from the TOML document `src`, interpreted
+    /// exactly as a `Rocket.toml` file would be: the parameters are those of
+    /// the active environment, as determined by `ROCKET_ENV`, including any
+    /// `[global]` parameters and `ROCKET_{PARAM}` environment variables. The
+    /// root configuration directory is set to the current working directory.
+    ///
+    /// Parameters can be further customized with the builder's methods before
+    /// the configuration is finalized.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the current directory cannot be retrieved, if `src`
+    /// isn't valid TOML, or if any parameter is invalid.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::ConfigBuilder;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let config = ConfigBuilder::from_toml_str(r#"
+    ///     [global]
+    ///     port = 9234
+    ///     motd = "hello"
+    /// "#)?.workers(3).finalize()?;
+    ///
+    /// assert_eq!(config.port, 9234);
+    /// assert_eq!(config.workers, 3);
+    /// assert_eq!(config.get_str("motd"), Ok("hello"));
+    /// # Ok(())
+    /// # }
+    /// # config_test().unwrap();
+    /// ```
+    pub fn from_toml_str(src: &str) -> Result<ConfigBuilder> {
+        let cwd = env::current_dir().map_err(|_| ConfigError::BadCWD)?;
+        ConfigBuilder::from_toml(src.to_string(), cwd.join("Rocket.custom.toml"))
+    }
+
+    /// Creates a `ConfigBuilder` from the TOML file at `path`, interpreted
+    /// exactly as a `Rocket.toml` file would be. See
+    /// [`from_toml_str()`](#method.from_toml_str) for details. The root
+    /// configuration directory is set to the directory containing the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `NotFound` error if there is no file at `path`, an `IoError`
+    /// if it can't be read, and otherwise the same errors as
+    /// [`from_toml_str()`](#method.from_toml_str).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::ConfigBuilder;
+    ///
+    /// # if false {
+    /// let path = ::std::env::args().nth(1).unwrap_or("/etc/myapp/rocket.toml".into());
+    /// let config = ConfigBuilder::from_toml_file(path)
+    ///     .and_then(|builder| builder.workers(8).finalize());
+    ///
+    /// let rocket = rocket::custom(config.expect("valid configuration"), true);
+    /// # }
+    /// ```
+    pub fn from_toml_file<P: AsRef<Path>>(path: P) -> Result<ConfigBuilder> {
+        let cwd = env::current_dir().map_err(|_| ConfigError::BadCWD)?;
+        let path = cwd.join(path);
+
+        let mut file = File::open(&path).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => ConfigError::NotFound,
+            _ => ConfigError::IoError
+        })?;
+
+        let mut contents = String::new();
+        file.read_to_string(&mut contents).map_err(|_| ConfigError::IoError)?;
+        ConfigBuilder::from_toml(contents, path)
+    }
+
+    /// Returns a builder for the active configuration parsed from `src`, which
+    /// was read from `path`.
+    fn from_toml(src: String, path: PathBuf) -> Result<ConfigBuilder> {
+        let config = RocketConfig::parse(src, path)?.into_active();
+        Ok(ConfigBuilder::from_base(config))
+    }
+
+    /// Returns a builder for `config`, which carries the parameters that have
+    /// no builder representation.
+    pub(crate) fn from_base(config: Config) -> ConfigBuilder {
+        let mut builder = ConfigBuilder::from_config(&config);
+        builder.base = Some(config);
+        builder
+    }
+
     /// Sets the `address` in the configuration being built.
     ///
     /// # Example
@@ -157,6 +377,171 @@ impl ConfigBuilder {
         self
     }
 
+    /// Sets the maximum number of idle keep-alive connections to `max`. If
+    /// `max` is `None`, the number of idle connections is unbounded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .max_idle_connections(64)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.max_idle_connections, Some(64));
+    /// ```
+    #[inline]
+    pub fn max_idle_connections<T: Into<Option<u32>>>(mut self, max: T) -> Self {
+        self.max_idle_connections = max.into();
+        self
+    }
+
+    /// Sets the maximum number of open connections to `max`. If `max` is
+    /// `None`, the number of open connections is unbounded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .max_connections(1024)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.max_connections, Some(1024));
+    /// ```
+    #[inline]
+    pub fn max_connections<T: Into<Option<u32>>>(mut self, max: T) -> Self {
+        self.max_connections = max.into();
+        self
+    }
+
+    /// Sets the maximum number of requests served on one connection to `max`.
+    /// If `max` is `None`, the number of requests is unbounded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .max_requests_per_connection(100)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.max_requests_per_connection, Some(100));
+    /// ```
+    #[inline]
+    pub fn max_requests_per_connection<T: Into<Option<u32>>>(mut self, max: T) -> Self {
+        self.max_requests_per_connection = max.into();
+        self
+    }
+
+    /// Sets the length of the queue of pending connections to `backlog`. If
+    /// `backlog` is `None`, the system default is used.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .backlog(64)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.backlog, Some(64));
+    /// ```
+    #[inline]
+    pub fn backlog<T: Into<Option<u32>>>(mut self, backlog: T) -> Self {
+        self.backlog = backlog.into();
+        self
+    }
+
+    /// Sets the maximum number of open connections from any one client IP
+    /// address to `max`. If `max` is `None`, the number of connections per
+    /// address is unbounded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .max_connections_per_ip(16)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.max_connections_per_ip, Some(16));
+    /// ```
+    #[inline]
+    pub fn max_connections_per_ip<T: Into<Option<u32>>>(mut self, max: T) -> Self {
+        self.max_connections_per_ip = max.into();
+        self
+    }
+
+    /// Exempts the IP network `network`, an IP address or a network in CIDR
+    /// notation, from the `max_connections_per_ip` limit in the configuration
+    /// being built. Load balancers and proxies, which carry the connections of
+    /// many clients, are typically exempt.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .max_connections_per_ip(16)
+    ///     .connection_limit_exempt("10.0.0.0/8")
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.connection_limit_exempt, vec!["10.0.0.0/8"]);
+    /// ```
+    pub fn connection_limit_exempt<S: Into<String>>(mut self, network: S) -> Self {
+        self.connection_limit_exempt.push(network.into());
+        self
+    }
+
+    /// Sets the maximum size, in bytes, of a request's head in the
+    /// configuration being built. Requests with larger heads are answered
+    /// with `431 Request Header Fields Too Large`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .max_header_size(64 * 1024)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.max_header_size, 65536);
+    /// ```
+    #[inline]
+    pub fn max_header_size(mut self, max: u32) -> Self {
+        self.max_header_size = max;
+        self
+    }
+
+    /// Sets the maximum number of headers in a request in the configuration
+    /// being built. Requests with more headers are answered with `431 Request
+    /// Header Fields Too Large`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .max_header_count(32)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.max_header_count, 32);
+    /// ```
+    #[inline]
+    pub fn max_header_count(mut self, max: u32) -> Self {
+        self.max_header_count = max;
+        self
+    }
+
     /// Sets the `log_level` in the configuration being built.
     ///
     /// # Example
@@ -193,15 +578,23 @@ impl ConfigBuilder {
         self
     }
 
-    /// Sets the `limits` in the configuration being built.
+    /// Sets the `limits`, including the request body timeouts, in the
+    /// configuration being built.
     ///
     /// # Example
     ///
     /// ```rust
+    /// use std::time::Duration;
     /// use rocket::config::{Config, Environment, Limits};
+    /// use rocket::http::MediaType;
+    ///
+    /// let limits = Limits::new()
+    ///     .limit("json", 5 * (1 << 20))
+    ///     .read_timeout(Duration::from_secs(10))
+    ///     .body_timeout_for(MediaType::JSON, Duration::from_secs(30));
     ///
     /// let mut config = Config::build(Environment::Staging)
-    ///     .limits(Limits::new().limit("json", 5 * (1 << 20)))
+    ///     .limits(limits)
     ///     .unwrap();
     /// ```
     pub fn limits(mut self, limits: Limits) -> Self {
@@ -230,52 +623,162 @@ impl ConfigBuilder {
         where C: Into<String>, K: Into<String>
     {
         self.tls = Some((certs_path.into(), key_path.into()));
+        self.tls_bytes = None;
         self
     }
 
-    /// Sets the `environment` in the configuration being built.
+    /// Sets the TLS configuration in the configuration being built from
+    /// in-memory PEM data instead of files. Replaces any paths set via
+    /// [`tls()`](#method.tls).
+    ///
+    /// `certs` is the certificate chain in X.509 PEM format. `key` is the
+    /// private key, an RSA key in either PKCS#1 or PKCS#8 PEM format. If `ca`
+    /// is `Some`, it is the certificate store client certificates are verified
+    /// against and takes precedence over
+    /// [`cert_store_path()`](#method.cert_store_path).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// # let read_secret = |_: &str| vec![];
+    /// let certs = read_secret("tls/certs");
+    /// let key = read_secret("tls/key");
+    /// let mut config = Config::build(Environment::Staging)
+    ///     .tls_bytes(certs, key, None)
+    /// # ; /*
+    ///     .unwrap();
+    /// # */
+    /// ```
+    pub fn tls_bytes(mut self, certs: Vec<u8>, key: Vec<u8>, ca: Option<Vec<u8>>) -> Self {
+        self.tls_bytes = Some((certs, key, ca));
+        self.tls = None;
+        self
+    }
+
+    /// Sets the certificates served to TLS clients by the hostname they
+    /// request via SNI in the configuration being built. Each entry is a
+    /// `(hostname, certs_path, key_path)` triple; the files are in the same
+    /// formats as for [`tls()`](#method.tls). Clients that request any other
+    /// hostname, or none at all, are served the default certificates, which
+    /// must be set via `tls()` or [`tls_bytes()`](#method.tls_bytes).
     ///
     /// # Example
     ///
     /// ```rust
     /// use rocket::config::{Config, Environment};
     ///
+    /// let mut config = Config::build(Environment::Staging)
+    ///     .tls("/path/to/default.pem", "/path/to/default.key")
+    ///     .tls_sni(vec![
+    ///         ("api.example.com", "/path/to/api.pem", "/path/to/api.key"),
+    ///         ("www.example.com", "/path/to/www.pem", "/path/to/www.key"),
+    ///     ])
+    /// # ; /*
+    ///     .unwrap();
+    /// # */
+    /// ```
+    pub fn tls_sni<H, C, K>(mut self, certs: Vec<(H, C, K)>) -> Self
+        where H: Into<String>, C: Into<String>, K: Into<String>
+    {
+        self.tls_sni = certs.into_iter()
+            .map(|(hostname, certs, key)| (hostname.into(), certs.into(), key.into()))
+            .collect();
+
+        self
+    }
+
+    /// Sets the path to the certificate store that client certificates are
+    /// verified against when mutual TLS is enabled. The store must contain
+    /// X.509 certificates in PEM format.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment, MutualTls};
+    ///
+    /// let mut config = Config::build(Environment::Staging)
+    ///     .tls("/path/to/certs.pem", "/path/to/key.pem")
+    ///     .cert_store_path("/path/to/client_ca.pem")
+    ///     .mutual_tls(MutualTls::Required)
+    /// # ; /*
+    ///     .unwrap();
+    /// # */
+    /// ```
+    pub fn cert_store_path<P: Into<String>>(mut self, path: P) -> Self {
+        self.cert_store_path = Some(path.into());
+        self
+    }
+
+    /// Sets the mutual TLS policy in the configuration being built: whether
+    /// TLS clients must authenticate with a certificate from the certificate
+    /// store set via [`cert_store_path()`](#method.cert_store_path).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment, MutualTls};
+    ///
     /// let config = Config::build(Environment::Staging)
-    ///     .environment(Environment::Production)
+    ///     .mutual_tls(MutualTls::Optional)
     ///     .unwrap();
     ///
-    /// assert_eq!(config.environment, Environment::Production);
+    /// assert_eq!(config.mutual_tls, MutualTls::Optional);
     /// ```
     #[inline]
-    pub fn environment(mut self, env: Environment) -> Self {
-        self.environment = env;
+    pub fn mutual_tls(mut self, policy: MutualTls) -> Self {
+        self.mutual_tls = policy;
         self
     }
 
-    /// Sets the `root` in the configuration being built.
+    /// Sets the number of seconds new TLS connections have to complete the
+    /// handshake in the configuration being built. If `timeout` is `None`,
+    /// the handshake is unbounded.
     ///
     /// # Example
     ///
     /// ```rust
-    /// # use std::path::Path;
     /// use rocket::config::{Config, Environment};
     ///
     /// let config = Config::build(Environment::Staging)
-    ///     .root("/my_app/dir")
+    ///     .tls_handshake_timeout(5)
     ///     .unwrap();
     ///
-    /// assert_eq!(config.root(), Path::new("/my_app/dir"));
+    /// assert_eq!(config.tls_handshake_timeout, Some(5));
     /// ```
-    pub fn root<P: AsRef<Path>>(mut self, path: P) -> Self {
-        self.root = path.as_ref().to_path_buf();
+    #[inline]
+    pub fn tls_handshake_timeout<T: Into<Option<u32>>>(mut self, timeout: T) -> Self {
+        self.tls_handshake_timeout = timeout.into();
         self
     }
 
-    /// Adds an extra configuration parameter with `name` and `value` to the
-    /// configuration being built. The value can be any type that implements
-    /// `Into<Value>` including `&str`, `String`, `Vec<V: Into<Value>>`,
-    /// `HashMap<S: Into<String>, V: Into<Value>>`, and most integer and float
-    /// types.
+    /// Sets the path to the DER-encoded OCSP response stapled to TLS
+    /// handshakes that are served the default certificates in the
+    /// configuration being built. The certificates must be set via
+    /// [`tls()`](#method.tls) or [`tls_bytes()`](#method.tls_bytes).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let mut config = Config::build(Environment::Staging)
+    ///     .tls("/path/to/certs.pem", "/path/to/key.pem")
+    ///     .tls_ocsp_response("/path/to/ocsp.der")
+    ///     .tls_ocsp_refresh(60 * 60)
+    /// # ; /*
+    ///     .unwrap();
+    /// # */
+    /// ```
+    pub fn tls_ocsp_response<P: Into<String>>(mut self, path: P) -> Self {
+        self.tls_ocsp_response_path = Some(path.into());
+        self
+    }
+
+    /// Sets the number of seconds between rereads of the stapled OCSP
+    /// response in the configuration being built. If `refresh` is `None`, the
+    /// response is only reread when the certificates are reloaded.
     ///
     /// # Example
     ///
@@ -283,25 +786,43 @@ impl ConfigBuilder {
     /// use rocket::config::{Config, Environment};
     ///
     /// let config = Config::build(Environment::Staging)
-    ///     .extra("pi", 3.14)
-    ///     .extra("custom_dir", "/a/b/c")
+    ///     .tls_ocsp_refresh(60 * 60)
     ///     .unwrap();
     ///
-    /// assert_eq!(config.get_float("pi"), Ok(3.14));
-    /// assert_eq!(config.get_str("custom_dir"), Ok("/a/b/c"));
+    /// assert_eq!(config.tls_ocsp_refresh, Some(3600));
     /// ```
-    pub fn extra<V: Into<Value>>(mut self, name: &str, value: V) -> Self {
-        self.extras.insert(name.into(), value.into());
+    #[inline]
+    pub fn tls_ocsp_refresh<T: Into<Option<u32>>>(mut self, refresh: T) -> Self {
+        self.tls_ocsp_refresh = refresh.into();
         self
     }
 
-    /// Return the `Config` structure that was being built by this builder.
+    /// Sets the protocols advertised to TLS clients via ALPN, most preferred
+    /// first, in the configuration being built. See
+    /// [`Config::set_tls_alpn()`](/rocket/struct.Config.html#method.set_tls_alpn)
+    /// for which protocol names are valid.
     ///
-    /// # Errors
+    /// # Example
     ///
-    /// If the current working directory cannot be retrieved, returns a `BadCWD`
-    /// error. If the address or secret key fail to parse, returns a `BadType`
-    /// error.
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .tls_alpn(vec!["h2", "http/1.1"])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.tls_alpn, vec!["h2", "http/1.1"]);
+    /// ```
+    pub fn tls_alpn<S: Into<String>>(mut self, protocols: Vec<S>) -> Self {
+        self.tls_alpn = protocols.into_iter().map(|p| p.into()).collect();
+        self
+    }
+
+    /// Sets the TLS protocol versions clients may negotiate in the
+    /// configuration being built. See
+    /// [`Config::set_tls_versions()`](/rocket/struct.Config.html#method.set_tls_versions)
+    /// for the supported versions; any other causes
+    /// [`finalize()`](#method.finalize) to fail.
     ///
     /// # Example
     ///
@@ -309,39 +830,568 @@ impl ConfigBuilder {
     /// use rocket::config::{Config, Environment};
     ///
     /// let config = Config::build(Environment::Staging)
-    ///     .address("127.0.0.1")
-    ///     .port(700)
-    ///     .workers(12)
-    ///     .keep_alive(None)
-    ///     .finalize();
+    ///     .tls_versions(vec!["TLSv1.2", "TLSv1.3"])
+    ///     .unwrap();
     ///
-    /// assert!(config.is_ok());
+    /// assert_eq!(config.tls_versions, vec!["TLSv1.2", "TLSv1.3"]);
     ///
-    /// let config = Config::build(Environment::Staging)
-    ///     .address("123.123.123.123.123 whoops!")
+    /// let result = Config::build(Environment::Staging)
+    ///     .tls_versions(vec!["TLSv1.0"])
     ///     .finalize();
     ///
-    /// assert!(config.is_err());
+    /// assert!(result.is_err());
     /// ```
-    pub fn finalize(self) -> Result<Config> {
-        let mut config = Config::new(self.environment)?;
-        config.set_address(self.address)?;
-        config.set_port(self.port);
-        config.set_workers(self.workers);
+    pub fn tls_versions<S: Into<String>>(mut self, versions: Vec<S>) -> Self {
+        self.tls_versions = versions.into_iter().map(|v| v.into()).collect();
+        self
+    }
+
+    /// Sets the TLS cipher suites clients may negotiate, most preferred
+    /// first, in the configuration being built. See
+    /// [`Config::set_tls_ciphersuites()`](/rocket/struct.Config.html#method.set_tls_ciphersuites)
+    /// for the supported cipher suites; any other causes
+    /// [`finalize()`](#method.finalize) to fail.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .tls_ciphersuites(vec![
+    ///         "TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384",
+    ///         "TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256",
+    ///     ])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.tls_ciphersuites.len(), 2);
+    /// ```
+    pub fn tls_ciphersuites<S: Into<String>>(mut self, ciphersuites: Vec<S>) -> Self {
+        self.tls_ciphersuites = ciphersuites.into_iter().map(|c| c.into()).collect();
+        self
+    }
+
+    /// Adds `fingerprint`, the SHA-256 fingerprint of a client certificate in
+    /// hexadecimal, to the fingerprints the
+    /// [`AllowedClient`](/rocket/tls/struct.AllowedClient.html) request guard
+    /// accepts in the configuration being built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let alice = "0bf5ad9e01f51f68cf44f2fdb0951bccf5157e0e7bc1487b84b3400bae928b8d";
+    /// let config = Config::build(Environment::Staging)
+    ///     .mtls_allowed_fingerprint(alice)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.mtls_allowed_fingerprints[0][..2], [0x0b, 0xf5]);
+    /// ```
+    pub fn mtls_allowed_fingerprint<S: Into<String>>(mut self, fingerprint: S) -> Self {
+        self.mtls_allowed_fingerprints.push(fingerprint.into());
+        self
+    }
+
+    /// Sets whether request bodies are verified against the checksum in the
+    /// `Content-MD5` or `Digest` header, when one is present. Bodies are
+    /// verified as they are read; those that aren't read to completion aren't
+    /// verified.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .verify_checksums(true)
+    ///     .unwrap();
+    ///
+    /// assert!(config.verify_checksums);
+    /// ```
+    #[inline]
+    pub fn verify_checksums(mut self, verify: bool) -> Self {
+        self.verify_checksums = verify;
+        self
+    }
+
+    /// Sets the value of the `Server` header sent with every response to
+    /// `name`. If `name` is `None`, the header is omitted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .server_name("MyApp")
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.server_name, Some("MyApp".to_string()));
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .server_name(None)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.server_name, None);
+    /// ```
+    #[inline]
+    pub fn server_name<'a, T: Into<Option<&'a str>>>(mut self, name: T) -> Self {
+        self.server_name = name.into().map(|name| name.to_string());
+        self
+    }
+
+    /// Sets the policy used to remove or rewrite response headers in the
+    /// configuration being built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment, HeaderPolicy};
+    ///
+    /// let policy = HeaderPolicy::new().deny("Server");
+    /// let config = Config::build(Environment::Staging)
+    ///     .header_policy(policy)
+    ///     .unwrap();
+    ///
+    /// assert!(!config.header_policy.is_allowed("Server"));
+    /// ```
+    #[inline]
+    pub fn header_policy(mut self, policy: HeaderPolicy) -> Self {
+        self.header_policy = policy;
+        self
+    }
+
+    /// Sets the configured state of the feature toggle `name` to `enabled` in
+    /// the configuration being built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .feature("beta_search", true)
+    ///     .feature("new_checkout", false)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.features.get("beta_search"), Some(&true));
+    /// assert_eq!(config.features.get("new_checkout"), Some(&false));
+    /// ```
+    pub fn feature<S: Into<String>>(mut self, name: S, enabled: bool) -> Self {
+        self.features.insert(name.into(), enabled);
+        self
+    }
+
+    /// Adds `host` to the hosts that requests may be sent to in the
+    /// configuration being built. Once any host is added, requests for other
+    /// hosts are rejected. See [`Host`](/rocket/request/struct.Host.html) for
+    /// the pattern syntax.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .allowed_host("example.com")
+    ///     .allowed_host("*.example.com")
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.allowed_hosts, vec!["example.com", "*.example.com"]);
+    /// ```
+    pub fn allowed_host<S: Into<String>>(mut self, host: S) -> Self {
+        self.allowed_hosts.push(host.into());
+        self
+    }
+
+    /// Sets the HSTS policy sent when TLS is enabled in the configuration
+    /// being built. If `hsts` is `None`, no policy is sent.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment, Hsts};
+    ///
+    /// let config = Config::build(Environment::Production)
+    ///     .hsts(Hsts::new(86400))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.hsts, Some(Hsts::new(86400)));
+    /// ```
+    #[inline]
+    pub fn hsts<H: Into<Option<Hsts>>>(mut self, hsts: H) -> Self {
+        self.hsts = hsts.into();
+        self
+    }
+
+    /// Sets whether plaintext requests with an `Upgrade-Insecure-Requests: 1`
+    /// header are redirected to HTTPS in the configuration being built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Production)
+    ///     .upgrade_insecure_requests(true)
+    ///     .unwrap();
+    ///
+    /// assert!(config.upgrade_insecure_requests);
+    /// ```
+    #[inline]
+    pub fn upgrade_insecure_requests(mut self, upgrade: bool) -> Self {
+        self.upgrade_insecure_requests = upgrade;
+        self
+    }
+
+    /// Sets the port HTTPS is served on, which upgraded requests are
+    /// redirected to, in the configuration being built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Production)
+    ///     .upgrade_insecure_requests(true)
+    ///     .https_port(8443)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.https_port, 8443);
+    /// ```
+    #[inline]
+    pub fn https_port(mut self, port: u16) -> Self {
+        self.https_port = port;
+        self
+    }
+
+    /// Trusts the proxies in the IP network `network`, an IP address or a
+    /// network in CIDR notation, to report the scheme of requests via
+    /// `X-Forwarded-Proto` in the configuration being built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Production)
+    ///     .trusted_proxy("10.0.0.0/8")
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.trusted_proxies, vec!["10.0.0.0/8"]);
+    /// ```
+    pub fn trusted_proxy<S: Into<String>>(mut self, network: S) -> Self {
+        self.trusted_proxies.push(network.into());
+        self
+    }
+
+    /// Sets the algorithm used to digest response bodies in the configuration
+    /// being built. If `algorithm` is `None`, no digest is computed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    /// use rocket::data::ChecksumAlgorithm;
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .response_digest(ChecksumAlgorithm::Sha256)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.response_digest, Some(ChecksumAlgorithm::Sha256));
+    /// ```
+    #[inline]
+    pub fn response_digest<A: Into<Option<ChecksumAlgorithm>>>(mut self, algorithm: A) -> Self {
+        self.response_digest = algorithm.into();
+        self
+    }
+
+    /// Sets the limits on the number and total size of request and response
+    /// cookies in the configuration being built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment, CookieLimits, CookieOverflow};
+    ///
+    /// let limits = CookieLimits::new(20, 4096).overflow(CookieOverflow::TruncateOldest);
+    /// let config = Config::build(Environment::Staging)
+    ///     .cookie_limits(limits)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.cookie_limits, limits);
+    /// ```
+    #[inline]
+    pub fn cookie_limits(mut self, limits: CookieLimits) -> Self {
+        self.cookie_limits = limits;
+        self
+    }
+
+    /// Sets the algorithm private cookies are encrypted with in the
+    /// configuration being built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    /// use rocket::http::CookieCipher;
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .private_cookie_cipher(CookieCipher::ChaCha20Poly1305)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.private_cookie_cipher, CookieCipher::ChaCha20Poly1305);
+    /// ```
+    #[inline]
+    pub fn private_cookie_cipher(mut self, cipher: CookieCipher) -> Self {
+        self.private_cookie_cipher = cipher;
+        self
+    }
+
+    /// Sets the compression settings in the configuration being built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment, CompressionConfig};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .compression(CompressionConfig::default().min_size(256))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.compression.unwrap().min_body_size(), 256);
+    /// ```
+    #[inline]
+    pub fn compression<C: Into<Option<CompressionConfig>>>(mut self, compression: C) -> Self {
+        self.compression = compression.into();
+        self
+    }
+
+    /// Sets how text request bodies are decoded in the configuration being
+    /// built. If `encoding` is `None`, only valid UTF-8 is accepted,
+    /// regardless of charset.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    /// use rocket::data::TextEncoding;
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .text_encoding(TextEncoding::Lossy)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.text_encoding, Some(TextEncoding::Lossy));
+    /// ```
+    #[inline]
+    pub fn text_encoding<E: Into<Option<TextEncoding>>>(mut self, encoding: E) -> Self {
+        self.text_encoding = encoding.into();
+        self
+    }
+
+    /// Sets the `environment` in the configuration being built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .environment(Environment::Production)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.environment, Environment::Production);
+    /// ```
+    #[inline]
+    pub fn environment(mut self, env: Environment) -> Self {
+        self.environment = env;
+        self
+    }
+
+    /// Sets the `root` in the configuration being built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::path::Path;
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .root("/my_app/dir")
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.root(), Path::new("/my_app/dir"));
+    /// ```
+    pub fn root<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.root = path.as_ref().to_path_buf();
+        self
+    }
+
+    /// Adds an extra configuration parameter with `name` and `value` to the
+    /// configuration being built. The value can be any type that implements
+    /// `Into<Value>` including `&str`, `String`, `Vec<V: Into<Value>>`,
+    /// `HashMap<S: Into<String>, V: Into<Value>>`, and most integer and float
+    /// types.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .extra("pi", 3.14)
+    ///     .extra("custom_dir", "/a/b/c")
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.get_float("pi"), Ok(3.14));
+    /// assert_eq!(config.get_str("custom_dir"), Ok("/a/b/c"));
+    /// ```
+    pub fn extra<V: Into<Value>>(mut self, name: &str, value: V) -> Self {
+        self.extras.insert(name.into(), value.into());
+        self
+    }
+
+    /// Overlays parameters read from environment variables named
+    /// `{PREFIX}{PARAM}`, where the prefix is compared case-insensitively, on
+    /// the configuration being built. For instance, with a prefix of `APP_`,
+    /// `APP_PORT=8000` sets the `port` parameter.
+    ///
+    /// Values are parsed as TOML, as for `ROCKET_{PARAM}` variables; see the
+    /// [module level documentation](/rocket/config/index.html#environment-variables).
+    /// Variables that don't name a standard parameter set extras. The
+    /// variables are read when this method is called and applied when the
+    /// configuration is finalized, over all other parameters, no matter the
+    /// order of the builder calls. Values that don't parse or have the wrong
+    /// type for their parameter cause finalizing to fail with `BadEnvVal`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::env;
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// env::set_var("MYAPP_PORT", "9234");
+    /// env::set_var("MYAPP_GREETING", "\"Hello!\"");
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .port(8000)
+    ///     .merge_env("MYAPP_")
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.port, 9234);
+    /// assert_eq!(config.get_str("greeting"), Ok("Hello!"));
+    /// ```
+    pub fn merge_env(mut self, prefix: &str) -> Self {
+        self.env_vars.extend(env_vars(prefix));
+        self
+    }
+
+    /// Return the `Config` structure that was being built by this builder.
+    ///
+    /// # Errors
+    ///
+    /// If the current working directory cannot be retrieved, returns a `BadCWD`
+    /// error. If the address or secret key fail to parse, returns a `BadType`
+    /// error. If an environment variable merged via
+    /// [`merge_env()`](#method.merge_env) is invalid, returns a `BadEnvVal`
+    /// error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .address("127.0.0.1")
+    ///     .port(700)
+    ///     .workers(12)
+    ///     .keep_alive(None)
+    ///     .finalize();
+    ///
+    /// assert!(config.is_ok());
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .address("123.123.123.123.123 whoops!")
+    ///     .finalize();
+    ///
+    /// assert!(config.is_err());
+    /// ```
+    pub fn finalize(self) -> Result<Config> {
+        let mut config = match self.base {
+            Some(mut base) => {
+                base.environment = self.environment;
+                base
+            }
+            None => Config::new(self.environment)?
+        };
+
+        config.set_address(self.address)?;
+        config.set_port(self.port);
+        config.set_workers(self.workers);
         config.set_keep_alive(self.keep_alive);
+        config.set_max_idle_connections(self.max_idle_connections);
+        config.set_max_connections(self.max_connections);
+        config.set_max_requests_per_connection(self.max_requests_per_connection);
+        config.set_backlog(self.backlog);
+        config.set_max_connections_per_ip(self.max_connections_per_ip);
+        config.set_connection_limit_exempt(self.connection_limit_exempt)?;
+        config.set_max_header_size(self.max_header_size)?;
+        config.set_max_header_count(self.max_header_count)?;
         config.set_log_level(self.log_level);
         config.set_extras(self.extras);
         config.set_root(self.root);
         config.set_limits(self.limits);
+        config.set_verify_checksums(self.verify_checksums);
+        config.set_server_name(self.server_name.as_ref().map(|name| name.as_str()));
+        config.set_header_policy(self.header_policy);
+        config.set_features(self.features);
+        config.set_allowed_hosts(self.allowed_hosts)?;
+        config.set_hsts(self.hsts);
+        config.set_upgrade_insecure_requests(self.upgrade_insecure_requests);
+        config.set_https_port(self.https_port);
+        config.set_trusted_proxies(self.trusted_proxies)?;
+        config.set_response_digest(self.response_digest);
+        config.set_cookie_limits(self.cookie_limits);
+        config.set_private_cookie_cipher(self.private_cookie_cipher);
+        config.set_compression(self.compression);
+        config.set_text_encoding(self.text_encoding);
+        config.set_mutual_tls(self.mutual_tls);
+        config.set_tls_handshake_timeout(self.tls_handshake_timeout);
+        config.set_tls_ocsp_refresh(self.tls_ocsp_refresh);
+        config.set_tls_alpn(self.tls_alpn)?;
+        config.set_tls_versions(self.tls_versions)?;
+        config.set_tls_ciphersuites(self.tls_ciphersuites)?;
+        config.set_mtls_allowed_fingerprints(self.mtls_allowed_fingerprints)?;
 
         if let Some((certs_path, key_path)) = self.tls {
             config.set_tls(&certs_path, &key_path)?;
         }
 
+        if let Some(cert_store_path) = self.cert_store_path {
+            config.set_cert_store(&cert_store_path)?;
+        }
+
+        if let Some((certs, key, ca)) = self.tls_bytes {
+            config.set_tls_bytes(&certs, &key, ca.as_ref().map(|ca| &ca[..]))?;
+        }
+
+        for (hostname, certs_path, key_path) in self.tls_sni {
+            config.set_tls_sni(&hostname, &certs_path, &key_path)?;
+        }
+
+        if let Some(ocsp_path) = self.tls_ocsp_response_path {
+            config.set_tls_ocsp_response(&ocsp_path)?;
+        }
+
         if let Some(key) = self.secret_key {
             config.set_secret_key(key)?;
         }
 
+        for (key, val) in self.env_vars {
+            set_from_env(&mut config, &key, &val)?;
+        }
+
         Ok(config)
     }
 
@@ -9,8 +9,14 @@ use super::custom_values::*;
 use {num_cpus, base64};
 use config::Environment::*;
 use config::{Result, ConfigBuilder, Environment, ConfigError, LoggingLevel};
+use request::Host;
+use listener::{IpNetwork, MAX_HEADER_SIZE, MAX_HEADER_COUNT};
+use data::{ChecksumAlgorithm, TextEncoding};
 use config::{Table, Value, Array, Datetime};
-use http::Key;
+use http::{Key, CookieCipher};
+use http::uncased::uncased_eq;
+use security::{Secret, derive_key};
+use serde::de::DeserializeOwned;
 
 /// Structure for Rocket application configuration.
 ///
@@ -49,14 +55,87 @@ pub struct Config {
     pub workers: u16,
     /// Keep-alive timeout in seconds or None if disabled.
     pub keep_alive: Option<u32>,
+    /// Maximum number of idle keep-alive connections or None if unbounded.
+    pub max_idle_connections: Option<u32>,
+    /// Maximum number of open connections or None if unbounded.
+    pub max_connections: Option<u32>,
+    /// Maximum number of requests served on one connection or None if
+    /// unbounded.
+    pub max_requests_per_connection: Option<u32>,
+    /// The length of the queue of pending connections or None for the system
+    /// default.
+    pub backlog: Option<u32>,
+    /// Maximum number of open connections from one IP address or None if
+    /// unbounded.
+    pub max_connections_per_ip: Option<u32>,
+    /// The IP networks exempt from `max_connections_per_ip`.
+    pub connection_limit_exempt: Vec<String>,
+    /// Maximum size, in bytes, of a request's head.
+    pub max_header_size: u32,
+    /// Maximum number of headers in a request.
+    pub max_header_count: u32,
     /// How much information to log.
     pub log_level: LoggingLevel,
     /// The secret key.
     pub(crate) secret_key: SecretKey,
     /// TLS configuration.
     pub(crate) tls: Option<TlsConfig>,
+    /// The certificates served to clients that request a hostname via SNI.
+    pub(crate) tls_sni: Vec<(String, TlsConfig)>,
+    /// The OCSP response stapled to handshakes served the default certificate.
+    pub(crate) tls_ocsp: Option<OcspResponse>,
+    /// The certificates client certificates are verified against.
+    pub(crate) cert_store: Option<CertStore>,
+    /// Whether TLS clients must authenticate with a certificate.
+    pub mutual_tls: MutualTls,
+    /// Number of seconds new TLS connections have to complete the handshake
+    /// or None if unbounded.
+    pub tls_handshake_timeout: Option<u32>,
+    /// Number of seconds between rereads of the stapled OCSP response or None
+    /// if it's only read at launch.
+    pub tls_ocsp_refresh: Option<u32>,
+    /// The protocols advertised to TLS clients via ALPN, most preferred first.
+    pub tls_alpn: Vec<String>,
+    /// The TLS protocol versions clients may negotiate, or all that are
+    /// supported if empty.
+    pub tls_versions: Vec<String>,
+    /// The TLS cipher suites clients may negotiate, most preferred first, or
+    /// all that are supported if empty.
+    pub tls_ciphersuites: Vec<String>,
+    /// The SHA-256 fingerprints of the client certificates `AllowedClient`
+    /// accepts.
+    pub mtls_allowed_fingerprints: Vec<[u8; 32]>,
     /// Streaming read size limits.
     pub limits: Limits,
+    /// Whether to verify request bodies against `Content-MD5`/`Digest`.
+    pub verify_checksums: bool,
+    /// The value of the `Server` header or None if the header is omitted.
+    pub server_name: Option<String>,
+    /// Policy for scrubbing response headers.
+    pub header_policy: HeaderPolicy,
+    /// The configured state of each feature toggle.
+    pub features: HashMap<String, bool>,
+    /// The hosts requests may be sent to or empty if any host is allowed.
+    pub allowed_hosts: Vec<String>,
+    /// The HSTS policy sent over TLS or None if no policy is sent.
+    pub hsts: Option<Hsts>,
+    /// Whether plaintext requests asking to be upgraded are redirected to HTTPS.
+    pub upgrade_insecure_requests: bool,
+    /// The port HTTPS is served on, which upgraded requests are redirected to.
+    pub https_port: u16,
+    /// The IP networks of proxies trusted to report the request's scheme.
+    pub trusted_proxies: Vec<String>,
+    /// The algorithm used to digest response bodies or None if they aren't.
+    pub response_digest: Option<ChecksumAlgorithm>,
+    /// Limits on the number and size of request and response cookies.
+    pub cookie_limits: CookieLimits,
+    /// The algorithm private cookies are encrypted with.
+    pub private_cookie_cipher: CookieCipher,
+    /// Compression settings or None if bodies aren't compressed.
+    pub compression: Option<CompressionConfig>,
+    /// How text request bodies are decoded or None if only valid UTF-8 is
+    /// accepted, regardless of charset.
+    pub text_encoding: Option<TextEncoding>,
     /// Extra parameters that aren't part of Rocket's core config.
     pub extras: HashMap<String, Value>,
     /// The path to the configuration file this config belongs to.
@@ -217,6 +296,8 @@ impl Config {
     /// Returns the default configuration for the environment `env` given that
     /// the configuration was stored at `config_path`. If `config_path` is not
     /// an absolute path, an `Err` of `ConfigError::BadFilePath` is returned.
+    /// Custom environments have the same defaults as the production
+    /// environment.
     ///
     /// # Panics
     ///
@@ -244,10 +325,42 @@ impl Config {
                     port: 8000,
                     workers: default_workers,
                     keep_alive: Some(5),
+                    max_idle_connections: None,
+                    max_connections: None,
+                    max_requests_per_connection: None,
+                    backlog: None,
+                    max_connections_per_ip: None,
+                    connection_limit_exempt: vec![],
+                    max_header_size: MAX_HEADER_SIZE,
+                    max_header_count: MAX_HEADER_COUNT,
                     log_level: LoggingLevel::Normal,
                     secret_key: key,
                     tls: None,
+                    tls_sni: vec![],
+                    tls_ocsp: None,
+                    cert_store: None,
+                    mutual_tls: MutualTls::Disabled,
+                    tls_handshake_timeout: Some(10),
+                    tls_ocsp_refresh: None,
+                    tls_alpn: vec![],
+                    tls_versions: vec![],
+                    tls_ciphersuites: vec![],
+                    mtls_allowed_fingerprints: vec![],
                     limits: Limits::default(),
+                    verify_checksums: false,
+                    server_name: Some("Rocket".to_string()),
+                    header_policy: HeaderPolicy::default(),
+                    features: HashMap::new(),
+                    allowed_hosts: vec![],
+                    hsts: None,
+                    upgrade_insecure_requests: false,
+                    https_port: 443,
+                    trusted_proxies: vec![],
+                    response_digest: None,
+                    cookie_limits: CookieLimits::default(),
+                    private_cookie_cipher: CookieCipher::default(),
+                    compression: None,
+                    text_encoding: None,
                     extras: HashMap::new(),
                     config_path: config_path,
                 }
@@ -259,25 +372,89 @@ impl Config {
                     port: 8000,
                     workers: default_workers,
                     keep_alive: Some(5),
+                    max_idle_connections: None,
+                    max_connections: None,
+                    max_requests_per_connection: None,
+                    backlog: None,
+                    max_connections_per_ip: None,
+                    connection_limit_exempt: vec![],
+                    max_header_size: MAX_HEADER_SIZE,
+                    max_header_count: MAX_HEADER_COUNT,
                     log_level: LoggingLevel::Normal,
                     secret_key: key,
                     tls: None,
+                    tls_sni: vec![],
+                    tls_ocsp: None,
+                    cert_store: None,
+                    mutual_tls: MutualTls::Disabled,
+                    tls_handshake_timeout: Some(10),
+                    tls_ocsp_refresh: None,
+                    tls_alpn: vec![],
+                    tls_versions: vec![],
+                    tls_ciphersuites: vec![],
+                    mtls_allowed_fingerprints: vec![],
                     limits: Limits::default(),
+                    verify_checksums: false,
+                    server_name: Some("Rocket".to_string()),
+                    header_policy: HeaderPolicy::default(),
+                    features: HashMap::new(),
+                    allowed_hosts: vec![],
+                    hsts: None,
+                    upgrade_insecure_requests: false,
+                    https_port: 443,
+                    trusted_proxies: vec![],
+                    response_digest: None,
+                    cookie_limits: CookieLimits::default(),
+                    private_cookie_cipher: CookieCipher::default(),
+                    compression: None,
+                    text_encoding: None,
                     extras: HashMap::new(),
                     config_path: config_path,
                 }
             }
-            Production => {
+            Production | Custom(_) => {
                 Config {
-                    environment: Production,
+                    environment: env,
                     address: "0.0.0.0".to_string(),
                     port: 8000,
                     workers: default_workers,
                     keep_alive: Some(5),
+                    max_idle_connections: None,
+                    max_connections: None,
+                    max_requests_per_connection: None,
+                    backlog: None,
+                    max_connections_per_ip: None,
+                    connection_limit_exempt: vec![],
+                    max_header_size: MAX_HEADER_SIZE,
+                    max_header_count: MAX_HEADER_COUNT,
                     log_level: LoggingLevel::Critical,
                     secret_key: key,
                     tls: None,
+                    tls_sni: vec![],
+                    tls_ocsp: None,
+                    cert_store: None,
+                    mutual_tls: MutualTls::Disabled,
+                    tls_handshake_timeout: Some(10),
+                    tls_ocsp_refresh: None,
+                    tls_alpn: vec![],
+                    tls_versions: vec![],
+                    tls_ciphersuites: vec![],
+                    mtls_allowed_fingerprints: vec![],
                     limits: Limits::default(),
+                    verify_checksums: false,
+                    server_name: Some("Rocket".to_string()),
+                    header_policy: HeaderPolicy::default(),
+                    features: HashMap::new(),
+                    allowed_hosts: vec![],
+                    hsts: None,
+                    upgrade_insecure_requests: false,
+                    https_port: 443,
+                    trusted_proxies: vec![],
+                    response_digest: None,
+                    cookie_limits: CookieLimits::default(),
+                    private_cookie_cipher: CookieCipher::default(),
+                    compression: None,
+                    text_encoding: None,
                     extras: HashMap::new(),
                     config_path: config_path,
                 }
@@ -309,9 +486,49 @@ impl Config {
     ///   * **port**: Integer (16-bit unsigned)
     ///   * **workers**: Integer (16-bit unsigned)
     ///   * **keep_alive**: Integer or Boolean (false) or String ('none')
+    ///   * **max_idle_connections**: Integer or Boolean (false) or String ('none')
+    ///   * **max_connections**: Integer or Boolean (false) or String ('none')
+    ///   * **max_requests_per_connection**: Integer or Boolean (false) or
+    ///     String ('none')
+    ///   * **backlog**: Integer or Boolean (false) or String ('none')
+    ///   * **max_connections_per_ip**: Integer or Boolean (false) or String
+    ///     ('none')
+    ///   * **connection_limit_exempt**: Array (Strings)
+    ///   * **max_header_size**: Integer
+    ///   * **max_header_count**: Integer
     ///   * **log**: String
     ///   * **secret_key**: String (256-bit base64)
-    ///   * **tls**: Table (`certs` (path as String), `key` (path as String))
+    ///   * **tls**: Table (`certs` (path as String), `key` (path as String),
+    ///     `cert_store` (path as String, optional), `ocsp_response_path` (path
+    ///     as String, optional), `sni` (Table of Tables with `certs` and `key`,
+    ///     optional))
+    ///   * **mutual_tls**: String ('disabled', 'optional', or 'required')
+    ///   * **tls_handshake_timeout**: Integer or Boolean (false) or String
+    ///     ('none')
+    ///   * **tls_ocsp_refresh**: Integer or Boolean (false) or String ('none')
+    ///   * **tls_alpn**: Array (Strings)
+    ///   * **tls_versions**: Array (Strings)
+    ///   * **tls_ciphersuites**: Array (Strings)
+    ///   * **mtls_allowed_fingerprints**: Array (Strings)
+    ///   * **verify_checksums**: Boolean
+    ///   * **server_name**: String or Boolean (false)
+    ///   * **header_policy**: Table (`allow`, `deny` (arrays of Strings),
+    ///     `rewrite` (Table of Strings))
+    ///   * **features**: Table (Booleans)
+    ///   * **allowed_hosts**: Array (Strings)
+    ///   * **hsts**: Table (`max_age` (Integer), `include_subdomains`,
+    ///     `preload` (Booleans)) or Boolean
+    ///   * **upgrade_insecure_requests**: Boolean
+    ///   * **https_port**: Integer (16-bit unsigned)
+    ///   * **trusted_proxies**: Array (Strings)
+    ///   * **response_digest**: String ('MD5' or 'SHA-256') or Boolean (false)
+    ///   * **cookie_limits**: Table (`max_count`, `max_size` (Integers),
+    ///     `overflow` (String: 'reject', 'truncate_oldest', or 'log'))
+    ///   * **private_cookie_cipher**: String ('AES-256-GCM' or
+    ///     'ChaCha20-Poly1305')
+    ///   * **compression**: Table (`gzip`, `brotli`, `zstd` (Integers or
+    ///     Boolean (false)), `min_size` (Integer)) or Boolean
+    ///   * **text_encoding**: String ('strict' or 'lossy') or Boolean (false)
     pub(crate) fn set_raw(&mut self, name: &str, val: &Value) -> Result<()> {
         let (id, ok) = (|val| val, |_| Ok(()));
         config_from_raw!(self, name, val,
@@ -319,10 +536,39 @@ impl Config {
             port => (u16, set_port, ok),
             workers => (u16, set_workers, ok),
             keep_alive => (u32_option, set_keep_alive, ok),
+            max_idle_connections => (u32_option, set_max_idle_connections, ok),
+            max_connections => (u32_option, set_max_connections, ok),
+            max_requests_per_connection => (u32_option, set_max_requests_per_connection, ok),
+            backlog => (u32_option, set_backlog, ok),
+            max_connections_per_ip => (u32_option, set_max_connections_per_ip, ok),
+            connection_limit_exempt => (networks, set_connection_limit_exempt, id),
+            max_header_size => (u32, set_max_header_size, id),
+            max_header_count => (u32, set_max_header_count, id),
             log => (log_level, set_log_level, ok),
             secret_key => (str, set_secret_key, id),
             tls => (tls_config, set_raw_tls, id),
+            mutual_tls => (mutual_tls, set_mutual_tls, ok),
+            tls_handshake_timeout => (u32_option, set_tls_handshake_timeout, ok),
+            tls_ocsp_refresh => (u32_option, set_tls_ocsp_refresh, ok),
+            tls_alpn => (strings, set_tls_alpn, id),
+            tls_versions => (strings, set_tls_versions, id),
+            tls_ciphersuites => (strings, set_tls_ciphersuites, id),
+            mtls_allowed_fingerprints => (fingerprints, set_mtls_allowed_fingerprints, id),
             limits => (limits, set_limits, ok),
+            verify_checksums => (bool, set_verify_checksums, ok),
+            server_name => (server_name, set_server_name, ok),
+            header_policy => (header_policy, set_header_policy, ok),
+            features => (features, set_features, ok),
+            allowed_hosts => (hosts, set_allowed_hosts, id),
+            hsts => (hsts, set_hsts, ok),
+            upgrade_insecure_requests => (bool, set_upgrade_insecure_requests, ok),
+            https_port => (u16, set_https_port, ok),
+            trusted_proxies => (networks, set_trusted_proxies, id),
+            response_digest => (digest_algorithm, set_response_digest, ok),
+            cookie_limits => (cookie_limits, set_cookie_limits, ok),
+            private_cookie_cipher => (cookie_cipher, set_private_cookie_cipher, ok),
+            compression => (compression, set_compression, ok),
+            text_encoding => (text_encoding, set_text_encoding, ok),
             | _ => {
                 self.extras.insert(name.into(), val.clone());
                 Ok(())
@@ -450,96 +696,1151 @@ impl Config {
         self.keep_alive = timeout.into();
     }
 
+    /// Sets the maximum number of idle keep-alive connections to `max`. When
+    /// more connections than `max` are idle, the least recently used are
+    /// closed. If `max` is `None`, the number of idle connections is unbounded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// // Keep at most 128 idle connections open.
+    /// config.set_max_idle_connections(128);
+    ///
+    /// // Don't limit the number of idle connections.
+    /// config.set_max_idle_connections(None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_max_idle_connections<T: Into<Option<u32>>>(&mut self, max: T) {
+        self.max_idle_connections = max.into();
+    }
+
+    /// Sets the maximum number of open connections to `max`. Connections
+    /// accepted while `max` connections are open are closed immediately. If
+    /// `max` is `None`, the number of open connections is unbounded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// // Keep at most 1024 connections open.
+    /// config.set_max_connections(1024);
+    ///
+    /// // Don't limit the number of open connections.
+    /// config.set_max_connections(None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_max_connections<T: Into<Option<u32>>>(&mut self, max: T) {
+        self.max_connections = max.into();
+    }
+
+    /// Sets the maximum number of requests served on one connection to `max`.
+    /// The response to the last request a connection may carry closes it. If
+    /// `max` is `None`, connections are kept alive indefinitely.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// // Close connections after they've carried 100 requests.
+    /// config.set_max_requests_per_connection(100);
+    ///
+    /// // Don't limit the number of requests per connection.
+    /// config.set_max_requests_per_connection(None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_max_requests_per_connection<T: Into<Option<u32>>>(&mut self, max: T) {
+        self.max_requests_per_connection = max.into();
+    }
+
+    /// Sets the length of the queue of connections waiting to be accepted to
+    /// `backlog`. If `backlog` is `None`, the system default is used.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// // Queue at most 64 pending connections.
+    /// config.set_backlog(64);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_backlog<T: Into<Option<u32>>>(&mut self, backlog: T) {
+        self.backlog = backlog.into();
+    }
+
+    /// Sets the maximum number of open connections from any one client IP
+    /// address to `max`. Connections accepted from an address with `max` open
+    /// connections are closed immediately. If `max` is `None`, the number of
+    /// connections per address is unbounded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// // Keep at most 16 connections from one address open.
+    /// config.set_max_connections_per_ip(16);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_max_connections_per_ip<T: Into<Option<u32>>>(&mut self, max: T) {
+        self.max_connections_per_ip = max.into();
+    }
+
+    /// Sets the IP networks exempt from `max_connections_per_ip`, such as
+    /// those of load balancers, to `networks`. Networks are IP addresses or
+    /// networks in CIDR notation, as in `10.0.0.0/8`.
+    ///
+    /// # Errors
+    ///
+    /// If any of the networks in `networks` is malformed, an error of variant
+    /// `BadType` is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// let networks = vec!["10.0.0.0/8".to_string(), "192.168.1.10".to_string()];
+    /// assert!(config.set_connection_limit_exempt(networks).is_ok());
+    /// assert!(config.set_connection_limit_exempt(vec!["10.0.0.0/40".to_string()]).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_connection_limit_exempt(&mut self, networks: Vec<String>) -> Result<()> {
+        if !networks.iter().all(|network| IpNetwork::parse(network).is_some()) {
+            return Err(self.bad_type("connection_limit_exempt", "a malformed network",
+                                     "IP addresses, optionally with a `/` prefix length"));
+        }
+
+        self.connection_limit_exempt = networks;
+        Ok(())
+    }
+
+    /// Sets the maximum size, in bytes, of a request's head, its request line
+    /// and headers, to `max`. Requests with larger heads are answered with
+    /// `431 Request Header Fields Too Large` and their connection is closed.
+    /// Defaults to 409600 (400KiB), the most Hyper can read.
+    ///
+    /// # Errors
+    ///
+    /// If `max` is `0` or larger than 409600, an error of variant `BadType` is
+    /// returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// // Accept request heads of at most 64KiB.
+    /// assert!(config.set_max_header_size(64 * 1024).is_ok());
+    /// assert!(config.set_max_header_size(1024 * 1024).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_max_header_size(&mut self, max: u32) -> Result<()> {
+        if max == 0 || max > MAX_HEADER_SIZE {
+            return Err(self.bad_type("max_header_size", "an out of range integer",
+                                     "an integer from 1 to 409600"));
+        }
+
+        self.max_header_size = max;
+        Ok(())
+    }
+
+    /// Sets the maximum number of headers in a request to `max`. Requests
+    /// with more headers are answered with `431 Request Header Fields Too
+    /// Large` and their connection is closed. Defaults to 100, the most Hyper
+    /// can parse.
+    ///
+    /// # Errors
+    ///
+    /// If `max` is `0` or larger than 100, an error of variant `BadType` is
+    /// returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// // Accept requests with at most 32 headers.
+    /// assert!(config.set_max_header_count(32).is_ok());
+    /// assert!(config.set_max_header_count(500).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_max_header_count(&mut self, max: u32) -> Result<()> {
+        if max == 0 || max > MAX_HEADER_COUNT {
+            return Err(self.bad_type("max_header_count", "an out of range integer",
+                                     "an integer from 1 to 100"));
+        }
+
+        self.max_header_count = max;
+        Ok(())
+    }
+
     /// Sets the `secret_key` in `self` to `key` which must be a 256-bit base64
     /// encoded string.
     ///
-    /// # Errors
+    /// # Errors
+    ///
+    /// If `key` is not a valid 256-bit base64 encoded string, returns a
+    /// `BadType` error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::new(Environment::Staging)?;
+    /// let key = "8Xui8SN4mI+7egV/9dlfYYLGQJeEx4+DwmSQLwDVXJg=";
+    /// assert!(config.set_secret_key(key).is_ok());
+    /// assert!(config.set_secret_key("hello? anyone there?").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_secret_key<K: Into<String>>(&mut self, key: K) -> Result<()> {
+        let key = Secret::new(key.into());
+        let error = self.bad_type("secret_key", "string",
+                                  "a 256-bit base64 encoded string");
+
+        if key.expose().len() != 44 {
+            return Err(error);
+        }
+
+        let bytes = match base64::decode(key.expose()) {
+            Ok(bytes) => Secret::new(bytes),
+            Err(_) => return Err(error)
+        };
+
+        self.secret_key = SecretKey::Provided(Key::from_master(bytes.expose()));
+        Ok(())
+    }
+
+    /// Sets the logging level for `self` to `log_level`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, LoggingLevel, Environment};
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::new(Environment::Staging)?;
+    /// config.set_log_level(LoggingLevel::Critical);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_log_level(&mut self, log_level: LoggingLevel) {
+        self.log_level = log_level;
+    }
+
+    /// Set the receive limits in `self` to `limits`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Limits};
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    /// config.set_limits(Limits::default().limit("json", 4 * (1 << 20)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Sets the TLS configuration in `self`.
+    ///
+    /// Certificates are read from `certs_path`. The certificate chain must be
+    /// in X.509 PEM format. The private key is read from `key_path`. The
+    /// private key must be an RSA key in either PKCS#1 or PKCS#8 PEM format.
+    ///
+    /// # Errors
+    ///
+    /// If reading either the certificates or private key fails, an error of
+    /// variant `Io` is returned. If either the certificates or private key
+    /// files are malformed or cannot be parsed, an error of `BadType` is
+    /// returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    /// config.set_tls("/etc/ssl/my_certs.pem", "/etc/ssl/priv.key")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "tls")]
+    pub fn set_tls(&mut self, certs_path: &str, key_path: &str) -> Result<()> {
+        self.tls = Some(self.load_tls(certs_path, key_path)?);
+        Ok(())
+    }
+
+    #[doc(hidden)]
+    #[cfg(not(feature = "tls"))]
+    pub fn set_tls(&mut self, _: &str, _: &str) -> Result<()> {
+        self.tls = Some(TlsConfig);
+        Ok(())
+    }
+
+    /// Reads the certificates at `certs_path` and the private key at
+    /// `key_path`.
+    #[cfg(feature = "tls")]
+    fn load_tls(&self, certs_path: &str, key_path: &str) -> Result<TlsConfig> {
+        use hyper_sync_rustls::util as tls;
+        use hyper_sync_rustls::util::Error::Io;
+        let pem_err = "malformed PEM file";
+
+        // Load the certificates.
+        let certs = tls::load_certs(self.root_relative(certs_path))
+            .map_err(|e| match e {
+                Io(e) => ConfigError::Io(e, "tls.certs"),
+                _ => self.bad_type("tls", pem_err, "a valid certificates file")
+            })?;
+
+        // And now the private key.
+        let key = tls::load_private_key(self.root_relative(key_path))
+            .map_err(|e| match e {
+                Io(e) => ConfigError::Io(e, "tls.key"),
+                _ => self.bad_type("tls", pem_err, "a valid private key file")
+            })?;
+
+        let paths = Some((certs_path.to_string(), key_path.to_string()));
+        Ok(TlsConfig { certs: certs, key: key, paths: paths })
+    }
+
+    /// Sets the certificates served to TLS clients that request `hostname`
+    /// via SNI, replacing any previously set for `hostname`. Clients that
+    /// request any other hostname, or none at all, are served the default
+    /// certificates set via [`set_tls()`](#method.set_tls) or
+    /// [`set_tls_bytes()`](#method.set_tls_bytes), which must be set first.
+    ///
+    /// Certificates are read from `certs_path` and the private key from
+    /// `key_path`, in the same formats as for `set_tls()`. `hostname` must be
+    /// a DNS name; wildcards aren't supported.
+    ///
+    /// # Errors
+    ///
+    /// If no default certificates are set or `hostname` isn't a valid DNS
+    /// name, an error of variant `BadType` is returned. Otherwise, errors are
+    /// returned as for `set_tls()`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    /// config.set_tls("/etc/ssl/default.pem", "/etc/ssl/default.key")?;
+    /// config.set_tls_sni("api.example.com", "/etc/ssl/api.pem", "/etc/ssl/api.key")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "tls")]
+    pub fn set_tls_sni(&mut self, hostname: &str, certs_path: &str, key_path: &str) -> Result<()> {
+        let hostname = self.sni_hostname(hostname)?;
+        let tls = self.load_tls(certs_path, key_path)?;
+        self.tls_sni.retain(|&(ref name, _)| name != &hostname);
+        self.tls_sni.push((hostname, tls));
+        Ok(())
+    }
+
+    #[doc(hidden)]
+    #[cfg(not(feature = "tls"))]
+    pub fn set_tls_sni(&mut self, hostname: &str, _: &str, _: &str) -> Result<()> {
+        let hostname = self.sni_hostname(hostname)?;
+        self.tls_sni.retain(|&(ref name, _)| name != &hostname);
+        self.tls_sni.push((hostname, TlsConfig));
+        Ok(())
+    }
+
+    /// Validates `hostname` as an SNI hostname, returning it in lowercase.
+    fn sni_hostname(&self, hostname: &str) -> Result<String> {
+        if self.tls.is_none() {
+            return Err(self.bad_type("tls.sni", "SNI certificates without a default",
+                                     "default certificates in `tls`"));
+        }
+
+        let is_label = |label: &str| {
+            !label.is_empty() && label.len() <= 63
+                && !label.starts_with('-') && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        };
+
+        if hostname.len() > 253 || !hostname.split('.').all(is_label) {
+            return Err(self.bad_type("tls.sni", "an invalid hostname", "a DNS name"));
+        }
+
+        Ok(hostname.to_ascii_lowercase())
+    }
+
+    /// Sets the TLS configuration in `self` from in-memory PEM data instead of
+    /// files, as when certificates are received from a secrets manager.
+    ///
+    /// `certs` is the certificate chain in X.509 PEM format. `key` is the
+    /// private key, an RSA key in either PKCS#1 or PKCS#8 PEM format. If `ca`
+    /// is `Some`, it is used as the certificate store client certificates are
+    /// verified against, as if set via
+    /// [`set_cert_store()`](#method.set_cert_store).
+    ///
+    /// # Errors
+    ///
+    /// If any of `certs`, `key`, or `ca` are malformed or contain no
+    /// certificate or key, an error of `BadType` is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// # let read_secret = |_: &str| vec![];
+    /// let mut config = Config::development()?;
+    /// let certs = read_secret("tls/certs");
+    /// let key = read_secret("tls/key");
+    /// config.set_tls_bytes(&certs, &key, None)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "tls")]
+    pub fn set_tls_bytes(&mut self, certs: &[u8], key: &[u8], ca: Option<&[u8]>) -> Result<()> {
+        use rustls::internal::pemfile;
+
+        let pem_err = "malformed PEM data";
+        let certs = match pemfile::certs(&mut { certs }) {
+            Ok(ref certs) if certs.is_empty() => {
+                return Err(self.bad_type("tls", "PEM data without certificates",
+                                         "valid certificates"));
+            }
+            Ok(certs) => certs,
+            Err(_) => return Err(self.bad_type("tls", pem_err, "valid certificates"))
+        };
+
+        // The key may be in either PKCS#8 or PKCS#1 format.
+        let key = pemfile::pkcs8_private_keys(&mut { key })
+            .ok()
+            .and_then(|mut keys| keys.pop())
+            .or_else(|| pemfile::rsa_private_keys(&mut { key }).ok().and_then(|mut k| k.pop()));
+
+        let key = match key {
+            Some(key) => key,
+            None => return Err(self.bad_type("tls", pem_err, "a valid private key"))
+        };
+
+        let cert_store = match ca {
+            Some(ca) => match pemfile::certs(&mut { ca }) {
+                Ok(ref certs) if certs.is_empty() => {
+                    return Err(self.bad_type("tls", "an empty certificate store",
+                                             "a certificate store with at least one certificate"));
+                }
+                Ok(certs) => Some(CertStore(certs)),
+                Err(_) => return Err(self.bad_type("tls", pem_err, "a valid certificate store"))
+            },
+            None => None
+        };
+
+        self.tls = Some(TlsConfig { certs: certs, key: key, paths: None });
+        if cert_store.is_some() {
+            self.cert_store = cert_store;
+        }
+
+        Ok(())
+    }
+
+    #[doc(hidden)]
+    #[cfg(not(feature = "tls"))]
+    pub fn set_tls_bytes(&mut self, _: &[u8], _: &[u8], ca: Option<&[u8]>) -> Result<()> {
+        self.tls = Some(TlsConfig);
+        if ca.is_some() {
+            self.cert_store = Some(CertStore);
+        }
+
+        Ok(())
+    }
+
+    /// Sets the certificate store that client certificates are verified
+    /// against when mutual TLS is enabled. The store is read from
+    /// `cert_store_path`, a file of X.509 certificates in PEM format, usually
+    /// those of the certificate authorities that issue client certificates.
+    ///
+    /// # Errors
+    ///
+    /// If reading the certificates fails, an error of variant `Io` is
+    /// returned. If the file is malformed or contains no certificates, an
+    /// error of `BadType` is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, MutualTls};
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    /// config.set_tls("/etc/ssl/my_certs.pem", "/etc/ssl/priv.key")?;
+    /// config.set_cert_store("/etc/ssl/client_ca.pem")?;
+    /// config.set_mutual_tls(MutualTls::Required);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "tls")]
+    pub fn set_cert_store(&mut self, cert_store_path: &str) -> Result<()> {
+        use hyper_sync_rustls::util as tls;
+        use hyper_sync_rustls::util::Error::Io;
+
+        let certs = tls::load_certs(self.root_relative(cert_store_path))
+            .map_err(|e| match e {
+                Io(e) => ConfigError::Io(e, "tls.cert_store"),
+                _ => self.bad_type("tls", "malformed PEM file", "a valid certificate store file")
+            })?;
+
+        if certs.is_empty() {
+            return Err(self.bad_type("tls", "an empty certificate store",
+                                     "a certificate store with at least one certificate"));
+        }
+
+        self.cert_store = Some(CertStore(certs));
+        Ok(())
+    }
+
+    #[doc(hidden)]
+    #[cfg(not(feature = "tls"))]
+    pub fn set_cert_store(&mut self, _: &str) -> Result<()> {
+        self.cert_store = Some(CertStore);
+        Ok(())
+    }
+
+    /// Sets the mutual TLS policy: whether TLS clients must authenticate with
+    /// a certificate. Unless the policy is `Disabled`, a certificate store
+    /// must be set via [`set_cert_store()`](#method.set_cert_store) as well.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, MutualTls};
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// config.set_mutual_tls(MutualTls::Optional);
+    /// assert_eq!(config.mutual_tls, MutualTls::Optional);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_mutual_tls(&mut self, policy: MutualTls) {
+        self.mutual_tls = policy;
+    }
+
+    /// Sets the number of seconds new TLS connections have to complete the
+    /// handshake and send the head of their first request to `timeout`.
+    /// Connections that take longer are closed. If `timeout` is `None`, the
+    /// handshake is unbounded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// // Close connections that haven't completed the handshake in 5 seconds.
+    /// config.set_tls_handshake_timeout(5);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_tls_handshake_timeout<T: Into<Option<u32>>>(&mut self, timeout: T) {
+        self.tls_handshake_timeout = timeout.into();
+    }
+
+    /// Sets the OCSP response stapled to TLS handshakes that are served the
+    /// default certificate, read from `ocsp_path`, a DER-encoded response from
+    /// the certificate's issuer, as written by `openssl ocsp -respout`.
+    /// Stapling spares clients a request to the issuer's OCSP responder, and
+    /// clients are required to receive a stapled response for certificates
+    /// with the OCSP Must-Staple extension. The certificates must be set first,
+    /// via [`set_tls()`](#method.set_tls) or
+    /// [`set_tls_bytes()`](#method.set_tls_bytes).
+    ///
+    /// The response is reread from `ocsp_path` whenever the certificates are
+    /// reloaded and, if set, every
+    /// [`tls_ocsp_refresh`](#method.set_tls_ocsp_refresh) seconds.
+    ///
+    /// # Errors
+    ///
+    /// If reading the response fails, an error of variant `Io` is returned. If
+    /// no certificates are set or the file isn't a DER-encoded structure, an
+    /// error of `BadType` is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    /// config.set_tls("/etc/ssl/my_certs.pem", "/etc/ssl/priv.key")?;
+    /// config.set_tls_ocsp_response("/etc/ssl/ocsp.der")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "tls")]
+    pub fn set_tls_ocsp_response(&mut self, ocsp_path: &str) -> Result<()> {
+        use std::fs::File;
+        use std::io::Read;
+
+        if self.tls.is_none() {
+            return Err(self.bad_type("tls.ocsp_response_path", "no certificates",
+                                     "certificates in `tls`"));
+        }
+
+        let mut der = vec![];
+        File::open(self.root_relative(ocsp_path))
+            .and_then(|mut file| file.read_to_end(&mut der))
+            .map_err(|e| ConfigError::Io(e, "tls.ocsp_response_path"))?;
+
+        // An OCSPResponse is a DER SEQUENCE; anything else isn't a response.
+        if der.len() < 2 || der[0] != 0x30 {
+            return Err(self.bad_type("tls.ocsp_response_path", "a malformed OCSP response",
+                                     "a DER-encoded OCSP response"));
+        }
+
+        self.tls_ocsp = Some(OcspResponse { path: ocsp_path.to_string(), der: der });
+        Ok(())
+    }
+
+    #[doc(hidden)]
+    #[cfg(not(feature = "tls"))]
+    pub fn set_tls_ocsp_response(&mut self, _: &str) -> Result<()> {
+        if self.tls.is_none() {
+            return Err(self.bad_type("tls.ocsp_response_path", "no certificates",
+                                     "certificates in `tls`"));
+        }
+
+        self.tls_ocsp = Some(OcspResponse);
+        Ok(())
+    }
+
+    /// Sets the number of seconds between rereads of the OCSP response set via
+    /// [`set_tls_ocsp_response()`](#method.set_tls_ocsp_response) from its
+    /// file. OCSP responses expire, usually within days, so the file should be
+    /// updated, as by a cron job, and reread before the stapled response
+    /// expires. If `refresh` is `None`, the default, the response is only
+    /// reread when the certificates are
+    /// [reloaded](/rocket/tls/index.html#reloading-certificates).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// // Reread the OCSP response every hour.
+    /// config.set_tls_ocsp_refresh(60 * 60);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_tls_ocsp_refresh<T: Into<Option<u32>>>(&mut self, refresh: T) {
+        self.tls_ocsp_refresh = refresh.into();
+    }
+
+    /// Sets the protocols advertised to TLS clients via Application-Layer
+    /// Protocol Negotiation (ALPN), most preferred first. Clients that
+    /// support ALPN select one of the advertised protocols during the
+    /// handshake; the selected protocol is available via
+    /// [`Request::alpn_protocol()`](/rocket/struct.Request.html#method.alpn_protocol).
+    /// If `protocols` is empty, the default, no protocols are advertised.
+    ///
+    /// Rocket only serves HTTP/1.x. Advertise other protocols, such as `h2`,
+    /// only if connections that negotiate them are handled before reaching
+    /// Rocket, as by a proxy that passes TLS through.
+    ///
+    /// # Errors
+    ///
+    /// If any of the protocol names is empty, longer than 255 bytes, or
+    /// contains characters other than printable ASCII, an error of variant
+    /// `BadType` is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// assert!(config.set_tls_alpn(vec!["http/1.1".into()]).is_ok());
+    /// assert!(config.set_tls_alpn(vec!["".into()]).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_tls_alpn(&mut self, protocols: Vec<String>) -> Result<()> {
+        let printable = |p: &String| p.bytes().all(|b| b > 0x20 && b < 0x7f);
+        if protocols.iter().any(|p| p.is_empty() || p.len() > 255 || !printable(p)) {
+            return Err(self.bad_type("tls_alpn", "an invalid protocol name",
+                                     "ALPN protocol names, such as \"http/1.1\""));
+        }
+
+        self.tls_alpn = protocols;
+        Ok(())
+    }
+
+    /// Sets the TLS protocol versions clients may negotiate to `versions`.
+    /// Clients that support none of them fail the handshake. If `versions` is
+    /// empty, the default, every supported version may be negotiated.
+    ///
+    /// The supported versions are `TLSv1.2` and `TLSv1.3`. Older versions,
+    /// including TLS 1.0 and 1.1, are never negotiated.
+    ///
+    /// # Errors
+    ///
+    /// If any of the versions isn't a supported version, compared
+    /// case-insensitively, an error of variant `BadType` is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// assert!(config.set_tls_versions(vec!["tlsv1.3".into()]).is_ok());
+    /// assert_eq!(config.tls_versions, vec!["TLSv1.3"]);
+    ///
+    /// assert!(config.set_tls_versions(vec!["TLSv1.1".into()]).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_tls_versions(&mut self, versions: Vec<String>) -> Result<()> {
+        self.tls_versions = self.tls_names("tls_versions", versions, TLS_VERSIONS,
+                                           "\"TLSv1.2\" or \"TLSv1.3\"")?;
+        Ok(())
+    }
+
+    /// Sets the TLS cipher suites clients may negotiate to `ciphersuites`,
+    /// most preferred first. If `ciphersuites` is empty, the default, every
+    /// supported cipher suite may be negotiated.
+    ///
+    /// The supported cipher suites are named as in the IANA registry, except
+    /// that those for TLS 1.3 are prefixed `TLS13_`:
+    ///
+    ///   * `TLS13_CHACHA20_POLY1305_SHA256`
+    ///   * `TLS13_AES_256_GCM_SHA384`
+    ///   * `TLS13_AES_128_GCM_SHA256`
+    ///   * `TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256`
+    ///   * `TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256`
+    ///   * `TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384`
+    ///   * `TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256`
+    ///   * `TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384`
+    ///   * `TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256`
+    ///
+    /// At least one of the suites must be usable with the versions set via
+    /// [`set_tls_versions()`](#method.set_tls_versions); otherwise, Rocket
+    /// fails to launch.
+    ///
+    /// # Errors
+    ///
+    /// If any of the cipher suites isn't a supported cipher suite, compared
+    /// case-insensitively, an error of variant `BadType` is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// let suites = vec!["TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384".into()];
+    /// assert!(config.set_tls_ciphersuites(suites).is_ok());
+    ///
+    /// let weak = vec!["TLS_RSA_WITH_RC4_128_SHA".into()];
+    /// assert!(config.set_tls_ciphersuites(weak).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_tls_ciphersuites(&mut self, ciphersuites: Vec<String>) -> Result<()> {
+        self.tls_ciphersuites = self.tls_names("tls_ciphersuites", ciphersuites,
+                                               TLS_CIPHERSUITES, "supported cipher suites")?;
+        Ok(())
+    }
+
+    /// Returns `names`, each of which must be one of `known` compared
+    /// case-insensitively, as they're named in `known`.
+    fn tls_names(
+        &self,
+        param: &str,
+        names: Vec<String>,
+        known: &[&str],
+        expect: &str
+    ) -> Result<Vec<String>> {
+        names.iter()
+            .map(|name| {
+                known.iter()
+                    .find(|known| uncased_eq(known, name))
+                    .map(|known| known.to_string())
+                    .ok_or_else(|| self.bad_type(param, "an unsupported name", expect))
+            })
+            .collect()
+    }
+
+    /// Sets the SHA-256 fingerprints of the client certificates that the
+    /// [`AllowedClient`](/rocket/tls/struct.AllowedClient.html) request guard
+    /// accepts. Each fingerprint is 64 hexadecimal digits, in either case,
+    /// optionally with pairs of digits separated by colons, as printed by
+    /// `openssl x509 -noout -fingerprint -sha256`.
+    ///
+    /// # Errors
+    ///
+    /// If any of the fingerprints is malformed, an error of variant `BadType`
+    /// is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// let fingerprint = "0B:F5:AD:9E:01:F5:1F:68:CF:44:F2:FD:B0:95:1B:CC:\
+    ///                    F5:15:7E:0E:7B:C1:48:7B:84:B3:40:0B:AE:92:8B:8D";
+    /// assert!(config.set_mtls_allowed_fingerprints(vec![fingerprint.into()]).is_ok());
+    /// assert!(config.set_mtls_allowed_fingerprints(vec!["0bf5ad".into()]).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_mtls_allowed_fingerprints(&mut self, fingerprints: Vec<String>) -> Result<()> {
+        let nibble = |digit: u8| (digit as char).to_digit(16).unwrap() as u8;
+        let mut parsed = Vec::with_capacity(fingerprints.len());
+        for fingerprint in &fingerprints {
+            let digits: Vec<u8> = fingerprint.bytes().filter(|&b| b != b':').collect();
+            if digits.len() != 64 || !digits.iter().all(|b| b.is_ascii_hexdigit()) {
+                return Err(self.bad_type("mtls_allowed_fingerprints", "a malformed fingerprint",
+                                         "SHA-256 fingerprints in hexadecimal"));
+            }
+
+            let mut digest = [0; 32];
+            for (byte, pair) in digest.iter_mut().zip(digits.chunks(2)) {
+                *byte = nibble(pair[0]) << 4 | nibble(pair[1]);
+            }
+
+            parsed.push(digest);
+        }
+
+        self.mtls_allowed_fingerprints = parsed;
+        Ok(())
+    }
+
+    #[cfg(not(test))]
+    #[inline(always)]
+    fn set_raw_tls(&mut self, raw: RawTls) -> Result<()> {
+        self.set_tls(raw.certs, raw.key)?;
+        for (hostname, certs_path, key_path) in raw.sni {
+            self.set_tls_sni(hostname, certs_path, key_path)?;
+        }
+
+        if let Some(ocsp_path) = raw.ocsp_response {
+            self.set_tls_ocsp_response(ocsp_path)?;
+        }
+
+        match raw.cert_store {
+            Some(cert_store_path) => self.set_cert_store(cert_store_path),
+            None => Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    fn set_raw_tls(&mut self, _: RawTls) -> Result<()> {
+        Ok(())
+    }
+
+    /// Sets whether request bodies are verified against the checksum in the
+    /// `Content-MD5` or `Digest` header, when one is present. Requests whose
+    /// bodies don't match are failed with a `422 Unprocessable Entity`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// config.set_verify_checksums(true);
+    /// assert!(config.verify_checksums);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_verify_checksums(&mut self, verify: bool) {
+        self.verify_checksums = verify;
+    }
+
+    /// Sets the value of the `Server` header sent with every response to
+    /// `name`. If `name` is `None`, the header is omitted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// // Identify as "MyApp" instead of "Rocket".
+    /// config.set_server_name("MyApp");
+    ///
+    /// // Don't send a `Server` header at all.
+    /// config.set_server_name(None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_server_name<'a, T: Into<Option<&'a str>>>(&mut self, name: T) {
+        self.server_name = name.into().map(|name| name.to_string());
+    }
+
+    /// Sets the policy used to remove or rewrite response headers before they
+    /// are written out to `policy`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, HeaderPolicy};
     ///
-    /// If `key` is not a valid 256-bit base64 encoded string, returns a
-    /// `BadType` error.
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// config.set_header_policy(HeaderPolicy::new().deny("X-Powered-By"));
+    /// assert!(!config.header_policy.is_allowed("X-Powered-By"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_header_policy(&mut self, policy: HeaderPolicy) {
+        self.header_policy = policy;
+    }
+
+    /// Sets the configured state of the feature toggles in `self` to those in
+    /// `features`. Features that aren't present are disabled.
     ///
     /// # Example
     ///
     /// ```rust
-    /// use rocket::config::{Config, Environment};
+    /// use std::collections::HashMap;
+    /// use rocket::config::Config;
     ///
     /// # use rocket::config::ConfigError;
     /// # fn config_test() -> Result<(), ConfigError> {
-    /// let mut config = Config::new(Environment::Staging)?;
-    /// let key = "8Xui8SN4mI+7egV/9dlfYYLGQJeEx4+DwmSQLwDVXJg=";
-    /// assert!(config.set_secret_key(key).is_ok());
-    /// assert!(config.set_secret_key("hello? anyone there?").is_err());
+    /// let mut config = Config::development()?;
+    ///
+    /// let mut features = HashMap::new();
+    /// features.insert("beta_search".to_string(), true);
+    /// config.set_features(features);
     /// # Ok(())
     /// # }
     /// ```
-    pub fn set_secret_key<K: Into<String>>(&mut self, key: K) -> Result<()> {
-        let key = key.into();
-        let error = self.bad_type("secret_key", "string",
-                                  "a 256-bit base64 encoded string");
+    #[inline]
+    pub fn set_features(&mut self, features: HashMap<String, bool>) {
+        self.features = features;
+    }
 
-        if key.len() != 44 {
-            return Err(error);
+    /// Sets the hosts that requests may be sent to. Requests for other hosts
+    /// are rejected with `421 Misdirected Request`; requests without a valid
+    /// `Host` header are rejected with `400 Bad Request`. If `hosts` is empty,
+    /// requests for any host are accepted. See
+    /// [`Host`](/rocket/request/struct.Host.html) for the pattern syntax.
+    ///
+    /// # Errors
+    ///
+    /// If any of the patterns in `hosts` is malformed, an error of variant
+    /// `BadType` is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// let hosts = vec!["example.com".to_string(), "*.example.com".to_string()];
+    /// assert!(config.set_allowed_hosts(hosts).is_ok());
+    /// assert!(config.set_allowed_hosts(vec!["exa mple.com".to_string()]).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_allowed_hosts(&mut self, hosts: Vec<String>) -> Result<()> {
+        if !hosts.iter().all(|host| Host::is_valid_pattern(host)) {
+            return Err(self.bad_type("allowed_hosts", "a malformed host",
+                                     "host names, optionally with a port or `*.` prefix"));
         }
 
-        let bytes = match base64::decode(&key) {
-            Ok(bytes) => bytes,
-            Err(_) => return Err(error)
-        };
-
-        self.secret_key = SecretKey::Provided(Key::from_master(&bytes));
+        self.allowed_hosts = hosts;
         Ok(())
     }
 
-    /// Sets the logging level for `self` to `log_level`.
+    /// Sets the HSTS policy sent with every response when TLS is enabled. If
+    /// `hsts` is `None`, no policy is sent.
     ///
     /// # Example
     ///
     /// ```rust
-    /// use rocket::config::{Config, LoggingLevel, Environment};
+    /// use rocket::config::{Config, Hsts};
     ///
     /// # use rocket::config::ConfigError;
     /// # fn config_test() -> Result<(), ConfigError> {
-    /// let mut config = Config::new(Environment::Staging)?;
-    /// config.set_log_level(LoggingLevel::Critical);
+    /// let mut config = Config::production()?;
+    ///
+    /// config.set_hsts(Hsts::preload());
+    /// assert_eq!(config.hsts, Some(Hsts::preload()));
     /// # Ok(())
     /// # }
     /// ```
     #[inline]
-    pub fn set_log_level(&mut self, log_level: LoggingLevel) {
-        self.log_level = log_level;
+    pub fn set_hsts<H: Into<Option<Hsts>>>(&mut self, hsts: H) {
+        self.hsts = hsts.into();
     }
 
-    /// Set the receive limits in `self` to `limits`.
+    /// Sets whether plaintext requests with an `Upgrade-Insecure-Requests: 1`
+    /// header are redirected to the same URI over HTTPS.
+    ///
+    /// Requests are redirected to `https_port` and only when TLS is not
+    /// enabled. To avoid a redirect loop, the redirect is never issued by a
+    /// plaintext server that listens on `https_port`, nor for requests that a
+    /// TLS-terminating proxy in `trusted_proxies` reports as HTTPS.
     ///
     /// # Example
     ///
     /// ```rust
-    /// use rocket::config::{Config, Limits};
+    /// use rocket::config::Config;
     ///
     /// # use rocket::config::ConfigError;
     /// # fn config_test() -> Result<(), ConfigError> {
-    /// let mut config = Config::development()?;
-    /// config.set_limits(Limits::default().limit("json", 4 * (1 << 20)));
+    /// let mut config = Config::production()?;
+    ///
+    /// config.set_upgrade_insecure_requests(true);
+    /// assert!(config.upgrade_insecure_requests);
     /// # Ok(())
     /// # }
     /// ```
     #[inline]
-    pub fn set_limits(&mut self, limits: Limits) {
-        self.limits = limits;
+    pub fn set_upgrade_insecure_requests(&mut self, upgrade: bool) {
+        self.upgrade_insecure_requests = upgrade;
     }
 
-    /// Sets the TLS configuration in `self`.
+    /// Sets the port HTTPS is served on, which requests upgraded by
+    /// `upgrade_insecure_requests` are redirected to, to `port`. Defaults to
+    /// `443`, which is left out of the redirect's `Location`.
     ///
-    /// Certificates are read from `certs_path`. The certificate chain must be
-    /// in X.509 PEM format. The private key is read from `key_path`. The
-    /// private key must be an RSA key in either PKCS#1 or PKCS#8 PEM format.
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::production()?;
+    ///
+    /// config.set_https_port(8443);
+    /// assert_eq!(config.https_port, 8443);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_https_port(&mut self, port: u16) {
+        self.https_port = port;
+    }
+
+    /// Sets the IP networks of the proxies trusted to report the scheme of a
+    /// request via `X-Forwarded-Proto` to `networks`. Networks are IP
+    /// addresses or networks in CIDR notation, as in `10.0.0.0/8`. A request
+    /// a trusted proxy reports as HTTPS, because the proxy terminated TLS, is
+    /// never upgraded by `upgrade_insecure_requests`.
     ///
     /// # Errors
     ///
-    /// If reading either the certificates or private key fails, an error of
-    /// variant `Io` is returned. If either the certificates or private key
-    /// files are malformed or cannot be parsed, an error of `BadType` is
-    /// returned.
+    /// If any of the networks in `networks` is malformed, an error of variant
+    /// `BadType` is returned.
     ///
     /// # Example
     ///
@@ -548,51 +1849,153 @@ impl Config {
     ///
     /// # use rocket::config::ConfigError;
     /// # fn config_test() -> Result<(), ConfigError> {
-    /// let mut config = Config::development()?;
-    /// config.set_tls("/etc/ssl/my_certs.pem", "/etc/ssl/priv.key")?;
+    /// let mut config = Config::production()?;
+    ///
+    /// assert!(config.set_trusted_proxies(vec!["10.0.0.0/8".to_string()]).is_ok());
+    /// assert!(config.set_trusted_proxies(vec!["proxy".to_string()]).is_err());
     /// # Ok(())
     /// # }
     /// ```
-    #[cfg(feature = "tls")]
-    pub fn set_tls(&mut self, certs_path: &str, key_path: &str) -> Result<()> {
-        use hyper_sync_rustls::util as tls;
-        use hyper_sync_rustls::util::Error::Io;
-        let pem_err = "malformed PEM file";
+    pub fn set_trusted_proxies(&mut self, networks: Vec<String>) -> Result<()> {
+        if !networks.iter().all(|network| IpNetwork::parse(network).is_some()) {
+            return Err(self.bad_type("trusted_proxies", "a malformed network",
+                                     "IP addresses, optionally with a `/` prefix length"));
+        }
 
-        // Load the certificates.
-        let certs = tls::load_certs(self.root_relative(certs_path))
-            .map_err(|e| match e {
-                Io(e) => ConfigError::Io(e, "tls.certs"),
-                _ => self.bad_type("tls", pem_err, "a valid certificates file")
-            })?;
+        self.trusted_proxies = networks;
+        Ok(())
+    }
 
-        // And now the private key.
-        let key = tls::load_private_key(self.root_relative(key_path))
-            .map_err(|e| match e {
-                Io(e) => ConfigError::Io(e, "tls.key"),
-                _ => self.bad_type("tls", pem_err, "a valid private key file")
-            })?;
+    /// Sets the algorithm used to compute the `Digest` and `Content-Digest`
+    /// headers of response bodies to `algorithm`. If `algorithm` is `None`, no
+    /// digest is computed.
+    ///
+    /// Only sized bodies are digested: they are buffered in memory so that the
+    /// digest can be sent as a header. Streamed bodies are sent as-is.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    /// use rocket::data::ChecksumAlgorithm;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::production()?;
+    ///
+    /// config.set_response_digest(ChecksumAlgorithm::Sha256);
+    /// assert_eq!(config.response_digest, Some(ChecksumAlgorithm::Sha256));
+    ///
+    /// config.set_response_digest(None);
+    /// assert_eq!(config.response_digest, None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_response_digest<A: Into<Option<ChecksumAlgorithm>>>(&mut self, algorithm: A) {
+        self.response_digest = algorithm.into();
+    }
 
-        self.tls = Some(TlsConfig { certs, key });
-        Ok(())
+    /// Sets the limits on the number and total size of the cookies in
+    /// requests and in the `Set-Cookie` headers of responses.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, CookieLimits, CookieOverflow};
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// let limits = CookieLimits::new(20, 4096).overflow(CookieOverflow::Reject);
+    /// config.set_cookie_limits(limits);
+    /// assert_eq!(config.cookie_limits, limits);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_cookie_limits(&mut self, limits: CookieLimits) {
+        self.cookie_limits = limits;
     }
 
-    #[doc(hidden)]
-    #[cfg(not(feature = "tls"))]
-    pub fn set_tls(&mut self, _: &str, _: &str) -> Result<()> {
-        self.tls = Some(TlsConfig);
-        Ok(())
+    /// Sets the algorithm private cookies are encrypted with. Private cookies
+    /// encrypted with another algorithm are still read and are re-encrypted
+    /// with `cipher` when they are.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    /// use rocket::http::CookieCipher;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// config.set_private_cookie_cipher(CookieCipher::ChaCha20Poly1305);
+    /// assert_eq!(config.private_cookie_cipher, CookieCipher::ChaCha20Poly1305);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_private_cookie_cipher(&mut self, cipher: CookieCipher) {
+        self.private_cookie_cipher = cipher;
     }
 
-    #[cfg(not(test))]
-    #[inline(always)]
-    fn set_raw_tls(&mut self, paths: (&str, &str)) -> Result<()> {
-        self.set_tls(paths.0, paths.1)
+    /// Sets the compression settings for response and request bodies.
+    /// Bodies aren't compressed or decompressed if `compression` is `None`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, CompressionConfig};
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    ///
+    /// config.set_compression(CompressionConfig::default().gzip(9));
+    /// assert_eq!(config.compression.unwrap().gzip_level(), Some(9));
+    ///
+    /// config.set_compression(None);
+    /// assert_eq!(config.compression, None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_compression<C: Into<Option<CompressionConfig>>>(&mut self, compression: C) {
+        self.compression = compression.into();
     }
 
-    #[cfg(test)]
-    fn set_raw_tls(&mut self, _: (&str, &str)) -> Result<()> {
-        Ok(())
+    /// Sets how the `String`, `Form`, and other text data guards decode
+    /// request bodies to `encoding`. If `encoding` is `None`, the default,
+    /// bodies must be valid UTF-8 and their `charset` is ignored. Otherwise,
+    /// bodies in a charset other than UTF-8 are rejected with `415
+    /// Unsupported Media Type`, and invalid UTF-8 is rejected or replaced as
+    /// set by the [`TextEncoding`](/rocket/data/enum.TextEncoding.html).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    /// use rocket::data::TextEncoding;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::production()?;
+    ///
+    /// config.set_text_encoding(TextEncoding::Strict);
+    /// assert_eq!(config.text_encoding, Some(TextEncoding::Strict));
+    ///
+    /// config.set_text_encoding(None);
+    /// assert_eq!(config.text_encoding, None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_text_encoding<E: Into<Option<TextEncoding>>>(&mut self, encoding: E) {
+        self.text_encoding = encoding.into();
     }
 
     /// Sets the extras for `self` to be the key/value pairs in `extras`.
@@ -657,6 +2060,42 @@ impl Config {
         self.secret_key.inner()
     }
 
+    /// Derives a 256-bit key for `purpose`, such as `"csrf"`, from the secret
+    /// key with HKDF-SHA256. Keys derived for different purposes are
+    /// independent of each other and of the keys used for private cookies, so
+    /// each subsystem can have its own key without configuring more than one
+    /// secret. A derived key is the same for as long as the secret key is; if
+    /// the secret key is generated, it changes on every launch.
+    ///
+    /// Purposes beginning with `rocket/` are reserved for Rocket's own use.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development()?;
+    /// config.set_secret_key("8Xui8SN4mI+7egV/9dlfYYLGQJeEx4+DwmSQLwDVXJg=")?;
+    ///
+    /// let csrf_key = config.derived_key("csrf");
+    /// assert_eq!(csrf_key, config.derived_key("csrf"));
+    /// assert!(csrf_key != config.derived_key("sessions"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn derived_key(&self, purpose: &str) -> Secret<[u8; 32]> {
+        derive_key(self.secret_key(), purpose)
+    }
+
+    /// Returns `true` if TLS is configured and the `tls` feature is enabled.
+    #[inline]
+    pub(crate) fn tls_enabled(&self) -> bool {
+        cfg!(feature = "tls") && self.tls.is_some()
+    }
+
     /// Attempts to retrieve the extra named `name` as a borrowed string.
     ///
     /// # Errors
@@ -854,6 +2293,101 @@ impl Config {
         v.as_datetime().ok_or_else(|| self.bad_type(name, v.type_str(), "a datetime"))
     }
 
+    /// Attempts to deserialize the extra named `name`, including any nested
+    /// tables and arrays, into a value of type `T`.
+    ///
+    /// This allows structured configuration, such as the settings of a
+    /// database pool, to be retrieved in one call instead of one `get_`
+    /// call per key. `T` is typically a structure that derives `Deserialize`.
+    ///
+    /// # Errors
+    ///
+    /// If an extra with `name` doesn't exist, returns an `Err` of `NotFound`.
+    /// If an extra with `name` _does_ exist but can't be deserialized into a
+    /// `T`, returns a `BadExtract` error describing what didn't match.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// extern crate rocket;
+    /// #[macro_use] extern crate serde_derive;
+    ///
+    /// use std::collections::BTreeMap;
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize)]
+    /// struct Pool {
+    ///     url: String,
+    ///     size: u32,
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut pool = BTreeMap::new();
+    /// pool.insert("url".to_string(), "postgres://localhost/db".into());
+    /// pool.insert("size".to_string(), 16.into());
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .extra("pool", pool)
+    ///     .unwrap();
+    ///
+    /// let pool: Pool = config.extract("pool").unwrap();
+    /// assert_eq!(pool, Pool { url: "postgres://localhost/db".into(), size: 16 });
+    /// # }
+    /// ```
+    pub fn extract<T: DeserializeOwned>(&self, name: &str) -> Result<T> {
+        let value = self.extras.get(name).ok_or_else(|| ConfigError::NotFound)?;
+        value.clone().try_into().map_err(|e| self.bad_extract(name, e))
+    }
+
+    /// Attempts to deserialize all of the extras, as a table whose keys are
+    /// the names of the extras, into a value of type `T`. This is the
+    /// equivalent of [`extract`] for the root of the configuration.
+    ///
+    /// [`extract`]: /rocket/struct.Config.html#method.extract
+    ///
+    /// # Errors
+    ///
+    /// If the extras can't be deserialized into a `T`, returns a `BadExtract`
+    /// error describing what didn't match.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// extern crate rocket;
+    /// #[macro_use] extern crate serde_derive;
+    ///
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Features {
+    ///     beta: bool,
+    ///     #[serde(default)]
+    ///     legacy: bool,
+    /// }
+    ///
+    /// # fn main() {
+    /// let config = Config::build(Environment::Staging)
+    ///     .extra("beta", true)
+    ///     .unwrap();
+    ///
+    /// let features: Features = config.extract_root().unwrap();
+    /// assert!(features.beta && !features.legacy);
+    /// # }
+    /// ```
+    pub fn extract_root<T: DeserializeOwned>(&self) -> Result<T> {
+        let table: Table = self.extras.iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+
+        Value::Table(table).try_into().map_err(|e| self.bad_extract("extras", e))
+    }
+
+    #[inline]
+    fn bad_extract<E: fmt::Display>(&self, name: &str, error: E) -> ConfigError {
+        let id = format!("{}.{}", self.environment, name);
+        ConfigError::BadExtract(id, error.to_string(), self.config_path.clone())
+    }
+
     /// Returns the path at which the configuration file for `self` is stored.
     /// For instance, if the configuration file is at `/tmp/Rocket.toml`, the
     /// path `/tmp` is returned.
@@ -914,7 +2448,36 @@ impl fmt::Debug for Config {
         s.field("port", &self.port);
         s.field("workers", &self.workers);
         s.field("keep_alive", &self.keep_alive);
+        s.field("max_idle_connections", &self.max_idle_connections);
+        s.field("max_connections", &self.max_connections);
+        s.field("max_requests_per_connection", &self.max_requests_per_connection);
+        s.field("backlog", &self.backlog);
+        s.field("max_connections_per_ip", &self.max_connections_per_ip);
+        s.field("connection_limit_exempt", &self.connection_limit_exempt);
+        s.field("max_header_size", &self.max_header_size);
+        s.field("max_header_count", &self.max_header_count);
         s.field("log_level", &self.log_level);
+        s.field("verify_checksums", &self.verify_checksums);
+        s.field("server_name", &self.server_name);
+        s.field("header_policy", &self.header_policy);
+        s.field("features", &self.features);
+        s.field("allowed_hosts", &self.allowed_hosts);
+        s.field("hsts", &self.hsts);
+        s.field("upgrade_insecure_requests", &self.upgrade_insecure_requests);
+        s.field("https_port", &self.https_port);
+        s.field("trusted_proxies", &self.trusted_proxies);
+        s.field("response_digest", &self.response_digest);
+        s.field("cookie_limits", &self.cookie_limits);
+        s.field("private_cookie_cipher", &self.private_cookie_cipher);
+        s.field("compression", &self.compression);
+        s.field("text_encoding", &self.text_encoding);
+        s.field("mutual_tls", &self.mutual_tls);
+        s.field("tls_handshake_timeout", &self.tls_handshake_timeout);
+        s.field("tls_ocsp_refresh", &self.tls_ocsp_refresh);
+        s.field("tls_alpn", &self.tls_alpn);
+        s.field("tls_versions", &self.tls_versions);
+        s.field("tls_ciphersuites", &self.tls_ciphersuites);
+        s.field("mtls_allowed_fingerprints", &self.mtls_allowed_fingerprints);
 
         for (key, value) in self.extras() {
             s.field(key, &value);
@@ -932,6 +2495,35 @@ impl PartialEq for Config {
             && self.workers == other.workers
             && self.log_level == other.log_level
             && self.keep_alive == other.keep_alive
+            && self.max_idle_connections == other.max_idle_connections
+            && self.max_connections == other.max_connections
+            && self.max_requests_per_connection == other.max_requests_per_connection
+            && self.backlog == other.backlog
+            && self.max_connections_per_ip == other.max_connections_per_ip
+            && self.connection_limit_exempt == other.connection_limit_exempt
+            && self.max_header_size == other.max_header_size
+            && self.max_header_count == other.max_header_count
+            && self.verify_checksums == other.verify_checksums
+            && self.server_name == other.server_name
+            && self.header_policy == other.header_policy
+            && self.features == other.features
+            && self.allowed_hosts == other.allowed_hosts
+            && self.hsts == other.hsts
+            && self.upgrade_insecure_requests == other.upgrade_insecure_requests
+            && self.https_port == other.https_port
+            && self.trusted_proxies == other.trusted_proxies
+            && self.response_digest == other.response_digest
+            && self.cookie_limits == other.cookie_limits
+            && self.private_cookie_cipher == other.private_cookie_cipher
+            && self.compression == other.compression
+            && self.text_encoding == other.text_encoding
+            && self.mutual_tls == other.mutual_tls
+            && self.tls_handshake_timeout == other.tls_handshake_timeout
+            && self.tls_ocsp_refresh == other.tls_ocsp_refresh
+            && self.tls_alpn == other.tls_alpn
+            && self.tls_versions == other.tls_versions
+            && self.tls_ciphersuites == other.tls_ciphersuites
+            && self.mtls_allowed_fingerprints == other.mtls_allowed_fingerprints
             && self.environment == other.environment
             && self.extras == other.extras
     }
@@ -1,10 +1,15 @@
 use std::fmt;
+use std::cmp;
+use std::collections::HashMap;
+use std::time::Duration;
 
 #[cfg(feature = "tls")] use rustls::{Certificate, PrivateKey};
 
 use config::{Result, Config, Value, ConfigError, LoggingLevel};
+use response::Response;
+use data::{ChecksumAlgorithm, TextEncoding};
 use http::uncased::uncased_eq;
-use http::Key;
+use http::{Key, MediaType, CookieCipher};
 
 #[derive(Clone)]
 pub enum SecretKey {
@@ -42,13 +47,83 @@ impl fmt::Display for SecretKey {
 #[derive(Clone)]
 pub struct TlsConfig {
     pub certs: Vec<Certificate>,
-    pub key: PrivateKey
+    pub key: PrivateKey,
+    /// The paths the certificates and key were read from, if they were.
+    pub paths: Option<(String, String)>,
 }
 
 #[cfg(not(feature = "tls"))]
 #[derive(Clone)]
 pub struct TlsConfig;
 
+#[cfg(feature = "tls")]
+#[derive(Clone)]
+pub struct CertStore(pub Vec<Certificate>);
+
+#[cfg(not(feature = "tls"))]
+#[derive(Clone)]
+pub struct CertStore;
+
+/// A DER-encoded OCSP response for the default certificate and the path it
+/// was read from.
+#[cfg(feature = "tls")]
+#[derive(Clone)]
+pub struct OcspResponse {
+    pub path: String,
+    pub der: Vec<u8>,
+}
+
+#[cfg(not(feature = "tls"))]
+#[derive(Clone)]
+pub struct OcspResponse;
+
+/// Whether TLS clients authenticate with a certificate: the mutual TLS policy.
+///
+/// Client certificates are verified against the certificate store configured
+/// via the `cert_store` key of the `tls` table or
+/// [`ConfigBuilder::cert_store_path()`]. A store is required unless mutual TLS
+/// is disabled.
+///
+/// [`ConfigBuilder::cert_store_path()`]: /rocket/config/struct.ConfigBuilder.html#method.cert_store_path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MutualTls {
+    /// Client certificates are not requested.
+    Disabled,
+    /// Client certificates are requested but not required. Clients without a
+    /// certificate are served; a certificate that is presented must be valid.
+    Optional,
+    /// Every client must present a valid certificate. Handshakes with clients
+    /// that don't fail.
+    Required,
+}
+
+impl Default for MutualTls {
+    fn default() -> MutualTls {
+        MutualTls::Disabled
+    }
+}
+
+impl MutualTls {
+    fn from_name(name: &str) -> Option<MutualTls> {
+        match name {
+            "disabled" => Some(MutualTls::Disabled),
+            "optional" => Some(MutualTls::Optional),
+            "required" => Some(MutualTls::Required),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for MutualTls {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MutualTls::Disabled => write!(f, "disabled"),
+            MutualTls::Optional => write!(f, "optional"),
+            MutualTls::Required => write!(f, "required"),
+        }
+    }
+}
+
 /// Mapping from data type to size limits.
 ///
 /// A `Limits` structure contains a mapping from a given data type ("forms",
@@ -64,6 +139,20 @@ pub struct TlsConfig;
 ///
 ///   * **forms**: 32KiB
 ///
+/// # Timeouts
+///
+/// A `Limits` structure also bounds how long Rocket waits for the body of a
+/// request. The _read timeout_, 5 seconds by default, bounds each individual
+/// read of the body from the network. The _body timeout_, unset by default,
+/// bounds the total time spent reading the body, starting when Rocket begins
+/// reading it. Reads that exceed either fail with an error of kind
+/// `WouldBlock` or `TimedOut`. Both timeouts can be overridden for requests
+/// with a given `Content-Type` via [`read_timeout_for()`] and
+/// [`body_timeout_for()`].
+///
+/// [`read_timeout_for()`]: /rocket/config/struct.Limits.html#method.read_timeout_for
+/// [`body_timeout_for()`]: /rocket/config/struct.Limits.html#method.body_timeout_for
+///
 /// # Usage
 ///
 /// A `Limits` structure is created following the builder pattern:
@@ -80,13 +169,25 @@ pub struct TlsConfig;
 pub struct Limits {
     // We cache this internally but don't share that fact in the API.
     pub(crate) forms: u64,
-    extra: Vec<(String, u64)>
+    extra: Vec<(String, u64)>,
+    read_timeout: Duration,
+    body_timeout: Option<Duration>,
+    timeouts: Vec<ContentTimeouts>,
 }
 
+/// Timeouts overridden for a content type: (media type, read, body).
+type ContentTimeouts = (MediaType, Option<Duration>, Option<Duration>);
+
 impl Default for Limits {
     fn default() -> Limits {
-        // Default limit for forms is 32KiB.
-        Limits { forms: 32 * 1024, extra: Vec::new() }
+        // Default limit for forms is 32KiB, reads time out after 5 seconds.
+        Limits {
+            forms: 32 * 1024,
+            extra: Vec::new(),
+            read_timeout: Duration::from_secs(5),
+            body_timeout: None,
+            timeouts: Vec::new(),
+        }
     }
 }
 
@@ -173,6 +274,154 @@ impl Limits {
 
         None
     }
+
+    /// Sets the timeout for each read of a request body, consuming `self` and
+    /// returning the new `Limits` structure. The default is 5 seconds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `timeout` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use rocket::config::Limits;
+    ///
+    /// let limits = Limits::new().read_timeout(Duration::from_secs(10));
+    /// assert_eq!(limits.get_read_timeout(None), Duration::from_secs(10));
+    /// ```
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        assert!(timeout != Duration::from_secs(0), "read timeouts must be nonzero");
+        self.read_timeout = timeout;
+        self
+    }
+
+    /// Sets the timeout for reading an entire request body, consuming `self`
+    /// and returning the new `Limits` structure. By default, there is none.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `timeout` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use rocket::config::Limits;
+    ///
+    /// let limits = Limits::new().body_timeout(Duration::from_secs(30));
+    /// assert_eq!(limits.get_body_timeout(None), Some(Duration::from_secs(30)));
+    /// ```
+    pub fn body_timeout(mut self, timeout: Duration) -> Self {
+        assert!(timeout != Duration::from_secs(0), "body timeouts must be nonzero");
+        self.body_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the read timeout for request bodies with the media type
+    /// `media_type`, consuming `self` and returning the new `Limits`
+    /// structure. A media type with a `*` subtype, as in `multipart/*`,
+    /// applies to every subtype without a timeout of its own.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `timeout` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use rocket::config::Limits;
+    /// use rocket::http::MediaType;
+    ///
+    /// let limits = Limits::new()
+    ///     .read_timeout_for(MediaType::JSON, Duration::from_secs(2));
+    ///
+    /// let json = MediaType::JSON;
+    /// assert_eq!(limits.get_read_timeout(Some(&json)), Duration::from_secs(2));
+    /// assert_eq!(limits.get_read_timeout(None), Duration::from_secs(5));
+    /// ```
+    pub fn read_timeout_for(mut self, media_type: MediaType, timeout: Duration) -> Self {
+        assert!(timeout != Duration::from_secs(0), "read timeouts must be nonzero");
+        self.timeout_entry(media_type).1 = Some(timeout);
+        self
+    }
+
+    /// Sets the body timeout for request bodies with the media type
+    /// `media_type`, consuming `self` and returning the new `Limits`
+    /// structure. A media type with a `*` subtype, as in `multipart/*`,
+    /// applies to every subtype without a timeout of its own.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `timeout` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use rocket::config::Limits;
+    /// use rocket::http::MediaType;
+    ///
+    /// let uploads = MediaType::new("multipart", "*");
+    /// let limits = Limits::new()
+    ///     .body_timeout(Duration::from_secs(30))
+    ///     .body_timeout_for(uploads, Duration::from_secs(300));
+    ///
+    /// let form = MediaType::FormData;
+    /// assert_eq!(limits.get_body_timeout(Some(&form)), Some(Duration::from_secs(300)));
+    /// assert_eq!(limits.get_body_timeout(None), Some(Duration::from_secs(30)));
+    /// ```
+    pub fn body_timeout_for(mut self, media_type: MediaType, timeout: Duration) -> Self {
+        assert!(timeout != Duration::from_secs(0), "body timeouts must be nonzero");
+        self.timeout_entry(media_type).2 = Some(timeout);
+        self
+    }
+
+    /// Returns the read timeout for a request body with the media type
+    /// `media_type`, if any.
+    pub fn get_read_timeout(&self, media_type: Option<&MediaType>) -> Duration {
+        self.lookup_timeout(media_type, |entry| entry.1).unwrap_or(self.read_timeout)
+    }
+
+    /// Returns the body timeout for a request body with the media type
+    /// `media_type`, if any, or `None` if there is no body timeout.
+    pub fn get_body_timeout(&self, media_type: Option<&MediaType>) -> Option<Duration> {
+        self.lookup_timeout(media_type, |entry| entry.2).or(self.body_timeout)
+    }
+
+    fn timeout_entry(&mut self, media_type: MediaType) -> &mut ContentTimeouts {
+        let i = match self.timeouts.iter().position(|entry| entry.0.exact_eq(&media_type)) {
+            Some(i) => i,
+            None => {
+                self.timeouts.push((media_type, None, None));
+                self.timeouts.len() - 1
+            }
+        };
+
+        &mut self.timeouts[i]
+    }
+
+    /// Looks up the timeout `get` selects for `media_type`: that of an entry
+    /// for the exact type, otherwise that of an entry for its `*` subtype.
+    fn lookup_timeout<F>(&self, media_type: Option<&MediaType>, get: F) -> Option<Duration>
+        where F: Fn(&ContentTimeouts) -> Option<Duration>
+    {
+        let media_type = media_type?;
+        let same_top = |entry: &&ContentTimeouts| entry.0.top() == media_type.top();
+        let exact = self.timeouts.iter()
+            .filter(&same_top)
+            .find(|entry| entry.0.sub() == media_type.sub())
+            .and_then(&get);
+
+        exact.or_else(|| {
+            self.timeouts.iter()
+                .filter(&same_top)
+                .find(|entry| entry.0.sub() == "*")
+                .and_then(&get)
+        })
+    }
 }
 
 impl fmt::Display for Limits {
@@ -194,14 +443,599 @@ impl fmt::Display for Limits {
             fmt_size(val, f)?;
         }
 
+        write!(f, ", read_timeout = {}s", self.read_timeout.as_secs())?;
+        if let Some(timeout) = self.body_timeout {
+            write!(f, ", body_timeout = {}s", timeout.as_secs())?;
+        }
+
+        for &(ref media_type, read, body) in &self.timeouts {
+            write!(f, ", {}*", media_type)?;
+            if let Some(timeout) = read {
+                write!(f, " read_timeout = {}s", timeout.as_secs())?;
+            }
+
+            if let Some(timeout) = body {
+                write!(f, " body_timeout = {}s", timeout.as_secs())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Policy for scrubbing response headers before they are written out.
+///
+/// A `HeaderPolicy` removes or rewrites response headers that may reveal
+/// details about an application, such as `Server`, `X-Powered-By`, or debugging
+/// headers, after all fairings have run. It consists of three lists:
+///
+///   * **deny**: headers that are always removed.
+///   * **allow**: if non-empty, headers that are _not_ in this list are
+///     removed, except those essential to HTTP: `Content-Type`,
+///     `Content-Encoding`, `Content-Range`, `Location`, `Set-Cookie`, `Allow`,
+///     and `WWW-Authenticate`.
+///   * **rewrite**: headers whose value, when present, is replaced.
+///
+/// Header names are matched case-insensitively. A name ending in `*`, such as
+/// `X-Debug-*`, matches every header with that prefix. The default policy
+/// leaves responses untouched.
+///
+/// # Usage
+///
+/// A `HeaderPolicy` structure is created following the builder pattern:
+///
+/// ```rust
+/// use rocket::config::HeaderPolicy;
+///
+/// // Remove `X-Powered-By` and debug headers; disguise the `Server` header.
+/// let policy = HeaderPolicy::new()
+///     .deny("X-Powered-By")
+///     .deny("X-Debug-*")
+///     .rewrite("Server", "webserver");
+///
+/// assert!(!policy.is_allowed("x-debug-trace"));
+/// assert!(policy.is_allowed("Server"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeaderPolicy {
+    allow: Vec<String>,
+    deny: Vec<String>,
+    rewrite: Vec<(String, String)>,
+}
+
+/// Headers that an allow list never removes.
+/// The TLS protocol versions that can be enabled via `tls_versions`.
+pub(crate) const TLS_VERSIONS: &[&str] = &["TLSv1.2", "TLSv1.3"];
+
+/// The TLS cipher suites that can be enabled via `tls_ciphersuites`, in order
+/// of preference. The `TLS13_` suites are only used with TLS 1.3; the rest are
+/// only used with TLS 1.2.
+pub(crate) const TLS_CIPHERSUITES: &[&str] = &[
+    "TLS13_CHACHA20_POLY1305_SHA256",
+    "TLS13_AES_256_GCM_SHA384",
+    "TLS13_AES_128_GCM_SHA256",
+    "TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256",
+    "TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256",
+    "TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384",
+    "TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256",
+    "TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384",
+    "TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256",
+];
+
+const ESSENTIAL_HEADERS: &'static [&'static str] = &[
+    "Content-Type", "Content-Encoding", "Content-Range", "Location",
+    "Set-Cookie", "Allow", "WWW-Authenticate"
+];
+
+// Whether the header `name` matches `pattern`, which may end in a `*`.
+fn header_matches(pattern: &str, name: &str) -> bool {
+    if pattern.ends_with('*') {
+        let prefix = &pattern[..(pattern.len() - 1)];
+        name.get(..prefix.len()).map_or(false, |start| uncased_eq(start, prefix))
+    } else {
+        uncased_eq(pattern, name)
+    }
+}
+
+impl HeaderPolicy {
+    /// Construct a new `HeaderPolicy` that leaves all headers untouched.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::HeaderPolicy;
+    ///
+    /// let policy = HeaderPolicy::new();
+    /// assert!(policy.is_empty());
+    /// assert!(policy.is_allowed("X-Powered-By"));
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        HeaderPolicy::default()
+    }
+
+    /// Adds `name` to the allow list, consuming `self` and returning the new
+    /// policy. Once the allow list is non-empty, headers not in the list are
+    /// removed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::HeaderPolicy;
+    ///
+    /// let policy = HeaderPolicy::new()
+    ///     .allow("Cache-Control")
+    ///     .allow("X-Request-*");
+    ///
+    /// assert!(policy.is_allowed("cache-control"));
+    /// assert!(policy.is_allowed("X-Request-Id"));
+    /// assert!(policy.is_allowed("Content-Type"));
+    /// assert!(!policy.is_allowed("Server"));
+    /// ```
+    pub fn allow<S: Into<String>>(mut self, name: S) -> Self {
+        self.allow.push(name.into());
+        self
+    }
+
+    /// Adds `name` to the deny list, consuming `self` and returning the new
+    /// policy. Denied headers are removed even if they are also allowed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::HeaderPolicy;
+    ///
+    /// let policy = HeaderPolicy::new().deny("Server");
+    /// assert!(!policy.is_allowed("server"));
+    /// assert!(policy.is_allowed("Cache-Control"));
+    /// ```
+    pub fn deny<S: Into<String>>(mut self, name: S) -> Self {
+        self.deny.push(name.into());
+        self
+    }
+
+    /// Adds or replaces a rewrite rule in `self` so that the value of the
+    /// header `name`, when present in a response, is replaced with `value`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::HeaderPolicy;
+    ///
+    /// let policy = HeaderPolicy::new().rewrite("Server", "webserver");
+    /// assert_eq!(policy.rewritten("server"), Some("webserver"));
+    /// assert_eq!(policy.rewritten("Via"), None);
+    /// ```
+    pub fn rewrite<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        let (name, value) = (name.into(), value.into());
+        self.rewrite.retain(|&(ref existing, _)| !uncased_eq(existing, &name));
+        self.rewrite.push((name, value));
+        self
+    }
+
+    /// Returns `true` if the policy leaves all headers untouched.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty() && self.rewrite.is_empty()
+    }
+
+    /// Returns `true` if a header named `name` is kept by this policy.
+    pub fn is_allowed(&self, name: &str) -> bool {
+        if self.deny.iter().any(|pattern| header_matches(pattern, name)) {
+            return false;
+        }
+
+        self.allow.is_empty()
+            || ESSENTIAL_HEADERS.iter().any(|essential| uncased_eq(essential, name))
+            || self.allow.iter().any(|pattern| header_matches(pattern, name))
+    }
+
+    /// Returns the value the header `name` is rewritten to, if any.
+    pub fn rewritten(&self, name: &str) -> Option<&str> {
+        self.rewrite.iter()
+            .find(|&&(ref header, _)| header_matches(header, name))
+            .map(|&(_, ref value)| value.as_str())
+    }
+
+    /// Removes and rewrites the headers in `response` according to `self`.
+    pub(crate) fn apply(&self, response: &mut Response) {
+        if self.is_empty() {
+            return;
+        }
+
+        let names: Vec<String> = response.headers().iter()
+            .map(|header| header.name().to_string())
+            .collect();
+
+        for name in names {
+            if !self.is_allowed(&name) {
+                response.remove_header(&name);
+            } else if let Some(value) = self.rewritten(&name) {
+                response.set_raw_header(name, value.to_string());
+            }
+        }
+    }
+}
+
+impl fmt::Display for HeaderPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "none");
+        }
+
+        let mut first = true;
+        let mut list = |f: &mut fmt::Formatter, kind: &str, names: Vec<&str>| {
+            if names.is_empty() {
+                return Ok(());
+            }
+
+            let sep = if first { "" } else { ", " };
+            first = false;
+            write!(f, "{}{} = [{}]", sep, kind, names.join(", "))
+        };
+
+        list(f, "allow", self.allow.iter().map(|s| s.as_str()).collect())?;
+        list(f, "deny", self.deny.iter().map(|s| s.as_str()).collect())?;
+        list(f, "rewrite", self.rewrite.iter().map(|&(ref s, _)| s.as_str()).collect())
+    }
+}
+
+/// HTTP Strict Transport Security (HSTS) policy.
+///
+/// When TLS is enabled and an `Hsts` policy is configured, every response
+/// carries a `Strict-Transport-Security` header instructing browsers to only
+/// contact the application over HTTPS for `max_age` seconds. The header is
+/// never sent over plaintext connections, where browsers ignore it.
+///
+/// A policy that includes the `preload` directive must also satisfy the
+/// requirements for inclusion in browser preload lists: a `max_age` of at least
+/// one year and the `includeSubDomains` directive. Such a policy is created
+/// with [`Hsts::preload()`](#method.preload).
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::config::Hsts;
+///
+/// let hsts = Hsts::new(86400).include_subdomains(true);
+/// assert_eq!(hsts.to_string(), "max-age=86400; includeSubDomains");
+///
+/// let hsts = Hsts::preload();
+/// assert_eq!(hsts.to_string(), "max-age=63072000; includeSubDomains; preload");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hsts {
+    max_age: u64,
+    include_subdomains: bool,
+    preload: bool,
+}
+
+/// The minimum `max-age`, one year, that browser preload lists accept.
+const PRELOAD_MIN_MAX_AGE: u64 = 365 * 24 * 60 * 60;
+
+impl Default for Hsts {
+    fn default() -> Hsts {
+        Hsts::new(PRELOAD_MIN_MAX_AGE)
+    }
+}
+
+impl Hsts {
+    /// Returns a policy with a `max-age` of `max_age` seconds that doesn't
+    /// apply to subdomains.
+    #[inline]
+    pub fn new(max_age: u64) -> Hsts {
+        Hsts { max_age: max_age, include_subdomains: false, preload: false }
+    }
+
+    /// Returns a preload-ready policy: a `max-age` of two years with the
+    /// `includeSubDomains` and `preload` directives.
+    #[inline]
+    pub fn preload() -> Hsts {
+        Hsts { max_age: 2 * PRELOAD_MIN_MAX_AGE, include_subdomains: true, preload: true }
+    }
+
+    /// Sets whether the policy applies to subdomains as well.
+    #[inline]
+    pub fn include_subdomains(mut self, include: bool) -> Hsts {
+        self.include_subdomains = include;
+        self
+    }
+
+    /// Returns the `max-age` of the policy in seconds.
+    #[inline]
+    pub fn max_age(&self) -> u64 {
+        self.max_age
+    }
+
+    /// Returns `true` if the policy includes the `preload` directive.
+    #[inline]
+    pub fn is_preload(&self) -> bool {
+        self.preload
+    }
+}
+
+impl fmt::Display for Hsts {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "max-age={}", self.max_age)?;
+        if self.include_subdomains {
+            write!(f, "; includeSubDomains")?;
+        }
+
+        if self.preload {
+            write!(f, "; preload")?;
+        }
+
         Ok(())
     }
 }
 
+/// What to do with cookies that exceed the configured [`CookieLimits`].
+///
+/// [`CookieLimits`]: /rocket/config/struct.CookieLimits.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CookieOverflow {
+    /// Requests are rejected with `431 Request Header Fields Too Large`;
+    /// responses are replaced with a `500 Internal Server Error`.
+    Reject,
+    /// The oldest cookies, those first in the request's `Cookie` headers or
+    /// first set by the application, are dropped until the rest fit. Removal
+    /// cookies are never dropped; if a response's cookies can't fit without
+    /// dropping one, the response is rejected as with `Reject`.
+    TruncateOldest,
+    /// A warning is logged and all cookies are kept.
+    Log,
+}
+
+impl CookieOverflow {
+    fn from_name(name: &str) -> Option<CookieOverflow> {
+        match name {
+            "reject" => Some(CookieOverflow::Reject),
+            "truncate_oldest" => Some(CookieOverflow::TruncateOldest),
+            "log" => Some(CookieOverflow::Log),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for CookieOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CookieOverflow::Reject => write!(f, "reject"),
+            CookieOverflow::TruncateOldest => write!(f, "truncate_oldest"),
+            CookieOverflow::Log => write!(f, "log"),
+        }
+    }
+}
+
+/// Limits on the number and total size of cookies.
+///
+/// The limits apply independently to the cookies in an incoming request's
+/// `Cookie` headers and to the `Set-Cookie` headers Rocket emits for the
+/// cookies an application sets. The size of a cookie is the length of its
+/// serialized form: `name=value` inbound and the full `Set-Cookie` value
+/// outbound. Cookies exceeding either limit are handled according to the
+/// [`CookieOverflow`] policy.
+///
+/// By default, at most 50 cookies totaling at most 8KiB are expected, and a
+/// warning is logged when a limit is exceeded.
+///
+/// [`CookieOverflow`]: /rocket/config/enum.CookieOverflow.html
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::config::{CookieLimits, CookieOverflow};
+///
+/// let limits = CookieLimits::new(20, 4096).overflow(CookieOverflow::Reject);
+/// assert_eq!(limits.max_count(), 20);
+/// assert_eq!(limits.to_string(), "20 cookies, 4096 bytes, reject");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CookieLimits {
+    max_count: usize,
+    max_size: usize,
+    overflow: CookieOverflow,
+}
+
+impl Default for CookieLimits {
+    fn default() -> CookieLimits {
+        CookieLimits::new(50, 8 * 1024)
+    }
+}
+
+impl CookieLimits {
+    /// Returns limits of at most `max_count` cookies totaling at most
+    /// `max_size` bytes that log a warning when exceeded.
+    #[inline]
+    pub fn new(max_count: usize, max_size: usize) -> CookieLimits {
+        CookieLimits {
+            max_count: max_count,
+            max_size: max_size,
+            overflow: CookieOverflow::Log
+        }
+    }
+
+    /// Sets the policy applied when the limits are exceeded.
+    #[inline]
+    pub fn overflow(mut self, overflow: CookieOverflow) -> CookieLimits {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Returns the maximum number of cookies.
+    #[inline]
+    pub fn max_count(&self) -> usize {
+        self.max_count
+    }
+
+    /// Returns the maximum total size of all cookies in bytes.
+    #[inline]
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// Returns the policy applied when the limits are exceeded.
+    #[inline]
+    pub fn overflow_policy(&self) -> CookieOverflow {
+        self.overflow
+    }
+
+    /// Given the sizes of cookies from oldest to newest, returns how many of
+    /// the oldest cookies must be dropped for the rest to fit the limits.
+    pub(crate) fn excess(&self, sizes: &[usize]) -> usize {
+        let mut total: usize = sizes.iter().sum();
+        let mut excess = 0;
+        while excess < sizes.len()
+            && (sizes.len() - excess > self.max_count || total > self.max_size)
+        {
+            total -= sizes[excess];
+            excess += 1;
+        }
+
+        excess
+    }
+}
+
+impl fmt::Display for CookieLimits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} cookies, {} bytes, {}", self.max_count, self.max_size, self.overflow)
+    }
+}
+
+/// Compression settings for response and request bodies.
+///
+/// When compression is configured, sized response bodies of at least
+/// `min_size` bytes are compressed with the content coding the client prefers
+/// according to the quality values in its `Accept-Encoding` header. Only the
+/// codings enabled here whose feature is enabled are offered: `br` with the
+/// `brotli` feature, `zstd` with the `zstd` feature, and `gzip` with the
+/// `gzip` feature. Among codings of equal quality, `br` is preferred, then
+/// `zstd`, then `gzip`. Responses that already have a `Content-Encoding` and
+/// media types that are already compressed, such as images, are left alone.
+///
+/// Request bodies sent with one of the enabled codings are decompressed
+/// before routing, and the `Content-Encoding` header is removed. A body that is
+/// larger, or decompresses to more, than the `decompressed` limit, 1MiB by
+/// default, is rejected with `413 Payload Too Large`, and a body that can't be
+/// decompressed with `400 Bad Request`. Checksums in `Content-MD5` and
+/// `Digest` headers are verified against the compressed body.
+///
+/// By default, every coding is enabled: `gzip` at level 6 of 0 to 9, `br` at
+/// quality 4 of 0 to 11, and `zstd` at level 3 of 1 to 21. Bodies smaller than
+/// 1KiB aren't compressed.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::config::CompressionConfig;
+///
+/// let compression = CompressionConfig::default()
+///     .brotli(11)
+///     .zstd(None)
+///     .min_size(512);
+///
+/// assert_eq!(compression.brotli_quality(), Some(11));
+/// assert_eq!(compression.zstd_level(), None);
+/// assert_eq!(compression.to_string(), "gzip 6, br 11, min 512 bytes");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionConfig {
+    gzip: Option<u32>,
+    brotli: Option<u32>,
+    zstd: Option<i32>,
+    min_size: usize,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> CompressionConfig {
+        CompressionConfig { gzip: Some(6), brotli: Some(4), zstd: Some(3), min_size: 1024 }
+    }
+}
+
+impl CompressionConfig {
+    /// Sets the `gzip` compression level, clamped to `0` through `9`, or
+    /// disables `gzip` if `level` is `None`.
+    #[inline]
+    pub fn gzip<L: Into<Option<u32>>>(mut self, level: L) -> CompressionConfig {
+        self.gzip = level.into().map(|level| cmp::min(level, 9));
+        self
+    }
+
+    /// Sets the `br` compression quality, clamped to `0` through `11`, or
+    /// disables `br` if `quality` is `None`.
+    #[inline]
+    pub fn brotli<Q: Into<Option<u32>>>(mut self, quality: Q) -> CompressionConfig {
+        self.brotli = quality.into().map(|quality| cmp::min(quality, 11));
+        self
+    }
+
+    /// Sets the `zstd` compression level, clamped to `1` through `21`, or
+    /// disables `zstd` if `level` is `None`.
+    #[inline]
+    pub fn zstd<L: Into<Option<i32>>>(mut self, level: L) -> CompressionConfig {
+        self.zstd = level.into().map(|level| cmp::max(1, cmp::min(level, 21)));
+        self
+    }
+
+    /// Sets the minimum size in bytes of response bodies that are compressed.
+    #[inline]
+    pub fn min_size(mut self, min_size: usize) -> CompressionConfig {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Returns the `gzip` compression level or `None` if `gzip` is disabled.
+    #[inline]
+    pub fn gzip_level(&self) -> Option<u32> {
+        self.gzip
+    }
+
+    /// Returns the `br` compression quality or `None` if `br` is disabled.
+    #[inline]
+    pub fn brotli_quality(&self) -> Option<u32> {
+        self.brotli
+    }
+
+    /// Returns the `zstd` compression level or `None` if `zstd` is disabled.
+    #[inline]
+    pub fn zstd_level(&self) -> Option<i32> {
+        self.zstd
+    }
+
+    /// Returns the minimum size in bytes of response bodies that are
+    /// compressed.
+    #[inline]
+    pub fn min_body_size(&self) -> usize {
+        self.min_size
+    }
+}
+
+impl fmt::Display for CompressionConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(level) = self.gzip {
+            write!(f, "gzip {}, ", level)?;
+        }
+
+        if let Some(quality) = self.brotli {
+            write!(f, "br {}, ", quality)?;
+        }
+
+        if let Some(level) = self.zstd {
+            write!(f, "zstd {}, ", level)?;
+        }
+
+        write!(f, "min {} bytes", self.min_size)
+    }
+}
+
 pub fn str<'a>(conf: &Config, name: &str, v: &'a Value) -> Result<&'a str> {
     v.as_str().ok_or(conf.bad_type(name, v.type_str(), "a string"))
 }
 
+pub fn bool(conf: &Config, name: &str, v: &Value) -> Result<bool> {
+    v.as_bool().ok_or(conf.bad_type(name, v.type_str(), "a boolean"))
+}
+
 pub fn u64(conf: &Config, name: &str, value: &Value) -> Result<u64> {
     match value.as_integer() {
         Some(x) if x >= 0 => Ok(x as u64),
@@ -216,6 +1050,13 @@ pub fn u16(conf: &Config, name: &str, value: &Value) -> Result<u16> {
     }
 }
 
+pub fn u32(conf: &Config, name: &str, value: &Value) -> Result<u32> {
+    match value.as_integer() {
+        Some(x) if x >= 0 && x <= (u32::max_value() as i64) => Ok(x as u32),
+        _ => Err(conf.bad_type(name, value.type_str(), "a 32-bit unsigned integer"))
+    }
+}
+
 pub fn log_level(conf: &Config,
                           name: &str,
                           value: &Value
@@ -224,44 +1065,373 @@ pub fn log_level(conf: &Config,
         .and_then(|s| s.parse().map_err(|e| conf.bad_type(name, value.type_str(), e)))
 }
 
-pub fn tls_config<'v>(conf: &Config,
-                               name: &str,
-                               value: &'v Value,
-                               ) -> Result<(&'v str, &'v str)> {
-    let (mut certs_path, mut key_path) = (None, None);
+/// The paths in a `tls` table.
+pub struct RawTls<'v> {
+    pub certs: &'v str,
+    pub key: &'v str,
+    pub cert_store: Option<&'v str>,
+    pub ocsp_response: Option<&'v str>,
+    /// (hostname, certs, key) for each entry in the `sni` table.
+    pub sni: Vec<(&'v str, &'v str, &'v str)>,
+}
+
+pub fn tls_config<'v>(conf: &Config, name: &str, value: &'v Value) -> Result<RawTls<'v>> {
+    let (mut certs_path, mut key_path, mut cert_store_path) = (None, None, None);
+    let (mut ocsp_path, mut sni) = (None, vec![]);
     let table = value.as_table()
         .ok_or_else(|| conf.bad_type(name, value.type_str(), "a table"))?;
 
-    let env = conf.environment;
+    let env = &conf.environment;
     for (key, value) in table {
         match key.as_str() {
             "certs" => certs_path = Some(str(conf, "tls.certs", value)?),
             "key" => key_path = Some(str(conf, "tls.key", value)?),
+            "cert_store" => cert_store_path = Some(str(conf, "tls.cert_store", value)?),
+            "ocsp_response_path" => {
+                ocsp_path = Some(str(conf, "tls.ocsp_response_path", value)?)
+            }
+            "sni" => sni = tls_sni(conf, value)?,
             _ => return Err(ConfigError::UnknownKey(format!("{}.tls.{}", env, key)))
         }
     }
 
     if let (Some(certs), Some(key)) = (certs_path, key_path) {
-        Ok((certs, key))
+        Ok(RawTls {
+            certs: certs,
+            key: key,
+            cert_store: cert_store_path,
+            ocsp_response: ocsp_path,
+            sni: sni
+        })
     } else {
         Err(conf.bad_type(name, "a table with missing entries",
                             "a table with `certs` and `key` entries"))
     }
 }
 
+/// Parses the `tls.sni` table, which maps hostnames to tables with `certs`
+/// and `key` entries.
+fn tls_sni<'v>(conf: &Config, value: &'v Value) -> Result<Vec<(&'v str, &'v str, &'v str)>> {
+    let table = value.as_table()
+        .ok_or_else(|| conf.bad_type("tls.sni", value.type_str(), "a table"))?;
+
+    let mut sni = vec![];
+    for (hostname, entry) in table {
+        let id = format!("tls.sni.{}", hostname);
+        let entry = entry.as_table()
+            .ok_or_else(|| conf.bad_type(&id, entry.type_str(), "a table"))?;
+
+        let (mut certs_path, mut key_path) = (None, None);
+        for (key, value) in entry {
+            match key.as_str() {
+                "certs" => certs_path = Some(str(conf, &id, value)?),
+                "key" => key_path = Some(str(conf, &id, value)?),
+                _ => {
+                    let env = &conf.environment;
+                    return Err(ConfigError::UnknownKey(format!("{}.{}.{}", env, id, key)));
+                }
+            }
+        }
+
+        match (certs_path, key_path) {
+            (Some(certs), Some(key)) => sni.push((hostname.as_str(), certs, key)),
+            _ => return Err(conf.bad_type(&id, "a table with missing entries",
+                                          "a table with `certs` and `key` entries"))
+        }
+    }
+
+    Ok(sni)
+}
+
 pub fn limits(conf: &Config, name: &str, value: &Value) -> Result<Limits> {
     let table = value.as_table()
         .ok_or_else(|| conf.bad_type(name, value.type_str(), "a table"))?;
 
     let mut limits = Limits::default();
     for (key, val) in table {
-        let val = u64(conf, &format!("limits.{}", key), val)?;
-        limits = limits.limit(key.as_str(), val);
+        let id = format!("limits.{}", key);
+        match key.as_str() {
+            "read_timeout" => limits = limits.read_timeout(timeout(conf, &id, val)?),
+            "body_timeout" => limits = limits.body_timeout(timeout(conf, &id, val)?),
+            "timeouts" => limits = content_timeouts(conf, limits, val)?,
+            _ => limits = limits.limit(key.as_str(), u64(conf, &id, val)?),
+        }
+    }
+
+    Ok(limits)
+}
+
+/// Parses a nonzero timeout in seconds.
+fn timeout(conf: &Config, name: &str, value: &Value) -> Result<Duration> {
+    match value.as_integer() {
+        Some(secs) if secs > 0 => Ok(Duration::from_secs(secs as u64)),
+        _ => Err(conf.bad_type(name, value.type_str(), "a positive integer"))
+    }
+}
+
+/// Parses the `limits.timeouts` table, which maps media types to tables with
+/// `read_timeout` and `body_timeout` keys, into `limits`.
+fn content_timeouts(conf: &Config, mut limits: Limits, value: &Value) -> Result<Limits> {
+    let table = value.as_table()
+        .ok_or_else(|| conf.bad_type("limits.timeouts", value.type_str(), "a table"))?;
+
+    for (name, entry) in table {
+        let id = format!("limits.timeouts.{}", name);
+        let media_type = MediaType::parse_flexible(name)
+            .ok_or_else(|| conf.bad_type(&id, "a string", "a media type"))?;
+
+        let entry = entry.as_table()
+            .ok_or_else(|| conf.bad_type(&id, entry.type_str(), "a table"))?;
+
+        for (key, val) in entry {
+            let id = format!("{}.{}", id, key);
+            let media_type = media_type.clone();
+            limits = match key.as_str() {
+                "read_timeout" => limits.read_timeout_for(media_type, timeout(conf, &id, val)?),
+                "body_timeout" => limits.body_timeout_for(media_type, timeout(conf, &id, val)?),
+                _ => return Err(ConfigError::UnknownKey(format!("{}.{}", conf.environment, id)))
+            };
+        }
+    }
+
+    Ok(limits)
+}
+
+pub fn header_policy(conf: &Config, name: &str, value: &Value) -> Result<HeaderPolicy> {
+    fn names<'v>(conf: &Config, name: &str, value: &'v Value) -> Result<Vec<&'v str>> {
+        let array = value.as_array()
+            .ok_or_else(|| conf.bad_type(name, value.type_str(), "an array of strings"))?;
+
+        array.iter().map(|v| str(conf, name, v)).collect()
+    }
+
+    let table = value.as_table()
+        .ok_or_else(|| conf.bad_type(name, value.type_str(), "a table"))?;
+
+    let mut policy = HeaderPolicy::new();
+    for (key, value) in table {
+        match key.as_str() {
+            "allow" => for header in names(conf, "header_policy.allow", value)? {
+                policy = policy.allow(header);
+            },
+            "deny" => for header in names(conf, "header_policy.deny", value)? {
+                policy = policy.deny(header);
+            },
+            "rewrite" => {
+                let rewrites = value.as_table().ok_or_else(|| {
+                    conf.bad_type("header_policy.rewrite", value.type_str(), "a table")
+                })?;
+
+                for (header, value) in rewrites {
+                    let id = format!("header_policy.rewrite.{}", header);
+                    policy = policy.rewrite(header.as_str(), str(conf, &id, value)?);
+                }
+            }
+            _ => {
+                let id = format!("{}.header_policy.{}", conf.environment, key);
+                return Err(ConfigError::UnknownKey(id));
+            }
+        }
+    }
+
+    Ok(policy)
+}
+
+pub fn server_name<'v>(conf: &Config, name: &str, value: &'v Value) -> Result<Option<&'v str>> {
+    match (value.as_str(), value.as_bool()) {
+        (Some(server), _) => Ok(Some(server)),
+        (None, Some(false)) => Ok(None),
+        _ => Err(conf.bad_type(name, value.type_str(), "a string or 'false'"))
+    }
+}
+
+pub fn digest_algorithm(
+    conf: &Config,
+    name: &str,
+    value: &Value
+) -> Result<Option<ChecksumAlgorithm>> {
+    let expect = "'MD5', 'SHA-256', or 'false'";
+    match (value.as_str(), value.as_bool()) {
+        (Some(alg), _) => ChecksumAlgorithm::from_name(alg)
+            .map(Some)
+            .ok_or_else(|| conf.bad_type(name, value.type_str(), expect)),
+        (None, Some(false)) => Ok(None),
+        _ => Err(conf.bad_type(name, value.type_str(), expect))
+    }
+}
+
+pub fn text_encoding(conf: &Config, name: &str, value: &Value) -> Result<Option<TextEncoding>> {
+    let expect = "'strict', 'lossy', or 'false'";
+    match (value.as_str(), value.as_bool()) {
+        (Some(mode), _) => TextEncoding::from_name(mode)
+            .map(Some)
+            .ok_or_else(|| conf.bad_type(name, value.type_str(), expect)),
+        (None, Some(false)) => Ok(None),
+        _ => Err(conf.bad_type(name, value.type_str(), expect))
+    }
+}
+
+pub fn cookie_cipher(conf: &Config, name: &str, value: &Value) -> Result<CookieCipher> {
+    let expect = "'AES-256-GCM' or 'ChaCha20-Poly1305'";
+    let cipher = value.as_str()
+        .ok_or_else(|| conf.bad_type(name, value.type_str(), expect))?;
+
+    CookieCipher::from_name(cipher).ok_or_else(|| conf.bad_type(name, value.type_str(), expect))
+}
+
+pub fn features(conf: &Config, name: &str, value: &Value) -> Result<HashMap<String, bool>> {
+    let table = value.as_table()
+        .ok_or_else(|| conf.bad_type(name, value.type_str(), "a table"))?;
+
+    let mut features = HashMap::new();
+    for (key, val) in table {
+        let enabled = bool(conf, &format!("features.{}", key), val)?;
+        features.insert(key.clone(), enabled);
+    }
+
+    Ok(features)
+}
+
+pub fn hosts(conf: &Config, name: &str, value: &Value) -> Result<Vec<String>> {
+    let array = value.as_array()
+        .ok_or_else(|| conf.bad_type(name, value.type_str(), "an array of strings"))?;
+
+    array.iter()
+        .map(|host| str(conf, name, host).map(|host| host.to_string()))
+        .collect()
+}
+
+pub fn networks(conf: &Config, name: &str, value: &Value) -> Result<Vec<String>> {
+    let array = value.as_array()
+        .ok_or_else(|| conf.bad_type(name, value.type_str(), "an array of strings"))?;
+
+    array.iter()
+        .map(|network| str(conf, name, network).map(|network| network.trim().to_string()))
+        .collect()
+}
+
+pub fn fingerprints(conf: &Config, name: &str, value: &Value) -> Result<Vec<String>> {
+    let array = value.as_array()
+        .ok_or_else(|| conf.bad_type(name, value.type_str(), "an array of strings"))?;
+
+    array.iter()
+        .map(|fingerprint| str(conf, name, fingerprint).map(|f| f.trim().to_string()))
+        .collect()
+}
+
+pub fn strings(conf: &Config, name: &str, value: &Value) -> Result<Vec<String>> {
+    let array = value.as_array()
+        .ok_or_else(|| conf.bad_type(name, value.type_str(), "an array of strings"))?;
+
+    array.iter()
+        .map(|string| str(conf, name, string).map(|string| string.to_string()))
+        .collect()
+}
+
+pub fn hsts(conf: &Config, name: &str, value: &Value) -> Result<Option<Hsts>> {
+    if let Some(enabled) = value.as_bool() {
+        return Ok(if enabled { Some(Hsts::default()) } else { None });
+    }
+
+    let table = value.as_table()
+        .ok_or_else(|| conf.bad_type(name, value.type_str(), "a table or boolean"))?;
+
+    let mut hsts = Hsts::default();
+    for (key, value) in table {
+        match key.as_str() {
+            "max_age" => hsts.max_age = u64(conf, "hsts.max_age", value)?,
+            "include_subdomains" => {
+                hsts.include_subdomains = bool(conf, "hsts.include_subdomains", value)?
+            }
+            "preload" => hsts.preload = bool(conf, "hsts.preload", value)?,
+            _ => {
+                let id = format!("{}.hsts.{}", conf.environment, key);
+                return Err(ConfigError::UnknownKey(id));
+            }
+        }
+    }
+
+    if hsts.preload && (!hsts.include_subdomains || hsts.max_age < PRELOAD_MIN_MAX_AGE) {
+        return Err(conf.bad_type(name, "a policy that can't be preloaded",
+            "`include_subdomains = true` and a `max_age` of a year or more with `preload`"));
+    }
+
+    Ok(Some(hsts))
+}
+
+pub fn cookie_limits(conf: &Config, name: &str, value: &Value) -> Result<CookieLimits> {
+    let table = value.as_table()
+        .ok_or_else(|| conf.bad_type(name, value.type_str(), "a table"))?;
+
+    let mut limits = CookieLimits::default();
+    for (key, value) in table {
+        match key.as_str() {
+            "max_count" => limits.max_count = u64(conf, "cookie_limits.max_count", value)? as usize,
+            "max_size" => limits.max_size = u64(conf, "cookie_limits.max_size", value)? as usize,
+            "overflow" => {
+                let expect = "'reject', 'truncate_oldest', or 'log'";
+                limits.overflow = str(conf, "cookie_limits.overflow", value)
+                    .ok()
+                    .and_then(CookieOverflow::from_name)
+                    .ok_or_else(|| conf.bad_type("cookie_limits.overflow", value.type_str(), expect))?;
+            }
+            _ => {
+                let id = format!("{}.cookie_limits.{}", conf.environment, key);
+                return Err(ConfigError::UnknownKey(id));
+            }
+        }
     }
 
     Ok(limits)
 }
 
+pub fn compression(conf: &Config, name: &str, value: &Value) -> Result<Option<CompressionConfig>> {
+    if let Some(enabled) = value.as_bool() {
+        return Ok(if enabled { Some(CompressionConfig::default()) } else { None });
+    }
+
+    let table = value.as_table()
+        .ok_or_else(|| conf.bad_type(name, value.type_str(), "a table or boolean"))?;
+
+    // Each coding is set to a level, clamped by the setters, or disabled with
+    // `false`. No coding has levels above 21.
+    let level = |key: &str, value: &Value| -> Result<Option<u64>> {
+        match value.as_bool() {
+            Some(false) => Ok(None),
+            _ => {
+                let level = u64(conf, &format!("compression.{}", key), value)?;
+                Ok(Some(cmp::min(level, 21)))
+            }
+        }
+    };
+
+    let mut compression = CompressionConfig::default();
+    for (key, value) in table {
+        match key.as_str() {
+            "gzip" => compression = compression.gzip(level(key, value)?.map(|l| l as u32)),
+            "brotli" => compression = compression.brotli(level(key, value)?.map(|l| l as u32)),
+            "zstd" => compression = compression.zstd(level(key, value)?.map(|l| l as i32)),
+            "min_size" => {
+                let min_size = u64(conf, "compression.min_size", value)?;
+                compression = compression.min_size(min_size as usize);
+            }
+            _ => {
+                let id = format!("{}.compression.{}", conf.environment, key);
+                return Err(ConfigError::UnknownKey(id));
+            }
+        }
+    }
+
+    Ok(Some(compression))
+}
+
+pub fn mutual_tls(conf: &Config, name: &str, value: &Value) -> Result<MutualTls> {
+    str(conf, name, value)
+        .ok()
+        .and_then(MutualTls::from_name)
+        .ok_or_else(|| conf.bad_type(name, value.type_str(), "'disabled', 'optional', or 'required'"))
+}
+
 pub fn u32_option(conf: &Config, name: &str, value: &Value) -> Result<Option<u32>> {
     let expect = "a 32-bit unsigned integer or 'none' or 'false'";
     let err = Err(conf.bad_type(name, value.type_str(), expect));
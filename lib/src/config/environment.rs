@@ -9,7 +9,14 @@ use self::Environment::*;
 pub const CONFIG_ENV: &'static str = "ROCKET_ENV";
 
 /// An enum corresponding to the valid configuration environments.
-#[derive(Hash, PartialEq, Eq, Debug, Clone, Copy)]
+///
+/// Besides the three standard environments, applications can declare custom
+/// environments, or _profiles_, such as `qa`, `canary`, or `loadtest`, in
+/// `Rocket.toml`. See the [config module documentation] for how custom
+/// environments are declared and what their defaults are.
+///
+/// [config module documentation]: /rocket/config/index.html#custom-environments
+#[derive(Hash, PartialEq, Eq, Debug, Clone)]
 pub enum Environment {
     /// The development environment.
     Development,
@@ -17,6 +24,8 @@ pub enum Environment {
     Staging,
     /// The production environment.
     Production,
+    /// A custom environment with the given name.
+    Custom(String),
 }
 
 impl Environment {
@@ -28,7 +37,9 @@ impl Environment {
     /// # Errors
     ///
     /// Returns a `BadEnv` `ConfigError` if `ROCKET_ENV` is set and contains an
-    /// invalid or unknown environment name.
+    /// invalid environment name. Any valid name that isn't one of the standard
+    /// environments is returned as a `Custom` environment; whether it's
+    /// declared is checked when the configuration is read.
     pub fn active() -> Result<Environment, ConfigError> {
         match env::var(CONFIG_ENV) {
             Ok(s) => s.parse().map_err(|_| ConfigError::BadEnv(s)),
@@ -44,7 +55,7 @@ impl Environment {
         "development, staging, production"
     }
 
-    /// Returns a list of all of the possible environments.
+    /// Returns a list of all of the standard environments.
     #[inline]
     pub(crate) fn all() -> [Environment; 3] {
         [Development, Staging, Production]
@@ -61,8 +72,8 @@ impl Environment {
     /// assert!(!Environment::Production.is_dev());
     /// ```
     #[inline]
-    pub fn is_dev(self) -> bool {
-        self == Development
+    pub fn is_dev(&self) -> bool {
+        *self == Development
     }
 
     /// Returns `true` if `self` is `Environment::Staging`.
@@ -76,8 +87,8 @@ impl Environment {
     /// assert!(!Environment::Production.is_stage());
     /// ```
     #[inline]
-    pub fn is_stage(self) -> bool {
-        self == Staging
+    pub fn is_stage(&self) -> bool {
+        *self == Staging
     }
 
     /// Returns `true` if `self` is `Environment::Production`.
@@ -91,9 +102,37 @@ impl Environment {
     /// assert!(!Environment::Staging.is_prod());
     /// ```
     #[inline]
-    pub fn is_prod(self) -> bool {
-        self == Production
+    pub fn is_prod(&self) -> bool {
+        *self == Production
     }
+
+    /// Returns `true` if `self` is a custom environment.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Environment;
+    ///
+    /// assert!(Environment::Custom("qa".into()).is_custom());
+    /// assert!(!Environment::Production.is_custom());
+    /// ```
+    #[inline]
+    pub fn is_custom(&self) -> bool {
+        match *self {
+            Custom(_) => true,
+            _ => false
+        }
+    }
+}
+
+/// Returns `true` if `name` can name a custom environment: it is made up of
+/// lowercase ASCII letters, digits, `-`, and `_`, starts with a letter, and
+/// isn't reserved.
+fn is_valid_custom_name(name: &str) -> bool {
+    let valid_char = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_';
+    name != super::GLOBAL_ENV_NAME
+        && name.chars().next().map_or(false, |c| c.is_ascii_lowercase())
+        && name.chars().all(valid_char)
 }
 
 impl FromStr for Environment {
@@ -142,11 +181,24 @@ impl FromStr for Environment {
     /// let env = "prod".parse::<Environment>();
     /// assert_eq!(env.unwrap(), Environment::Production);
     /// ```
+    ///
+    /// Parsing a custom environment:
+    ///
+    /// ```rust
+    /// use rocket::config::Environment;
+    ///
+    /// let env = "canary".parse::<Environment>();
+    /// assert_eq!(env.unwrap(), Environment::Custom("canary".into()));
+    ///
+    /// assert!("Not Valid!".parse::<Environment>().is_err());
+    /// assert!("global".parse::<Environment>().is_err());
+    /// ```
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let env = match s {
             "dev" | "devel" | "development" => Development,
             "stage" | "staging" => Staging,
             "prod" | "production" => Production,
+            _ if is_valid_custom_name(s) => Custom(s.to_string()),
             _ => return Err(()),
         };
 
@@ -160,6 +212,7 @@ impl fmt::Display for Environment {
             Development => write!(f, "development"),
             Staging => write!(f, "staging"),
             Production => write!(f, "production"),
+            Custom(ref name) => write!(f, "{}", name),
         }
     }
 }
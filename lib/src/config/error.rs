@@ -24,11 +24,12 @@ pub enum ConfigError {
     ///
     /// Parameters: (path, reason)
     BadFilePath(PathBuf, &'static str),
-    /// An environment specified in `ROCKET_ENV` is invalid.
+    /// An environment specified in `ROCKET_ENV` is invalid or was not declared.
     ///
     /// Parameters: (environment_name)
     BadEnv(String),
-    /// An environment specified as a table `[environment]` is invalid.
+    /// An environment specified as a table `[environment]` is invalid, or is a
+    /// custom environment that doesn't declare the environment it `extends`.
     ///
     /// Parameters: (environment_name, filename)
     BadEntry(String, PathBuf),
@@ -48,6 +49,14 @@ pub enum ConfigError {
     ///
     /// Parameters: (key)
     UnknownKey(String),
+    /// A config value couldn't be deserialized into the requested type.
+    ///
+    /// Parameters: (entry_name, error_description, filename)
+    BadExtract(String, String, PathBuf),
+    /// An environment variable couldn't be interpolated into a config value.
+    ///
+    /// Parameters: (entry_name, error_description, filename)
+    BadInterpolation(String, String, PathBuf),
 }
 
 impl ConfigError {
@@ -71,10 +80,12 @@ impl ConfigError {
                 error!("[{}] is not a known configuration environment", name);
                 info_!("in {:?}", White.paint(filename));
                 info_!("valid environments are: {}", White.paint(valid_entries));
+                info_!("custom environments must declare the environment they `extends`");
             }
             BadEnv(ref name) => {
                 error!("'{}' is not a valid ROCKET_ENV value", name);
                 info_!("valid environments are: {}", White.paint(valid_envs));
+                info_!("or a custom environment declared in Rocket.toml");
             }
             BadType(ref name, expected, actual, ref filename) => {
                 error!("{} key could not be parsed", White.paint(name));
@@ -101,6 +112,16 @@ impl ConfigError {
                 error!("the configuration key '{}' is unknown and disallowed in \
                        this position", White.paint(key));
             }
+            BadExtract(ref name, ref desc, ref filename) => {
+                error!("{} could not be extracted", White.paint(name));
+                info_!("in {:?}", White.paint(filename));
+                info_!("{}", desc);
+            }
+            BadInterpolation(ref name, ref desc, ref filename) => {
+                error!("{} key could not be interpolated", White.paint(name));
+                info_!("in {:?}", White.paint(filename));
+                info_!("{}", desc);
+            }
         }
     }
 
@@ -134,6 +155,10 @@ impl fmt::Display for ConfigError {
             BadEnv(ref e) => write!(f, "{:?} is not a valid `ROCKET_ENV` value", e),
             ParseError(..) => write!(f, "the config file contains invalid TOML"),
             UnknownKey(ref k) => write!(f, "'{}' is an unknown key", k),
+            BadExtract(ref n, ref d, _) => write!(f, "'{}' could not be extracted: {}", n, d),
+            BadInterpolation(ref n, ref d, _) => {
+                write!(f, "'{}' could not be interpolated: {}", n, d)
+            }
             BadEntry(ref e, _) => {
                 write!(f, "{:?} is not a valid `[environment]` entry", e)
             }
@@ -161,6 +186,8 @@ impl Error for ConfigError {
             BadType(..) => "a key was specified with a value of the wrong type",
             BadEnvVal(..) => "an environment variable could not be parsed",
             UnknownKey(..) => "an unknown key was used in a disallowed position",
+            BadExtract(..) => "a value could not be deserialized into the requested type",
+            BadInterpolation(..) => "an environment variable could not be interpolated",
         }
     }
 }
@@ -176,6 +203,12 @@ impl PartialEq for ConfigError {
             (&BadEnv(ref e1), &BadEnv(ref e2)) => e1 == e2,
             (&ParseError(..), &ParseError(..)) => true,
             (&UnknownKey(ref k1), &UnknownKey(ref k2)) => k1 == k2,
+            (&BadExtract(ref n1, ref d1, _), &BadExtract(ref n2, ref d2, _)) => {
+                n1 == n2 && d1 == d2
+            }
+            (&BadInterpolation(ref n1, ref d1, _), &BadInterpolation(ref n2, ref d2, _)) => {
+                n1 == n2 && d1 == d2
+            }
             (&BadEntry(ref e1, _), &BadEntry(ref e2, _)) => e1 == e2,
             (&BadType(ref n1, e1, a1, _), &BadType(ref n2, e2, a2, _)) => {
                 n1 == n2 && e1 == e2 && a1 == a2
@@ -186,7 +219,8 @@ impl PartialEq for ConfigError {
             (&BadCWD, _) | (&NotFound, _) | (&IoError, _) | (&Io(..), _)
                 | (&BadFilePath(..), _) | (&BadEnv(..), _) | (&ParseError(..), _)
                 | (&UnknownKey(..), _) | (&BadEntry(..), _) | (&BadType(..), _)
-                | (&BadEnvVal(..), _) => false
+                | (&BadEnvVal(..), _) | (&BadExtract(..), _)
+                | (&BadInterpolation(..), _) => false
         }
     }
 }
@@ -9,7 +9,8 @@
 //!
 //! ### Environments
 //!
-//! Rocket applications are always running in one of three environments:
+//! Rocket applications are always running in one of three standard
+//! environments or in a [custom environment](#custom-environments):
 //!
 //!   * development _or_ dev
 //!   * staging _or_ stage
@@ -24,6 +25,40 @@
 //! ROCKET_ENV=production ./target/release/rocket_app
 //! ```
 //!
+//! ### Custom Environments
+//!
+//! Applications can declare additional environments, such as `qa`, `canary`,
+//! or `loadtest`, as tables in `Rocket.toml`. A custom environment's name is
+//! made up of lowercase letters, digits, `-`, and `_`, and starts with a
+//! letter. Its table must name the standard environment it `extends`:
+//!
+//! ```toml
+//! [production]
+//! port = 80
+//!
+//! [canary]
+//! extends = "production"
+//! port = 8080
+//! rollout_percent = 5
+//! ```
+//!
+//! A custom environment starts with the configuration of the environment it
+//! extends, including the values set in that environment's table, and then
+//! applies the values in its own table. Any extras in its table, like
+//! `rollout_percent` above, are only present in that environment. As with
+//! the standard environments, `[global]` values and `ROCKET_{PARAM}`
+//! environment variables override the values in its table. A custom
+//! environment is made active by setting `ROCKET_ENV` to its name:
+//!
+//! ```sh
+//! ROCKET_ENV=canary ./target/release/rocket_app
+//! ```
+//!
+//! Setting `ROCKET_ENV` to the name of a custom environment that isn't declared
+//! in `Rocket.toml` is an error. A `Config` for a custom environment built in
+//! code, via [`Config::build()`](struct.Config.html#method.build), uses the
+//! production defaults.
+//!
 //! ### Configuration Parameters
 //!
 //! Each environments consists of several standard configuration parameters as
@@ -41,22 +76,184 @@
 //!   * **keep_alive**: _[integer, 'false', or 'none']_ timeout, in seconds, for
 //!     HTTP keep-alive. disabled on 'false' or 'none'
 //!     * examples: `5`, `60`, `false`, `"none"`
+//!   * **max_idle_connections**: _[integer, 'false', or 'none']_ the maximum
+//!     number of idle keep-alive connections to hold open; the least recently
+//!     used are closed first. unbounded on 'false' or 'none'
+//!     * examples: `128`, `1024`, `"none"`
+//!   * **max_connections**: _[integer, 'false', or 'none']_ the maximum number
+//!     of open connections; connections accepted beyond it are closed
+//!     immediately. unbounded on 'false' or 'none'
+//!     * examples: `1024`, `"none"`
+//!   * **max_requests_per_connection**: _[integer, 'false', or 'none']_ the
+//!     maximum number of requests served on one keep-alive connection before
+//!     it is closed. unbounded on 'false' or 'none'
+//!     * examples: `100`, `"none"`
+//!   * **backlog**: _[integer, 'false', or 'none']_ the length of the queue of
+//!     connections waiting to be accepted. system default on 'false' or 'none'
+//!     * examples: `64`, `1024`
+//!   * **max_connections_per_ip**: _[integer, 'false', or 'none']_ the maximum
+//!     number of open connections from one client IP address; connections
+//!     accepted beyond it are closed immediately. unbounded on 'false' or
+//!     'none'
+//!     * examples: `16`, `"none"`
+//!   * **connection_limit_exempt**: _[array]_ the IP addresses and CIDR
+//!     networks, such as those of load balancers, exempt from
+//!     `max_connections_per_ip`
+//!     * example: `["10.0.0.0/8", "192.168.1.10"]`
+//!   * **max_header_size**: _[integer]_ the maximum size, in bytes, of a
+//!     request's head: its request line and headers. requests with larger
+//!     heads are answered with `431 Request Header Fields Too Large`. at most
+//!     and by default `409600` (400KiB), the most Hyper can read
+//!     * example: `65536`
+//!   * **max_header_count**: _[integer]_ the maximum number of headers in a
+//!     request. requests with more are answered with `431 Request Header
+//!     Fields Too Large`. at most and by default `100`, the most Hyper can
+//!     parse
+//!     * example: `50`
 //!   * **log**: _[string]_ how much information to log; one of `"normal"`,
 //!     `"debug"`, or `"critical"`
 //!   * **secret_key**: _[string]_ a 256-bit base64 encoded string (44
 //!     characters) to use as the secret key
 //!     * example: `"8Xui8SN4mI+7egV/9dlfYYLGQJeEx4+DwmSQLwDVXJg="`
-//!   * **tls**: _[table]_ a table with two keys:
+//!   * **tls**: _[table]_ a table with two keys and optional others:
 //!     1. `certs`: _[string]_ a path to a certificate chain in PEM format
 //!     2. `key`: _[string]_ a path to a private key file in PEM format for the
 //!        certificate in `certs`
+//!     3. `cert_store`: _[string]_ a path to the certificates, in PEM format,
+//!        that client certificates are verified against for mutual TLS
+//!     4. `ocsp_response_path`: _[string]_ a path to a DER-encoded OCSP
+//!        response for the certificate in `certs`, stapled to handshakes
+//!     5. `sni`: _[table]_ a table mapping hostnames to tables with `certs`
+//!        and `key` keys, the certificates served to clients that request
+//!        that hostname
 //!
 //!     * example: `{ certs = "/path/to/certs.pem", key = "/path/to/key.pem" }`
+//!   * **mutual_tls**: _[string]_ whether TLS clients must authenticate with a
+//!     certificate from `tls.cert_store`: `"disabled"`, the default,
+//!     `"optional"`, or `"required"`
+//!     * example: `"required"`
+//!   * **tls_handshake_timeout**: _[integer, 'false', or 'none']_ the number
+//!     of seconds new TLS connections have to complete the handshake and send
+//!     the head of their first request, or unbounded if `false` or `"none"`;
+//!     defaults to `10`
+//!     * example: `5`
+//!   * **tls_ocsp_refresh**: _[integer, 'false', or 'none']_ the number of
+//!     seconds between rereads of the file `tls.ocsp_response_path`, or only
+//!     when certificates are reloaded if `false` or `"none"`, the default
+//!     * example: `3600`
+//!   * **tls_alpn**: _[array]_ the protocols advertised to TLS clients via
+//!     ALPN, most preferred first; none are advertised by default. the
+//!     negotiated protocol is available via
+//!     [`Request::alpn_protocol()`](/rocket/struct.Request.html#method.alpn_protocol)
+//!     * example: `["http/1.1"]`
+//!   * **tls_versions**: _[array]_ the TLS protocol versions clients may
+//!     negotiate, of `"TLSv1.2"` and `"TLSv1.3"`; all are allowed by default.
+//!     older versions are never negotiated
+//!     * example: `["TLSv1.3"]`
+//!   * **tls_ciphersuites**: _[array]_ the TLS cipher suites clients may
+//!     negotiate, most preferred first; all supported cipher suites are
+//!     allowed by default. see
+//!     [`Config::set_tls_ciphersuites()`](/rocket/struct.Config.html#method.set_tls_ciphersuites)
+//!     for the supported cipher suites
+//!     * example: `["TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384"]`
+//!   * **mtls_allowed_fingerprints**: _[array]_ the SHA-256 fingerprints, in
+//!     hexadecimal and optionally colon separated, of the client certificates
+//!     the [`AllowedClient`](/rocket/tls/struct.AllowedClient.html) request
+//!     guard accepts
+//!     * example: `["0b:f5:ad:9e:...:8b:8d"]`
 //!   * **limits**: _[table]_ a table where each key (_[string]_) corresponds to
 //!   a data type and the value (_[u64]_) corresponds to the maximum size in
 //!   bytes Rocket should accept for that type.
 //!     * example: `{ forms = 65536 }` (maximum form size to 64KiB)
 //!
+//!     The `response` limit, if set, is the maximum size of a response body.
+//!     Responses with larger bodies are replaced with a `500`, or aborted if
+//!     they are streamed. Routes can override the limit with the
+//!     `response_limit` route parameter.
+//!
+//!     The `memory` limit, if set, is the maximum number of bytes Rocket
+//!     buffers in memory on behalf of a single request: bodies read by data
+//!     guards and response bodies buffered for digesting or sealing. Requests
+//!     that exceed it fail with `413`, or `500` for response buffers, and are
+//!     counted in the `requests.memory_exceeded` metric.
+//!
+//!     The `decompressed` limit, 1MiB by default, is the maximum size of a
+//!     request body before and after decompression when `compression` is
+//!     configured.
+//!
+//!     The `read_timeout` key, 5 by default, is the number of seconds Rocket
+//!     waits for each read of a request body, and the `body_timeout` key, unset
+//!     by default, the number of seconds it waits for the entire body. Both can
+//!     be overridden per content type in a `timeouts` table:
+//!     `{ read_timeout = 5, timeouts = { "multipart/*" = { body_timeout = 300 } } }`
+//!   * **verify_checksums**: _[bool]_ whether to verify request bodies against
+//!     the checksum in a `Content-MD5` or `Digest` header, failing with `422`
+//!     on mismatch. only bodies read to completion by a handler are verified
+//!     * examples: `true`, `false`
+//!   * **server_name**: _[string or 'false']_ the value of the `Server` header
+//!     sent with every response. the header is omitted on 'false'
+//!     * examples: `"Rocket"`, `"MyApp"`, `false`
+//!   * **header_policy**: _[table]_ a table with up to three keys used to scrub
+//!     response headers before they are written:
+//!     1. `deny`: _[array]_ names of headers to always remove
+//!     2. `allow`: _[array]_ names of the only headers to keep, in addition to
+//!        those essential to HTTP
+//!     3. `rewrite`: _[table]_ header names mapped to replacement values
+//!
+//!     names ending in `*` match by prefix
+//!     * example: `{ deny = ["X-Powered-By", "X-Debug-*"], rewrite = { Server = "web" } }`
+//!   * **features**: _[table]_ a table where each key (_[string]_) names a
+//!     [feature toggle](/rocket/features/) and the value (_[bool]_) is whether
+//!     it is enabled. undeclared features are disabled
+//!     * example: `{ beta_search = true, new_checkout = false }`
+//!   * **allowed_hosts**: _[array]_ the hosts, as in the `Host` header, that
+//!     requests may be sent to; requests for other hosts are rejected with
+//!     `421`. a leading `*.` matches subdomains. any host is allowed if empty
+//!     * example: `["example.com", "*.example.com", "localhost:8000"]`
+//!   * **hsts**: _[table or bool]_ the `Strict-Transport-Security` policy sent
+//!     with every response when TLS is enabled; a table with the keys
+//!     `max_age` (_[integer]_, seconds), `include_subdomains` and `preload`
+//!     (_[bool]_). a policy with `preload` must include subdomains and have a
+//!     `max_age` of at least a year. `true` uses a one year `max_age`
+//!     * example: `{ max_age = 63072000, include_subdomains = true, preload = true }`
+//!   * **upgrade_insecure_requests**: _[bool]_ whether plaintext requests with
+//!     an `Upgrade-Insecure-Requests: 1` header are redirected to HTTPS
+//!     * examples: `true`, `false`
+//!   * **https_port**: _[integer]_ the port HTTPS is served on, which
+//!     upgraded requests are redirected to. defaults to `443`
+//!     * example: `8443`
+//!   * **trusted_proxies**: _[array]_ the IP addresses and CIDR networks of
+//!     TLS-terminating proxies trusted to report a request's scheme via
+//!     `X-Forwarded-Proto`; requests they report as `https` aren't upgraded
+//!     * example: `["10.0.0.0/8"]`
+//!   * **response_digest**: _[string or 'false']_ the algorithm used to
+//!     compute the `Digest` and `Content-Digest` headers of sized response
+//!     bodies, one of `MD5` or `SHA-256`. no digest is computed if `false`
+//!     * examples: `"SHA-256"`, `false`
+//!   * **cookie_limits**: _[table]_ limits on the cookies in a request's
+//!     `Cookie` headers and on the `Set-Cookie` headers of a response; a table
+//!     with the keys `max_count` (_[integer]_), `max_size` (_[integer]_, total
+//!     bytes), and `overflow`, one of `"reject"`, `"truncate_oldest"`, or
+//!     `"log"`. by default, a warning is logged beyond 50 cookies or 8KiB
+//!     * example: `{ max_count = 20, max_size = 4096, overflow = "reject" }`
+//!   * **private_cookie_cipher**: _[string]_ the algorithm
+//!     [private cookies](/rocket/http/enum.Cookies.html#private-cookies) are
+//!     encrypted with, `"AES-256-GCM"`, the default, or `"ChaCha20-Poly1305"`.
+//!     cookies encrypted with the other algorithm are re-encrypted when read
+//!     * example: `"ChaCha20-Poly1305"`
+//!   * **compression**: _[table or bool]_ compression of response bodies and
+//!     decompression of request bodies; `true` enables the defaults, and a
+//!     table sets the `gzip`, `brotli`, and `zstd` levels, `false` disabling a
+//!     coding, and the `min_size` of compressed bodies. codings are only
+//!     available with the feature of the same name
+//!     * example: `{ brotli = 11, zstd = false, min_size = 512 }`
+//!   * **text_encoding**: _[string or 'false']_ how the `String`, form, and
+//!     JSON data guards decode request bodies, `"strict"` or `"lossy"`. either
+//!     rejects bodies in a `charset` other than UTF-8 with `415`; invalid
+//!     UTF-8 is rejected with `400` if `"strict"` and replaced if `"lossy"`.
+//!     if `false`, the default, bodies must be valid UTF-8 in any `charset`
+//!     * examples: `"strict"`, `false`
+//!
 //! ### Rocket.toml
 //!
 //! The `Rocket.toml` file is used to specify the configuration parameters for
@@ -123,6 +320,33 @@
 //! address = "0.0.0.0"
 //! ```
 //!
+//! Settings shared by several applications can be kept in separate files and
+//! included with a top-level `include` array of paths, relative to the
+//! including file. The included files are merged in order, each overriding
+//! the ones before it, and the including file overrides them all. Tables are
+//! merged key by key. Included files may include other files. Relative paths
+//! in parameter values are always relative to `Rocket.toml`:
+//!
+//! ```toml
+//! include = ["../shared/base.toml", "/etc/app/secrets.toml"]
+//!
+//! [production]
+//! port = 9000
+//! ```
+//!
+//! Environment variables can be interpolated into string values: `${NAME}` is
+//! replaced with the value of the environment variable `NAME`, and
+//! `${NAME:-default}` with the value or, if the variable isn't set, `default`.
+//! Loading the configuration fails if a variable without a default isn't set.
+//! A literal `${` is written as `$${`:
+//!
+//! ```toml
+//! [production]
+//! address = "${BIND_ADDRESS:-0.0.0.0}"
+//! database = "postgres://app:${DB_PASSWORD}@${DB_HOST:-localhost}/app"
+//! template = "Hello, $${name}!"
+//! ```
+//!
 //! ### TLS Configuration
 //!
 //! TLS can be enabled by specifying the `tls.key` and `tls.certs` parameters.
@@ -136,6 +360,61 @@
 //! key = "/path/to/key.pem"
 //! ```
 //!
+//! Certificates and keys that aren't stored in files, for instance those
+//! received from a secrets manager at boot, can be set from memory via
+//! [`ConfigBuilder::tls_bytes()`](struct.ConfigBuilder.html#method.tls_bytes)
+//! or [`Config::set_tls_bytes()`](struct.Config.html#method.set_tls_bytes).
+//!
+//! An instance that serves several hostnames can serve each its own
+//! certificate, selected by the hostname the client requests via SNI. Clients
+//! that request any other hostname, or none at all, are served the default
+//! `certs` and `key`:
+//!
+//! ```toml
+//! [global.tls]
+//! certs = "/path/to/default_certs.pem"
+//! key = "/path/to/default_key.pem"
+//!
+//! [global.tls.sni."api.example.com"]
+//! certs = "/path/to/api_certs.pem"
+//! key = "/path/to/api_key.pem"
+//! ```
+//!
+//! SNI certificates can also be set via
+//! [`ConfigBuilder::tls_sni()`](struct.ConfigBuilder.html#method.tls_sni).
+//!
+//! An OCSP response for the default certificate, as fetched from the issuer
+//! with `openssl ocsp -respout`, is stapled to handshakes when its path is
+//! set via `ocsp_response_path`. Responses expire, so the file should be
+//! updated periodically; `tls_ocsp_refresh` rereads it on an interval:
+//!
+//! ```toml
+//! [global]
+//! tls_ocsp_refresh = 3600
+//!
+//! [global.tls]
+//! certs = "/path/to/certs.pem"
+//! key = "/path/to/key.pem"
+//! ocsp_response_path = "/path/to/ocsp.der"
+//! ```
+//!
+//! Certificates read from files can be reloaded while the application runs,
+//! as when they are renewed; see the [`tls`](/rocket/tls/index.html) module.
+//!
+//! To require clients to authenticate with a certificate, mutual TLS, add a
+//! `cert_store` with the certificates of the authorities that issue client
+//! certificates and set the `mutual_tls` policy:
+//!
+//! ```toml
+//! [global]
+//! mutual_tls = "required"
+//!
+//! [global.tls]
+//! certs = "/path/to/certs.pem"
+//! key = "/path/to/key.pem"
+//! cert_store = "/path/to/client_ca.pem"
+//! ```
+//!
 //! ### Environment Variables
 //!
 //! All configuration parameters, including extras, can be overridden through
@@ -163,6 +442,27 @@
 //! ROCKET_DICT={key="abc",val=123}
 //! ```
 //!
+//! `ROCKET_{PARAM}` variables only override parameters read from
+//! `Rocket.toml`. A configuration built in code with
+//! [`ConfigBuilder`](struct.ConfigBuilder.html) can be overridden by variables
+//! with any prefix via
+//! [`ConfigBuilder::merge_env()`](struct.ConfigBuilder.html#method.merge_env).
+//!
+//! ### Command-Line Arguments
+//!
+//! Applications launched with
+//! [`Rocket::from_args()`](/rocket/struct.Rocket.html#method.from_args)
+//! instead of `rocket::ignite()` additionally accept a common set of
+//! command-line flags that select the configuration file and environment and
+//! set the address and port, as well as `name=value` arguments that set any
+//! parameter. Arguments take precedence over environment variables:
+//!
+//! ```sh
+//! ROCKET_PORT=3721 ./your_application --env staging --port 8000 workers=4
+//! ```
+//!
+//! See [`Args`](struct.Args.html) for details.
+//!
 //! ## Retrieving Configuration Parameters
 //!
 //! Configuration parameters for the currently active configuration environment
@@ -200,36 +500,109 @@ mod config;
 mod builder;
 mod toml_ext;
 mod custom_values;
+mod args;
 
 use std::fs::{self, File};
 use std::collections::HashMap;
-use std::io::Read;
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
 use std::process;
 use std::env;
 
 use toml;
 
-pub use self::custom_values::Limits;
+pub use self::custom_values::{Limits, HeaderPolicy, Hsts, CookieLimits, CookieOverflow};
+pub use self::custom_values::{CompressionConfig, MutualTls};
+#[cfg(feature = "tls")] pub(crate) use self::custom_values::TlsConfig;
 pub use toml::value::{Array, Table, Value, Datetime};
 pub use self::error::ConfigError;
 pub use self::environment::Environment;
 pub use self::config::Config;
 pub use self::builder::ConfigBuilder;
+pub use self::args::{Args, ArgsError};
 pub use logger::LoggingLevel;
 pub(crate) use self::toml_ext::LoggedValue;
 
 use logger;
 use self::Environment::*;
 use self::environment::CONFIG_ENV;
-use self::toml_ext::parse_simple_toml_value;
+use self::toml_ext::{parse_simple_toml_value, interpolate};
 use http::uncased::uncased_eq;
 
 const CONFIG_FILENAME: &'static str = "Rocket.toml";
 const GLOBAL_ENV_NAME: &'static str = "global";
+const EXTENDS_KEY: &'static str = "extends";
+const INCLUDE_KEY: &'static str = "include";
 const ENV_VAR_PREFIX: &'static str = "ROCKET_";
 const PREHANDLED_VARS: [&'static str; 2] = ["ROCKET_CODEGEN_DEBUG", CONFIG_ENV];
 
+/// Returns the environment variables whose names start with `prefix`, compared
+/// case-insensitively, as pairs of the lowercased name without the prefix and
+/// the value. Variables that are handled elsewhere are skipped.
+pub(crate) fn env_vars(prefix: &str) -> Vec<(String, String)> {
+    env::vars()
+        .filter(|&(ref key, _)| key.len() > prefix.len() && key.is_char_boundary(prefix.len()))
+        .filter(|&(ref key, _)| uncased_eq(&key[..prefix.len()], prefix))
+        .filter(|&(ref key, _)| !PREHANDLED_VARS.iter().any(|var| uncased_eq(key, var)))
+        .map(|(key, val)| (key[prefix.len()..].to_lowercase(), val))
+        .collect()
+}
+
+/// Parses `val`, the value of the environment variable for the parameter
+/// `key`, as a TOML value and sets the parameter in `config` to it.
+pub(crate) fn set_from_env(config: &mut Config, key: &str, val: &str) -> Result<()> {
+    let toml_val = match parse_simple_toml_value(val) {
+        Ok(toml_val) => toml_val,
+        Err(e) => return Err(ConfigError::BadEnvVal(key.into(), val.into(), e))
+    };
+
+    match config.set_raw(key, &toml_val) {
+        Err(ConfigError::BadType(_, exp, actual, _)) => {
+            let e = format!("expected {}, but found {}", exp, actual);
+            Err(ConfigError::BadEnvVal(key.into(), val.into(), e))
+        }
+        result => result
+    }
+}
+
+/// Interpolates environment variables into the strings in `value`, the value
+/// of the entry `name` in the file at `path`.
+fn interpolate_env(value: &mut Value, name: &str, path: &Path) -> Result<()> {
+    match *value {
+        Value::String(ref mut string) if string.contains('$') => {
+            let interpolated = interpolate(string, |var| env::var(var).ok())
+                .map_err(|e| ConfigError::BadInterpolation(name.into(), e, path.into()))?;
+
+            *string = interpolated;
+        }
+        Value::Array(ref mut array) => for (i, value) in array.iter_mut().enumerate() {
+            interpolate_env(value, &format!("{}[{}]", name, i), path)?;
+        },
+        Value::Table(ref mut table) => for (key, value) in table.iter_mut() {
+            interpolate_env(value, &format!("{}.{}", name, key), path)?;
+        },
+        _ => { /* nothing to interpolate */ }
+    }
+
+    Ok(())
+}
+
+/// Merges `overrides` into `base`. Tables present in both are merged
+/// recursively; any other value in `overrides` replaces the one in `base`.
+fn merge_tables(base: &mut Table, overrides: Table) {
+    for (key, value) in overrides {
+        let value = match (base.remove(&key), value) {
+            (Some(Value::Table(mut table)), Value::Table(overrides)) => {
+                merge_tables(&mut table, overrides);
+                Value::Table(table)
+            }
+            (_, value) => value
+        };
+
+        base.insert(key, value);
+    }
+}
+
 /// Wraps `std::result` with the error type of
 /// [ConfigError](enum.ConfigError.html).
 pub type Result<T> = ::std::result::Result<T, ConfigError>;
@@ -252,7 +625,7 @@ impl RocketConfig {
     /// panics.
     pub fn new(config: Config) -> RocketConfig {
         let f = config.config_path.clone();
-        let active_env = config.environment;
+        let active_env = config.environment.clone();
 
         // None of these unwraps should fail since the filename is coming from
         // an existing connfig.
@@ -260,7 +633,7 @@ impl RocketConfig {
         configs.insert(Development, Config::default(Development, &f).unwrap());
         configs.insert(Staging, Config::default(Staging, &f).unwrap());
         configs.insert(Production, Config::default(Production, &f).unwrap());
-        configs.insert(active_env, config);
+        configs.insert(active_env.clone(), config);
 
         RocketConfig {
             active_env: active_env,
@@ -273,34 +646,59 @@ impl RocketConfig {
     pub fn read() -> Result<RocketConfig> {
         // Find the config file, starting from the `cwd` and working backwords.
         let file = RocketConfig::find()?;
+        RocketConfig::read_file(&file)
+    }
 
+    /// Read the configuration from the file at `path`. Returns `NotFound` if
+    /// there is no such file.
+    fn read_file(path: &Path) -> Result<RocketConfig> {
         // Try to open the config file for reading.
-        let mut handle = File::open(&file).map_err(|_| ConfigError::IoError)?;
+        let mut handle = File::open(path).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => ConfigError::NotFound,
+            _ => ConfigError::IoError
+        })?;
 
         // Read the configure file to a string for parsing.
         let mut contents = String::new();
         handle.read_to_string(&mut contents).map_err(|_| ConfigError::IoError)?;
 
         // Parse the config and return the result.
-        RocketConfig::parse(contents, &file)
+        RocketConfig::parse(contents, path)
     }
 
     /// Return the default configuration for all environments and marks the
     /// active environment (via the CONFIG_ENV variable) as active.
     pub fn active_default<P: AsRef<Path>>(filename: P) -> Result<RocketConfig> {
+        let mut config = RocketConfig::defaults(filename)?;
+
+        // Override any variables from the environment.
+        config.override_from_env()?;
+        config.check_active()?;
+        Ok(config)
+    }
+
+    /// Return the default configuration for the standard environments and
+    /// marks the active environment (via the CONFIG_ENV variable) as active,
+    /// without checking that the active environment exists.
+    fn defaults<P: AsRef<Path>>(filename: P) -> Result<RocketConfig> {
         let mut defaults = HashMap::new();
-        defaults.insert(Development, Config::default(Development, &filename)?);
-        defaults.insert(Staging, Config::default(Staging, &filename)?);
-        defaults.insert(Production, Config::default(Production, &filename)?);
+        for env in &Environment::all() {
+            defaults.insert(env.clone(), Config::default(env.clone(), &filename)?);
+        }
 
-        let mut config = RocketConfig {
+        Ok(RocketConfig {
             active_env: Environment::active()?,
             config: defaults,
-        };
+        })
+    }
 
-        // Override any variables from the environment.
-        config.override_from_env()?;
-        Ok(config)
+    /// Returns a `BadEnv` error if the active environment is a custom
+    /// environment that wasn't declared.
+    fn check_active(&self) -> Result<()> {
+        match self.config.contains_key(&self.active_env) {
+            true => Ok(()),
+            false => Err(ConfigError::BadEnv(self.active_env.to_string()))
+        }
     }
 
     /// Iteratively search for `CONFIG_FILENAME` starting at the current working
@@ -327,8 +725,8 @@ impl RocketConfig {
     }
 
     #[inline]
-    fn get_mut(&mut self, env: Environment) -> &mut Config {
-        match self.config.get_mut(&env) {
+    fn get_mut(&mut self, env: &Environment) -> &mut Config {
+        match self.config.get_mut(env) {
             Some(config) => config,
             None => panic!("set(): {} config is missing.", env),
         }
@@ -338,7 +736,7 @@ impl RocketConfig {
     /// derived from the TOML table `kvs`. The environment must already exist in
     /// `self`, otherwise this function panics. Any existing values are
     /// overriden by those in `kvs`.
-    fn set_from_table(&mut self, env: Environment, kvs: &Table) -> Result<()> {
+    fn set_from_table(&mut self, env: &Environment, kvs: &Table) -> Result<()> {
         for (key, value) in kvs {
             self.get_mut(env).set_raw(key, value)?;
         }
@@ -347,8 +745,8 @@ impl RocketConfig {
     }
 
     /// Retrieves the `Config` for the environment `env`.
-    pub fn get(&self, env: Environment) -> &Config {
-        match self.config.get(&env) {
+    pub fn get(&self, env: &Environment) -> &Config {
+        match self.config.get(env) {
             Some(config) => config,
             None => panic!("get(): {} config is missing.", env),
         }
@@ -357,66 +755,114 @@ impl RocketConfig {
     /// Retrieves the `Config` for the active environment.
     #[inline]
     pub fn active(&self) -> &Config {
-        self.get(self.active_env)
+        self.get(&self.active_env)
+    }
+
+    /// Returns the `Config` for the active environment, discarding the rest.
+    fn into_active(mut self) -> Config {
+        match self.config.remove(&self.active_env) {
+            Some(config) => config,
+            None => panic!("into_active(): {} config is missing.", self.active_env),
+        }
     }
 
     // Override all environments with values from env variables if present.
     fn override_from_env(&mut self) -> Result<()> {
-        for (key, val) in env::vars() {
-            if key.len() < ENV_VAR_PREFIX.len() {
-                continue
-            } else if !uncased_eq(&key[..ENV_VAR_PREFIX.len()], ENV_VAR_PREFIX) {
-                continue
-            }
-
-            // Skip environment variables that are handled elsewhere.
-            if PREHANDLED_VARS.iter().any(|var| uncased_eq(&key, var)) {
-                continue
-            }
-
-            // Parse the key and value and try to set the variable for all envs.
-            let key = key[ENV_VAR_PREFIX.len()..].to_lowercase();
-            let toml_val = match parse_simple_toml_value(&val) {
-                Ok(val) => val,
-                Err(e) => return Err(ConfigError::BadEnvVal(key, val, e))
-            };
-
-            for env in &Environment::all() {
-                match self.get_mut(*env).set_raw(&key, &toml_val) {
-                    Err(ConfigError::BadType(_, exp, actual, _)) => {
-                        let e = format!("expected {}, but found {}", exp, actual);
-                        return Err(ConfigError::BadEnvVal(key, val, e))
-                    }
-                    Err(e) => return Err(e),
-                    Ok(_) => { /* move along */ }
-                }
+        for (key, val) in env_vars(ENV_VAR_PREFIX) {
+            for config in self.config.values_mut() {
+                set_from_env(config, &key, &val)?;
             }
         }
 
         Ok(())
     }
 
-    /// Parses the configuration from the Rocket.toml file. Also overrides any
-    /// values there with values from the environment.
-    fn parse<P: AsRef<Path>>(src: String, filename: P) -> Result<RocketConfig> {
+    /// Parses `src`, read from `path`, as a TOML table and interpolates
+    /// environment variables into its strings.
+    fn parse_table(src: String, path: &Path) -> Result<Table> {
         use self::ConfigError::ParseError;
 
-        // Parse the source as TOML, if possible.
-        let path = filename.as_ref().to_path_buf();
-        let table = match src.parse::<toml::Value>() {
+        let mut table = match src.parse::<toml::Value>() {
             Ok(toml::Value::Table(table)) => table,
             Ok(value) => {
                 let err = format!("expected a table, found {}", value.type_str());
-                return Err(ConfigError::ParseError(src, path, err, Some((1, 1))));
+                return Err(ParseError(src, path.to_path_buf(), err, Some((1, 1))));
             }
-            Err(e) => return Err(ParseError(src, path, e.to_string(), e.line_col()))
+            Err(e) => {
+                return Err(ParseError(src, path.to_path_buf(), e.to_string(), e.line_col()));
+            }
+        };
+
+        for (key, value) in table.iter_mut() {
+            interpolate_env(value, key, path)?;
+        }
+
+        Ok(table)
+    }
+
+    /// Merges the files listed in the `include` key of `table`, read from
+    /// `path`, and then `table` itself, each overriding the ones before.
+    /// `including` holds the files that are including `path`.
+    fn resolve_includes(
+        mut table: Table,
+        path: &Path,
+        including: &mut Vec<PathBuf>
+    ) -> Result<Table> {
+        let includes = match table.remove(INCLUDE_KEY) {
+            Some(Value::Array(includes)) => includes,
+            Some(value) => return Err(ConfigError::BadType(
+                INCLUDE_KEY.into(), "an array of paths", value.type_str(), path.into()
+            )),
+            None => return Ok(table)
         };
 
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        including.push(fs::canonicalize(path).unwrap_or_else(|_| path.into()));
+
+        let mut merged = Table::new();
+        for value in includes {
+            let included = match value.as_str() {
+                Some(included) => dir.join(included),
+                None => return Err(ConfigError::BadType(
+                    INCLUDE_KEY.into(), "an array of paths", value.type_str(), path.into()
+                ))
+            };
+
+            let canonical = fs::canonicalize(&included).unwrap_or_else(|_| included.clone());
+            if including.contains(&canonical) {
+                return Err(ConfigError::BadFilePath(included, "the included file includes itself"));
+            }
+
+            let mut contents = String::new();
+            File::open(&included)
+                .and_then(|mut file| file.read_to_string(&mut contents))
+                .map_err(|_| ConfigError::BadFilePath(included.clone(), "the included file can't be read"))?;
+
+            let table = RocketConfig::parse_table(contents, &included)?;
+            merge_tables(&mut merged, RocketConfig::resolve_includes(table, &included, including)?);
+        }
+
+        including.pop();
+        merge_tables(&mut merged, table);
+        Ok(merged)
+    }
+
+    /// Parses the configuration from the Rocket.toml file, including the files
+    /// it includes. Also overrides any values there with values from the
+    /// environment.
+    fn parse<P: AsRef<Path>>(src: String, filename: P) -> Result<RocketConfig> {
+        // Parse the source as TOML, if possible, and merge in the includes.
+        let path = filename.as_ref().to_path_buf();
+        let table = RocketConfig::parse_table(src, &path)?;
+        let table = RocketConfig::resolve_includes(table, &path, &mut vec![])?;
+
         // Create a config with the defaults; set the env to the active one.
-        let mut config = RocketConfig::active_default(filename)?;
+        let mut config = RocketConfig::defaults(filename)?;
 
-        // Store all of the global overrides, if any, for later use.
+        // Store all of the global overrides and custom environments, if any,
+        // for later use.
         let mut global = None;
+        let mut custom = vec![];
 
         // Parse the values from the TOML file.
         for (entry, value) in table {
@@ -437,23 +883,55 @@ impl RocketConfig {
             // This is not the global table. Parse the environment name from the
             // table entry name and then set all of the key/values.
             match entry.as_str().parse() {
-                Ok(env) => config.set_from_table(env, kv_pairs)?,
+                Ok(Custom(name)) => custom.push((name, kv_pairs.clone())),
+                Ok(env) => config.set_from_table(&env, kv_pairs)?,
                 Err(_) => Err(ConfigError::BadEntry(entry.clone(), path.clone()))?
             }
         }
 
+        // Each custom environment starts as a copy of the standard environment
+        // it extends, as configured in the file, then sets its own values.
+        for (name, mut kv_pairs) in custom {
+            let base = match kv_pairs.remove(EXTENDS_KEY) {
+                Some(value) => RocketConfig::extended_env(&name, &value, &path)?,
+                None => return Err(ConfigError::BadEntry(name, path.clone()))
+            };
+
+            let env = Custom(name);
+            let mut profile = config.get(&base).clone();
+            profile.environment = env.clone();
+            config.config.insert(env.clone(), profile);
+            config.set_from_table(&env, &kv_pairs)?;
+        }
+
         // Override all of the environments with the global values.
         if let Some(ref global_kv_pairs) = global {
-            for env in &Environment::all() {
-                config.set_from_table(*env, global_kv_pairs)?;
+            let envs: Vec<_> = config.config.keys().cloned().collect();
+            for env in &envs {
+                config.set_from_table(env, global_kv_pairs)?;
             }
         }
 
         // Override any variables from the environment.
         config.override_from_env()?;
+        config.check_active()?;
 
         Ok(config)
     }
+
+    /// Parses the `extends` value of the custom environment `name`, which must
+    /// name a standard environment.
+    fn extended_env(name: &str, value: &Value, path: &Path) -> Result<Environment> {
+        let key = format!("{}.{}", name, EXTENDS_KEY);
+        let actual = match value.as_str().map(|s| s.parse::<Environment>()) {
+            Some(Ok(Custom(_))) => "a custom environment",
+            Some(Ok(env)) => return Ok(env),
+            Some(Err(_)) => "an invalid environment",
+            None => value.type_str()
+        };
+
+        Err(ConfigError::BadType(key, "a standard environment", actual, path.to_path_buf()))
+    }
 }
 
 /// Returns the active configuration and whether this call initialized the
@@ -481,7 +959,8 @@ pub(crate) fn init() -> Config {
     let config = RocketConfig::read().unwrap_or_else(|e| {
         match e {
             ParseError(..) | BadEntry(..) | BadEnv(..) | BadType(..) | Io(..)
-                | BadFilePath(..) | BadEnvVal(..) | UnknownKey(..) => bail(e),
+                | BadFilePath(..) | BadEnvVal(..) | UnknownKey(..)
+                | BadExtract(..) | BadInterpolation(..) => bail(e),
             IoError | BadCWD => warn!("Failed reading Rocket.toml. Using defaults."),
             NotFound => { /* try using the default below */ }
         }
@@ -498,18 +977,37 @@ pub(crate) fn init() -> Config {
     config.active().clone()
 }
 
+/// Returns the configuration selected by the command-line arguments `args`,
+/// the first of which is the name of the program. See [`Args`] for details.
+///
+/// # Errors
+///
+/// Returns `ArgsError::Help` if help was requested, an error describing the
+/// first invalid argument if the arguments can't be parsed, and
+/// `ArgsError::Config` if the selected configuration can't be read.
+pub(crate) fn init_from_args<I>(args: I) -> ::std::result::Result<Config, ArgsError>
+    where I: Iterator<Item = String>
+{
+    let args = Args::parse(args.skip(1))?;
+    Ok(args.builder().and_then(|builder| builder.finalize())?)
+}
+
 #[cfg(test)]
 mod test {
     use std::env;
     use std::sync::Mutex;
 
     use super::{RocketConfig, Config, ConfigError, ConfigBuilder};
-    use super::{Environment, GLOBAL_ENV_NAME};
+    use super::{Environment, HeaderPolicy, Hsts, CookieLimits, CookieOverflow};
+    use super::{CompressionConfig, MutualTls};
+    use super::GLOBAL_ENV_NAME;
     use super::environment::CONFIG_ENV;
     use super::Environment::*;
     use super::Result;
 
     use ::logger::LoggingLevel;
+    use ::data::{ChecksumAlgorithm, TextEncoding};
+    use ::http::CookieCipher;
 
     const TEST_CONFIG_FILENAME: &'static str = "/tmp/testing/Rocket.toml";
 
@@ -530,7 +1028,7 @@ mod test {
         ($env:expr, $rconfig:expr, $econfig:expr) => (
             let expected = $econfig.finalize().unwrap();
             match $rconfig {
-                Ok(ref config) => assert_eq!(config.get($env), &expected),
+                Ok(ref config) => assert_eq!(config.get(&$env), &expected),
                 Err(ref e) => panic!("Config {} failed: {:?}", stringify!($rconfig), e)
             }
         );
@@ -745,6 +1243,34 @@ mod test {
             [global]
             tls = { certs = "some/path.pem", key = "some/key.pem" }
         "#.to_string(), TEST_CONFIG_FILENAME).is_ok());
+
+        assert!(RocketConfig::parse(r#"
+            [staging.tls]
+            certs = "some/path.pem"
+            key = "some/key.pem"
+            cert_store = "some/client_ca.pem"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_ok());
+
+        assert!(RocketConfig::parse(r#"
+            [staging.tls]
+            certs = "some/path.pem"
+            key = "some/key.pem"
+
+            [staging.tls.sni."api.example.com"]
+            certs = "some/api.pem"
+            key = "some/api_key.pem"
+
+            [staging.tls.sni."www.example.com"]
+            certs = "some/www.pem"
+            key = "some/www_key.pem"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_ok());
+
+        assert!(RocketConfig::parse(r#"
+            [staging.tls]
+            certs = "some/path.pem"
+            key = "some/key.pem"
+            ocsp_response_path = "some/ocsp.der"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_ok());
     }
 
     #[test]
@@ -772,6 +1298,59 @@ mod test {
             [staging]
             tls = { cert = "some/path.pem", key = "some/key.pem" }
         "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [staging]
+            tls = { certs = "some/path.pem", key = "some/key.pem", sni = "a.com" }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [staging.tls]
+            certs = "some/path.pem"
+            key = "some/key.pem"
+            sni = { "a.com" = { certs = "some/a.pem" } }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [staging.tls]
+            certs = "some/path.pem"
+            key = "some/key.pem"
+            sni = { "a.com" = { certs = "some/a.pem", key = "some/a.pem", ca = "x" } }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [staging.tls]
+            certs = "some/path.pem"
+            key = "some/key.pem"
+            ocsp_response_path = true
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [staging.tls]
+            certs = "some/path.pem"
+            key = "some/key.pem"
+            ocsp = "some/ocsp.der"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    // Only do this test when the tls feature is disabled since the file paths
+    // we're supplying don't actually exist.
+    #[test]
+    #[cfg(not(feature = "tls"))]
+    fn test_tls_sni_hostnames() {
+        let mut config = Config::new(Environment::Staging).unwrap();
+        assert!(config.set_tls_sni("a.com", "some/a.pem", "some/a_key.pem").is_err());
+
+        config.set_tls("some/path.pem", "some/key.pem").unwrap();
+        assert!(config.set_tls_sni("API.example.com", "some/a.pem", "some/a_key.pem").is_ok());
+        assert!(config.set_tls_sni("api.example.com", "some/b.pem", "some/b_key.pem").is_ok());
+        assert!(config.set_tls_sni("www-1.example.com", "some/c.pem", "some/c_key.pem").is_ok());
+        let hostnames: Vec<_> = config.tls_sni.iter().map(|&(ref name, _)| &**name).collect();
+        assert_eq!(hostnames, vec!["api.example.com", "www-1.example.com"]);
+
+        for bad in &["", "*.example.com", "a..com", "-a.com", "a.com.", "a_b.com", "a.com:80"] {
+            assert!(config.set_tls_sni(bad, "some/a.pem", "some/a_key.pem").is_err(), "{}", bad);
+        }
     }
 
     #[test]
@@ -971,90 +1550,912 @@ mod test {
     }
 
     #[test]
-    fn test_good_log_levels() {
+    fn test_max_idle_connections() {
         // Take the lock so changing the environment doesn't cause races.
         let _env_lock = ENV_LOCK.lock().unwrap();
         env::set_var(CONFIG_ENV, "stage");
 
         check_config!(RocketConfig::parse(r#"
                           [stage]
-                          log = "normal"
+                          max_idle_connections = 128
                       "#.to_string(), TEST_CONFIG_FILENAME), {
-                          default_config(Staging).log_level(LoggingLevel::Normal)
+                          default_config(Staging).max_idle_connections(128)
                       });
 
-
         check_config!(RocketConfig::parse(r#"
                           [stage]
-                          log = "debug"
+                          max_idle_connections = false
                       "#.to_string(), TEST_CONFIG_FILENAME), {
-                          default_config(Staging).log_level(LoggingLevel::Debug)
+                          default_config(Staging).max_idle_connections(None)
                       });
 
-        check_config!(RocketConfig::parse(r#"
-                          [stage]
-                          log = "critical"
-                      "#.to_string(), TEST_CONFIG_FILENAME), {
-                          default_config(Staging).log_level(LoggingLevel::Critical)
-                      });
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            max_idle_connections = -1
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
     }
 
     #[test]
-    fn test_bad_log_level_values() {
+    fn test_connection_limits() {
         // Take the lock so changing the environment doesn't cause races.
         let _env_lock = ENV_LOCK.lock().unwrap();
-        env::remove_var(CONFIG_ENV);
+        env::set_var(CONFIG_ENV, "stage");
 
-        assert!(RocketConfig::parse(r#"
-            [dev]
-            log = false
-        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          max_connections = 1024
+                          max_requests_per_connection = 100
+                          backlog = 64
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging)
+                              .max_connections(1024)
+                              .max_requests_per_connection(100)
+                              .backlog(64)
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          max_connections = false
+                          max_requests_per_connection = "none"
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging)
+                              .max_connections(None)
+                              .max_requests_per_connection(None)
+                      });
 
         assert!(RocketConfig::parse(r#"
-            [development]
-            log = 0
+            [stage]
+            max_connections = -1
         "#.to_string(), TEST_CONFIG_FILENAME).is_err());
 
         assert!(RocketConfig::parse(r#"
-            [prod]
-            log = "no"
+            [stage]
+            backlog = "lots"
         "#.to_string(), TEST_CONFIG_FILENAME).is_err());
     }
 
     #[test]
-    fn test_good_secret_key() {
+    fn test_tls_handshake_timeout() {
         // Take the lock so changing the environment doesn't cause races.
         let _env_lock = ENV_LOCK.lock().unwrap();
         env::set_var(CONFIG_ENV, "stage");
 
+        assert_eq!(default_config(Staging).unwrap().tls_handshake_timeout, Some(10));
+
         check_config!(RocketConfig::parse(r#"
                           [stage]
-                          secret_key = "TpUiXK2d/v5DFxJnWL12suJKPExKR8h9zd/o+E7SU+0="
+                          tls_handshake_timeout = 5
                       "#.to_string(), TEST_CONFIG_FILENAME), {
-                          default_config(Staging).secret_key(
-                              "TpUiXK2d/v5DFxJnWL12suJKPExKR8h9zd/o+E7SU+0="
-                          )
+                          default_config(Staging).tls_handshake_timeout(5)
                       });
 
         check_config!(RocketConfig::parse(r#"
                           [stage]
-                          secret_key = "jTyprDberFUiUFsJ3vcb1XKsYHWNBRvWAnXTlbTgGFU="
+                          tls_handshake_timeout = false
                       "#.to_string(), TEST_CONFIG_FILENAME), {
-                          default_config(Staging).secret_key(
-                              "jTyprDberFUiUFsJ3vcb1XKsYHWNBRvWAnXTlbTgGFU="
-                          )
+                          default_config(Staging).tls_handshake_timeout(None)
                       });
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            tls_handshake_timeout = "soon"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
     }
 
     #[test]
-    fn test_bad_secret_key() {
+    fn test_tls_ocsp_refresh() {
         // Take the lock so changing the environment doesn't cause races.
         let _env_lock = ENV_LOCK.lock().unwrap();
-        env::remove_var(CONFIG_ENV);
+        env::set_var(CONFIG_ENV, "stage");
 
-        assert!(RocketConfig::parse(r#"
-            [dev]
-            secret_key = true
+        assert_eq!(default_config(Staging).unwrap().tls_ocsp_refresh, None);
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          tls_ocsp_refresh = 3600
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).tls_ocsp_refresh(3600)
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          tls_ocsp_refresh = "none"
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).tls_ocsp_refresh(None)
+                      });
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            tls_ocsp_refresh = "hourly"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_tls_alpn() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        assert!(default_config(Staging).unwrap().tls_alpn.is_empty());
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          tls_alpn = ["h2", "http/1.1"]
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).tls_alpn(vec!["h2", "http/1.1"])
+                      });
+
+        assert!(default_config(Staging).tls_alpn(vec!["http/1.1", ""]).finalize().is_err());
+        assert!(default_config(Staging).tls_alpn(vec!["http 1.1"]).finalize().is_err());
+        assert!(default_config(Staging).tls_alpn(vec!["x".repeat(256)]).finalize().is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            tls_alpn = "http/1.1"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            tls_alpn = [1, 1]
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_tls_versions_and_ciphersuites() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        let config = default_config(Staging).unwrap();
+        assert!(config.tls_versions.is_empty());
+        assert!(config.tls_ciphersuites.is_empty());
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          tls_versions = ["tlsv1.2", "TLSv1.3"]
+                          tls_ciphersuites = ["tls13_aes_256_gcm_sha384"]
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging)
+                              .tls_versions(vec!["TLSv1.2", "TLSv1.3"])
+                              .tls_ciphersuites(vec!["TLS13_AES_256_GCM_SHA384"])
+                      });
+
+        assert!(default_config(Staging).tls_versions(vec!["TLSv1.1"]).finalize().is_err());
+        assert!(default_config(Staging).tls_versions(vec!["SSLv3"]).finalize().is_err());
+        assert!(default_config(Staging).tls_versions(vec![""]).finalize().is_err());
+
+        let weak = vec!["TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256", "TLS_RSA_WITH_RC4_128_MD5"];
+        assert!(default_config(Staging).tls_ciphersuites(weak).finalize().is_err());
+        assert!(default_config(Staging).tls_ciphersuites(vec!["AES"]).finalize().is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            tls_versions = "TLSv1.3"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            tls_ciphersuites = ["TLS_NULL_WITH_NULL_NULL"]
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    // The file doesn't exist, so only check this when it isn't read.
+    #[test]
+    #[cfg(not(feature = "tls"))]
+    fn test_tls_ocsp_response_requires_certs() {
+        let mut config = Config::new(Environment::Staging).unwrap();
+        assert!(config.set_tls_ocsp_response("some/ocsp.der").is_err());
+
+        config.set_tls("some/path.pem", "some/key.pem").unwrap();
+        assert!(config.set_tls_ocsp_response("some/ocsp.der").is_ok());
+    }
+
+    #[test]
+    fn test_mtls_allowed_fingerprints() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        let alice = "0bf5ad9e01f51f68cf44f2fdb0951bccf5157e0e7bc1487b84b3400bae928b8d";
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          mtls_allowed_fingerprints = [
+                              "0BF5AD9E01F51F68CF44F2FDB0951BCCF5157E0E7BC1487B84B3400BAE928B8D",
+                              "0000000000000000000000000000000000000000000000000000000000000000"
+                          ]
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging)
+                              .mtls_allowed_fingerprint(alice)
+                              .mtls_allowed_fingerprint(&*"0".repeat(64))
+                      });
+
+        let mut config = default_config(Staging).mtls_allowed_fingerprint(alice).unwrap();
+        assert_eq!(config.mtls_allowed_fingerprints[0][..4], [0x0b, 0xf5, 0xad, 0x9e]);
+
+        let colons: Vec<_> = alice.as_bytes().chunks(2)
+            .map(|pair| ::std::str::from_utf8(pair).unwrap())
+            .collect();
+        assert!(config.set_mtls_allowed_fingerprints(vec![colons.join(":")]).is_ok());
+        assert!(config.set_mtls_allowed_fingerprints(vec![format!("+{}", &alice[1..])]).is_err());
+        assert!(config.set_mtls_allowed_fingerprints(vec![format!("{}00", alice)]).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            mtls_allowed_fingerprints = "0bf5ad9e"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            mtls_allowed_fingerprints = ["0bf5ad9e"]
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_per_ip_connection_limits() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          max_connections_per_ip = 16
+                          connection_limit_exempt = ["10.0.0.0/8", "::1"]
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging)
+                              .max_connections_per_ip(16)
+                              .connection_limit_exempt("10.0.0.0/8")
+                              .connection_limit_exempt("::1")
+                      });
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            connection_limit_exempt = "10.0.0.0/8"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            connection_limit_exempt = ["10.0.0.0/64"]
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            connection_limit_exempt = ["load-balancer"]
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_header_limits() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        let config = default_config(Staging).unwrap();
+        assert_eq!(config.max_header_size, 409600);
+        assert_eq!(config.max_header_count, 100);
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          max_header_size = 65536
+                          max_header_count = 50
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).max_header_size(65536).max_header_count(50)
+                      });
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            max_header_size = 0
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            max_header_size = 1048576
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            max_header_count = 101
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            max_header_count = "many"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_server_name() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          server_name = "MyApp"
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).server_name("MyApp")
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          server_name = false
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).server_name(None)
+                      });
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            server_name = true
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            server_name = 10
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_features() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage.features]
+                          beta_search = true
+                          new_checkout = false
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging)
+                              .feature("beta_search", true)
+                              .feature("new_checkout", false)
+                      });
+
+        assert!(RocketConfig::parse(r#"
+            [stage.features]
+            beta_search = "yes"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            features = ["beta_search"]
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_allowed_hosts() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          allowed_hosts = ["example.com", "*.example.com", "localhost:8000"]
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging)
+                              .allowed_host("example.com")
+                              .allowed_host("*.example.com")
+                              .allowed_host("localhost:8000")
+                      });
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            allowed_hosts = "example.com"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            allowed_hosts = ["example.com:http"]
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            allowed_hosts = ["*example.com"]
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_hsts() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          hsts = true
+                          upgrade_insecure_requests = true
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging)
+                              .hsts(Hsts::default())
+                              .upgrade_insecure_requests(true)
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          upgrade_insecure_requests = true
+                          https_port = 8443
+                          trusted_proxies = ["10.0.0.0/8", "::1"]
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging)
+                              .upgrade_insecure_requests(true)
+                              .https_port(8443)
+                              .trusted_proxy("10.0.0.0/8")
+                              .trusted_proxy("::1")
+                      });
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            trusted_proxies = ["proxy.local"]
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          hsts = { max_age = 600, include_subdomains = true }
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).hsts(Hsts::new(600).include_subdomains(true))
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          hsts = { max_age = 63072000, include_subdomains = true, preload = true }
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).hsts(Hsts::preload())
+                      });
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            hsts = { max_age = 600, include_subdomains = true, preload = true }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            hsts = { preload = true }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            hsts = { max_age = 600, subdomains = true }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_response_digest() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          response_digest = "SHA-256"
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).response_digest(ChecksumAlgorithm::Sha256)
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          response_digest = "md5"
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).response_digest(ChecksumAlgorithm::Md5)
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          response_digest = false
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).response_digest(None)
+                      });
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            response_digest = "SHA-1"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            response_digest = true
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_merge_env() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var("MERGE_TEST_PORT", "7810");
+        env::set_var("merge_test_limits", "{ forms = 1024 }");
+        env::set_var("MERGE_TEST_FLAG", "true");
+
+        let config = Config::build(Environment::Staging)
+            .port(8000)
+            .merge_env("MERGE_TEST_")
+            .workers(3)
+            .unwrap();
+
+        assert_eq!(config.port, 7810);
+        assert_eq!(config.workers, 3);
+        assert_eq!(config.limits.get("forms"), Some(1024));
+        assert_eq!(config.get_bool("flag"), Ok(true));
+
+        env::set_var("MERGE_TEST_PORT", "seventy");
+        let result = Config::build(Environment::Staging).merge_env("merge_test_").finalize();
+        match result {
+            Err(ConfigError::BadEnvVal(ref key, ..)) => assert_eq!(key, "port"),
+            _ => panic!("expected a BadEnvVal error")
+        }
+
+        env::remove_var("MERGE_TEST_PORT");
+        env::remove_var("merge_test_limits");
+        env::remove_var("MERGE_TEST_FLAG");
+    }
+
+    #[test]
+    fn test_mutual_tls() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          mutual_tls = "required"
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).mutual_tls(MutualTls::Required)
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          mutual_tls = "optional"
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).mutual_tls(MutualTls::Optional)
+                      });
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            mutual_tls = "sometimes"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            mutual_tls = true
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "tls")]
+    fn test_bad_tls_bytes() {
+        let not_pem = b"certainly not PEM".to_vec();
+        assert!(Config::build(Environment::Staging)
+            .tls_bytes(not_pem.clone(), not_pem.clone(), None)
+            .finalize()
+            .is_err());
+
+        let mut config = Config::staging().unwrap();
+        assert!(config.set_tls_bytes(&not_pem, &not_pem, Some(&not_pem)).is_err());
+        assert!(!config.tls_enabled());
+    }
+
+    #[test]
+    fn test_private_cookie_cipher() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        let config = default_config(Staging).unwrap();
+        assert_eq!(config.private_cookie_cipher, CookieCipher::Aes256Gcm);
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          private_cookie_cipher = "chacha20-poly1305"
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging)
+                              .private_cookie_cipher(CookieCipher::ChaCha20Poly1305)
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          private_cookie_cipher = "AES-256-GCM"
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).private_cookie_cipher(CookieCipher::Aes256Gcm)
+                      });
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            private_cookie_cipher = "AES-128-CBC"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            private_cookie_cipher = true
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_cookie_limits() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          cookie_limits = { max_count = 20, max_size = 4096 }
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).cookie_limits(CookieLimits::new(20, 4096))
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          cookie_limits = { overflow = "truncate_oldest" }
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).cookie_limits(CookieLimits::default()
+                              .overflow(CookieOverflow::TruncateOldest))
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          cookie_limits = { max_count = 5, overflow = "reject" }
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).cookie_limits(CookieLimits::new(5, 8192)
+                              .overflow(CookieOverflow::Reject))
+                      });
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            cookie_limits = { overflow = "drop" }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            cookie_limits = { max_cookies = 10 }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            cookie_limits = 10
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_limits_timeouts() {
+        use std::time::Duration;
+        use http::MediaType;
+
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        let config = RocketConfig::parse(r#"
+            [stage.limits]
+            forms = 1024
+            read_timeout = 10
+            body_timeout = 60
+
+            [stage.limits.timeouts."multipart/*"]
+            body_timeout = 600
+
+            [stage.limits.timeouts."application/json"]
+            read_timeout = 2
+        "#.to_string(), TEST_CONFIG_FILENAME).unwrap().into_active();
+
+        let limits = &config.limits;
+        let (json, form) = (MediaType::JSON, MediaType::FormData);
+        assert_eq!(limits.get("forms"), Some(1024));
+        assert_eq!(limits.get_read_timeout(None), Duration::from_secs(10));
+        assert_eq!(limits.get_body_timeout(None), Some(Duration::from_secs(60)));
+        assert_eq!(limits.get_read_timeout(Some(&json)), Duration::from_secs(2));
+        assert_eq!(limits.get_body_timeout(Some(&json)), Some(Duration::from_secs(60)));
+        assert_eq!(limits.get_read_timeout(Some(&form)), Duration::from_secs(10));
+        assert_eq!(limits.get_body_timeout(Some(&form)), Some(Duration::from_secs(600)));
+
+        for bad in &["read_timeout = 0", "body_timeout = \"5s\"",
+                     "timeouts = { \"no/ type\" = { read_timeout = 1 } }",
+                     "timeouts = { \"text/plain\" = { idle_timeout = 1 } }"] {
+            let toml = format!("[stage]\nlimits = {{ {} }}", bad);
+            assert!(RocketConfig::parse(toml, TEST_CONFIG_FILENAME).is_err(), "{}", bad);
+        }
+
+        env::remove_var(CONFIG_ENV);
+    }
+
+    #[test]
+    fn test_compression() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          compression = true
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).compression(CompressionConfig::default())
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          compression = { brotli = 11, zstd = false, min_size = 512 }
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).compression(CompressionConfig::default()
+                              .brotli(11).zstd(None).min_size(512))
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          compression = { gzip = 40 }
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).compression(CompressionConfig::default().gzip(9))
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          compression = false
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging)
+                      });
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            compression = { deflate = 6 }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            compression = { gzip = "best" }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_text_encoding() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        assert_eq!(default_config(Staging).unwrap().text_encoding, None);
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          text_encoding = "strict"
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).text_encoding(TextEncoding::Strict)
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          text_encoding = "Lossy"
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).text_encoding(TextEncoding::Lossy)
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          text_encoding = false
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).text_encoding(None)
+                      });
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            text_encoding = "latin1"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            text_encoding = true
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_header_policy() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          header_policy = { deny = ["X-Powered-By", "X-Debug-*"] }
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).header_policy(
+                              HeaderPolicy::new().deny("X-Powered-By").deny("X-Debug-*"))
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          header_policy = { allow = ["Cache-Control"], rewrite = { Server = "web" } }
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).header_policy(
+                              HeaderPolicy::new().allow("Cache-Control").rewrite("Server", "web"))
+                      });
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            header_policy = { deny = "Server" }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [stage]
+            header_policy = { strip = ["Server"] }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_good_log_levels() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          log = "normal"
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).log_level(LoggingLevel::Normal)
+                      });
+
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          log = "debug"
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).log_level(LoggingLevel::Debug)
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          log = "critical"
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).log_level(LoggingLevel::Critical)
+                      });
+    }
+
+    #[test]
+    fn test_bad_log_level_values() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::remove_var(CONFIG_ENV);
+
+        assert!(RocketConfig::parse(r#"
+            [dev]
+            log = false
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [development]
+            log = 0
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [prod]
+            log = "no"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_good_secret_key() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          secret_key = "TpUiXK2d/v5DFxJnWL12suJKPExKR8h9zd/o+E7SU+0="
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).secret_key(
+                              "TpUiXK2d/v5DFxJnWL12suJKPExKR8h9zd/o+E7SU+0="
+                          )
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          secret_key = "jTyprDberFUiUFsJ3vcb1XKsYHWNBRvWAnXTlbTgGFU="
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).secret_key(
+                              "jTyprDberFUiUFsJ3vcb1XKsYHWNBRvWAnXTlbTgGFU="
+                          )
+                      });
+    }
+
+    #[test]
+    fn test_bad_secret_key() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::remove_var(CONFIG_ENV);
+
+        assert!(RocketConfig::parse(r#"
+            [dev]
+            secret_key = true
         "#.to_string(), TEST_CONFIG_FILENAME).is_err());
 
         assert!(RocketConfig::parse(r#"
@@ -1089,6 +2490,297 @@ mod test {
         "#.to_string(), TEST_CONFIG_FILENAME).is_err());
     }
 
+    #[test]
+    fn test_builder_from_toml() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "staging");
+
+        let toml = r#"
+            [staging]
+            port = 9000
+            secret_key = "TpUiXK2d/v5DFxJnWL12suJKPExKR8h9zd/o+E7SU+0="
+
+            [production]
+            port = 80
+
+            [global]
+            workers = 7
+        "#;
+
+        let config = ConfigBuilder::from_toml_str(toml).unwrap().port(9001).finalize().unwrap();
+        assert_eq!(config.environment, Staging);
+        assert_eq!(config.port, 9001);
+        assert_eq!(config.workers, 7);
+        assert!(!config.secret_key.is_generated());
+
+        let path = env::temp_dir().join("rocket_builder_from_toml.toml");
+        ::std::fs::write(&path, toml).unwrap();
+        let config = ConfigBuilder::from_toml_file(&path).unwrap().finalize().unwrap();
+        assert_eq!(config.port, 9000);
+        assert_eq!(config.root(), env::temp_dir().as_path());
+        ::std::fs::remove_file(&path).unwrap();
+
+        let missing = env::temp_dir().join("rocket_builder_missing.toml");
+        assert_eq!(ConfigBuilder::from_toml_file(missing).err(), Some(ConfigError::NotFound));
+        assert!(ConfigBuilder::from_toml_str("[staging]\nport = \"x\"").is_err());
+        env::remove_var(CONFIG_ENV);
+    }
+
+    #[test]
+    fn test_args() {
+        use super::{Args, ArgsError};
+
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "staging");
+        env::set_var("ROCKET_WORKERS", "5");
+
+        let path = env::temp_dir().join("rocket_args.toml");
+        ::std::fs::write(&path, r#"
+            [staging]
+            port = 9000
+
+            [production]
+            port = 80
+            motd = "prod"
+        "#).unwrap();
+
+        let config_arg = path.to_str().unwrap();
+        let args = Args::parse(vec!["--config", config_arg]).unwrap();
+        let config = args.builder().unwrap().finalize().unwrap();
+        assert_eq!(config.environment, Staging);
+        assert_eq!(config.port, 9000);
+        assert_eq!(config.workers, 5);
+
+        let args = Args::parse(vec!["-c", config_arg, "-e", "prod", "-a", "0.0.0.0",
+                                    "workers=9", "motd=cli"]).unwrap();
+        let config = args.builder().unwrap().finalize().unwrap();
+        assert_eq!(config.environment, Production);
+        assert_eq!(config.port, 80);
+        assert_eq!(config.address, "0.0.0.0");
+        assert_eq!(config.workers, 9);
+        assert_eq!(config.get_str("motd"), Ok("cli"));
+
+        let args = Args::parse(vec!["-c", config_arg, "-p", "8080"]).unwrap();
+        assert_eq!(args.builder().unwrap().finalize().unwrap().port, 8080);
+
+        let args = Args::parse(vec!["-c", config_arg, "-e", "testing"]).unwrap();
+        assert_eq!(args.builder().err(), Some(ConfigError::BadEnv("testing".into())));
+
+        let args = Args::parse(vec!["-c", config_arg, "workers=many"]).unwrap();
+        assert!(args.builder().unwrap().finalize().is_err());
+
+        let from_args = |args: &[&str]| {
+            let args = args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+            super::init_from_args(args.into_iter())
+        };
+
+        assert_eq!(from_args(&["app", "-c", config_arg]).unwrap().port, 9000);
+        assert_eq!(from_args(&["app", "--help"]).err(), Some(ArgsError::Help));
+        assert_eq!(from_args(&["app", "--verbose"]).err(),
+                   Some(ArgsError::Unknown("--verbose".into())));
+        assert_eq!(from_args(&["app", "-c", config_arg, "-e", "testing"]).err(),
+                   Some(ArgsError::Config(ConfigError::BadEnv("testing".into()))));
+        ::std::fs::remove_file(&path).unwrap();
+
+        let missing = env::temp_dir().join("rocket_args_missing.toml");
+        let args = Args::parse(vec!["-c", missing.to_str().unwrap()]).unwrap();
+        assert_eq!(args.builder().err(), Some(ConfigError::NotFound));
+
+        env::remove_var("ROCKET_WORKERS");
+        env::remove_var(CONFIG_ENV);
+    }
+
+    #[test]
+    fn test_includes() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "production");
+
+        let dir = env::temp_dir().join("rocket_includes");
+        let _ = ::std::fs::create_dir_all(dir.join("shared"));
+        let write = |name: &str, src: &str| ::std::fs::write(dir.join(name), src).unwrap();
+        write("shared/base.toml", r#"
+            include = ["limits.toml"]
+
+            [global]
+            workers = 3
+
+            [production]
+            port = 7000
+            address = "1.2.3.4"
+            motd = "base"
+        "#);
+
+        write("shared/limits.toml", "[global.limits]\nforms = 1024\njson = 2048\n");
+        write("secrets.toml", "[production]\nport = 7001\nmotd = \"secrets\"\n");
+
+        let src = r#"
+            include = ["shared/base.toml", "secrets.toml"]
+
+            [global.limits]
+            json = 4096
+
+            [production]
+            address = "5.6.7.8"
+        "#;
+
+        let config = RocketConfig::parse(src.into(), dir.join("Rocket.toml")).unwrap();
+        let config = config.active();
+        assert_eq!(config.workers, 3);
+        assert_eq!(config.port, 7001);
+        assert_eq!(config.address, "5.6.7.8");
+        assert_eq!(config.get_str("motd"), Ok("secrets"));
+        assert_eq!(config.limits.get("forms"), Some(1024));
+        assert_eq!(config.limits.get("json"), Some(4096));
+
+        // Includes must be an array of readable, non-recursive files.
+        write("loop.toml", "include = [\"loop.toml\"]");
+        for src in &["include = [\"loop.toml\"]", "include = [\"missing.toml\"]"] {
+            match RocketConfig::parse(src.to_string(), dir.join("Rocket.toml")) {
+                Err(ConfigError::BadFilePath(..)) => { /* expected */ }
+                result => panic!("expected a bad file path, got {:?}", result.err())
+            }
+        }
+
+        for src in &["include = \"base.toml\"", "include = [1]"] {
+            match RocketConfig::parse(src.to_string(), dir.join("Rocket.toml")) {
+                Err(ConfigError::BadType(ref key, ..)) if key == "include" => { /* expected */ }
+                result => panic!("expected a bad type, got {:?}", result.err())
+            }
+        }
+
+        let _ = ::std::fs::remove_dir_all(&dir);
+        env::remove_var(CONFIG_ENV);
+    }
+
+    #[test]
+    fn test_env_interpolation() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "development");
+        env::set_var("ROCKET_TEST_DB_HOST", "db.internal");
+        env::remove_var("ROCKET_TEST_DB_PORT");
+
+        let config = RocketConfig::parse(r#"
+            [development]
+            address = "${ROCKET_TEST_ADDRESS:-127.0.0.1}"
+            database = "postgres://${ROCKET_TEST_DB_HOST}:${ROCKET_TEST_DB_PORT:-5432}/app"
+            hosts = ["${ROCKET_TEST_DB_HOST}", "$${literal}"]
+            nested = { url = "http://${ROCKET_TEST_DB_HOST}/" }
+        "#.to_string(), TEST_CONFIG_FILENAME).unwrap();
+
+        let config = config.active();
+        assert_eq!(config.address, "127.0.0.1");
+        assert_eq!(config.get_str("database"), Ok("postgres://db.internal:5432/app"));
+
+        let hosts = config.get_slice("hosts").unwrap();
+        assert_eq!(hosts[0].as_str(), Some("db.internal"));
+        assert_eq!(hosts[1].as_str(), Some("${literal}"));
+
+        let nested = config.get_table("nested").unwrap();
+        assert_eq!(nested["url"].as_str(), Some("http://db.internal/"));
+
+        let error = RocketConfig::parse(r#"
+            [development]
+            database = "postgres://${ROCKET_TEST_DB_PORT}/app"
+        "#.to_string(), TEST_CONFIG_FILENAME).err();
+
+        let expected = ConfigError::BadInterpolation(
+            "development.database".into(),
+            "environment variable 'ROCKET_TEST_DB_PORT' is not set".into(),
+            TEST_CONFIG_FILENAME.into()
+        );
+
+        assert_eq!(error, Some(expected));
+        env::remove_var("ROCKET_TEST_DB_HOST");
+        env::remove_var(CONFIG_ENV);
+    }
+
+    #[test]
+    fn test_extract() {
+        use std::collections::HashMap;
+
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "development");
+
+        let config = RocketConfig::parse(r#"
+            [development]
+            beta = true
+
+            [development.pools.main]
+            size = 16
+            timeout = 5
+        "#.to_string(), TEST_CONFIG_FILENAME).unwrap().into_active();
+
+        let pools: HashMap<String, HashMap<String, u32>> = config.extract("pools").unwrap();
+        assert_eq!(pools["main"]["size"], 16);
+        assert_eq!(pools["main"]["timeout"], 5);
+
+        let root: HashMap<String, ::toml::Value> = config.extract_root().unwrap();
+        assert_eq!(root["beta"].as_bool(), Some(true));
+
+        assert_eq!(config.extract::<bool>("missing"), Err(ConfigError::NotFound));
+        match config.extract::<HashMap<String, String>>("pools") {
+            Err(ConfigError::BadExtract(ref name, _, _)) => {
+                assert_eq!(name, "development.pools");
+            }
+            result => panic!("expected a BadExtract error, found {:?}", result)
+        }
+
+        env::remove_var(CONFIG_ENV);
+    }
+
+    #[test]
+    fn test_custom_environments() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+
+        let toml = r#"
+            [staging]
+            port = 9000
+
+            [qa]
+            extends = "staging"
+            workers = 3
+            tier = "qa"
+
+            [global]
+            address = "::1"
+        "#;
+
+        env::set_var(CONFIG_ENV, "qa");
+        let qa = Custom("qa".into());
+        check_config!(RocketConfig::parse(toml.into(), TEST_CONFIG_FILENAME), {
+            default_config(qa.clone()).log_level(LoggingLevel::Normal)
+                .port(9000).workers(3).address("::1").extra("tier", "qa")
+        });
+
+        // Custom environment extras don't leak into other environments.
+        check_config!(Staging, RocketConfig::parse(toml.into(), TEST_CONFIG_FILENAME), {
+            default_config(Staging).port(9000).address("::1")
+        });
+
+        // An undeclared custom environment can't be active.
+        env::set_var(CONFIG_ENV, "canary");
+        let err = ConfigError::BadEnv("canary".into());
+        assert_eq!(RocketConfig::parse(toml.into(), TEST_CONFIG_FILENAME).err(), Some(err));
+        env::remove_var(CONFIG_ENV);
+
+        // Custom environments must extend a standard environment.
+        assert!(RocketConfig::parse(r#"
+            [qa]
+            extends = "canary"
+        "#.into(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [qa]
+            extends = 1
+        "#.into(), TEST_CONFIG_FILENAME).is_err());
+    }
+
     #[test]
     fn test_global_overrides() {
         // Take the lock so changing the environment doesn't cause races.
@@ -1102,21 +2794,21 @@ mod test {
                               [{}]
                               address = "::1"
                           "#, GLOBAL_ENV_NAME), TEST_CONFIG_FILENAME), {
-                              default_config(*env).address("::1")
+                              default_config(env.clone()).address("::1")
                           });
 
             check_config!(RocketConfig::parse(format!(r#"
                               [{}]
                               database = "mysql"
                           "#, GLOBAL_ENV_NAME), TEST_CONFIG_FILENAME), {
-                              default_config(*env).extra("database", "mysql")
+                              default_config(env.clone()).extra("database", "mysql")
                           });
 
             check_config!(RocketConfig::parse(format!(r#"
                               [{}]
                               port = 3980
                           "#, GLOBAL_ENV_NAME), TEST_CONFIG_FILENAME), {
-                              default_config(*env).port(3980)
+                              default_config(env.clone()).port(3980)
                           });
         }
     }
@@ -1157,7 +2849,7 @@ mod test {
 
             // And non-active configs.
             for env in &Environment::all() {
-                check_value(&*key.to_lowercase(), val, rconfig.get(*env));
+                check_value(&*key.to_lowercase(), val, rconfig.get(env));
             }
         }
 
@@ -1195,7 +2887,7 @@ mod test {
 
             // And non-active configs.
             for env in &Environment::all() {
-                check_value(&*key.to_lowercase(), val, r.get(*env));
+                check_value(&*key.to_lowercase(), val, r.get(env));
             }
         }
 
@@ -88,6 +88,54 @@ pub fn parse_simple_toml_value(mut input: &str) -> Result<Value, String> {
     result.map_err(|e| e.to_string())
 }
 
+/// Replaces every `${NAME}` in `src` with the value `lookup` returns for
+/// `NAME`, which is required, and every `${NAME:-default}` with the value or,
+/// if there is none, `default`. `$${` is replaced with a literal `${`. Values
+/// aren't interpolated themselves.
+pub fn interpolate<F>(src: &str, lookup: F) -> Result<String, String>
+    where F: Fn(&str) -> Option<String>
+{
+    let mut output = String::with_capacity(src.len());
+    let mut rest = src;
+    while let Some(i) = rest.find('$') {
+        output.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if rest.starts_with("$${") {
+            output.push_str("${");
+            rest = &rest[3..];
+            continue;
+        } else if !rest.starts_with("${") {
+            output.push('$');
+            rest = &rest[1..];
+            continue;
+        }
+
+        let end = rest.find('}').ok_or_else(|| format!("unterminated `${{` in '{}'", src))?;
+        let (name, default) = match rest[2..end].find(":-") {
+            Some(j) => (&rest[2..(2 + j)], Some(&rest[(4 + j)..end])),
+            None => (&rest[2..end], None)
+        };
+
+        let valid = name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && name.chars().next().map_or(false, |c| !c.is_ascii_digit());
+
+        if !valid {
+            return Err(format!("'{}' is not a valid environment variable name", name));
+        }
+
+        match (lookup(name), default) {
+            (Some(value), _) => output.push_str(&value),
+            (None, Some(default)) => output.push_str(default),
+            (None, None) => return Err(format!("environment variable '{}' is not set", name)),
+        }
+
+        rest = &rest[(end + 1)..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
 /// A simple wrapper over a `Value` reference with a custom implementation of
 /// `Display`. This is used to log config values at initialization.
 pub(crate) struct LoggedValue<'a>(pub &'a Value);
@@ -116,7 +164,7 @@ impl<'a> fmt::Display for LoggedValue<'a> {
 #[cfg(test)]
 mod test {
     use std::collections::BTreeMap;
-    use super::parse_simple_toml_value;
+    use super::{parse_simple_toml_value, interpolate};
     use super::Value::{self, *};
 
     macro_rules! assert_parse {
@@ -177,4 +225,27 @@ mod test {
             map
         }));
     }
+
+    #[test]
+    fn test_interpolate() {
+        let lookup = |name: &str| match name {
+            "HOST" => Some("db.local".to_string()),
+            "PORT" => Some("5432".to_string()),
+            "EMPTY" => Some("".to_string()),
+            _ => None
+        };
+
+        let ok = |src: &str| interpolate(src, lookup).unwrap();
+        assert_eq!(ok("postgres://${HOST}:${PORT}/app"), "postgres://db.local:5432/app");
+        assert_eq!(ok("${USER:-rocket}@${HOST:-localhost}"), "rocket@db.local");
+        assert_eq!(ok("${EMPTY:-default}${MISSING:-}"), "");
+        assert_eq!(ok("$${HOST} costs $5 $"), "${HOST} costs $5 $");
+        assert_eq!(ok("no variables"), "no variables");
+
+        assert!(interpolate("${MISSING}", lookup).is_err());
+        assert!(interpolate("${HOST", lookup).is_err());
+        assert!(interpolate("${}", lookup).is_err());
+        assert!(interpolate("${1HOST}", lookup).is_err());
+        assert!(interpolate("${HO-ST:-x}", lookup).is_err());
+    }
 }
@@ -0,0 +1,266 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use md5;
+use sha2::{Sha256, Digest};
+use base64;
+
+use http::HeaderMap;
+use http::uncased::uncased_eq;
+use security::constant_time_eq;
+
+/// A hash algorithm Rocket can use to verify the checksum of a request body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChecksumAlgorithm {
+    /// MD5, as used by the `Content-MD5` header and the `MD5` digest.
+    Md5,
+    /// SHA-256, as used by the `SHA-256` digest.
+    Sha256,
+}
+
+impl ChecksumAlgorithm {
+    /// Returns the name of the algorithm as it appears in a `Digest` header.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::data::ChecksumAlgorithm;
+    ///
+    /// assert_eq!(ChecksumAlgorithm::Md5.name(), "MD5");
+    /// assert_eq!(ChecksumAlgorithm::Sha256.name(), "SHA-256");
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ChecksumAlgorithm::Md5 => "MD5",
+            ChecksumAlgorithm::Sha256 => "SHA-256",
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Option<ChecksumAlgorithm> {
+        if uncased_eq(name, "MD5") {
+            Some(ChecksumAlgorithm::Md5)
+        } else if uncased_eq(name, "SHA-256") {
+            Some(ChecksumAlgorithm::Sha256)
+        } else {
+            None
+        }
+    }
+}
+
+/// The checksum of a request or response body.
+///
+/// When checksum verification is enabled via the `verify_checksums`
+/// configuration parameter and a request carries a `Content-MD5` or `Digest`
+/// header, Rocket hashes the body as it is read through a
+/// [`DataStream`](/rocket/data/struct.DataStream.html). Once the stream has
+/// been read to completion, the computed checksum can be retrieved via
+/// [`DataStream::checksum()`], for instance, to store alongside an upload.
+/// Bodies that aren't read to completion are not verified.
+///
+/// The `Display` implementation formats the checksum as it would appear in a
+/// `Digest` header: `SHA-256=<base64>`.
+///
+/// [`DataStream::checksum()`]: /rocket/data/struct.DataStream.html#method.checksum
+#[derive(Debug, Clone)]
+pub struct Checksum {
+    algorithm: ChecksumAlgorithm,
+    bytes: Vec<u8>,
+}
+
+impl Checksum {
+    /// Computes the checksum of `bytes` using `algorithm`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::data::{Checksum, ChecksumAlgorithm};
+    ///
+    /// let checksum = Checksum::compute(ChecksumAlgorithm::Md5, b"hello");
+    /// assert_eq!(checksum.to_base64(), "XUFAKrxLKna5cZ2REBfFkg==");
+    /// assert_eq!(checksum.to_string(), "MD5=XUFAKrxLKna5cZ2REBfFkg==");
+    /// ```
+    pub fn compute(algorithm: ChecksumAlgorithm, bytes: &[u8]) -> Checksum {
+        let mut hasher = Hasher::new(algorithm);
+        hasher.update(bytes);
+        hasher.finish()
+    }
+
+    /// Returns the algorithm used to compute this checksum.
+    #[inline(always)]
+    pub fn algorithm(&self) -> ChecksumAlgorithm {
+        self.algorithm
+    }
+
+    /// Returns the raw bytes of this checksum.
+    #[inline(always)]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the checksum encoded in base64, as it appears in `Content-MD5`
+    /// and `Digest` headers.
+    #[inline]
+    pub fn to_base64(&self) -> String {
+        base64::encode(&self.bytes)
+    }
+
+    /// Returns the checksum as it appears in a `Content-Digest` header:
+    /// `sha-256=:<base64>:`.
+    pub(crate) fn to_content_digest(&self) -> String {
+        format!("{}=:{}:", self.algorithm.name().to_lowercase(), self.to_base64())
+    }
+
+    /// Returns the checksum the client claims the body has, if any. A supported
+    /// algorithm in a `Digest` header is preferred, strongest first, over a
+    /// `Content-MD5` header. Malformed values are ignored.
+    pub(crate) fn expected(headers: &HeaderMap) -> Option<Checksum> {
+        let mut best: Option<Checksum> = None;
+        for value in headers.get("Digest") {
+            for instance in value.split(',') {
+                let mut parts = instance.trim().splitn(2, '=');
+                let (name, encoded) = match (parts.next(), parts.next()) {
+                    (Some(name), Some(encoded)) => (name.trim(), encoded.trim()),
+                    _ => continue
+                };
+
+                let algorithm = match ChecksumAlgorithm::from_name(name) {
+                    Some(algorithm) => algorithm,
+                    None => continue
+                };
+
+                // SHA-256 is the strongest algorithm we support: keep it.
+                let have_sha = best.as_ref()
+                    .map_or(false, |b| b.algorithm == ChecksumAlgorithm::Sha256);
+
+                if have_sha {
+                    continue;
+                }
+
+                if let Ok(bytes) = base64::decode(encoded) {
+                    best = Some(Checksum { algorithm: algorithm, bytes: bytes });
+                }
+            }
+        }
+
+        if best.is_some() {
+            return best;
+        }
+
+        headers.get_one("Content-MD5")
+            .and_then(|value| base64::decode(value.trim()).ok())
+            .map(|bytes| Checksum { algorithm: ChecksumAlgorithm::Md5, bytes: bytes })
+    }
+}
+
+impl fmt::Display for Checksum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}={}", self.algorithm.name(), self.to_base64())
+    }
+}
+
+/// Compares the digests in constant time.
+impl PartialEq for Checksum {
+    fn eq(&self, other: &Checksum) -> bool {
+        self.algorithm == other.algorithm && constant_time_eq(&self.bytes, &other.bytes)
+    }
+}
+
+impl Eq for Checksum { }
+
+/// The checksum expected for a body and, once the body has been fully read,
+/// the checksum that was actually computed. Shared between the `Data`, the
+/// `DataStream`, and the dispatcher, which fails mismatched requests.
+pub(crate) struct ChecksumState {
+    expected: Checksum,
+    computed: Mutex<Option<Checksum>>,
+}
+
+impl ChecksumState {
+    pub(crate) fn new(expected: Checksum) -> ChecksumState {
+        ChecksumState { expected: expected, computed: Mutex::new(None) }
+    }
+
+    #[inline]
+    pub(crate) fn computed(&self) -> Option<Checksum> {
+        self.computed.lock().expect("checksum lock").clone()
+    }
+
+    /// Returns `true` if the body has been fully read and its checksum does
+    /// not match the expected checksum.
+    pub(crate) fn is_mismatch(&self) -> bool {
+        self.computed().map_or(false, |computed| computed != self.expected)
+    }
+}
+
+enum Hasher {
+    Md5(md5::Context),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    fn new(algorithm: ChecksumAlgorithm) -> Hasher {
+        match algorithm {
+            ChecksumAlgorithm::Md5 => Hasher::Md5(md5::Context::new()),
+            ChecksumAlgorithm::Sha256 => Hasher::Sha256(Sha256::default()),
+        }
+    }
+
+    #[inline]
+    fn update(&mut self, bytes: &[u8]) {
+        match *self {
+            Hasher::Md5(ref mut ctx) => ctx.consume(bytes),
+            Hasher::Sha256(ref mut sha) => sha.input(bytes),
+        }
+    }
+
+    fn finish(self) -> Checksum {
+        match self {
+            Hasher::Md5(ctx) => Checksum {
+                algorithm: ChecksumAlgorithm::Md5,
+                bytes: ctx.compute().0.to_vec()
+            },
+            Hasher::Sha256(sha) => Checksum {
+                algorithm: ChecksumAlgorithm::Sha256,
+                bytes: sha.result().to_vec()
+            },
+        }
+    }
+}
+
+/// Incrementally hashes bytes read from a body and records the result in the
+/// shared `ChecksumState` once the body is exhausted.
+pub(crate) struct Verifier {
+    hasher: Option<Hasher>,
+    state: Arc<ChecksumState>,
+}
+
+impl Verifier {
+    pub(crate) fn new(state: Arc<ChecksumState>) -> Verifier {
+        let hasher = Hasher::new(state.expected.algorithm);
+        Verifier { hasher: Some(hasher), state: state }
+    }
+
+    #[inline]
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        if let Some(ref mut hasher) = self.hasher {
+            hasher.update(bytes);
+        }
+    }
+
+    /// Finalizes the hash. Returns `false` if the computed checksum doesn't
+    /// match the expected checksum.
+    pub(crate) fn finish(&mut self) -> bool {
+        let computed = match self.hasher.take() {
+            Some(hasher) => hasher.finish(),
+            None => return !self.state.is_mismatch()
+        };
+
+        *self.state.computed.lock().expect("checksum lock") = Some(computed);
+        !self.state.is_mismatch()
+    }
+
+    #[inline]
+    pub(crate) fn checksum(&self) -> Option<Checksum> {
+        self.state.computed()
+    }
+}
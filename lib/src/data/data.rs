@@ -1,18 +1,18 @@
 use std::io::{self, Read, Write, Cursor, Chain};
 use std::path::Path;
 use std::fs::File;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use std::sync::{Arc, Mutex};
 
-#[cfg(feature = "tls")] use super::net_stream::HttpsStream;
-
-use super::data_stream::{DataStream, kill_stream};
+use super::data_stream::{DataStream, Deadline, kill_stream};
+use super::checksum::{Checksum, ChecksumState, Verifier};
 use super::net_stream::NetStream;
 use ext::ReadExt;
 
 use http::hyper;
 use http::hyper::h1::HttpReader;
 use http::hyper::h1::HttpReader::*;
-use http::hyper::net::{HttpStream, NetworkStream};
+use http::hyper::net::NetworkStream;
 
 pub type HyperBodyReader<'a, 'b> =
     self::HttpReader<&'a mut hyper::buffer::BufReader<&'b mut NetworkStream>>;
@@ -55,6 +55,8 @@ pub struct Data {
     buffer: Vec<u8>,
     is_complete: bool,
     stream: BodyReader,
+    checksum: Option<Arc<ChecksumState>>,
+    deadline: Option<Deadline>,
 }
 
 impl Data {
@@ -83,42 +85,44 @@ impl Data {
         // actually do this, however.
         let empty_http_stream = HttpReader::SizedReader(empty_stream, 0);
         let stream = ::std::mem::replace(&mut self.stream, empty_http_stream);
-        DataStream(Cursor::new(buffer).chain(stream))
+        let verifier = self.checksum.take().map(Verifier::new);
+        DataStream(Cursor::new(buffer).chain(stream), verifier, self.deadline.take())
+    }
+
+    // Arranges for the body to be verified against the `expected` checksum as
+    // it is read. The returned state records the outcome of the verification.
+    pub(crate) fn verify_checksum(&mut self, expected: Checksum) -> Arc<ChecksumState> {
+        let state = Arc::new(ChecksumState::new(expected));
+        self.checksum = Some(state.clone());
+        state
     }
 
+    // Creates the data of a request from its Hyper body. Each read of the body
+    // from the network times out after `read_timeout`, and, if there is a
+    // `body_timeout`, reads fail once it has passed since this call.
+    //
     // FIXME: This is absolutely terrible (downcasting!), thanks to Hyper.
-    pub(crate) fn from_hyp(mut body: HyperBodyReader) -> Result<Data, &'static str> {
+    pub(crate) fn from_hyp(
+        mut body: HyperBodyReader,
+        read_timeout: Duration,
+        body_timeout: Option<Duration>,
+    ) -> Result<Data, &'static str> {
         // Steal the internal, undecoded data buffer and net stream from Hyper.
         let (mut hyper_buf, pos, cap) = body.get_mut().take_buf();
         unsafe { hyper_buf.set_len(cap); }
         let hyper_net_stream = body.get_ref().get_ref();
 
-        #[cfg(feature = "tls")]
-        #[inline(always)]
-        fn concrete_stream(stream: &&mut NetworkStream) -> Option<NetStream> {
-            stream.downcast_ref::<HttpsStream>()
-                .map(|s| NetStream::Https(s.clone()))
-                .or_else(|| {
-                    stream.downcast_ref::<HttpStream>()
-                        .map(|s| NetStream::Http(s.clone()))
-                })
-        }
-
-        #[cfg(not(feature = "tls"))]
-        #[inline(always)]
-        fn concrete_stream(stream: &&mut NetworkStream) -> Option<NetStream> {
-            stream.downcast_ref::<HttpStream>()
-                .map(|s| NetStream::Http(s.clone()))
-        }
-
         // Retrieve the underlying Http(s)Stream from Hyper.
-        let net_stream = match concrete_stream(hyper_net_stream) {
+        let net_stream = match NetStream::from_hyp(&**hyper_net_stream) {
             Some(net_stream) => net_stream,
             None => return Err("Stream is not an HTTP(s) stream!")
         };
 
-        // Set the read timeout to 5 seconds.
-        net_stream.set_read_timeout(Some(Duration::from_secs(5))).expect("timeout set");
+        // Reads of the peek buffer are bounded by the timeouts, too.
+        let now = Instant::now();
+        let deadline = body_timeout.map(|body| Deadline::new(now + body, read_timeout));
+        let timeout = body_timeout.map_or(read_timeout, |body| ::std::cmp::min(body, read_timeout));
+        net_stream.set_read_timeout(Some(timeout)).expect("timeout set");
 
         // TODO: Explain this.
         trace_!("Hyper buffer: [{}..{}] ({} bytes).", pos, cap, cap - pos);
@@ -135,7 +139,9 @@ impl Data {
             ChunkedReader(_, n) => ChunkedReader(inner_data, n)
         };
 
-        Ok(Data::new(http_stream))
+        let mut data = Data::new(http_stream);
+        data.deadline = deadline;
+        Ok(data)
     }
 
     /// Retrieve the `peek` buffer.
@@ -257,6 +263,8 @@ impl Data {
             buffer: peek_buf,
             stream: stream,
             is_complete: eof,
+            checksum: None,
+            deadline: None,
         }
     }
 
@@ -269,8 +277,38 @@ impl Data {
             buffer: data,
             stream: HttpReader::SizedReader(empty_stream, 0),
             is_complete: true,
+            checksum: None,
+            deadline: None,
         }
     }
+
+    /// This creates a `data` object that streams its contents from the local
+    /// data source `reader` until it reaches EOF.
+    #[inline]
+    pub(crate) fn local_stream<R: Read + Send + 'static>(reader: R) -> Data {
+        let local: Box<Read + Send> = Box::new(reader);
+        let stream = Cursor::new(vec![]).chain(NetStream::Local(Arc::new(Mutex::new(local))));
+        Data::new(HttpReader::EofReader(stream))
+    }
+
+    /// This creates a `data` object whose contents, `data` split into chunks
+    /// of the sizes in `chunks`, are read with `chunked` transfer encoding.
+    pub(crate) fn local_chunked(data: &[u8], chunks: &[usize]) -> Data {
+        let mut encoded = vec![];
+        let mut rest = data;
+        for &size in chunks {
+            let (chunk, remaining) = rest.split_at(size);
+            encoded.extend_from_slice(format!("{:x}\r\n", size).as_bytes());
+            encoded.extend_from_slice(chunk);
+            encoded.extend_from_slice(b"\r\n");
+            rest = remaining;
+        }
+
+        encoded.extend_from_slice(b"0\r\n\r\n");
+        let local: Box<Read + Send> = Box::new(Cursor::new(encoded));
+        let stream = Cursor::new(vec![]).chain(NetStream::Local(Arc::new(Mutex::new(local))));
+        Data::new(HttpReader::ChunkedReader(stream, None))
+    }
 }
 
 impl Drop for Data {
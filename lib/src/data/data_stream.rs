@@ -1,7 +1,9 @@
 use std::io::{self, Read, Cursor, Chain};
 use std::net::Shutdown;
+use std::time::{Duration, Instant};
 
 use super::data::BodyReader;
+use super::checksum::{Checksum, Verifier};
 use http::hyper::net::NetworkStream;
 use http::hyper::h1::HttpReader;
 
@@ -12,9 +14,89 @@ pub type InnerStream = Chain<Cursor<Vec<u8>>, BodyReader>;
 ///
 /// This stream can only be obtained by calling
 /// [Data::open](/rocket/data/struct.Data.html#method.open). The stream contains
-/// all of the data in the body of the request. It exposes no methods directly.
-/// Instead, it must be used as an opaque `Read` structure.
-pub struct DataStream(pub(crate) InnerStream);
+/// all of the data in the body of the request. It must be used as an opaque
+/// `Read` structure.
+///
+/// # Checksums
+///
+/// If checksum verification is enabled and the request carried a checksum in
+/// a `Content-MD5` or `Digest` header, the data is hashed as it is read. When
+/// the end of the stream is reached and the checksum doesn't match, the final
+/// read returns an error of kind `InvalidData`, and the request is failed with
+/// a `422 Unprocessable Entity`. The computed checksum is available via
+/// [`checksum()`](#method.checksum) once the stream has been exhausted.
+///
+/// Verification only covers bodies that are read to completion. A body that
+/// is read partially, for instance, because a handler stops at a size limit,
+/// is never verified, and its request isn't failed. Because the body is
+/// verified as it streams, the `422` response replaces the handler's response
+/// _after_ the handler has run: handlers with side effects should read the
+/// body to the end, and check that the read succeeded, before acting on it.
+///
+/// # Timeouts
+///
+/// Reads from the network are bounded by the read and body timeouts in the
+/// configured [`Limits`](/rocket/config/struct.Limits.html). Once the body
+/// timeout has passed, reads fail with an error of kind `TimedOut`.
+pub struct DataStream(
+    pub(crate) InnerStream,
+    pub(crate) Option<Verifier>,
+    pub(crate) Option<Deadline>,
+);
+
+/// The time by which a request body must have been read.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Deadline {
+    at: Instant,
+    read_timeout: Duration,
+}
+
+impl Deadline {
+    #[inline]
+    pub(crate) fn new(at: Instant, read_timeout: Duration) -> Deadline {
+        Deadline { at: at, read_timeout: read_timeout }
+    }
+
+    /// Bounds the next read from the network of `stream` by the time left
+    /// until the deadline, failing if there is none left.
+    fn arm(&self, stream: &InnerStream) -> io::Result<()> {
+        let now = Instant::now();
+        if now >= self.at {
+            let msg = "request body was not received before the body timeout";
+            return Err(io::Error::new(io::ErrorKind::TimedOut, msg));
+        }
+
+        let timeout = ::std::cmp::min(self.at - now, self.read_timeout);
+        let (_, network) = stream.get_ref().1.get_ref().get_ref();
+        network.set_read_timeout(Some(timeout))
+    }
+}
+
+impl DataStream {
+    /// Returns the checksum computed for the request body if the body was
+    /// verified and the stream has been read to completion. Otherwise, returns
+    /// `None`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::io;
+    /// use rocket::Data;
+    ///
+    /// fn handler(data: Data) -> io::Result<String> {
+    ///     let mut stream = data.open();
+    ///     io::copy(&mut stream, &mut io::sink())?;
+    ///     Ok(match stream.checksum() {
+    ///         Some(checksum) => format!("verified {}", checksum),
+    ///         None => "unverified".into()
+    ///     })
+    /// }
+    /// ```
+    #[inline]
+    pub fn checksum(&self) -> Option<Checksum> {
+        self.1.as_ref().and_then(|verifier| verifier.checksum())
+    }
+}
 
 // TODO: Have a `BufRead` impl for `DataStream`. At the moment, this isn't
 // possible since Hyper's `HttpReader` doesn't implement `BufRead`.
@@ -22,7 +104,21 @@ impl Read for DataStream {
     #[inline(always)]
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         trace_!("DataStream::read()");
-        self.0.read(buf)
+        if let Some(ref deadline) = self.2 {
+            deadline.arm(&self.0)?;
+        }
+
+        let n = self.0.read(buf)?;
+        if let Some(ref mut verifier) = self.1 {
+            if n > 0 {
+                verifier.update(&buf[..n]);
+            } else if !buf.is_empty() && !verifier.finish() {
+                let msg = "request body does not match its checksum";
+                return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+            }
+        }
+
+        Ok(n)
     }
 }
 
@@ -0,0 +1,147 @@
+use std::fmt;
+use std::error::Error;
+use std::str::Utf8Error;
+
+use request::Request;
+use http::Status;
+use http::uncased::uncased_eq;
+
+/// How Rocket's text data guards decode request bodies that aren't valid
+/// UTF-8, as configured via the `text_encoding` configuration parameter.
+///
+/// When either is configured, the `charset` parameter of the request's
+/// `Content-Type` is honored as well: bodies in a charset other than UTF-8 or
+/// US-ASCII, its subset, are rejected with `415 Unsupported Media Type`
+/// instead of being decoded as UTF-8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    /// Bodies that aren't valid UTF-8 are rejected with `400 Bad Request`.
+    Strict,
+    /// Invalid UTF-8 sequences are replaced with `U+FFFD REPLACEMENT
+    /// CHARACTER`.
+    Lossy,
+}
+
+impl TextEncoding {
+    /// Returns the name of the mode as it appears in configuration.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::data::TextEncoding;
+    ///
+    /// assert_eq!(TextEncoding::Strict.name(), "strict");
+    /// assert_eq!(TextEncoding::Lossy.name(), "lossy");
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match *self {
+            TextEncoding::Strict => "strict",
+            TextEncoding::Lossy => "lossy",
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Option<TextEncoding> {
+        if uncased_eq(name, "strict") {
+            Some(TextEncoding::Strict)
+        } else if uncased_eq(name, "lossy") {
+            Some(TextEncoding::Lossy)
+        } else {
+            None
+        }
+    }
+}
+
+/// The error returned by [`decode_text()`] when a request body can't be
+/// decoded as text.
+///
+/// [`decode_text()`]: /rocket/data/fn.decode_text.html
+#[derive(Debug, Clone, PartialEq)]
+pub enum EncodingError {
+    /// The `charset` parameter of the request's `Content-Type` names a
+    /// charset other than UTF-8 or US-ASCII.
+    Charset(String),
+    /// The body isn't valid UTF-8.
+    Utf8(Utf8Error),
+}
+
+impl EncodingError {
+    /// Returns the status the request should fail with: `415 Unsupported
+    /// Media Type` for an unsupported charset and `400 Bad Request` for
+    /// invalid UTF-8.
+    pub fn status(&self) -> Status {
+        match *self {
+            EncodingError::Charset(_) => Status::UnsupportedMediaType,
+            EncodingError::Utf8(_) => Status::BadRequest,
+        }
+    }
+}
+
+impl fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EncodingError::Charset(ref charset) => write!(f, "unsupported charset: {}", charset),
+            EncodingError::Utf8(ref e) => write!(f, "body is not valid UTF-8: {}", e),
+        }
+    }
+}
+
+impl Error for EncodingError {
+    fn description(&self) -> &str {
+        match *self {
+            EncodingError::Charset(_) => "unsupported charset",
+            EncodingError::Utf8(_) => "body is not valid UTF-8",
+        }
+    }
+}
+
+/// Decodes `bytes`, the body of `request`, as UTF-8 text according to the
+/// `text_encoding` configuration parameter. This is how the `String` and
+/// `Form` data guards decode bodies; custom data guards that read text should
+/// do the same.
+///
+/// If `text_encoding` isn't set, the default, the body must be valid UTF-8
+/// and its `charset` is ignored. Otherwise, a `charset` other than UTF-8 or
+/// US-ASCII is an error, and invalid UTF-8 is an error or is replaced as
+/// configured. See [`TextEncoding`] for details.
+///
+/// [`TextEncoding`]: /rocket/data/enum.TextEncoding.html
+///
+/// # Example
+///
+/// ```rust
+/// # use rocket::Request;
+/// # use rocket::http::Method;
+/// use rocket::data::decode_text;
+///
+/// # Request::example(Method::Post, "/", |request| {
+/// assert_eq!(decode_text(request, b"hello".to_vec()).unwrap(), "hello");
+/// assert!(decode_text(request, vec![0xc3, 0x28]).is_err());
+/// # });
+/// ```
+pub fn decode_text(request: &Request, bytes: Vec<u8>) -> Result<String, EncodingError> {
+    let encoding = request.config().text_encoding;
+    if encoding.is_some() {
+        let charset = request.content_type()
+            .and_then(|ct| ct.params().find(|&(key, _)| uncased_eq(key, "charset")))
+            .map(|(_, charset)| charset.trim_matches('"'));
+
+        match charset {
+            Some(charset) if !is_utf8_compatible(charset) => {
+                return Err(EncodingError::Charset(charset.to_string()));
+            }
+            _ => {}
+        }
+    }
+
+    match String::from_utf8(bytes) {
+        Ok(string) => Ok(string),
+        Err(e) => match encoding {
+            Some(TextEncoding::Lossy) => Ok(String::from_utf8_lossy(e.as_bytes()).into_owned()),
+            _ => Err(EncodingError::Utf8(e.utf8_error()))
+        }
+    }
+}
+
+fn is_utf8_compatible(charset: &str) -> bool {
+    ["utf-8", "utf8", "us-ascii", "ascii"].iter().any(|name| uncased_eq(name, charset))
+}
@@ -4,7 +4,7 @@ use outcome::{self, IntoOutcome};
 use outcome::Outcome::*;
 use http::Status;
 use request::Request;
-use data::Data;
+use data::{Data, decode_text};
 
 /// Type alias for the `Outcome` of a `FromData` conversion.
 pub type Outcome<S, E> = outcome::Outcome<S, (Status, E), Data>;
@@ -255,14 +255,37 @@ impl<T: FromData> FromData for Option<T> {
     }
 }
 
+// Reads all of `data` into memory, accounting for it against the request's
+// `memory` limit. Fails with `413 Payload Too Large` if the limit is exceeded.
+fn read_to_memory(request: &Request, data: Data) -> Result<Vec<u8>, (Status, io::Error)> {
+    let mut bytes = Vec::new();
+    let result = match request.memory_remaining() {
+        Some(remaining) => data.open().take(remaining.saturating_add(1)).read_to_end(&mut bytes),
+        None => data.open().read_to_end(&mut bytes)
+    };
+
+    result.map_err(|e| (Status::BadRequest, e))?;
+    request.reserve_memory(bytes.len() as u64)
+        .map_err(|e| (Status::PayloadTooLarge, io::Error::new(io::ErrorKind::Other, e.to_string())))?;
+
+    Ok(bytes)
+}
+
 impl FromData for String {
     type Error = io::Error;
 
-    fn from_data(_: &Request, data: Data) -> Outcome<Self, Self::Error> {
-        let mut string = String::new();
-        match data.open().read_to_string(&mut string) {
-            Ok(_) => Success(string),
-            Err(e) => Failure((Status::BadRequest, e))
+    fn from_data(request: &Request, data: Data) -> Outcome<Self, Self::Error> {
+        let bytes = match read_to_memory(request, data) {
+            Ok(bytes) => bytes,
+            Err(e) => return Failure(e)
+        };
+
+        match decode_text(request, bytes) {
+            Ok(string) => Success(string),
+            Err(e) => {
+                error_!("Couldn't decode the request body: {}.", e);
+                Failure((e.status(), io::Error::new(io::ErrorKind::InvalidData, e)))
+            }
         }
     }
 }
@@ -270,11 +293,10 @@ impl FromData for String {
 impl FromData for Vec<u8> {
     type Error = io::Error;
 
-    fn from_data(_: &Request, data: Data) -> Outcome<Self, Self::Error> {
-        let mut bytes = Vec::new();
-        match data.open().read_to_end(&mut bytes) {
-            Ok(_) => Success(bytes),
-            Err(e) => Failure((Status::BadRequest, e))
+    fn from_data(request: &Request, data: Data) -> Outcome<Self, Self::Error> {
+        match read_to_memory(request, data) {
+            Ok(bytes) => Success(bytes),
+            Err(e) => Failure(e)
         }
     }
 }
@@ -4,7 +4,13 @@ mod data;
 mod data_stream;
 mod net_stream;
 mod from_data;
+mod checksum;
+mod encoding;
 
 pub use self::data::Data;
 pub use self::data_stream::DataStream;
 pub use self::from_data::{FromData, Outcome};
+pub use self::checksum::{Checksum, ChecksumAlgorithm};
+pub use self::encoding::{TextEncoding, EncodingError, decode_text};
+pub(crate) use self::net_stream::NetStream;
+#[cfg(feature = "tls")] pub(crate) use self::net_stream::HttpsStream;
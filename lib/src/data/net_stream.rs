@@ -1,9 +1,12 @@
 use std::io;
 use std::net::{SocketAddr, Shutdown};
 use std::time::Duration;
+use std::sync::{Arc, Mutex};
 
 #[cfg(feature = "tls")] use hyper_sync_rustls::{WrappedStream, ServerSession};
+#[cfg(feature = "tls")] use tls::{PeerCertificates, TlsInfo};
 use http::hyper::net::{HttpStream, NetworkStream};
+use listener::{Limited, Rearm};
 
 use self::NetStream::*;
 
@@ -16,9 +19,93 @@ pub enum NetStream {
     Http(HttpStream),
     #[cfg(feature = "tls")]
     Https(HttpsStream),
+    Local(Arc<Mutex<Box<io::Read + Send>>>),
     Empty,
 }
 
+impl NetStream {
+    // FIXME: This is absolutely terrible (downcasting!), thanks to Hyper.
+    #[cfg(feature = "tls")]
+    pub(crate) fn from_hyp(stream: &NetworkStream) -> Option<NetStream> {
+        stream.downcast_ref::<Limited<HttpsStream>>()
+            .map(|s| Https(s.inner.clone()))
+            .or_else(|| stream.downcast_ref::<Limited<HttpStream>>().map(|s| Http(s.inner.clone())))
+            .or_else(|| stream.downcast_ref::<HttpsStream>().map(|s| Https(s.clone())))
+            .or_else(|| stream.downcast_ref::<HttpStream>().map(|s| Http(s.clone())))
+    }
+
+    #[cfg(not(feature = "tls"))]
+    pub(crate) fn from_hyp(stream: &NetworkStream) -> Option<NetStream> {
+        stream.downcast_ref::<Limited<HttpStream>>()
+            .map(|s| Http(s.inner.clone()))
+            .or_else(|| stream.downcast_ref::<HttpStream>().map(|s| Http(s.clone())))
+    }
+}
+
+impl NetStream {
+    /// Returns a handle that rearms the request head limits of the Hyper
+    /// stream `stream` when dropped, if it's a connection accepted by a
+    /// `Listener`.
+    pub(crate) fn rearm_head(stream: &NetworkStream) -> Option<Rearm> {
+        #[cfg(feature = "tls")] {
+            if let Some(limited) = stream.downcast_ref::<Limited<HttpsStream>>() {
+                return Some(limited.rearm_head());
+            }
+        }
+
+        stream.downcast_ref::<Limited<HttpStream>>().map(|limited| limited.rearm_head())
+    }
+
+    /// Returns the certificate chain the peer on the Hyper stream `stream`
+    /// presented during the TLS handshake, if it's a TLS stream and the peer
+    /// presented one. The chain is read from the TLS session by the first
+    /// request on a connection and shared by the rest.
+    #[cfg(feature = "tls")]
+    pub(crate) fn peer_certificates(stream: &NetworkStream) -> Option<Arc<PeerCertificates>> {
+        fn read(stream: &HttpsStream) -> Option<Arc<PeerCertificates>> {
+            use rustls::Session;
+
+            let chain = stream.to_tls_stream().get_session().get_peer_certificates()?;
+            Some(Arc::new(PeerCertificates::new(chain)))
+        }
+
+        match stream.downcast_ref::<Limited<HttpsStream>>() {
+            Some(limited) => limited.peer.get_or_set(|| read(&limited.inner)).clone(),
+            None => stream.downcast_ref::<HttpsStream>().and_then(read)
+        }
+    }
+
+    /// Returns the parameters the TLS handshake on the Hyper stream `stream`
+    /// negotiated, if it's a TLS stream. Like the peer's certificates, they're
+    /// read by the first request on a connection and shared by the rest.
+    #[cfg(feature = "tls")]
+    pub(crate) fn tls_info(stream: &NetworkStream) -> Option<Arc<TlsInfo>> {
+        fn read(stream: &HttpsStream) -> Option<Arc<TlsInfo>> {
+            TlsInfo::read(stream.to_tls_stream().get_session()).map(Arc::new)
+        }
+
+        match stream.downcast_ref::<Limited<HttpsStream>>() {
+            Some(limited) => limited.session.get_or_set(|| read(&limited.inner)).clone(),
+            None => stream.downcast_ref::<HttpsStream>().and_then(read)
+        }
+    }
+
+    /// Returns the protocol the peer on the Hyper stream `stream` selected
+    /// via ALPN, if it's a TLS stream and the peer selected one.
+    #[cfg(feature = "tls")]
+    pub(crate) fn alpn_protocol(stream: &NetworkStream) -> Option<String> {
+        use rustls::Session;
+
+        let stream = stream.downcast_ref::<Limited<HttpsStream>>()
+            .map(|limited| &limited.inner)
+            .or_else(|| stream.downcast_ref::<HttpsStream>())?;
+
+        let session = stream.to_tls_stream();
+        let protocol = session.get_session().get_alpn_protocol();
+        protocol.map(|protocol| protocol.to_string())
+    }
+}
+
 impl io::Read for NetStream {
     #[inline(always)]
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
@@ -26,6 +113,7 @@ impl io::Read for NetStream {
         let res = match *self {
             Http(ref mut stream) => stream.read(buf),
             #[cfg(feature = "tls")] Https(ref mut stream) => stream.read(buf),
+            Local(ref stream) => stream.lock().expect("local stream lock").read(buf),
             Empty => Ok(0),
         };
 
@@ -41,7 +129,7 @@ impl io::Write for NetStream {
         match *self {
             Http(ref mut stream) => stream.write(buf),
             #[cfg(feature = "tls")] Https(ref mut stream) => stream.write(buf),
-            Empty => Ok(0),
+            Local(_) | Empty => Ok(0),
         }
     }
 
@@ -50,7 +138,7 @@ impl io::Write for NetStream {
         match *self {
             Http(ref mut stream) => stream.flush(),
             #[cfg(feature = "tls")] Https(ref mut stream) => stream.flush(),
-            Empty => Ok(()),
+            Local(_) | Empty => Ok(()),
         }
     }
 }
@@ -61,7 +149,7 @@ impl NetworkStream for NetStream {
         match *self {
             Http(ref mut stream) => stream.peer_addr(),
             #[cfg(feature = "tls")] Https(ref mut stream) => stream.peer_addr(),
-            Empty => Err(io::Error::from(io::ErrorKind::AddrNotAvailable)),
+            Local(_) | Empty => Err(io::Error::from(io::ErrorKind::AddrNotAvailable)),
         }
     }
 
@@ -70,7 +158,7 @@ impl NetworkStream for NetStream {
         match *self {
             Http(ref stream) => stream.set_read_timeout(dur),
             #[cfg(feature = "tls")] Https(ref stream) => stream.set_read_timeout(dur),
-            Empty => Ok(()),
+            Local(_) | Empty => Ok(()),
         }
     }
 
@@ -79,7 +167,7 @@ impl NetworkStream for NetStream {
         match *self {
             Http(ref stream) => stream.set_write_timeout(dur),
             #[cfg(feature = "tls")] Https(ref stream) => stream.set_write_timeout(dur),
-            Empty => Ok(()),
+            Local(_) | Empty => Ok(()),
         }
     }
 
@@ -88,7 +176,7 @@ impl NetworkStream for NetStream {
         match *self {
             Http(ref mut stream) => stream.close(how),
             #[cfg(feature = "tls")] Https(ref mut stream) => stream.close(how),
-            Empty => Ok(()),
+            Local(_) | Empty => Ok(()),
         }
     }
 }
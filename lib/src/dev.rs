@@ -0,0 +1,508 @@
+//! A watch-and-reload mode for development.
+//!
+//! The [`DevReload`] fairing watches directories of assets and templates for
+//! changes while an application is being developed. When a file in one of the
+//! directories is added, modified, or removed, the fairing notifies the
+//! [`ReloadHandle`] it manages. Caches keyed to those files observe the
+//! handle's [generation] and clear themselves on the next request: the
+//! template context of `rocket_contrib` is reloaded and its response cache is
+//! emptied. Applications register their own callbacks with
+//! [`DevReload::on_change()`].
+//!
+//! By default, the `static` and `templates` directories, relative to the
+//! configuration file, are watched, along with the directory in the
+//! `template_dir` configuration parameter, if it is set. Directories that
+//! don't exist are ignored until they do.
+//!
+//! # Live Reload
+//!
+//! Unless disabled with [`DevReload::live_reload()`], the fairing mounts a
+//! server-sent events endpoint at `/rocket/live-reload` and injects a small
+//! script into every HTML response with a sized body. The script reloads the
+//! page when a watched file changes and when the application is restarted.
+//! Each open page holds a connection, and so a worker, for as long as it is
+//! open: configure enough workers for the pages you keep open.
+//!
+//! # Activation
+//!
+//! The fairing only activates in the `development` environment of debug
+//! builds. Otherwise, attaching it does nothing: it's safe to leave attached.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rocket::dev::DevReload;
+//!
+//! # if false {
+//! rocket::ignite()
+//!     .attach(DevReload::new().watch("assets"))
+//!     .launch();
+//! # }
+//! ```
+//!
+//! [`DevReload`]: /rocket/dev/struct.DevReload.html
+//! [`ReloadHandle`]: /rocket/dev/struct.ReloadHandle.html
+//! [generation]: /rocket/dev/struct.ReloadHandle.html#method.generation
+//! [`DevReload::on_change()`]: /rocket/dev/struct.DevReload.html#method.on_change
+//! [`DevReload::live_reload()`]: /rocket/dev/struct.DevReload.html#method.live_reload
+
+use std::fs;
+use std::io::{self, Read, Cursor};
+use std::path::{Path, PathBuf};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Condvar};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::thread;
+
+use {Rocket, Request, Response, Data, Route, State};
+use config::{Config, ConfigError};
+use fairing::{Fairing, Info, Kind};
+use handler::Outcome;
+use http::{Method, ContentType};
+use response::Body;
+
+/// The path of the live reload events endpoint.
+const LIVE_RELOAD_PATH: &'static str = "/rocket/live-reload";
+
+/// The name given to the live reload route.
+const LIVE_RELOAD_ROUTE_NAME: &'static str = "rocket::dev";
+
+/// How often an idle events stream sends a comment to detect disconnects.
+const KEEP_ALIVE_SECS: u64 = 15;
+
+/// The directories watched by default, relative to the configuration file.
+const DEFAULT_DIRS: &'static [&'static str] = &["static", "templates"];
+
+type Callback = Box<Fn(&[PathBuf]) + Send + Sync + 'static>;
+
+struct Inner {
+    /// Identifies this run of the application to live reload scripts.
+    id: u64,
+    generation: Mutex<u64>,
+    changed: Condvar,
+    callbacks: Mutex<Vec<Callback>>,
+}
+
+/// A handle to the changes observed by a [`DevReload`] fairing.
+///
+/// The handle is managed by the fairing when it activates; retrieve it with
+/// `State<ReloadHandle>`, or with `rocket.state::<ReloadHandle>()`. Its
+/// absence means that the fairing isn't active. Clones of a handle refer to
+/// the same fairing.
+///
+/// The handle's [`generation()`] starts at `0` and increases whenever a change
+/// is observed. A cache records the generation its contents were built at and
+/// rebuilds them when it differs from the current one.
+///
+/// [`DevReload`]: /rocket/dev/struct.DevReload.html
+/// [`generation()`]: /rocket/dev/struct.ReloadHandle.html#method.generation
+#[derive(Clone)]
+pub struct ReloadHandle {
+    inner: Arc<Inner>,
+}
+
+impl ReloadHandle {
+    fn new() -> ReloadHandle {
+        let id = SystemTime::now().duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() * 1_000_000_000 + d.subsec_nanos() as u64)
+            .unwrap_or(0);
+
+        ReloadHandle {
+            inner: Arc::new(Inner {
+                id: id,
+                generation: Mutex::new(0),
+                changed: Condvar::new(),
+                callbacks: Mutex::new(vec![]),
+            })
+        }
+    }
+
+    /// Returns the number of changes observed so far.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::dev::DevReload;
+    ///
+    /// let handle = DevReload::new().handle();
+    /// assert_eq!(handle.generation(), 0);
+    ///
+    /// handle.notify(&[]);
+    /// assert_eq!(handle.generation(), 1);
+    /// ```
+    pub fn generation(&self) -> u64 {
+        *self.inner.generation.lock().expect("reload generation lock")
+    }
+
+    /// Registers `f` to be called with the changed paths whenever a change is
+    /// observed. Callbacks are called on the watcher's thread, in the order
+    /// they were registered.
+    pub fn on_change<F>(&self, f: F)
+        where F: Fn(&[PathBuf]) + Send + Sync + 'static
+    {
+        self.inner.callbacks.lock().expect("reload callbacks lock").push(Box::new(f));
+    }
+
+    /// Records a change to `paths`, as if the watcher had observed it: the
+    /// generation is increased, callbacks are called, and open pages are
+    /// reloaded.
+    pub fn notify(&self, paths: &[PathBuf]) {
+        {
+            let mut generation = self.inner.generation.lock().expect("reload generation lock");
+            *generation += 1;
+            self.inner.changed.notify_all();
+        }
+
+        for callback in self.inner.callbacks.lock().expect("reload callbacks lock").iter() {
+            callback(paths);
+        }
+    }
+
+    /// Waits at most `timeout` for the generation to differ from `seen`, then
+    /// returns the current generation.
+    fn wait(&self, seen: u64, timeout: Duration) -> u64 {
+        let generation = self.inner.generation.lock().expect("reload generation lock");
+        if *generation != seen {
+            return *generation;
+        }
+
+        let (generation, _) = self.inner.changed.wait_timeout(generation, timeout)
+            .expect("reload generation lock");
+
+        *generation
+    }
+}
+
+/// A fairing that watches asset and template directories during development.
+///
+/// See the [module level documentation](/rocket/dev/index.html) for details.
+pub struct DevReload {
+    dirs: Vec<PathBuf>,
+    interval: Duration,
+    live_reload: bool,
+    handle: ReloadHandle,
+}
+
+impl DevReload {
+    /// Returns a fairing that watches the default directories every 500
+    /// milliseconds with live reload enabled.
+    #[inline]
+    pub fn new() -> DevReload {
+        DevReload {
+            dirs: vec![],
+            interval: Duration::from_millis(500),
+            live_reload: true,
+            handle: ReloadHandle::new(),
+        }
+    }
+
+    /// Additionally watches `dir`. A relative `dir` is relative to the
+    /// configuration file.
+    pub fn watch<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.dirs.push(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets how often the directories are checked for changes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is zero.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        assert!(interval > Duration::from_secs(0), "watch interval must be non-zero");
+        self.interval = interval;
+        self
+    }
+
+    /// Sets whether the live reload endpoint is mounted and its script
+    /// injected into HTML responses.
+    pub fn live_reload(mut self, enabled: bool) -> Self {
+        self.live_reload = enabled;
+        self
+    }
+
+    /// Registers `f` to be called with the changed paths whenever a change is
+    /// observed. See [`ReloadHandle::on_change()`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::dev::DevReload;
+    ///
+    /// let fairing = DevReload::new().on_change(|paths| {
+    ///     println!("{} files changed", paths.len());
+    /// });
+    /// ```
+    ///
+    /// [`ReloadHandle::on_change()`]: /rocket/dev/struct.ReloadHandle.html#method.on_change
+    pub fn on_change<F>(self, f: F) -> Self
+        where F: Fn(&[PathBuf]) + Send + Sync + 'static
+    {
+        self.handle.on_change(f);
+        self
+    }
+
+    /// Returns a handle to the changes this fairing observes. The same handle
+    /// is managed by Rocket when the fairing activates.
+    #[inline]
+    pub fn handle(&self) -> ReloadHandle {
+        self.handle.clone()
+    }
+
+    /// Whether the fairing activates with `config`.
+    fn is_active(config: &Config) -> bool {
+        cfg!(debug_assertions) && config.environment.is_dev()
+    }
+
+    /// Returns the watched directories, resolved against `config`.
+    fn directories(&self, config: &Config) -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = DEFAULT_DIRS.iter()
+            .map(|dir| config.root_relative(dir))
+            .collect();
+
+        dirs.extend(self.dirs.iter().map(|dir| config.root_relative(dir)));
+
+        match config.get_str("template_dir") {
+            Ok(dir) => dirs.push(config.root_relative(dir)),
+            Err(ConfigError::NotFound) => { /* the default is already watched */ }
+            Err(e) => warn_!("Not watching `template_dir`: {}", e),
+        }
+
+        dirs.sort();
+        dirs.dedup();
+        dirs
+    }
+
+    fn events<'r>(request: &'r Request, data: Data) -> Outcome<'r> {
+        let handle = match request.guard::<State<ReloadHandle>>() {
+            ::outcome::Outcome::Success(handle) => handle.inner().clone(),
+            _ => return Outcome::forward(data)
+        };
+
+        let hello = format!("event: hello\ndata: {}\n\n", handle.inner.id);
+        let seen = handle.generation();
+        let events = Events { handle: handle, seen: seen, pending: Cursor::new(hello.into_bytes()) };
+        let mut response = Response::build()
+            .header(ContentType::new("text", "event-stream"))
+            .raw_header("Cache-Control", "no-cache")
+            .chunked_body(events, 1024)
+            .finalize();
+
+        response.set_flush_chunks(true);
+        Outcome::Success(response)
+    }
+}
+
+/// The modification times of the files in the watched directories.
+type Snapshot = HashMap<PathBuf, SystemTime>;
+
+// Records the files in `dir`, recursively, ignoring those that can't be read.
+fn scan(dir: &Path, snapshot: &mut Snapshot) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return
+    };
+
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        match entry.metadata() {
+            Ok(ref metadata) if metadata.is_dir() => scan(&path, snapshot),
+            Ok(metadata) => {
+                let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+                snapshot.insert(path, modified);
+            }
+            Err(_) => continue
+        }
+    }
+}
+
+fn snapshot(dirs: &[PathBuf]) -> Snapshot {
+    let mut snapshot = Snapshot::new();
+    for dir in dirs {
+        scan(dir, &mut snapshot);
+    }
+
+    snapshot
+}
+
+/// Returns the paths that were added, modified, or removed between `old` and
+/// `new`, sorted.
+fn changes(old: &Snapshot, new: &Snapshot) -> Vec<PathBuf> {
+    let mut changed: Vec<PathBuf> = new.iter()
+        .filter(|&(path, modified)| old.get(path) != Some(modified))
+        .map(|(path, _)| path.clone())
+        .chain(old.keys().filter(|path| !new.contains_key(*path)).cloned())
+        .collect();
+
+    changed.sort();
+    changed
+}
+
+/// Reads as a stream of server-sent events: a `hello` event with the run's
+/// identifier, then a `reload` event for every change.
+struct Events {
+    handle: ReloadHandle,
+    seen: u64,
+    pending: Cursor<Vec<u8>>,
+}
+
+impl Read for Events {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = self.pending.read(buf)?;
+            if n > 0 || buf.is_empty() {
+                return Ok(n);
+            }
+
+            let generation = self.handle.wait(self.seen, Duration::from_secs(KEEP_ALIVE_SECS));
+            let event = match generation != self.seen {
+                true => format!("event: reload\ndata: {}\n\n", generation),
+                false => ": keep-alive\n\n".to_string()
+            };
+
+            self.seen = generation;
+            self.pending = Cursor::new(event.into_bytes());
+        }
+    }
+}
+
+/// Returns the live reload script for HTML pages.
+fn script() -> String {
+    format!("<script>(function() {{\
+        var id = null, source = new EventSource(\"{}\");\
+        source.addEventListener(\"hello\", function(e) {{\
+            if (id !== null && id !== e.data) location.reload();\
+            id = e.data;\
+        }});\
+        source.addEventListener(\"reload\", function() {{ location.reload(); }});\
+    }})();</script>", LIVE_RELOAD_PATH)
+}
+
+/// Inserts `script` before the last `</body>` in `html`, or appends it if
+/// there is none.
+fn inject(html: &mut Vec<u8>, script: &str) {
+    let lowercase = html.to_ascii_lowercase();
+    let position = lowercase.windows(7).rposition(|window| window == b"</body>");
+    let at = position.unwrap_or(html.len());
+    let tail = html.split_off(at);
+    html.extend_from_slice(script.as_bytes());
+    html.extend(tail);
+}
+
+impl Fairing for DevReload {
+    fn info(&self) -> Info {
+        Info { name: "Development Reload", kind: Kind::Attach | Kind::Launch | Kind::Response }
+    }
+
+    fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        if !DevReload::is_active(rocket.config()) {
+            return Ok(rocket);
+        }
+
+        let rocket = rocket.manage(self.handle.clone());
+        if !self.live_reload {
+            return Ok(rocket);
+        }
+
+        let mut route = Route::new(Method::Get, LIVE_RELOAD_PATH, DevReload::events);
+        route.name = Some(LIVE_RELOAD_ROUTE_NAME);
+        Ok(rocket.mount("/", vec![route]))
+    }
+
+    fn on_launch(&self, rocket: &Rocket) {
+        if !DevReload::is_active(rocket.config()) {
+            return;
+        }
+
+        let dirs = self.directories(rocket.config());
+        let (handle, interval) = (self.handle.clone(), self.interval);
+        info!("Watching for changes in development:");
+        for dir in &dirs {
+            info_!("{}", dir.display());
+        }
+
+        let spawned = thread::Builder::new().name("rocket-dev-watch".into()).spawn(move || {
+            let mut current = snapshot(&dirs);
+            loop {
+                thread::sleep(interval);
+                let next = snapshot(&dirs);
+                let changed = changes(&current, &next);
+                if !changed.is_empty() {
+                    info!("Reloading: {} changed file(s).", changed.len());
+                    handle.notify(&changed);
+                }
+
+                current = next;
+            }
+        });
+
+        if let Err(e) = spawned {
+            error_!("Failed to start the development watcher: {}", e);
+        }
+    }
+
+    fn on_response(&self, request: &Request, response: &mut Response) {
+        if !self.live_reload || !DevReload::is_active(request.config()) {
+            return;
+        }
+
+        let is_html = response.content_type().map_or(false, |ct| ct.is_html());
+        if !is_html || response.headers().contains("Content-Encoding") {
+            return;
+        }
+
+        let mut html = vec![];
+        let result = match response.body() {
+            Some(Body::Sized(body, _)) => body.read_to_end(&mut html),
+            _ => return
+        };
+
+        if let Err(e) = result {
+            error_!("Failed to read an HTML body for live reload: {}", e);
+            return;
+        }
+
+        inject(&mut html, &script());
+        response.set_sized_body(Cursor::new(html));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{Snapshot, changes, inject};
+
+    #[test]
+    fn test_changes() {
+        let (a, b, c) = (PathBuf::from("/a"), PathBuf::from("/b"), PathBuf::from("/c"));
+        let then = UNIX_EPOCH + Duration::from_secs(1);
+        let now = UNIX_EPOCH + Duration::from_secs(2);
+
+        let mut old = Snapshot::new();
+        old.insert(a.clone(), then);
+        old.insert(b.clone(), then);
+
+        let mut new = Snapshot::new();
+        new.insert(a.clone(), then);
+        new.insert(c.clone(), now);
+
+        assert!(changes(&old, &old).is_empty());
+        assert_eq!(changes(&old, &new), vec![b.clone(), c.clone()]);
+
+        new.insert(a.clone(), now);
+        assert_eq!(changes(&old, &new), vec![a, b, c]);
+    }
+
+    #[test]
+    fn test_inject() {
+        let mut html = b"<html><BODY>hi</BODY></html>".to_vec();
+        inject(&mut html, "<s>");
+        assert_eq!(html, b"<html><BODY>hi<s></BODY></html>".to_vec());
+
+        let mut html = b"hi".to_vec();
+        inject(&mut html, "<s>");
+        assert_eq!(html, b"hi<s>".to_vec());
+    }
+}
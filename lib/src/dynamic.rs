@@ -0,0 +1,171 @@
+//! Adding and removing routes while Rocket is running.
+//!
+//! Routes are usually mounted before launch and fixed from then on.
+//! Applications that load plugins or let administrators define redirects can
+//! instead dedicate a mount point to _dynamic_ routes with
+//! [`Rocket::mount_dynamic()`]. Routes can be added to and removed from that
+//! mount point at any time, including after launch, via the [`DynamicRoutes`]
+//! handle returned by [`Rocket::dynamic_routes()`].
+//!
+//! Dynamic routes are a fallback: they are only tried, in order of rank, once
+//! every matching mounted route has forwarded the request. A dynamic route
+//! can't collide with another dynamic route, but it may collide with a mounted
+//! route, which then takes precedence. Changes are visible to the next request
+//! that is routed. A request that was routed to a dynamic route before it was
+//! removed keeps the route alive until the request has been handled.
+//!
+//! # Example
+//!
+//! Add, then remove, a redirect from `/go/old-home` to `/`:
+//!
+//! ```rust
+//! use rocket::{Request, Route, Data};
+//! use rocket::handler::Outcome;
+//! use rocket::http::Method;
+//! use rocket::response::Redirect;
+//!
+//! fn to_home<'r>(req: &'r Request, _: Data) -> Outcome<'r> {
+//!     Outcome::from(req, Redirect::to("/"))
+//! }
+//!
+//! let rocket = rocket::ignite().mount_dynamic("/go");
+//! let routes = rocket.dynamic_routes().expect("dynamic routes are enabled");
+//! routes.add(Route::new(Method::Get, "/old-home", to_home)).expect("no collision");
+//! assert_eq!(routes.routes().len(), 1);
+//!
+//! assert!(routes.remove(Method::Get, "/old-home").is_some());
+//! assert!(routes.routes().is_empty());
+//! ```
+//!
+//! [`Rocket::mount_dynamic()`]: /rocket/struct.Rocket.html#method.mount_dynamic
+//! [`Rocket::dynamic_routes()`]: /rocket/struct.Rocket.html#method.dynamic_routes
+//! [`DynamicRoutes`]: /rocket/dynamic/struct.DynamicRoutes.html
+
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use http::Method;
+use http::uri::Uri;
+use request::Request;
+use router::{Route, Collider};
+
+/// The error returned when a dynamic route can't be added.
+#[derive(Debug)]
+pub enum DynamicRouteError {
+    /// The route collides with the contained dynamic route.
+    Collision(Route),
+}
+
+impl fmt::Display for DynamicRouteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DynamicRouteError::Collision(ref route) => {
+                write!(f, "the route collides with the dynamic route {}", route)
+            }
+        }
+    }
+}
+
+struct Inner {
+    base: String,
+    /// The routes in use, sorted by rank.
+    active: RwLock<Vec<Arc<Route>>>,
+}
+
+/// A route that matches a request: a mounted route or a dynamic route.
+pub(crate) enum Matched<'a> {
+    Mounted(&'a Route),
+    Dynamic(Arc<Route>),
+}
+
+/// A handle to the dynamic routes of a Rocket instance.
+///
+/// A `DynamicRoutes` is retrieved via [`Rocket::dynamic_routes()`]. It is cheap
+/// to clone, and all clones modify the same set of routes. See the [module
+/// level documentation](/rocket/dynamic/index.html) for how dynamic routes are
+/// routed.
+///
+/// [`Rocket::dynamic_routes()`]: /rocket/struct.Rocket.html#method.dynamic_routes
+#[derive(Clone)]
+pub struct DynamicRoutes {
+    inner: Arc<Inner>,
+}
+
+impl DynamicRoutes {
+    #[inline]
+    pub(crate) fn new(base: &str) -> DynamicRoutes {
+        DynamicRoutes {
+            inner: Arc::new(Inner {
+                base: base.to_string(),
+                active: RwLock::new(vec![]),
+            })
+        }
+    }
+
+    /// Returns the mount point of the dynamic routes.
+    #[inline(always)]
+    pub fn base(&self) -> &str {
+        &self.inner.base
+    }
+
+    /// Returns the full URI of a route with URI `uri` mounted at the base.
+    fn mounted_uri(&self, uri: &str) -> String {
+        Uri::new(format!("{}/{}", self.inner.base, uri)).to_string()
+    }
+
+    /// Mounts `route` at the base and adds it to the dynamic routes. The route
+    /// serves requests as soon as this method returns.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Collision` error, and doesn't add the route, if the route
+    /// collides with a dynamic route that was already added.
+    pub fn add(&self, mut route: Route) -> Result<(), DynamicRouteError> {
+        let uri = self.mounted_uri(&route.uri.to_string());
+        route.set_base(&self.inner.base);
+        route.set_uri(uri);
+
+        let mut active = self.inner.active.write().expect("dynamic routes lock");
+        if let Some(existing) = active.iter().find(|r| r.collides_with(&route)) {
+            return Err(DynamicRouteError::Collision((**existing).clone()));
+        }
+
+        info!("Adding dynamic route: {}", route);
+        let i = active.iter().position(|r| r.rank > route.rank).unwrap_or(active.len());
+        active.insert(i, Arc::new(route));
+        Ok(())
+    }
+
+    /// Removes the dynamic route with method `method` and URI `uri`, the URI
+    /// it was added with, returning a copy of it. Returns `None` if there is no
+    /// such route.
+    pub fn remove(&self, method: Method, uri: &str) -> Option<Route> {
+        let uri = self.mounted_uri(uri);
+        let mut active = self.inner.active.write().expect("dynamic routes lock");
+        let i = active.iter().position(|r| r.method == method && r.uri.to_string() == uri)?;
+        let route = (*active.remove(i)).clone();
+        info!("Removed dynamic route: {}", route);
+        Some(route)
+    }
+
+    /// Returns a copy of each dynamic route, in order of rank.
+    pub fn routes(&self) -> Vec<Route> {
+        let active = self.inner.active.read().expect("dynamic routes lock");
+        active.iter().map(|route| (**route).clone()).collect()
+    }
+
+    /// Returns the dynamic routes that match `request`, in order of rank.
+    pub(crate) fn route(&self, request: &Request) -> Vec<Arc<Route>> {
+        let active = self.inner.active.read().expect("dynamic routes lock");
+        active.iter().filter(|route| route.collides_with(request)).cloned().collect()
+    }
+}
+
+impl fmt::Debug for DynamicRoutes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DynamicRoutes")
+            .field("base", &self.inner.base)
+            .field("routes", &self.routes())
+            .finish()
+    }
+}
@@ -0,0 +1,394 @@
+//! Application-layer encryption of request and response bodies.
+//!
+//! This module is only available when the `envelope` feature is enabled.
+//!
+//! For deployments where bodies must stay confidential beyond the TLS
+//! connection, for instance across TLS-terminating proxies, Rocket can seal
+//! bodies in an _envelope_: AES-256-GCM encryption with a key specific to each
+//! client. Envelope handling is enabled via [`Rocket::envelope()`] with a
+//! [`KeyProvider`] that resolves the key of the client making each request:
+//! [`ClientCertificateKeys`] derives it from the certificate the client
+//! authenticated its TLS connection with, while [`KeyRing`] selects one of a
+//! fixed set of keys by an identifier the client sends.
+//!
+//! A request whose body is sealed carries a `Content-Encoding: aes256gcm`
+//! header; its `Content-Type` is that of the plaintext. A body compressed
+//! before it was sealed lists both codings in the order they were applied, as
+//! in `Content-Encoding: gzip, aes256gcm`. Before routing, Rocket opens the
+//! body and removes the `aes256gcm` coding, so handlers and data guards see
+//! the plaintext body as they would any other, decompressed as usual if
+//! [compression] is configured. A body that can't be opened, or that was
+//! encoded further after it was sealed, is rejected with `400 Bad Request`,
+//! and a sealed body larger than the `envelope` limit, 1MiB by default, with
+//! `413 Payload Too Large`.
+//!
+//! The body of the response to a request for which a key was resolved is
+//! sealed with the same key after all response fairings have run and after
+//! it's compressed, and `aes256gcm` is appended to the response's
+//! `Content-Encoding`: a compressed response carries `Content-Encoding: gzip,
+//! aes256gcm`, an uncompressed one `Content-Encoding: aes256gcm`. If
+//! envelopes are [`required`], requests for which no key is resolved are
+//! rejected with `401 Unauthorized` and never reach a handler.
+//!
+//! A sealed body is a random 12-byte nonce followed by the ciphertext and the
+//! 16-byte authentication tag. The associated data binds each body to its
+//! direction and to the request: it is `request` or `response`, a space, the
+//! request's method, a space, and the request's URI, as in `request POST
+//! /transfer`. A sealed body can thus not be replayed to another endpoint or
+//! passed off as a response.
+//!
+//! Envelopes are handled by Rocket while dispatching rather than by a pair of
+//! fairings because a fairing can't do either half: request fairings can't
+//! replace a request's body, which must be opened before its checksum is
+//! verified, it's decompressed, or its idempotency fingerprint is computed,
+//! and response fairings run before the response is compressed, while a body
+//! must be sealed last.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rocket::envelope::{Envelope, KeyRing, EnvelopeKey};
+//!
+//! let keys = KeyRing::new()
+//!     .key("client-7", EnvelopeKey::new([7; 32]));
+//!
+//! # if false {
+//! rocket::ignite()
+//!     .envelope(Envelope::new(keys).required(true))
+//!     .launch();
+//! # }
+//! ```
+//!
+//! [`Rocket::envelope()`]: /rocket/struct.Rocket.html#method.envelope
+//! [`KeyProvider`]: /rocket/envelope/trait.KeyProvider.html
+//! [`ClientCertificateKeys`]: /rocket/envelope/struct.ClientCertificateKeys.html
+//! [`KeyRing`]: /rocket/envelope/struct.KeyRing.html
+//! [`required`]: /rocket/envelope/struct.Envelope.html#method.required
+//! [compression]: /rocket/config/struct.CompressionConfig.html
+
+use std::fmt;
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+
+use ring::aead::{self, SealingKey, OpeningKey, AES_256_GCM};
+use ring::rand::{SecureRandom, SystemRandom};
+
+use request::Request;
+use response::Response;
+use data::Data;
+use http::{Header, Status};
+use security::Secret;
+#[cfg(feature = "tls")] use config::Config;
+#[cfg(feature = "tls")] use http::Certificate;
+#[cfg(feature = "tls")] use tls::MutualTlsUser;
+#[cfg(feature = "tls")] use x509;
+
+/// The `Content-Encoding` of a sealed body.
+pub const ENCODING: &str = "aes256gcm";
+
+/// The length of the nonce preceding a sealed body.
+const NONCE_LEN: usize = 12;
+
+/// The default maximum size of a sealed request body.
+const DEFAULT_BODY_LIMIT: u64 = 1024 * 1024;
+
+/// An AES-256 key used to seal and open the bodies of one client.
+///
+/// The key material is zeroed when the key is dropped and is never displayed.
+#[derive(Clone)]
+pub struct EnvelopeKey(Secret<[u8; 32]>);
+
+impl EnvelopeKey {
+    /// Returns a key with the 256 bits of key material in `bytes`.
+    pub fn new(bytes: [u8; 32]) -> EnvelopeKey {
+        EnvelopeKey(Secret::new(bytes))
+    }
+
+    /// Returns a key with the key material in `bytes` if it is 32 bytes long.
+    pub fn from_slice(bytes: &[u8]) -> Option<EnvelopeKey> {
+        if bytes.len() != 32 {
+            return None;
+        }
+
+        let mut key = [0u8; 32];
+        key.copy_from_slice(bytes);
+        Some(EnvelopeKey::new(key))
+    }
+
+    /// Seals `plaintext` with the associated data `ad`.
+    pub fn seal(&self, ad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let key = SealingKey::new(&AES_256_GCM, self.0.expose()).expect("AES-256 key");
+        let mut sealed = vec![0u8; NONCE_LEN];
+        SystemRandom::new().fill(&mut sealed).expect("random nonce");
+
+        let tag_len = AES_256_GCM.tag_len();
+        let mut in_out = plaintext.to_vec();
+        in_out.extend(::std::iter::repeat(0).take(tag_len));
+        let len = aead::seal_in_place(&key, &sealed, ad, &mut in_out, tag_len)
+            .expect("sealing with a valid key and nonce");
+
+        sealed.extend_from_slice(&in_out[..len]);
+        sealed
+    }
+
+    /// Opens `sealed` with the associated data `ad`, returning `None` if it
+    /// wasn't sealed with `self` and `ad` or has been tampered with.
+    pub fn open(&self, ad: &[u8], sealed: &[u8]) -> Option<Vec<u8>> {
+        if sealed.len() < NONCE_LEN + AES_256_GCM.tag_len() {
+            return None;
+        }
+
+        let key = OpeningKey::new(&AES_256_GCM, self.0.expose()).ok()?;
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let mut in_out = ciphertext.to_vec();
+        aead::open_in_place(&key, nonce, ad, 0, &mut in_out).ok().map(|p| p.to_vec())
+    }
+}
+
+impl fmt::Debug for EnvelopeKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("EnvelopeKey([REDACTED])")
+    }
+}
+
+/// Trait implemented by types that resolve the envelope key of the client
+/// making a request.
+///
+/// A provider may, for instance, derive the key from the client's
+/// authenticated TLS session, as [`ClientCertificateKeys`] does, or look it up
+/// by an identifier the client sends, as [`KeyRing`] does.
+///
+/// [`ClientCertificateKeys`]: /rocket/envelope/struct.ClientCertificateKeys.html
+/// [`KeyRing`]: /rocket/envelope/struct.KeyRing.html
+pub trait KeyProvider: Send + Sync + 'static {
+    /// Returns the key of the client making `request` or `None` if it has
+    /// none.
+    fn key(&self, request: &Request) -> Option<EnvelopeKey>;
+}
+
+/// A [`KeyProvider`] that derives the key of each client from the certificate
+/// it authenticated its TLS connection with. This type is only available when
+/// the `tls` feature is enabled.
+///
+/// The key of a client is derived from the configured `secret_key` and the
+/// SHA-256 fingerprint of the client's leaf certificate, so every client with
+/// a certificate has a key of its own, no keys need to be stored, and clients
+/// without a certificate have none. A client is provisioned with its key via
+/// [`key_for()`](#method.key_for). Since keys are derived from the secret key,
+/// it must be configured for them to stay the same across launches.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::envelope::{Envelope, ClientCertificateKeys};
+///
+/// # if false {
+/// rocket::ignite()
+///     .envelope(Envelope::new(ClientCertificateKeys).required(true))
+///     .launch();
+/// # }
+/// ```
+///
+/// [`KeyProvider`]: /rocket/envelope/trait.KeyProvider.html
+#[cfg(feature = "tls")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ClientCertificateKeys;
+
+#[cfg(feature = "tls")]
+impl ClientCertificateKeys {
+    /// Returns the key of the client with the leaf certificate `certificate`
+    /// under the configuration `config`.
+    pub fn key_for(config: &Config, certificate: &Certificate) -> EnvelopeKey {
+        ClientCertificateKeys::derive(config, &x509::sha256(&certificate.0))
+    }
+
+    fn derive(config: &Config, fingerprint: &[u8; 32]) -> EnvelopeKey {
+        let hex: Vec<_> = fingerprint.iter().map(|b| format!("{:02x}", b)).collect();
+        EnvelopeKey(config.derived_key(&format!("rocket/envelope/{}", hex.concat())))
+    }
+}
+
+#[cfg(feature = "tls")]
+impl KeyProvider for ClientCertificateKeys {
+    fn key(&self, request: &Request) -> Option<EnvelopeKey> {
+        let user = request.guard::<MutualTlsUser>().succeeded()?;
+        Some(ClientCertificateKeys::derive(request.config(), user.fingerprint()))
+    }
+}
+
+/// A [`KeyProvider`] with a fixed set of keys, selected by the
+/// `Envelope-Key-Id` request header. The identifier only selects a key: a
+/// client must hold the key to seal requests or open responses.
+///
+/// [`KeyProvider`]: /rocket/envelope/trait.KeyProvider.html
+#[derive(Default)]
+pub struct KeyRing {
+    keys: HashMap<String, EnvelopeKey>,
+}
+
+impl KeyRing {
+    /// Returns an empty key ring.
+    pub fn new() -> KeyRing {
+        KeyRing::default()
+    }
+
+    /// Adds `key` with the identifier `id`, replacing any key with that
+    /// identifier.
+    pub fn key<S: Into<String>>(mut self, id: S, key: EnvelopeKey) -> Self {
+        self.keys.insert(id.into(), key);
+        self
+    }
+}
+
+impl KeyProvider for KeyRing {
+    fn key(&self, request: &Request) -> Option<EnvelopeKey> {
+        let id = request.headers().get_one("Envelope-Key-Id")?;
+        self.keys.get(id.trim()).cloned()
+    }
+}
+
+impl fmt::Debug for KeyRing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.keys.keys()).finish()
+    }
+}
+
+/// Envelope handling configuration: a key provider and whether envelopes are
+/// required. See the [module level documentation](/rocket/envelope/index.html)
+/// for details.
+pub struct Envelope {
+    provider: Box<KeyProvider>,
+    required: bool,
+}
+
+impl Envelope {
+    /// Returns a configuration that resolves keys with `provider` and accepts
+    /// requests from clients without a key.
+    pub fn new<P: KeyProvider>(provider: P) -> Envelope {
+        Envelope { provider: Box::new(provider), required: false }
+    }
+
+    /// Sets whether requests from clients without a key are rejected.
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    // The associated data of a body sent in `direction` for `request`.
+    fn associated_data(direction: &str, request: &Request) -> Vec<u8> {
+        format!("{} {} {}", direction, request.method(), request.uri()).into_bytes()
+    }
+
+    /// Resolves the key of the client making `request` and opens the body in
+    /// `data` if it is sealed. The returned `Data` replaces `data`. Returns
+    /// the status to reject the request with if it can't be processed.
+    pub(crate) fn open(
+        &self,
+        request: &mut Request,
+        data: Data,
+        limit: Option<u64>
+    ) -> (Data, Result<Option<EnvelopeKey>, Status>) {
+        // The codings applied to the body, in order. Sealing must be the last.
+        let mut codings: Vec<String> = request.headers().get("Content-Encoding")
+            .flat_map(|encoding| encoding.split(','))
+            .map(|coding| coding.trim().to_string())
+            .filter(|coding| !coding.is_empty())
+            .collect();
+
+        let is_sealed = codings.iter().any(|coding| coding.eq_ignore_ascii_case(ENCODING));
+
+        let key = match self.provider.key(request) {
+            Some(key) => key,
+            None if self.required || is_sealed => {
+                warn_!("Request has no envelope key.");
+                return (Data::local(vec![]), Err(Status::Unauthorized));
+            }
+            None => return (data, Ok(None))
+        };
+
+        if !is_sealed {
+            return (data, Ok(Some(key)));
+        }
+
+        if !codings.last().map_or(false, |coding| coding.eq_ignore_ascii_case(ENCODING)) {
+            warn_!("Request body was encoded after it was sealed.");
+            return (Data::local(vec![]), Err(Status::BadRequest));
+        }
+
+        let limit = limit.unwrap_or(DEFAULT_BODY_LIMIT);
+        let mut sealed = vec![];
+        if let Err(e) = data.open().take(limit + 1).read_to_end(&mut sealed) {
+            error_!("Failed to read sealed request body: {:?}", e);
+            return (Data::local(vec![]), Err(Status::BadRequest));
+        }
+
+        if sealed.len() as u64 > limit {
+            warn_!("Sealed body exceeds the envelope limit of {} bytes.", limit);
+            return (Data::local(vec![]), Err(Status::PayloadTooLarge));
+        }
+
+        if request.reserve_memory(sealed.len() as u64).is_err() {
+            return (Data::local(vec![]), Err(Status::PayloadTooLarge));
+        }
+
+        let ad = Envelope::associated_data("request", request);
+        match key.open(&ad, &sealed) {
+            Some(plaintext) => {
+                codings.pop();
+                match codings.is_empty() {
+                    true => request.remove_header("Content-Encoding"),
+                    false => {
+                        let encoding = Header::new("Content-Encoding", codings.join(", "));
+                        request.replace_header(encoding);
+                    }
+                }
+
+                (Data::local(plaintext), Ok(Some(key)))
+            }
+            None => {
+                warn_!("Sealed request body could not be opened.");
+                (Data::local(vec![]), Err(Status::BadRequest))
+            }
+        }
+    }
+
+    /// Seals the body of `response`, if it has one, with `key`.
+    pub(crate) fn seal(&self, key: &EnvelopeKey, request: &Request, response: &mut Response) {
+        let body = match response.take_body() {
+            Some(body) => body,
+            None => return
+        };
+
+        let plaintext = match body.into_bytes() {
+            Some(plaintext) => plaintext,
+            None => {
+                error_!("Failed to read response body to seal it.");
+                response.set_status(Status::InternalServerError);
+                response.set_sized_body(Cursor::new(Vec::new()));
+                return;
+            }
+        };
+
+        if request.reserve_memory(plaintext.len() as u64).is_err() {
+            response.set_status(Status::InternalServerError);
+            response.set_sized_body(Cursor::new(Vec::new()));
+            return;
+        }
+
+        // Any coding the body already has, like compression, was applied first.
+        let mut codings: Vec<String> = response.headers().get("Content-Encoding")
+            .map(|coding| coding.to_string())
+            .collect();
+
+        codings.push(ENCODING.to_string());
+        let ad = Envelope::associated_data("response", request);
+        response.set_sized_body(Cursor::new(key.seal(&ad, &plaintext)));
+        response.set_raw_header("Content-Encoding", codings.join(", "));
+    }
+}
+
+impl fmt::Debug for Envelope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Envelope").field("required", &self.required).finish()
+    }
+}
@@ -1,12 +1,14 @@
 //! Types representing various errors that can occur in a Rocket application.
 
 use std::{io, fmt};
+use std::collections::HashMap;
 use std::sync::atomic::{Ordering, AtomicBool};
 
 use yansi::Paint;
 
 use http::hyper;
 use router::Route;
+use fairing::AttachError;
 
 /// [unstable] Error type for Rocket. Likely to change.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
@@ -26,20 +28,62 @@ pub enum Error {
 /// The kind of launch error that occured.
 ///
 /// In almost every instance, a launch error occurs because of an I/O error;
-/// this is represented by the `Io` variant. A launch error may also occur
-/// because of ill-defined routes that lead to collisions or because a fairing
-/// encountered an error; these are represented by the `Collision` and
-/// `FailedFairing` variants, respectively. The `Unknown` variant captures all
-/// other kinds of launch errors.
+/// this is represented by the `Io` variant, or the `Bind` variant when the
+/// server's socket couldn't be bound. A launch error may also occur because
+/// the TLS configuration can't be used, because of ill-defined routes that
+/// lead to collisions, or because a fairing encountered an error; these are
+/// represented by the `TlsConfig`, `Collision`, and `FailedFairings` variants,
+/// respectively. The `Unknown` variant captures all other kinds of launch
+/// errors.
+///
+/// # Exit Codes
+///
+/// Each kind maps to a process exit code via
+/// [`exit_code`](#method.exit_code), following the conventions of
+/// `sysexits.h`, so that supervisors can tell failures apart:
+///
+/// | kind             | exit code | `sysexits.h` name  |
+/// |------------------|-----------|--------------------|
+/// | `Bind`           | 69        | `EX_UNAVAILABLE`   |
+/// | `Io`             | 74        | `EX_IOERR`         |
+/// | `TlsConfig`      | 78        | `EX_CONFIG`        |
+/// | `Collision`      | 70        | `EX_SOFTWARE`      |
+/// | `FailedFairings` | 75        | `EX_TEMPFAIL`      |
+/// | `Unknown`        | 1         |                    |
 #[derive(Debug)]
 pub enum LaunchErrorKind {
     Bind(hyper::Error),
     Io(io::Error),
+    TlsConfig(String),
     Collision(Vec<(Route, Route)>),
-    FailedFairings(Vec<&'static str>),
+    FailedFairings(Vec<AttachError>),
     Unknown(Box<::std::error::Error + Send + Sync>)
 }
 
+impl LaunchErrorKind {
+    /// Returns the process exit code for this kind of launch error. See the
+    /// [table above](#exit-codes) for the mapping.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::error::LaunchErrorKind;
+    ///
+    /// let kind = LaunchErrorKind::TlsConfig("tls feature is disabled".into());
+    /// assert_eq!(kind.exit_code(), 78);
+    /// ```
+    pub fn exit_code(&self) -> i32 {
+        match *self {
+            LaunchErrorKind::Bind(_) => 69,
+            LaunchErrorKind::Io(_) => 74,
+            LaunchErrorKind::TlsConfig(_) => 78,
+            LaunchErrorKind::Collision(_) => 70,
+            LaunchErrorKind::FailedFairings(_) => 75,
+            LaunchErrorKind::Unknown(_) => 1,
+        }
+    }
+}
+
 /// An error that occurs during launch.
 ///
 /// A `LaunchError` is returned by
@@ -129,6 +173,27 @@ impl LaunchError {
         self.mark_handled();
         &self.kind
     }
+
+    /// Returns the process exit code for this launch error. This is the exit
+    /// code of its [`kind`](#method.kind); see
+    /// [`LaunchErrorKind`](/rocket/error/enum.LaunchErrorKind.html) for the
+    /// mapping.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # if false {
+    /// let error = rocket::ignite().launch();
+    ///
+    /// // This line is only reached if launch failed.
+    /// eprintln!("Launch failed: {}", error);
+    /// ::std::process::exit(error.exit_code());
+    /// # }
+    /// ```
+    #[inline]
+    pub fn exit_code(&self) -> i32 {
+        self.kind().exit_code()
+    }
 }
 
 impl From<hyper::Error> for LaunchError {
@@ -154,8 +219,16 @@ impl fmt::Display for LaunchErrorKind {
         match *self {
             LaunchErrorKind::Bind(ref e) => write!(f, "binding failed: {}", e),
             LaunchErrorKind::Io(ref e) => write!(f, "I/O error: {}", e),
+            LaunchErrorKind::TlsConfig(ref e) => write!(f, "TLS configuration error: {}", e),
             LaunchErrorKind::Collision(_) => write!(f, "route collisions detected"),
-            LaunchErrorKind::FailedFairings(_) => write!(f, "a launch fairing failed"),
+            LaunchErrorKind::FailedFairings(ref failures) => {
+                write!(f, "{} attach fairing(s) failed", failures.len())?;
+                for failure in failures {
+                    write!(f, "; {}", failure)?;
+                }
+
+                Ok(())
+            }
             LaunchErrorKind::Unknown(ref e) => write!(f, "unknown error: {}", e)
         }
     }
@@ -184,6 +257,7 @@ impl ::std::error::Error for LaunchError {
         match *self.kind() {
             LaunchErrorKind::Bind(_) => "failed to bind to given address/port",
             LaunchErrorKind::Io(_) => "an I/O error occured during launch",
+            LaunchErrorKind::TlsConfig(_) => "the TLS configuration could not be used",
             LaunchErrorKind::Collision(_) => "route collisions were detected",
             LaunchErrorKind::FailedFairings(_) => "a launch fairing reported an error",
             LaunchErrorKind::Unknown(_) => "an unknown error occured during launch"
@@ -206,6 +280,10 @@ impl Drop for LaunchError {
                 error!("Rocket failed to launch due to an I/O error.");
                 panic!("{}", e);
             }
+            LaunchErrorKind::TlsConfig(ref e) => {
+                error!("Rocket failed to launch due to an unusable TLS configuration.");
+                panic!("{}", e);
+            }
             LaunchErrorKind::Collision(ref collisions) => {
                 error!("Rocket failed to launch due to the following routing collisions:");
                 for &(ref a, ref b) in collisions {
@@ -217,8 +295,8 @@ impl Drop for LaunchError {
             }
             LaunchErrorKind::FailedFairings(ref failures) => {
                 error!("Rocket failed to launch due to failing fairings:");
-                for fairing in failures {
-                    info_!("{}", Paint::white(fairing));
+                for failure in failures {
+                    info_!("{}", Paint::white(failure));
                 }
 
                 panic!("launch fairing failure");
@@ -230,3 +308,128 @@ impl Drop for LaunchError {
         }
     }
 }
+
+/// An error with a stable, machine-readable code and interpolation parameters.
+///
+/// Types implementing `ErrorCode` can be rendered into human-readable messages
+/// in any language by a [`Messages`](/rocket/error/struct.Messages.html)
+/// catalog. The `code` identifies the kind of error and never changes between
+/// releases; the `params` are the values that a message template may refer to
+/// as `{name}`, such as the name of the offending field or a minimum length.
+///
+/// Every implementation provides a default English template via `english`,
+/// which is used whenever a catalog has no template for the error's code.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::error::{ErrorCode, Messages};
+/// use rocket::request::FormError;
+///
+/// let error = FormError::missing("email");
+/// assert_eq!(error.code(), "form.missing");
+/// assert_eq!(Messages::english().render(&error), "'email' is required.");
+///
+/// let french = Messages::new().set("form.missing", "Le champ « {field} » est requis.");
+/// assert_eq!(french.render(&error), "Le champ « email » est requis.");
+/// ```
+pub trait ErrorCode {
+    /// The stable, machine-readable code identifying this kind of error, such
+    /// as `form.missing`.
+    fn code(&self) -> &'static str;
+
+    /// The parameters that may be interpolated into a message for this error.
+    /// The default implementation returns no parameters.
+    fn params(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+
+    /// The default English template for this error.
+    fn english(&self) -> &'static str;
+}
+
+/// A catalog of message templates, keyed by error code.
+///
+/// A template is a string in which `{name}` is replaced with the value of the
+/// parameter `name` of the error being rendered; unknown parameters are left
+/// as-is. Errors whose code has no template in the catalog are rendered with
+/// their default English template, so an empty catalog is the default English
+/// renderer.
+#[derive(Debug, Clone, Default)]
+pub struct Messages {
+    templates: HashMap<String, String>,
+}
+
+impl Messages {
+    /// Returns an empty catalog. Every error is rendered with its default
+    /// English template until templates are added with
+    /// [`set`](#method.set).
+    #[inline(always)]
+    pub fn new() -> Messages {
+        Messages { templates: HashMap::new() }
+    }
+
+    /// Returns the default English catalog. This is an alias for
+    /// [`new`](#method.new).
+    #[inline(always)]
+    pub fn english() -> Messages {
+        Messages::new()
+    }
+
+    /// Sets the template for errors with code `code` to `template`.
+    #[inline]
+    pub fn set<C: Into<String>, T: Into<String>>(mut self, code: C, template: T) -> Messages {
+        self.templates.insert(code.into(), template.into());
+        self
+    }
+
+    /// Returns the template for the code `code`, if one was set.
+    #[inline]
+    pub fn get(&self, code: &str) -> Option<&str> {
+        self.templates.get(code).map(|t| t.as_str())
+    }
+
+    /// Renders `error` into a message using the template for its code or, if
+    /// there is none, its default English template.
+    pub fn render<E: ErrorCode + ?Sized>(&self, error: &E) -> String {
+        let template = self.get(error.code()).unwrap_or_else(|| error.english());
+        interpolate(template, &error.params())
+    }
+}
+
+fn interpolate(template: &str, params: &[(&'static str, String)]) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let name = after.find('}').map(|end| &after[..end]);
+        match name.and_then(|n| params.iter().find(|&&(k, _)| k == n)) {
+            Some(&(k, ref value)) => {
+                output.push_str(value);
+                rest = &after[k.len() + 1..];
+            }
+            None => {
+                output.push('{');
+                rest = after;
+            }
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+impl ErrorCode for io::Error {
+    fn code(&self) -> &'static str {
+        "data.io"
+    }
+
+    fn params(&self) -> Vec<(&'static str, String)> {
+        vec![("error", self.to_string())]
+    }
+
+    fn english(&self) -> &'static str {
+        "The request data could not be read."
+    }
+}
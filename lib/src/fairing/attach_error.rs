@@ -0,0 +1,98 @@
+use std::fmt;
+use std::error::Error;
+
+/// An error reported by an attach fairing, with context.
+///
+/// An attach fairing reports a failure with context by returning the result of
+/// [`Rocket::fail_attach()`] from its `on_attach` callback. Fairings that
+/// simply return `Err(rocket)` are reported with a generic message. Rocket
+/// records the name of the failing fairing in the error, calls the fairing's
+/// [`on_attach_failure`] hook, and continues attaching fairings. Launching
+/// then fails with a [`FailedFairings`] error containing every `AttachError`,
+/// so that all failures are reported together.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::fairing::{AdHoc, AttachError};
+///
+/// let rocket = rocket::ignite()
+///     .attach(AdHoc::on_attach(|rocket| {
+///         let error = "connection refused";
+///         rocket.fail_attach(AttachError::new("failed to connect to the database")
+///             .with_cause(error))
+///     }));
+/// ```
+///
+/// [`Rocket::fail_attach()`]: /rocket/struct.Rocket.html#method.fail_attach
+/// [`on_attach_failure`]: /rocket/fairing/trait.Fairing.html#method.on_attach_failure
+/// [`FailedFairings`]: /rocket/error/enum.LaunchErrorKind.html#variant.FailedFairings
+#[derive(Debug)]
+pub struct AttachError {
+    fairing: &'static str,
+    message: String,
+    cause: Option<Box<Error + Send + Sync>>,
+}
+
+impl AttachError {
+    /// Returns a new `AttachError` with the message `message`.
+    #[inline]
+    pub fn new<S: Into<String>>(message: S) -> AttachError {
+        AttachError { fairing: "", message: message.into(), cause: None }
+    }
+
+    /// Sets the underlying cause of this error to `cause`. The cause is
+    /// available via `Error::cause()`.
+    #[inline]
+    pub fn with_cause<E>(mut self, cause: E) -> AttachError
+        where E: Into<Box<Error + Send + Sync>>
+    {
+        self.cause = Some(cause.into());
+        self
+    }
+
+    /// Sets the name of the fairing that failed.
+    #[inline]
+    pub(crate) fn for_fairing(mut self, name: &'static str) -> AttachError {
+        self.fairing = name;
+        self
+    }
+
+    /// Returns the name of the fairing that failed. This is empty until the
+    /// error has been returned from `on_attach`.
+    #[inline(always)]
+    pub fn fairing(&self) -> &'static str {
+        self.fairing
+    }
+
+    /// Returns the message describing the failure.
+    #[inline(always)]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for AttachError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.fairing.is_empty() {
+            write!(f, "{}: ", self.fairing)?;
+        }
+
+        write!(f, "{}", self.message)?;
+        if let Some(ref cause) = self.cause {
+            write!(f, " ({})", cause)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for AttachError {
+    fn description(&self) -> &str {
+        &self.message
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        self.cause.as_ref().map(|cause| &**cause as &Error)
+    }
+}
@@ -1,10 +1,10 @@
 use {Rocket, Request, Response, Data};
-use fairing::{Fairing, Kind};
+use fairing::{Fairing, Info, Kind, AttachError};
 
 #[derive(Default)]
 pub struct Fairings {
     all_fairings: Vec<Box<Fairing>>,
-    attach_failures: Vec<&'static str>,
+    attach_failures: Vec<AttachError>,
     launch: Vec<&'static Fairing>,
     request: Vec<&'static Fairing>,
     response: Vec<&'static Fairing>,
@@ -21,8 +21,21 @@ impl Fairings {
         let kind = fairing.info().kind;
         let name = fairing.info().name;
         if kind.is(Kind::Attach) {
-            rocket = fairing.on_attach(rocket)
-                .unwrap_or_else(|r| { self.attach_failures.push(name); r })
+            rocket = match fairing.on_attach(rocket) {
+                Ok(mut rocket) => {
+                    rocket.attach_error = None;
+                    rocket
+                }
+                Err(mut rocket) => {
+                    let error = rocket.attach_error.take()
+                        .unwrap_or_else(|| AttachError::new("attach callback failed"))
+                        .for_fairing(name);
+
+                    fairing.on_attach_failure(&rocket, &error);
+                    self.attach_failures.push(error);
+                    rocket
+                }
+            };
         }
 
         self.add(fairing);
@@ -59,10 +72,16 @@ impl Fairings {
         }
     }
 
+    pub fn info(&self) -> Vec<Info> {
+        self.all_fairings.iter().map(|fairing| fairing.info()).collect()
+    }
+
     pub fn append(&mut self, others: Fairings) {
         for fairing in others.all_fairings {
             self.add(fairing);
         }
+
+        self.attach_failures.extend(others.attach_failures);
     }
 
     #[inline(always)]
@@ -86,11 +105,16 @@ impl Fairings {
         }
     }
 
-    pub fn failures(&self) -> Option<&[&'static str]> {
+    #[inline]
+    pub fn has_fairings(&self) -> bool {
+        !self.all_fairings.is_empty()
+    }
+
+    pub fn take_failures(&mut self) -> Option<Vec<AttachError>> {
         if self.attach_failures.is_empty() {
             None
         } else {
-            Some(&self.attach_failures)
+            Some(::std::mem::replace(&mut self.attach_failures, vec![]))
         }
     }
 
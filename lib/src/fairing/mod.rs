@@ -54,10 +54,12 @@ use {Rocket, Request, Response, Data};
 mod fairings;
 mod ad_hoc;
 mod info_kind;
+mod attach_error;
 
 pub(crate) use self::fairings::Fairings;
 pub use self::ad_hoc::AdHoc;
 pub use self::info_kind::{Info, Kind};
+pub use self::attach_error::AttachError;
 
 // We might imagine that a request fairing returns an `Outcome`. If it returns
 // `Success`, we don't do any routing and use that response directly. Same if it
@@ -143,7 +145,10 @@ pub use self::info_kind::{Info, Kind};
 ///     issues are better handled via [request
 ///     guards](/rocket/request/trait.FromRequest.html) or via response
 ///     callbacks. Any modifications to a request are persisted and can
-///     potentially alter how a request is routed.
+///     potentially alter how a request is routed, as well as how Rocket's
+///     host allowlist, HTTPS upgrades, and idempotency handling treat it. The
+///     data is the body as it was received, before it is decompressed or
+///     opened from an envelope.
 ///
 ///   * **Response (`on_response`)**
 ///
@@ -305,11 +310,29 @@ pub trait Fairing: Send + Sync + 'static {
     /// parameter is the `Rocket` instance that is currently being built for
     /// this application.
     ///
+    /// To report a failure with context, return the result of
+    /// [`Rocket::fail_attach()`](/rocket/struct.Rocket.html#method.fail_attach)
+    /// with an [`AttachError`](/rocket/fairing/struct.AttachError.html).
+    ///
     /// ## Default Implementation
     ///
     /// The default implementation of this method simply returns `Ok(rocket)`.
     fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> { Ok(rocket) }
 
+    /// The attach failure callback.
+    ///
+    /// This method is called right after `on_attach` returns `Err`. The
+    /// `rocket` parameter is the `Rocket` instance returned by `on_attach`, and
+    /// `error` describes the failure. Use this method to clean up any state
+    /// that was initialized before the failure, such as spawned threads or
+    /// temporary files, since the application will not launch.
+    ///
+    /// ## Default Implementation
+    ///
+    /// The default implementation of this method does nothing.
+    #[allow(unused_variables)]
+    fn on_attach_failure(&self, rocket: &Rocket, error: &AttachError) {}
+
     /// The launch callback.
     ///
     /// This method is called just prior to launching the application if
@@ -350,6 +373,38 @@ pub trait Fairing: Send + Sync + 'static {
     fn on_response(&self, request: &Request, response: &mut Response) {}
 }
 
+impl Fairing for Box<Fairing> {
+    #[inline]
+    fn info(&self) -> Info {
+        (**self).info()
+    }
+
+    #[inline]
+    fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        (**self).on_attach(rocket)
+    }
+
+    #[inline]
+    fn on_attach_failure(&self, rocket: &Rocket, error: &AttachError) {
+        (**self).on_attach_failure(rocket, error)
+    }
+
+    #[inline]
+    fn on_launch(&self, rocket: &Rocket) {
+        (**self).on_launch(rocket)
+    }
+
+    #[inline]
+    fn on_request(&self, request: &mut Request, data: &Data) {
+        (**self).on_request(request, data)
+    }
+
+    #[inline]
+    fn on_response(&self, request: &Request, response: &mut Response) {
+        (**self).on_response(request, response)
+    }
+}
+
 impl<T: Fairing> Fairing for ::std::sync::Arc<T> {
     #[inline]
     fn info(&self) -> Info {
@@ -361,6 +416,11 @@ impl<T: Fairing> Fairing for ::std::sync::Arc<T> {
         (self as &T).on_attach(rocket)
     }
 
+    #[inline]
+    fn on_attach_failure(&self, rocket: &Rocket, error: &AttachError) {
+        (self as &T).on_attach_failure(rocket, error)
+    }
+
     #[inline]
     fn on_launch(&self, rocket: &Rocket) {
         (self as &T).on_launch(rocket)
@@ -0,0 +1,184 @@
+//! Feature toggles that can be flipped per environment and at runtime.
+//!
+//! Features are named boolean flags declared in the `features` table of an
+//! environment's configuration:
+//!
+//! ```toml
+//! [development.features]
+//! new_checkout = true
+//! beta_search = true
+//!
+//! [production.features]
+//! new_checkout = false
+//! ```
+//!
+//! A feature that isn't declared is disabled. The current state of every
+//! feature is held in the [`Features`] registry of an instance of `Rocket`,
+//! retrieved via [`Rocket::features()`] or with the `&Features` request guard.
+//! The registry allows a flag's configured value to be overridden at runtime,
+//! without a restart.
+//!
+//! To gate an entire route on a feature, declare a type implementing
+//! [`Feature`] and use the [`Flag`] request guard. The guard forwards when the
+//! feature is disabled:
+//!
+//! ```rust
+//! # #![feature(plugin, decl_macro)]
+//! # #![plugin(rocket_codegen)]
+//! # extern crate rocket;
+//! use rocket::features::{Feature, Flag};
+//!
+//! struct NewCheckout;
+//!
+//! impl Feature for NewCheckout {
+//!     const NAME: &'static str = "new_checkout";
+//! }
+//!
+//! #[get("/checkout")]
+//! fn new_checkout(_flag: Flag<NewCheckout>) -> &'static str {
+//!     "the new checkout"
+//! }
+//!
+//! #[get("/checkout", rank = 2)]
+//! fn old_checkout() -> &'static str {
+//!     "the old checkout"
+//! }
+//! # fn main() {  }
+//! ```
+//!
+//! [`Features`]: /rocket/features/struct.Features.html
+//! [`Feature`]: /rocket/features/trait.Feature.html
+//! [`Flag`]: /rocket/features/struct.Flag.html
+//! [`Rocket::features()`]: /rocket/struct.Rocket.html#method.features
+
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::RwLock;
+
+use request::{Request, FromRequest, Outcome};
+use outcome::Outcome::*;
+
+/// The registry of feature toggles for an instance of `Rocket`.
+///
+/// The registry is initialized from the configured `features` table. Any flag
+/// can be overridden at runtime with [`set`](#method.set); an override takes
+/// precedence over the configured value until it is
+/// [`reset`](#method.reset).
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::config::{Config, Environment};
+///
+/// let config = Config::build(Environment::Staging)
+///     .feature("beta_search", true)
+///     .unwrap();
+///
+/// let rocket = rocket::custom(config, false);
+/// let features = rocket.features();
+/// assert!(features.is_enabled("beta_search"));
+///
+/// features.set("beta_search", false);
+/// assert!(!features.is_enabled("beta_search"));
+///
+/// features.reset("beta_search");
+/// assert!(features.is_enabled("beta_search"));
+/// ```
+pub struct Features {
+    configured: HashMap<String, bool>,
+    overrides: RwLock<HashMap<String, bool>>,
+}
+
+impl Features {
+    pub(crate) fn new(configured: HashMap<String, bool>) -> Features {
+        Features { configured: configured, overrides: RwLock::new(HashMap::new()) }
+    }
+
+    /// Returns `true` if the feature `name` is enabled. Features that are
+    /// neither configured nor overridden are disabled.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        let overrides = self.overrides.read().expect("features read lock");
+        overrides.get(name)
+            .or_else(|| self.configured.get(name))
+            .map_or(false, |&enabled| enabled)
+    }
+
+    /// Overrides the state of the feature `name` with `enabled`, regardless of
+    /// its configured value.
+    pub fn set<S: Into<String>>(&self, name: S, enabled: bool) {
+        let mut overrides = self.overrides.write().expect("features write lock");
+        overrides.insert(name.into(), enabled);
+    }
+
+    /// Removes any runtime override for the feature `name`, restoring its
+    /// configured value. Returns `true` if there was an override.
+    pub fn reset(&self, name: &str) -> bool {
+        let mut overrides = self.overrides.write().expect("features write lock");
+        overrides.remove(name).is_some()
+    }
+
+    /// Returns the name and current state of every configured or overridden
+    /// feature, sorted by name. This is useful, for instance, to make the
+    /// state of all features available to templates.
+    pub fn snapshot(&self) -> Vec<(String, bool)> {
+        let overrides = self.overrides.read().expect("features read lock");
+        let mut all: HashMap<&str, bool> = HashMap::new();
+        for (name, &enabled) in self.configured.iter().chain(overrides.iter()) {
+            all.insert(name.as_str(), enabled);
+        }
+
+        let mut features: Vec<_> = all.into_iter()
+            .map(|(name, enabled)| (name.to_string(), enabled))
+            .collect();
+
+        features.sort();
+        features
+    }
+}
+
+impl fmt::Debug for Features {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.snapshot()).finish()
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for &'a Features {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> Outcome<Self, Self::Error> {
+        Success(request.features())
+    }
+}
+
+/// Trait implemented by types that name a feature toggle.
+///
+/// See the [`Flag`](/rocket/features/struct.Flag.html) request guard.
+pub trait Feature {
+    /// The name of the feature in the `features` configuration table.
+    const NAME: &'static str;
+}
+
+/// Request guard that succeeds only when the feature `F` is enabled.
+///
+/// When `F` is disabled, the guard forwards, allowing a lower-ranked route,
+/// such as the previous version of an endpoint, to handle the request. See the
+/// [module level documentation](/rocket/features/index.html) for an example.
+pub struct Flag<F: Feature>(PhantomData<F>);
+
+impl<F: Feature> fmt::Debug for Flag<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Flag({})", F::NAME)
+    }
+}
+
+impl<'a, 'r, F: Feature> FromRequest<'a, 'r> for Flag<F> {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> Outcome<Self, Self::Error> {
+        match request.features().is_enabled(F::NAME) {
+            true => Success(Flag(PhantomData)),
+            false => Forward(())
+        }
+    }
+}
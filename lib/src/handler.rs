@@ -1,10 +1,14 @@
 //! The types of request and error handlers and their return values.
 
+use std::io;
+
+use yansi::Paint;
+
 use data::Data;
 use request::Request;
-use response::{self, Response, Responder};
+use response::{self, Response, Responder, Body};
 use error::Error;
-use http::Status;
+use http::{Status, ContentType};
 use outcome;
 
 /// Type alias for the `Outcome` of a `Handler`.
@@ -13,6 +17,10 @@ pub type Outcome<'r> = outcome::Outcome<Response<'r>, Status, Data>;
 /// The type of a request handler.
 pub type Handler = for<'r> fn(&'r Request, Data) -> Outcome<'r>;
 
+/// The type of a `HEAD` length handler: returns the length of the body the
+/// route's handler would respond with, if it can be determined cheaply.
+pub type HeadLength = for<'r> fn(&'r Request) -> Option<u64>;
+
 /// The type of an error handler.
 pub type ErrorHandler = for<'r> fn(Error, &'r Request) -> response::Result<'r>;
 
@@ -41,6 +49,29 @@ impl<'r> Outcome<'r> {
         }
     }
 
+    /// Returns the response to an automatically handled `HEAD` request from
+    /// the `head_length` function of the route handling it, if it has one and
+    /// it can determine the length. Called by generated handlers once all of
+    /// the route's guards have succeeded. For internal use only!
+    #[doc(hidden)]
+    pub fn _from_head_length(req: &'r Request) -> Option<Outcome<'r>> {
+        if !req.is_autohandled_head() {
+            return None;
+        }
+
+        let route = req.route()?;
+        let length = (route.head_length?)(req)?;
+        info_!("Answering {} from route metadata.", Paint::white("HEAD"));
+
+        let mut response = Response::new();
+        if let Some(ref format) = route.format {
+            response.set_header(ContentType(format.clone()));
+        }
+
+        response.set_raw_body(Body::Sized(io::empty(), length));
+        Some(outcome::Outcome::Success(response))
+    }
+
     /// Return an `Outcome` of `Failure` with the status code `code`. This is
     /// equivalent to `Outcome::Failure(code)`.
     ///
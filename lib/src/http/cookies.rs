@@ -3,8 +3,16 @@ use std::cell::RefMut;
 
 pub use cookie::{Cookie, Key, CookieJar};
 use cookie::{SameSite, Delta};
+use ring::aead::{self, SealingKey, OpeningKey, Algorithm, AES_256_GCM, CHACHA20_POLY1305};
+use ring::rand::{SecureRandom, SystemRandom};
+use base64;
 
 use http::Header;
+use http::uncased::uncased_eq;
+use security::{Secret, derive_key};
+
+/// The length of the nonce preceding an encrypted cookie value.
+const NONCE_LEN: usize = 12;
 
 /// Collection of one or more HTTP cookies.
 ///
@@ -109,17 +117,36 @@ use http::Header;
 /// is usually done through tools like `openssl`. Using `openssl`, for instance,
 /// a 256-bit base64 key can be generated with the command `openssl rand -base64
 /// 32`.
+///
+/// ## Encryption Algorithm
+///
+/// Private cookies are encrypted with the [`CookieCipher`] selected by the
+/// `private_cookie_cipher` configuration parameter: AES-256-GCM, the default,
+/// or ChaCha20-Poly1305. The value of an encrypted cookie is prefixed with the
+/// version of its format, which identifies the algorithm, so cookies
+/// encrypted with either algorithm, or by versions of Rocket that predate the
+/// prefix, can be read. A private cookie that isn't encrypted with the
+/// selected algorithm is re-encrypted when it's read and sent back to the
+/// client, with the defaults of [`add_private`], so changing the algorithm
+/// doesn't sign anyone out.
+///
+/// [`CookieCipher`]: /rocket/http/enum.CookieCipher.html
 pub enum Cookies<'a> {
     #[doc(hidden)]
-    Jarred(RefMut<'a, CookieJar>, &'a Key),
+    Jarred(RefMut<'a, CookieJar>, &'a Key, CookieCipher, RefMut<'a, Vec<String>>),
     #[doc(hidden)]
     Empty(CookieJar)
 }
 
 impl<'a> Cookies<'a> {
     #[inline]
-    pub(crate) fn new(jar: RefMut<'a, CookieJar>, key: &'a Key) -> Cookies<'a> {
-        Cookies::Jarred(jar, key)
+    pub(crate) fn new(
+        jar: RefMut<'a, CookieJar>,
+        key: &'a Key,
+        cipher: CookieCipher,
+        order: RefMut<'a, Vec<String>>
+    ) -> Cookies<'a> {
+        Cookies::Jarred(jar, key, cipher, order)
     }
 
     #[inline]
@@ -134,11 +161,24 @@ impl<'a> Cookies<'a> {
 
     /// Adds an original `cookie` to this collection.
     pub(crate) fn add_original(&mut self, cookie: Cookie<'static>) {
-        if let Cookies::Jarred(ref mut jar, _) = *self {
+        if let Cookies::Jarred(ref mut jar, ..) = *self {
             jar.add_original(cookie)
         }
     }
 
+    /// Records that the cookie named `name` was just added or removed.
+    fn changed(order: &mut Vec<String>, name: &str) {
+        order.retain(|changed| changed != name);
+        order.push(name.to_string());
+    }
+
+    /// Removes the original cookie named `name` from this collection.
+    pub(crate) fn remove_original(&mut self, name: &str) {
+        if let Cookies::Jarred(ref mut jar, ..) = *self {
+            jar.force_remove(Cookie::named(name.to_string()))
+        }
+    }
+
     /// Returns a reference to the `Cookie` inside this container with the name
     /// `name`. If no such cookie exists, returns `None`.
     ///
@@ -153,7 +193,7 @@ impl<'a> Cookies<'a> {
     /// ```
     pub fn get(&self, name: &str) -> Option<&Cookie<'static>> {
         match *self {
-            Cookies::Jarred(ref jar, _) => jar.get(name),
+            Cookies::Jarred(ref jar, ..) => jar.get(name),
             Cookies::Empty(_) => None
         }
     }
@@ -163,6 +203,12 @@ impl<'a> Cookies<'a> {
     /// `Cookie` with the decrypted value. If the cookie cannot be found, or the
     /// cookie fails to authenticate or decrypt, `None` is returned.
     ///
+    /// If the cookie wasn't encrypted with the configured [`CookieCipher`], it
+    /// is re-encrypted with it and added to the collection, as if by
+    /// [`add_private`](#method.add_private).
+    ///
+    /// [`CookieCipher`]: /rocket/http/enum.CookieCipher.html
+    ///
     /// # Example
     ///
     /// ```rust
@@ -173,10 +219,35 @@ impl<'a> Cookies<'a> {
     /// }
     /// ```
     pub fn get_private(&mut self, name: &str) -> Option<Cookie<'static>> {
-        match *self {
-            Cookies::Jarred(ref mut jar, key) => jar.private(key).get(name),
-            Cookies::Empty(_) => None
+        let (jar, key, cipher, order) = match *self {
+            Cookies::Jarred(ref mut jar, key, cipher, ref mut order) => (jar, key, cipher, order),
+            Cookies::Empty(_) => return None
+        };
+
+        let mut cookie = jar.get(name)?.clone();
+        let opened = CookieCipher::open(key, name, cookie.value());
+        let upgrade = match opened {
+            Some((used, value)) => {
+                cookie.set_value(value);
+                used != cipher
+            }
+            // The value predates versioned formats; it's AES-256-GCM.
+            None => {
+                cookie = jar.private(key).get(name)?;
+                true
+            }
+        };
+
+        if upgrade {
+            let mut upgraded = cookie.clone();
+            Cookies::set_private_defaults(&mut upgraded);
+            let sealed = cipher.seal(key, name, upgraded.value());
+            upgraded.set_value(sealed);
+            jar.add(upgraded);
+            Cookies::changed(order, name);
         }
+
+        Some(cookie)
     }
 
     /// Adds `cookie` to this collection.
@@ -198,7 +269,8 @@ impl<'a> Cookies<'a> {
     /// }
     /// ```
     pub fn add(&mut self, cookie: Cookie<'static>) {
-        if let Cookies::Jarred(ref mut jar, _) = *self {
+        if let Cookies::Jarred(ref mut jar, _, _, ref mut order) = *self {
+            Cookies::changed(order, cookie.name());
             jar.add(cookie)
         }
     }
@@ -230,17 +302,22 @@ impl<'a> Cookies<'a> {
     /// }
     /// ```
     pub fn add_private(&mut self, mut cookie: Cookie<'static>) {
-        if let Cookies::Jarred(ref mut jar, key) = *self {
+        if let Cookies::Jarred(ref mut jar, key, cipher, ref mut order) = *self {
+            Cookies::changed(order, cookie.name());
             Cookies::set_private_defaults(&mut cookie);
-            jar.private(key).add(cookie)
+            let sealed = cipher.seal(key, cookie.name(), cookie.value());
+            cookie.set_value(sealed);
+            jar.add(cookie)
         }
     }
 
     /// Adds an original, private `cookie` to the collection.
     pub(crate) fn add_original_private(&mut self, mut cookie: Cookie<'static>) {
-        if let Cookies::Jarred(ref mut jar, key) = *self {
+        if let Cookies::Jarred(ref mut jar, key, cipher, _) = *self {
             Cookies::set_private_defaults(&mut cookie);
-            jar.private(key).add_original(cookie)
+            let sealed = cipher.seal(key, cookie.name(), cookie.value());
+            cookie.set_value(sealed);
+            jar.add_original(cookie)
         }
     }
 
@@ -291,7 +368,8 @@ impl<'a> Cookies<'a> {
     /// }
     /// ```
     pub fn remove(&mut self, cookie: Cookie<'static>) {
-        if let Cookies::Jarred(ref mut jar, _) = *self {
+        if let Cookies::Jarred(ref mut jar, _, _, ref mut order) = *self {
+            Cookies::changed(order, cookie.name());
             jar.remove(cookie)
         }
     }
@@ -312,12 +390,13 @@ impl<'a> Cookies<'a> {
     /// }
     /// ```
     pub fn remove_private(&mut self, mut cookie: Cookie<'static>) {
-        if let Cookies::Jarred(ref mut jar, key) = *self {
+        if let Cookies::Jarred(ref mut jar, _, _, ref mut order) = *self {
             if cookie.path().is_none() {
                 cookie.set_path("/");
             }
 
-            jar.private(key).remove(cookie)
+            Cookies::changed(order, cookie.name());
+            jar.remove(cookie)
         }
     }
 
@@ -336,28 +415,190 @@ impl<'a> Cookies<'a> {
     /// ```
     pub fn iter<'s>(&'s self) -> impl Iterator<Item=&'s Cookie<'static>> {
         match *self {
-            Cookies::Jarred(ref jar, _) => jar.iter(),
+            Cookies::Jarred(ref jar, ..) => jar.iter(),
             Cookies::Empty(ref jar) => jar.iter()
         }
     }
 
     pub(crate) fn delta(&self) -> Delta {
         match *self {
-            Cookies::Jarred(ref jar, _) => jar.delta(),
+            Cookies::Jarred(ref jar, ..) => jar.delta(),
             Cookies::Empty(ref jar) => jar.delta()
         }
     }
+
+    /// Returns the changes to this collection, oldest first, each paired with
+    /// whether it's a removal. Returns `None` if the order of any change isn't
+    /// known.
+    pub(crate) fn ordered_delta(&self) -> Option<Vec<(Cookie<'static>, bool)>> {
+        let (jar, order) = match *self {
+            Cookies::Jarred(ref jar, _, _, ref order) => (jar, order),
+            Cookies::Empty(_) => return Some(vec![])
+        };
+
+        let mut delta = vec![];
+        for cookie in jar.delta() {
+            let position = order.iter().position(|name| name == cookie.name())?;
+            let removal = jar.get(cookie.name()).is_none();
+            delta.push((position, cookie.clone(), removal));
+        }
+
+        delta.sort_by_key(|&(position, ..)| position);
+        Some(delta.into_iter().map(|(_, cookie, removal)| (cookie, removal)).collect())
+    }
 }
 
 impl<'a> fmt::Debug for Cookies<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            Cookies::Jarred(ref jar, _) => write!(f, "{:?}", jar),
+            Cookies::Jarred(ref jar, ..) => write!(f, "{:?}", jar),
             Cookies::Empty(ref jar) => write!(f, "{:?}", jar)
         }
     }
 }
 
+/// An authenticated encryption algorithm for private cookies.
+///
+/// The algorithm is selected via the `private_cookie_cipher` configuration
+/// parameter. Both algorithms are equally secure; AES-256-GCM is faster on
+/// CPUs with AES instructions while ChaCha20-Poly1305 is faster on CPUs
+/// without them, such as many embedded ARM processors. See the
+/// [`Cookies`](/rocket/http/enum.Cookies.html#encryption-algorithm)
+/// documentation for how cookies are upgraded when the algorithm changes.
+///
+/// # Format
+///
+/// An encrypted value is the version of the format, a `.`, and the base64
+/// encoding of a random 12-byte nonce followed by the ciphertext and tag. The
+/// cookie's name is the associated data. The versions are:
+///
+///   * `1`: AES-256-GCM, keyed by the encryption half of the `secret_key`
+///   * `2`: ChaCha20-Poly1305, keyed by the key derived from the `secret_key`
+///     for the purpose `rocket/private-cookies/chacha20-poly1305`, as by
+///     [`Config::derived_key()`]
+///
+/// Values without a version are AES-256-GCM, as encrypted by versions of
+/// Rocket that predate the prefix.
+///
+/// [`Config::derived_key()`]: /rocket/config/struct.Config.html#method.derived_key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CookieCipher {
+    /// AES-256 in Galois/Counter Mode. The default.
+    Aes256Gcm,
+    /// ChaCha20-Poly1305, as specified in RFC 7539.
+    ChaCha20Poly1305,
+}
+
+impl Default for CookieCipher {
+    #[inline(always)]
+    fn default() -> CookieCipher {
+        CookieCipher::Aes256Gcm
+    }
+}
+
+impl CookieCipher {
+    /// Returns the name of the algorithm as in the `private_cookie_cipher`
+    /// configuration parameter.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::http::CookieCipher;
+    ///
+    /// assert_eq!(CookieCipher::Aes256Gcm.name(), "AES-256-GCM");
+    /// assert_eq!(CookieCipher::ChaCha20Poly1305.name(), "ChaCha20-Poly1305");
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match *self {
+            CookieCipher::Aes256Gcm => "AES-256-GCM",
+            CookieCipher::ChaCha20Poly1305 => "ChaCha20-Poly1305",
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Option<CookieCipher> {
+        if uncased_eq(name, "AES-256-GCM") {
+            Some(CookieCipher::Aes256Gcm)
+        } else if uncased_eq(name, "ChaCha20-Poly1305") {
+            Some(CookieCipher::ChaCha20Poly1305)
+        } else {
+            None
+        }
+    }
+
+    fn version(&self) -> &'static str {
+        match *self {
+            CookieCipher::Aes256Gcm => "1",
+            CookieCipher::ChaCha20Poly1305 => "2",
+        }
+    }
+
+    fn algorithm(&self) -> &'static Algorithm {
+        match *self {
+            CookieCipher::Aes256Gcm => &AES_256_GCM,
+            CookieCipher::ChaCha20Poly1305 => &CHACHA20_POLY1305,
+        }
+    }
+
+    /// Returns the key this algorithm encrypts with under `key`. Each
+    /// algorithm has its own so that no key is used with two algorithms.
+    fn key(&self, key: &Key) -> Secret<Vec<u8>> {
+        match *self {
+            CookieCipher::Aes256Gcm => Secret::new(key.encryption().to_vec()),
+            CookieCipher::ChaCha20Poly1305 => {
+                let derived = derive_key(key, "rocket/private-cookies/chacha20-poly1305");
+                Secret::new(derived.expose().to_vec())
+            }
+        }
+    }
+
+    /// Encrypts `value`, the value of the cookie named `name`, under `key`.
+    pub(crate) fn seal(&self, key: &Key, name: &str, value: &str) -> String {
+        let key = SealingKey::new(self.algorithm(), self.key(key).expose()).expect("256-bit key");
+        let mut sealed = vec![0u8; NONCE_LEN];
+        SystemRandom::new().fill(&mut sealed).expect("random nonce");
+
+        let tag_len = self.algorithm().tag_len();
+        let mut in_out = value.as_bytes().to_vec();
+        in_out.extend(::std::iter::repeat(0).take(tag_len));
+        let len = aead::seal_in_place(&key, &sealed, name.as_bytes(), &mut in_out, tag_len)
+            .expect("sealing with a valid key and nonce");
+
+        sealed.extend_from_slice(&in_out[..len]);
+        format!("{}.{}", self.version(), base64::encode(&sealed))
+    }
+
+    /// Decrypts `value`, the value of the cookie named `name`, under `key`,
+    /// returning the algorithm it was encrypted with and the plaintext.
+    /// Returns `None` if `value` isn't in a versioned format, or if it fails
+    /// to authenticate or decrypt.
+    pub(crate) fn open(key: &Key, name: &str, value: &str) -> Option<(CookieCipher, String)> {
+        let dot = value.find('.')?;
+        let cipher = match &value[..dot] {
+            "1" => CookieCipher::Aes256Gcm,
+            "2" => CookieCipher::ChaCha20Poly1305,
+            _ => return None
+        };
+
+        let sealed = base64::decode(&value[(dot + 1)..]).ok()?;
+        if sealed.len() < NONCE_LEN + cipher.algorithm().tag_len() {
+            return None;
+        }
+
+        let key = OpeningKey::new(cipher.algorithm(), cipher.key(key).expose()).ok()?;
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let mut in_out = ciphertext.to_vec();
+        let plaintext = aead::open_in_place(&key, nonce, name.as_bytes(), 0, &mut in_out).ok()?;
+        String::from_utf8(plaintext.to_vec()).ok().map(|value| (cipher, value))
+    }
+}
+
+impl fmt::Display for CookieCipher {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 impl<'c> From<Cookie<'c>> for Header<'static> {
     fn from(cookie: Cookie) -> Header<'static> {
         Header::new("Set-Cookie", cookie.encoded().to_string())
@@ -369,3 +610,48 @@ impl<'a, 'c> From<&'a Cookie<'c>> for Header<'static> {
         Header::new("Set-Cookie", cookie.encoded().to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::{Cookie, CookieJar, Cookies, CookieCipher, Key};
+
+    #[test]
+    fn test_cipher_round_trip() {
+        let key = Key::generate();
+        for &cipher in &[CookieCipher::Aes256Gcm, CookieCipher::ChaCha20Poly1305] {
+            let sealed = cipher.seal(&key, "name", "value");
+            assert_eq!(CookieCipher::open(&key, "name", &sealed), Some((cipher, "value".into())));
+            assert_eq!(CookieCipher::open(&key, "other", &sealed), None);
+            assert_eq!(CookieCipher::open(&Key::generate(), "name", &sealed), None);
+            assert_ne!(cipher.seal(&key, "name", "value"), sealed);
+        }
+
+        assert_eq!(CookieCipher::open(&key, "name", "value"), None);
+        assert_eq!(CookieCipher::open(&key, "name", "3.AAAA"), None);
+        assert_eq!(CookieCipher::from_name("aes-256-gcm"), Some(CookieCipher::Aes256Gcm));
+        assert_eq!(CookieCipher::from_name("AES-256-CBC"), None);
+    }
+
+    #[test]
+    fn test_legacy_cookies_are_upgraded() {
+        let key = Key::generate();
+        let mut legacy = CookieJar::new();
+        legacy.private(&key).add(Cookie::new("name", "value"));
+        let sealed = legacy.get("name").unwrap().value().to_string();
+
+        let jar = RefCell::new(CookieJar::new());
+        let order = RefCell::new(vec![]);
+        jar.borrow_mut().add_original(Cookie::new("name", sealed));
+        let cipher = CookieCipher::Aes256Gcm;
+        let mut cookies = Cookies::new(jar.borrow_mut(), &key, cipher, order.borrow_mut());
+        assert_eq!(cookies.get_private("name").unwrap().value(), "value");
+
+        let upgraded = cookies.get("name").unwrap().value().to_string();
+        assert!(upgraded.starts_with("1."));
+        assert_eq!(cookies.delta().count(), 1);
+        assert_eq!(cookies.get_private("name").unwrap().value(), "value");
+        assert_eq!(cookies.delta().count(), 1);
+    }
+}
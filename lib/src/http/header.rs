@@ -1,10 +1,71 @@
 use std::borrow::{Borrow, Cow};
+use std::error::Error;
 use std::fmt;
 
 use indexmap::IndexMap;
 
 use http::uncased::{Uncased, UncasedStr};
 
+/// The error returned when a header could not be written as-is: written out,
+/// it would corrupt the response or inject headers or content into it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeaderError {
+    /// The header's name is empty.
+    EmptyName,
+    /// The header's name contains a character, at byte index `index`, that
+    /// isn't allowed in a name. Names are tokens: ASCII letters, digits, and
+    /// any of ``!#$%&'*+-.^_`|~``.
+    InvalidName {
+        /// The header's name.
+        name: String,
+        /// The byte index of the first disallowed character.
+        index: usize
+    },
+    /// The header's value contains a control character, such as a carriage
+    /// return or line feed, at byte index `index`. Only horizontal tabs are
+    /// allowed.
+    InvalidValue {
+        /// The header's name.
+        name: String,
+        /// The byte index of the first control character.
+        index: usize
+    },
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HeaderError::EmptyName => write!(f, "header name is empty"),
+            HeaderError::InvalidName { ref name, index } => {
+                write!(f, "header name {:?} has an invalid character at {}", name, index)
+            }
+            HeaderError::InvalidValue { ref name, index } => {
+                write!(f, "value of header {:?} has a control character at {}", name, index)
+            }
+        }
+    }
+}
+
+impl Error for HeaderError {
+    fn description(&self) -> &str {
+        match *self {
+            HeaderError::EmptyName => "empty header name",
+            HeaderError::InvalidName { .. } => "invalid character in header name",
+            HeaderError::InvalidValue { .. } => "control character in header value",
+        }
+    }
+}
+
+#[inline]
+fn is_token_char(byte: u8) -> bool {
+    match byte {
+        b'a'...b'z' | b'A'...b'Z' | b'0'...b'9' => true,
+        b'!' | b'#' | b'$' | b'%' | b'&' | b'\'' | b'*' | b'+' | b'-' | b'.' => true,
+        b'^' | b'_' | b'`' | b'|' | b'~' => true,
+        _ => false
+    }
+}
+
 /// Simple representation of an HTTP header.
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct Header<'h> {
@@ -42,6 +103,15 @@ impl<'h> Header<'h> {
     /// let header = Header::new("X-Custom-Header", value);
     /// assert_eq!(header.to_string(), "X-Custom-Header: custom value");
     /// ```
+    ///
+    /// The name and value aren't checked. A header whose name or value could
+    /// split a response, as one containing a line break would, causes the
+    /// response to be replaced with a `500` error when it's written, counted
+    /// in the `responses.invalid_headers` metric. To check a name or value
+    /// that came from a client or other untrusted source when the header is
+    /// constructed, use [`Header::try_new()`].
+    ///
+    /// [`Header::try_new()`]: /rocket/http/struct.Header.html#method.try_new
     #[inline(always)]
     pub fn new<'a: 'h, 'b: 'h, N, V>(name: N, value: V) -> Header<'h>
         where N: Into<Cow<'a, str>>, V: Into<Cow<'b, str>>
@@ -52,6 +122,68 @@ impl<'h> Header<'h> {
         }
     }
 
+    /// Constructs a new header after checking that it can be written as-is:
+    /// `name` must be a non-empty token, and `value` must not contain control
+    /// characters other than horizontal tabs. Otherwise, returns a
+    /// [`HeaderError`] describing the first problem.
+    ///
+    /// [`HeaderError`]: /rocket/http/enum.HeaderError.html
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::http::{Header, HeaderError};
+    ///
+    /// let header = Header::try_new("X-Custom-Header", "custom value");
+    /// assert_eq!(header.unwrap().value(), "custom value");
+    ///
+    /// let header = Header::try_new("X-Custom-Header", "value\r\nSet-Cookie: a=b");
+    /// let error = HeaderError::InvalidValue { name: "X-Custom-Header".into(), index: 5 };
+    /// assert_eq!(header, Err(error));
+    ///
+    /// assert!(Header::try_new("X Custom", "value").is_err());
+    /// assert!(Header::try_new("", "value").is_err());
+    /// ```
+    #[inline]
+    pub fn try_new<'a: 'h, 'b: 'h, N, V>(name: N, value: V) -> Result<Header<'h>, HeaderError>
+        where N: Into<Cow<'a, str>>, V: Into<Cow<'b, str>>
+    {
+        let header = Header::new(name, value);
+        header.validate()?;
+        Ok(header)
+    }
+
+    /// Checks that this header can be written as-is, as
+    /// [`Header::try_new()`] does.
+    ///
+    /// [`Header::try_new()`]: /rocket/http/struct.Header.html#method.try_new
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::http::Header;
+    ///
+    /// assert!(Header::new("Location", "/home").validate().is_ok());
+    /// assert!(Header::new("Location", "/home\n").validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), HeaderError> {
+        let name = self.name.as_str();
+        if name.is_empty() {
+            return Err(HeaderError::EmptyName);
+        }
+
+        if let Some(index) = name.bytes().position(|b| !is_token_char(b)) {
+            return Err(HeaderError::InvalidName { name: name.to_string(), index: index });
+        }
+
+        let control = |b: u8| (b < 0x20 && b != b'\t') || b == 0x7F;
+        if let Some(index) = self.value.bytes().position(control) {
+            return Err(HeaderError::InvalidValue { name: name.to_string(), index: index });
+        }
+
+        Ok(())
+    }
+
     /// Returns the name of this header with casing preserved. To do a
     /// case-insensitive equality check, use `.name` directly.
     ///
@@ -546,6 +678,28 @@ impl<'h> HeaderMap<'h> {
         })
     }
 
+    /// Checks that every header in the map can be written as-is, as
+    /// [`Header::validate()`] does, returning the error for the first that
+    /// can't.
+    ///
+    /// [`Header::validate()`]: /rocket/http/struct.Header.html#method.validate
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::http::HeaderMap;
+    ///
+    /// let mut map = HeaderMap::new();
+    /// map.add_raw("X-Custom", "value");
+    /// assert!(map.validate().is_ok());
+    ///
+    /// map.add_raw("X-Other", "value\r\n\r\n<script>");
+    /// assert!(map.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), HeaderError> {
+        self.iter().map(|header| header.validate()).collect()
+    }
+
     /// Consumes `self` and returns an iterator over all of the `Header`s stored
     /// in the map. Header names are returned in no specific order, but all
     /// values for a given header name are grouped together, and values are in
@@ -607,7 +761,7 @@ impl<'h> HeaderMap<'h> {
 
 #[cfg(test)]
 mod tests {
-    use super::HeaderMap;
+    use super::{Header, HeaderMap, HeaderError};
 
     #[test]
     fn case_insensitive_add_get() {
@@ -631,4 +785,25 @@ mod tests {
         let vals: Vec<_> = map.get("x-CuStOm").collect();
         assert_eq!(vals, vec!["a", "b", "c"]);
     }
+
+    #[test]
+    fn control_characters_are_rejected() {
+        let values = [("a\rb", 1), ("a\nb", 1), ("\r\n", 0), ("a\0", 1), ("\x1b", 0), ("a\x7f", 1)];
+        for &(value, index) in &values {
+            let error = HeaderError::InvalidValue { name: "X-Custom".into(), index: index };
+            assert_eq!(Header::try_new("X-Custom", value), Err(error));
+        }
+
+        assert!(Header::try_new("X-Custom", "a\tb, \"c\"; d=é").is_ok());
+        assert!(Header::try_new("X-Custom", "").is_ok());
+    }
+
+    #[test]
+    fn names_must_be_tokens() {
+        assert!(Header::try_new("X-Custom_1.a~b!", "v").is_ok());
+        assert_eq!(Header::try_new("", "v"), Err(HeaderError::EmptyName));
+        for name in &["X Custom", "X-Custom:", "X-Custom\r\nSet-Cookie", "(X)", "É"] {
+            assert!(Header::try_new(*name, "v").is_err(), "{:?} was accepted", name);
+        }
+    }
 }
@@ -31,9 +31,10 @@ pub use self::method::Method;
 pub use self::content_type::ContentType;
 pub use self::accept::{Accept, QMediaType};
 pub use self::status::{Status, StatusClass};
-pub use self::header::{Header, HeaderMap};
+pub use self::header::{Header, HeaderMap, HeaderError};
 pub use self::raw_str::RawStr;
 
 pub use self::media_type::MediaType;
-pub use self::cookies::{Cookie, Cookies};
+pub use self::cookies::{Cookie, Cookies, CookieCipher};
+#[cfg(feature = "tls")] pub use rustls::Certificate;
 pub(crate) use self::cookies::{Key, CookieJar};
@@ -0,0 +1,247 @@
+use std::fmt;
+
+/// A constraint on the value of a dynamic path segment.
+///
+/// Constraints are written after the parameter name in a route path, separated
+/// by a `:`, as in `/users/<id:int>` or `/posts/<slug:[a-z0-9-]+>`. A request
+/// is only routed to a route if every constrained segment of the request's
+/// path satisfies the corresponding constraint; otherwise, the route does not
+/// match, and routing continues with the next route.
+///
+/// Because the shape of the segment is verified before routing, a failure to
+/// parse a constrained parameter with `FromParam` (for instance, an integer
+/// that overflows its type) results in a `400 Bad Request` instead of a
+/// forward.
+///
+/// Two routes whose paths differ only in segments with _disjoint_ constraints,
+/// such as `/<id:int>` and `/<name:alpha>`, do not collide, even when they
+/// have the same rank.
+///
+/// # Syntax
+///
+/// The following named constraints are supported:
+///
+///   * **int**: an optionally negative integer: `-?[0-9]+`
+///   * **uint**: a non-negative integer: `[0-9]+`
+///   * **alpha**: ASCII letters: `[a-zA-Z]+`
+///   * **alnum**: ASCII letters and digits: `[a-zA-Z0-9]+`
+///   * **hex**: hexadecimal digits: `[0-9a-fA-F]+`
+///   * **uuid**: a hyphenated UUID, such as
+///     `67e55044-10b1-426f-9247-bb680e5fe0c8`
+///
+/// Additionally, a constraint may be a single bracketed character class with
+/// an optional quantifier. A class contains characters and ranges of
+/// characters, such as `[a-z0-9_-]`; a `-` is literal when it is the first or
+/// last character in the class. The quantifier is one of `+` (one or more, the
+/// default), `*` (zero or more), `{n}` (exactly `n`), or `{n,m}` (between `n`
+/// and `m`, inclusive). `/`, `?`, `#`, `<`, and `>` may not appear in a
+/// constraint.
+///
+/// Constraints are checked against the percent-decoded value of a segment.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::http::uri::Constraint;
+///
+/// let int = Constraint::parse("int").unwrap();
+/// assert!(int.matches("-120"));
+/// assert!(!int.matches("12a"));
+///
+/// let year = Constraint::parse("[0-9]{4}").unwrap();
+/// assert!(year.matches("2018"));
+/// assert!(!year.matches("18"));
+///
+/// let slug = Constraint::parse("[a-z-]+").unwrap();
+/// assert!(slug.is_disjoint(&int) == false);
+/// assert!(Constraint::parse("alpha").unwrap().is_disjoint(&int));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraint {
+    source: String,
+    kind: Kind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Kind {
+    Int,
+    Uuid,
+    Class(Vec<(char, char)>, usize, Option<usize>),
+}
+
+const DIGITS: &'static [(char, char)] = &[('0', '9')];
+const ALPHA: &'static [(char, char)] = &[('A', 'Z'), ('a', 'z')];
+const ALNUM: &'static [(char, char)] = &[('0', '9'), ('A', 'Z'), ('a', 'z')];
+const HEX: &'static [(char, char)] = &[('0', '9'), ('A', 'F'), ('a', 'f')];
+
+fn in_class(class: &[(char, char)], c: char) -> bool {
+    class.iter().any(|&(lo, hi)| c >= lo && c <= hi)
+}
+
+fn parse_class(string: &str) -> Result<Vec<(char, char)>, String> {
+    let chars: Vec<char> = string.chars().collect();
+    if chars.is_empty() {
+        return Err("character classes cannot be empty".into());
+    }
+
+    let mut class = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\\' || (c == '^' && i == 0) {
+            return Err(format!("unsupported character '{}' in class", c));
+        }
+
+        if i + 2 < chars.len() && chars[i + 1] == '-' {
+            let (lo, hi) = (c, chars[i + 2]);
+            if lo > hi {
+                return Err(format!("invalid range '{}-{}'", lo, hi));
+            }
+
+            class.push((lo, hi));
+            i += 3;
+        } else {
+            class.push((c, c));
+            i += 1;
+        }
+    }
+
+    Ok(class)
+}
+
+fn parse_quantifier(string: &str) -> Result<(usize, Option<usize>), String> {
+    let invalid = || format!("invalid quantifier '{}'", string);
+    match string {
+        "" | "+" => Ok((1, None)),
+        "*" => Ok((0, None)),
+        _ if string.starts_with('{') && string.ends_with('}') => {
+            let inner = &string[1..(string.len() - 1)];
+            let mut bounds = inner.splitn(2, ',').map(|s| s.trim().parse::<usize>());
+            match (bounds.next(), bounds.next()) {
+                (Some(Ok(n)), None) => Ok((n, Some(n))),
+                (Some(Ok(n)), Some(Ok(m))) if n <= m => Ok((n, Some(m))),
+                _ => Err(invalid())
+            }
+        }
+        _ => Err(invalid())
+    }
+}
+
+impl Constraint {
+    /// Parses a constraint from its textual form. Returns a description of the
+    /// problem if `string` is not a valid constraint.
+    pub fn parse(string: &str) -> Result<Constraint, String> {
+        if let Some(c) = string.chars().find(|&c| "/?#<>".contains(c)) {
+            return Err(format!("'{}' is not allowed in a constraint", c));
+        }
+
+        let kind = match string {
+            "int" => Kind::Int,
+            "uint" => Kind::Class(DIGITS.to_vec(), 1, None),
+            "alpha" => Kind::Class(ALPHA.to_vec(), 1, None),
+            "alnum" => Kind::Class(ALNUM.to_vec(), 1, None),
+            "hex" => Kind::Class(HEX.to_vec(), 1, None),
+            "uuid" => Kind::Uuid,
+            _ if string.starts_with('[') => {
+                let end = match string.rfind(']') {
+                    Some(end) if end > 0 => end,
+                    _ => return Err("character class is missing a closing ']'".into())
+                };
+
+                let class = parse_class(&string[1..end])?;
+                let (min, max) = parse_quantifier(&string[(end + 1)..])?;
+                Kind::Class(class, min, max)
+            }
+            _ => return Err(format!("unknown constraint '{}'", string))
+        };
+
+        Ok(Constraint { source: string.to_string(), kind: kind })
+    }
+
+    /// Returns `true` if `value` satisfies this constraint.
+    pub fn matches(&self, value: &str) -> bool {
+        match self.kind {
+            Kind::Int => {
+                let digits = if value.starts_with('-') { &value[1..] } else { value };
+                !digits.is_empty() && digits.chars().all(|c| in_class(DIGITS, c))
+            }
+            Kind::Uuid => {
+                value.len() == 36 && value.char_indices().all(|(i, c)| match i {
+                    8 | 13 | 18 | 23 => c == '-',
+                    _ => in_class(HEX, c)
+                })
+            }
+            Kind::Class(ref class, min, max) => {
+                let count = value.chars().count();
+                count >= min
+                    && max.map_or(true, |max| count <= max)
+                    && value.chars().all(|c| in_class(class, c))
+            }
+        }
+    }
+
+    // The characters that may appear in a matching value and the bounds on the
+    // length of a matching value, in characters.
+    fn shape(&self) -> (Vec<(char, char)>, usize, Option<usize>) {
+        match self.kind {
+            Kind::Int => (vec![('-', '-'), ('0', '9')], 1, None),
+            Kind::Uuid => (vec![('-', '-'), ('0', '9'), ('A', 'F'), ('a', 'f')], 36, Some(36)),
+            Kind::Class(ref class, min, max) => (class.clone(), min, max),
+        }
+    }
+
+    /// Returns `true` if no value can satisfy both `self` and `other`. This is
+    /// conservative: a return value of `false` does not imply that such a
+    /// value exists.
+    pub fn is_disjoint(&self, other: &Constraint) -> bool {
+        let (class_a, min_a, max_a) = self.shape();
+        let (class_b, min_b, max_b) = other.shape();
+
+        // The lengths of matching values can't overlap.
+        if max_a.map_or(false, |max| max < min_b) || max_b.map_or(false, |max| max < min_a) {
+            return true;
+        }
+
+        // The values must be non-empty and share no characters.
+        let overlap = class_a.iter()
+            .any(|&(lo_a, hi_a)| class_b.iter().any(|&(lo_b, hi_b)| lo_a <= hi_b && lo_b <= hi_a));
+
+        min_a > 0 && min_b > 0 && !overlap
+    }
+
+    /// Splits the text of a dynamic segment, without its enclosing `<` and
+    /// `>`, into the parameter name and its constraint, if any.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::http::uri::Constraint;
+    ///
+    /// assert_eq!(Constraint::split_param("id:int"), ("id", Some("int")));
+    /// assert_eq!(Constraint::split_param("id"), ("id", None));
+    /// ```
+    pub fn split_param(param: &str) -> (&str, Option<&str>) {
+        match param.find(':') {
+            Some(i) => (&param[..i], Some(&param[(i + 1)..])),
+            None => (param, None)
+        }
+    }
+
+    /// Returns the constraint of the route path segment `segment`, if it is a
+    /// dynamic segment with a valid constraint.
+    #[doc(hidden)]
+    pub fn of_segment(segment: &str) -> Option<Constraint> {
+        if !segment.starts_with('<') || !segment.ends_with('>') || segment.ends_with("..>") {
+            return None;
+        }
+
+        let (_, constraint) = Constraint::split_param(&segment[1..(segment.len() - 1)]);
+        constraint.and_then(|c| Constraint::parse(c).ok())
+    }
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.source.fmt(f)
+    }
+}
@@ -3,7 +3,9 @@
 mod uri;
 mod uri_display;
 mod from_uri_param;
+mod constraint;
 
 pub use self::uri::*;
 pub use self::uri_display::*;
 pub use self::from_uri_param::*;
+pub use self::constraint::Constraint;
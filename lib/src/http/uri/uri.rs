@@ -27,7 +27,7 @@ impl<'a> Uri<'a> {
     /// absolute, well formed URI.
     pub fn new<T: Into<Cow<'a, str>>>(uri: T) -> Uri<'a> {
         let uri = uri.into();
-        let qmark = uri.find('?');
+        let qmark = find_query_mark(&uri);
         let hmark = uri.find('#');
 
         let end = uri.len();
@@ -263,6 +263,73 @@ impl<'a> Uri<'a> {
         ::percent_encoding::utf8_percent_encode(string, set).into()
     }
 
+    /// Returns a normalized form of this URI, suitable for comparing URIs or
+    /// keying caches: without empty segments or a fragment, with the
+    /// percent-encoded unreserved characters (`A-Z`, `a-z`, `0-9`, `-`, `.`,
+    /// `_`, and `~`) decoded, and with other percent-encodings in uppercase.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::http::uri::Uri;
+    ///
+    /// let uri = Uri::new("//a/%62%2f//c?x=%7e%2a#end");
+    /// assert_eq!(uri.normalized(), "/a/b%2F/c?x=~%2A");
+    /// assert_eq!(Uri::new("/a/b%2F/c/?x=~%2A").normalized(), uri.normalized());
+    /// ```
+    pub fn normalized(&self) -> String {
+        fn normalize(string: &str, out: &mut Vec<u8>) {
+            let bytes = string.as_bytes();
+            let mut i = 0;
+            while i < bytes.len() {
+                let decoded = match (bytes[i], bytes.get(i + 1), bytes.get(i + 2)) {
+                    (b'%', Some(&h), Some(&l)) => match (hex(h), hex(l)) {
+                        (Some(h), Some(l)) => Some((h << 4) | l),
+                        _ => None
+                    },
+                    _ => None
+                };
+
+                match decoded {
+                    Some(b) if is_unreserved(b) => out.push(b),
+                    Some(b) => out.extend_from_slice(format!("%{:02X}", b).as_bytes()),
+                    None => {
+                        out.push(bytes[i]);
+                        i += 1;
+                        continue;
+                    }
+                }
+
+                i += 3;
+            }
+        }
+
+        fn hex(b: u8) -> Option<u8> {
+            (b as char).to_digit(16).map(|d| d as u8)
+        }
+
+        fn is_unreserved(b: u8) -> bool {
+            b.is_ascii_alphanumeric() || b"-._~".contains(&b)
+        }
+
+        let mut out = vec![];
+        if self.segment_count() == 0 {
+            out.push(b'/');
+        }
+
+        for segment in self.segments() {
+            out.push(b'/');
+            normalize(segment, &mut out);
+        }
+
+        if let Some(query) = self.query() {
+            out.push(b'?');
+            normalize(query, &mut out);
+        }
+
+        String::from_utf8(out).expect("normalized URIs are UTF-8")
+    }
+
     /// Returns the inner string of this URI.
     ///
     /// The returned string is in raw form. It contains empty segments. If you'd
@@ -327,6 +394,26 @@ impl From<String> for Uri<'static> {
     }
 }
 
+// Returns the index of the `?` that begins the query part of `uri`, if any. A
+// `?` that marks an optional dynamic segment in a route path, as in `<name?>`
+// or `<name?:int>`, is not the beginning of a query.
+fn find_query_mark(uri: &str) -> Option<usize> {
+    let bytes = uri.as_bytes();
+    let mut in_param = false;
+    for (i, &c) in bytes.iter().enumerate() {
+        match c {
+            b'<' => in_param = true,
+            b'>' | b'/' => in_param = false,
+            b'?' if in_param && i + 1 < bytes.len()
+                && (bytes[i + 1] == b'>' || bytes[i + 1] == b':') => continue,
+            b'?' => return Some(i),
+            _ => continue
+        }
+    }
+
+    None
+}
+
 impl<'a> fmt::Display for Uri<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // If this is the root path, then there are "zero" segments.
@@ -563,6 +650,16 @@ mod tests {
         test_query("/?", Some(""));
         test_query("?#", Some(""));
         test_query("/?hi", Some("hi"));
+        test_query("/<a?>?<b>", Some("<b>"));
+        test_query("/<a?:int>?b", Some("b"));
+        test_query("/<a?b", Some("b"));
+    }
+
+    #[test]
+    fn optional_param_is_not_query() {
+        test_query("/<a?>", None);
+        test_query("/a/<b?>/<c?:int>", None);
+        assert_eq!(Uri::new("/a/<b?>/<c?>").segment_count(), 3);
     }
 
     #[test]
@@ -597,4 +694,18 @@ mod tests {
         assert_eq!(uri_to_string("/a///b/c/d///"), "/a/b/c/d".to_string());
         assert_eq!(uri_to_string("/a/b/c#a?123"), "/a/b/c#a?123".to_string());
     }
+
+    #[test]
+    fn normalized() {
+        let normalized = |string| Uri::new(string).normalized();
+
+        assert_eq!(normalized(""), "/");
+        assert_eq!(normalized("//"), "/");
+        assert_eq!(normalized("/a//b/"), "/a/b");
+        assert_eq!(normalized("/a#frag"), "/a");
+        assert_eq!(normalized("/%61%5a%30%2d%2E%5f%7E"), "/aZ0-._~");
+        assert_eq!(normalized("/%2f%e2%9c%93?%3d%3D"), "/%2F%E2%9C%93?%3D%3D");
+        assert_eq!(normalized("/a%?b=%zz%4"), "/a%?b=%zz%4");
+        assert_eq!(normalized("/a?"), "/a?");
+    }
 }
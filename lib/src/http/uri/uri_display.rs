@@ -83,6 +83,45 @@ use self::priv_encode_set::PATH_ENCODE_SET;
 ///
 ///     Uses the implementation of `UriDisplay` for `T`.
 ///
+/// # Deriving
+///
+/// `UriDisplay` can be derived for structures and enums via
+/// `#[derive(UriDisplay)]`. Single-field tuple structures render as their
+/// field, structures with named fields render as a query string, and unit
+/// variants render as their name. Fields and variants can be renamed with
+/// `#[uri(rename = "name")]` and fields omitted with `#[uri(skip)]`:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::http::uri::UriDisplay;
+///
+/// #[derive(UriDisplay)]
+/// enum Order {
+///     #[uri(rename = "asc")]
+///     Ascending,
+///     Descending
+/// }
+///
+/// #[derive(UriDisplay)]
+/// struct Search {
+///     #[uri(rename = "q")]
+///     query: String,
+///     order: Order,
+///     #[uri(skip)]
+///     cached: bool,
+/// }
+///
+/// # fn main() {
+/// let search = Search { query: "a b".into(), order: Order::Ascending, cached: true };
+/// assert_eq!(format!("{}", &search as &UriDisplay), "q=a%20b&order=asc");
+/// # }
+/// ```
+///
+/// The companion `FromUriParam` derive allows such types to be created from
+/// their fields' values directly in `uri!` invocations. See the [code
+/// generation documentation](/rocket_codegen/#uridisplay-and-fromuriparam) for
+/// details.
+///
 /// # Implementing
 ///
 /// Implementing `UriDisplay` is similar to implementing `Display` with the
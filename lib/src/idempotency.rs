@@ -0,0 +1,350 @@
+//! Replay of responses to retried `POST` requests via `Idempotency-Key`.
+//!
+//! Clients that retry a non-idempotent request after a network failure risk
+//! performing the same operation twice. To avoid this, a client may send an
+//! `Idempotency-Key` header with a unique value with every `POST` request,
+//! reusing the same value when it retries. When idempotency handling is enabled
+//! via [`Rocket::idempotency()`], Rocket records the first response to each key
+//! in an [`IdempotencyStore`] and replays it, without invoking any handler, for
+//! retries that arrive within the configured time-to-live.
+//!
+//! Each recorded response carries a fingerprint of the request's method, URI,
+//! and body. A retry whose fingerprint differs from the original request's is
+//! a misuse of the key and is rejected with `422 Unprocessable Entity`. A
+//! retry that arrives while the original request is still being processed is
+//! rejected with `409 Conflict`. Replayed responses carry an
+//! `Idempotent-Replayed: true` header.
+//!
+//! Keys are scoped to the client that sent them: to the request's
+//! `Authorization` header, if it has one, and otherwise to the client's IP
+//! address. A client can't replay the response to another client's request
+//! by guessing or observing its key.
+//!
+//! Responses with a `5xx` status aren't recorded so that the request can be
+//! retried. To fingerprint it, the request body is buffered in memory, up to
+//! the `idempotency` limit, 1MiB by default; larger bodies are rejected with
+//! `413 Payload Too Large`. The same limit bounds the recorded response body:
+//! responses with larger bodies are sent as usual but aren't recorded.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use rocket::idempotency::{Idempotency, MemoryStore};
+//!
+//! # if false {
+//! rocket::ignite()
+//!     .idempotency(Idempotency::new(MemoryStore::new()).ttl(Duration::from_secs(3600)))
+//!     .launch();
+//! # }
+//! ```
+//!
+//! [`Rocket::idempotency()`]: /rocket/struct.Rocket.html#method.idempotency
+//! [`IdempotencyStore`]: /rocket/idempotency/trait.IdempotencyStore.html
+
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Read};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use sha2::{Sha256, Digest};
+use base64;
+
+use request::Request;
+use response::{Body, Response};
+use data::Data;
+use http::{Method, Header, Status};
+use security::constant_time_eq;
+
+/// The default maximum size of a body that is fingerprinted.
+const DEFAULT_BODY_LIMIT: u64 = 1024 * 1024;
+
+/// A response recorded for an idempotency key.
+#[derive(Debug, Clone)]
+pub struct StoredResponse {
+    /// A fingerprint of the request that produced the response.
+    pub fingerprint: String,
+    /// The response's status.
+    pub status: Status,
+    /// The response's headers.
+    pub headers: Vec<Header<'static>>,
+    /// The response's body.
+    pub body: Vec<u8>,
+}
+
+impl StoredResponse {
+    pub(crate) fn to_response<'r>(&self) -> Response<'r> {
+        let mut builder = Response::build();
+        builder.status(self.status);
+        for header in &self.headers {
+            builder.header_adjoin(header.clone());
+        }
+
+        builder.raw_header("Idempotent-Replayed", "true");
+        builder.sized_body(Cursor::new(self.body.clone()));
+        builder.finalize()
+    }
+}
+
+/// Trait implemented by storage backends for recorded responses.
+///
+/// Implementations must be thread-safe: they are accessed concurrently from
+/// all of Rocket's worker threads. Implementations are responsible for
+/// expiring entries after the time-to-live passed to `insert`.
+pub trait IdempotencyStore: Send + Sync + 'static {
+    /// Returns the unexpired response recorded for `key`, if any.
+    fn get(&self, key: &str) -> Option<StoredResponse>;
+
+    /// Records `response` for `key` for at least `ttl`.
+    fn insert(&self, key: String, response: StoredResponse, ttl: Duration);
+}
+
+/// An in-memory, process-local `IdempotencyStore`.
+///
+/// Expired entries are removed lazily as new entries are inserted.
+pub struct MemoryStore {
+    entries: Mutex<HashMap<String, (Instant, StoredResponse)>>,
+}
+
+impl MemoryStore {
+    /// Returns a new, empty `MemoryStore`.
+    pub fn new() -> MemoryStore {
+        MemoryStore { entries: Mutex::new(HashMap::new()) }
+    }
+}
+
+impl IdempotencyStore for MemoryStore {
+    fn get(&self, key: &str) -> Option<StoredResponse> {
+        let entries = self.entries.lock().expect("idempotency store lock");
+        entries.get(key)
+            .and_then(|&(expires, ref response)| match Instant::now() < expires {
+                true => Some(response.clone()),
+                false => None
+            })
+    }
+
+    fn insert(&self, key: String, response: StoredResponse, ttl: Duration) {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().expect("idempotency store lock");
+        entries.retain(|_, &mut (expires, _)| now < expires);
+        entries.insert(key, (now + ttl, response));
+    }
+}
+
+/// Idempotency handling configuration: a store and a time-to-live for the
+/// responses recorded in it.
+pub struct Idempotency {
+    store: Box<IdempotencyStore>,
+    ttl: Duration,
+    in_flight: Mutex<HashSet<String>>,
+}
+
+/// The outcome of looking up a request's idempotency key.
+pub(crate) enum Lookup<'i> {
+    /// The request has no key; process it normally.
+    None,
+    /// The request is the first with its key; process it and record it.
+    Record(Pending<'i>),
+    /// The request is a retry; reply with the recorded response.
+    Replay(StoredResponse),
+    /// The request can't be processed.
+    Reject(Status),
+}
+
+/// A request being processed whose response will be recorded. Releases the
+/// key when dropped, so that a panicking handler doesn't lock the key out.
+pub(crate) struct Pending<'i> {
+    idempotency: &'i Idempotency,
+    key: String,
+    fingerprint: String,
+    limit: u64,
+}
+
+impl Idempotency {
+    /// Returns a new `Idempotency` configuration that records responses in
+    /// `store` for 24 hours.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::idempotency::{Idempotency, MemoryStore};
+    ///
+    /// let idempotency = Idempotency::new(MemoryStore::new());
+    /// ```
+    pub fn new<S: IdempotencyStore>(store: S) -> Idempotency {
+        Idempotency {
+            store: Box::new(store),
+            ttl: Duration::from_secs(24 * 60 * 60),
+            in_flight: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Sets the time-to-live of recorded responses to `ttl`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use rocket::idempotency::{Idempotency, MemoryStore};
+    ///
+    /// let idempotency = Idempotency::new(MemoryStore::new())
+    ///     .ttl(Duration::from_secs(600));
+    /// ```
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Looks up the idempotency key of `request`, if any. Buffers `data` to
+    /// fingerprint it; the returned `Data` replaces it.
+    pub(crate) fn lookup<'i>(
+        &'i self,
+        request: &Request,
+        data: Data,
+        limit: Option<u64>
+    ) -> (Data, Lookup<'i>) {
+        let key = match request.headers().get_one("Idempotency-Key") {
+            Some(key) if request.method() == Method::Post => key.trim(),
+            _ => return (data, Lookup::None)
+        };
+
+        if key.is_empty() || key.len() > 255 {
+            warn_!("Invalid idempotency key.");
+            return (Data::local(vec![]), Lookup::Reject(Status::BadRequest));
+        }
+
+        let limit = limit.unwrap_or(DEFAULT_BODY_LIMIT);
+        let mut body = vec![];
+        if let Err(e) = data.open().take(limit + 1).read_to_end(&mut body) {
+            error_!("Failed to read body for idempotency fingerprint: {:?}", e);
+            return (Data::local(vec![]), Lookup::Reject(Status::BadRequest));
+        }
+
+        if body.len() as u64 > limit {
+            warn_!("Body exceeds the idempotency limit of {} bytes.", limit);
+            return (Data::local(vec![]), Lookup::Reject(Status::PayloadTooLarge));
+        }
+
+        let fingerprint = {
+            let mut hasher = Sha256::default();
+            hasher.input(request.method().as_str().as_bytes());
+            hasher.input(b" ");
+            hasher.input(request.uri().as_str().as_bytes());
+            hasher.input(b"\n");
+            hasher.input(&body);
+            base64::encode(&hasher.result())
+        };
+
+        // The key is reserved before the store is queried so that concurrent
+        // requests can't both run the handler. The store, which may be slow,
+        // is queried without holding the lock; a request that loses the race
+        // for the key is only in conflict if there's no recorded response.
+        let key = scoped_key(request, key);
+        let data = Data::local(body);
+        let reserved = self.in_flight.lock().expect("in-flight lock").insert(key.clone());
+        let pending = match reserved {
+            true => Some(Pending {
+                idempotency: self,
+                key: key.clone(),
+                fingerprint: fingerprint.clone(),
+                limit: limit
+            }),
+            false => None
+        };
+
+        // Dropping `pending` on replay or reject releases the reservation.
+        if let Some(recorded) = self.store.get(&key) {
+            if !constant_time_eq(recorded.fingerprint.as_bytes(), fingerprint.as_bytes()) {
+                warn_!("Idempotency key reused with a different request.");
+                return (data, Lookup::Reject(Status::UnprocessableEntity));
+            }
+
+            info_!("Replaying response for idempotency key.");
+            return (data, Lookup::Replay(recorded));
+        }
+
+        match pending {
+            Some(pending) => (data, Lookup::Record(pending)),
+            None => {
+                warn_!("Request with this idempotency key is already in progress.");
+                (data, Lookup::Reject(Status::Conflict))
+            }
+        }
+    }
+}
+
+/// Returns `key` scoped to the client that sent `request`: to its
+/// `Authorization` header, if it has one, and otherwise to its IP address.
+fn scoped_key(request: &Request, key: &str) -> String {
+    if let Some(authorization) = request.headers().get_one("Authorization") {
+        let digest = Sha256::digest(authorization.as_bytes());
+        return format!("auth:{} {}", base64::encode(&digest), key);
+    }
+
+    match request.client_ip() {
+        Some(ip) => format!("ip:{} {}", ip, key),
+        None => format!("anonymous {}", key)
+    }
+}
+
+impl<'i> Pending<'i> {
+    /// Records `response`, buffering its body, unless it is a server error or
+    /// its body exceeds the limit. A body that isn't recorded is left intact.
+    pub(crate) fn record(self, response: &mut Response) {
+        if response.status().code >= 500 {
+            return;
+        }
+
+        let body = match response.take_body() {
+            Some(Body::Sized(body, size)) if size > self.limit => {
+                warn_!("Response exceeds the idempotency limit of {} bytes.", self.limit);
+                response.set_raw_body(Body::Sized(body, size));
+                return;
+            }
+            Some(body) => {
+                let kind = match body {
+                    Body::Sized(_, size) => Body::Sized((), size),
+                    Body::Chunked(_, chunk_size) => Body::Chunked((), chunk_size),
+                };
+
+                let mut reader = body.into_inner();
+                let mut buffer = vec![];
+                let result = (&mut reader).take(self.limit + 1).read_to_end(&mut buffer);
+                if result.is_err() || buffer.len() as u64 > self.limit {
+                    match result {
+                        Ok(_) => warn_!("Response exceeds the idempotency limit of {} bytes.",
+                                        self.limit),
+                        Err(e) => error_!("Failed to read response to record: {:?}", e),
+                    }
+
+                    let rest = Cursor::new(buffer).chain(reader);
+                    response.set_raw_body(kind.map(|_| rest));
+                    return;
+                }
+
+                buffer
+            }
+            None => vec![]
+        };
+
+        response.set_sized_body(Cursor::new(body.clone()));
+
+        let recorded = StoredResponse {
+            fingerprint: self.fingerprint.clone(),
+            status: response.status(),
+            headers: response.headers().iter()
+                .map(|h| Header::new(h.name().to_string(), h.value().to_string()))
+                .collect(),
+            body: body,
+        };
+
+        let idempotency = self.idempotency;
+        idempotency.store.insert(self.key.clone(), recorded, idempotency.ttl);
+    }
+}
+
+impl<'i> Drop for Pending<'i> {
+    fn drop(&mut self) {
+        self.idempotency.in_flight.lock().expect("in-flight lock").remove(&self.key);
+    }
+}
@@ -104,20 +104,34 @@
 #[macro_use] extern crate log;
 #[macro_use] extern crate pear;
 #[cfg(feature = "tls")] extern crate rustls;
+#[cfg(feature = "tls")] extern crate webpki;
 #[cfg(feature = "tls")] extern crate hyper_sync_rustls;
+#[cfg(feature = "regex")] extern crate regex;
+#[cfg(feature = "json")] extern crate serde_json;
+#[cfg(feature = "password")] extern crate argon2;
+#[cfg(feature = "password")] extern crate bcrypt;
+#[cfg(feature = "gzip")] extern crate flate2;
+#[cfg(feature = "brotli")] extern crate brotli;
+#[cfg(feature = "zstd")] extern crate zstd;
+#[cfg(feature = "plugins")] extern crate libloading;
 #[macro_use] extern crate percent_encoding;
 extern crate yansi;
 extern crate hyper;
 extern crate toml;
+extern crate serde;
 extern crate num_cpus;
 extern crate state;
 extern crate cookie;
 extern crate time;
 extern crate memchr;
 extern crate base64;
+extern crate md5;
+extern crate sha2;
+extern crate ring;
 extern crate smallvec;
 extern crate indexmap;
 extern crate isatty;
+extern crate net2;
 
 #[cfg(test)] #[macro_use] extern crate lazy_static;
 
@@ -133,15 +147,36 @@ pub mod data;
 pub mod handler;
 pub mod fairing;
 pub mod error;
+pub mod metrics;
+pub mod idempotency;
+pub mod alert;
+pub mod security;
+#[cfg(feature = "oauth2")] pub mod oauth2;
+#[cfg(feature = "password")] pub mod password;
+#[cfg(feature = "envelope")] pub mod envelope;
+#[cfg(feature = "plugins")] pub mod plugin;
+pub mod api_key;
+pub mod replay;
+pub mod features;
+pub mod versioning;
+pub mod admin;
+pub mod summary;
+pub mod compression;
+pub mod tls;
+pub mod dynamic;
+pub mod dev;
 
 mod router;
 mod rocket;
 mod codegen;
 mod catcher;
+mod coalesce;
+mod listener;
 mod ext;
+#[cfg(feature = "tls")] mod x509;
 
 #[doc(inline)] pub use response::Response;
-#[doc(inline)] pub use handler::{Handler, ErrorHandler};
+#[doc(inline)] pub use handler::{Handler, HeadLength, ErrorHandler};
 #[doc(hidden)] pub use codegen::{StaticRouteInfo, StaticCatchInfo};
 #[doc(inline)] pub use outcome::Outcome;
 #[doc(inline)] pub use data::Data;
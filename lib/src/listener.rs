@@ -0,0 +1,683 @@
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, SocketAddr, Shutdown, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use net2::TcpBuilder;
+#[cfg(feature = "tls")] use state::Storage;
+
+use config::Config;
+use metrics::{Metrics, Counter};
+use http::hyper;
+use http::hyper::net::{HttpListener, HttpStream, NetworkListener, NetworkStream, SslServer};
+#[cfg(feature = "tls")] use tls::{PeerCertificates, TlsInfo};
+
+/// The largest `max_header_size` Hyper can honor. Hyper buffers at most
+/// 417,792 bytes of a request head and drops the connection beyond that, so
+/// larger heads must be caught before Hyper sees the whole buffer.
+pub(crate) const MAX_HEADER_SIZE: u32 = 400 * 1024;
+
+/// The largest `max_header_count` Hyper can honor: Hyper parses at most 100
+/// headers and drops the connection beyond that.
+pub(crate) const MAX_HEADER_COUNT: u32 = 100;
+
+/// The response written to connections whose request head is too large.
+const HEADERS_TOO_LARGE: &[u8] = b"HTTP/1.1 431 Request Header Fields Too Large\r\n\
+    Connection: close\r\nContent-Length: 0\r\n\r\n";
+
+/// Binds a plaintext listener to `addr` according to the connection limits in
+/// `config`.
+pub(crate) fn http(addr: &str, config: &Config, metrics: &Metrics)
+    -> hyper::Result<Listener<Plaintext>>
+{
+    let listener = bind(addr, config.backlog)?;
+    Ok(Listener::new(listener, Plaintext, config, metrics))
+}
+
+/// Binds a TLS listener to `addr` that wraps connections with `ssl` according
+/// to the connection limits in `config`.
+#[cfg(feature = "tls")]
+pub(crate) fn https<S>(addr: &str, config: &Config, metrics: &Metrics, ssl: S)
+    -> hyper::Result<Listener<S>>
+    where S: SslServer<HttpStream> + Clone
+{
+    let listener = bind(addr, config.backlog)?;
+    Ok(Listener::new(listener, ssl, config, metrics))
+}
+
+/// Binds a TCP listener to `addr`. With a `backlog`, at most that many
+/// connections wait to be accepted; otherwise, the system default applies.
+fn bind(addr: &str, backlog: Option<u32>) -> hyper::Result<HttpListener> {
+    let backlog = match backlog {
+        Some(backlog) => backlog,
+        None => return HttpListener::new(addr)
+    };
+
+    let addr = match addr.to_socket_addrs()?.next() {
+        Some(addr) => addr,
+        None => {
+            let msg = "address resolved to no socket addresses";
+            return Err(io::Error::new(io::ErrorKind::AddrNotAvailable, msg).into());
+        }
+    };
+
+    let builder = match addr {
+        SocketAddr::V4(_) => TcpBuilder::new_v4()?,
+        SocketAddr::V6(_) => TcpBuilder::new_v6()?,
+    };
+
+    builder.reuse_address(true)?.bind(addr)?;
+    let backlog = ::std::cmp::min(backlog, i32::max_value() as u32) as i32;
+    Ok(HttpListener::from(builder.listen(backlog)?))
+}
+
+/// An IP network in CIDR notation, as in `10.0.0.0/8`. A plain address is a
+/// network of that address alone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct IpNetwork {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpNetwork {
+    pub(crate) fn parse(string: &str) -> Option<IpNetwork> {
+        let (addr, prefix) = match string.find('/') {
+            Some(i) => (&string[..i], Some(&string[(i + 1)..])),
+            None => (string, None)
+        };
+
+        let addr: IpAddr = addr.parse().ok()?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix.parse::<u8>().ok().filter(|&p| p <= max)?,
+            None => max
+        };
+
+        Some(IpNetwork { addr: addr, prefix: prefix })
+    }
+
+    pub(crate) fn contains(&self, ip: IpAddr) -> bool {
+        fn matches(a: &[u8], b: &[u8], prefix: usize) -> bool {
+            let (bytes, bits) = (prefix / 8, prefix % 8);
+            let mask = !(0xFFu8 >> bits);
+            a[..bytes] == b[..bytes] && (bits == 0 || a[bytes] & mask == b[bytes] & mask)
+        }
+
+        let prefix = self.prefix as usize;
+        match (self.addr, ip) {
+            (IpAddr::V4(a), IpAddr::V4(b)) => matches(&a.octets(), &b.octets(), prefix),
+            (IpAddr::V6(a), IpAddr::V6(b)) => matches(&a.octets(), &b.octets(), prefix),
+            (IpAddr::V4(a), IpAddr::V6(b)) => match b.to_ipv4() {
+                Some(b) => matches(&a.octets(), &b.octets(), prefix),
+                None => false
+            },
+            (IpAddr::V6(_), IpAddr::V4(_)) => false,
+        }
+    }
+}
+
+/// The open connections of a listener, counted against a maximum overall and
+/// a maximum per client IP address. Clients in the `exempt` networks are only
+/// counted against the overall maximum.
+struct Slots {
+    open: AtomicUsize,
+    max: Option<usize>,
+    per_ip: Mutex<HashMap<IpAddr, usize>>,
+    max_per_ip: Option<usize>,
+    exempt: Vec<IpNetwork>,
+    gauge: Counter,
+}
+
+/// An open connection's claim on a slot, and on a slot for its IP address if
+/// it's limited. The slots are freed when dropped.
+struct Slot(Arc<Slots>, Option<IpAddr>);
+
+/// The reason a connection couldn't claim a slot.
+#[derive(Debug, PartialEq)]
+enum Full {
+    Overall,
+    PerIp,
+}
+
+impl Slots {
+    fn claim(slots: &Arc<Slots>, ip: Option<IpAddr>) -> Result<Slot, Full> {
+        let limited_ip = match (slots.max_per_ip, ip) {
+            (Some(max), Some(ip)) if !slots.exempt.iter().any(|net| net.contains(ip)) => {
+                let mut per_ip = slots.per_ip.lock().expect("per-ip lock");
+                let count = per_ip.entry(ip).or_insert(0);
+                if *count >= max {
+                    return Err(Full::PerIp);
+                }
+
+                *count += 1;
+                Some(ip)
+            }
+            _ => None
+        };
+
+        let mut open = slots.open.load(Ordering::SeqCst);
+        loop {
+            if slots.max.map_or(false, |max| open >= max) {
+                if let Some(ip) = limited_ip {
+                    slots.release_ip(ip);
+                }
+
+                return Err(Full::Overall);
+            }
+
+            let prev = slots.open.compare_and_swap(open, open + 1, Ordering::SeqCst);
+            if prev == open {
+                slots.gauge.set(open + 1);
+                return Ok(Slot(slots.clone(), limited_ip));
+            }
+
+            open = prev;
+        }
+    }
+
+    fn release_ip(&self, ip: IpAddr) {
+        let mut per_ip = self.per_ip.lock().expect("per-ip lock");
+        let remaining = match per_ip.get_mut(&ip) {
+            Some(count) => {
+                *count -= 1;
+                *count
+            }
+            None => return
+        };
+
+        if remaining == 0 {
+            per_ip.remove(&ip);
+        }
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        let open = self.0.open.fetch_sub(1, Ordering::SeqCst);
+        self.0.gauge.set(open - 1);
+        if let Some(ip) = self.1 {
+            self.0.release_ip(ip);
+        }
+    }
+}
+
+/// The limit a request head exceeded.
+#[derive(Debug, PartialEq)]
+enum TooLarge {
+    Size,
+    Count,
+}
+
+/// The progress of reading a request head, checked against the
+/// `max_header_size` and `max_header_count` limits as it's read.
+///
+/// Once the head is complete, the bytes read through the connection belong to
+/// the body and aren't counted until the head is `rearm`ed, which Rocket does
+/// once it's done handling the request and before Hyper parses the next one.
+#[derive(Debug, Default)]
+struct Head {
+    max_size: usize,
+    max_count: usize,
+    size: usize,
+    /// The number of lines begun, ignoring empty lines before the request
+    /// line; every line after the request line is a header.
+    lines: usize,
+    /// Whether the current line is empty so far.
+    empty: bool,
+    done: bool,
+}
+
+impl Head {
+    fn new(max_size: usize, max_count: usize) -> Head {
+        Head { max_size: max_size, max_count: max_count, empty: true, ..Head::default() }
+    }
+
+    fn rearm(&mut self) {
+        *self = Head::new(self.max_size, self.max_count);
+    }
+
+    fn read(&mut self, bytes: &[u8]) -> Result<(), TooLarge> {
+        if self.done {
+            return Ok(());
+        }
+
+        for &byte in bytes {
+            self.size += 1;
+            if self.size > self.max_size {
+                return Err(TooLarge::Size);
+            }
+
+            match byte {
+                b'\n' if self.empty && self.lines > 0 => {
+                    self.done = true;
+                    return Ok(());
+                }
+                b'\n' => self.empty = true,
+                b'\r' => {}
+                _ if self.empty => {
+                    self.empty = false;
+                    self.lines += 1;
+                    if self.lines - 1 > self.max_count {
+                        return Err(TooLarge::Count);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A handle to the request head tracking of a connection that rearms it, to
+/// count the next request's head, when dropped.
+pub(crate) struct Rearm(Arc<Mutex<Head>>);
+
+impl Drop for Rearm {
+    fn drop(&mut self) {
+        self.0.lock().expect("head lock").rearm();
+    }
+}
+
+/// The "wrapping" of plaintext connections: they're served as accepted.
+#[derive(Clone)]
+pub(crate) struct Plaintext;
+
+impl SslServer<HttpStream> for Plaintext {
+    type Stream = HttpStream;
+
+    #[inline(always)]
+    fn wrap_server(&self, stream: HttpStream) -> hyper::Result<HttpStream> {
+        Ok(stream)
+    }
+}
+
+/// A listener that holds at most `max_connections` connections open at once,
+/// and at most `max_connections_per_ip` from any one client IP address.
+///
+/// Every accepted connection claims a slot that is freed once Hyper is done
+/// with the connection. Connections accepted while no slot is available are
+/// closed immediately, before any data is read, so an overloaded server
+/// sheds new connections instead of running out of file descriptors, and a
+/// single client can't tie up every worker. Connections are only wrapped with
+/// `wrap`, which performs the TLS handshake when serving over TLS, once they
+/// have claimed a slot.
+#[derive(Clone)]
+pub(crate) struct Listener<S> {
+    inner: HttpListener,
+    wrap: S,
+    slots: Arc<Slots>,
+    rejected: Counter,
+    rejected_per_ip: Counter,
+    max_header_size: usize,
+    max_header_count: usize,
+    headers_too_large: Counter,
+}
+
+impl<S> Listener<S> {
+    fn new(inner: HttpListener, wrap: S, config: &Config, metrics: &Metrics) -> Listener<S> {
+        let exempt = config.connection_limit_exempt.iter()
+            .filter_map(|network| IpNetwork::parse(network))
+            .collect();
+
+        let slots = Slots {
+            open: AtomicUsize::new(0),
+            max: config.max_connections.map(|max| max as usize),
+            per_ip: Mutex::new(HashMap::new()),
+            max_per_ip: config.max_connections_per_ip.map(|max| max as usize),
+            exempt: exempt,
+            gauge: metrics.counter("listener.open"),
+        };
+
+        Listener {
+            inner: inner,
+            wrap: wrap,
+            slots: Arc::new(slots),
+            rejected: metrics.counter("listener.rejected"),
+            rejected_per_ip: metrics.counter("listener.rejected_per_ip"),
+            max_header_size: config.max_header_size as usize,
+            max_header_count: config.max_header_count as usize,
+            headers_too_large: metrics.counter("listener.headers_too_large"),
+        }
+    }
+}
+
+impl<S> NetworkListener for Listener<S>
+    where S: SslServer<HttpStream> + Clone, S::Stream: Clone
+{
+    type Stream = Limited<S::Stream>;
+
+    fn accept(&mut self) -> hyper::Result<Limited<S::Stream>> {
+        loop {
+            let mut stream = self.inner.accept()?;
+            let ip = stream.peer_addr().ok().map(|addr| addr.ip());
+            match Slots::claim(&self.slots, ip) {
+                Ok(slot) => {
+                    // If the handshake fails, dropping `slot` frees it.
+                    let stream = self.wrap.wrap_server(stream)?;
+                    let head = Head::new(self.max_header_size, self.max_header_count);
+                    return Ok(Limited::new(stream, slot, head, self.headers_too_large.clone()));
+                }
+                Err(Full::Overall) => {
+                    warn_!("Connection limit reached. Closing new connection.");
+                    self.rejected.incr();
+                }
+                Err(Full::PerIp) => {
+                    warn_!("Connection limit for {} reached. Closing new connection.",
+                           ip.map_or("client".to_string(), |ip| ip.to_string()));
+                    self.rejected_per_ip.incr();
+                }
+            }
+
+            let _ = stream.close(Shutdown::Both);
+        }
+    }
+
+    fn local_addr(&mut self) -> io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    fn set_read_timeout(&mut self, duration: Option<Duration>) {
+        self.inner.set_read_timeout(duration)
+    }
+
+    fn set_write_timeout(&mut self, duration: Option<Duration>) {
+        self.inner.set_write_timeout(duration)
+    }
+}
+
+/// A connection accepted by a `Listener`. Its slot is freed once every clone
+/// has been dropped.
+///
+/// Request heads larger than `max_header_size` or with more headers than
+/// `max_header_count` are answered with `431 Request Header Fields Too Large`
+/// and the connection is closed.
+#[derive(Clone)]
+pub(crate) struct Limited<S> {
+    pub(crate) inner: S,
+    _slot: Arc<Slot>,
+    head: Arc<Mutex<Head>>,
+    headers_too_large: Counter,
+    /// The certificates the client presented, read by the first request on
+    /// the connection and shared by the rest.
+    #[cfg(feature = "tls")]
+    pub(crate) peer: Arc<Storage<Option<Arc<PeerCertificates>>>>,
+    /// The parameters the TLS handshake negotiated, read by the first request
+    /// on the connection and shared by the rest.
+    #[cfg(feature = "tls")]
+    pub(crate) session: Arc<Storage<Option<Arc<TlsInfo>>>>,
+}
+
+impl<S> Limited<S> {
+    fn new(inner: S, slot: Slot, head: Head, headers_too_large: Counter) -> Limited<S> {
+        Limited {
+            inner: inner,
+            _slot: Arc::new(slot),
+            head: Arc::new(Mutex::new(head)),
+            headers_too_large: headers_too_large,
+            #[cfg(feature = "tls")]
+            peer: Arc::new(Storage::new()),
+            #[cfg(feature = "tls")]
+            session: Arc::new(Storage::new()),
+        }
+    }
+
+    /// Returns a handle that rearms the request head limits of the connection
+    /// once dropped, after the current request has been handled.
+    pub(crate) fn rearm_head(&self) -> Rearm {
+        Rearm(self.head.clone())
+    }
+}
+
+impl<S: Read + Write> Read for Limited<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        let (limit, max) = {
+            let mut head = self.head.lock().expect("head lock");
+            match head.read(&buf[..n]) {
+                Ok(()) => return Ok(n),
+                Err(TooLarge::Size) => ("max_header_size", head.max_size),
+                Err(TooLarge::Count) => ("max_header_count", head.max_count),
+            }
+        };
+
+        // Hyper would drop the connection without a word; answer it first.
+        warn_!("Request head exceeds `{}` of {}. Responding with 431.", limit, max);
+        self.headers_too_large.incr();
+        let _ = self.inner.write_all(HEADERS_TOO_LARGE).and_then(|_| self.inner.flush());
+        Err(io::Error::new(io::ErrorKind::InvalidData, "request header fields too large"))
+    }
+}
+
+impl<S: Write> Write for Limited<S> {
+    #[inline(always)]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    #[inline(always)]
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<S: NetworkStream> NetworkStream for Limited<S> {
+    #[inline(always)]
+    fn peer_addr(&mut self) -> io::Result<SocketAddr> {
+        self.inner.peer_addr()
+    }
+
+    #[inline(always)]
+    fn set_read_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        self.inner.set_read_timeout(dur)
+    }
+
+    #[inline(always)]
+    fn set_write_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        self.inner.set_write_timeout(dur)
+    }
+
+    #[inline(always)]
+    fn close(&mut self, how: Shutdown) -> io::Result<()> {
+        self.inner.close(how)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::io::{self, Read, Write, Cursor};
+    use std::net::{IpAddr, SocketAddr, TcpStream};
+    use std::sync::{Arc, Mutex};
+    use std::sync::atomic::AtomicUsize;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::{Slots, Full, IpNetwork, Head, TooLarge, Limited, Listener, HEADERS_TOO_LARGE, bind};
+    use config::{Config, Environment};
+    use metrics::Metrics;
+    use http::hyper;
+    use http::hyper::net::{HttpStream, NetworkListener, NetworkStream, SslServer};
+
+    fn slots(metrics: &Metrics, max: usize, max_per_ip: usize, exempt: &[&str]) -> Arc<Slots> {
+        Arc::new(Slots {
+            open: AtomicUsize::new(0),
+            max: Some(max),
+            per_ip: Mutex::new(HashMap::new()),
+            max_per_ip: Some(max_per_ip),
+            exempt: exempt.iter().map(|net| IpNetwork::parse(net).unwrap()).collect(),
+            gauge: metrics.counter("open"),
+        })
+    }
+
+    fn ip(string: &str) -> Option<IpAddr> {
+        Some(string.parse().unwrap())
+    }
+
+    #[test]
+    fn test_slots() {
+        let metrics = Metrics::new();
+        let slots = slots(&metrics, 2, 2, &[]);
+
+        let first = Slots::claim(&slots, None).unwrap();
+        let second = Slots::claim(&slots, None).unwrap();
+        assert_eq!(Slots::claim(&slots, None).err(), Some(Full::Overall));
+        assert_eq!(metrics.get("open"), Some(2));
+
+        drop(first);
+        assert_eq!(metrics.get("open"), Some(1));
+        let _third = Slots::claim(&slots, None).unwrap();
+        assert!(Slots::claim(&slots, None).is_err());
+
+        drop(second);
+        assert_eq!(metrics.get("open"), Some(1));
+    }
+
+    #[test]
+    fn test_per_ip_slots() {
+        let metrics = Metrics::new();
+        let slots = slots(&metrics, 4, 1, &["10.0.0.0/8"]);
+
+        let first = Slots::claim(&slots, ip("1.2.3.4")).unwrap();
+        assert_eq!(Slots::claim(&slots, ip("1.2.3.4")).err(), Some(Full::PerIp));
+        let _other = Slots::claim(&slots, ip("1.2.3.5")).unwrap();
+
+        // Exempt clients are only limited by the overall maximum.
+        let _lb = Slots::claim(&slots, ip("10.1.2.3")).unwrap();
+        let _lb2 = Slots::claim(&slots, ip("10.1.2.3")).unwrap();
+        assert_eq!(Slots::claim(&slots, ip("10.1.2.3")).err(), Some(Full::Overall));
+
+        // A rejection for the overall maximum doesn't hold the IP's slot.
+        drop(first);
+        let _again = Slots::claim(&slots, ip("1.2.3.4")).unwrap();
+        assert!(slots.per_ip.lock().unwrap().get(&ip("10.1.2.3").unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_head_limits() {
+        let mut head = Head::new(64, 2);
+        assert_eq!(head.read(b"\r\nGET / HTTP/1.1\r\nHost: a\r\n"), Ok(()));
+        assert_eq!(head.read(b"Accept: */*\r\n\r\nX-Not-A-Header: body"), Ok(()));
+        assert!(head.done);
+
+        // The body isn't counted, however many lines or bytes it has.
+        assert_eq!(head.read("  \"line\": true,\n".repeat(100).as_bytes()), Ok(()));
+
+        // The next request's head is counted once the head is rearmed.
+        head.rearm();
+        assert_eq!(head.read(b"GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\n"), Ok(()));
+        assert_eq!(head.read(b"C: 3\r\n"), Err(TooLarge::Count));
+
+        let mut head = Head::new(32, 100);
+        assert_eq!(head.read(b"GET / HTTP/1.1\r\n"), Ok(()));
+        assert_eq!(head.read(b"Authorization: Bearer 0123456789"), Err(TooLarge::Size));
+
+        let mut head = Head::new(16, 100);
+        assert_eq!(head.read(b"GET / HTTP/1.1\n\n"), Ok(()));
+        assert!(head.done);
+    }
+
+    // A connection that reads from `input` and writes to `output`.
+    struct Stream(Cursor<Vec<u8>>, Vec<u8>);
+
+    impl Read for Stream {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    impl Write for Stream {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.1.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_oversized_heads_are_answered() {
+        let metrics = Metrics::new();
+        let slot = Slots::claim(&slots(&metrics, 1, 1, &[]), None).unwrap();
+        let stream = Stream(Cursor::new(b"GET / HTTP/1.1\r\nCookie: a=1\r\n\r\n".to_vec()), vec![]);
+        let mut limited = Limited::new(stream, slot, Head::new(24, 100), metrics.counter("big"));
+
+        let mut buf = vec![];
+        assert!(limited.read_to_end(&mut buf).is_err());
+        assert_eq!(limited.inner.1, HEADERS_TOO_LARGE);
+        assert_eq!(metrics.get("big"), Some(1));
+    }
+
+    #[test]
+    fn test_ip_networks() {
+        let net = IpNetwork::parse("192.168.0.0/16").unwrap();
+        assert!(net.contains("192.168.4.1".parse().unwrap()));
+        assert!(!net.contains("192.169.0.1".parse().unwrap()));
+        assert!(net.contains("::ffff:192.168.0.9".parse().unwrap()));
+
+        let net = IpNetwork::parse("10.0.0.1").unwrap();
+        assert!(net.contains("10.0.0.1".parse().unwrap()));
+        assert!(!net.contains("10.0.0.2".parse().unwrap()));
+
+        let net = IpNetwork::parse("fd00::/8").unwrap();
+        assert!(net.contains("fd12::1".parse().unwrap()));
+        assert!(!net.contains("fe80::1".parse().unwrap()));
+
+        assert!(IpNetwork::parse("0.0.0.0/0").unwrap().contains("8.8.8.8".parse().unwrap()));
+        assert!(IpNetwork::parse("10.0.0.0/33").is_none());
+        assert!(IpNetwork::parse("10.0.0/8").is_none());
+        assert!(IpNetwork::parse("localhost").is_none());
+    }
+
+    // A stand-in for the TLS handshake that records the connections it wraps.
+    #[derive(Clone)]
+    struct Handshake(Arc<Mutex<Vec<SocketAddr>>>);
+
+    impl SslServer<HttpStream> for Handshake {
+        type Stream = HttpStream;
+
+        fn wrap_server(&self, mut stream: HttpStream) -> hyper::Result<HttpStream> {
+            self.0.lock().unwrap().push(stream.peer_addr()?);
+            Ok(stream)
+        }
+    }
+
+    fn assert_no_handshake_over_limit(config: Config, rejected: &str) {
+        let metrics = Metrics::new();
+        let handshake = Handshake(Arc::new(Mutex::new(vec![])));
+        let inner = bind("127.0.0.1:0", None).unwrap();
+        let mut listener = Listener::new(inner, handshake.clone(), &config, &metrics);
+        let addr = listener.local_addr().unwrap();
+
+        let first = TcpStream::connect(addr).unwrap();
+        let held = listener.accept().unwrap();
+
+        // The second connection is over the limit: it's closed, unwrapped.
+        let mut second = TcpStream::connect(addr).unwrap();
+        second.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let mut accepting = listener.clone();
+        let accepted = thread::spawn(move || accepting.accept().is_ok());
+        assert_eq!(second.read(&mut [0; 1]).unwrap(), 0);
+        assert_eq!(handshake.0.lock().unwrap().len(), 1);
+        assert_eq!(metrics.get(rejected), Some(1));
+
+        // Once the first connection's slot is freed, the next one is wrapped.
+        drop(held);
+        let third = TcpStream::connect(addr).unwrap();
+        assert!(accepted.join().unwrap());
+        let wrapped = handshake.0.lock().unwrap().clone();
+        assert_eq!(wrapped, vec![first.local_addr().unwrap(), third.local_addr().unwrap()]);
+    }
+
+    #[test]
+    fn test_no_handshake_over_limit() {
+        let config = Config::build(Environment::Development).max_connections(1).unwrap();
+        assert_no_handshake_over_limit(config, "listener.rejected");
+
+        let config = Config::build(Environment::Development).max_connections_per_ip(1).unwrap();
+        assert_no_handshake_over_limit(config, "listener.rejected_per_ip");
+    }
+}
@@ -1,8 +1,8 @@
 use {Rocket, Request, Response};
-use local::LocalRequest;
-use http::{Method, CookieJar, uri::Uri};
+use local::{LocalRequest, LocalResponse, Recording, Exchange, Divergence};
+use http::{Method, Header, Cookie, CookieJar, uri::Uri};
 use error::LaunchError;
-use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
 
 /// A structure to construct requests for local dispatching.
 ///
@@ -30,6 +30,35 @@ use std::cell::RefCell;
 /// [`untracked`](Client::untracked()) constructor to create a `Client` that
 /// _will not_ track cookies.
 ///
+/// The cookies tracked by a `Client` can be exported with [`export_cookies`]
+/// and imported into another `Client`, possibly in another process, with
+/// [`import_cookies`]. This allows a session established by one test to be
+/// continued by another.
+///
+/// ## Redirects
+///
+/// By default, a `Client` returns redirect responses as-is. A `Client`
+/// configured with [`follow_redirects`] instead dispatches a new request to the
+/// `Location` of `301`, `302`, `303`, `307`, and `308` responses, up to a limit
+/// of [`max_redirects`] redirects, and returns the final response.
+///
+/// ## Concurrency
+///
+/// A `Client` is `Sync`: requests can be created and dispatched against a
+/// single `Client` from many threads at once, for instance by sharing it via
+/// an `Arc`. A [`LocalRequest`] and its [`LocalResponse`] borrow the `Client`
+/// and are confined to the thread that created them. Use
+/// [`LocalRequest::dispatch_owned()`] to instead receive a fully buffered
+/// [`OwnedResponse`] that can be sent to another thread.
+///
+/// ## Recording and Replay
+///
+/// A `Client` configured with [`record`] records every request it dispatches
+/// along with the response. The [`Recording`], retrieved with
+/// [`take_recording`], can be serialized and later replayed against another
+/// `Client` with [`replay`] to check that a new build of an application
+/// responds as the old one did.
+///
 /// ## Example
 ///
 /// The following snippet creates a `Client` from a `Rocket` instance and
@@ -48,29 +77,54 @@ use std::cell::RefCell;
 /// [`new`]: #method.new
 /// [`untracked`]: #method.untracked
 /// [`LocalRequest`]: /rocket/local/struct.LocalRequest.html
+/// [`LocalResponse`]: /rocket/local/struct.LocalResponse.html
+/// [`LocalRequest::dispatch_owned()`]: /rocket/local/struct.LocalRequest.html#method.dispatch_owned
+/// [`OwnedResponse`]: /rocket/local/struct.OwnedResponse.html
 /// [`get`]: #method.get
 /// [`put`]: #method.put
 /// [`post`]: #method.post
+/// [`export_cookies`]: #method.export_cookies
+/// [`import_cookies`]: #method.import_cookies
+/// [`follow_redirects`]: #method.follow_redirects
+/// [`max_redirects`]: #method.max_redirects
+/// [`record`]: #method.record
+/// [`take_recording`]: #method.take_recording
+/// [`replay`]: #method.replay
+/// [`Recording`]: /rocket/local/struct.Recording.html
 pub struct Client {
-    rocket: Rocket,
-    cookies: Option<RefCell<CookieJar>>,
+    rocket: Arc<Rocket>,
+    cookies: Option<Mutex<CookieJar>>,
+    follow_redirects: bool,
+    max_redirects: usize,
+    recording: Option<Mutex<Recording>>,
 }
 
+/// The number of redirects a `Client` follows by default.
+const DEFAULT_MAX_REDIRECTS: usize = 10;
+
 impl Client {
     /// Constructs a new `Client`. If `tracked` is `true`, an empty `CookieJar`
     /// is created for cookie tracking. Otherwise, the internal `CookieJar` is
     /// set to `None`.
-    fn _new(rocket: Rocket, tracked: bool) -> Result<Client, LaunchError> {
+    fn _new(mut rocket: Rocket, tracked: bool) -> Result<Client, LaunchError> {
         if let Some(err) = rocket.prelaunch_check() {
             return Err(err);
         }
 
+        rocket.warm();
+
         let cookies = match tracked {
-            true => Some(RefCell::new(CookieJar::new())),
+            true => Some(Mutex::new(CookieJar::new())),
             false => None
         };
 
-        Ok(Client { rocket, cookies })
+        Ok(Client {
+            rocket: Arc::new(rocket),
+            cookies: cookies,
+            follow_redirects: false,
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            recording: None
+        })
     }
 
     /// Construct a new `Client` from an instance of `Rocket` with cookie
@@ -150,11 +204,267 @@ impl Client {
         &self.rocket
     }
 
+    /// Returns a shared handle to the `Rocket` instance, for dispatching on
+    /// another thread.
+    #[inline(always)]
+    pub(crate) fn shared_rocket(&self) -> Arc<Rocket> {
+        self.rocket.clone()
+    }
+
+    /// Sets whether `self` follows redirects. When `follow` is `true`,
+    /// dispatching a request whose response is a `301`, `302`, `303`, `307`,
+    /// or `308` redirect dispatches a new request to the response's `Location`
+    /// and returns the response to that request instead.
+    ///
+    /// The new request carries the headers, remote address, and cookies of
+    /// the request before it, including any cookies set or removed by the
+    /// redirecting response. `307` and `308` redirects keep the method and
+    /// body of the original request, `303` redirects, and `301` and `302`
+    /// redirects of `POST` requests, switch to a bodiless `GET`, and other
+    /// redirects keep the method. Bodies set via a reader are not resent.
+    ///
+    /// At most [`max_redirects`] redirects are followed in a row. Once the
+    /// limit is reached, the last redirect response is returned.
+    ///
+    /// [`max_redirects`]: #method.max_redirects
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #![feature(plugin, decl_macro)]
+    /// # #![plugin(rocket_codegen)]
+    /// # extern crate rocket;
+    /// use rocket::local::Client;
+    /// use rocket::response::Redirect;
+    ///
+    /// #[get("/old")]
+    /// fn old() -> Redirect { Redirect::to("/new") }
+    ///
+    /// #[get("/new")]
+    /// fn current() -> &'static str { "new" }
+    ///
+    /// # fn main() {
+    /// let rocket = rocket::ignite().mount("/", routes![old, current]);
+    /// let client = Client::new(rocket).unwrap().follow_redirects(true);
+    ///
+    /// let mut response = client.get("/old").dispatch();
+    /// assert_eq!(response.body_string(), Some("new".into()));
+    /// # }
+    /// ```
+    #[inline]
+    pub fn follow_redirects(mut self, follow: bool) -> Client {
+        self.follow_redirects = follow;
+        self
+    }
+
+    /// Sets the maximum number of redirects in a row that `self` follows when
+    /// [`follow_redirects`] is enabled. The default is `10`.
+    ///
+    /// [`follow_redirects`]: #method.follow_redirects
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    ///
+    /// # #[allow(unused_variables)]
+    /// let client = Client::new(rocket::ignite()).unwrap()
+    ///     .follow_redirects(true)
+    ///     .max_redirects(3);
+    /// ```
+    #[inline]
+    pub fn max_redirects(mut self, max: usize) -> Client {
+        self.max_redirects = max;
+        self
+    }
+
+    // Returns the maximum number of redirects `self` follows in a row.
+    #[inline(always)]
+    pub(crate) fn redirect_limit(&self) -> usize {
+        match self.follow_redirects {
+            true => self.max_redirects,
+            false => 0
+        }
+    }
+
+    /// Sets whether `self` records the requests it dispatches and their
+    /// responses. Recorded exchanges are retrieved with [`take_recording()`].
+    ///
+    /// Response bodies are buffered in memory to be recorded. Request bodies
+    /// set via a reader are not recorded.
+    ///
+    /// [`take_recording()`]: #method.take_recording
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap().record(true);
+    /// client.get("/").dispatch();
+    /// assert_eq!(client.take_recording().len(), 1);
+    /// ```
+    #[inline]
+    pub fn record(mut self, record: bool) -> Client {
+        self.recording = match record {
+            true => Some(Mutex::new(Recording::new())),
+            false => None
+        };
+
+        self
+    }
+
+    /// Returns the exchanges recorded by `self` since it was configured to
+    /// [`record`] or since the last call to this method. If `self` isn't
+    /// recording, the returned `Recording` is empty.
+    ///
+    /// [`record`]: #method.record
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap().record(true);
+    /// client.get("/missing").dispatch();
+    ///
+    /// let recording = client.take_recording();
+    /// assert_eq!(recording.exchanges()[0].uri(), "/missing");
+    /// assert!(client.take_recording().is_empty());
+    /// ```
+    pub fn take_recording(&self) -> Recording {
+        match self.recording {
+            Some(ref recording) => {
+                let mut recording = recording.lock().expect("recording lock");
+                ::std::mem::replace(&mut *recording, Recording::new())
+            }
+            None => Recording::new()
+        }
+    }
+
+    /// Dispatches the requests in `recording`, in order, and compares each
+    /// response's status and body to the recorded ones, returning the
+    /// exchanges whose responses differ. Headers, which often vary between
+    /// runs, aren't compared.
+    ///
+    /// Recorded cookies are sent as request cookies in addition to any tracked
+    /// by `self`; use an [`untracked`] client to send exactly the recorded
+    /// cookies. Redirects are not followed, even if `self` follows them, as
+    /// each followed redirect is recorded separately.
+    ///
+    /// [`untracked`]: #method.untracked
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::{Client, Recording};
+    ///
+    /// let old = Client::new(rocket::ignite()).unwrap().record(true);
+    /// old.get("/").dispatch();
+    /// let log = old.take_recording().to_string();
+    ///
+    /// let recording: Recording = log.parse().unwrap();
+    /// let new = Client::untracked(rocket::ignite()).unwrap();
+    /// assert!(new.replay(&recording).is_empty());
+    /// ```
+    pub fn replay(&self, recording: &Recording) -> Vec<Divergence> {
+        let mut divergences = vec![];
+        for (i, exchange) in recording.exchanges().iter().enumerate() {
+            let mut request = self.req(exchange.method(), exchange.uri().to_string());
+            for header in exchange.headers().iter() {
+                if !header.name().eq_ignore_ascii_case("Cookie") {
+                    let (name, value) = (header.name().to_string(), header.value().to_string());
+                    request.add_header(Header::new(name, value));
+                    continue;
+                }
+
+                for cookie_str in header.value().split(';').map(|s| s.trim()) {
+                    if let Ok(cookie) = Cookie::parse(cookie_str.to_string()) {
+                        request = request.cookie(cookie);
+                    }
+                }
+            }
+
+            request.set_body(exchange.body());
+            let response = request.dispatch_once().into_owned();
+            let expected = exchange.response();
+            let body_differs = expected.body().unwrap_or(&[]) != response.body().unwrap_or(&[]);
+            if expected.status() != response.status() || body_differs {
+                divergences.push(Divergence {
+                    index: i,
+                    method: exchange.method(),
+                    uri: exchange.uri().to_string(),
+                    expected: expected.status(),
+                    actual: response.status(),
+                    body_differs: body_differs,
+                });
+            }
+        }
+
+        divergences
+    }
+
+    #[inline(always)]
+    pub(crate) fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    // If `self` is recording, appends `exchange` to the recording.
+    pub(crate) fn record_exchange(&self, exchange: Exchange) {
+        if let Some(ref recording) = self.recording {
+            recording.lock().expect("recording lock").push(exchange);
+        }
+    }
+
+    // Returns `true` if `self` tracks cookies.
+    pub(crate) fn tracks_cookies(&self) -> bool {
+        self.cookies.is_some()
+    }
+
+    /// Dispatches each of the requests in `requests`, in order, and returns
+    /// their responses in the same order. Each request is sent with the
+    /// cookies tracked by `self` at the time it is dispatched, so cookies set
+    /// or removed by a response apply to the requests that follow it even if
+    /// the requests were created beforehand. Cookies added to a request
+    /// explicitly, via [`LocalRequest::cookie()`] and friends, are sent as is.
+    ///
+    /// [`LocalRequest::cookie()`]: /rocket/local/struct.LocalRequest.html#method.cookie
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the requests was not created by `self`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    /// use rocket::http::Status;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// let responses = client.dispatch_all(vec![
+    ///     client.get("/"),
+    ///     client.post("/login"),
+    ///     client.get("/profile"),
+    /// ]);
+    ///
+    /// let statuses: Vec<_> = responses.iter().map(|r| r.status()).collect();
+    /// assert_eq!(statuses, vec![Status::NotFound; 3]);
+    /// ```
+    pub fn dispatch_all<'c>(&'c self, requests: Vec<LocalRequest<'c>>) -> Vec<LocalResponse<'c>> {
+        requests.into_iter()
+            .map(|mut request| {
+                assert!(request.is_from(self), "request was created by another client");
+                request.sync_cookies();
+                request.dispatch()
+            })
+            .collect()
+    }
+
     // If `self` is tracking cookies, updates the internal cookie jar with the
     // changes reflected by `response`.
     pub(crate) fn update_cookies(&self, response: &Response) {
         if let Some(ref jar) = self.cookies {
-            let mut jar = jar.borrow_mut();
+            let mut jar = jar.lock().expect("cookie jar lock");
             let current_time = ::time::now();
             for cookie in response.cookies() {
                 if let Some(expires) = cookie.expires() {
@@ -169,6 +479,175 @@ impl Client {
         }
     }
 
+    /// Exports the cookies tracked by `self` to a string from which they can
+    /// be restored, possibly in another process, via [`import_cookies()`].
+    ///
+    /// The exported string contains one cookie per line in `Set-Cookie`
+    /// syntax, ordered by name, and includes each cookie's attributes. If
+    /// `self` doesn't track cookies, the string is empty.
+    ///
+    /// Note that the string contains the cookies' values verbatim. Treat it
+    /// with the same care as the cookies themselves.
+    ///
+    /// [`import_cookies()`]: #method.import_cookies
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// client.import_cookies("session=abc123; Path=/").unwrap();
+    /// assert_eq!(client.export_cookies(), "session=abc123; Path=/\n");
+    /// ```
+    pub fn export_cookies(&self) -> String {
+        let jar = match self.cookies {
+            Some(ref jar) => jar.lock().expect("cookie jar lock"),
+            None => return String::new()
+        };
+
+        let mut cookies: Vec<_> = jar.iter().collect();
+        cookies.sort_by(|a, b| a.name().cmp(b.name()));
+        let exported = cookies.iter()
+            .map(|cookie| format!("{}\n", cookie.encoded()))
+            .collect();
+
+        exported
+    }
+
+    /// Imports the cookies in `store`, as exported by [`export_cookies()`],
+    /// into the cookies tracked by `self`, replacing existing cookies with the
+    /// same name. Expired cookies are skipped, as are empty lines and lines
+    /// beginning with `#`.
+    ///
+    /// [`export_cookies()`]: #method.export_cookies
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `self` doesn't track cookies or if a line in
+    /// `store` isn't a valid cookie. In the latter case, no cookies are
+    /// imported.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// assert!(client.import_cookies("# a saved session\nuser_id=12\n").is_ok());
+    /// assert!(client.import_cookies("not a cookie").is_err());
+    ///
+    /// let untracked = Client::untracked(rocket::ignite()).unwrap();
+    /// assert!(untracked.import_cookies("user_id=12").is_err());
+    /// ```
+    pub fn import_cookies(&self, store: &str) -> Result<(), String> {
+        let jar = match self.cookies {
+            Some(ref jar) => jar,
+            None => return Err("the client does not track cookies".to_string())
+        };
+
+        let mut cookies = vec![];
+        for (i, line) in store.lines().map(|line| line.trim()).enumerate() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            match Cookie::parse_encoded(line) {
+                Ok(cookie) => cookies.push(cookie.into_owned()),
+                Err(e) => return Err(format!("line {}: invalid cookie: {}", i + 1, e))
+            }
+        }
+
+        let mut jar = jar.lock().expect("cookie jar lock");
+        let current_time = ::time::now();
+        for cookie in cookies {
+            match cookie.expires() {
+                Some(expires) if expires <= current_time => continue,
+                _ => jar.add(cookie)
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the cookies tracked by `self`, ordered by name. These are the
+    /// cookies sent with each request `self` dispatches. If `self` doesn't
+    /// track cookies, the returned vector is empty.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    /// use rocket::http::Cookie;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// assert!(client.cookies().is_empty());
+    ///
+    /// client.add_cookie(Cookie::new("session", "abc123")).unwrap();
+    /// assert_eq!(client.cookies(), vec![Cookie::new("session", "abc123")]);
+    /// ```
+    pub fn cookies(&self) -> Vec<Cookie<'static>> {
+        let jar = match self.cookies {
+            Some(ref jar) => jar.lock().expect("cookie jar lock"),
+            None => return vec![]
+        };
+
+        let mut cookies: Vec<_> = jar.iter().cloned().collect();
+        cookies.sort_by(|a, b| a.name().cmp(b.name()));
+        cookies
+    }
+
+    /// Adds `cookie` to the cookies tracked by `self`, replacing any cookie
+    /// with the same name. The cookie is sent with every following request
+    /// `self` dispatches.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `self` doesn't track cookies.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    /// use rocket::http::Cookie;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// assert!(client.add_cookie(Cookie::new("user_id", "12")).is_ok());
+    ///
+    /// let untracked = Client::untracked(rocket::ignite()).unwrap();
+    /// assert!(untracked.add_cookie(Cookie::new("user_id", "12")).is_err());
+    /// ```
+    pub fn add_cookie<'a>(&self, cookie: Cookie<'a>) -> Result<(), String> {
+        match self.cookies {
+            Some(ref jar) => {
+                jar.lock().expect("cookie jar lock").add(cookie.into_owned());
+                Ok(())
+            }
+            None => Err("the client does not track cookies".to_string())
+        }
+    }
+
+    /// Removes all of the cookies tracked by `self`, as if starting a new
+    /// session. Does nothing if `self` doesn't track cookies.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    /// use rocket::http::Cookie;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// client.add_cookie(Cookie::new("session", "abc123")).unwrap();
+    ///
+    /// client.clear_cookies();
+    /// assert!(client.cookies().is_empty());
+    /// ```
+    pub fn clear_cookies(&self) {
+        if let Some(ref jar) = self.cookies {
+            *jar.lock().expect("cookie jar lock") = CookieJar::new();
+        }
+    }
+
     /// Create a local `GET` request to the URI `uri`.
     ///
     /// When dispatched, the request will be served by the instance of Rocket
@@ -352,7 +831,7 @@ impl Client {
         let request = Request::new(&self.rocket, method, uri);
 
         if let Some(ref jar) = self.cookies {
-            for cookie in jar.borrow().iter() {
+            for cookie in jar.lock().expect("cookie jar lock").iter() {
                 request.cookies().add_original(cookie.clone().into_owned());
             }
         }
@@ -96,13 +96,33 @@
 //! }
 //! ```
 //!
+//! To avoid rebuilding the application for every test, freeze it into a
+//! [`Prototype`] once and create a fresh instance or `Client` from it in each
+//! test.
+//!
+//! To catch unintended changes in complete responses, compare the
+//! [`Snapshot`] of a response, a stable textual form of its status, headers,
+//! and body, to one stored alongside the test.
+//!
 //! [`Client`]: /rocket/local/struct.Client.html
+//! [`Snapshot`]: /rocket/local/struct.Snapshot.html
+//! [`Prototype`]: /rocket/local/struct.Prototype.html
 //! [`LocalRequest`]: /rocket/local/struct.LocalRequest.html
 //! [`Rocket`]: /rocket/struct.Rocket.html
 //!
 
 mod request;
 mod client;
+mod prototype;
+mod multipart;
+mod owned;
+mod record;
+mod snapshot;
 
 pub use self::request::{LocalResponse, LocalRequest};
 pub use self::client::Client;
+pub use self::prototype::Prototype;
+pub use self::multipart::Multipart;
+pub use self::owned::OwnedResponse;
+pub use self::record::{Recording, Exchange, Divergence};
+pub use self::snapshot::Snapshot;
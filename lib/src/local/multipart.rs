@@ -0,0 +1,177 @@
+use std::fmt;
+
+use http::ContentType;
+
+enum Part {
+    Text {
+        name: String,
+        value: String,
+    },
+    File {
+        name: String,
+        filename: String,
+        content_type: ContentType,
+        data: Vec<u8>,
+    },
+}
+
+/// A builder for `multipart/form-data` request bodies.
+///
+/// A `Multipart` collects named text fields and file parts, in order. When
+/// passed to [`LocalRequest::multipart()`], it is encoded into the request's
+/// body with a generated boundary, and the request's `Content-Type` is set to
+/// `multipart/form-data` with that boundary.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::local::{Client, Multipart};
+/// use rocket::http::ContentType;
+///
+/// let client = Client::new(rocket::ignite()).unwrap();
+/// let form = Multipart::new()
+///     .text("title", "Holiday")
+///     .file("photo", "beach.png", ContentType::PNG, &[0x89, b'P', b'N', b'G']);
+///
+/// # #[allow(unused_variables)]
+/// let req = client.post("/upload").multipart(form);
+/// ```
+///
+/// [`LocalRequest::multipart()`]: /rocket/local/struct.LocalRequest.html#method.multipart
+pub struct Multipart {
+    parts: Vec<Part>,
+}
+
+impl Multipart {
+    /// Returns a new, empty `Multipart` body.
+    #[inline(always)]
+    pub fn new() -> Multipart {
+        Multipart { parts: vec![] }
+    }
+
+    /// Appends a text field named `name` with the value `value`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Multipart;
+    ///
+    /// # #[allow(unused_variables)]
+    /// let form = Multipart::new().text("name", "Bob").text("age", "42");
+    /// ```
+    #[inline]
+    pub fn text<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.parts.push(Part::Text { name: name.into(), value: value.into() });
+        self
+    }
+
+    /// Appends a file part named `name` with the file name `filename`, the
+    /// content type `content_type`, and the contents `data`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Multipart;
+    /// use rocket::http::ContentType;
+    ///
+    /// # #[allow(unused_variables)]
+    /// let form = Multipart::new()
+    ///     .file("notes", "notes.txt", ContentType::Plain, "some notes");
+    /// ```
+    #[inline]
+    pub fn file<N, F, D>(mut self, name: N, filename: F, content_type: ContentType, data: D) -> Self
+        where N: Into<String>, F: Into<String>, D: AsRef<[u8]>
+    {
+        self.parts.push(Part::File {
+            name: name.into(),
+            filename: filename.into(),
+            content_type: content_type,
+            data: data.as_ref().into(),
+        });
+
+        self
+    }
+
+    /// Returns a boundary that appears in none of the parts.
+    fn boundary(&self) -> String {
+        let contains = |haystack: &[u8], needle: &[u8]| {
+            haystack.windows(needle.len()).any(|window| window == needle)
+        };
+
+        (0..).map(|i| format!("RocketLocalBoundary{:08x}", i))
+            .find(|boundary| self.parts.iter().all(|part| {
+                let (name, data) = match *part {
+                    Part::Text { ref name, ref value } => (name, value.as_bytes()),
+                    Part::File { ref name, ref data, .. } => (name, &data[..]),
+                };
+
+                !contains(name.as_bytes(), boundary.as_bytes())
+                    && !contains(data, boundary.as_bytes())
+            }))
+            .expect("a boundary")
+    }
+
+    /// Encodes `self` into a `Content-Type` and body.
+    pub(crate) fn encode(&self) -> (ContentType, Vec<u8>) {
+        let boundary = self.boundary();
+        let mut body = vec![];
+        for part in &self.parts {
+            body.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+            match *part {
+                Part::Text { ref name, ref value } => {
+                    let disposition = format!("Content-Disposition: form-data; \
+                        name=\"{}\"\r\n\r\n", Escaped(name));
+
+                    body.extend_from_slice(disposition.as_bytes());
+                    body.extend_from_slice(value.as_bytes());
+                }
+                Part::File { ref name, ref filename, ref content_type, ref data } => {
+                    let headers = format!("Content-Disposition: form-data; \
+                        name=\"{}\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+                        Escaped(name), Escaped(filename), content_type);
+
+                    body.extend_from_slice(headers.as_bytes());
+                    body.extend_from_slice(data);
+                }
+            }
+
+            body.extend_from_slice(b"\r\n");
+        }
+
+        body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+        let content_type = ContentType::with_params("multipart", "form-data",
+                                                    ("boundary", boundary));
+        (content_type, body)
+    }
+}
+
+impl Default for Multipart {
+    #[inline(always)]
+    fn default() -> Multipart {
+        Multipart::new()
+    }
+}
+
+impl fmt::Debug for Multipart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Multipart").field("parts", &self.parts.len()).finish()
+    }
+}
+
+/// Escapes quotes and line breaks in a quoted header parameter value.
+struct Escaped<'a>(&'a str);
+
+impl<'a> fmt::Display for Escaped<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("%22")?,
+                '\r' => f.write_str("%0D")?,
+                '\n' => f.write_str("%0A")?,
+                c => write!(f, "{}", c)?,
+            }
+        }
+
+        Ok(())
+    }
+}
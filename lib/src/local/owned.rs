@@ -0,0 +1,128 @@
+use std::fmt;
+
+use http::{Status, Header, HeaderMap, Cookie};
+use local::{LocalResponse, Snapshot};
+
+/// A fully buffered response to a local request that owns all of its data.
+///
+/// Unlike a [`LocalResponse`], an `OwnedResponse` borrows neither the
+/// [`Client`] nor the request that produced it and can thus be sent across
+/// threads. This makes it possible to dispatch requests concurrently against a
+/// single `Client`, which is `Sync`, from multiple threads and send the
+/// responses back for inspection.
+///
+/// An `OwnedResponse` is created via [`LocalRequest::dispatch_owned()`] or
+/// [`LocalResponse::into_owned()`].
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// use rocket::local::Client;
+/// use rocket::http::Status;
+///
+/// let client = Arc::new(Client::new(rocket::ignite()).expect("valid rocket"));
+/// let handles: Vec<_> = (0..4).map(|_| {
+///     let client = client.clone();
+///     thread::spawn(move || client.get("/").dispatch_owned())
+/// }).collect();
+///
+/// for handle in handles {
+///     assert_eq!(handle.join().unwrap().status(), Status::NotFound);
+/// }
+/// ```
+///
+/// [`LocalResponse`]: /rocket/local/struct.LocalResponse.html
+/// [`Client`]: /rocket/local/struct.Client.html
+/// [`LocalRequest::dispatch_owned()`]: /rocket/local/struct.LocalRequest.html#method.dispatch_owned
+/// [`LocalResponse::into_owned()`]: /rocket/local/struct.LocalResponse.html#method.into_owned
+pub struct OwnedResponse {
+    status: Status,
+    headers: HeaderMap<'static>,
+    body: Option<Vec<u8>>,
+}
+
+impl OwnedResponse {
+    #[inline(always)]
+    pub(crate) fn new(status: Status, headers: HeaderMap<'static>, body: Option<Vec<u8>>)
+        -> OwnedResponse
+    {
+        OwnedResponse { status: status, headers: headers, body: body }
+    }
+
+    /// Buffers the status, headers, and body of `response`.
+    pub(crate) fn from_local(mut response: LocalResponse) -> OwnedResponse {
+        let headers = owned_headers(response.headers());
+        let body = response.take_body().and_then(|body| body.into_bytes());
+        OwnedResponse::new(response.status(), headers, body)
+    }
+
+    /// Returns the status of the response.
+    #[inline(always)]
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Returns the headers of the response.
+    #[inline(always)]
+    pub fn headers(&self) -> &HeaderMap<'static> {
+        &self.headers
+    }
+
+    /// Returns the cookies set by the response's `Set-Cookie` headers.
+    /// Malformed cookies are skipped.
+    pub fn cookies(&self) -> Vec<Cookie> {
+        self.headers.get("Set-Cookie")
+            .filter_map(|header| Cookie::parse_encoded(header).ok())
+            .collect()
+    }
+
+    /// Returns the body of the response, if there is one.
+    #[inline(always)]
+    pub fn body(&self) -> Option<&[u8]> {
+        self.body.as_ref().map(|body| &body[..])
+    }
+
+    /// Returns the body of the response as a string if there is one and it is
+    /// valid UTF-8.
+    pub fn body_string(&self) -> Option<String> {
+        self.body().and_then(|body| String::from_utf8(body.to_vec()).ok())
+    }
+
+    /// Consumes `self` and returns its body, if there is one.
+    #[inline(always)]
+    pub fn into_body(self) -> Option<Vec<u8>> {
+        self.body
+    }
+
+    /// Consumes `self` and returns a [`Snapshot`] of it for snapshot testing.
+    ///
+    /// [`Snapshot`]: /rocket/local/struct.Snapshot.html
+    #[inline]
+    pub fn into_snapshot(self) -> Snapshot {
+        Snapshot::new(self)
+    }
+}
+
+impl fmt::Debug for OwnedResponse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OwnedResponse")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .field("body", &self.body.as_ref().map(|body| body.len()))
+            .finish()
+    }
+}
+
+/// Copies `headers` into a `HeaderMap` that owns its names and values.
+pub(crate) fn owned_headers(headers: &HeaderMap) -> HeaderMap<'static> {
+    let mut owned = HeaderMap::new();
+    for header in headers.iter() {
+        let (name, value) = (header.name().to_string(), header.value().to_string());
+        owned.add(Header::new(name, value));
+    }
+
+    owned
+}
@@ -0,0 +1,147 @@
+use std::sync::Arc;
+use std::collections::HashMap;
+
+use {Rocket, Catcher};
+use config::Config;
+use router::Router;
+use versioning::Versioning;
+use error::LaunchError;
+use local::Client;
+
+type Constructor = Arc<Fn(Rocket) -> Rocket + Send + Sync + 'static>;
+
+#[derive(Clone)]
+struct Frozen {
+    config: Config,
+    router: Router,
+    catchers: HashMap<u16, Catcher>,
+    versioning: Option<Versioning>,
+    constructors: Vec<Constructor>,
+}
+
+/// A frozen, cheap-to-clone blueprint of a configured `Rocket` instance.
+///
+/// A `Prototype` captures the configuration, routes, catchers, and versioning
+/// of a `Rocket` instance. Any number of independent `Rocket` instances can
+/// then be created from it via [`rocket()`](#method.rocket) or
+/// [`client()`](#method.client) without mounting routes or reparsing
+/// configuration again, which makes it convenient to share a single
+/// application setup among many tests.
+///
+/// Managed state and fairings are _not_ captured, since they can't be shared
+/// between instances. Instead, register a constructor with
+/// [`on_instance()`](#method.on_instance) that manages fresh state or
+/// attaches fairings. Constructors run, in the order they were registered,
+/// each time an instance is created. Idempotency handling is configured the
+/// same way.
+///
+/// # Example
+///
+/// ```rust
+/// # #![feature(plugin, decl_macro)]
+/// # #![plugin(rocket_codegen)]
+/// # extern crate rocket;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// use rocket::State;
+/// use rocket::local::Prototype;
+///
+/// #[derive(Default)]
+/// struct Hits(AtomicUsize);
+///
+/// #[get("/")]
+/// fn index(hits: State<Hits>) -> String {
+///     (hits.0.fetch_add(1, Ordering::Relaxed) + 1).to_string()
+/// }
+///
+/// # fn main() {
+/// let prototype = Prototype::new(rocket::ignite().mount("/", routes![index]))
+///     .on_instance(|rocket| rocket.manage(Hits::default()));
+///
+/// // Each client has its own, fresh `Hits` state.
+/// for _ in 0..2 {
+///     let client = prototype.client().expect("valid rocket");
+///     let mut response = client.get("/").dispatch();
+///     assert_eq!(response.body_string(), Some("1".into()));
+/// }
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Prototype {
+    frozen: Arc<Frozen>,
+}
+
+impl Prototype {
+    /// Freezes `rocket` into a new `Prototype`. Any managed state and fairings
+    /// of `rocket` are discarded; a warning is logged if `rocket` has
+    /// fairings.
+    pub fn new(rocket: Rocket) -> Prototype {
+        if rocket.fairings.has_fairings() {
+            warn!("Fairings aren't captured by a prototype. Attach them with `on_instance`.");
+        }
+
+        let frozen = Frozen {
+            config: rocket.config,
+            router: rocket.router,
+            catchers: rocket.catchers,
+            versioning: rocket.versioning,
+            constructors: vec![],
+        };
+
+        Prototype { frozen: Arc::new(frozen) }
+    }
+
+    /// Registers `f` to be called with each new instance created from this
+    /// prototype. The instance returned by `f` is the instance that is
+    /// returned to the caller.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Prototype;
+    /// use rocket::fairing::AdHoc;
+    ///
+    /// let prototype = Prototype::new(rocket::ignite())
+    ///     .on_instance(|rocket| rocket.manage(String::from("fresh state")))
+    ///     .on_instance(|rocket| rocket.attach(AdHoc::on_response(|_, _| ())));
+    ///
+    /// let rocket = prototype.rocket();
+    /// assert_eq!(rocket.state::<String>().map(|s| s.as_str()), Some("fresh state"));
+    /// ```
+    pub fn on_instance<F>(mut self, f: F) -> Prototype
+        where F: Fn(Rocket) -> Rocket + Send + Sync + 'static
+    {
+        Arc::make_mut(&mut self.frozen).constructors.push(Arc::new(f));
+        self
+    }
+
+    /// Creates a new `Rocket` instance from this prototype, running every
+    /// registered constructor.
+    pub fn rocket(&self) -> Rocket {
+        let frozen = &*self.frozen;
+        let mut rocket = Rocket::custom(frozen.config.clone(), false);
+        rocket.router = frozen.router.clone();
+        rocket.catchers = frozen.catchers.clone();
+        rocket.versioning = frozen.versioning.clone();
+
+        frozen.constructors.iter().fold(rocket, |rocket, constructor| constructor(rocket))
+    }
+
+    /// Creates a new tracking `Client` for a new `Rocket` instance created
+    /// from this prototype. See [`Client::new()`] for details.
+    ///
+    /// [`Client::new()`]: /rocket/local/struct.Client.html#method.new
+    #[inline]
+    pub fn client(&self) -> Result<Client, LaunchError> {
+        Client::new(self.rocket())
+    }
+
+    /// Creates a new untracked `Client` for a new `Rocket` instance created
+    /// from this prototype. See [`Client::untracked()`] for details.
+    ///
+    /// [`Client::untracked()`]: /rocket/local/struct.Client.html#method.untracked
+    #[inline]
+    pub fn untracked_client(&self) -> Result<Client, LaunchError> {
+        Client::untracked(self.rocket())
+    }
+}
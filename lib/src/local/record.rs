@@ -0,0 +1,298 @@
+use std::fmt;
+use std::str::FromStr;
+
+use base64;
+
+use http::{Method, Status, Header, HeaderMap};
+use local::OwnedResponse;
+
+/// A request dispatched by a recording [`Client`] and the response to it.
+///
+/// [`Client`]: /rocket/local/struct.Client.html
+#[derive(Debug)]
+pub struct Exchange {
+    method: Method,
+    uri: String,
+    headers: HeaderMap<'static>,
+    body: Vec<u8>,
+    response: OwnedResponse,
+}
+
+impl Exchange {
+    #[inline(always)]
+    pub(crate) fn new(
+        method: Method,
+        uri: String,
+        headers: HeaderMap<'static>,
+        body: Vec<u8>,
+        response: OwnedResponse
+    ) -> Exchange {
+        Exchange { method: method, uri: uri, headers: headers, body: body, response: response }
+    }
+
+    /// Returns the method of the request.
+    #[inline(always)]
+    pub fn method(&self) -> Method {
+        self.method
+    }
+
+    /// Returns the URI of the request.
+    #[inline(always)]
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Returns the headers of the request. The request's cookies, if any, are
+    /// included as a `Cookie` header.
+    #[inline(always)]
+    pub fn headers(&self) -> &HeaderMap<'static> {
+        &self.headers
+    }
+
+    /// Returns the body of the request.
+    #[inline(always)]
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Returns the response to the request.
+    #[inline(always)]
+    pub fn response(&self) -> &OwnedResponse {
+        &self.response
+    }
+}
+
+/// A log of the requests dispatched by a recording [`Client`] and their
+/// responses.
+///
+/// A `Recording` is retrieved from a `Client` with [`Client::take_recording()`]
+/// and can be replayed against another `Client`, perhaps for a newer build of
+/// the application, with [`Client::replay()`].
+///
+/// # Serialization
+///
+/// A `Recording` is serialized to a line-based text format by its `Display`
+/// implementation and parsed from that format by [`Recording::parse()`] or its
+/// `FromStr` implementation. Each exchange is a block of lines, separated from
+/// the next by an empty line. Request lines begin with `>`, response lines with
+/// `<`:
+///
+/// ```text
+/// > POST /message
+/// > H Content-Type: text/plain; charset=utf-8
+/// > B SGVsbG8sIHdvcmxkIQ==
+/// < 201
+/// < H Content-Type: text/plain; charset=utf-8
+/// < B Q3JlYXRlZA==
+/// ```
+///
+/// The first line of a block contains the request's method and URI, the
+/// status code follows the request's headers (`H`) and body (`B`, in base64),
+/// and the response's headers and body follow the status code.
+///
+/// [`Client`]: /rocket/local/struct.Client.html
+/// [`Client::take_recording()`]: /rocket/local/struct.Client.html#method.take_recording
+/// [`Client::replay()`]: /rocket/local/struct.Client.html#method.replay
+/// [`Recording::parse()`]: #method.parse
+#[derive(Debug, Default)]
+pub struct Recording {
+    exchanges: Vec<Exchange>,
+}
+
+impl Recording {
+    /// Returns an empty `Recording`.
+    #[inline(always)]
+    pub fn new() -> Recording {
+        Recording { exchanges: vec![] }
+    }
+
+    #[inline(always)]
+    pub(crate) fn push(&mut self, exchange: Exchange) {
+        self.exchanges.push(exchange);
+    }
+
+    /// Returns the recorded exchanges in the order they occurred.
+    #[inline(always)]
+    pub fn exchanges(&self) -> &[Exchange] {
+        &self.exchanges
+    }
+
+    /// Returns the number of recorded exchanges.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.exchanges.len()
+    }
+
+    /// Returns `true` if no exchanges were recorded.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.exchanges.is_empty()
+    }
+
+    /// Parses a `Recording` serialized by its `Display` implementation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error message identifying the offending line if `string`
+    /// isn't a valid serialized `Recording`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Recording;
+    ///
+    /// let recording = Recording::parse("> GET /\n< 200\n< B aGk=\n").unwrap();
+    /// assert_eq!(recording.len(), 1);
+    /// assert_eq!(recording.exchanges()[0].uri(), "/");
+    /// assert_eq!(recording.exchanges()[0].response().body(), Some(&b"hi"[..]));
+    ///
+    /// assert!(Recording::parse("GET /").is_err());
+    /// ```
+    pub fn parse(string: &str) -> Result<Recording, String> {
+        let mut recording = Recording::new();
+        let mut block = vec![];
+        for (i, line) in string.lines().enumerate() {
+            if !line.trim().is_empty() {
+                block.push((i + 1, line));
+            } else if !block.is_empty() {
+                recording.push(parse_exchange(&block)?);
+                block.clear();
+            }
+        }
+
+        if !block.is_empty() {
+            recording.push(parse_exchange(&block)?);
+        }
+
+        Ok(recording)
+    }
+}
+
+impl FromStr for Recording {
+    type Err = String;
+
+    #[inline(always)]
+    fn from_str(string: &str) -> Result<Recording, String> {
+        Recording::parse(string)
+    }
+}
+
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, exchange) in self.exchanges.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+
+            writeln!(f, "> {} {}", exchange.method, exchange.uri)?;
+            for header in exchange.headers.iter() {
+                writeln!(f, "> H {}: {}", header.name(), header.value())?;
+            }
+
+            if !exchange.body.is_empty() {
+                writeln!(f, "> B {}", base64::encode(&exchange.body))?;
+            }
+
+            let response = &exchange.response;
+            writeln!(f, "< {}", response.status().code)?;
+            for header in response.headers().iter() {
+                writeln!(f, "< H {}: {}", header.name(), header.value())?;
+            }
+
+            if let Some(body) = response.body() {
+                writeln!(f, "< B {}", base64::encode(body))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_header(line: usize, string: &str) -> Result<Header<'static>, String> {
+    match string.find(": ") {
+        Some(i) => Ok(Header::new(string[..i].to_string(), string[(i + 2)..].to_string())),
+        None => Err(format!("line {}: expected a header, found `{}`", line, string))
+    }
+}
+
+fn parse_body(line: usize, string: &str) -> Result<Vec<u8>, String> {
+    base64::decode(string).map_err(|e| format!("line {}: invalid body: {}", line, e))
+}
+
+fn parse_exchange(block: &[(usize, &str)]) -> Result<Exchange, String> {
+    let (first, request_line) = block[0];
+    let mut parts = request_line.splitn(3, ' ');
+    let (method, uri) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(">"), Some(method), Some(uri)) => match method.parse::<Method>() {
+            Ok(method) => (method, uri.to_string()),
+            Err(_) => return Err(format!("line {}: unknown method `{}`", first, method))
+        },
+        _ => return Err(format!("line {}: expected `> METHOD URI`", first))
+    };
+
+    let (mut headers, mut body) = (HeaderMap::new(), vec![]);
+    let (mut status, mut response_headers, mut response_body) = (None, HeaderMap::new(), None);
+    for &(line, string) in &block[1..] {
+        if string.starts_with("> ") && status.is_some() {
+            return Err(format!("line {}: request line after the response status", line));
+        } else if string.starts_with("> H ") {
+            headers.add(parse_header(line, &string[4..])?);
+        } else if string.starts_with("> B ") {
+            body = parse_body(line, &string[4..])?;
+        } else if string.starts_with("< H ") && status.is_some() {
+            response_headers.add(parse_header(line, &string[4..])?);
+        } else if string.starts_with("< B ") && status.is_some() {
+            response_body = Some(parse_body(line, &string[4..])?);
+        } else if string.starts_with("< ") && status.is_none() {
+            let code = string[2..].trim().parse::<u16>()
+                .map_err(|_| format!("line {}: invalid status `{}`", line, &string[2..]))?;
+
+            status = Some(Status::raw(code));
+        } else {
+            return Err(format!("line {}: unexpected line `{}`", line, string));
+        }
+    }
+
+    let status = status.ok_or_else(|| format!("line {}: missing response status", first))?;
+    let response = OwnedResponse::new(status, response_headers, response_body);
+    Ok(Exchange::new(method, uri, headers, body, response))
+}
+
+/// A replayed exchange whose response differs from the recorded response.
+///
+/// Returned by [`Client::replay()`].
+///
+/// [`Client::replay()`]: /rocket/local/struct.Client.html#method.replay
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// The index of the exchange in the replayed recording.
+    pub index: usize,
+    /// The method of the replayed request.
+    pub method: Method,
+    /// The URI of the replayed request.
+    pub uri: String,
+    /// The status of the recorded response.
+    pub expected: Status,
+    /// The status of the response to the replayed request.
+    pub actual: Status,
+    /// Whether the body of the response differs from the recorded body.
+    pub body_differs: bool,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "exchange {} ({} {}): ", self.index, self.method, self.uri)?;
+        if self.expected != self.actual {
+            write!(f, "expected `{}`, found `{}`", self.expected, self.actual)?;
+            if self.body_differs {
+                write!(f, "; ")?;
+            }
+        }
+
+        if self.body_differs {
+            write!(f, "the response body differs")?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,12 +1,24 @@
 use std::fmt;
+use std::io::{Read, Cursor};
 use std::rc::Rc;
 use std::mem::transmute;
 use std::net::SocketAddr;
 use std::ops::{Deref, DerefMut};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
 
 use {Request, Response, Data};
-use local::Client;
-use http::{Header, Cookie};
+use response::Body;
+use local::{Client, Multipart, OwnedResponse, Exchange, Snapshot};
+use local::owned::owned_headers;
+use http::{Method, Header, HeaderMap, Cookie, Status, ContentType};
+use metrics::Timings;
+
+#[cfg(feature = "tls")] use std::io;
+#[cfg(feature = "tls")] use std::path::Path;
+#[cfg(feature = "tls")] use http::Certificate;
+#[cfg(feature = "tls")] use tls::TlsInfo;
 
 /// A structure representing a local request as created by [`Client`].
 ///
@@ -17,7 +29,8 @@ use http::{Header, Cookie};
 /// [`add_header`] method. Cookies can be added via the [`cookie`] builder
 /// method. The remote IP address can be set via the [`remote`] builder method.
 /// The body of the request can be set via the [`body`] builder method or
-/// [`set_body`] method.
+/// [`set_body`] method. A `multipart/form-data` body can be built with a
+/// [`Multipart`] and set via the [`multipart`] builder method.
 ///
 /// ## Example
 ///
@@ -65,6 +78,9 @@ use http::{Header, Cookie};
 /// [`remote`]: #method.remote
 /// [`body`]: #method.body
 /// [`set_body`]: #method.set_body
+/// [`body_reader`]: #method.body_reader
+/// [`multipart`]: #method.multipart
+/// [`Multipart`]: /rocket/local/struct.Multipart.html
 /// [`dispatch`]: #method.dispatch
 /// [`mut_dispatch`]: #method.mut_dispatch
 /// [`cloned_dispatch`]: #method.cloned_dispatch
@@ -72,7 +88,11 @@ pub struct LocalRequest<'c> {
     client: &'c Client,
     ptr: *mut Request<'c>,
     request: Rc<Request<'c>>,
-    data: Vec<u8>
+    data: Vec<u8>,
+    reader: Option<Box<Read + Send>>,
+    chunks: Option<Vec<usize>>,
+    timeout: Option<Duration>,
+    explicit_cookies: Vec<String>,
 }
 
 impl<'c> LocalRequest<'c> {
@@ -80,7 +100,14 @@ impl<'c> LocalRequest<'c> {
     pub(crate) fn new(client: &'c Client, request: Request<'c>) -> LocalRequest<'c> {
         let mut request = Rc::new(request);
         let ptr = Rc::get_mut(&mut request).unwrap() as *mut Request;
-        LocalRequest { client, ptr, request, data: vec![] }
+        LocalRequest {
+            client, ptr, request,
+            data: vec![],
+            reader: None,
+            chunks: None,
+            timeout: None,
+            explicit_cookies: vec![],
+        }
     }
 
     /// Retrieves the inner `Request` as seen by Rocket.
@@ -168,6 +195,169 @@ impl<'c> LocalRequest<'c> {
         self
     }
 
+    /// Sets the scheme the request is made with to `scheme`, as in `https`.
+    /// By default, the scheme is `https` if TLS is enabled and `http`
+    /// otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// let req = client.get("/").scheme("https");
+    /// assert_eq!(req.inner().scheme(), "https");
+    /// ```
+    #[inline]
+    pub fn scheme(mut self, scheme: &str) -> Self {
+        self.request().set_scheme(scheme);
+        self
+    }
+
+    /// Sets the authority the request is made to, as in `example.com:8443`,
+    /// by replacing the request's `Host` header.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// let req = client.get("/").host("example.com:8443");
+    /// assert_eq!(req.inner().headers().get_one("Host"), Some("example.com:8443"));
+    /// ```
+    #[inline]
+    pub fn host(mut self, authority: &str) -> Self {
+        self.request().replace_header(Header::new("Host", authority.to_string()));
+        self
+    }
+
+    /// Sets the certificate the client presented during the TLS handshake to
+    /// `certificate`, a chain of one certificate. This method is only
+    /// available when the `tls` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    /// use rocket::http::Certificate;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// # let der = vec![0x30];
+    /// let req = client.get("/").certificate(Certificate(der));
+    /// ```
+    #[cfg(feature = "tls")]
+    #[inline]
+    pub fn certificate(self, certificate: Certificate) -> Self {
+        self.certificates(vec![certificate])
+    }
+
+    /// Sets the certificate chain the client presented during the TLS
+    /// handshake to `certificates`, leaf certificate first, followed by any
+    /// intermediate certificates. This method is only available when the
+    /// `tls` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    /// use rocket::http::Certificate;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// # let (leaf, intermediate) = (vec![0x30], vec![0x30]);
+    /// let req = client.get("/")
+    ///     .certificates(vec![Certificate(leaf), Certificate(intermediate)]);
+    /// ```
+    #[cfg(feature = "tls")]
+    #[inline]
+    pub fn certificates(mut self, certificates: Vec<Certificate>) -> Self {
+        self.request().set_client_certificates(certificates);
+        self
+    }
+
+    /// Reads a certificate chain in PEM format, leaf certificate first, from
+    /// the file at `path` and sets it as the chain the client presented
+    /// during the TLS handshake. This method is only available when the `tls`
+    /// feature is enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind `InvalidData` if the file contains no
+    /// certificates or is not valid PEM, and any I/O error encountered while
+    /// reading it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// # if false {
+    /// let req = client.get("/")
+    ///     .certificate_file("tests/certs/client-chain.pem")
+    ///     .expect("valid certificate chain");
+    /// # }
+    /// ```
+    #[cfg(feature = "tls")]
+    pub fn certificate_file<P: AsRef<Path>>(self, path: P) -> io::Result<Self> {
+        use hyper_sync_rustls::util;
+        use hyper_sync_rustls::util::Error::Io;
+
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed PEM file");
+        let certificates = util::load_certs(path.as_ref())
+            .map_err(|e| match e { Io(e) => e, _ => invalid() })?;
+
+        if certificates.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(self.certificates(certificates))
+    }
+
+    /// Sets the protocol the client selected via ALPN during the TLS handshake
+    /// to `protocol`. This method is only available when the `tls` feature is
+    /// enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// let req = client.get("/").alpn_protocol("http/1.1");
+    /// ```
+    #[cfg(feature = "tls")]
+    #[inline]
+    pub fn alpn_protocol<S: Into<String>>(mut self, protocol: S) -> Self {
+        self.request().set_alpn_protocol(protocol);
+        self
+    }
+
+    /// Sets the parameters negotiated by the TLS handshake the request was
+    /// received on to `info`. This method is only available when the `tls`
+    /// feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    /// use rocket::tls::TlsInfo;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// let req = client.get("/").tls_info(TlsInfo {
+    ///     protocol_version: "TLSv1.2".into(),
+    ///     cipher_suite: "TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256".into(),
+    ///     sni_hostname: None,
+    ///     resumed: false,
+    /// });
+    /// ```
+    #[cfg(feature = "tls")]
+    #[inline]
+    pub fn tls_info(mut self, info: TlsInfo) -> Self {
+        self.request().set_tls_info(info);
+        self
+    }
+
     /// Add a cookie to this request.
     ///
     /// # Examples
@@ -185,7 +375,8 @@ impl<'c> LocalRequest<'c> {
     ///     .cookie(Cookie::new("user_id", "12"));
     /// ```
     #[inline]
-    pub fn cookie<'a>(self, cookie: Cookie<'a>) -> Self {
+    pub fn cookie<'a>(mut self, cookie: Cookie<'a>) -> Self {
+        self.explicit_cookies.push(cookie.name().to_string());
         self.request.cookies().add_original(cookie.into_owned());
         self
     }
@@ -206,8 +397,9 @@ impl<'c> LocalRequest<'c> {
     /// let req = client.get("/").cookies(cookies);
     /// ```
     #[inline]
-    pub fn cookies<'a>(self, cookies: Vec<Cookie<'a>>) -> Self {
+    pub fn cookies<'a>(mut self, cookies: Vec<Cookie<'a>>) -> Self {
         for cookie in cookies {
+            self.explicit_cookies.push(cookie.name().to_string());
             self.request.cookies().add_original(cookie.into_owned());
         }
 
@@ -231,16 +423,12 @@ impl<'c> LocalRequest<'c> {
     /// let req = client.get("/").private_cookie(Cookie::new("user_id", "sb"));
     /// ```
     #[inline]
-    pub fn private_cookie(self, cookie: Cookie<'static>) -> Self {
+    pub fn private_cookie(mut self, cookie: Cookie<'static>) -> Self {
+        self.explicit_cookies.push(cookie.name().to_string());
         self.request.cookies().add_original_private(cookie);
         self
     }
 
-    // TODO: For CGI, we want to be able to set the body to be stdin without
-    // actually reading everything into a vector. Can we allow that here while
-    // keeping the simplicity? Looks like it would require us to reintroduce a
-    // NetStream::Local(Box<Read>) or something like that.
-
     /// Set the body (data) of the request.
     ///
     /// # Examples
@@ -259,7 +447,7 @@ impl<'c> LocalRequest<'c> {
     /// ```
     #[inline]
     pub fn body<S: AsRef<[u8]>>(mut self, body: S) -> Self {
-        self.data = body.as_ref().into();
+        self.set_body(body);
         self
     }
 
@@ -280,6 +468,156 @@ impl<'c> LocalRequest<'c> {
     #[inline]
     pub fn set_body<S: AsRef<[u8]>>(&mut self, body: S) {
         self.data = body.as_ref().into();
+        self.reader = None;
+        self.chunks = None;
+    }
+
+    /// Set the body (data) of the request to be streamed from `reader`.
+    ///
+    /// Unlike [`body`], the body is not buffered in memory: it is read from
+    /// `reader`, until EOF, as the application reads the request's data. This
+    /// makes it possible to test with very large bodies or to feed the body
+    /// from a stream such as standard input.
+    ///
+    /// Because `reader` can only be read once, a body set via this method is
+    /// not cloned by [`cloned_dispatch`]; the clone is dispatched with an
+    /// empty body instead.
+    ///
+    /// [`body`]: #method.body
+    /// [`cloned_dispatch`]: #method.cloned_dispatch
+    ///
+    /// # Examples
+    ///
+    /// Stream the body from a file.
+    ///
+    /// ```rust
+    /// use std::fs::File;
+    /// use rocket::local::Client;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// # if false {
+    /// let file = File::open("/tmp/large-upload.bin").expect("file");
+    /// # #[allow(unused_variables)]
+    /// let req = client.post("/upload").body_reader(file);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn body_reader<R: Read + Send + 'static>(mut self, reader: R) -> Self {
+        self.set_body_reader(reader);
+        self
+    }
+
+    /// Set the body (data) of the request to be streamed from `reader` without
+    /// consuming `self`. See [`body_reader`](#method.body_reader) for details.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io::{self, Read};
+    /// use rocket::local::Client;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// let mut req = client.post("/");
+    /// req.set_body_reader(io::repeat(b'a').take(1 << 30));
+    /// ```
+    #[inline]
+    pub fn set_body_reader<R: Read + Send + 'static>(&mut self, reader: R) {
+        self.data = vec![];
+        self.reader = Some(Box::new(reader));
+        self.chunks = None;
+    }
+
+    /// Set the body (data) of the request to `chunks`, sent with `chunked`
+    /// transfer encoding, and the `Transfer-Encoding` header to `chunked`.
+    ///
+    /// The application reads the body through the same chunked decoder used
+    /// for chunked requests received over the network, with each of `chunks`
+    /// as one chunk. Empty chunks are skipped since an empty chunk terminates
+    /// a chunked body.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// # #[allow(unused_variables)]
+    /// let req = client.post("/upload").chunked_body(vec!["Hello, ", "world!"]);
+    /// ```
+    #[inline]
+    pub fn chunked_body<I, C>(mut self, chunks: I) -> Self
+        where I: IntoIterator<Item = C>, C: AsRef<[u8]>
+    {
+        let (mut data, mut sizes) = (vec![], vec![]);
+        for chunk in chunks {
+            let chunk = chunk.as_ref();
+            if !chunk.is_empty() {
+                data.extend_from_slice(chunk);
+                sizes.push(chunk.len());
+            }
+        }
+
+        self.set_body(data);
+        self.chunks = Some(sizes);
+        self.request().replace_header(Header::new("Transfer-Encoding", "chunked"));
+        self
+    }
+
+    /// Set the body (data) of the request to the `multipart/form-data` encoding
+    /// of `form` and the `Content-Type` header to `multipart/form-data` with
+    /// the generated boundary, replacing any existing `Content-Type`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use rocket::local::{Client, Multipart};
+    /// use rocket::http::ContentType;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// let form = Multipart::new()
+    ///     .text("description", "quarterly report")
+    ///     .file("report", "report.csv", ContentType::CSV, "q1,q2\n10,20\n");
+    ///
+    /// # #[allow(unused_variables)]
+    /// let req = client.post("/upload").multipart(form);
+    /// ```
+    #[inline]
+    pub fn multipart(mut self, form: Multipart) -> Self {
+        let (content_type, body) = form.encode();
+        self.request().replace_header(content_type);
+        self.set_body(body);
+        self
+    }
+
+    /// Sets a deadline for the handling of this request.
+    ///
+    /// When a timeout is set, the request is dispatched on a separate thread.
+    /// If no response is produced within `timeout`, dispatching returns a
+    /// synthetic `503 Service Unavailable` response whose body names the
+    /// request and the timeout, and the thread is abandoned. A handler that
+    /// never returns thus fails a test instead of hanging it. If redirects are
+    /// followed, each redirect is dispatched with the same timeout.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use rocket::local::Client;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// let response = client.get("/").timeout(Duration::from_secs(5)).dispatch();
+    /// assert_eq!(response.status().code, 404);
+    /// ```
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    // Takes the body out of `self`, leaving an empty body in its place.
+    fn take_data(&mut self) -> Data {
+        let data = ::std::mem::replace(&mut self.data, vec![]);
+        local_data(data, self.chunks.take(), self.reader.take())
     }
 
     /// Dispatches the request, returning the response.
@@ -297,14 +635,29 @@ impl<'c> LocalRequest<'c> {
     /// ```
     #[inline(always)]
     pub fn dispatch(mut self) -> LocalResponse<'c> {
-        let req = unsafe { transmute(self.request()) };
-        let response = self.client.rocket().dispatch(req, Data::local(self.data));
-        self.client.update_cookies(&response);
+        self.dispatch_following()
+    }
 
-        LocalResponse {
-            _request: self.request,
-            response: response
-        }
+    /// Dispatches the request, returning a fully buffered response that owns
+    /// its data and can be sent to other threads.
+    ///
+    /// This method consumes `self`. Use it to dispatch requests concurrently
+    /// from multiple threads against a shared `Client`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::thread;
+    /// use rocket::local::Client;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// let response = client.get("/").dispatch_owned();
+    /// let status = thread::spawn(move || response.status()).join().unwrap();
+    /// assert_eq!(status.code, 404);
+    /// ```
+    #[inline(always)]
+    pub fn dispatch_owned(self) -> OwnedResponse {
+        self.dispatch().into_owned()
     }
 
     /// Dispatches the request, returning the response.
@@ -328,6 +681,8 @@ impl<'c> LocalRequest<'c> {
         let cloned = (*self.request).clone();
         let mut req = LocalRequest::new(self.client, cloned);
         req.data = self.data.clone();
+        req.chunks = self.chunks.clone();
+        req.timeout = self.timeout;
         req.dispatch()
     }
 
@@ -360,15 +715,226 @@ impl<'c> LocalRequest<'c> {
     /// ```
     #[inline(always)]
     pub fn mut_dispatch(&mut self) -> LocalResponse<'c> {
-        let data = ::std::mem::replace(&mut self.data, vec![]);
-        let req = unsafe { transmute(self.request()) };
-        let response = self.client.rocket().dispatch(req, Data::local(data));
+        self.dispatch_following()
+    }
+
+    // Returns `true` if `self` was created by `client`.
+    pub(crate) fn is_from(&self, client: &Client) -> bool {
+        ::std::ptr::eq(self.client, client)
+    }
+
+    // Replaces the cookies the request was created with from the client's
+    // cookie jar with the jar's current cookies. Cookies added to the request
+    // explicitly are kept.
+    pub(crate) fn sync_cookies(&mut self) {
+        if !self.client.tracks_cookies() {
+            return;
+        }
+
+        let current = self.client.cookies();
+        let mut cookies = self.request.cookies();
+        let stale: Vec<String> = cookies.iter()
+            .map(|cookie| cookie.name().to_string())
+            .filter(|name| !self.explicit_cookies.contains(name))
+            .collect();
+
+        for name in stale {
+            cookies.remove_original(&name);
+        }
+
+        for cookie in current {
+            if !self.explicit_cookies.iter().any(|name| name == cookie.name()) {
+                cookies.add_original(cookie);
+            }
+        }
+    }
+
+    // Dispatches the request without following redirects.
+    pub(crate) fn dispatch_once(&mut self) -> LocalResponse<'c> {
+        let recorded = match self.client.is_recording() {
+            true => Some(self.recorded_request()),
+            false => None
+        };
+
+        let response = match self.timeout {
+            Some(timeout) => self.dispatch_watched(timeout),
+            None => {
+                let data = self.take_data();
+                let req = unsafe { transmute(self.request()) };
+                self.client.rocket().dispatch(req, data)
+            }
+        };
+
         self.client.update_cookies(&response);
 
-        LocalResponse {
+        let mut response = LocalResponse {
             _request: self.request.clone(),
             response: response
+        };
+
+        if let Some((method, uri, headers, body)) = recorded {
+            let response_headers = owned_headers(response.headers());
+            let response_body = response.buffered_body();
+            let owned = OwnedResponse::new(response.status(), response_headers, response_body);
+            self.client.record_exchange(Exchange::new(method, uri, headers, body, owned));
         }
+
+        response
+    }
+
+    // Dispatches a copy of the request on a new thread, returning a `503`
+    // response if the thread doesn't respond within `timeout`.
+    fn dispatch_watched(&mut self, timeout: Duration) -> Response<'c> {
+        let rocket = self.client.shared_rocket();
+        let (method, uri) = (self.request.method(), self.request.uri().as_str().to_string());
+        let headers = owned_headers(self.request.headers());
+        let cookies: Vec<_> = self.request.cookies().iter()
+            .map(|cookie| cookie.clone().into_owned())
+            .collect();
+
+        let (remote, scheme) = (self.request.remote(), self.request.scheme().to_string());
+        #[cfg(feature = "tls")]
+        let certificates = self.request.peer_certificates().cloned();
+        #[cfg(feature = "tls")]
+        let alpn_protocol = self.request.alpn_protocol().map(|p| p.to_string());
+        #[cfg(feature = "tls")]
+        let tls_info = self.request.shared_tls_info().cloned();
+        let body = ::std::mem::replace(&mut self.data, vec![]);
+        let (chunks, reader) = (self.chunks.take(), self.reader.take());
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut request = Request::new(&rocket, method, uri);
+            for header in headers.into_iter() {
+                request.add_header(header);
+            }
+
+            for cookie in cookies {
+                request.cookies().add_original(cookie);
+            }
+
+            if let Some(remote) = remote {
+                request.set_remote(remote);
+            }
+
+            request.set_scheme(&scheme);
+            #[cfg(feature = "tls")]
+            {
+                if let Some(certificates) = certificates {
+                    request.set_peer_certificates(certificates);
+                }
+
+                if let Some(protocol) = alpn_protocol {
+                    request.set_alpn_protocol(protocol);
+                }
+
+                if let Some(info) = tls_info {
+                    request.set_shared_tls_info(info);
+                }
+            }
+
+            let data = local_data(body, chunks, reader);
+            let (status, headers, body) = {
+                let mut response = rocket.dispatch(&mut request, data);
+                let body = response.take_body().map(|body| match body {
+                    Body::Chunked(_, size) => (body.into_bytes(), Some(size)),
+                    Body::Sized(..) => (body.into_bytes(), None),
+                });
+
+                (response.status(), owned_headers(response.headers()), body)
+            };
+
+            // The receiver is gone if the deadline passed; nothing to do then.
+            let _ = sender.send((status, headers, body, request.timings()));
+        });
+
+        let mut response = Response::new();
+        match receiver.recv_timeout(timeout) {
+            Ok((status, headers, body, timings)) => {
+                self.request.update_timings(|t| *t = timings);
+                response.set_status(status);
+                for header in headers.into_iter() {
+                    response.adjoin_header(header);
+                }
+
+                match body {
+                    Some((bytes, Some(size))) => {
+                        let bytes = bytes.unwrap_or_default();
+                        response.set_chunked_body(Cursor::new(bytes), size);
+                    }
+                    Some((bytes, None)) => {
+                        response.set_sized_body(Cursor::new(bytes.unwrap_or_default()));
+                    }
+                    None => {  }
+                }
+            }
+            Err(_) => {
+                error_!("{} {} timed out after {:?}.", self.request.method(),
+                        self.request.uri(), timeout);
+
+                let message = format!("{} {} timed out after {:?}", self.request.method(),
+                                      self.request.uri(), timeout);
+
+                response.set_status(Status::ServiceUnavailable);
+                response.set_header(ContentType::Plain);
+                response.set_sized_body(Cursor::new(message));
+            }
+        }
+
+        response
+    }
+
+    // Captures the parts of the request that are recorded. Cookies are
+    // recorded as a `Cookie` header.
+    fn recorded_request(&self) -> (Method, String, HeaderMap<'static>, Vec<u8>) {
+        let mut headers = owned_headers(self.request.headers());
+        let cookies: Vec<_> = self.request.cookies().iter()
+            .map(|cookie| format!("{}={}", cookie.name(), cookie.value()))
+            .collect();
+
+        if !cookies.is_empty() {
+            headers.add(Header::new("Cookie", cookies.join("; ")));
+        }
+
+        let uri = self.request.uri().as_str().to_string();
+        (self.request.method(), uri, headers, self.data.clone())
+    }
+
+    // Dispatches the request, then follows redirects if the client does.
+    fn dispatch_following(&mut self) -> LocalResponse<'c> {
+        let limit = self.client.redirect_limit();
+        let mut body = match limit > 0 && self.reader.is_none() {
+            true => self.data.clone(),
+            false => vec![]
+        };
+
+        let mut response = self.dispatch_once();
+        for _ in 0..limit {
+            let mut next = match response.redirect(self.client, &mut body) {
+                Some(request) => request,
+                None => break
+            };
+
+            next.timeout = self.timeout;
+            #[cfg(feature = "tls")]
+            {
+                if let Some(certificates) = self.request.peer_certificates() {
+                    next.request().set_peer_certificates(certificates.clone());
+                }
+
+                if let Some(protocol) = self.request.alpn_protocol() {
+                    next.request().set_alpn_protocol(protocol);
+                }
+
+                if let Some(info) = self.request.shared_tls_info() {
+                    next.request().set_shared_tls_info(info.clone());
+                }
+            }
+
+            response = next.dispatch_once();
+        }
+
+        response
     }
 }
 
@@ -380,18 +946,450 @@ impl<'c> fmt::Debug for LocalRequest<'c> {
 
 /// A structure representing a response from dispatching a local request.
 ///
-/// This structure is a thin wrapper around [`Response`]. It implements no
-/// methods of its own; all functionality is exposed via the `Deref` and
-/// `DerefMut` implementations with a target of `Response`. In other words, when
-/// invoking methods, a `LocalResponse` can be treated exactly as if it were a
-/// `Response`.
+/// This structure is a thin wrapper around [`Response`]. Most functionality is
+/// exposed via the `Deref` and `DerefMut` implementations with a target of
+/// `Response`. In other words, when invoking methods, a `LocalResponse` can be
+/// treated exactly as if it were a `Response`.
+///
+/// # Assertions
+///
+/// `LocalResponse` additionally implements chainable assertion methods for
+/// use in tests: [`assert_status`], [`assert_header`], [`assert_body`],
+/// [`assert_body_contains`] and, with the `json` feature enabled,
+/// [`assert_json`]. On failure, these methods panic with a message that
+/// includes the full response. Assertions on the body buffer it in memory so
+/// that it can be inspected again afterwards.
+///
+/// ```rust
+/// use rocket::local::Client;
+/// use rocket::http::Status;
+///
+/// let client = Client::new(rocket::ignite()).expect("valid rocket");
+/// client.get("/").dispatch()
+///     .assert_status(Status::NotFound)
+///     .assert_header("Content-Type", "text/html; charset=utf-8")
+///     .assert_body_contains("404: Not Found");
+/// ```
 ///
 /// [`Response`]: /rocket/struct.Response.html
+/// [`assert_status`]: #method.assert_status
+/// [`assert_header`]: #method.assert_header
+/// [`assert_body`]: #method.assert_body
+/// [`assert_body_contains`]: #method.assert_body_contains
+/// [`assert_json`]: #method.assert_json
+/// [`json`]: #method.json
 pub struct LocalResponse<'c> {
     _request: Rc<Request<'c>>,
     response: Response<'c>,
 }
 
+impl<'c> LocalResponse<'c> {
+    /// Asserts that the status of the response is `status`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, displaying the full response, if the status is not `status`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    /// use rocket::http::Status;
+    ///
+    /// let client = Client::new(rocket::ignite()).expect("valid rocket");
+    /// client.get("/").dispatch().assert_status(Status::NotFound);
+    /// ```
+    pub fn assert_status(&mut self, status: Status) -> &mut Self {
+        if self.response.status() != status {
+            let message = format!("expected status `{}`, found `{}`",
+                                  status, self.response.status());
+            self.fail(message);
+        }
+
+        self
+    }
+
+    /// Asserts that the response contains a header named `name` with the
+    /// value `value`. Header names are compared case-insensitively. If
+    /// several headers are named `name`, any one of them may match.
+    ///
+    /// # Panics
+    ///
+    /// Panics, displaying the full response, if no such header exists.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    ///
+    /// let client = Client::new(rocket::ignite()).expect("valid rocket");
+    /// client.get("/").dispatch().assert_header("Server", "Rocket");
+    /// ```
+    pub fn assert_header<V: AsRef<str>>(&mut self, name: &str, value: V) -> &mut Self {
+        let value = value.as_ref();
+        if !self.response.headers().get(name).any(|v| v == value) {
+            let message = format!("expected header `{}: {}`", name, value);
+            self.fail(message);
+        }
+
+        self
+    }
+
+    /// Asserts that the body of the response is exactly `expected`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, displaying the full response, if the body differs or the
+    /// response has no body.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #![feature(plugin, decl_macro)]
+    /// # #![plugin(rocket_codegen)]
+    /// # extern crate rocket;
+    /// use rocket::local::Client;
+    ///
+    /// #[get("/")]
+    /// fn hello() -> &'static str { "Hello, world!" }
+    ///
+    /// # fn main() {
+    /// let rocket = rocket::ignite().mount("/", routes![hello]);
+    /// let client = Client::new(rocket).expect("valid rocket");
+    /// client.get("/").dispatch().assert_body("Hello, world!");
+    /// # }
+    /// ```
+    pub fn assert_body<B: AsRef<[u8]>>(&mut self, expected: B) -> &mut Self {
+        let expected = expected.as_ref();
+        if self.buffered_body().as_ref().map(|b| &b[..]) != Some(expected) {
+            let message = format!("expected body `{}`", String::from_utf8_lossy(expected));
+            self.fail(message);
+        }
+
+        self
+    }
+
+    /// Asserts that the body of the response, as a string, contains `needle`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, displaying the full response, if the body doesn't contain
+    /// `needle` or the response has no body.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    ///
+    /// let client = Client::new(rocket::ignite()).expect("valid rocket");
+    /// client.get("/").dispatch().assert_body_contains("Not Found");
+    /// ```
+    pub fn assert_body_contains<S: AsRef<str>>(&mut self, needle: S) -> &mut Self {
+        let needle = needle.as_ref();
+        let found = self.buffered_body()
+            .map(|body| String::from_utf8_lossy(&body).contains(needle))
+            .unwrap_or(false);
+
+        if !found {
+            let message = format!("expected body to contain `{}`", needle);
+            self.fail(message);
+        }
+
+        self
+    }
+
+    /// Deserializes the JSON body of the response into a value of type `T`.
+    ///
+    /// Returns `None` if the response's `Content-Type` isn't `application/json`
+    /// or an `application/*+json` type, if the response has no body, or if the
+    /// body can't be deserialized into `T`. The body is buffered, so it can be
+    /// read again afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #![feature(plugin, decl_macro)]
+    /// # #![plugin(rocket_codegen)]
+    /// # extern crate rocket;
+    /// use std::collections::HashMap;
+    /// use rocket::local::Client;
+    /// use rocket::response::content;
+    ///
+    /// #[get("/")]
+    /// fn counts() -> content::Json<&'static str> {
+    ///     content::Json(r#"{ "apples": 3, "pears": 5 }"#)
+    /// }
+    ///
+    /// # fn main() {
+    /// let rocket = rocket::ignite().mount("/", routes![counts]);
+    /// let client = Client::new(rocket).expect("valid rocket");
+    /// let counts: HashMap<String, usize> = client.get("/").dispatch().json().unwrap();
+    /// assert_eq!(counts["apples"], 3);
+    /// # }
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn json<T: ::serde::de::DeserializeOwned>(&mut self) -> Option<T> {
+        let is_json = self.response.content_type().map_or(false, |ct| {
+            ct.top() == "application" && (ct.sub() == "json"
+                || ct.sub().as_str().to_ascii_lowercase().ends_with("+json"))
+        });
+
+        if !is_json {
+            return None;
+        }
+
+        self.buffered_body().and_then(|body| ::serde_json::from_slice(&body).ok())
+    }
+
+    /// Asserts that the body of the response is valid JSON for `T` and
+    /// returns the deserialized value.
+    ///
+    /// # Panics
+    ///
+    /// Panics, displaying the full response and the deserialization error, if
+    /// the body can't be deserialized into `T` or the response has no body.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #![feature(plugin, decl_macro)]
+    /// # #![plugin(rocket_codegen)]
+    /// # extern crate rocket;
+    /// use std::collections::HashMap;
+    /// use rocket::local::Client;
+    ///
+    /// #[get("/")]
+    /// fn counts() -> &'static str { r#"{ "apples": 3, "pears": 5 }"# }
+    ///
+    /// # fn main() {
+    /// let rocket = rocket::ignite().mount("/", routes![counts]);
+    /// let client = Client::new(rocket).expect("valid rocket");
+    /// let counts: HashMap<String, usize> = client.get("/").dispatch().assert_json();
+    /// assert_eq!(counts["pears"], 5);
+    /// # }
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn assert_json<T: ::serde::de::DeserializeOwned>(&mut self) -> T {
+        let result = match self.buffered_body() {
+            Some(body) => ::serde_json::from_slice(&body).map_err(|e| e.to_string()),
+            None => Err("response has no body".to_string())
+        };
+
+        match result {
+            Ok(value) => value,
+            Err(e) => self.fail(format!("expected JSON body: {}", e))
+        }
+    }
+
+    /// Returns the time Rocket spent dispatching the request, from the start
+    /// of routing until the response was finalized. This is the same as
+    /// `self.timings().total`.
+    ///
+    /// When redirects are followed, this is the time spent dispatching the
+    /// last request.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use rocket::local::Client;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// let response = client.get("/").dispatch();
+    /// assert!(response.time() < Duration::from_secs(1));
+    /// ```
+    #[inline]
+    pub fn time(&self) -> Duration {
+        self._request.timings().total
+    }
+
+    /// Returns the time Rocket spent in each phase of dispatching the request.
+    /// See [`Timings`] for details.
+    ///
+    /// [`Timings`]: /rocket/metrics/struct.Timings.html
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #![feature(plugin, decl_macro)]
+    /// # #![plugin(rocket_codegen)]
+    /// # extern crate rocket;
+    /// use rocket::local::Client;
+    ///
+    /// #[get("/")]
+    /// fn index() -> &'static str { "Hello, world!" }
+    ///
+    /// # fn main() {
+    /// let client = Client::new(rocket::ignite().mount("/", routes![index])).unwrap();
+    /// let timings = client.get("/").dispatch().timings();
+    /// assert!(timings.handler <= timings.total);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn timings(&self) -> Timings {
+        self._request.timings()
+    }
+
+    /// Buffers the response into an [`OwnedResponse`], which can be sent to
+    /// other threads.
+    ///
+    /// [`OwnedResponse`]: /rocket/local/struct.OwnedResponse.html
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// let response = client.get("/").dispatch().into_owned();
+    /// assert!(response.body_string().unwrap().contains("404"));
+    /// ```
+    #[inline]
+    pub fn into_owned(self) -> OwnedResponse {
+        OwnedResponse::from_local(self)
+    }
+
+    /// Buffers the response into a [`Snapshot`], a stable textual form of its
+    /// status, headers, and body for snapshot testing.
+    ///
+    /// [`Snapshot`]: /rocket/local/struct.Snapshot.html
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// let snapshot = client.get("/").dispatch().into_snapshot();
+    /// assert!(snapshot.to_string().starts_with("404 Not Found\n"));
+    /// ```
+    #[inline]
+    pub fn into_snapshot(self) -> Snapshot {
+        self.into_owned().into_snapshot()
+    }
+
+    // If `self` is a redirect, returns the request to its target, clearing
+    // `body` if the redirect changes the method to `GET`.
+    fn redirect(&self, client: &'c Client, body: &mut Vec<u8>) -> Option<LocalRequest<'c>> {
+        let previous = &self._request;
+        let location = self.response.headers().get_one("Location")?;
+        let method = match (self.response.status().code, previous.method()) {
+            (307, method) | (308, method) => method,
+            (303, Method::Head) => Method::Head,
+            (303, _) | (301, Method::Post) | (302, Method::Post) => Method::Get,
+            (301, method) | (302, method) => method,
+            _ => return None
+        };
+
+        let keep_body = method == previous.method();
+        if !keep_body {
+            body.clear();
+        }
+
+        let target = redirect_target(previous.uri().path(), location);
+        let mut request = client.req(method, target);
+        for header in previous.headers().iter() {
+            let is_body_header = header.name().eq_ignore_ascii_case("Content-Type")
+                || header.name().eq_ignore_ascii_case("Content-Length");
+
+            if keep_body || !is_body_header {
+                let (name, value) = (header.name().to_string(), header.value().to_string());
+                request.add_header(Header::new(name, value));
+            }
+        }
+
+        if let Some(remote) = previous.remote() {
+            request.request().set_remote(remote);
+        }
+
+        // An absolute location also determines the scheme and authority.
+        match location.find("://") {
+            Some(i) => {
+                let authority = location[(i + 3)..].split('/').next().unwrap_or("");
+                request.request().set_scheme(&location[..i]);
+                if !authority.is_empty() {
+                    let authority = authority.to_string();
+                    request.request().replace_header(Header::new("Host", authority));
+                }
+            }
+            None => request.request().set_scheme(previous.scheme())
+        }
+
+        // The previous request's cookies reflect the response's changes.
+        for cookie in previous.cookies().iter() {
+            request.request.cookies().add_original(cookie.clone());
+        }
+
+        request.set_body(&body[..]);
+        Some(request)
+    }
+
+    // Reads the body, if any, into memory and replaces it with an equivalent
+    // in-memory body so that it can be read again.
+    fn buffered_body(&mut self) -> Option<Vec<u8>> {
+        let body = self.response.take_body()?;
+        let chunk_size = match body {
+            Body::Chunked(_, size) => Some(size),
+            Body::Sized(..) => None
+        };
+
+        let bytes = body.into_bytes().unwrap_or_default();
+        match chunk_size {
+            Some(size) => self.response.set_chunked_body(Cursor::new(bytes.clone()), size),
+            None => self.response.set_sized_body(Cursor::new(bytes.clone()))
+        }
+
+        Some(bytes)
+    }
+
+    // Panics with `message` followed by the full response.
+    fn fail(&mut self, message: String) -> ! {
+        let mut response = format!("{}\n", self.response.status());
+        for header in self.response.headers().iter() {
+            response.push_str(&format!("{}\n", header));
+        }
+
+        if let Some(body) = self.buffered_body() {
+            response.push_str(&format!("\n{}\n", String::from_utf8_lossy(&body)));
+        }
+
+        panic!("assertion failed: {}\n\n{}", message, response);
+    }
+}
+
+// Returns the `Data` for a local body: streamed from `reader` if there is
+// one, otherwise `data`, chunk-encoded with `chunks` if there are any.
+fn local_data(data: Vec<u8>, chunks: Option<Vec<usize>>, reader: Option<Box<Read + Send>>)
+    -> Data
+{
+    match (reader, chunks) {
+        (Some(reader), _) => Data::local_stream(reader),
+        (None, Some(chunks)) => Data::local_chunked(&data, &chunks),
+        (None, None) => Data::local(data)
+    }
+}
+
+// Resolves the redirect `location` against the request path `base`, returning
+// the path and query of the target.
+fn redirect_target(base: &str, location: &str) -> String {
+    if let Some(i) = location.find("://") {
+        let rest = &location[(i + 3)..];
+        return match rest.find('/') {
+            Some(j) => rest[j..].to_string(),
+            None => "/".to_string()
+        };
+    }
+
+    if location.starts_with('/') {
+        return location.to_string();
+    }
+
+    match base.rfind('/') {
+        Some(i) => format!("{}{}", &base[..(i + 1)], location),
+        None => format!("/{}", location)
+    }
+}
+
 impl<'c> Deref for LocalResponse<'c> {
     type Target = Response<'c>;
 
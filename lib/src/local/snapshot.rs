@@ -0,0 +1,167 @@
+use std::fmt;
+use std::str;
+
+use base64;
+
+use http::Status;
+use local::OwnedResponse;
+
+/// The value printed in place of a redacted header's value.
+const REDACTED: &str = "[redacted]";
+
+/// The headers that are redacted by default because their values change from
+/// one run to the next.
+const VOLATILE_HEADERS: &[&str] = &["Date", "Expires", "Last-Modified"];
+
+/// A stable, textual representation of a response for snapshot testing.
+///
+/// A `Snapshot` is created via [`LocalResponse::into_snapshot()`] or
+/// [`OwnedResponse::into_snapshot()`] and serialized by its `Display`
+/// implementation. The first line is the status, followed by one line per
+/// header, sorted by name and then by value, an empty line, and the body.
+/// Bodies that aren't valid UTF-8 are written as `[<n> bytes, base64]`
+/// followed by the base64 encoded bytes on the next line. A response without a
+/// body is written as `[no body]`.
+///
+/// Headers whose values differ from run to run are _redacted_: their values are
+/// replaced with `[redacted]`. By default, the `Date`, `Expires`, and
+/// `Last-Modified` headers are redacted. More headers can be redacted with
+/// [`redact()`] and redacted headers revealed with [`reveal()`].
+///
+/// The serialized form only depends on the response's status, headers, and
+/// body, so it can be stored alongside a test and compared, as by a
+/// snapshot-testing framework, to catch unintended changes in the output.
+///
+/// # Example
+///
+/// ```rust
+/// # #![feature(plugin, decl_macro)]
+/// # #![plugin(rocket_codegen)]
+/// # extern crate rocket;
+/// use rocket::local::Client;
+///
+/// #[get("/")]
+/// fn hello() -> &'static str { "Hello, world!" }
+///
+/// # fn main() {
+/// let rocket = rocket::ignite().mount("/", routes![hello]);
+/// let client = Client::new(rocket).expect("valid rocket");
+/// let snapshot = client.get("/").dispatch().into_snapshot();
+///
+/// assert_eq!(snapshot.to_string(), "200 OK\n\
+///                                   Content-Type: text/plain; charset=utf-8\n\
+///                                   Server: Rocket\n\
+///                                   \n\
+///                                   Hello, world!");
+/// # }
+/// ```
+///
+/// [`LocalResponse::into_snapshot()`]: /rocket/local/struct.LocalResponse.html#method.into_snapshot
+/// [`OwnedResponse::into_snapshot()`]: /rocket/local/struct.OwnedResponse.html#method.into_snapshot
+/// [`redact()`]: #method.redact
+/// [`reveal()`]: #method.reveal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    status: Status,
+    headers: Vec<(String, String)>,
+    body: Option<Vec<u8>>,
+    redacted: Vec<String>,
+}
+
+impl Snapshot {
+    /// Captures the status, headers, and body of `response`.
+    pub(crate) fn new(response: OwnedResponse) -> Snapshot {
+        let status = response.status();
+        let mut headers: Vec<(String, String)> = response.headers().iter()
+            .map(|header| (header.name().to_string(), header.value().to_string()))
+            .collect();
+
+        headers.sort_by(|a, b| {
+            (a.0.to_ascii_lowercase(), &a.1).cmp(&(b.0.to_ascii_lowercase(), &b.1))
+        });
+
+        let redacted = VOLATILE_HEADERS.iter().map(|name| name.to_ascii_lowercase()).collect();
+        Snapshot {
+            status: status,
+            headers: headers,
+            body: response.into_body(),
+            redacted: redacted
+        }
+    }
+
+    /// Redacts the values of the headers named `name`, compared
+    /// case-insensitively.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// let snapshot = client.get("/").dispatch().into_snapshot().redact("Server");
+    /// assert!(snapshot.to_string().contains("Server: [redacted]\n"));
+    /// ```
+    pub fn redact(mut self, name: &str) -> Self {
+        let name = name.to_ascii_lowercase();
+        if !self.redacted.contains(&name) {
+            self.redacted.push(name);
+        }
+
+        self
+    }
+
+    /// Reveals the values of the headers named `name`, compared
+    /// case-insensitively, if they were redacted.
+    pub fn reveal(mut self, name: &str) -> Self {
+        let name = name.to_ascii_lowercase();
+        self.redacted.retain(|redacted| *redacted != name);
+        self
+    }
+
+    /// Returns the status of the response.
+    #[inline(always)]
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Returns the body of the response, if there is one.
+    #[inline(always)]
+    pub fn body(&self) -> Option<&[u8]> {
+        self.body.as_ref().map(|body| &body[..])
+    }
+
+    /// Returns the value of the header `name` as it appears in the snapshot,
+    /// redacted or not. If several headers are named `name`, the first in
+    /// sorted order is returned.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter()
+            .find(|&&(ref header, _)| header.eq_ignore_ascii_case(name))
+            .map(|&(ref header, ref value)| self.value_of(header, value))
+    }
+
+    // The value of the header `name` with value `value` as written out.
+    fn value_of<'a>(&self, name: &str, value: &'a str) -> &'a str {
+        match self.redacted.contains(&name.to_ascii_lowercase()) {
+            true => REDACTED,
+            false => value
+        }
+    }
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.status)?;
+        for &(ref name, ref value) in &self.headers {
+            writeln!(f, "{}: {}", name, self.value_of(name, value))?;
+        }
+
+        writeln!(f)?;
+        match self.body {
+            Some(ref body) => match str::from_utf8(body) {
+                Ok(string) => write!(f, "{}", string),
+                Err(_) => write!(f, "[{} bytes, base64]\n{}", body.len(), base64::encode(body))
+            },
+            None => write!(f, "[no body]")
+        }
+    }
+}
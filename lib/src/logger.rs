@@ -6,7 +6,7 @@ use std::fmt;
 use log;
 use yansi::Paint;
 
-struct RocketLogger(LoggingLevel);
+struct RocketLogger;
 
 /// Defines the different levels for log messages.
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
@@ -77,7 +77,7 @@ macro_rules! warn_ { ($($args:expr),+) => { log_!(warn: $($args),+); }; }
 impl log::Log for RocketLogger {
     #[inline(always)]
     fn enabled(&self, record: &log::Metadata) -> bool {
-        record.target().starts_with("launch") || record.level() <= self.0.max_log_level()
+        record.target().starts_with("launch") || record.level() <= level().max_log_level()
     }
 
     fn log(&self, record: &log::Record) {
@@ -87,7 +87,7 @@ impl log::Log for RocketLogger {
         }
 
         // Don't print Hyper or Rustls messages unless debug is enabled.
-        let configged_level = self.0;
+        let configged_level = level();
         let from_hyper = record.module_path().map_or(false, |m| m.starts_with("hyper::"));
         let from_rustls = record.module_path().map_or(false, |m| m.starts_with("rustls::"));
         if configged_level != LoggingLevel::Debug && (from_hyper || from_rustls) {
@@ -142,8 +142,9 @@ pub(crate) fn try_init(level: LoggingLevel, verbose: bool) {
     }
 
     push_max_level(level);
-    if let Err(e) = log::set_boxed_logger(Box::new(RocketLogger(level))) {
-        if verbose {
+    match log::set_boxed_logger(Box::new(RocketLogger)) {
+        Ok(_) => LEVEL.store(level_to_usize(level), Ordering::Release),
+        Err(e) => if verbose {
             eprintln!("Logger failed to initialize: {}", e);
         }
     }
@@ -153,6 +154,30 @@ use std::sync::atomic::{AtomicUsize, AtomicBool, Ordering};
 
 static PUSHED: AtomicBool = AtomicBool::new(false);
 static LAST_LOG_FILTER: AtomicUsize = AtomicUsize::new(0);
+static LEVEL: AtomicUsize = AtomicUsize::new(1);
+
+fn level_to_usize(level: LoggingLevel) -> usize {
+    match level {
+        LoggingLevel::Critical => 0,
+        LoggingLevel::Normal => 1,
+        LoggingLevel::Debug => 2,
+    }
+}
+
+/// The level of the messages Rocket's logger currently prints.
+pub(crate) fn level() -> LoggingLevel {
+    match LEVEL.load(Ordering::Acquire) {
+        0 => LoggingLevel::Critical,
+        1 => LoggingLevel::Normal,
+        _ => LoggingLevel::Debug,
+    }
+}
+
+/// Changes the level of the messages Rocket's logger prints to `level`.
+pub(crate) fn set_level(level: LoggingLevel) {
+    LEVEL.store(level_to_usize(level), Ordering::Release);
+    log::set_max_level(level.max_log_level().to_level_filter());
+}
 
 fn filter_to_usize(filter: log::LevelFilter) -> usize {
     match filter {
@@ -0,0 +1,353 @@
+use std::collections::HashMap;
+use std::net::{SocketAddr, Shutdown};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use http::hyper::net::NetworkStream;
+use data::NetStream;
+use super::{Metrics, Counter};
+
+/// A point-in-time summary of the connections being served by Rocket.
+///
+/// Returned by [`Rocket::connection_stats()`]. A connection is _active_ while a
+/// request it carried is being processed and _idle_ while it is being kept
+/// alive waiting for another request. `open` is always `active + idle`.
+///
+/// [`Rocket::connection_stats()`]: /rocket/struct.Rocket.html#method.connection_stats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ConnectionStats {
+    /// The number of connections currently open.
+    pub open: usize,
+    /// The number of open connections waiting for a new request.
+    pub idle: usize,
+    /// The number of open connections with a request in flight.
+    pub active: usize,
+}
+
+struct Connection {
+    // A handle to the underlying socket used to evict the connection. This is
+    // `None` for streams we don't know how to close.
+    stream: Option<NetStream>,
+    in_flight: usize,
+    served: usize,
+    last_used: Instant,
+}
+
+impl Connection {
+    #[inline(always)]
+    fn is_idle(&self) -> bool {
+        self.in_flight == 0
+    }
+
+    fn close(mut self) {
+        if let Some(ref mut stream) = self.stream {
+            let _ = stream.close(Shutdown::Both);
+        }
+    }
+}
+
+/// Marks the request on a tracked connection as complete when dropped.
+pub(crate) struct Tracked<'c> {
+    connections: &'c Connections,
+    remote: SocketAddr,
+    keep_alive: bool,
+}
+
+impl<'c> Tracked<'c> {
+    /// Whether the connection will be kept alive once the response has been
+    /// written. This is `false` if the client asked for the connection to be
+    /// closed or if the connection has carried as many requests as it may.
+    #[inline(always)]
+    pub(crate) fn keep_alive(&self) -> bool {
+        self.keep_alive
+    }
+}
+
+impl<'c> Drop for Tracked<'c> {
+    fn drop(&mut self) {
+        self.connections.end(self.remote, self.keep_alive);
+    }
+}
+
+/// Central registry of the connections being served.
+///
+/// Hyper doesn't tell us when a connection is opened or closed, so connections
+/// are keyed by the remote address and tracked as requests arrive on them.
+/// Because we hold a handle to each socket, idle connections that outlive the
+/// keep-alive timeout are reaped here, and the least recently used idle
+/// connections are evicted when there are more than `max_idle` of them. A
+/// connection is closed once it has carried `max_requests` requests.
+pub(crate) struct Connections {
+    conns: Mutex<HashMap<SocketAddr, Connection>>,
+    keep_alive: Option<Duration>,
+    max_idle: Option<usize>,
+    max_requests: Option<usize>,
+    open: Counter,
+    idle: Counter,
+    active: Counter,
+    evicted: Counter,
+    reaped: Counter,
+}
+
+impl Connections {
+    pub(crate) fn new(
+        metrics: &Metrics,
+        keep_alive: Option<Duration>,
+        max_idle: Option<usize>,
+        max_requests: Option<usize>
+    ) -> Connections {
+        Connections {
+            conns: Mutex::new(HashMap::new()),
+            keep_alive: keep_alive,
+            max_idle: max_idle,
+            max_requests: max_requests,
+            open: metrics.counter("connections.open"),
+            idle: metrics.counter("connections.idle"),
+            active: metrics.counter("connections.active"),
+            evicted: metrics.counter("connections.evicted"),
+            reaped: metrics.counter("connections.reaped"),
+        }
+    }
+
+    /// Records that a request has begun processing on the connection from
+    /// `remote` and returns a guard that records its completion when dropped.
+    /// `keep_alive` indicates whether the client asked for the connection to be
+    /// kept alive once the response has been written.
+    pub(crate) fn track(
+        &self,
+        remote: SocketAddr,
+        stream: Option<NetStream>,
+        keep_alive: bool
+    ) -> Tracked {
+        let served = self.begin(remote, stream);
+        let keep_alive = keep_alive && self.max_requests.map_or(true, |max| served < max);
+        Tracked { connections: self, remote: remote, keep_alive: keep_alive }
+    }
+
+    /// Records that a request has begun processing on the connection from
+    /// `remote`, registering the connection if it's new. Returns the number of
+    /// requests the connection has carried, including this one.
+    pub(crate) fn begin(&self, remote: SocketAddr, stream: Option<NetStream>) -> usize {
+        let mut conns = self.conns.lock().expect("connections lock");
+        let served = {
+            let conn = conns.entry(remote).or_insert_with(|| Connection {
+                stream: stream,
+                in_flight: 0,
+                served: 0,
+                last_used: Instant::now(),
+            });
+
+            conn.in_flight += 1;
+            conn.served += 1;
+            conn.last_used = Instant::now();
+            conn.served
+        };
+
+        self.update(&conns);
+        served
+    }
+
+    /// Records that a request on the connection from `remote` has completed.
+    /// If `keep_alive` is `false`, the connection is about to be closed by the
+    /// server and is forgotten. Otherwise, it becomes idle, and idle
+    /// connections over the cap are evicted.
+    pub(crate) fn end(&self, remote: SocketAddr, keep_alive: bool) {
+        let mut conns = self.conns.lock().expect("connections lock");
+        let forget = match conns.get_mut(&remote) {
+            Some(conn) => {
+                conn.in_flight = conn.in_flight.saturating_sub(1);
+                conn.last_used = Instant::now();
+                conn.is_idle() && (!keep_alive || self.keep_alive.is_none())
+            }
+            None => false
+        };
+
+        if forget {
+            conns.remove(&remote);
+        }
+
+        if let Some(max) = self.max_idle {
+            loop {
+                let idle = conns.values().filter(|c| c.is_idle()).count();
+                if idle <= max {
+                    break;
+                }
+
+                let lru = conns.iter()
+                    .filter(|&(_, c)| c.is_idle())
+                    .min_by_key(|&(_, c)| c.last_used)
+                    .map(|(addr, _)| *addr);
+
+                if let Some(conn) = lru.and_then(|addr| conns.remove(&addr)) {
+                    trace_!("Evicting least recently used idle connection.");
+                    self.evicted.incr();
+                    conn.close();
+                }
+            }
+        }
+
+        self.update(&conns);
+    }
+
+    /// Closes and forgets every idle connection that has outlived the
+    /// keep-alive timeout.
+    pub(crate) fn reap(&self) {
+        let timeout = match self.keep_alive {
+            Some(timeout) => timeout,
+            None => return
+        };
+
+        let mut conns = self.conns.lock().expect("connections lock");
+        let expired: Vec<SocketAddr> = conns.iter()
+            .filter(|&(_, c)| c.is_idle() && c.last_used.elapsed() >= timeout)
+            .map(|(addr, _)| *addr)
+            .collect();
+
+        for addr in expired {
+            if let Some(conn) = conns.remove(&addr) {
+                self.reaped.incr();
+                conn.close();
+            }
+        }
+
+        self.update(&conns);
+    }
+
+    /// Returns the current connection counts.
+    pub(crate) fn stats(&self) -> ConnectionStats {
+        let conns = self.conns.lock().expect("connections lock");
+        Connections::count(&conns)
+    }
+
+    fn count(conns: &HashMap<SocketAddr, Connection>) -> ConnectionStats {
+        let idle = conns.values().filter(|c| c.is_idle()).count();
+        ConnectionStats { open: conns.len(), idle: idle, active: conns.len() - idle }
+    }
+
+    fn update(&self, conns: &HashMap<SocketAddr, Connection>) {
+        let stats = Connections::count(conns);
+        self.open.set(stats.open);
+        self.idle.set(stats.idle);
+        self.active.set(stats.active);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::net::SocketAddr;
+    use std::time::Duration;
+
+    use super::{Connections, ConnectionStats};
+    use metrics::Metrics;
+
+    fn addr(port: u16) -> SocketAddr {
+        format!("127.0.0.1:{}", port).parse().unwrap()
+    }
+
+    #[test]
+    fn test_stats() {
+        let metrics = Metrics::new();
+        let connections = Connections::new(&metrics, Some(Duration::from_secs(5)), None, None);
+        assert_eq!(connections.stats(), ConnectionStats::default());
+
+        connections.begin(addr(4000), None);
+        connections.begin(addr(4001), None);
+        connections.begin(addr(4001), None);
+        assert_eq!(connections.stats(), ConnectionStats { open: 2, idle: 0, active: 2 });
+
+        connections.end(addr(4000), true);
+        connections.end(addr(4001), true);
+        assert_eq!(connections.stats(), ConnectionStats { open: 2, idle: 1, active: 1 });
+        assert_eq!(metrics.get("connections.open"), Some(2));
+        assert_eq!(metrics.get("connections.idle"), Some(1));
+        assert_eq!(metrics.get("connections.active"), Some(1));
+
+        connections.end(addr(4001), false);
+        assert_eq!(connections.stats(), ConnectionStats { open: 1, idle: 1, active: 0 });
+    }
+
+    #[test]
+    fn test_stats_without_keep_alive() {
+        let connections = Connections::new(&Metrics::new(), None, None, None);
+        connections.begin(addr(4000), None);
+        connections.end(addr(4000), true);
+        assert_eq!(connections.stats(), ConnectionStats::default());
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let metrics = Metrics::new();
+        let keep_alive = Some(Duration::from_secs(5));
+        let connections = Connections::new(&metrics, keep_alive, Some(2), None);
+        for port in 4000..4003 {
+            connections.begin(addr(port), None);
+        }
+
+        for port in &[4001, 4000, 4002] {
+            thread::sleep(Duration::from_millis(5));
+            connections.end(addr(*port), true);
+        }
+
+        assert_eq!(metrics.get("connections.evicted"), Some(1));
+        assert_eq!(connections.stats(), ConnectionStats { open: 2, idle: 2, active: 0 });
+
+        let conns = connections.conns.lock().unwrap();
+        assert!(!conns.contains_key(&addr(4001)));
+        assert!(conns.contains_key(&addr(4000)));
+        assert!(conns.contains_key(&addr(4002)));
+    }
+
+    #[test]
+    fn test_lru_eviction_skips_active() {
+        let metrics = Metrics::new();
+        let keep_alive = Some(Duration::from_secs(5));
+        let connections = Connections::new(&metrics, keep_alive, Some(1), None);
+        for port in 4000..4003 {
+            connections.begin(addr(port), None);
+        }
+
+        connections.end(addr(4000), true);
+        assert_eq!(metrics.get("connections.evicted"), Some(0));
+        assert_eq!(connections.stats(), ConnectionStats { open: 3, idle: 1, active: 2 });
+    }
+
+    #[test]
+    fn test_reap() {
+        let metrics = Metrics::new();
+        let keep_alive = Some(Duration::from_millis(20));
+        let connections = Connections::new(&metrics, keep_alive, None, None);
+        connections.begin(addr(4000), None);
+        connections.begin(addr(4001), None);
+        connections.end(addr(4000), true);
+
+        connections.reap();
+        assert_eq!(metrics.get("connections.reaped"), Some(0));
+        assert_eq!(connections.stats(), ConnectionStats { open: 2, idle: 1, active: 1 });
+
+        thread::sleep(Duration::from_millis(30));
+        connections.reap();
+        assert_eq!(metrics.get("connections.reaped"), Some(1));
+        assert_eq!(connections.stats(), ConnectionStats { open: 1, idle: 0, active: 1 });
+
+        connections.end(addr(4001), true);
+        thread::sleep(Duration::from_millis(30));
+        connections.reap();
+        assert_eq!(metrics.get("connections.reaped"), Some(2));
+        assert_eq!(connections.stats(), ConnectionStats::default());
+    }
+
+    #[test]
+    fn test_max_requests() {
+        let keep_alive = Some(Duration::from_secs(5));
+        let connections = Connections::new(&Metrics::new(), keep_alive, None, Some(2));
+        let remote = "127.0.0.1:4000".parse().unwrap();
+
+        assert!(connections.track(remote, None, true).keep_alive());
+        assert!(!connections.track(remote, None, true).keep_alive());
+        assert_eq!(connections.stats().open, 0);
+
+        assert!(connections.track(remote, None, true).keep_alive());
+        assert!(!connections.track(remote, None, false).keep_alive());
+    }
+}
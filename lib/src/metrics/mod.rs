@@ -0,0 +1,161 @@
+//! Runtime metrics maintained by Rocket.
+//!
+//! Rocket keeps a small registry of named counters and gauges describing the
+//! state of a running application: how many connections are open, how many
+//! requests failed, and so on. The registry for an instance of `Rocket` is
+//! retrieved via [`Rocket::metrics()`]. Values are plain `usize`s and can be
+//! exported to any monitoring system by iterating over a
+//! [snapshot](/rocket/metrics/struct.Metrics.html#method.snapshot).
+//!
+//! Applications can register their own counters as well:
+//!
+//! ```rust
+//! let rocket = rocket::ignite();
+//!
+//! let logins = rocket.metrics().counter("app.logins");
+//! logins.incr();
+//! logins.add(2);
+//!
+//! assert_eq!(rocket.metrics().get("app.logins"), Some(3));
+//! ```
+//!
+//! [`Rocket::metrics()`]: /rocket/struct.Rocket.html#method.metrics
+
+mod connections;
+mod timings;
+
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use indexmap::IndexMap;
+
+pub use self::connections::ConnectionStats;
+pub use self::timings::Timings;
+pub(crate) use self::connections::Connections;
+
+/// A registry of named counters and gauges.
+///
+/// Names are arbitrary strings. By convention, names are `.` separated with
+/// the subsystem as the first component, for instance, `connections.open`.
+/// Metrics are listed in the order in which they were first registered.
+pub struct Metrics {
+    values: RwLock<IndexMap<String, Counter>>,
+}
+
+/// A handle to a single named value in a [`Metrics`] registry.
+///
+/// A `Counter` is cheap to clone; all clones refer to the same value. The value
+/// can be used either as a monotonically increasing counter, via
+/// [`incr`](#method.incr) and [`add`](#method.add), or as a gauge, via
+/// [`set`](#method.set).
+///
+/// [`Metrics`]: /rocket/metrics/struct.Metrics.html
+#[derive(Clone, Debug)]
+pub struct Counter(Arc<AtomicUsize>);
+
+impl Counter {
+    #[inline]
+    fn new() -> Counter {
+        Counter(Arc::new(AtomicUsize::new(0)))
+    }
+
+    /// Increments the value by one.
+    #[inline]
+    pub fn incr(&self) {
+        self.add(1);
+    }
+
+    /// Increments the value by `n`.
+    #[inline]
+    pub fn add(&self, n: usize) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Decrements the value by `n`, saturating at zero.
+    #[inline]
+    pub fn sub(&self, n: usize) {
+        let mut current = self.get();
+        loop {
+            let new = current.saturating_sub(n);
+            let prev = self.0.compare_and_swap(current, new, Ordering::Relaxed);
+            if prev == current {
+                break;
+            }
+
+            current = prev;
+        }
+    }
+
+    /// Sets the value to `value`.
+    #[inline]
+    pub fn set(&self, value: usize) {
+        self.0.store(value, Ordering::Relaxed);
+    }
+
+    /// Returns the current value.
+    #[inline]
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl Metrics {
+    #[inline]
+    pub(crate) fn new() -> Metrics {
+        Metrics { values: RwLock::new(IndexMap::new()) }
+    }
+
+    /// Returns the counter named `name`, registering it with an initial value
+    /// of `0` if it doesn't yet exist.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let rocket = rocket::ignite();
+    /// let counter = rocket.metrics().counter("app.hits");
+    /// counter.incr();
+    ///
+    /// assert_eq!(rocket.metrics().counter("app.hits").get(), 1);
+    /// ```
+    pub fn counter(&self, name: &str) -> Counter {
+        if let Some(counter) = self.values.read().expect("metrics read").get(name) {
+            return counter.clone();
+        }
+
+        let mut values = self.values.write().expect("metrics write");
+        values.entry(name.to_string()).or_insert_with(Counter::new).clone()
+    }
+
+    /// Returns the current value of the metric named `name` if it has been
+    /// registered. Otherwise, returns `None`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let rocket = rocket::ignite();
+    /// assert_eq!(rocket.metrics().get("app.unknown"), None);
+    ///
+    /// rocket.metrics().counter("app.known").set(10);
+    /// assert_eq!(rocket.metrics().get("app.known"), Some(10));
+    /// ```
+    pub fn get(&self, name: &str) -> Option<usize> {
+        self.values.read().expect("metrics read").get(name).map(|c| c.get())
+    }
+
+    /// Returns a point-in-time copy of every registered metric and its value
+    /// in registration order.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let rocket = rocket::ignite();
+    /// for (name, value) in rocket.metrics().snapshot() {
+    ///     println!("{} = {}", name, value);
+    /// }
+    /// ```
+    pub fn snapshot(&self) -> Vec<(String, usize)> {
+        self.values.read().expect("metrics read").iter()
+            .map(|(name, counter)| (name.clone(), counter.get()))
+            .collect()
+    }
+}
@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+/// The time spent in each phase of dispatching a single request.
+///
+/// Rocket measures every request it dispatches. The timings of a request
+/// dispatched via a local [`Client`] are retrieved via
+/// [`LocalResponse::timings()`].
+///
+/// The phases don't necessarily add up to `total`, which also includes
+/// preprocessing, error catchers, and finalizing the response. Request guards,
+/// including the data guard, are only measured separately for routes declared
+/// with a route attribute; they are otherwise counted as part of the handler.
+/// When a request is forwarded, the guards and handlers of every route that was
+/// tried are counted.
+///
+/// [`Client`]: /rocket/local/struct.Client.html
+/// [`LocalResponse::timings()`]: /rocket/local/struct.LocalResponse.html#method.timings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Timings {
+    /// The time spent finding the routes that match the request.
+    pub routing: Duration,
+    /// The time spent in request and data guards.
+    pub guards: Duration,
+    /// The time spent in handlers, including the conversion of the
+    /// handler's return value into a response.
+    pub handler: Duration,
+    /// The time spent in request and response fairings.
+    pub fairings: Duration,
+    /// The time spent dispatching the request from start to finish.
+    pub total: Duration,
+}
@@ -0,0 +1,642 @@
+//! OAuth 2.0 authorization code flow and OpenID Connect sign-in.
+//!
+//! This module is only available when the `oauth2` feature is enabled.
+//!
+//! The [`OAuth2`] managed state drives the [authorization code flow] with
+//! [PKCE]: [`OAuth2::login()`] redirects the user to the provider's
+//! authorization endpoint, and the [`TokenResponse`] request guard, used in
+//! the route the provider redirects back to, verifies the callback and
+//! exchanges its code for tokens at the provider's token endpoint.
+//!
+//! The `state` parameter, PKCE code verifier, and OpenID Connect `nonce` of a
+//! login are kept in a short-lived [private cookie], so a callback can only
+//! complete a login started by the same browser. A callback whose `state`
+//! doesn't match is rejected.
+//!
+//! When an `issuer` is configured, the provider is treated as an OpenID
+//! Connect provider: the `openid` scope is requested, and the ID token in the
+//! token response is validated. Its claims are kept in a private cookie and
+//! are available to later requests via the [`OidcUser`] request guard. Since
+//! the ID token is received directly from the token endpoint over TLS, its
+//! signature isn't verified; its issuer, audience, expiry, and nonce are.
+//!
+//! Tokens can be persisted, for instance to call the provider's APIs later,
+//! with a [`TokenStore`].
+//!
+//! # Configuration
+//!
+//! The provider and client are configured in the `oauth` table:
+//!
+//! ```toml
+//! [global.oauth]
+//! client_id = "rocket-app"
+//! client_secret = "hunter2"
+//! auth_uri = "https://accounts.example.com/authorize"
+//! token_uri = "https://accounts.example.com/token"
+//! redirect_uri = "https://app.example.com/auth/callback"
+//! scopes = ["email", "profile"]
+//! issuer = "https://accounts.example.com"
+//! ```
+//!
+//! Only `client_secret`, `scopes`, and `issuer` are optional. The
+//! [`OAuth2::fairing()`] fairing reads the configuration and manages the
+//! resulting `OAuth2`.
+//!
+//! # Example
+//!
+//! ```rust
+//! # #![feature(plugin, decl_macro)]
+//! # #![plugin(rocket_codegen)]
+//! # extern crate rocket;
+//! use rocket::State;
+//! use rocket::http::Cookies;
+//! use rocket::response::Redirect;
+//! use rocket::oauth2::{OAuth2, OidcUser, TokenResponse};
+//!
+//! #[get("/login")]
+//! fn login(oauth: State<OAuth2>, mut cookies: Cookies) -> Redirect {
+//!     oauth.login(&mut cookies)
+//! }
+//!
+//! #[get("/auth/callback")]
+//! fn callback(_token: TokenResponse) -> Redirect {
+//!     Redirect::to("/")
+//! }
+//!
+//! #[get("/")]
+//! fn index(user: OidcUser) -> String {
+//!     format!("Hello, {}!", user.name().unwrap_or(user.subject()))
+//! }
+//!
+//! fn main() {
+//! # if false {
+//!     rocket::ignite()
+//!         .attach(OAuth2::fairing())
+//!         .mount("/", routes![login, callback, index])
+//!         .launch();
+//! # }
+//! }
+//! ```
+//!
+//! [authorization code flow]: https://tools.ietf.org/html/rfc6749#section-4.1
+//! [PKCE]: https://tools.ietf.org/html/rfc7636
+//! [private cookie]: /rocket/http/enum.Cookies.html#private-cookies
+//! [`OAuth2`]: /rocket/oauth2/struct.OAuth2.html
+//! [`OAuth2::login()`]: /rocket/oauth2/struct.OAuth2.html#method.login
+//! [`OAuth2::fairing()`]: /rocket/oauth2/struct.OAuth2.html#method.fairing
+//! [`TokenResponse`]: /rocket/oauth2/struct.TokenResponse.html
+//! [`OidcUser`]: /rocket/oauth2/struct.OidcUser.html
+//! [`TokenStore`]: /rocket/oauth2/trait.TokenStore.html
+
+mod oidc;
+
+pub use self::oidc::OidcUser;
+
+use std::fmt;
+use std::io::Read;
+
+use base64;
+use serde_json::{self, Value};
+use sha2::{Sha256, Digest};
+use cookie::{Key, SameSite};
+use time::Duration;
+
+use config::{Config, ConfigError};
+use fairing::{Fairing, AdHoc};
+use request::{Request, FromRequest, Outcome, State};
+use response::Redirect;
+use outcome::Outcome::*;
+use http::{Cookie, Cookies, Status};
+use security::{Secret, constant_time_eq};
+
+/// The private cookie holding the state of a login in progress.
+const FLOW_COOKIE: &str = "rocket_oauth";
+
+mod priv_encode_set {
+    /// The set of characters that must be encoded in a query or form value.
+    define_encode_set! {
+        pub QUERY_VALUE_ENCODE_SET = [::percent_encoding::PATH_SEGMENT_ENCODE_SET] | {'+', '&', '='}
+    }
+}
+
+// Encodes `pairs` as an `application/x-www-form-urlencoded` string.
+fn form_encode(pairs: &[(&str, &str)]) -> String {
+    use percent_encoding::utf8_percent_encode;
+    use self::priv_encode_set::QUERY_VALUE_ENCODE_SET;
+
+    pairs.iter()
+        .map(|&(key, value)| format!("{}={}", key,
+            utf8_percent_encode(value, QUERY_VALUE_ENCODE_SET)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+// Returns a random, URL-safe token with 256 bits of entropy.
+fn random_token() -> String {
+    let key = Key::generate();
+    base64::encode_config(&key.master()[..32], base64::URL_SAFE_NO_PAD)
+}
+
+/// The configuration of an OAuth 2.0 client and its provider.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::oauth2::OAuthConfig;
+///
+/// let config = OAuthConfig::new(
+///     "rocket-app",
+///     "https://accounts.example.com/authorize",
+///     "https://accounts.example.com/token",
+///     "https://app.example.com/auth/callback"
+/// );
+///
+/// assert!(config.scopes.is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct OAuthConfig {
+    /// The client identifier issued by the provider.
+    pub client_id: String,
+    /// The client secret issued by the provider, if any.
+    pub client_secret: Option<Secret<String>>,
+    /// The provider's authorization endpoint.
+    pub auth_uri: String,
+    /// The provider's token endpoint.
+    pub token_uri: String,
+    /// The URI the provider redirects to after authorization.
+    pub redirect_uri: String,
+    /// The scopes to request.
+    pub scopes: Vec<String>,
+    /// The OpenID Connect issuer identifier or `None` for plain OAuth 2.0.
+    pub issuer: Option<String>,
+}
+
+impl OAuthConfig {
+    /// Returns the configuration of a public client, with no secret, scopes,
+    /// or issuer.
+    pub fn new<A, B, C, D>(client_id: A, auth_uri: B, token_uri: C, redirect_uri: D)
+        -> OAuthConfig
+        where A: Into<String>, B: Into<String>, C: Into<String>, D: Into<String>
+    {
+        OAuthConfig {
+            client_id: client_id.into(),
+            client_secret: None,
+            auth_uri: auth_uri.into(),
+            token_uri: token_uri.into(),
+            redirect_uri: redirect_uri.into(),
+            scopes: vec![],
+            issuer: None,
+        }
+    }
+
+    /// Reads the configuration from the `oauth` table of `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no `oauth` table, if a required key is
+    /// missing, or if a value has the wrong type.
+    pub fn from_config(config: &Config) -> Result<OAuthConfig, ConfigError> {
+        let table = config.get_table("oauth")?;
+        let string = |key: &str| match table.get(key) {
+            Some(value) => value.as_str()
+                .map(|value| Some(value.to_string()))
+                .ok_or_else(|| config.bad_type(&format!("oauth.{}", key),
+                                               value.type_str(), "a string")),
+            None => Ok(None)
+        };
+
+        let required = |key: &str| {
+            let missing = config.bad_type(&format!("oauth.{}", key), "nothing", "a string");
+            string(key).and_then(|value| value.ok_or(missing))
+        };
+
+        let mut scopes = vec![];
+        if let Some(value) = table.get("scopes") {
+            let bad_type = |actual| config.bad_type("oauth.scopes", actual, "an array of strings");
+            let array = value.as_array().ok_or_else(|| bad_type(value.type_str()))?;
+            for scope in array {
+                let scope = scope.as_str().ok_or_else(|| bad_type(scope.type_str()))?;
+                scopes.push(scope.to_string());
+            }
+        }
+
+        Ok(OAuthConfig {
+            client_id: required("client_id")?,
+            client_secret: string("client_secret")?.map(Secret::new),
+            auth_uri: required("auth_uri")?,
+            token_uri: required("token_uri")?,
+            redirect_uri: required("redirect_uri")?,
+            scopes: scopes,
+            issuer: string("issuer")?,
+        })
+    }
+}
+
+/// Trait implemented by types that send token requests to a provider.
+///
+/// The default transport sends requests with Rocket's HTTP client over TLS. A
+/// custom transport can, for instance, route requests through a proxy or, in
+/// tests, answer them without a provider.
+pub trait Transport: Send + Sync + 'static {
+    /// Sends `form`, URL-encoded, in a `POST` request to `url` and returns
+    /// the body of the response, whatever its status. Returns an error message
+    /// if no response is received.
+    fn post_form(&self, url: &str, form: &[(&str, &str)]) -> Result<Vec<u8>, String>;
+}
+
+/// The default [`Transport`](/rocket/oauth2/trait.Transport.html): Hyper's
+/// HTTP client with a TLS connector.
+#[derive(Debug, Default)]
+pub struct HyperTransport;
+
+impl Transport for HyperTransport {
+    fn post_form(&self, url: &str, form: &[(&str, &str)]) -> Result<Vec<u8>, String> {
+        use hyper::Client;
+        use hyper::net::HttpsConnector;
+        use hyper::header::Headers;
+        use hyper_sync_rustls::TlsClient;
+
+        let mut headers = Headers::new();
+        headers.set_raw("Content-Type", vec![b"application/x-www-form-urlencoded".to_vec()]);
+        headers.set_raw("Accept", vec![b"application/json".to_vec()]);
+
+        let body = form_encode(form);
+        let client = Client::with_connector(HttpsConnector::new(TlsClient::new()));
+        let mut response = client.post(url)
+            .headers(headers)
+            .body(&body[..])
+            .send()
+            .map_err(|e| e.to_string())?;
+
+        // Errors are reported in the body, which is parsed by the caller.
+        let mut bytes = vec![];
+        response.read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+        Ok(bytes)
+    }
+}
+
+/// Trait implemented by types that persist the tokens of completed logins.
+///
+/// The unit type `()` is a `TokenStore` that discards all tokens; it is used
+/// unless another store is set via [`OAuth2::token_store()`].
+///
+/// [`OAuth2::token_store()`]: /rocket/oauth2/struct.OAuth2.html#method.token_store
+pub trait TokenStore: Send + Sync + 'static {
+    /// Called with the tokens of each completed login and, for OpenID Connect
+    /// providers, the user that logged in.
+    fn store(&self, token: &TokenResponse, user: Option<&OidcUser>);
+}
+
+impl TokenStore for () {
+    fn store(&self, _: &TokenResponse, _: Option<&OidcUser>) {  }
+}
+
+/// The reason a login failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OAuthError {
+    /// No `OAuth2` is managed.
+    Unconfigured,
+    /// The callback arrived without a login in progress, or the login expired.
+    MissingState,
+    /// The `state` of the callback doesn't match that of the login.
+    StateMismatch,
+    /// The callback is missing the authorization code or is malformed.
+    BadCallback,
+    /// The provider reported an error, with the given error code.
+    Provider(String),
+    /// The token request failed, for the given reason.
+    Transport(String),
+    /// The token response is invalid, for the given reason.
+    InvalidResponse(String),
+    /// The ID token is invalid, for the given reason.
+    InvalidIdToken(String),
+}
+
+impl OAuthError {
+    /// Returns the status a failed [`TokenResponse`] guard responds with:
+    /// `500` if no `OAuth2` is managed, `502` if the provider couldn't be
+    /// reached or responded incorrectly, and `400` otherwise.
+    ///
+    /// [`TokenResponse`]: /rocket/oauth2/struct.TokenResponse.html
+    pub fn status(&self) -> Status {
+        match *self {
+            OAuthError::Unconfigured => Status::InternalServerError,
+            OAuthError::Transport(_) | OAuthError::InvalidResponse(_) => Status::BadGateway,
+            _ => Status::BadRequest
+        }
+    }
+}
+
+impl fmt::Display for OAuthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            OAuthError::Unconfigured => write!(f, "no `OAuth2` is managed"),
+            OAuthError::MissingState => write!(f, "no login is in progress"),
+            OAuthError::StateMismatch => write!(f, "the callback state doesn't match"),
+            OAuthError::BadCallback => write!(f, "the callback is malformed"),
+            OAuthError::Provider(ref e) => write!(f, "the provider reported '{}'", e),
+            OAuthError::Transport(ref e) => write!(f, "the token request failed: {}", e),
+            OAuthError::InvalidResponse(ref e) => write!(f, "invalid token response: {}", e),
+            OAuthError::InvalidIdToken(ref e) => write!(f, "invalid ID token: {}", e),
+        }
+    }
+}
+
+/// The tokens issued by the provider at the end of a login.
+///
+/// As a request guard, `TokenResponse` completes a login: it verifies the
+/// callback's `state`, exchanges its authorization code for tokens, and, for
+/// OpenID Connect providers, validates the ID token and signs the user in. It
+/// should be used in the route at the configured `redirect_uri`. The guard
+/// fails with the [`status`] of the [`OAuthError`].
+///
+/// [`status`]: /rocket/oauth2/enum.OAuthError.html#method.status
+/// [`OAuthError`]: /rocket/oauth2/enum.OAuthError.html
+#[derive(Debug, Clone)]
+pub struct TokenResponse {
+    access_token: Secret<String>,
+    token_type: String,
+    expires_in: Option<u64>,
+    refresh_token: Option<Secret<String>>,
+    scope: Option<String>,
+    id_token: Option<String>,
+}
+
+impl TokenResponse {
+    /// Parses a token endpoint response body.
+    fn parse(body: &[u8]) -> Result<TokenResponse, OAuthError> {
+        let invalid = |e: &str| OAuthError::InvalidResponse(e.to_string());
+        let value: Value = serde_json::from_slice(body).map_err(|e| invalid(&e.to_string()))?;
+        if let Some(error) = value.get("error").and_then(|e| e.as_str()) {
+            return Err(OAuthError::Provider(error.to_string()));
+        }
+
+        let string = |key: &str| value.get(key).and_then(|v| v.as_str()).map(|v| v.to_string());
+        Ok(TokenResponse {
+            access_token: string("access_token").map(Secret::new)
+                .ok_or_else(|| invalid("missing `access_token`"))?,
+            token_type: string("token_type").ok_or_else(|| invalid("missing `token_type`"))?,
+            expires_in: value.get("expires_in").and_then(|v| v.as_u64()),
+            refresh_token: string("refresh_token").map(Secret::new),
+            scope: string("scope"),
+            id_token: string("id_token"),
+        })
+    }
+
+    /// Returns the access token.
+    #[inline(always)]
+    pub fn access_token(&self) -> &str {
+        self.access_token.expose()
+    }
+
+    /// Returns the type of the access token, usually `Bearer`.
+    #[inline(always)]
+    pub fn token_type(&self) -> &str {
+        &self.token_type
+    }
+
+    /// Returns the lifetime of the access token in seconds, if known.
+    #[inline(always)]
+    pub fn expires_in(&self) -> Option<u64> {
+        self.expires_in
+    }
+
+    /// Returns the refresh token, if one was issued.
+    #[inline(always)]
+    pub fn refresh_token(&self) -> Option<&str> {
+        self.refresh_token.as_ref().map(|token| token.expose().as_str())
+    }
+
+    /// Returns the scopes granted, if they differ from those requested.
+    #[inline(always)]
+    pub fn scope(&self) -> Option<&str> {
+        self.scope.as_ref().map(|scope| scope.as_str())
+    }
+
+    /// Returns the raw ID token, if one was issued.
+    #[inline(always)]
+    pub fn id_token(&self) -> Option<&str> {
+        self.id_token.as_ref().map(|token| token.as_str())
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for TokenResponse {
+    type Error = OAuthError;
+
+    fn from_request(request: &'a Request<'r>) -> Outcome<Self, Self::Error> {
+        let oauth = match request.guard::<State<OAuth2>>() {
+            Success(oauth) => oauth,
+            _ => {
+                error_!("A `TokenResponse` guard was used, but no `OAuth2` is managed.");
+                info_!("Attach `OAuth2::fairing()` or manage an `OAuth2`.");
+                return Failure((Status::InternalServerError, OAuthError::Unconfigured));
+            }
+        };
+
+        match oauth.complete(request) {
+            Ok(token) => Success(token),
+            Err(e) => {
+                warn_!("OAuth login failed: {}", e);
+                Failure((e.status(), e))
+            }
+        }
+    }
+}
+
+/// An OAuth 2.0 client, as managed state.
+///
+/// See the [module level documentation](/rocket/oauth2/index.html) for an
+/// overview and an example.
+pub struct OAuth2 {
+    config: OAuthConfig,
+    transport: Box<Transport>,
+    store: Box<TokenStore>,
+}
+
+impl OAuth2 {
+    /// Returns a client configured by `config` that uses the default
+    /// transport and discards tokens.
+    pub fn new(config: OAuthConfig) -> OAuth2 {
+        OAuth2 { config: config, transport: Box::new(HyperTransport), store: Box::new(()) }
+    }
+
+    /// Sets the transport used to send token requests.
+    pub fn transport<T: Transport>(mut self, transport: T) -> OAuth2 {
+        self.transport = Box::new(transport);
+        self
+    }
+
+    /// Sets the store that persists the tokens of completed logins.
+    pub fn token_store<S: TokenStore>(mut self, store: S) -> OAuth2 {
+        self.store = Box::new(store);
+        self
+    }
+
+    /// Returns the configuration of `self`.
+    #[inline(always)]
+    pub fn config(&self) -> &OAuthConfig {
+        &self.config
+    }
+
+    /// Returns a fairing that reads the `oauth` configuration table with
+    /// [`OAuthConfig::from_config()`] and manages the resulting `OAuth2`.
+    /// Launch is aborted if the configuration is missing or invalid.
+    ///
+    /// [`OAuthConfig::from_config()`]: /rocket/oauth2/struct.OAuthConfig.html#method.from_config
+    pub fn fairing() -> impl Fairing {
+        OAuth2::fairing_with(|oauth| oauth)
+    }
+
+    /// Like [`fairing()`](#method.fairing), but passes the `OAuth2` through
+    /// `customize` before managing it. Use this to set a transport or token
+    /// store.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::oauth2::{OAuth2, TokenStore, TokenResponse, OidcUser};
+    ///
+    /// struct Log;
+    ///
+    /// impl TokenStore for Log {
+    ///     fn store(&self, token: &TokenResponse, _: Option<&OidcUser>) {
+    ///         println!("issued a '{}' token", token.token_type());
+    ///     }
+    /// }
+    ///
+    /// # #[allow(unused_variables)]
+    /// let fairing = OAuth2::fairing_with(|oauth| oauth.token_store(Log));
+    /// ```
+    pub fn fairing_with<F>(customize: F) -> impl Fairing
+        where F: FnOnce(OAuth2) -> OAuth2 + Send + 'static
+    {
+        AdHoc::on_attach(move |rocket| {
+            match OAuthConfig::from_config(rocket.config()) {
+                Ok(config) => Ok(rocket.manage(customize(OAuth2::new(config)))),
+                Err(e) => {
+                    e.pretty_print();
+                    Err(rocket)
+                }
+            }
+        })
+    }
+
+    /// Starts a login: records a new `state`, PKCE code verifier, and `nonce`
+    /// in a private cookie that expires in ten minutes and returns a redirect
+    /// to the provider's authorization endpoint.
+    pub fn login(&self, cookies: &mut Cookies) -> Redirect {
+        let (state, verifier, nonce) = (random_token(), random_token(), random_token());
+        let challenge = Sha256::digest(verifier.as_bytes());
+        let challenge = base64::encode_config(&challenge, base64::URL_SAFE_NO_PAD);
+
+        let mut scopes: Vec<&str> = self.config.scopes.iter().map(|s| s.as_str()).collect();
+        if self.config.issuer.is_some() && !scopes.contains(&"openid") {
+            scopes.insert(0, "openid");
+        }
+
+        let scope = scopes.join(" ");
+        let mut params = vec![
+            ("response_type", "code"),
+            ("client_id", &*self.config.client_id),
+            ("redirect_uri", &*self.config.redirect_uri),
+            ("state", &*state),
+            ("code_challenge", &*challenge),
+            ("code_challenge_method", "S256"),
+        ];
+
+        if !scope.is_empty() {
+            params.push(("scope", &*scope));
+        }
+
+        if self.config.issuer.is_some() {
+            params.push(("nonce", &*nonce));
+        }
+
+        // The provider redirects back cross-site, so the cookie must be `Lax`.
+        let flow = format!("{}.{}.{}", state, verifier, nonce);
+        cookies.add_private(Cookie::build(FLOW_COOKIE, flow)
+            .path("/")
+            .same_site(SameSite::Lax)
+            .expires(::time::now() + Duration::minutes(10))
+            .finish());
+
+        let separator = if self.config.auth_uri.contains('?') { "&" } else { "?" };
+        Redirect::to(format!("{}{}{}", self.config.auth_uri, separator, form_encode(&params)))
+    }
+
+    /// Signs out the user signed in via OpenID Connect, if any.
+    pub fn logout(&self, cookies: &mut Cookies) {
+        cookies.remove_private(OidcUser::removal_cookie());
+    }
+
+    // Completes the login whose callback is `request`.
+    fn complete(&self, request: &Request) -> Result<TokenResponse, OAuthError> {
+        let mut cookies = request.cookies();
+        let flow = cookies.get_private(FLOW_COOKIE).ok_or(OAuthError::MissingState)?;
+        cookies.remove_private(Cookie::build(FLOW_COOKIE, "").path("/").finish());
+
+        let mut parts = flow.value().split('.');
+        let (state, verifier, nonce) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(state), Some(verifier), Some(nonce)) => (state, verifier, nonce),
+            _ => return Err(OAuthError::MissingState)
+        };
+
+        let (mut code, mut callback_state, mut error) = (None, None, None);
+        for (key, value) in request.raw_query_pairs() {
+            let value = value.url_decode().map_err(|_| OAuthError::BadCallback)?;
+            match key.as_str() {
+                "code" => code = Some(value),
+                "state" => callback_state = Some(value),
+                "error" => error = Some(value),
+                _ => {}
+            }
+        }
+
+        if let Some(error) = error {
+            return Err(OAuthError::Provider(error));
+        }
+
+        let callback_state = callback_state.ok_or(OAuthError::StateMismatch)?;
+        if !constant_time_eq(state.as_bytes(), callback_state.as_bytes()) {
+            return Err(OAuthError::StateMismatch);
+        }
+
+        let code = code.ok_or(OAuthError::BadCallback)?;
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", &*code),
+            ("redirect_uri", &*self.config.redirect_uri),
+            ("client_id", &*self.config.client_id),
+            ("code_verifier", verifier),
+        ];
+
+        if let Some(ref secret) = self.config.client_secret {
+            form.push(("client_secret", secret.expose().as_str()));
+        }
+
+        let body = self.transport.post_form(&self.config.token_uri, &form)
+            .map_err(OAuthError::Transport)?;
+
+        let token = TokenResponse::parse(&body)?;
+        let user = match self.config.issuer {
+            Some(ref issuer) => {
+                let id_token = token.id_token()
+                    .ok_or_else(|| OAuthError::InvalidIdToken("missing".into()))?;
+
+                let user = OidcUser::validate(id_token, issuer, &self.config.client_id, nonce)?;
+                cookies.add_private(user.cookie());
+                Some(user)
+            }
+            None => None
+        };
+
+        self.store.store(&token, user.as_ref());
+        Ok(token)
+    }
+}
+
+impl fmt::Debug for OAuth2 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OAuth2").field("config", &self.config).finish()
+    }
+}
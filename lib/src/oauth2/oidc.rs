@@ -0,0 +1,210 @@
+use std::fmt;
+
+use base64;
+use serde_json::{self, Map, Value};
+use cookie::SameSite;
+
+use request::{Request, FromRequest, Outcome};
+use outcome::Outcome::*;
+use http::Cookie;
+use security::constant_time_eq;
+
+use super::OAuthError;
+
+/// The private cookie holding the claims of the signed in user.
+const USER_COOKIE: &str = "rocket_oidc";
+
+// Returns the current time in seconds since the Unix epoch.
+fn now() -> i64 {
+    ::time::now_utc().to_timespec().sec
+}
+
+/// A user signed in via OpenID Connect.
+///
+/// An `OidcUser` exposes the claims of the validated ID token received when
+/// the user logged in. The claims are kept in a private cookie that expires
+/// with the ID token; [`OAuth2::logout()`] removes it.
+///
+/// # Request Guard
+///
+/// The guard forwards if no user is signed in or if the ID token of the
+/// signed in user has expired.
+///
+/// ```rust
+/// # #![feature(plugin, decl_macro)]
+/// # #![plugin(rocket_codegen)]
+/// # extern crate rocket;
+/// use rocket::oauth2::OidcUser;
+///
+/// #[get("/profile")]
+/// fn profile(user: OidcUser) -> String {
+///     format!("{} <{}>", user.subject(), user.email().unwrap_or("no email"))
+/// }
+///
+/// #[get("/profile", rank = 2)]
+/// fn anonymous() -> &'static str {
+///     "Please log in."
+/// }
+/// # fn main() {  }
+/// ```
+///
+/// [`OAuth2::logout()`]: /rocket/oauth2/struct.OAuth2.html#method.logout
+#[derive(Clone, PartialEq)]
+pub struct OidcUser {
+    claims: Map<String, Value>,
+}
+
+impl OidcUser {
+    /// Decodes the claims of `id_token` and validates them: the token must
+    /// have been issued by `issuer` to `client_id` for the login with `nonce`,
+    /// must not have expired, and must identify its subject.
+    ///
+    /// The signature isn't verified: the token was received directly from the
+    /// token endpoint over TLS, which OpenID Connect Core §3.1.3.7 allows as
+    /// proof of its origin.
+    pub(crate) fn validate(
+        id_token: &str,
+        issuer: &str,
+        client_id: &str,
+        nonce: &str
+    ) -> Result<OidcUser, OAuthError> {
+        let invalid = |reason: &str| OAuthError::InvalidIdToken(reason.to_string());
+
+        let mut parts = id_token.split('.');
+        let payload = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(_), Some(payload), Some(_), None) => payload,
+            _ => return Err(invalid("not a JWT"))
+        };
+
+        let payload = base64::decode_config(payload, base64::URL_SAFE_NO_PAD)
+            .map_err(|_| invalid("malformed payload"))?;
+
+        let claims = match serde_json::from_slice(&payload) {
+            Ok(Value::Object(claims)) => claims,
+            _ => return Err(invalid("malformed claims"))
+        };
+
+        let user = OidcUser { claims: claims };
+        if user.issuer() != Some(issuer) {
+            return Err(invalid("wrong issuer"));
+        }
+
+        // Borrows of `user` must end before it's returned.
+        {
+            let audiences: Vec<&str> = match user.claim("aud") {
+                Some(&Value::String(ref aud)) => vec![aud.as_str()],
+                Some(&Value::Array(ref auds)) => auds.iter().filter_map(|a| a.as_str()).collect(),
+                _ => vec![]
+            };
+
+            if !audiences.contains(&client_id) {
+                return Err(invalid("wrong audience"));
+            }
+
+            if audiences.len() > 1 {
+                match user.claim("azp").and_then(|azp| azp.as_str()) {
+                    Some(azp) if azp == client_id => {},
+                    _ => return Err(invalid("wrong authorized party"))
+                }
+            }
+
+            let token_nonce = user.claim("nonce").and_then(|n| n.as_str()).unwrap_or("");
+            if !constant_time_eq(token_nonce.as_bytes(), nonce.as_bytes()) {
+                return Err(invalid("wrong nonce"));
+            }
+        }
+
+        if !user.is_valid() {
+            return Err(invalid("expired or missing subject"));
+        }
+
+        Ok(user)
+    }
+
+    /// Returns `true` if the claims identify a subject and haven't expired.
+    fn is_valid(&self) -> bool {
+        let has_subject = self.claim("sub").map(|sub| sub.is_string()).unwrap_or(false);
+        let unexpired = self.expires_at().map(|exp| exp > now()).unwrap_or(false);
+        has_subject && unexpired
+    }
+
+    /// Returns the private cookie that keeps `self` signed in.
+    pub(crate) fn cookie(&self) -> Cookie<'static> {
+        let expires = ::time::at_utc(::time::Timespec::new(self.expires_at().unwrap_or(0), 0));
+        let claims = Value::Object(self.claims.clone()).to_string();
+        Cookie::build(USER_COOKIE, claims)
+            .path("/")
+            .same_site(SameSite::Lax)
+            .expires(expires)
+            .finish()
+    }
+
+    /// Returns a cookie that removes the cookie returned by `cookie()`.
+    pub(crate) fn removal_cookie() -> Cookie<'static> {
+        Cookie::build(USER_COOKIE, "").path("/").finish()
+    }
+
+    /// Returns the subject identifier: the `sub` claim.
+    pub fn subject(&self) -> &str {
+        self.claim("sub").and_then(|sub| sub.as_str()).unwrap_or("")
+    }
+
+    /// Returns the issuer identifier: the `iss` claim.
+    pub fn issuer(&self) -> Option<&str> {
+        self.claim("iss").and_then(|iss| iss.as_str())
+    }
+
+    /// Returns the `email` claim, if present.
+    pub fn email(&self) -> Option<&str> {
+        self.claim("email").and_then(|email| email.as_str())
+    }
+
+    /// Returns the `name` claim, if present.
+    pub fn name(&self) -> Option<&str> {
+        self.claim("name").and_then(|name| name.as_str())
+    }
+
+    /// Returns the expiry of the ID token, the `exp` claim, in seconds since
+    /// the Unix epoch.
+    pub fn expires_at(&self) -> Option<i64> {
+        self.claim("exp").and_then(|exp| exp.as_i64())
+    }
+
+    /// Returns the claim named `name`, if present.
+    pub fn claim(&self, name: &str) -> Option<&Value> {
+        self.claims.get(name)
+    }
+
+    /// Returns all of the claims.
+    pub fn claims(&self) -> &Map<String, Value> {
+        &self.claims
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for OidcUser {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> Outcome<Self, ()> {
+        let cookie = match request.cookies().get_private(USER_COOKIE) {
+            Some(cookie) => cookie,
+            None => return Forward(())
+        };
+
+        match serde_json::from_str(cookie.value()) {
+            Ok(Value::Object(claims)) => {
+                let user = OidcUser { claims: claims };
+                if user.is_valid() { Success(user) } else { Forward(()) }
+            }
+            _ => Forward(())
+        }
+    }
+}
+
+impl fmt::Debug for OidcUser {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OidcUser")
+            .field("subject", &self.subject())
+            .field("issuer", &self.issuer())
+            .finish()
+    }
+}
@@ -0,0 +1,435 @@
+//! Password hashing and login throttling.
+//!
+//! This module is only available when the `password` feature is enabled.
+//!
+//! Passwords should never be stored, only their hashes. [`hash()`] hashes a
+//! password with Argon2id and a random salt, producing a self-describing hash
+//! string in the PHC format, and [`verify()`] checks a password against a hash
+//! in constant time. Hashes produced by bcrypt, as is common in databases
+//! migrated from other frameworks, are verified as well; [`needs_rehash()`]
+//! identifies them so that they can be replaced by an Argon2id hash the next
+//! time the user logs in.
+//!
+//! Login endpoints are a target for password guessing. A [`LoginThrottle`],
+//! as managed state, counts failed logins per account, per client IP address,
+//! and per pair of the two, and refuses further attempts once too many have
+//! failed recently.
+//!
+//! # Example
+//!
+//! ```rust
+//! # #![feature(plugin, decl_macro)]
+//! # #![plugin(rocket_codegen)]
+//! # extern crate rocket;
+//! use std::net::SocketAddr;
+//!
+//! use rocket::State;
+//! use rocket::request::Form;
+//! use rocket::password::{self, LoginThrottle, Throttled};
+//!
+//! #[derive(FromForm)]
+//! struct Login {
+//!     username: String,
+//!     password: String,
+//! }
+//!
+//! # fn stored_hash(_: &str) -> Option<String> { None }
+//! #[post("/login", data = "<login>")]
+//! fn login(
+//!     login: Form<Login>,
+//!     remote: SocketAddr,
+//!     throttle: State<LoginThrottle>
+//! ) -> Result<&'static str, Throttled> {
+//!     let login = login.get();
+//!     throttle.check(&login.username, remote.ip())?;
+//!
+//!     let hash = stored_hash(&login.username);
+//!     match hash.map(|hash| password::verify(&login.password, &hash)) {
+//!         Some(true) => {
+//!             throttle.record_success(&login.username, remote.ip());
+//!             Ok("welcome")
+//!         }
+//!         _ => {
+//!             throttle.record_failure(&login.username, remote.ip());
+//!             Ok("invalid username or password")
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//! # if false {
+//!     rocket::ignite()
+//!         .manage(LoginThrottle::default())
+//!         .mount("/", routes![login])
+//!         .launch();
+//! # }
+//! }
+//! ```
+//!
+//! [`hash()`]: /rocket/password/fn.hash.html
+//! [`verify()`]: /rocket/password/fn.verify.html
+//! [`needs_rehash()`]: /rocket/password/fn.needs_rehash.html
+//! [`LoginThrottle`]: /rocket/password/struct.LoginThrottle.html
+
+use std::fmt;
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use argon2::{self, Config, Variant};
+use bcrypt;
+use ring::rand::{SecureRandom, SystemRandom};
+
+use request::Request;
+use response::{Response, Responder};
+use http::Status;
+
+/// The number of random bytes in the salt of an Argon2id hash.
+const SALT_LEN: usize = 16;
+
+/// The error returned when a password can't be hashed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashError(String);
+
+impl fmt::Display for HashError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "password hashing failed: {}", self.0)
+    }
+}
+
+/// Hashes `password` with Argon2id and a random salt and returns the hash in
+/// the PHC string format, which includes the salt and hashing parameters.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::password;
+///
+/// let hash = password::hash("hunter2").unwrap();
+/// assert!(hash.starts_with("$argon2id$"));
+/// assert!(password::verify("hunter2", &hash));
+/// assert!(!password::verify("hunter3", &hash));
+/// ```
+pub fn hash(password: &str) -> Result<String, HashError> {
+    let mut salt = [0; SALT_LEN];
+    SystemRandom::new().fill(&mut salt)
+        .map_err(|_| HashError("failed to generate a random salt".into()))?;
+
+    let config = Config { variant: Variant::Argon2id, ..Config::default() };
+    argon2::hash_encoded(password.as_bytes(), &salt, &config)
+        .map_err(|e| HashError(e.to_string()))
+}
+
+/// Hashes `password` with bcrypt at the given `cost`, a base-2 logarithm of
+/// the number of rounds between 4 and 31. Prefer [`hash()`](fn.hash.html)
+/// unless bcrypt is required for compatibility.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::password;
+///
+/// let hash = password::hash_bcrypt("hunter2", 4).unwrap();
+/// assert!(password::verify("hunter2", &hash));
+/// assert!(password::needs_rehash(&hash));
+/// ```
+pub fn hash_bcrypt(password: &str, cost: u32) -> Result<String, HashError> {
+    bcrypt::hash(password, cost).map_err(|e| HashError(e.to_string()))
+}
+
+/// Returns `true` if `password` matches `hash`, an Argon2 hash in the PHC
+/// string format or a bcrypt hash. Returns `false` if `hash` is malformed.
+pub fn verify(password: &str, hash: &str) -> bool {
+    if hash.starts_with("$argon2") {
+        argon2::verify_encoded(hash, password.as_bytes()).unwrap_or(false)
+    } else if hash.starts_with("$2") {
+        bcrypt::verify(password, hash).unwrap_or(false)
+    } else {
+        false
+    }
+}
+
+/// Returns `true` if `hash` wasn't produced by [`hash()`](fn.hash.html) with
+/// its current parameters and should be replaced, after the next successful
+/// [`verify()`](fn.verify.html), by a new hash of the password.
+pub fn needs_rehash(hash: &str) -> bool {
+    let config = Config::default();
+    let current = format!("$argon2id$v=19$m={},t={},p={}$",
+                          config.mem_cost, config.time_cost, config.lanes);
+    !hash.starts_with(&current)
+}
+
+/// The default number of failed logins allowed within the window.
+const DEFAULT_MAX_FAILURES: usize = 5;
+
+/// The default window in which failed logins are counted.
+const DEFAULT_WINDOW_SECS: u64 = 15 * 60;
+
+/// The default maximum number of tracked accounts, addresses, and pairs.
+const DEFAULT_CAPACITY: usize = 64 * 1024;
+
+/// A set of failed logins counted together: those for an account from an
+/// address, those for an account, and those from an address.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Bucket {
+    Pair(String, IpAddr),
+    Account(String),
+    Ip(IpAddr),
+}
+
+/// Throttles login attempts per account, per client IP address, and per pair
+/// of the two.
+///
+/// A `LoginThrottle` records failed logins in three buckets: one for each pair
+/// of an account name and an IP address, one for each account, and one for
+/// each address. Once a bucket holds its maximum number of failures within the
+/// last `window`, [`check()`](#method.check) refuses further attempts that
+/// count against it until the oldest of those failures is older than `window`.
+/// A successful login clears the failures of its pair.
+///
+/// The pair bucket, which allows `max_failures` failures, throttles a single
+/// attacker guessing one account's password without locking out the account's
+/// owner. The account bucket, which allows four times as many by default,
+/// bounds the guesses against one account from an attacker rotating through
+/// addresses; while it is full, the account's owner is refused too. The
+/// address bucket, which allows ten times as many by default, bounds the
+/// guesses from one address spread across many accounts.
+///
+/// At most `capacity` buckets, 65536 by default, are tracked. Once there are
+/// more, expired buckets are removed and, if that isn't enough, so are the
+/// buckets with the oldest failures.
+///
+/// The default throttle allows 5 failures per pair, 20 per account, and 50 per
+/// address every 15 minutes.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use rocket::password::LoginThrottle;
+///
+/// let throttle = LoginThrottle::new(2, Duration::from_secs(60))
+///     .max_account_failures(3);
+///
+/// let ip = "203.0.113.7".parse().unwrap();
+/// let other_ip = "203.0.113.8".parse().unwrap();
+///
+/// throttle.record_failure("bob", ip);
+/// assert!(throttle.check("bob", ip).is_ok());
+///
+/// throttle.record_failure("bob", ip);
+/// assert!(throttle.check("bob", ip).is_err());
+/// assert!(throttle.check("bob", other_ip).is_ok());
+/// assert!(throttle.check("alice", ip).is_ok());
+///
+/// throttle.record_failure("bob", other_ip);
+/// assert!(throttle.check("bob", other_ip).is_err());
+/// ```
+pub struct LoginThrottle {
+    max_failures: usize,
+    max_account_failures: usize,
+    max_ip_failures: usize,
+    window: Duration,
+    capacity: usize,
+    failures: Mutex<HashMap<Bucket, VecDeque<Instant>>>,
+}
+
+impl LoginThrottle {
+    /// Returns a throttle allowing `max_failures` failed logins per account
+    /// and address pair, four times as many per account, and ten times as
+    /// many per address within `window`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_failures` is `0`.
+    pub fn new(max_failures: usize, window: Duration) -> LoginThrottle {
+        assert!(max_failures > 0, "a login throttle must allow at least one failure");
+        LoginThrottle {
+            max_failures: max_failures,
+            max_account_failures: max_failures.saturating_mul(4),
+            max_ip_failures: max_failures.saturating_mul(10),
+            window: window,
+            capacity: DEFAULT_CAPACITY,
+            failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the number of failed logins allowed per account, from any
+    /// address, within the window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is `0`.
+    pub fn max_account_failures(mut self, max: usize) -> Self {
+        assert!(max > 0, "a login throttle must allow at least one failure");
+        self.max_account_failures = max;
+        self
+    }
+
+    /// Sets the number of failed logins allowed per address, for any account,
+    /// within the window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is `0`.
+    pub fn max_ip_failures(mut self, max: usize) -> Self {
+        assert!(max > 0, "a login throttle must allow at least one failure");
+        self.max_ip_failures = max;
+        self
+    }
+
+    /// Sets the maximum number of accounts, addresses, and pairs of the two
+    /// whose failures are tracked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is less than `2`.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        assert!(capacity >= 2, "a login throttle must track at least two buckets");
+        self.capacity = capacity;
+        self
+    }
+
+    // Returns the buckets a login for `account` from `ip` counts against and
+    // the number of failures each allows.
+    fn buckets(&self, account: &str, ip: IpAddr) -> [(Bucket, usize); 3] {
+        [
+            (Bucket::Pair(account.to_string(), ip), self.max_failures),
+            (Bucket::Account(account.to_string()), self.max_account_failures),
+            (Bucket::Ip(ip), self.max_ip_failures),
+        ]
+    }
+
+    // Removes the failures older than the window from `failures`.
+    fn expire(&self, failures: &mut VecDeque<Instant>, now: Instant) {
+        while failures.front().map_or(false, |&at| now.duration_since(at) >= self.window) {
+            failures.pop_front();
+        }
+    }
+
+    // Makes room for a new bucket in `failures` if it's at capacity. Expired
+    // buckets are removed first, then those with the oldest failures, until
+    // only half of the capacity is used so that the cost of purging is spread
+    // over many failures.
+    fn purge(&self, failures: &mut HashMap<Bucket, VecDeque<Instant>>, now: Instant) {
+        if failures.len() < self.capacity {
+            return;
+        }
+
+        failures.retain(|_, recent| {
+            self.expire(recent, now);
+            !recent.is_empty()
+        });
+
+        let target = self.capacity / 2;
+        if failures.len() > target {
+            let mut by_age: Vec<(Instant, Bucket)> = failures.iter()
+                .filter_map(|(bucket, recent)| recent.back().map(|&at| (at, bucket.clone())))
+                .collect();
+
+            by_age.sort_by(|a, b| a.0.cmp(&b.0));
+            let excess = failures.len() - target;
+            for (_, bucket) in by_age.into_iter().take(excess) {
+                failures.remove(&bucket);
+            }
+        }
+    }
+
+    /// Returns `Ok` if a login for `account` from `ip` may be attempted and,
+    /// otherwise, a [`Throttled`] error with the time until it may.
+    ///
+    /// [`Throttled`]: /rocket/password/struct.Throttled.html
+    pub fn check(&self, account: &str, ip: IpAddr) -> Result<(), Throttled> {
+        let now = Instant::now();
+        let mut failures = self.failures.lock().expect("login throttle lock");
+        let mut wait: Option<Duration> = None;
+        for &(ref bucket, limit) in self.buckets(account, ip).iter() {
+            let recent = match failures.get_mut(bucket) {
+                Some(recent) => recent,
+                None => continue
+            };
+
+            self.expire(recent, now);
+            if recent.len() >= limit {
+                let oldest = recent[recent.len() - limit];
+                let remaining = self.window - now.duration_since(oldest);
+                wait = Some(wait.map_or(remaining, |wait| ::std::cmp::max(wait, remaining)));
+            }
+        }
+
+        match wait {
+            Some(wait) => Err(Throttled(wait)),
+            None => Ok(())
+        }
+    }
+
+    /// Records a failed login for `account` from `ip`.
+    pub fn record_failure(&self, account: &str, ip: IpAddr) {
+        let now = Instant::now();
+        let mut failures = self.failures.lock().expect("login throttle lock");
+        for &(ref bucket, limit) in self.buckets(account, ip).iter() {
+            if !failures.contains_key(bucket) {
+                self.purge(&mut failures, now);
+            }
+
+            let recent = failures.entry(bucket.clone()).or_insert_with(VecDeque::new);
+            self.expire(recent, now);
+            recent.push_back(now);
+            while recent.len() > limit {
+                recent.pop_front();
+            }
+        }
+    }
+
+    /// Records a successful login for `account` from `ip`, clearing the
+    /// failures of the pair. The failures counted against the account and the
+    /// address alone are kept.
+    pub fn record_success(&self, account: &str, ip: IpAddr) {
+        let mut failures = self.failures.lock().expect("login throttle lock");
+        failures.remove(&Bucket::Pair(account.to_string(), ip));
+    }
+}
+
+impl Default for LoginThrottle {
+    fn default() -> LoginThrottle {
+        LoginThrottle::new(DEFAULT_MAX_FAILURES, Duration::from_secs(DEFAULT_WINDOW_SECS))
+    }
+}
+
+impl fmt::Debug for LoginThrottle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LoginThrottle")
+            .field("max_failures", &self.max_failures)
+            .field("max_account_failures", &self.max_account_failures)
+            .field("max_ip_failures", &self.max_ip_failures)
+            .field("window", &self.window)
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+/// A refused login attempt, with the time until another attempt is allowed.
+///
+/// As a responder, `Throttled` responds with `429 Too Many Requests` and a
+/// `Retry-After` header with the number of seconds to wait, rounded up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Throttled(pub Duration);
+
+impl Throttled {
+    /// Returns the time until another attempt is allowed.
+    #[inline(always)]
+    pub fn retry_after(&self) -> Duration {
+        self.0
+    }
+}
+
+impl Responder<'static> for Throttled {
+    fn respond_to(self, _: &Request) -> Result<Response<'static>, Status> {
+        let seconds = self.0.as_secs() + if self.0.subsec_nanos() > 0 { 1 } else { 0 };
+        Response::build()
+            .status(Status::TooManyRequests)
+            .raw_header("Retry-After", seconds.to_string())
+            .ok()
+    }
+}
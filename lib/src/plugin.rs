@@ -0,0 +1,323 @@
+//! Loading routes and fairings from dynamically loaded libraries at launch.
+//!
+//! Large deployments can ship optional modules as plugins, shared libraries
+//! built separately from the application, instead of recompiling the
+//! application to add or remove them. A plugin is a `cdylib` crate that
+//! depends on `rocket` with the `plugins` feature enabled and declares a
+//! registration function with the [`rocket_plugin!`] macro. The function mounts
+//! the plugin's routes and attaches its fairings via a [`Registrar`].
+//!
+//! The application loads plugins by attaching the [`PluginLoader`] fairing.
+//! The loader loads the libraries given to [`PluginLoader::load()`] and those
+//! listed in the `plugins` configuration parameter, an array of paths relative
+//! to the configuration file:
+//!
+//! ```toml
+//! [global]
+//! plugins = ["plugins/libreports.so"]
+//! ```
+//!
+//! If a plugin can't be loaded, attaching the loader fails, and so does
+//! launching Rocket. Loaded libraries are never unloaded.
+//!
+//! # ABI Compatibility
+//!
+//! Rust doesn't have a stable ABI, so a plugin must be built by the same
+//! version of `rustc`, against the same version of Rocket, as the application
+//! that loads it. The loader checks both, along with the version of the plugin
+//! ABI itself, and refuses to load incompatible plugins.
+//!
+//! # Example
+//!
+//! A plugin that mounts a single route at `/reports`:
+//!
+//! ```rust
+//! #[macro_use] extern crate rocket;
+//!
+//! use rocket::{Request, Route, Data};
+//! use rocket::handler::Outcome;
+//! use rocket::http::Method;
+//! use rocket::plugin::Registrar;
+//!
+//! fn daily<'r>(req: &'r Request, _: Data) -> Outcome<'r> {
+//!     Outcome::from(req, "daily report")
+//! }
+//!
+//! fn register(registrar: &mut Registrar) {
+//!     registrar.mount("/reports", vec![Route::new(Method::Get, "/daily", daily)]);
+//! }
+//!
+//! rocket_plugin!("reports", register);
+//! # fn main() {  }
+//! ```
+//!
+//! An application that loads it:
+//!
+//! ```rust
+//! use rocket::plugin::PluginLoader;
+//!
+//! # if false {
+//! rocket::ignite()
+//!     .attach(PluginLoader::new().load("plugins/libreports.so"))
+//!     .launch();
+//! # }
+//! ```
+//!
+//! [`rocket_plugin!`]: /rocket/macro.rocket_plugin.html
+//! [`Registrar`]: /rocket/plugin/struct.Registrar.html
+//! [`PluginLoader`]: /rocket/plugin/struct.PluginLoader.html
+//! [`PluginLoader::load()`]: /rocket/plugin/struct.PluginLoader.html#method.load
+
+use std::{fmt, io, mem};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use libloading::Library;
+
+use {Rocket, Route};
+use config::{Config, ConfigError};
+use fairing::{Fairing, Info, Kind, AttachError};
+
+/// The version of the plugin ABI: the layout of [`PluginDeclaration`] and the
+/// signature of plugin registration functions.
+///
+/// [`PluginDeclaration`]: /rocket/plugin/struct.PluginDeclaration.html
+pub const ABI_VERSION: u32 = 1;
+
+/// The version of Rocket that plugins and applications are built against.
+#[doc(hidden)]
+pub const ROCKET_VERSION: &'static str = env!("CARGO_PKG_VERSION");
+
+/// The version of `rustc` that plugins and applications are built by.
+#[doc(hidden)]
+pub const RUSTC_VERSION: &'static str = env!("ROCKET_RUSTC_VERSION");
+
+/// The name of the symbol a plugin's declaration is exported as.
+const DECLARATION_SYMBOL: &'static [u8] = b"ROCKET_PLUGIN_DECLARATION\0";
+
+/// The declaration a plugin exports to be loaded. Declared by the
+/// [`rocket_plugin!`] macro; it shouldn't be constructed directly.
+///
+/// [`rocket_plugin!`]: /rocket/macro.rocket_plugin.html
+#[repr(C)]
+pub struct PluginDeclaration {
+    /// The plugin ABI version. Must be the first field.
+    #[doc(hidden)]
+    pub abi_version: u32,
+    #[doc(hidden)]
+    pub rustc_version: &'static str,
+    #[doc(hidden)]
+    pub rocket_version: &'static str,
+    #[doc(hidden)]
+    pub name: &'static str,
+    #[doc(hidden)]
+    pub register: fn(&mut Registrar),
+}
+
+/// Declares the registration function of a plugin.
+///
+/// The first argument is the plugin's name, a `&'static str`, and the second
+/// is the function, of type `fn(&mut Registrar)`, that registers the plugin's
+/// routes and fairings. The macro must be invoked exactly once, at the root of
+/// the plugin crate. See the [`plugin`](/rocket/plugin/index.html) module for
+/// an example.
+#[macro_export]
+macro_rules! rocket_plugin {
+    ($name:expr, $register:expr) => (
+        #[no_mangle]
+        #[doc(hidden)]
+        pub static ROCKET_PLUGIN_DECLARATION: $crate::plugin::PluginDeclaration =
+            $crate::plugin::PluginDeclaration {
+                abi_version: $crate::plugin::ABI_VERSION,
+                rustc_version: $crate::plugin::RUSTC_VERSION,
+                rocket_version: $crate::plugin::ROCKET_VERSION,
+                name: $name,
+                register: $register,
+            };
+    )
+}
+
+/// Collects the routes and fairings a plugin registers.
+///
+/// A `Registrar` is passed to the registration function declared with
+/// [`rocket_plugin!`]. Once the function returns, the routes are mounted and
+/// the fairings attached, in the order they were registered.
+///
+/// [`rocket_plugin!`]: /rocket/macro.rocket_plugin.html
+pub struct Registrar {
+    mounts: Vec<(String, Vec<Route>)>,
+    fairings: Vec<Box<Fairing>>,
+}
+
+impl Registrar {
+    #[inline]
+    fn new() -> Registrar {
+        Registrar { mounts: vec![], fairings: vec![] }
+    }
+
+    /// Mounts `routes` at `base`, as with
+    /// [`Rocket::mount()`](/rocket/struct.Rocket.html#method.mount).
+    pub fn mount(&mut self, base: &str, routes: Vec<Route>) -> &mut Self {
+        self.mounts.push((base.to_string(), routes));
+        self
+    }
+
+    /// Attaches `fairing`, as with
+    /// [`Rocket::attach()`](/rocket/struct.Rocket.html#method.attach).
+    pub fn attach<F: Fairing>(&mut self, fairing: F) -> &mut Self {
+        self.fairings.push(Box::new(fairing));
+        self
+    }
+}
+
+/// The error returned when a plugin can't be loaded.
+#[derive(Debug)]
+pub enum PluginError {
+    /// The library couldn't be loaded.
+    Load(io::Error),
+    /// The library doesn't declare a plugin with `rocket_plugin!`.
+    NotAPlugin,
+    /// The plugin isn't compatible with the application. Parameters: (what is
+    /// incompatible, the application's version, the plugin's version).
+    Incompatible(&'static str, String, String),
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PluginError::Load(ref e) => write!(f, "the library couldn't be loaded: {}", e),
+            PluginError::NotAPlugin => write!(f, "the library doesn't declare a plugin"),
+            PluginError::Incompatible(what, ref expected, ref found) => {
+                write!(f, "the plugin was built with {} {}, not {}", what, found, expected)
+            }
+        }
+    }
+}
+
+impl Error for PluginError {
+    fn description(&self) -> &str {
+        match *self {
+            PluginError::Load(..) => "the library couldn't be loaded",
+            PluginError::NotAPlugin => "the library doesn't declare a plugin",
+            PluginError::Incompatible(..) => "the plugin isn't compatible",
+        }
+    }
+}
+
+/// Loads the plugin at `path`, returning its name and what it registered.
+fn load(path: &Path) -> Result<(&'static str, Registrar), PluginError> {
+    let library = Library::new(path).map_err(PluginError::Load)?;
+
+    // The declaration lives in the library, which is never unloaded once the
+    // plugin is registered. Only the ABI version is read until it's known to
+    // match, since the layout of the remaining fields depends on it.
+    let declaration: &'static PluginDeclaration = unsafe {
+        match library.get::<*const PluginDeclaration>(DECLARATION_SYMBOL) {
+            Ok(symbol) => &**symbol,
+            Err(_) => return Err(PluginError::NotAPlugin)
+        }
+    };
+
+    let check = |what, expected: &str, found: &str| match expected == found {
+        true => Ok(()),
+        false => Err(PluginError::Incompatible(what, expected.into(), found.into()))
+    };
+
+    let abi_version = declaration.abi_version.to_string();
+    check("plugin ABI", &ABI_VERSION.to_string(), &abi_version)?;
+    check("rustc", RUSTC_VERSION, declaration.rustc_version)?;
+    check("Rocket", ROCKET_VERSION, declaration.rocket_version)?;
+
+    let mut registrar = Registrar::new();
+    (declaration.register)(&mut registrar);
+
+    // Handlers and fairings point into the library: it must never be unloaded.
+    mem::forget(library);
+    Ok((declaration.name, registrar))
+}
+
+/// Returns the paths in the `plugins` configuration parameter, relative to the
+/// configuration file.
+fn configured_paths(config: &Config) -> Result<Vec<PathBuf>, AttachError> {
+    let array = match config.get_slice("plugins") {
+        Ok(array) => array,
+        Err(ConfigError::NotFound) => return Ok(vec![]),
+        Err(e) => return Err(AttachError::new("`plugins` is invalid").with_cause(e.to_string())),
+    };
+
+    array.iter()
+        .map(|value| match value.as_str() {
+            Some(path) => Ok(config.root_relative(path)),
+            None => Err(AttachError::new("`plugins` must be an array of paths"))
+        })
+        .collect()
+}
+
+/// A fairing that loads plugins when it is attached.
+///
+/// See the [`plugin`](/rocket/plugin/index.html) module for details.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::plugin::PluginLoader;
+///
+/// # if false {
+/// // Load the plugins listed in the `plugins` configuration parameter.
+/// rocket::ignite().attach(PluginLoader::new()).launch();
+/// # }
+/// ```
+pub struct PluginLoader {
+    paths: Vec<PathBuf>,
+}
+
+impl PluginLoader {
+    /// Returns a loader that loads the plugins listed in the `plugins`
+    /// configuration parameter, if any.
+    #[inline]
+    pub fn new() -> PluginLoader {
+        PluginLoader { paths: vec![] }
+    }
+
+    /// Additionally loads the plugin at `path`. Plugins added with this method
+    /// are loaded before those in the configuration, in the order added.
+    pub fn load<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.paths.push(path.as_ref().to_path_buf());
+        self
+    }
+}
+
+impl Fairing for PluginLoader {
+    fn info(&self) -> Info {
+        Info { name: "Plugin Loader", kind: Kind::Attach }
+    }
+
+    fn on_attach(&self, mut rocket: Rocket) -> Result<Rocket, Rocket> {
+        let mut paths = self.paths.clone();
+        match configured_paths(rocket.config()) {
+            Ok(configured) => paths.extend(configured),
+            Err(e) => return rocket.fail_attach(e),
+        }
+
+        for path in paths {
+            let (name, registrar) = match load(&path) {
+                Ok(plugin) => plugin,
+                Err(e) => {
+                    let msg = format!("failed to load plugin {}", path.display());
+                    return rocket.fail_attach(AttachError::new(msg).with_cause(e));
+                }
+            };
+
+            info!("Loaded plugin '{}' from {}.", name, path.display());
+            for (base, routes) in registrar.mounts {
+                rocket = rocket.mount(&base, routes);
+            }
+
+            for fairing in registrar.fairings {
+                rocket = rocket.attach(fairing);
+            }
+        }
+
+        Ok(rocket)
+    }
+}
@@ -0,0 +1,369 @@
+//! Replay protection for signed requests via nonces and timestamps.
+//!
+//! A request authenticated by a signature over its contents, such as a webhook
+//! delivery or a request signed per the HTTP Signatures draft, stays valid for
+//! as long as the key that signed it. Anyone who captures such a request can
+//! send it again. To prevent this, the sender includes a timestamp and a
+//! unique nonce in every request, both covered by the signature, and the
+//! receiver rejects requests whose timestamp is outside of a window around the
+//! current time and requests with a nonce it has already seen.
+//!
+//! The [`Fresh`] request guard performs these checks. It reads the nonce and
+//! the timestamp, in seconds since the Unix epoch, from the `X-Nonce` and
+//! `X-Timestamp` headers and accepts timestamps up to five minutes from the
+//! current time. Both the headers and the window are set per route by the
+//! guard's [`ReplayWindow`] type parameter.
+//!
+//! Nonces are recorded in the [`NonceStore`] of the [`ReplayCache`] managed by
+//! the application. A nonce is only remembered until its timestamp falls out
+//! of the window, after which the timestamp check rejects the request anyway.
+//! The [`MemoryStore`] is process-local; deployments with several instances
+//! should share a store, such as one backed by a database, between them.
+//!
+//! `Fresh` only checks that a request hasn't been seen before; it doesn't
+//! verify the signature. Declare it _after_ the guard that verifies the
+//! signature so that unauthenticated requests can't use up nonces. Guards that
+//! read the nonce and timestamp some other way, for instance from a signed
+//! payload, can check them via [`ReplayCache::check()`] directly.
+//!
+//! # Example
+//!
+//! ```rust
+//! # #![feature(plugin, decl_macro)]
+//! # #![plugin(rocket_codegen)]
+//! # extern crate rocket;
+//! use rocket::replay::{Fresh, ReplayCache, ReplayWindow};
+//!
+//! struct Deliveries;
+//!
+//! impl ReplayWindow for Deliveries {
+//!     const SECONDS: u64 = 60;
+//!     const NONCE_HEADER: &'static str = "X-Delivery-Id";
+//! }
+//!
+//! // The signature would be verified by a guard declared before `fresh`.
+//! #[post("/webhook")]
+//! fn webhook(fresh: Fresh<Deliveries>) -> String {
+//!     format!("delivery {} received", fresh.nonce())
+//! }
+//!
+//! fn main() {
+//! # if false {
+//!     rocket::ignite()
+//!         .manage(ReplayCache::default())
+//!         .mount("/", routes![webhook])
+//!         .launch();
+//! # }
+//! }
+//! ```
+//!
+//! [`Fresh`]: /rocket/replay/struct.Fresh.html
+//! [`ReplayWindow`]: /rocket/replay/trait.ReplayWindow.html
+//! [`NonceStore`]: /rocket/replay/trait.NonceStore.html
+//! [`ReplayCache`]: /rocket/replay/struct.ReplayCache.html
+//! [`MemoryStore`]: /rocket/replay/struct.MemoryStore.html
+//! [`ReplayCache::check()`]: /rocket/replay/struct.ReplayCache.html#method.check
+
+use std::fmt;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use request::{Request, FromRequest, Outcome, State};
+use outcome::Outcome::*;
+use http::Status;
+
+/// The maximum length, in bytes, of a nonce.
+const MAX_NONCE_LEN: usize = 256;
+
+/// Trait implemented by types that configure the [`Fresh`] request guard of a
+/// route: the window around the current time timestamps must fall within and
+/// the headers the nonce and timestamp are read from.
+///
+/// [`Fresh`]: /rocket/replay/struct.Fresh.html
+pub trait ReplayWindow {
+    /// The number of seconds a request's timestamp may be before or after
+    /// the current time.
+    const SECONDS: u64;
+
+    /// The header the nonce is read from.
+    const NONCE_HEADER: &'static str = "X-Nonce";
+
+    /// The header the timestamp, in seconds since the Unix epoch, is read
+    /// from.
+    const TIMESTAMP_HEADER: &'static str = "X-Timestamp";
+}
+
+/// A five minute window with the default headers. The default window of a
+/// [`Fresh`](/rocket/replay/struct.Fresh.html) guard.
+pub struct FiveMinutes;
+
+impl ReplayWindow for FiveMinutes {
+    const SECONDS: u64 = 5 * 60;
+}
+
+/// Trait implemented by storage backends for seen nonces.
+///
+/// Implementations must be thread-safe: they are accessed concurrently from
+/// all of Rocket's worker threads. Checking for and recording a nonce must be
+/// a single atomic operation so that two concurrent requests with the same
+/// nonce can't both be accepted.
+pub trait NonceStore: Send + Sync + 'static {
+    /// Records `nonce` until at least `expires`. Returns `false` if `nonce`
+    /// was already recorded and hasn't expired, and `true` otherwise.
+    fn insert(&self, nonce: &str, expires: SystemTime) -> bool;
+}
+
+/// An in-memory, process-local `NonceStore`.
+///
+/// Expired nonces are removed lazily as new nonces are inserted.
+pub struct MemoryStore {
+    nonces: Mutex<HashMap<String, SystemTime>>,
+}
+
+impl MemoryStore {
+    /// Returns a new, empty `MemoryStore`.
+    pub fn new() -> MemoryStore {
+        MemoryStore { nonces: Mutex::new(HashMap::new()) }
+    }
+}
+
+impl NonceStore for MemoryStore {
+    fn insert(&self, nonce: &str, expires: SystemTime) -> bool {
+        let now = SystemTime::now();
+        let mut nonces = self.nonces.lock().expect("nonce store lock");
+        nonces.retain(|_, until| now < *until);
+        if nonces.contains_key(nonce) {
+            return false;
+        }
+
+        nonces.insert(nonce.to_string(), expires);
+        true
+    }
+}
+
+/// The reason a request was rejected as a possible replay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The request has no nonce or no timestamp. The status is `400 Bad
+    /// Request`.
+    Missing,
+    /// The nonce is empty or too long, or the timestamp isn't an integer.
+    /// The status is `400 Bad Request`.
+    Malformed,
+    /// The timestamp is outside of the window. The status is `401
+    /// Unauthorized`.
+    Stale,
+    /// The nonce was already seen. The status is `401 Unauthorized`.
+    Replayed,
+    /// No `ReplayCache` is managed. The status is `500 Internal Server
+    /// Error`.
+    Unconfigured,
+}
+
+impl ReplayError {
+    /// The status a request rejected for this reason fails with.
+    fn status(self) -> Status {
+        match self {
+            ReplayError::Missing | ReplayError::Malformed => Status::BadRequest,
+            ReplayError::Stale | ReplayError::Replayed => Status::Unauthorized,
+            ReplayError::Unconfigured => Status::InternalServerError,
+        }
+    }
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReplayError::Missing => write!(f, "missing nonce or timestamp"),
+            ReplayError::Malformed => write!(f, "malformed nonce or timestamp"),
+            ReplayError::Stale => write!(f, "timestamp outside of the window"),
+            ReplayError::Replayed => write!(f, "nonce already seen"),
+            ReplayError::Unconfigured => write!(f, "no replay cache is managed"),
+        }
+    }
+}
+
+/// The nonces seen by an application, as managed state.
+///
+/// The [`Fresh`] request guard requires a `ReplayCache` to be managed. The
+/// default cache records nonces in a [`MemoryStore`]; a cache with a custom
+/// [`NonceStore`] can be created via [`ReplayCache::new()`]:
+///
+/// ```rust
+/// use std::time::SystemTime;
+/// use rocket::replay::{ReplayCache, NonceStore};
+///
+/// struct Redis;
+///
+/// impl NonceStore for Redis {
+///     fn insert(&self, nonce: &str, expires: SystemTime) -> bool {
+///         /* SET nonce 1 NX EXAT expires */
+///         # true
+///     }
+/// }
+///
+/// # if false {
+/// rocket::ignite()
+///     .manage(ReplayCache::new(Redis))
+///     .launch();
+/// # }
+/// ```
+///
+/// [`Fresh`]: /rocket/replay/struct.Fresh.html
+/// [`MemoryStore`]: /rocket/replay/struct.MemoryStore.html
+/// [`NonceStore`]: /rocket/replay/trait.NonceStore.html
+/// [`ReplayCache::new()`]: #method.new
+pub struct ReplayCache {
+    store: Box<NonceStore>,
+}
+
+impl ReplayCache {
+    /// Returns a `ReplayCache` that records nonces in `store`.
+    #[inline]
+    pub fn new<S: NonceStore>(store: S) -> ReplayCache {
+        ReplayCache { store: Box::new(store) }
+    }
+
+    /// Checks that `timestamp` is within `window` of the current time and
+    /// that `nonce` hasn't been seen within the window, recording `nonce` if
+    /// both are the case.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Stale` if `timestamp` is outside of the window, `Malformed` if
+    /// `nonce` is empty or longer than 256 bytes, and `Replayed` if `nonce`
+    /// was already seen.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::{Duration, SystemTime};
+    /// use rocket::replay::{ReplayCache, ReplayError};
+    ///
+    /// let cache = ReplayCache::default();
+    /// let (now, window) = (SystemTime::now(), Duration::from_secs(60));
+    /// assert!(cache.check("8b1f2c", now, window).is_ok());
+    /// assert_eq!(cache.check("8b1f2c", now, window), Err(ReplayError::Replayed));
+    ///
+    /// let yesterday = now - Duration::from_secs(24 * 60 * 60);
+    /// assert_eq!(cache.check("d04e7a", yesterday, window), Err(ReplayError::Stale));
+    /// ```
+    pub fn check(
+        &self,
+        nonce: &str,
+        timestamp: SystemTime,
+        window: Duration
+    ) -> Result<(), ReplayError> {
+        let skew = match SystemTime::now().duration_since(timestamp) {
+            Ok(age) => age,
+            Err(e) => e.duration()
+        };
+
+        if skew > window {
+            return Err(ReplayError::Stale);
+        }
+
+        if nonce.is_empty() || nonce.len() > MAX_NONCE_LEN {
+            return Err(ReplayError::Malformed);
+        }
+
+        match self.store.insert(nonce, timestamp + window) {
+            true => Ok(()),
+            false => Err(ReplayError::Replayed)
+        }
+    }
+}
+
+impl Default for ReplayCache {
+    fn default() -> ReplayCache {
+        ReplayCache::new(MemoryStore::new())
+    }
+}
+
+/// Request guard for a request with a fresh nonce and timestamp.
+///
+/// The nonce and timestamp are read from the headers set by the window `W`
+/// and checked via the managed [`ReplayCache`]. The guard fails with:
+///
+///   * `400 Bad Request` if the nonce or timestamp is missing or malformed
+///   * `401 Unauthorized` if the timestamp is outside of the window or the
+///     nonce was already seen
+///   * `500 Internal Server Error` if no `ReplayCache` is managed
+///
+/// The default window, [`FiveMinutes`], accepts timestamps in the `X-Timestamp`
+/// header up to five minutes from the current time and reads the nonce from
+/// the `X-Nonce` header. See the [module level
+/// documentation](/rocket/replay/index.html) for an example.
+///
+/// [`ReplayCache`]: /rocket/replay/struct.ReplayCache.html
+/// [`FiveMinutes`]: /rocket/replay/struct.FiveMinutes.html
+pub struct Fresh<W: ReplayWindow = FiveMinutes> {
+    nonce: String,
+    timestamp: SystemTime,
+    _window: PhantomData<W>,
+}
+
+impl<W: ReplayWindow> Fresh<W> {
+    /// Returns the request's nonce.
+    #[inline(always)]
+    pub fn nonce(&self) -> &str {
+        &self.nonce
+    }
+
+    /// Returns the request's timestamp.
+    #[inline(always)]
+    pub fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+}
+
+impl<W: ReplayWindow> fmt::Debug for Fresh<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Fresh")
+            .field("nonce", &self.nonce)
+            .field("timestamp", &self.timestamp)
+            .finish()
+    }
+}
+
+impl<'a, 'r, W: ReplayWindow> FromRequest<'a, 'r> for Fresh<W> {
+    type Error = ReplayError;
+
+    fn from_request(request: &'a Request<'r>) -> Outcome<Self, Self::Error> {
+        let cache = match request.guard::<State<ReplayCache>>() {
+            Success(cache) => cache,
+            _ => {
+                error_!("A `Fresh` guard was used, but no `ReplayCache` is managed.");
+                info_!("Manage a `ReplayCache`, e.g. `ReplayCache::default()`.");
+                let error = ReplayError::Unconfigured;
+                return Failure((error.status(), error));
+            }
+        };
+
+        let headers = request.headers();
+        let (nonce, timestamp) = match (headers.get_one(W::NONCE_HEADER),
+                                        headers.get_one(W::TIMESTAMP_HEADER)) {
+            (Some(nonce), Some(timestamp)) => (nonce.trim(), timestamp.trim()),
+            _ => return Failure((ReplayError::Missing.status(), ReplayError::Missing))
+        };
+
+        let timestamp = match timestamp.parse::<u64>() {
+            Ok(secs) => UNIX_EPOCH + Duration::from_secs(secs),
+            Err(_) => return Failure((ReplayError::Malformed.status(), ReplayError::Malformed))
+        };
+
+        match cache.check(nonce, timestamp, Duration::from_secs(W::SECONDS)) {
+            Ok(()) => Success(Fresh {
+                nonce: nonce.to_string(),
+                timestamp: timestamp,
+                _window: PhantomData
+            }),
+            Err(e) => {
+                info_!("Rejected a possibly replayed request: {}.", e);
+                Failure((e.status(), e))
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::time::{Duration, Instant};
+
+use outcome::Outcome::*;
+use request::{Request, FromRequest, Outcome};
+
+/// A token a handler polls to learn whether its route's timeout has elapsed.
+///
+/// Routes can be given a timeout in milliseconds with the `timeout` route
+/// parameter, as in `#[get("/report", timeout = 5000)]`, or by setting
+/// [`Route::timeout`]. The timeout starts when the handler is called and
+/// covers both the handler and the writing of the response body. Rust can't
+/// interrupt a running handler, so cancellation is cooperative: long-running
+/// handlers should check [`is_cancelled()`] periodically and stop early.
+///
+/// Rocket enforces the timeout as follows:
+///
+///   * If the handler returns after the deadline, its response is discarded
+///     and the request fails with `503 Service Unavailable`.
+///   * If a streamed response body is still being written at the deadline,
+///     writing stops with an error and the connection is closed.
+///
+/// In both cases, the failure is logged and the `requests.timed_out` metric is
+/// incremented.
+///
+/// # Request Guard
+///
+/// As a request guard, `CancelToken` never fails. For routes without a
+/// timeout, the token is never cancelled.
+///
+/// # Example
+///
+/// ```rust
+/// # #![feature(plugin, decl_macro)]
+/// # #![plugin(rocket_codegen)]
+/// # extern crate rocket;
+/// use rocket::request::CancelToken;
+///
+/// # fn compute_row(_: usize) -> String { String::new() }
+/// #[get("/report", timeout = 5000)]
+/// fn report(token: CancelToken) -> Option<String> {
+///     let mut report = String::new();
+///     for row in 0..1000 {
+///         if token.is_cancelled() {
+///             return None;
+///         }
+///
+///         report.push_str(&compute_row(row));
+///     }
+///
+///     Some(report)
+/// }
+/// # fn main() {  }
+/// ```
+///
+/// [`Route::timeout`]: /rocket/struct.Route.html#structfield.timeout
+/// [`is_cancelled()`]: #method.is_cancelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CancelToken {
+    deadline: Option<Instant>,
+}
+
+impl CancelToken {
+    /// Returns a token that is cancelled at `deadline`, if any.
+    #[inline(always)]
+    pub(crate) fn new(deadline: Option<Instant>) -> CancelToken {
+        CancelToken { deadline: deadline }
+    }
+
+    /// Returns `true` if the route's timeout has elapsed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// use rocket::request::CancelToken;
+    ///
+    /// # Request::example(Method::Get, "/uri", |request| {
+    /// // Outside of a route with a timeout, the token is never cancelled.
+    /// let token = request.guard::<CancelToken>().unwrap();
+    /// assert!(!token.is_cancelled());
+    /// assert_eq!(token.remaining(), None);
+    /// # });
+    /// ```
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.deadline.map_or(false, |deadline| Instant::now() >= deadline)
+    }
+
+    /// Returns the instant at which the route's timeout elapses, if the route
+    /// has a timeout.
+    #[inline(always)]
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Returns the time left until the route's timeout elapses, zero if it has
+    /// elapsed, or `None` if the route has no timeout.
+    #[inline]
+    pub fn remaining(&self) -> Option<Duration> {
+        let now = Instant::now();
+        self.deadline.map(|deadline| match deadline > now {
+            true => deadline - now,
+            false => Duration::from_secs(0)
+        })
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for CancelToken {
+    type Error = ();
+
+    #[inline]
+    fn from_request(request: &'a Request<'r>) -> Outcome<Self, Self::Error> {
+        Success(CancelToken::new(request.deadline()))
+    }
+}
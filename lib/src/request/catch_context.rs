@@ -0,0 +1,157 @@
+use std::fmt;
+
+use router::Route;
+use metrics::Timings;
+use http::Status;
+
+/// The kind of guard that failed a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GuardKind {
+    /// A request guard: a type implementing `FromRequest`.
+    Request,
+    /// The data guard: a type implementing `FromData`.
+    Data,
+    /// A dynamic path parameter whose segment matched its constraint but
+    /// couldn't be parsed.
+    Param,
+    /// The query string guard: a type implementing `FromForm`.
+    Query,
+}
+
+impl fmt::Display for GuardKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GuardKind::Request => write!(f, "request guard"),
+            GuardKind::Data => write!(f, "data guard"),
+            GuardKind::Param => write!(f, "path parameter"),
+            GuardKind::Query => write!(f, "query guard"),
+        }
+    }
+}
+
+/// A guard of a route declared with a route attribute that failed a request.
+///
+/// Rocket records the failure when the guard fails and makes it available to
+/// error catchers via [`CatchContext::guard_failure()`].
+///
+/// [`CatchContext::guard_failure()`]: /rocket/request/struct.CatchContext.html#method.guard_failure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuardFailure {
+    /// The kind of guard that failed.
+    pub kind: GuardKind,
+    /// The name of the handler's argument the guard was for.
+    pub name: &'static str,
+    /// The type of the guard, as written in the handler's signature.
+    pub ty: &'static str,
+    /// The status the guard failed with.
+    pub status: Status,
+    /// The `Debug` representation of the guard's error, if its error type
+    /// implements `Debug`.
+    pub error: Option<String>,
+}
+
+impl fmt::Display for GuardFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} `{}: {}` failed with {}", self.kind, self.name, self.ty, self.status)?;
+        if let Some(ref error) = self.error {
+            write!(f, ": {}", error)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Information about why a request ended up at an error catcher.
+///
+/// A catcher declared with the `catch` attribute receives the context when it
+/// takes an argument of type `CatchContext`. It is also available to any code
+/// with access to the request via [`Request::catch_context()`].
+///
+/// # Example
+///
+/// ```rust
+/// # #![feature(plugin, decl_macro)]
+/// # #![plugin(rocket_codegen)]
+/// # extern crate rocket;
+/// use rocket::request::CatchContext;
+///
+/// #[catch(422)]
+/// fn unprocessable(context: CatchContext) -> String {
+///     match context.guard_failure() {
+///         Some(failure) => format!("invalid `{}`: {:?}", failure.name, failure.error),
+///         None => "the request could not be processed".into()
+///     }
+/// }
+/// # fn main() {  }
+/// ```
+///
+/// [`Request::catch_context()`]: /rocket/struct.Request.html#method.catch_context
+#[derive(Debug, Clone)]
+pub struct CatchContext<'r> {
+    pub(crate) route: Option<&'r Route>,
+    pub(crate) guard_failure: Option<GuardFailure>,
+    pub(crate) timings: Timings,
+}
+
+impl<'r> CatchContext<'r> {
+    /// Returns the route that was last tried for the request, if any. No route
+    /// was tried if the request was rejected before routing or matched none.
+    #[inline(always)]
+    pub fn route(&self) -> Option<&'r Route> {
+        self.route
+    }
+
+    /// Returns the guard that failed the request, if a guard of a route
+    /// declared with a route attribute did.
+    #[inline(always)]
+    pub fn guard_failure(&self) -> Option<&GuardFailure> {
+        self.guard_failure.as_ref()
+    }
+
+    /// Returns the timings of the request up to the point at which the
+    /// catcher was invoked. The `total` hasn't been measured yet and is zero.
+    #[inline(always)]
+    pub fn timings(&self) -> Timings {
+        self.timings
+    }
+}
+
+/// Wraps a guard's error so generated code can describe it via `Debug` when
+/// the error's type implements `Debug`. For internal use only!
+#[doc(hidden)]
+pub struct Describe<'a, E: 'a>(pub &'a E);
+
+#[doc(hidden)]
+pub trait DescribeDebug {
+    fn _rocket_describe(&self) -> Option<String>;
+}
+
+impl<'a, E: fmt::Debug> DescribeDebug for Describe<'a, E> {
+    fn _rocket_describe(&self) -> Option<String> {
+        Some(format!("{:?}", self.0))
+    }
+}
+
+#[doc(hidden)]
+pub trait DescribeOther {
+    fn _rocket_describe(&self) -> Option<String>;
+}
+
+impl<'a, 'b, E> DescribeOther for &'b Describe<'a, E> {
+    fn _rocket_describe(&self) -> Option<String> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Describe, DescribeDebug, DescribeOther};
+
+    struct Opaque;
+
+    #[test]
+    fn test_describe() {
+        assert_eq!((&Describe(&"bad input"))._rocket_describe(), Some("\"bad input\"".into()));
+        assert_eq!((&Describe(&Opaque))._rocket_describe(), None);
+    }
+}
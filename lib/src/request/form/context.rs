@@ -0,0 +1,196 @@
+use std::fmt::{self, Debug};
+
+use request::Request;
+use request::form::{Form, FromForm, FormItems, FormErrors, FormError, FormErrorKind};
+use request::form::form::FormResult;
+use data::{self, Data, FromData};
+
+/// The raw values and per-field errors of a submitted form.
+///
+/// A `FormContext` is produced by
+/// [`Contextual`](/rocket/request/struct.Contextual.html) and retains
+/// everything needed to re-render a form after a validation failure: the
+/// percent-decoded value the user submitted for each field and every error
+/// that was emitted while parsing those values.
+///
+/// # Example
+///
+/// ```rust
+/// # #![feature(plugin, decl_macro, custom_derive)]
+/// # #![plugin(rocket_codegen)]
+/// # extern crate rocket;
+/// use rocket::request::Contextual;
+///
+/// #[derive(FromForm)]
+/// struct Signup {
+///     name: String,
+///     age: u8,
+/// }
+///
+/// #[post("/signup", data = "<form>")]
+/// fn signup(form: Contextual<Signup>) -> String {
+///     let context = form.context();
+///     match form.get() {
+///         Some(signup) => format!("Welcome, {}!", signup.name),
+///         None => format!("Try again, {}: {}",
+///             context.value("name").unwrap_or("stranger"),
+///             context.errors())
+///     }
+/// }
+/// #
+/// # fn main() { }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormContext {
+    values: Vec<(String, String)>,
+    errors: FormErrors,
+}
+
+impl FormContext {
+    fn from_form_string(string: &str) -> FormContext {
+        let values = FormItems::from(string)
+            .map(|(k, v)| {
+                let key = k.url_decode().unwrap_or_else(|_| k.as_str().to_string());
+                let value = v.url_decode().unwrap_or_else(|_| v.as_str().to_string());
+                (key, value)
+            })
+            .collect();
+
+        FormContext { values: values, errors: FormErrors::new() }
+    }
+
+    /// Returns the first submitted value for the field named `name`, if any.
+    #[inline]
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values(name).next()
+    }
+
+    /// Returns an iterator over all of the submitted values for the field
+    /// named `name`.
+    #[inline]
+    pub fn values<'a>(&'a self, name: &'a str) -> impl Iterator<Item=&'a str> + 'a {
+        self.values.iter().filter(move |&&(ref k, _)| k == name).map(|&(_, ref v)| v.as_str())
+    }
+
+    /// Returns an iterator over all of the submitted `(name, value)` pairs in
+    /// the order they were submitted.
+    #[inline]
+    pub fn fields<'a>(&'a self) -> impl Iterator<Item=(&'a str, &'a str)> + 'a {
+        self.values.iter().map(|&(ref k, ref v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Returns all of the errors emitted while parsing the form.
+    #[inline(always)]
+    pub fn errors(&self) -> &FormErrors {
+        &self.errors
+    }
+
+    /// Returns an iterator over the errors associated with the field named
+    /// `name`.
+    #[inline]
+    pub fn field_errors<'a>(&'a self, name: &'a str) -> impl Iterator<Item=&'a FormError> + 'a {
+        self.errors.field(name)
+    }
+
+    /// Returns `true` if any errors were emitted while parsing the form.
+    #[inline(always)]
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+}
+
+/// A `FromData` type that parses a form leniently while retaining the raw
+/// submission and per-field errors.
+///
+/// Unlike [`Form`](/rocket/request/struct.Form.html) and
+/// [`LenientForm`](/rocket/request/struct.LenientForm.html), a `Contextual`
+/// does not fail when the form data cannot be parsed into a `T`. Instead, the
+/// parsed value is `None` and the accompanying
+/// [`FormContext`](/rocket/request/struct.FormContext.html) contains the raw
+/// submitted values along with an error for each field that failed to parse.
+/// This allows a handler to render the form again, prefilled with the user's
+/// input and annotated with errors, instead of discarding the submission.
+///
+/// Parsing is lenient: extra fields are ignored. `Contextual` requires that
+/// `T`'s `FromForm` error type is
+/// [`FormErrors`](/rocket/request/struct.FormErrors.html), which is the case
+/// for all types deriving `FromForm`.
+///
+/// ## Incoming Data Limits
+///
+/// A `Contextual` obeys the same data limits as a `Form`: `limits.forms`.
+pub struct Contextual<'f, T: FromForm<'f> + 'f> {
+    form: Option<Form<'f, T>>,
+    context: FormContext,
+}
+
+impl<'f, T: FromForm<'f, Error = FormErrors> + 'f> Contextual<'f, T> {
+    pub(crate) fn new(string: String) -> Contextual<'f, T> {
+        let mut context = FormContext::from_form_string(&string);
+        let form = match Form::new(string, false) {
+            FormResult::Ok(form) => Some(form),
+            FormResult::Err(_, errors) => {
+                context.errors = errors;
+                None
+            }
+            FormResult::Invalid(_) => {
+                // The form string itself was malformed: no field is to blame.
+                context.errors.push(FormError::new("", None::<String>, FormErrorKind::Invalid));
+                None
+            }
+        };
+
+        Contextual { form: form, context: context }
+    }
+}
+
+impl<'f, T: FromForm<'f> + 'f> Contextual<'f, T> {
+    /// Immutably borrows the parsed value, if parsing succeeded.
+    #[inline]
+    pub fn get(&'f self) -> Option<&T> {
+        self.form.as_ref().map(|form| form.get())
+    }
+
+    /// Returns the context of the submission: the raw submitted values and any
+    /// errors that were emitted while parsing them.
+    #[inline(always)]
+    pub fn context(&self) -> &FormContext {
+        &self.context
+    }
+}
+
+impl<'f, T: FromForm<'f> + 'static> Contextual<'f, T> {
+    /// Consumes `self` and returns the parsed value, if any, and the form
+    /// context. For safety reasons, this method may only be called when the
+    /// parsed value contains no non-`'static` references.
+    #[inline]
+    pub fn into_inner(self) -> (Option<T>, FormContext) {
+        (self.form.map(|form| form.into_inner()), self.context)
+    }
+}
+
+impl<'f, T: FromForm<'f> + Debug + 'f> Debug for Contextual<'f, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Contextual")
+            .field("form", &self.form)
+            .field("context", &self.context)
+            .finish()
+    }
+}
+
+impl<'f, T: FromForm<'f, Error = FormErrors>> FromData for Contextual<'f, T> {
+    /// The error that occurred while reading the form data, if any.
+    type Error = ();
+
+    /// Parses a `Contextual` from incoming form data.
+    ///
+    /// If the content type of the request data is not
+    /// `application/x-www-form-urlencoded`, `Forward`s the request. If reading
+    /// the incoming stream fails, returns a `Failure` with status code
+    /// `InternalServerError`. Otherwise, succeeds, even if the form could not
+    /// be parsed into a `T`.
+    #[inline]
+    fn from_data(request: &Request, data: Data) -> data::Outcome<Self, Self::Error> {
+        super::read_form_string(request, data).map(Contextual::new)
+    }
+}
@@ -0,0 +1,313 @@
+use std::fmt;
+use std::slice;
+use std::ops::Deref;
+
+use error::{ErrorCode, Messages};
+
+/// The kind of error that occurred while parsing a single form field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FormErrorKind {
+    /// A field that is required was not present in the form.
+    Missing,
+    /// The field was present but its value failed to parse.
+    Invalid,
+    /// The field was present but has no counterpart in the target structure.
+    /// Only emitted when parsing strictly.
+    Unexpected,
+    /// The value is shorter than the minimum length, `{min}`.
+    TooShort,
+    /// The value is longer than the maximum length, `{max}`.
+    TooLong,
+    /// The value is smaller than the minimum, `{min}`.
+    TooSmall,
+    /// The value is larger than the maximum, `{max}`.
+    TooLarge,
+    /// The value doesn't match the required pattern, `{pattern}`.
+    Pattern,
+    /// The value is not an email address.
+    Email,
+}
+
+impl FormErrorKind {
+    /// Returns the stable, machine-readable code for this kind of error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::request::FormErrorKind;
+    ///
+    /// assert_eq!(FormErrorKind::Missing.code(), "form.missing");
+    /// ```
+    pub fn code(self) -> &'static str {
+        match self {
+            FormErrorKind::Missing => "form.missing",
+            FormErrorKind::Invalid => "form.invalid",
+            FormErrorKind::Unexpected => "form.unexpected",
+            FormErrorKind::TooShort => "form.too_short",
+            FormErrorKind::TooLong => "form.too_long",
+            FormErrorKind::TooSmall => "form.too_small",
+            FormErrorKind::TooLarge => "form.too_large",
+            FormErrorKind::Pattern => "form.pattern",
+            FormErrorKind::Email => "form.email",
+        }
+    }
+
+    fn english(self) -> &'static str {
+        match self {
+            FormErrorKind::Missing => "'{field}' is required.",
+            FormErrorKind::Invalid => "'{field}' has an invalid value.",
+            FormErrorKind::Unexpected => "'{field}' is not an expected field.",
+            FormErrorKind::TooShort => "'{field}' must be at least {min} characters long.",
+            FormErrorKind::TooLong => "'{field}' must be at most {max} characters long.",
+            FormErrorKind::TooSmall => "'{field}' must be at least {min}.",
+            FormErrorKind::TooLarge => "'{field}' must be at most {max}.",
+            FormErrorKind::Pattern => "'{field}' is not in the expected format.",
+            FormErrorKind::Email => "'{field}' must be an email address.",
+        }
+    }
+}
+
+/// An error associated with a single form field.
+///
+/// Errors of this type are produced by the `FromForm` derive, one for each
+/// field that failed to parse or validate, and are collected into a
+/// [`FormErrors`](/rocket/request/struct.FormErrors.html).
+///
+/// Each error carries a stable code, available via its
+/// [`ErrorCode`](/rocket/error/trait.ErrorCode.html) implementation, and a set
+/// of parameters so that messages can be localized. The parameters always
+/// include `field`, the name of the field, and `value`, the raw submitted
+/// value, when there is one; additional parameters can be attached with
+/// [`with_param`](#method.with_param). The `Display` implementation renders
+/// the default English message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormError {
+    name: String,
+    value: Option<String>,
+    kind: FormErrorKind,
+    params: Vec<(&'static str, String)>,
+}
+
+impl FormError {
+    /// Creates a new error of kind `kind` for the field named `name` with the
+    /// raw submitted value `value`, if any.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::request::{FormError, FormErrorKind};
+    ///
+    /// let error = FormError::new("age", Some("ten"), FormErrorKind::Invalid);
+    /// assert_eq!(error.name(), "age");
+    /// assert_eq!(error.value(), Some("ten"));
+    /// ```
+    pub fn new<N, V>(name: N, value: Option<V>, kind: FormErrorKind) -> FormError
+        where N: Into<String>, V: Into<String>
+    {
+        FormError {
+            name: name.into(),
+            value: value.map(|v| v.into()),
+            kind: kind,
+            params: vec![],
+        }
+    }
+
+    /// Creates a new `Missing` error for the field named `name`.
+    #[inline]
+    pub fn missing<N: Into<String>>(name: N) -> FormError {
+        FormError::new(name, None::<String>, FormErrorKind::Missing)
+    }
+
+    /// Creates a new `Invalid` error for the field named `name` with the raw
+    /// value `value`.
+    #[inline]
+    pub fn invalid<N: Into<String>, V: Into<String>>(name: N, value: V) -> FormError {
+        FormError::new(name, Some(value), FormErrorKind::Invalid)
+    }
+
+    /// Creates a new `Unexpected` error for the field named `name` with the
+    /// raw value `value`.
+    #[inline]
+    pub fn unexpected<N: Into<String>, V: Into<String>>(name: N, value: V) -> FormError {
+        FormError::new(name, Some(value), FormErrorKind::Unexpected)
+    }
+
+    /// Returns the name of the field this error is associated with.
+    #[inline(always)]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the raw value that was submitted for the field, if any.
+    #[inline(always)]
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_ref().map(|v| v.as_str())
+    }
+
+    /// Returns the kind of this error.
+    #[inline(always)]
+    pub fn kind(&self) -> FormErrorKind {
+        self.kind
+    }
+
+    /// Attaches the interpolation parameter `name` with value `value` to this
+    /// error, replacing any existing parameter with the same name.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::request::FormError;
+    ///
+    /// let error = FormError::invalid("age", "200").with_param("max", 150);
+    /// assert_eq!(error.param("max"), Some("150"));
+    /// assert_eq!(error.param("field"), Some("age"));
+    /// ```
+    pub fn with_param<V: ToString>(mut self, name: &'static str, value: V) -> FormError {
+        self.params.retain(|&(k, _)| k != name);
+        self.params.push((name, value.to_string()));
+        self
+    }
+
+    /// Returns the value of the interpolation parameter `name`, if any.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        match name {
+            "field" => Some(self.name()),
+            "value" => self.value(),
+            _ => self.params.iter().find(|&&(k, _)| k == name).map(|&(_, ref v)| v.as_str())
+        }
+    }
+}
+
+impl ErrorCode for FormError {
+    #[inline(always)]
+    fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+
+    fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![("field", self.name.clone())];
+        if let Some(ref value) = self.value {
+            params.push(("value", value.clone()));
+        }
+
+        params.extend(self.params.iter().cloned());
+        params
+    }
+
+    #[inline(always)]
+    fn english(&self) -> &'static str {
+        self.kind.english()
+    }
+}
+
+impl fmt::Display for FormError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Messages::english().render(self))
+    }
+}
+
+/// A collection of [`FormError`](/rocket/request/struct.FormError.html)s.
+///
+/// This is the error type of `FromForm` implementations generated by the
+/// `FromForm` derive. Unlike the first-failure-wins behavior of a single error,
+/// a `FormErrors` contains an error for _every_ field that failed to parse,
+/// allowing all of them to be reported to the user at once.
+///
+/// `FormErrors` dereferences to a slice of `FormError`.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::request::{FormErrors, FormError};
+///
+/// let mut errors = FormErrors::new();
+/// errors.push(FormError::missing("name"));
+/// errors.push(FormError::invalid("age", "ten"));
+///
+/// assert_eq!(errors.len(), 2);
+/// assert_eq!(errors.field("age").count(), 1);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormErrors(Vec<FormError>);
+
+impl FormErrors {
+    /// Creates a new, empty collection of errors.
+    #[inline(always)]
+    pub fn new() -> FormErrors {
+        FormErrors(vec![])
+    }
+
+    /// Adds `error` to the collection.
+    #[inline(always)]
+    pub fn push(&mut self, error: FormError) {
+        self.0.push(error);
+    }
+
+    /// Returns an iterator over the errors associated with the field named
+    /// `name`.
+    #[inline]
+    pub fn field<'a>(&'a self, name: &'a str) -> impl Iterator<Item=&'a FormError> + 'a {
+        self.0.iter().filter(move |e| e.name == name)
+    }
+
+    /// Renders every error into a message using the catalog `messages`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::error::Messages;
+    /// use rocket::request::{FormErrors, FormError};
+    ///
+    /// let mut errors = FormErrors::new();
+    /// errors.push(FormError::missing("name"));
+    ///
+    /// let rendered = errors.render(&Messages::english());
+    /// assert_eq!(rendered, vec!["'name' is required.".to_string()]);
+    /// ```
+    pub fn render(&self, messages: &Messages) -> Vec<String> {
+        self.0.iter().map(|e| messages.render(e)).collect()
+    }
+
+    /// Consumes `self` and returns the inner vector of errors.
+    #[inline(always)]
+    pub fn into_inner(self) -> Vec<FormError> {
+        self.0
+    }
+}
+
+impl Deref for FormErrors {
+    type Target = [FormError];
+
+    #[inline(always)]
+    fn deref(&self) -> &[FormError] {
+        &self.0
+    }
+}
+
+impl<'a> IntoIterator for &'a FormErrors {
+    type Item = &'a FormError;
+    type IntoIter = slice::Iter<'a, FormError>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl From<Vec<FormError>> for FormErrors {
+    #[inline(always)]
+    fn from(errors: Vec<FormError>) -> FormErrors {
+        FormErrors(errors)
+    }
+}
+
+impl fmt::Display for FormErrors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, error) in self.0.iter().enumerate() {
+            if i != 0 { write!(f, "; ")?; }
+            write!(f, "{}", error)?;
+        }
+
+        Ok(())
+    }
+}
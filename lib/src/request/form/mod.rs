@@ -21,12 +21,19 @@ mod from_form;
 mod from_form_value;
 mod form;
 mod lenient;
+mod error;
+mod context;
+pub mod transform;
+pub mod validate;
 
 pub use self::form_items::FormItems;
 pub use self::from_form::FromForm;
 pub use self::from_form_value::FromFormValue;
 pub use self::form::Form;
 pub use self::lenient::LenientForm;
+pub use self::error::{FormError, FormErrors, FormErrorKind};
+pub use self::context::{Contextual, FormContext};
+pub use self::validate::Validate;
 
 use std::cmp;
 use std::io::Read;
@@ -34,38 +41,59 @@ use std::fmt::Debug;
 
 use outcome::Outcome::*;
 use request::Request;
-use data::{self, Data};
+use data::{self, Data, decode_text};
 use self::form::FormResult;
 use http::Status;
 
-fn from_data<'f, T>(request: &Request,
-                    data: Data,
-                    strict: bool
-                   ) -> data::Outcome<Form<'f, T>, Option<String>>
-    where T: FromForm<'f>, T::Error: Debug
-{
+fn read_form_string(request: &Request, data: Data) -> data::Outcome<String, ()> {
     if !request.content_type().map_or(false, |ct| ct.is_form()) {
         warn_!("Form data does not have form content type.");
         return Forward(data);
     }
 
     let limit = request.limits().forms;
-    let mut form_string = String::with_capacity(cmp::min(4096, limit) as usize);
+    let mut bytes = Vec::with_capacity(cmp::min(4096, limit) as usize);
     let mut stream = data.open().take(limit);
-    if let Err(e) = stream.read_to_string(&mut form_string) {
+    if let Err(e) = stream.read_to_end(&mut bytes) {
         error_!("IO Error: {:?}", e);
-        Failure((Status::InternalServerError, None))
-    } else {
-        match Form::new(form_string, strict) {
-            FormResult::Ok(form) => Success(form),
-            FormResult::Invalid(form_string) => {
-                error_!("The request's form string was malformed.");
-                Failure((Status::BadRequest, Some(form_string)))
-            }
-            FormResult::Err(form_string, e) => {
-                error_!("Failed to parse value from form: {:?}", e);
-                Failure((Status::UnprocessableEntity, Some(form_string)))
-            }
+        return Failure((Status::InternalServerError, ()));
+    }
+
+    let form_string = match decode_text(request, bytes) {
+        Ok(form_string) => form_string,
+        Err(e) => {
+            error_!("Couldn't decode the form data: {}.", e);
+            return Failure((e.status(), ()));
+        }
+    };
+
+    match request.reserve_memory(form_string.len() as u64) {
+        Ok(_) => Success(form_string),
+        Err(_) => Failure((Status::PayloadTooLarge, ()))
+    }
+}
+
+fn from_data<'f, T>(request: &Request,
+                    data: Data,
+                    strict: bool
+                   ) -> data::Outcome<Form<'f, T>, Option<String>>
+    where T: FromForm<'f>, T::Error: Debug
+{
+    let form_string = match read_form_string(request, data) {
+        Success(form_string) => form_string,
+        Failure((status, _)) => return Failure((status, None)),
+        Forward(data) => return Forward(data)
+    };
+
+    match Form::new(form_string, strict) {
+        FormResult::Ok(form) => Success(form),
+        FormResult::Invalid(form_string) => {
+            error_!("The request's form string was malformed.");
+            Failure((Status::BadRequest, Some(form_string)))
+        }
+        FormResult::Err(form_string, e) => {
+            error_!("Failed to parse value from form: {:?}", e);
+            Failure((Status::UnprocessableEntity, Some(form_string)))
         }
     }
 }
@@ -0,0 +1,109 @@
+//! Builtin transformers for form field values.
+//!
+//! A transformer is a function of type `fn(String) -> String` that is applied
+//! to the percent-decoded value of a form field before the value is parsed by
+//! its type's `FromFormValue` implementation. Transformers are requested with
+//! the `transform` key of the `form` field attribute when deriving `FromForm`;
+//! they run in the order they are listed:
+//!
+//! ```rust
+//! # #![feature(plugin, decl_macro, custom_derive)]
+//! # #![plugin(rocket_codegen)]
+//! # extern crate rocket;
+//! #[derive(FromForm)]
+//! struct Login {
+//!     #[form(transform = "trim, lowercase")]
+//!     email: String,
+//!     password: String,
+//! }
+//! # fn main() { }
+//! ```
+//!
+//! The names `trim`, `lowercase`, and `uppercase` refer to the functions in
+//! this module. Any other name is resolved as a path to a function in scope,
+//! allowing applications to define their own transformers:
+//!
+//! ```rust
+//! # #![feature(plugin, decl_macro, custom_derive)]
+//! # #![plugin(rocket_codegen)]
+//! # extern crate rocket;
+//! fn digits(value: String) -> String {
+//!     value.chars().filter(|c| c.is_digit(10)).collect()
+//! }
+//!
+//! #[derive(FromForm)]
+//! struct Contact {
+//!     #[form(transform = "trim, digits")]
+//!     phone: String,
+//! }
+//! # fn main() { }
+//! ```
+//!
+//! Because a transformed value is no longer a slice of the original form
+//! string, transformers can only be applied to fields whose type does not
+//! borrow from the form, such as `String` or `usize`, not `&RawStr`.
+
+use http::RawStr;
+
+mod priv_encode_set {
+    /// The set of characters that must be encoded in a form value so that it
+    /// decodes to the original string.
+    define_encode_set! {
+        pub FORM_VALUE_ENCODE_SET = [::percent_encoding::PATH_SEGMENT_ENCODE_SET] | {'+', '&', '='}
+    }
+}
+
+/// Removes leading and trailing whitespace.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::request::transform;
+///
+/// assert_eq!(transform::trim("  hi there ".into()), "hi there");
+/// ```
+pub fn trim(value: String) -> String {
+    let trimmed = value.trim();
+    if trimmed.len() == value.len() {
+        return value;
+    }
+
+    trimmed.to_string()
+}
+
+/// Converts the value to lowercase.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::request::transform;
+///
+/// assert_eq!(transform::lowercase("Bob@Example.COM".into()), "bob@example.com");
+/// ```
+pub fn lowercase(value: String) -> String {
+    value.to_lowercase()
+}
+
+/// Converts the value to uppercase.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::request::transform;
+///
+/// assert_eq!(transform::uppercase("nl91abna".into()), "NL91ABNA");
+/// ```
+pub fn uppercase(value: String) -> String {
+    value.to_uppercase()
+}
+
+/// Decodes `raw`, runs it through each of `transforms` in order, and
+/// re-encodes the result so that it can be parsed by `FromFormValue`. Used by
+/// the `FromForm` derive.
+#[doc(hidden)]
+pub fn apply(raw: &RawStr, transforms: &[fn(String) -> String]) -> String {
+    let decoded = raw.url_decode().unwrap_or_else(|_| raw.as_str().to_string());
+    let value = transforms.iter().fold(decoded, |value, transform| transform(value));
+    ::percent_encoding::utf8_percent_encode(&value, priv_encode_set::FORM_VALUE_ENCODE_SET)
+        .to_string()
+}
@@ -0,0 +1,334 @@
+//! Declarative validation of form fields and other structures.
+//!
+//! Fields of structures deriving `FromForm` may be annotated with one or more
+//! `validate` attributes. Each listed validator is run against the parsed
+//! value of the field; every failure is recorded as a
+//! [`FormError`](/rocket/request/struct.FormError.html) alongside any parse
+//! errors, so that a `Form` guard fails with a single `422 Unprocessable
+//! Entity` listing every problem with the submission:
+//!
+//! ```rust
+//! # #![feature(plugin, decl_macro, custom_derive)]
+//! # #![plugin(rocket_codegen)]
+//! # extern crate rocket;
+//! #[derive(FromForm)]
+//! struct Signup {
+//!     #[validate(length(min = 3, max = 32))]
+//!     username: String,
+//!     #[validate(email)]
+//!     email: String,
+//!     #[validate(range(min = 13, max = 130))]
+//!     age: u8,
+//! }
+//! # fn main() { }
+//! ```
+//!
+//! The following validators are supported:
+//!
+//!   * **length(min = N, max = N)**: the number of characters in a string, or
+//!     elements in a vector, is within the given inclusive bounds. Either
+//!     bound may be omitted.
+//!   * **range(min = N, max = N)**: a number is within the given inclusive
+//!     bounds. Either bound may be omitted.
+//!   * **regex = "PATTERN"**: a string matches the regular expression
+//!     `PATTERN`. Requires the `regex` feature of `rocket`.
+//!   * **email**: a string looks like an email address.
+//!
+//! Validators of a field whose value is `None` are not run, so optional fields
+//! are only validated when present.
+//!
+//! The same attributes can be used on any structure by deriving
+//! [`Validate`](/rocket/request/trait.Validate.html), which is useful for
+//! validating data deserialized by other data guards, such as JSON.
+
+use std::fmt::Display;
+
+use http::RawStr;
+use request::form::{FormError, FormErrors, FormErrorKind};
+
+/// Trait implemented by types whose values can be validated.
+///
+/// This trait can be derived, using the same `validate` field attributes as
+/// the `FromForm` derive. See the [module level documentation](index.html) for
+/// details.
+///
+/// # Example
+///
+/// ```rust
+/// # #![feature(plugin, decl_macro, custom_derive)]
+/// # #![plugin(rocket_codegen)]
+/// # extern crate rocket;
+/// use rocket::request::Validate;
+///
+/// #[derive(Validate)]
+/// struct Comment {
+///     #[validate(length(min = 1, max = 280))]
+///     body: String,
+/// }
+///
+/// # fn main() {
+/// let comment = Comment { body: "".into() };
+/// assert_eq!(comment.validate().unwrap_err().len(), 1);
+/// # }
+/// ```
+pub trait Validate {
+    /// Validates `self`, returning every validation failure on error.
+    fn validate(&self) -> Result<(), FormErrors>;
+}
+
+/// Types with a length that can be validated by the `length` validator.
+///
+/// The length of a string is its number of characters. A value of `None`
+/// indicates that there is nothing to validate.
+pub trait Length {
+    /// Returns the length of `self`, or `None` if `self` is absent.
+    fn length(&self) -> Option<usize>;
+}
+
+/// Numeric types that can be validated by the `range` validator.
+pub trait Number {
+    /// Returns `self` as an `f64`, or `None` if `self` is absent.
+    fn number(&self) -> Option<f64>;
+}
+
+/// Textual types that can be validated by the `regex` and `email` validators.
+pub trait Text {
+    /// Returns `self` as a string, or `None` if `self` is absent.
+    fn text(&self) -> Option<&str>;
+}
+
+impl Length for str {
+    fn length(&self) -> Option<usize> { Some(self.chars().count()) }
+}
+
+impl Length for String {
+    fn length(&self) -> Option<usize> { self.as_str().length() }
+}
+
+impl Length for RawStr {
+    fn length(&self) -> Option<usize> { self.as_str().length() }
+}
+
+impl<T> Length for Vec<T> {
+    fn length(&self) -> Option<usize> { Some(self.len()) }
+}
+
+impl<T> Length for [T] {
+    fn length(&self) -> Option<usize> { Some(self.len()) }
+}
+
+impl<'a, T: Length + ?Sized> Length for &'a T {
+    fn length(&self) -> Option<usize> { (**self).length() }
+}
+
+impl<T: Length> Length for Option<T> {
+    fn length(&self) -> Option<usize> { self.as_ref().and_then(|v| v.length()) }
+}
+
+macro_rules! impl_number {
+    ($($T:ty),*) => ($(
+        impl Number for $T {
+            fn number(&self) -> Option<f64> { Some(*self as f64) }
+        }
+    )*)
+}
+
+impl_number!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64);
+
+impl<'a, T: Number + ?Sized> Number for &'a T {
+    fn number(&self) -> Option<f64> { (**self).number() }
+}
+
+impl<T: Number> Number for Option<T> {
+    fn number(&self) -> Option<f64> { self.as_ref().and_then(|v| v.number()) }
+}
+
+impl Text for str {
+    fn text(&self) -> Option<&str> { Some(self) }
+}
+
+impl Text for String {
+    fn text(&self) -> Option<&str> { Some(self.as_str()) }
+}
+
+impl Text for RawStr {
+    fn text(&self) -> Option<&str> { Some(self.as_str()) }
+}
+
+impl<'a, T: Text + ?Sized> Text for &'a T {
+    fn text(&self) -> Option<&str> { (**self).text() }
+}
+
+impl<T: Text> Text for Option<T> {
+    fn text(&self) -> Option<&str> { self.as_ref().and_then(|v| v.text()) }
+}
+
+fn error<V: Display>(name: &str, value: V, kind: FormErrorKind) -> FormError {
+    FormError::new(name, Some(value.to_string()), kind)
+}
+
+/// Validates that the length of `value` is within `[min, max]`.
+///
+/// On failure, the error is of kind `TooShort`, with the parameter `min`, or
+/// `TooLong`, with the parameter `max`. Both carry the actual length in the
+/// parameter `length`.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::request::validate;
+///
+/// assert!(validate::length("name", "Bob", Some(3), None).is_ok());
+/// assert!(validate::length("name", "Al", Some(3), Some(10)).is_err());
+/// assert!(validate::length("name", &None::<String>, Some(3), None).is_ok());
+/// ```
+pub fn length<T>(name: &str, value: &T, min: Option<usize>, max: Option<usize>)
+    -> Result<(), FormError> where T: Length + ?Sized
+{
+    let len = match value.length() {
+        Some(len) => len,
+        None => return Ok(())
+    };
+
+    if let Some(min) = min {
+        if len < min {
+            let error = FormError::new(name, None::<String>, FormErrorKind::TooShort);
+            return Err(error.with_param("length", len).with_param("min", min));
+        }
+    }
+
+    if let Some(max) = max {
+        if len > max {
+            let error = FormError::new(name, None::<String>, FormErrorKind::TooLong);
+            return Err(error.with_param("length", len).with_param("max", max));
+        }
+    }
+
+    Ok(())
+}
+
+/// Validates that `value` is within `[min, max]`.
+///
+/// On failure, the error is of kind `TooSmall`, with the parameter `min`, or
+/// `TooLarge`, with the parameter `max`.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::request::validate;
+///
+/// assert!(validate::range("age", &30u8, Some(13.0), Some(130.0)).is_ok());
+/// assert!(validate::range("age", &7u8, Some(13.0), None).is_err());
+/// ```
+pub fn range<T>(name: &str, value: &T, min: Option<f64>, max: Option<f64>)
+    -> Result<(), FormError> where T: Number + ?Sized
+{
+    let number = match value.number() {
+        Some(number) => number,
+        None => return Ok(())
+    };
+
+    if let Some(min) = min {
+        if number < min {
+            return Err(error(name, number, FormErrorKind::TooSmall).with_param("min", min));
+        }
+    }
+
+    if let Some(max) = max {
+        if number > max {
+            return Err(error(name, number, FormErrorKind::TooLarge).with_param("max", max));
+        }
+    }
+
+    Ok(())
+}
+
+/// Validates that `value` looks like an email address: a non-empty local part
+/// and a domain containing a `.`, separated by a single `@`, without
+/// whitespace.
+///
+/// On failure, the error is of kind `Email`.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::request::validate;
+///
+/// assert!(validate::email("email", "bob@example.com").is_ok());
+/// assert!(validate::email("email", "bob@localhost").is_err());
+/// assert!(validate::email("email", "bob").is_err());
+/// ```
+pub fn email<T: Text + ?Sized>(name: &str, value: &T) -> Result<(), FormError> {
+    let text = match value.text() {
+        Some(text) => text,
+        None => return Ok(())
+    };
+
+    let valid = {
+        let mut parts = text.split('@');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(local), Some(domain), None) => {
+                !local.is_empty()
+                    && !text.chars().any(|c| c.is_whitespace())
+                    && domain.split('.').count() > 1
+                    && domain.split('.').all(|label| !label.is_empty())
+            }
+            _ => false
+        }
+    };
+
+    if !valid {
+        return Err(error(name, text, FormErrorKind::Email));
+    }
+
+    Ok(())
+}
+
+/// Validates that `value` matches the regular expression `pattern`.
+///
+/// Compiled expressions are cached per thread. An invalid `pattern` is logged
+/// and causes validation to fail. On failure, the error is of kind `Pattern`,
+/// with the parameter `pattern`.
+///
+/// This function is only available when the `regex` feature is enabled.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::request::validate;
+///
+/// assert!(validate::regex("zip", "02139", "^[0-9]{5}$").is_ok());
+/// assert!(validate::regex("zip", "0213", "^[0-9]{5}$").is_err());
+/// ```
+#[cfg(feature = "regex")]
+pub fn regex<T>(name: &str, value: &T, pattern: &'static str) -> Result<(), FormError>
+    where T: Text + ?Sized
+{
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use regex::Regex;
+
+    thread_local! {
+        static PATTERNS: RefCell<HashMap<&'static str, Option<Regex>>> = RefCell::new(HashMap::new());
+    }
+
+    let text = match value.text() {
+        Some(text) => text,
+        None => return Ok(())
+    };
+
+    let matched = PATTERNS.with(|patterns| {
+        let mut patterns = patterns.borrow_mut();
+        let regex = patterns.entry(pattern).or_insert_with(|| {
+            Regex::new(pattern).map_err(|e| error_!("Invalid validation regex: {}", e)).ok()
+        });
+
+        regex.as_ref().map_or(false, |regex| regex.is_match(text))
+    });
+
+    if !matched {
+        return Err(error(name, text, FormErrorKind::Pattern).with_param("pattern", pattern));
+    }
+
+    Ok(())
+}
@@ -237,7 +237,7 @@ impl<'a, 'r> FromRequest<'a, 'r> for &'a Uri<'a> {
     }
 }
 
-impl<'a, 'r> FromRequest<'a, 'r> for &'r Route {
+impl<'a, 'r> FromRequest<'a, 'r> for &'a Route {
     type Error = ();
 
     fn from_request(request: &'a Request<'r>) -> Outcome<Self, Self::Error> {
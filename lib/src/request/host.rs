@@ -0,0 +1,202 @@
+use std::fmt;
+
+use outcome::Outcome::*;
+use request::{Request, FromRequest, Outcome};
+use http::Status;
+use http::uncased::uncased_eq;
+
+/// The validated host a request was sent to.
+///
+/// The host is taken from the request's `Host` header. For requests with an
+/// absolute-form target, as in `GET http://example.com/ HTTP/1.1`, Rocket
+/// replaces the `Host` header with the authority of the target.
+///
+/// # Request Guard
+///
+/// As a request guard, `Host` fails with `400 Bad Request` if the request has
+/// no `Host` header, more than one, or a malformed one. If the `allowed_hosts`
+/// configuration parameter is set, Rocket rejects requests for any other host
+/// before routing with `421 Misdirected Request`, so a `Host` guard only ever
+/// yields an allowed host.
+///
+/// # Allowlist Patterns
+///
+/// Each entry in `allowed_hosts` is matched case-insensitively against the
+/// host. An entry is one of:
+///
+///   * `example.com`: the host `example.com` on any port
+///   * `example.com:8000`: the host `example.com` on port `8000`
+///   * `*.example.com`: any subdomain of `example.com`, but not `example.com`
+///
+/// # Example
+///
+/// ```rust
+/// # #![feature(plugin, decl_macro)]
+/// # #![plugin(rocket_codegen)]
+/// # extern crate rocket;
+/// use rocket::request::Host;
+///
+/// #[get("/")]
+/// fn index(host: Host) -> String {
+///     format!("Welcome to {}!", host.name())
+/// }
+/// # fn main() {  }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Host<'r> {
+    name: &'r str,
+    port: Option<u16>,
+}
+
+impl<'r> Host<'r> {
+    /// Parses a `Host` header value of the form `name` or `name:port`, where
+    /// `name` is a DNS name, an IPv4 address, or a bracketed IPv6 address.
+    /// Returns `None` if `value` is malformed, including if it is an IPv6
+    /// address without brackets.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::request::Host;
+    ///
+    /// let host = Host::parse("example.com:8000").unwrap();
+    /// assert_eq!(host.name(), "example.com");
+    /// assert_eq!(host.port(), Some(8000));
+    ///
+    /// let host = Host::parse("[::1]").unwrap();
+    /// assert_eq!(host.name(), "[::1]");
+    /// assert_eq!(host.port(), None);
+    ///
+    /// assert!(Host::parse("example.com:http").is_none());
+    /// assert!(Host::parse("exa mple.com").is_none());
+    /// assert!(Host::parse("::1").is_none());
+    /// ```
+    pub fn parse(value: &'r str) -> Option<Host<'r>> {
+        // Without brackets, an IPv6 address can't be told apart from a port.
+        if !value.starts_with('[') && value.matches(':').count() > 1 {
+            return None;
+        }
+
+        let (name, port) = match value.rfind(':') {
+            Some(i) if !value[i..].contains(']') => (&value[..i], Some(&value[(i + 1)..])),
+            _ => (value, None)
+        };
+
+        let valid_name = if name.starts_with('[') && name.ends_with(']') && name.len() > 2 {
+            let is_ipv6_char = |c: char| c.is_digit(16) || c == ':' || c == '.';
+            name[1..(name.len() - 1)].chars().all(is_ipv6_char)
+        } else {
+            let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '.';
+            !name.is_empty() && name.chars().all(is_name_char)
+        };
+
+        if !valid_name {
+            return None;
+        }
+
+        let port = match port {
+            Some(port) => Some(port.parse::<u16>().ok()?),
+            None => None
+        };
+
+        Some(Host { name: name, port: port })
+    }
+
+    /// Returns the name of the host, without the port.
+    #[inline(always)]
+    pub fn name(&self) -> &'r str {
+        self.name
+    }
+
+    /// Returns the port of the host, if one was given.
+    #[inline(always)]
+    pub fn port(&self) -> Option<u16> {
+        self.port
+    }
+
+    /// Returns `true` if `self` matches the allowlist entry `pattern`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::request::Host;
+    ///
+    /// let host = Host::parse("api.Example.com:8000").unwrap();
+    /// assert!(host.matches("api.example.com"));
+    /// assert!(host.matches("api.example.com:8000"));
+    /// assert!(host.matches("*.example.com"));
+    /// assert!(!host.matches("example.com"));
+    /// assert!(!host.matches("api.example.com:80"));
+    /// ```
+    pub fn matches(&self, pattern: &str) -> bool {
+        let (wildcard, pattern) = match pattern.starts_with("*.") {
+            true => (true, &pattern[1..]),
+            false => (false, pattern)
+        };
+
+        let pattern = match Host::parse(pattern.trim_left_matches('.')) {
+            Some(pattern) => pattern,
+            None => return false
+        };
+
+        if pattern.port.map_or(false, |port| Some(port) != self.port) {
+            return false;
+        }
+
+        if !wildcard {
+            return uncased_eq(self.name, pattern.name);
+        }
+
+        let suffix_len = pattern.name.len() + 1;
+        self.name.len() > suffix_len
+            && self.name.get((self.name.len() - suffix_len)..).map_or(false, |suffix| {
+                suffix.starts_with('.') && uncased_eq(&suffix[1..], pattern.name)
+            })
+    }
+
+    /// Returns `true` if `pattern` is a valid allowlist entry.
+    pub(crate) fn is_valid_pattern(pattern: &str) -> bool {
+        let host = match pattern.starts_with("*.") {
+            true => &pattern[2..],
+            false => pattern
+        };
+
+        Host::parse(host).is_some()
+    }
+
+    /// Parses the host of `request` and validates it against the allowlist
+    /// `allowed`. An empty allowlist allows any well-formed host.
+    pub(crate) fn check(request: &'r Request, allowed: &[String]) -> Result<Host<'r>, Status> {
+        let mut values = request.headers().get("Host");
+        let host = match (values.next(), values.next()) {
+            (Some(value), None) => Host::parse(value.trim()).ok_or(Status::BadRequest)?,
+            _ => return Err(Status::BadRequest)
+        };
+
+        if !allowed.is_empty() && !allowed.iter().any(|pattern| host.matches(pattern)) {
+            return Err(Status::MisdirectedRequest);
+        }
+
+        Ok(host)
+    }
+}
+
+impl<'r> fmt::Display for Host<'r> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.port {
+            Some(port) => write!(f, "{}:{}", self.name, port),
+            None => write!(f, "{}", self.name)
+        }
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for Host<'a> {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> Outcome<Self, Self::Error> {
+        match Host::check(request, &request.config().allowed_hosts) {
+            Ok(host) => Success(host),
+            Err(status) => Failure((status, ()))
+        }
+    }
+}
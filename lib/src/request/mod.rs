@@ -5,15 +5,29 @@ mod param;
 mod form;
 mod from_request;
 mod state;
+mod precondition;
+mod segment;
+pub(crate) mod negotiation;
+mod host;
+mod cancel;
+mod catch_context;
 
 #[cfg(test)]
 mod tests;
 
-pub use self::request::Request;
+pub use self::request::{Request, MemoryLimitExceeded};
 pub use self::from_request::{FromRequest, Outcome};
 pub use self::param::{FromParam, FromSegments};
+pub use self::segment::{Matrix, WithExtension};
 pub use self::form::{Form, LenientForm, FromForm, FromFormValue, FormItems};
+pub use self::form::{FormError, FormErrors, FormErrorKind, Contextual, FormContext};
+pub use self::form::{transform, validate, Validate};
 pub use self::state::State;
+pub use self::precondition::{Precondition, PreconditionError};
+pub use self::host::Host;
+pub use self::cancel::CancelToken;
+pub use self::catch_context::{CatchContext, GuardFailure, GuardKind};
+#[doc(hidden)] pub use self::catch_context::{Describe, DescribeDebug, DescribeOther};
 
 #[doc(inline)]
 pub use response::flash::FlashMessage;
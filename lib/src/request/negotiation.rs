@@ -0,0 +1,120 @@
+//! Quality value (`q`) logic for content and encoding negotiation.
+
+use http::{Accept, MediaType};
+
+// Whether the media range `range` from an Accept header includes `media_type`.
+fn includes(range: &MediaType, media_type: &MediaType) -> bool {
+    (range.top() == "*" || range.top() == media_type.top())
+        && (range.sub() == "*" || range.sub() == media_type.sub())
+}
+
+/// Returns the quality the client assigned to `media_type` in `accept`, a value
+/// in `[0, 1]`. The quality is that of the most specific media range that
+/// includes `media_type`, or `0` if no range does. When there is no Accept
+/// header, every media type has a quality of `1`.
+pub(crate) fn media_quality(accept: Option<&Accept>, media_type: &MediaType) -> f32 {
+    let accept = match accept {
+        Some(accept) => accept,
+        None => return 1.0
+    };
+
+    accept.iter()
+        .filter(|q_type| includes(q_type.media_type(), media_type))
+        .max_by_key(|q_type| q_type.media_type().specificity())
+        .map_or(0.0, |q_type| q_type.weight_or(1.0))
+}
+
+/// Returns the quality the client assigned to the content coding `encoding` in
+/// the values of its Accept-Encoding headers, `values`, a value in `[0, 1]`.
+///
+/// As in RFC 7231, when there is no Accept-Encoding header, every coding is
+/// acceptable. Otherwise, the quality is that of the entry naming `encoding`,
+/// or of the `*` entry if there is none. The `identity` coding is acceptable
+/// unless it is explicitly excluded.
+pub(crate) fn encoding_quality<'a, I>(values: I, encoding: &str) -> f32
+    where I: Iterator<Item = &'a str>
+{
+    let (mut present, mut exact, mut wildcard) = (false, None, None);
+    for entry in values.flat_map(|value| value.split(',')) {
+        let mut parts = entry.split(';').map(|part| part.trim());
+        let coding = match parts.next() {
+            Some(coding) if !coding.is_empty() => coding,
+            _ => continue
+        };
+
+        present = true;
+        let quality = parts
+            .filter_map(|param| {
+                let mut kv = param.splitn(2, '=').map(|s| s.trim());
+                match (kv.next(), kv.next()) {
+                    (Some(k), Some(v)) if k.eq_ignore_ascii_case("q") => Some(v),
+                    _ => None
+                }
+            })
+            .next()
+            .map_or(Some(1.0), |q| q.parse::<f32>().ok());
+
+        let quality = match quality {
+            Some(q) if q >= 0.0 && q <= 1.0 => q,
+            _ => continue
+        };
+
+        if coding.eq_ignore_ascii_case(encoding) {
+            exact = exact.or(Some(quality));
+        } else if coding == "*" {
+            wildcard = wildcard.or(Some(quality));
+        }
+    }
+
+    if !present {
+        return 1.0;
+    }
+
+    match (exact, wildcard) {
+        (Some(q), _) | (None, Some(q)) => q,
+        (None, None) if encoding.eq_ignore_ascii_case("identity") => 1.0,
+        (None, None) => 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn q_media(accept: &str, media_type: MediaType) -> f32 {
+        let accept: Accept = accept.parse().unwrap();
+        media_quality(Some(&accept), &media_type)
+    }
+
+    fn q_encoding(values: &[&str], encoding: &str) -> f32 {
+        encoding_quality(values.iter().cloned(), encoding)
+    }
+
+    #[test]
+    fn test_media_quality() {
+        assert_eq!(media_quality(None, &MediaType::JSON), 1.0);
+        assert_eq!(q_media("application/json", MediaType::JSON), 1.0);
+        assert_eq!(q_media("application/json", MediaType::HTML), 0.0);
+        assert_eq!(q_media("text/*;q=0.5", MediaType::HTML), 0.5);
+        assert_eq!(q_media("*/*;q=0.1, text/*;q=0.5", MediaType::JSON), 0.1);
+        assert_eq!(q_media("*/*;q=0.1, text/*;q=0.5, text/html", MediaType::HTML), 1.0);
+        assert_eq!(q_media("text/*, text/html;q=0", MediaType::HTML), 0.0);
+        assert_eq!(q_media("text/*, text/html;q=0", MediaType::Plain), 1.0);
+    }
+
+    #[test]
+    fn test_encoding_quality() {
+        assert_eq!(q_encoding(&[], "gzip"), 1.0);
+        assert_eq!(q_encoding(&["gzip"], "gzip"), 1.0);
+        assert_eq!(q_encoding(&["GZIP;q=0.5"], "gzip"), 0.5);
+        assert_eq!(q_encoding(&["deflate", "gzip;q=0.3"], "gzip"), 0.3);
+        assert_eq!(q_encoding(&["deflate, gzip ; q=0.3"], "gzip"), 0.3);
+        assert_eq!(q_encoding(&["deflate"], "gzip"), 0.0);
+        assert_eq!(q_encoding(&["*;q=0.2"], "br"), 0.2);
+        assert_eq!(q_encoding(&["*;q=0.2, br;q=0"], "br"), 0.0);
+        assert_eq!(q_encoding(&["gzip;q=2"], "gzip"), 0.0);
+        assert_eq!(q_encoding(&["gzip"], "identity"), 1.0);
+        assert_eq!(q_encoding(&["identity;q=0"], "identity"), 0.0);
+        assert_eq!(q_encoding(&["*;q=0"], "identity"), 0.0);
+    }
+}
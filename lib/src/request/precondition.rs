@@ -0,0 +1,252 @@
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use outcome::Outcome::*;
+use request::{Request, FromRequest, Outcome};
+use response::{self, Responder};
+use http::Status;
+use http::hyper::header::{EntityTag, HttpDate};
+
+/// The value of an `If-Match` or `If-None-Match` header.
+#[derive(Debug, Clone, PartialEq)]
+enum TagList {
+    /// `*`: matches any current representation.
+    Any,
+    /// A list of entity tags.
+    Tags(Vec<EntityTag>),
+}
+
+impl TagList {
+    fn parse<'h, I: Iterator<Item = &'h str>>(values: I) -> Option<TagList> {
+        let mut tags = vec![];
+        let mut present = false;
+        for value in values {
+            present = true;
+            for item in value.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+                if item == "*" {
+                    return Some(TagList::Any);
+                }
+
+                // Malformed tags can never match, so they're simply dropped.
+                if let Ok(tag) = EntityTag::from_str(item) {
+                    tags.push(tag);
+                }
+            }
+        }
+
+        if present { Some(TagList::Tags(tags)) } else { None }
+    }
+
+    /// Whether any tag matches `current` using the strong (`If-Match`) or weak
+    /// (`If-None-Match`) comparison function.
+    fn matches(&self, current: Option<&EntityTag>, strong: bool) -> bool {
+        match (self, current) {
+            (&TagList::Any, current) => current.is_some(),
+            (&TagList::Tags(_), None) => false,
+            (&TagList::Tags(ref tags), Some(current)) => tags.iter().any(|tag| {
+                if strong { tag.strong_eq(current) } else { tag.weak_eq(current) }
+            })
+        }
+    }
+}
+
+/// The error returned when evaluating a [`Precondition`] fails.
+///
+/// `PreconditionError` implements [`Responder`] by failing with the
+/// corresponding status, so it can be returned directly, or propagated with
+/// `?`, from handlers that return a `Result`. The status is then handled by
+/// the matching error catcher.
+///
+/// [`Precondition`]: /rocket/request/struct.Precondition.html
+/// [`Responder`]: /rocket/response/trait.Responder.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreconditionError {
+    /// The request's preconditions didn't hold: `412 Precondition Failed`.
+    Failed,
+    /// The request wasn't conditional but must be: `428 Precondition
+    /// Required`.
+    Required,
+}
+
+impl PreconditionError {
+    /// Returns the status that corresponds to this error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::request::PreconditionError;
+    /// use rocket::http::Status;
+    ///
+    /// assert_eq!(PreconditionError::Failed.status(), Status::PreconditionFailed);
+    /// assert_eq!(PreconditionError::Required.status(), Status::PreconditionRequired);
+    /// ```
+    pub fn status(&self) -> Status {
+        match *self {
+            PreconditionError::Failed => Status::PreconditionFailed,
+            PreconditionError::Required => Status::PreconditionRequired,
+        }
+    }
+}
+
+impl Responder<'static> for PreconditionError {
+    fn respond_to(self, _: &Request) -> response::Result<'static> {
+        Err(self.status())
+    }
+}
+
+/// A request guard for the conditional request headers used to implement
+/// optimistic concurrency control: `If-Match`, `If-None-Match`, and
+/// `If-Unmodified-Since`.
+///
+/// A `Precondition` guard always succeeds. Once the handler knows the current
+/// entity tag and modification time of the target resource, it calls
+/// [`evaluate`] to determine whether the request may proceed. Handlers that
+/// refuse unconditional writes additionally call [`require`]. Both return a
+/// [`PreconditionError`], which responds with `412 Precondition Failed` or
+/// `428 Precondition Required`, respectively.
+///
+/// Entity tags are passed to and compared by `evaluate` without their
+/// surrounding quotes; the current tag is always treated as a strong tag.
+///
+/// # Example
+///
+/// ```rust
+/// # #![feature(plugin, decl_macro)]
+/// # #![plugin(rocket_codegen)]
+/// # extern crate rocket;
+/// use rocket::request::{Precondition, PreconditionError};
+///
+/// # fn current_version(id: usize) -> String { id.to_string() }
+/// # fn store(id: usize, doc: String) -> String { id.to_string() }
+/// #[put("/doc/<id>", data = "<doc>")]
+/// fn update(id: usize, doc: String, pre: Precondition) -> Result<String, PreconditionError> {
+///     pre.require()?;
+///     pre.evaluate(Some(&current_version(id)), None)?;
+///     Ok(store(id, doc))
+/// }
+/// # fn main() { }
+/// ```
+///
+/// [`evaluate`]: /rocket/request/struct.Precondition.html#method.evaluate
+/// [`require`]: /rocket/request/struct.Precondition.html#method.require
+/// [`PreconditionError`]: /rocket/request/enum.PreconditionError.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct Precondition {
+    if_match: Option<TagList>,
+    if_none_match: Option<TagList>,
+    if_unmodified_since: Option<u64>,
+}
+
+impl Precondition {
+    /// Parses the conditional headers of `request`. Invalid dates are ignored,
+    /// as RFC 7232 requires.
+    pub(crate) fn from_headers(request: &Request) -> Precondition {
+        let headers = request.headers();
+        let unmodified_since = headers.get_one("If-Unmodified-Since")
+            .and_then(|value| HttpDate::from_str(value).ok())
+            .map(|date| date.0.to_timespec().sec)
+            .and_then(|secs| if secs >= 0 { Some(secs as u64) } else { None });
+
+        Precondition {
+            if_match: TagList::parse(headers.get("If-Match")),
+            if_none_match: TagList::parse(headers.get("If-None-Match")),
+            if_unmodified_since: unmodified_since,
+        }
+    }
+
+    /// Returns `true` if the request carries at least one of the `If-Match`,
+    /// `If-None-Match`, or `If-Unmodified-Since` headers.
+    #[inline]
+    pub fn is_conditional(&self) -> bool {
+        self.if_match.is_some()
+            || self.if_none_match.is_some()
+            || self.if_unmodified_since.is_some()
+    }
+
+    /// Returns `Err(PreconditionError::Required)` if the request is not
+    /// conditional and `Ok(())` otherwise. Use this in write handlers to
+    /// reject "blind" updates that may overwrite concurrent changes.
+    pub fn require(&self) -> Result<(), PreconditionError> {
+        match self.is_conditional() {
+            true => Ok(()),
+            false => Err(PreconditionError::Required)
+        }
+    }
+
+    /// Evaluates the request's preconditions against the target resource's
+    /// current entity tag, `etag`, and last modification time,
+    /// `last_modified`. A `None` entity tag means that the resource does not
+    /// currently exist.
+    ///
+    /// The preconditions are evaluated in the order mandated by RFC 7232:
+    /// `If-Match` (strong comparison) and, only in its absence,
+    /// `If-Unmodified-Since`, followed by `If-None-Match` (weak comparison).
+    /// Returns `Err(PreconditionError::Failed)` if any of them is false.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::request::{Precondition, PreconditionError};
+    ///
+    /// fn check(pre: &Precondition) -> Result<(), PreconditionError> {
+    ///     pre.evaluate(Some("v7"), None)
+    /// }
+    /// ```
+    pub fn evaluate(
+        &self,
+        etag: Option<&str>,
+        last_modified: Option<SystemTime>
+    ) -> Result<(), PreconditionError> {
+        let current = etag.map(|tag| EntityTag::strong(tag.to_string()));
+        if let Some(ref if_match) = self.if_match {
+            if !if_match.matches(current.as_ref(), true) {
+                return Err(PreconditionError::Failed);
+            }
+        } else if let (Some(since), Some(modified)) = (self.if_unmodified_since, last_modified) {
+            let modified = modified.duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0);
+
+            if modified > since {
+                return Err(PreconditionError::Failed);
+            }
+        }
+
+        if let Some(ref if_none_match) = self.if_none_match {
+            if if_none_match.matches(current.as_ref(), false) {
+                return Err(PreconditionError::Failed);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Evaluates the request's preconditions for a `GET` or `HEAD` request of
+    /// a resource whose current entity tag is `etag`. Returns
+    /// `Err(Status::PreconditionFailed)` if `If-Match` doesn't hold and
+    /// `Err(Status::NotModified)` if `If-None-Match` doesn't.
+    pub(crate) fn evaluate_read(&self, etag: &str) -> Result<(), Status> {
+        let current = EntityTag::strong(etag.to_string());
+        if let Some(ref if_match) = self.if_match {
+            if !if_match.matches(Some(&current), true) {
+                return Err(Status::PreconditionFailed);
+            }
+        }
+
+        if let Some(ref if_none_match) = self.if_none_match {
+            if if_none_match.matches(Some(&current), false) {
+                return Err(Status::NotModified);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for Precondition {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> Outcome<Self, Self::Error> {
+        Success(Precondition::from_headers(request))
+    }
+}
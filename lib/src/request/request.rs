@@ -1,31 +1,89 @@
 use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 use std::net::{IpAddr, SocketAddr};
 use std::fmt;
 use std::str;
+use std::time::Instant;
+use std::sync::Arc;
 
 use yansi::Paint;
 use state::{Container, Storage};
 
 use super::{FromParam, FromSegments, FromRequest, Outcome};
+use super::negotiation;
+use super::{CatchContext, GuardFailure};
 
 use rocket::Rocket;
 use router::Route;
-use config::{Config, Limits};
+use features::Features;
+use metrics::Timings;
+use versioning::NegotiatedVersion;
+use config::{Config, Limits, CookieOverflow};
 use http::uri::{Uri, Segments};
 use error::Error;
 use http::{Method, Header, HeaderMap, Cookies, CookieJar};
 use http::{RawStr, ContentType, Accept, MediaType};
 use http::hyper;
 
+#[cfg(feature = "tls")] use http::Certificate;
+#[cfg(feature = "tls")] use tls::{PeerCertificates, TlsInfo};
+
+/// The route a request was last routed to.
+#[derive(Clone, Copy)]
+enum Routed<'r> {
+    Mounted(&'r Route),
+    /// The `n`th dynamic route the request was routed to.
+    Dynamic(usize),
+}
+
+/// A dynamic route a request was routed to, followed by the next one, if any.
+/// Routes are only ever appended, so each lives as long as the request does,
+/// even if it's removed from the dynamic routes in the meantime.
+#[derive(Clone)]
+struct DynamicRoute {
+    route: Arc<Route>,
+    next: Storage<Box<DynamicRoute>>,
+}
+
 #[derive(Clone)]
 struct RequestState<'r> {
     config: &'r Config,
     managed: &'r Container,
+    features: &'r Features,
     params: RefCell<Vec<(usize, usize)>>,
-    route: Cell<Option<&'r Route>>,
+    route: Cell<Option<Routed<'r>>>,
+    dynamic_routes: Storage<Box<DynamicRoute>>,
     cookies: RefCell<CookieJar>,
+    cookie_order: RefCell<Vec<String>>,
+    cookies_overflowed: bool,
+    timings: Cell<Timings>,
+    guards_complete: Cell<Option<Instant>>,
+    autohandled_head: Cell<bool>,
+    deadline: Cell<Option<Instant>>,
+    guard_failure: RefCell<Option<GuardFailure>>,
+    memory: Cell<u64>,
+    memory_exceeded: Cell<bool>,
     accept: Storage<Option<Accept>>,
     content_type: Storage<Option<ContentType>>,
+    cache: Rc<Container>,
+}
+
+/// The error returned by [`Request::reserve_memory()`] when a request would
+/// exceed the configured `memory` limit.
+///
+/// [`Request::reserve_memory()`]: /rocket/struct.Request.html#method.reserve_memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryLimitExceeded {
+    /// The configured limit, in bytes.
+    pub limit: u64,
+    /// The total number of bytes the request would have used, in bytes.
+    pub requested: u64,
+}
+
+impl fmt::Display for MemoryLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "request needs {} bytes of memory, limit is {}", self.requested, self.limit)
+    }
 }
 
 /// The type of an incoming web request.
@@ -41,6 +99,13 @@ pub struct Request<'r> {
     uri: Uri<'r>,
     headers: HeaderMap<'r>,
     remote: Option<SocketAddr>,
+    scheme: Option<String>,
+    #[cfg(feature = "tls")]
+    certificates: Option<Arc<PeerCertificates>>,
+    #[cfg(feature = "tls")]
+    alpn_protocol: Option<String>,
+    #[cfg(feature = "tls")]
+    tls_info: Option<Arc<TlsInfo>>,
     state: RequestState<'r>
 }
 
@@ -59,14 +124,33 @@ impl<'r> Request<'r> {
             uri: uri.into(),
             headers: HeaderMap::new(),
             remote: None,
+            scheme: None,
+            #[cfg(feature = "tls")]
+            certificates: None,
+            #[cfg(feature = "tls")]
+            alpn_protocol: None,
+            #[cfg(feature = "tls")]
+            tls_info: None,
             state: RequestState {
                 config: &rocket.config,
                 managed: &rocket.state,
+                features: &rocket.features,
                 route: Cell::new(None),
+                dynamic_routes: Storage::new(),
                 params: RefCell::new(Vec::new()),
                 cookies: RefCell::new(CookieJar::new()),
+                cookie_order: RefCell::new(Vec::new()),
+                cookies_overflowed: false,
+                timings: Cell::new(Timings::default()),
+                guards_complete: Cell::new(None),
+                autohandled_head: Cell::new(false),
+                deadline: Cell::new(None),
+                guard_failure: RefCell::new(None),
+                memory: Cell::new(0),
+                memory_exceeded: Cell::new(false),
                 accept: Storage::new(),
                 content_type: Storage::new(),
+                cache: Rc::new(Container::new()),
             }
         }
     }
@@ -152,6 +236,62 @@ impl<'r> Request<'r> {
         *self.state.params.borrow_mut() = Vec::new();
     }
 
+    /// Returns the raw bytes of the query part of the request's URI, exactly as
+    /// they were received and without the leading `?`, if there is a query
+    /// part. No decoding of any kind is performed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # Request::example(Method::Get, "/uri?name=Bob%20Jr&b=+", |request| {
+    /// assert_eq!(request.raw_query(), Some(&b"name=Bob%20Jr&b=+"[..]));
+    /// # });
+    /// ```
+    #[inline]
+    pub fn raw_query(&self) -> Option<&[u8]> {
+        self.uri.query().map(|query| query.as_bytes())
+    }
+
+    /// Returns an iterator over the raw, undecoded key/value pairs in the query
+    /// part of the request's URI.
+    ///
+    /// Pairs are returned in the order in which they appear, and duplicate
+    /// keys are preserved. Each pair is split at its first `=`; a pair without
+    /// an `=` has an empty value. Empty pairs, as in `a=1&&b=2`, are skipped.
+    /// Unlike [`FormItems`], no pair causes iteration to stop early. This
+    /// makes it suitable for tasks that require the original encoding, such
+    /// as verifying OAuth signatures.
+    ///
+    /// [`FormItems`]: /rocket/request/struct.FormItems.html
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # Request::example(Method::Get, "/uri?b=2&a=x%2By&b=1&sig=ab==&flag", |request| {
+    /// let pairs: Vec<_> = request.raw_query_pairs()
+    ///     .map(|(k, v)| (k.as_str(), v.as_str()))
+    ///     .collect();
+    ///
+    /// assert_eq!(pairs, vec![
+    ///     ("b", "2"), ("a", "x%2By"), ("b", "1"), ("sig", "ab=="), ("flag", "")
+    /// ]);
+    /// # });
+    /// ```
+    #[inline]
+    pub fn raw_query_pairs<'a>(&'a self) -> impl Iterator<Item=(&'a RawStr, &'a RawStr)> + 'a {
+        self.uri.query().unwrap_or("").split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let mut parts = pair.splitn(2, '=');
+                let key = RawStr::from_str(parts.next().unwrap_or(""));
+                (key, RawStr::from_str(parts.next().unwrap_or("")))
+            })
+    }
+
     /// Returns the address of the remote connection that initiated this
     /// request if the address is known. If the address is not known, `None` is
     /// returned.
@@ -203,6 +343,210 @@ impl<'r> Request<'r> {
         self.remote = Some(address);
     }
 
+    /// Returns the scheme the request was made with, in lowercase: `https` if
+    /// TLS is enabled and `http` otherwise, unless the scheme has been set
+    /// with [`set_scheme()`](#method.set_scheme).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # Request::example(Method::Get, "/uri", |request| {
+    /// assert_eq!(request.scheme(), "http");
+    /// # });
+    /// ```
+    #[inline]
+    pub fn scheme(&self) -> &str {
+        match self.scheme {
+            Some(ref scheme) => scheme,
+            None if self.state.config.tls_enabled() => "https",
+            None => "http",
+        }
+    }
+
+    /// Sets the scheme the request was made with to `scheme`, as in `https`.
+    /// This is useful when TLS is terminated by a proxy in front of Rocket.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # Request::example(Method::Get, "/uri", |mut request| {
+    /// request.set_scheme("HTTPS");
+    /// assert_eq!(request.scheme(), "https");
+    /// # });
+    /// ```
+    #[inline]
+    pub fn set_scheme(&mut self, scheme: &str) {
+        self.scheme = Some(scheme.to_ascii_lowercase());
+    }
+
+    /// Returns the certificate chain the client presented during the TLS
+    /// handshake, leaf certificate first, if it presented one. This method is
+    /// only available when the `tls` feature is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # Request::example(Method::Get, "/uri", |request| {
+    /// assert!(request.client_certificates().is_none());
+    /// # });
+    /// ```
+    #[cfg(feature = "tls")]
+    #[inline(always)]
+    pub fn client_certificates(&self) -> Option<&[Certificate]> {
+        self.certificates.as_ref().map(|peer| peer.chain())
+    }
+
+    /// Sets the certificate chain the client presented to `certificates`,
+    /// leaf certificate first. This method is only available when the `tls`
+    /// feature is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// use rocket::http::Certificate;
+    ///
+    /// # Request::example(Method::Get, "/uri", |mut request| {
+    /// let chain = vec![Certificate(vec![0x30]), Certificate(vec![0x30])];
+    /// request.set_client_certificates(chain.clone());
+    ///
+    /// assert_eq!(request.client_certificates(), Some(&chain[..]));
+    /// # });
+    /// ```
+    #[cfg(feature = "tls")]
+    #[inline(always)]
+    pub fn set_client_certificates(&mut self, certificates: Vec<Certificate>) {
+        self.certificates = Some(Arc::new(PeerCertificates::new(certificates)));
+    }
+
+    /// Returns the protocol the client selected from those advertised via
+    /// ALPN during the TLS handshake, if it selected one. No protocols are
+    /// advertised unless the `tls_alpn` configuration parameter is set. This
+    /// method is only available when the `tls` feature is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # Request::example(Method::Get, "/uri", |request| {
+    /// assert!(request.alpn_protocol().is_none());
+    /// # });
+    /// ```
+    #[cfg(feature = "tls")]
+    #[inline(always)]
+    pub fn alpn_protocol(&self) -> Option<&str> {
+        self.alpn_protocol.as_ref().map(|protocol| protocol.as_str())
+    }
+
+    /// Sets the protocol the client selected via ALPN to `protocol`. This
+    /// method is only available when the `tls` feature is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # Request::example(Method::Get, "/uri", |mut request| {
+    /// request.set_alpn_protocol("http/1.1");
+    /// assert_eq!(request.alpn_protocol(), Some("http/1.1"));
+    /// # });
+    /// ```
+    #[cfg(feature = "tls")]
+    #[inline]
+    pub fn set_alpn_protocol<S: Into<String>>(&mut self, protocol: S) {
+        self.alpn_protocol = Some(protocol.into());
+    }
+
+    /// Returns the parameters negotiated by the TLS handshake of the
+    /// connection this request was received on, or `None` if it wasn't
+    /// received over TLS. This method is only available when the `tls` feature
+    /// is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # Request::example(Method::Get, "/uri", |request| {
+    /// if let Some(tls) = request.tls_info() {
+    ///     println!("{} with {}", tls.protocol_version, tls.cipher_suite);
+    /// }
+    /// # });
+    /// ```
+    #[cfg(feature = "tls")]
+    #[inline(always)]
+    pub fn tls_info(&self) -> Option<&TlsInfo> {
+        self.tls_info.as_ref().map(|info| &**info)
+    }
+
+    /// Sets the parameters negotiated by the TLS handshake of the connection
+    /// this request was received on to `info`. This method is only available
+    /// when the `tls` feature is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// use rocket::tls::TlsInfo;
+    ///
+    /// # Request::example(Method::Get, "/uri", |mut request| {
+    /// request.set_tls_info(TlsInfo {
+    ///     protocol_version: "TLSv1.2".into(),
+    ///     cipher_suite: "TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256".into(),
+    ///     sni_hostname: Some("rocket.rs".into()),
+    ///     resumed: false,
+    /// });
+    ///
+    /// assert_eq!(request.tls_info().unwrap().protocol_version, "TLSv1.2");
+    /// # });
+    /// ```
+    #[cfg(feature = "tls")]
+    #[inline]
+    pub fn set_tls_info(&mut self, info: TlsInfo) {
+        self.tls_info = Some(Arc::new(info));
+    }
+
+    /// Returns the TLS parameters, as shared by every request on the client's
+    /// connection.
+    #[cfg(feature = "tls")]
+    #[inline(always)]
+    pub(crate) fn shared_tls_info(&self) -> Option<&Arc<TlsInfo>> {
+        self.tls_info.as_ref()
+    }
+
+    /// Sets the TLS parameters to those shared by every request on the
+    /// client's connection.
+    #[cfg(feature = "tls")]
+    #[inline(always)]
+    pub(crate) fn set_shared_tls_info(&mut self, info: Arc<TlsInfo>) {
+        self.tls_info = Some(info);
+    }
+
+    /// Returns the certificates the client presented, as shared by every
+    /// request on the client's connection.
+    #[cfg(feature = "tls")]
+    #[inline(always)]
+    pub(crate) fn peer_certificates(&self) -> Option<&Arc<PeerCertificates>> {
+        self.certificates.as_ref()
+    }
+
+    /// Sets the certificates the client presented to those shared by every
+    /// request on the client's connection.
+    #[cfg(feature = "tls")]
+    #[inline(always)]
+    pub(crate) fn set_peer_certificates(&mut self, peer: Arc<PeerCertificates>) {
+        self.certificates = Some(peer);
+    }
+
     /// Returns the IP address in the "X-Real-IP" header of the request if such
     /// a header exists and contains a valid IP address.
     ///
@@ -329,6 +673,12 @@ impl<'r> Request<'r> {
         self.headers.replace(header.into());
     }
 
+    /// Removes all of the headers named `name` from `self`.
+    #[inline(always)]
+    pub(crate) fn remove_header(&mut self, name: &str) {
+        self.headers.remove(name);
+    }
+
     /// Returns a wrapped borrow to the cookies in `self`.
     ///
     /// [`Cookies`](/rocket/http/enum.Cookies.html) implements internal
@@ -352,7 +702,11 @@ impl<'r> Request<'r> {
     pub fn cookies(&self) -> Cookies {
         // FIXME: Can we do better? This is disappointing.
         match self.state.cookies.try_borrow_mut() {
-            Ok(jar) => Cookies::new(jar, self.state.config.secret_key()),
+            Ok(jar) => {
+                let config = self.state.config;
+                let order = self.state.cookie_order.borrow_mut();
+                Cookies::new(jar, config.secret_key(), config.private_cookie_cipher, order)
+            }
             Err(_) => {
                 error_!("Multiple `Cookies` instances are active at once.");
                 info_!("An instance of `Cookies` must be dropped before another \
@@ -419,6 +773,97 @@ impl<'r> Request<'r> {
         }).as_ref()
     }
 
+    /// Returns `true` if the client accepts responses of type `content_type`
+    /// according to the request's Accept header.
+    ///
+    /// The most specific media range in the Accept header that includes
+    /// `content_type` determines whether it is accepted: a quality (`q`) of
+    /// `0` means it is not. If there is no Accept header, every content type
+    /// is accepted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// use rocket::http::{Accept, ContentType};
+    ///
+    /// # Request::example(Method::Get, "/uri", |mut request| {
+    /// assert!(request.accepts(&ContentType::JSON));
+    ///
+    /// request.add_header(Accept::HTML);
+    /// assert!(request.accepts(&ContentType::HTML));
+    /// assert!(!request.accepts(&ContentType::JSON));
+    /// # });
+    /// ```
+    pub fn accepts(&self, content_type: &ContentType) -> bool {
+        negotiation::media_quality(self.accept(), content_type.media_type()) > 0.0
+    }
+
+    /// Returns the content type in `offered` that the client most prefers
+    /// according to the request's Accept header, or `None` if the client
+    /// accepts none of them.
+    ///
+    /// Content types are ranked by the quality the client assigned to them.
+    /// Ties are broken in favor of the content type that appears first in
+    /// `offered`, so `offered` should be listed in the server's order of
+    /// preference.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// use rocket::http::{Header, ContentType};
+    ///
+    /// # Request::example(Method::Get, "/uri", |mut request| {
+    /// let offered = [ContentType::JSON, ContentType::HTML];
+    /// request.add_header(Header::new("Accept", "text/html, application/*;q=0.8"));
+    /// assert_eq!(request.preferred_of(&offered), Some(&ContentType::HTML));
+    /// # });
+    /// ```
+    pub fn preferred_of<'a>(&self, offered: &'a [ContentType]) -> Option<&'a ContentType> {
+        let accept = self.accept();
+        let mut preferred = None;
+        for content_type in offered {
+            let quality = negotiation::media_quality(accept, content_type.media_type());
+            if quality > preferred.map_or(0.0, |(_, q)| q) {
+                preferred = Some((content_type, quality));
+            }
+        }
+
+        preferred.map(|(content_type, _)| content_type)
+    }
+
+    /// Returns `true` if the client accepts responses with the content coding
+    /// `encoding`, such as `"gzip"` or `"br"`, according to the request's
+    /// Accept-Encoding headers.
+    ///
+    /// A coding is accepted if the Accept-Encoding headers list it, or a `*`
+    /// entry applies to it, with a nonzero quality. If there is no
+    /// Accept-Encoding header, every coding is accepted. The `identity` coding
+    /// is accepted unless it is explicitly excluded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// use rocket::http::Header;
+    ///
+    /// # Request::example(Method::Get, "/uri", |mut request| {
+    /// request.add_header(Header::new("Accept-Encoding", "gzip, br;q=0"));
+    /// assert!(request.accepts_encoding("gzip"));
+    /// assert!(request.accepts_encoding("identity"));
+    /// assert!(!request.accepts_encoding("br"));
+    /// assert!(!request.accepts_encoding("deflate"));
+    /// # });
+    /// ```
+    pub fn accepts_encoding(&self, encoding: &str) -> bool {
+        let values = self.headers().get("Accept-Encoding");
+        negotiation::encoding_quality(values, encoding) > 0.0
+    }
+
     /// Returns the media type "format" of the request.
     ///
     /// The "format" of a request is either the Content-Type, if the request
@@ -475,6 +920,103 @@ impl<'r> Request<'r> {
         &self.state.config.limits
     }
 
+    /// Returns the approximate number of bytes buffered in memory on behalf of
+    /// this request so far: request bodies read into memory by Rocket's data
+    /// guards and response bodies buffered by Rocket, along with anything
+    /// accounted for with [`reserve_memory()`](#method.reserve_memory).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # Request::example(Method::Get, "/uri", |request| {
+    /// assert_eq!(request.memory_used(), 0);
+    /// # });
+    /// ```
+    #[inline]
+    pub fn memory_used(&self) -> u64 {
+        self.state.memory.get()
+    }
+
+    /// Returns the number of bytes that may still be buffered on behalf of
+    /// this request before the `memory` limit is reached, or `None` if no
+    /// `memory` limit is configured.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # Request::example(Method::Get, "/uri", |request| {
+    /// assert_eq!(request.memory_remaining(), None);
+    /// # });
+    /// ```
+    pub fn memory_remaining(&self) -> Option<u64> {
+        self.limits().get("memory").map(|limit| limit.saturating_sub(self.memory_used()))
+    }
+
+    /// Accounts for `bytes` more bytes buffered in memory on behalf of this
+    /// request. Data guards and handlers that buffer data themselves can use
+    /// this method to have the buffers count towards the request's `memory`
+    /// limit.
+    ///
+    /// # Errors
+    ///
+    /// If a `memory` limit is configured and the request would exceed it, the
+    /// bytes aren't accounted for and an error is returned. The request should
+    /// then fail: with `413 Payload Too Large` if the data came from the
+    /// client and `500 Internal Server Error` otherwise. Rocket records such
+    /// requests in the `requests.memory_exceeded` metric.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # Request::example(Method::Get, "/uri", |request| {
+    /// assert!(request.reserve_memory(4096).is_ok());
+    /// assert_eq!(request.memory_used(), 4096);
+    /// # });
+    /// ```
+    pub fn reserve_memory(&self, bytes: u64) -> Result<(), MemoryLimitExceeded> {
+        let requested = self.memory_used().saturating_add(bytes);
+        match self.limits().get("memory") {
+            Some(limit) if requested > limit => {
+                warn_!("Request exceeds the memory limit of {} bytes.", limit);
+                self.state.memory_exceeded.set(true);
+                Err(MemoryLimitExceeded { limit: limit, requested: requested })
+            }
+            _ => {
+                self.state.memory.set(requested);
+                Ok(())
+            }
+        }
+    }
+
+    /// Whether the request exceeded its `memory` limit. For internal use only!
+    #[inline(always)]
+    pub(crate) fn memory_exceeded(&self) -> bool {
+        self.state.memory_exceeded.get()
+    }
+
+    /// Returns the API version negotiated for this request, if any. A version
+    /// is only negotiated when [versioning](/rocket/versioning/index.html) is
+    /// enabled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # Request::example(Method::Get, "/uri", |request| {
+    /// assert_eq!(request.api_version(), None);
+    /// # });
+    /// ```
+    pub fn api_version(&self) -> Option<&str> {
+        self.local_cache(|| NegotiatedVersion(None)).0.as_ref().map(|v| v.as_str())
+    }
+
     /// Get the presently matched route, if any.
     ///
     /// This method returns `Some` any time a handler or its guards are being
@@ -490,8 +1032,46 @@ impl<'r> Request<'r> {
     /// let route = request.route();
     /// # });
     /// ```
-    pub fn route(&self) -> Option<&'r Route> {
-        self.state.route.get()
+    pub fn route(&self) -> Option<&Route> {
+        match self.state.route.get()? {
+            Routed::Mounted(route) => Some(route),
+            Routed::Dynamic(n) => self.dynamic_route(n),
+        }
+    }
+
+    /// Returns the `n`th dynamic route `self` was routed to.
+    fn dynamic_route(&self, n: usize) -> Option<&Route> {
+        let mut dynamic = self.state.dynamic_routes.try_get()?;
+        for _ in 0..n {
+            dynamic = dynamic.next.try_get()?;
+        }
+
+        Some(&dynamic.route)
+    }
+
+    /// Returns the context error catchers use to explain a failed request: the
+    /// route that was last tried, the guard that failed, if any, and the
+    /// timings so far. See [`CatchContext`] for details.
+    ///
+    /// [`CatchContext`]: /rocket/request/struct.CatchContext.html
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # Request::example(Method::Get, "/uri", |request| {
+    /// let context = request.catch_context();
+    /// assert!(context.route().is_none());
+    /// assert!(context.guard_failure().is_none());
+    /// # });
+    /// ```
+    pub fn catch_context<'a>(&'a self) -> CatchContext<'a> {
+        CatchContext {
+            route: self.route(),
+            guard_failure: self.state.guard_failure.borrow().clone(),
+            timings: self.timings(),
+        }
     }
 
     /// Invokes the request guard implemention for `T`, returning its outcome.
@@ -516,6 +1096,42 @@ impl<'r> Request<'r> {
         T::from_request(self)
     }
 
+    /// Retrieves the cached value for type `T` from the request-local cached
+    /// state of `self`. If no such value has previously been cached for this
+    /// request, `f` is called to produce the value which is subsequently
+    /// returned.
+    ///
+    /// The request-local cache lives as long as the request: values cached by
+    /// request fairings are visible to request guards, handlers, and
+    /// responders. It is typically used to compute a value once per request or
+    /// to pass values between these.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # type User = ();
+    /// fn current_user(request: &Request) -> User {
+    ///     // Validate request for a given user, load from database, etc.
+    /// }
+    ///
+    /// # Request::example(Method::Get, "/uri", |request| {
+    /// let user = request.local_cache(|| current_user(request));
+    /// # });
+    /// ```
+    pub fn local_cache<T, F>(&self, f: F) -> &T
+        where T: Send + Sync + 'static, F: FnOnce() -> T
+    {
+        match self.state.cache.try_get() {
+            Some(cached) => cached,
+            None => {
+                self.state.cache.set(f());
+                self.state.cache.get()
+            }
+        }
+    }
+
     /// Retrieves and parses into `T` the 0-indexed `n`th dynamic parameter from
     /// the request. Returns `Error::NoKey` if `n` is greater than the number of
     /// params. Returns `Error::BadParse` if the parameter type `T` can't be
@@ -614,10 +1230,28 @@ impl<'r> Request<'r> {
     /// TODO: Figure out the mount path from here.
     #[inline]
     pub(crate) fn set_route(&self, route: &'r Route) {
-        self.state.route.set(Some(route));
+        self.state.route.set(Some(Routed::Mounted(route)));
+        *self.state.guard_failure.borrow_mut() = None;
         *self.state.params.borrow_mut() = route.get_param_indexes(self.uri());
     }
 
+    /// Like `set_route()`, for the dynamic route `route`. `self` keeps the
+    /// route alive, so the returned borrow of it lasts as long as `self`'s.
+    pub(crate) fn set_dynamic_route(&self, route: Arc<Route>) -> &Route {
+        let (mut slot, mut n) = (&self.state.dynamic_routes, 0);
+        while let Some(dynamic) = slot.try_get() {
+            slot = &dynamic.next;
+            n += 1;
+        }
+
+        slot.set(Box::new(DynamicRoute { route: route, next: Storage::new() }));
+        self.state.route.set(Some(Routed::Dynamic(n)));
+        let route = self.dynamic_route(n).expect("dynamic route was just set");
+        *self.state.guard_failure.borrow_mut() = None;
+        *self.state.params.borrow_mut() = route.get_param_indexes(self.uri());
+        route
+    }
+
     /// Set the method of `self`, even when `self` is a shared reference.
     #[inline(always)]
     pub(crate) fn _set_method(&self, method: Method) {
@@ -628,6 +1262,79 @@ impl<'r> Request<'r> {
     #[inline]
     pub(crate) fn set_cookies(&mut self, jar: CookieJar) {
         self.state.cookies = RefCell::new(jar);
+        self.state.cookie_order = RefCell::new(Vec::new());
+    }
+
+    /// Whether the request's cookies exceeded the configured limits under the
+    /// `Reject` policy. For internal use only!
+    #[inline(always)]
+    pub(crate) fn cookies_overflowed(&self) -> bool {
+        self.state.cookies_overflowed
+    }
+
+    /// The timings of the dispatch of `self`. For internal use only!
+    #[inline(always)]
+    pub(crate) fn timings(&self) -> Timings {
+        self.state.timings.get()
+    }
+
+    /// Updates the timings of the dispatch of `self` with `f`.
+    #[inline(always)]
+    pub(crate) fn update_timings<F: FnOnce(&mut Timings)>(&self, f: F) {
+        let mut timings = self.state.timings.get();
+        f(&mut timings);
+        self.state.timings.set(timings);
+    }
+
+    /// Takes the instant at which the guards of the last handler run completed,
+    /// if they did. For internal use only!
+    #[inline(always)]
+    pub(crate) fn take_guards_complete(&self) -> Option<Instant> {
+        self.state.guards_complete.take()
+    }
+
+    /// Records that a guard of the current route failed the request. For
+    /// internal use only!
+    #[doc(hidden)]
+    #[inline]
+    pub fn _record_guard_failure(&self, failure: GuardFailure) {
+        *self.state.guard_failure.borrow_mut() = Some(failure);
+    }
+
+    /// Marks the point at which a generated handler has run all of its guards
+    /// and is about to call the route's function. For internal use only!
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn _mark_guards_complete(&self) {
+        self.state.guards_complete.set(Some(Instant::now()));
+    }
+
+    /// Marks the request as an automatically handled `HEAD` request whose
+    /// method has been rewritten to `GET`. For internal use only!
+    #[inline(always)]
+    pub(crate) fn set_autohandled_head(&self) {
+        self.state.autohandled_head.set(true);
+    }
+
+    /// Returns whether the request is an automatically handled `HEAD` request
+    /// whose method has been rewritten to `GET`. For internal use only!
+    #[inline(always)]
+    pub(crate) fn is_autohandled_head(&self) -> bool {
+        self.state.autohandled_head.get()
+    }
+
+    /// Sets the instant by which the route handling the request must finish
+    /// responding, if any. For internal use only!
+    #[inline(always)]
+    pub(crate) fn set_deadline(&self, deadline: Option<Instant>) {
+        self.state.deadline.set(deadline);
+    }
+
+    /// Returns the instant by which the route handling the request must finish
+    /// responding, if any. For internal use only!
+    #[inline(always)]
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.state.deadline.get()
     }
 
     /// Get the managed state T, if it exists. For internal use only!
@@ -636,6 +1343,18 @@ impl<'r> Request<'r> {
         self.state.managed.try_get()
     }
 
+    /// Get the active configuration. For internal use only!
+    #[inline(always)]
+    pub(crate) fn config(&self) -> &'r Config {
+        self.state.config
+    }
+
+    /// Get the registry of feature toggles. For internal use only!
+    #[inline(always)]
+    pub(crate) fn features(&self) -> &'r Features {
+        self.state.features
+    }
+
     /// Convert from Hyper types into a Rocket Request.
     pub(crate) fn from_hyp(rocket: &'r Rocket,
                            h_method: hyper::Method,
@@ -644,8 +1363,23 @@ impl<'r> Request<'r> {
                            h_addr: SocketAddr,
                            ) -> Result<Request<'r>, String> {
         // Get a copy of the URI for later use.
-        let uri = match h_uri {
-            hyper::RequestUri::AbsolutePath(s) => s,
+        // For absolute-form targets, the authority replaces the `Host` header.
+        let (uri, authority) = match h_uri {
+            hyper::RequestUri::AbsolutePath(s) => (s, None),
+            hyper::RequestUri::AbsoluteUri(ref url) if url.host_str().is_some() => {
+                let path = match url.query() {
+                    Some(query) => format!("{}?{}", url.path(), query),
+                    None => url.path().to_string()
+                };
+
+                let host = url.host_str().unwrap_or("");
+                let authority = match url.port() {
+                    Some(port) => format!("{}:{}", host, port),
+                    None => host.to_string()
+                };
+
+                (path, Some(authority))
+            }
             _ => return Err(format!("Bad URI: {}", h_uri)),
         };
 
@@ -661,7 +1395,7 @@ impl<'r> Request<'r> {
 
         // Set the request cookies, if they exist.
         if let Some(cookie_headers) = h_headers.get_raw("Cookie") {
-            let mut cookie_jar = CookieJar::new();
+            let mut cookies = vec![];
             for header in cookie_headers {
                 let raw_str = match ::std::str::from_utf8(header) {
                     Ok(string) => string,
@@ -670,11 +1404,34 @@ impl<'r> Request<'r> {
 
                 for cookie_str in raw_str.split(";").map(|s| s.trim()) {
                     if let Some(cookie) = Cookies::parse_cookie(cookie_str) {
-                        cookie_jar.add_original(cookie);
+                        cookies.push((cookie_str.len(), cookie));
                     }
                 }
             }
 
+            // Enforce the cookie limits, oldest cookies first.
+            let limits = rocket.config.cookie_limits;
+            let sizes: Vec<_> = cookies.iter().map(|&(size, _)| size).collect();
+            let excess = limits.excess(&sizes);
+            if excess > 0 {
+                match limits.overflow_policy() {
+                    CookieOverflow::Reject => request.state.cookies_overflowed = true,
+                    CookieOverflow::TruncateOldest => {
+                        warn_!("Dropping {} request cookie(s) exceeding the limits ({}).",
+                               excess, limits);
+                        cookies.drain(..excess);
+                    }
+                    CookieOverflow::Log => {
+                        warn_!("Request cookies exceed the limits ({}).", limits);
+                    }
+                }
+            }
+
+            let mut cookie_jar = CookieJar::new();
+            for (_, cookie) in cookies {
+                cookie_jar.add_original(cookie);
+            }
+
             request.set_cookies(cookie_jar);
         }
 
@@ -690,6 +1447,10 @@ impl<'r> Request<'r> {
             }
         }
 
+        if let Some(authority) = authority {
+            request.replace_header(Header::new("Host", authority));
+        }
+
         Ok(request)
     }
 }
@@ -0,0 +1,159 @@
+use http::{RawStr, ContentType};
+use request::{FromParam, FromFormValue};
+
+/// A dynamic path segment with matrix parameters.
+///
+/// Matrix parameters are `;`-separated `key=value` pairs that follow the value
+/// of a path segment, as in `/map;zoom=5;lat=42.3/tiles`. A `Matrix<T>` parses
+/// the portion of the segment before the first `;` as a `T` using `T`'s
+/// [FromParam](/rocket/request/trait.FromParam.html) implementation, and
+/// provides access to the matrix parameters that follow. A key without a value,
+/// as in `;debug`, has an empty value.
+///
+/// If parsing the value as a `T` fails, the error from `T` is returned.
+///
+/// # Example
+///
+/// ```rust
+/// # #![feature(plugin, decl_macro)]
+/// # #![plugin(rocket_codegen)]
+/// # extern crate rocket;
+/// use rocket::request::Matrix;
+///
+/// // A request to `/map;zoom=12/tiles` returns "map at zoom 12".
+/// #[get("/<map>/tiles")]
+/// fn tiles(map: Matrix<String>) -> String {
+///     let zoom: u8 = map.get_as("zoom").and_then(|r| r.ok()).unwrap_or(1);
+///     format!("{} at zoom {}", map.value(), zoom)
+/// }
+/// # fn main() {  }
+/// ```
+#[derive(Debug)]
+pub struct Matrix<'a, T = &'a RawStr> {
+    value: T,
+    params: &'a RawStr,
+}
+
+impl<'a, T> Matrix<'a, T> {
+    /// Returns a reference to the parsed value of the segment.
+    #[inline(always)]
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Consumes `self` and returns the parsed value of the segment.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// Returns an iterator over the raw `(key, value)` matrix parameters, in
+    /// the order they appear in the segment.
+    pub fn params(&self) -> impl Iterator<Item = (&'a RawStr, &'a RawStr)> {
+        let params: &'a str = self.params.as_str();
+        params.split(';')
+            .filter(|param| !param.is_empty())
+            .map(|param| match param.find('=') {
+                Some(i) => ((&param[..i]).into(), (&param[(i + 1)..]).into()),
+                None => (param.into(), "".into())
+            })
+    }
+
+    /// Returns the raw value of the first matrix parameter named `key`, if
+    /// there is one.
+    pub fn get(&self, key: &str) -> Option<&'a RawStr> {
+        self.params().find(|&(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Parses the value of the first matrix parameter named `key` as a `V`
+    /// using `V`'s `FromFormValue` implementation. Returns `None` if there is
+    /// no parameter named `key`.
+    pub fn get_as<V: FromFormValue<'a>>(&self, key: &str) -> Option<Result<V, V::Error>> {
+        self.get(key).map(V::from_form_value)
+    }
+}
+
+impl<'a, T: FromParam<'a>> FromParam<'a> for Matrix<'a, T> {
+    type Error = T::Error;
+
+    fn from_param(param: &'a RawStr) -> Result<Self, Self::Error> {
+        let (value, params) = match param.find(';') {
+            Some(i) => (&param[..i], &param[(i + 1)..]),
+            None => (param.as_str(), "")
+        };
+
+        Ok(Matrix { value: T::from_param(value.into())?, params: params.into() })
+    }
+}
+
+/// A dynamic path segment with a file extension.
+///
+/// A `WithExtension<T>` splits a segment such as `report.pdf` at its final
+/// `.`, parses the stem as a `T` using `T`'s
+/// [FromParam](/rocket/request/trait.FromParam.html) implementation, and
+/// retains the extension. A segment without a `.` has no extension.
+///
+/// To require an extension, a route can instead include it in the path, as in
+/// `/report.<ext>`.
+///
+/// # Example
+///
+/// ```rust
+/// # #![feature(plugin, decl_macro)]
+/// # #![plugin(rocket_codegen)]
+/// # extern crate rocket;
+/// use rocket::request::WithExtension;
+///
+/// // A request to `/reports/12.csv` returns "report 12 as csv".
+/// #[get("/reports/<report>")]
+/// fn report(report: WithExtension<usize>) -> String {
+///     let ext = report.extension().map_or("html", |ext| ext.as_str());
+///     format!("report {} as {}", report.value(), ext)
+/// }
+/// # fn main() {  }
+/// ```
+#[derive(Debug)]
+pub struct WithExtension<'a, T = &'a RawStr> {
+    value: T,
+    extension: Option<&'a RawStr>,
+}
+
+impl<'a, T> WithExtension<'a, T> {
+    /// Returns a reference to the parsed stem of the segment.
+    #[inline(always)]
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Consumes `self` and returns the parsed stem of the segment.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// Returns the raw extension of the segment, without the leading `.`, if
+    /// there is one.
+    #[inline(always)]
+    pub fn extension(&self) -> Option<&'a RawStr> {
+        self.extension
+    }
+
+    /// Returns the `ContentType` associated with the extension of the segment,
+    /// if there is an extension and it is known.
+    pub fn content_type(&self) -> Option<ContentType> {
+        self.extension.and_then(|ext| ContentType::from_extension(ext.as_str()))
+    }
+}
+
+impl<'a, T: FromParam<'a>> FromParam<'a> for WithExtension<'a, T> {
+    type Error = T::Error;
+
+    fn from_param(param: &'a RawStr) -> Result<Self, Self::Error> {
+        let (stem, extension) = match param.rfind('.') {
+            Some(i) if i > 0 => (&param[..i], Some((&param[(i + 1)..]).into())),
+            _ => (param.as_str(), None)
+        };
+
+        Ok(WithExtension { value: T::from_param(stem.into())?, extension: extension })
+    }
+}
@@ -2,7 +2,8 @@ use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::collections::HashMap;
 
 use {Rocket, Request, Config};
-use http::hyper;
+use config::{CookieLimits, CookieOverflow};
+use http::{hyper, Method};
 
 macro_rules! assert_headers {
     ($($key:expr => [$($value:expr),+]),+) => ({
@@ -47,3 +48,70 @@ fn test_multiple_headers_merge_into_one_from_hyp() {
     assert_headers!("friend" => ["alice"], "friend" => ["bob"], "friend" => ["carol"]);
     assert_headers!("friend" => ["alice"], "friend" => ["bob"], "enemy" => ["carol"]);
 }
+
+#[test]
+fn test_raw_query_pairs() {
+    let rocket = Rocket::custom(Config::development().unwrap(), true);
+    let request = Request::new(&rocket, Method::Get, "/?a=1&a=2&&c&d=e=f");
+    let pairs: Vec<_> = request.raw_query_pairs()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+
+    assert_eq!(pairs, vec![("a", "1"), ("a", "2"), ("c", ""), ("d", "e=f")]);
+    assert_eq!(request.raw_query(), Some(&b"a=1&a=2&&c&d=e=f"[..]));
+
+    let request = Request::new(&rocket, Method::Get, "/");
+    assert_eq!(request.raw_query(), None);
+    assert_eq!(request.raw_query_pairs().count(), 0);
+}
+
+fn request_with_cookies<'r>(rocket: &'r Rocket, cookies: &[&str]) -> Request<'r> {
+    let h_uri = hyper::RequestUri::AbsolutePath("/".to_string());
+    let h_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8000);
+    let mut h_headers = hyper::header::Headers::new();
+    for cookie in cookies {
+        h_headers.append_raw("Cookie", cookie.as_bytes().into());
+    }
+
+    Request::from_hyp(rocket, hyper::Method::Get, h_headers, h_uri, h_addr).unwrap()
+}
+
+#[test]
+fn test_cookie_limits_from_hyp() {
+    let cookies = ["a=1; b=2", "c=3", "d=4"];
+    let rocket_with = |overflow| {
+        let mut config = Config::development().unwrap();
+        config.set_cookie_limits(CookieLimits::new(2, 1024).overflow(overflow));
+        Rocket::custom(config, true)
+    };
+
+    let rocket = rocket_with(CookieOverflow::Log);
+    let request = request_with_cookies(&rocket, &cookies);
+    assert_eq!(request.cookies().iter().count(), 4);
+    assert!(!request.cookies_overflowed());
+
+    let rocket = rocket_with(CookieOverflow::TruncateOldest);
+    let request = request_with_cookies(&rocket, &cookies);
+    let mut names: Vec<_> = request.cookies().iter().map(|c| c.name().to_string()).collect();
+    names.sort();
+    assert_eq!(names, vec!["c", "d"]);
+    assert!(!request.cookies_overflowed());
+
+    let rocket = rocket_with(CookieOverflow::Reject);
+    let request = request_with_cookies(&rocket, &cookies);
+    assert!(request.cookies_overflowed());
+
+    let request = request_with_cookies(&rocket, &["a=1; b=2"]);
+    assert!(!request.cookies_overflowed());
+}
+
+#[test]
+fn test_cookie_limits_excess() {
+    let limits = CookieLimits::new(3, 10);
+    assert_eq!(limits.excess(&[]), 0);
+    assert_eq!(limits.excess(&[3, 3, 3]), 0);
+    assert_eq!(limits.excess(&[1, 1, 1, 1, 1]), 2);
+    assert_eq!(limits.excess(&[8, 1, 1, 1]), 1);
+    assert_eq!(limits.excess(&[4, 4, 4]), 1);
+    assert_eq!(limits.excess(&[20]), 1);
+}
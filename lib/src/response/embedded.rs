@@ -0,0 +1,191 @@
+use std::io::Cursor;
+use std::path::{Path, Component};
+
+use request::{Request, Precondition};
+use request::negotiation;
+use response::{self, Responder, Response};
+use http::{ContentType, Status};
+
+/// A directory of files compiled into the binary by the `embed_dir!` macro.
+///
+/// Embedding assets allows an application to be deployed as a single binary,
+/// without a filesystem to serve static files from. `embed_dir!("dir")`
+/// embeds every file in `dir`, relative to the crate's root, recursively. With
+/// `embed_dir!("dir", gzip)`, a gzip compressed copy of each file that
+/// compresses well is embedded as well. See the `rocket_codegen` documentation
+/// for details.
+///
+/// A file is retrieved with [`get()`] by its path relative to the embedded
+/// directory. Files are [`EmbeddedFile`]s, which respond with their contents.
+///
+/// # Example
+///
+/// Serve the embedded `static` directory at the root, after all other routes:
+///
+/// ```rust
+/// # #![feature(plugin, decl_macro)]
+/// # #![plugin(rocket_codegen)]
+/// # extern crate rocket;
+/// use std::path::PathBuf;
+/// use rocket::response::{EmbeddedDir, EmbeddedFile};
+///
+/// # /*
+/// static ASSETS: EmbeddedDir = embed_dir!("static", gzip);
+/// # */
+/// # static ASSETS: EmbeddedDir = EmbeddedDir { files: &[] };
+///
+/// #[get("/<path..>", rank = 5)]
+/// fn assets(path: PathBuf) -> Option<&'static EmbeddedFile> {
+///     ASSETS.get(path)
+/// }
+/// # fn main() {  }
+/// ```
+///
+/// [`get()`]: /rocket/response/struct.EmbeddedDir.html#method.get
+/// [`EmbeddedFile`]: /rocket/response/struct.EmbeddedFile.html
+#[derive(Debug, Clone, Copy)]
+pub struct EmbeddedDir {
+    #[doc(hidden)]
+    pub files: &'static [EmbeddedFile],
+}
+
+impl EmbeddedDir {
+    /// Returns the file at `path`, relative to the embedded directory, if
+    /// there is one. Paths that aren't normal, relative paths never match.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::EmbeddedDir;
+    ///
+    /// let empty = EmbeddedDir { files: &[] };
+    /// assert!(empty.get("index.html").is_none());
+    /// ```
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<&'static EmbeddedFile> {
+        let mut components = vec![];
+        for component in path.as_ref().components() {
+            match component {
+                Component::Normal(c) => components.push(c.to_str()?),
+                Component::CurDir => continue,
+                _ => return None
+            }
+        }
+
+        let path = components.join("/");
+        self.files.iter().find(|file| file.path == path)
+    }
+
+    /// Returns every file in the embedded directory, sorted by path.
+    #[inline(always)]
+    pub fn files(&self) -> &'static [EmbeddedFile] {
+        self.files
+    }
+}
+
+/// A file compiled into the binary by the `embed_dir!` macro.
+///
+/// An `EmbeddedFile` is retrieved from an [`EmbeddedDir`]. A reference to one
+/// responds with the file's contents and sets the `Content-Type` according to
+/// the file's extension if it's recognized.
+///
+/// # Validators and Compression
+///
+/// Responses carry a strong `ETag` derived from the file's contents. A
+/// request whose `If-None-Match` header matches the `ETag` receives a `304 Not
+/// Modified` response without a body, and one whose `If-Match` header doesn't
+/// fails with `412 Precondition Failed`.
+///
+/// If the file was embedded with a gzip compressed copy and the client accepts
+/// `gzip`, the compressed copy is sent with a `Content-Encoding: gzip` header
+/// and an `ETag` of its own. Rocket doesn't compress the response again.
+///
+/// [`EmbeddedDir`]: /rocket/response/struct.EmbeddedDir.html
+#[derive(Debug)]
+pub struct EmbeddedFile {
+    #[doc(hidden)]
+    pub path: &'static str,
+    #[doc(hidden)]
+    pub bytes: &'static [u8],
+    #[doc(hidden)]
+    pub gzipped: Option<&'static [u8]>,
+    #[doc(hidden)]
+    pub etag: &'static str,
+}
+
+impl EmbeddedFile {
+    /// Returns the path of the file relative to the embedded directory, with
+    /// components separated by `/`.
+    #[inline(always)]
+    pub fn path(&self) -> &'static str {
+        self.path
+    }
+
+    /// Returns the uncompressed contents of the file.
+    #[inline(always)]
+    pub fn bytes(&self) -> &'static [u8] {
+        self.bytes
+    }
+
+    /// Returns the entity tag of the uncompressed contents, without quotes.
+    #[inline(always)]
+    pub fn etag(&self) -> &'static str {
+        self.etag
+    }
+
+    /// Returns the Content-Type implied by the file's extension, if any.
+    pub fn content_type(&self) -> Option<ContentType> {
+        let name = self.path.rsplit('/').next().unwrap_or(self.path);
+        name.rfind('.').and_then(|i| ContentType::from_extension(&name[(i + 1)..]))
+    }
+}
+
+/// Whether the client making `request` accepts a gzip compressed response as
+/// readily as an uncompressed one.
+fn accepts_gzip(request: &Request) -> bool {
+    if !request.headers().contains("Accept-Encoding") {
+        return false;
+    }
+
+    let quality = |token| {
+        negotiation::encoding_quality(request.headers().get("Accept-Encoding"), token)
+    };
+
+    let gzip = quality("gzip");
+    gzip > 0.0 && gzip >= quality("identity")
+}
+
+/// Responds with the file's contents. See [`EmbeddedFile`] for the headers
+/// that are set and the conditional requests that are handled.
+///
+/// [`EmbeddedFile`]: /rocket/response/struct.EmbeddedFile.html
+impl<'r> Responder<'r> for &'static EmbeddedFile {
+    fn respond_to(self, req: &Request) -> response::Result<'r> {
+        let gzipped = self.gzipped.filter(|_| accepts_gzip(req));
+        let etag = match gzipped {
+            Some(_) => format!("{}.gz", self.etag),
+            None => self.etag.to_string()
+        };
+
+        let mut builder = Response::build();
+        builder.raw_header("ETag", format!("\"{}\"", etag));
+        if self.gzipped.is_some() {
+            builder.raw_header("Vary", "Accept-Encoding");
+        }
+
+        match Precondition::from_headers(req).evaluate_read(&etag) {
+            Ok(()) => {}
+            Err(Status::NotModified) => return builder.status(Status::NotModified).ok(),
+            Err(status) => return Err(status)
+        }
+
+        if let Some(content_type) = self.content_type() {
+            builder.header(content_type);
+        }
+
+        if gzipped.is_some() {
+            builder.raw_header("Content-Encoding", "gzip");
+        }
+
+        builder.sized_body(Cursor::new(gzipped.unwrap_or(self.bytes))).ok()
+    }
+}
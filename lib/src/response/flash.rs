@@ -1,6 +1,8 @@
 use std::convert::AsRef;
+use std::fmt;
 
 use time::Duration;
+use base64;
 
 use outcome::IntoOutcome;
 use response::{Response, Responder};
@@ -11,6 +13,150 @@ use std::sync::atomic::{AtomicBool, Ordering};
 // The name of the actual flash cookie.
 const FLASH_COOKIE_NAME: &'static str = "_flash";
 
+// The maximum size of the flash cookie's value. Browsers drop cookies whose
+// name and value exceed 4096 bytes; this leaves room for the name.
+const FLASH_COOKIE_MAX_SIZE: usize = 4080;
+
+// The prefix of an encoded list of messages. Single messages in the legacy
+// `{name length}{name}{message}` format always begin with a digit.
+const FLASH_LIST_PREFIX: char = '~';
+
+/// The severity of a flash message, for the conventional message names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FlashLevel {
+    /// An informational message, named `info`.
+    Info,
+    /// A success message, named `success`.
+    Success,
+    /// A warning, named `warning`.
+    Warning,
+    /// An error, named `error`.
+    Error,
+}
+
+impl FlashLevel {
+    /// Returns the name of messages with this level.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::FlashLevel;
+    ///
+    /// assert_eq!(FlashLevel::Warning.as_str(), "warning");
+    /// ```
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            FlashLevel::Info => "info",
+            FlashLevel::Success => "success",
+            FlashLevel::Warning => "warning",
+            FlashLevel::Error => "error",
+        }
+    }
+
+    /// Returns the level of messages named `name`, if `name` is one of the
+    /// conventional names.
+    pub fn from_name(name: &str) -> Option<FlashLevel> {
+        match name {
+            "info" => Some(FlashLevel::Info),
+            "success" => Some(FlashLevel::Success),
+            "warning" => Some(FlashLevel::Warning),
+            "error" => Some(FlashLevel::Error),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for FlashLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// A single message carried by a [`Flash`](/rocket/response/struct.Flash.html):
+/// a name, the message contents, and an optional payload.
+///
+/// The payload is an arbitrary string, typically serialized data such as JSON,
+/// that accompanies the message: the ID of the record that was just created,
+/// for instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlashEntry {
+    name: String,
+    message: String,
+    payload: Option<String>,
+}
+
+impl FlashEntry {
+    fn new(name: &str, message: &str) -> FlashEntry {
+        FlashEntry { name: name.to_string(), message: message.to_string(), payload: None }
+    }
+
+    /// Returns the name of this message.
+    #[inline(always)]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the contents of this message.
+    #[inline(always)]
+    pub fn msg(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the payload of this message, if any.
+    #[inline(always)]
+    pub fn payload(&self) -> Option<&str> {
+        self.payload.as_ref().map(|s| s.as_str())
+    }
+
+    /// Returns the level of this message if its name is one of the
+    /// conventional names: `info`, `success`, `warning`, or `error`.
+    #[inline]
+    pub fn level(&self) -> Option<FlashLevel> {
+        FlashLevel::from_name(&self.name)
+    }
+
+    fn encode_into(&self, out: &mut String) {
+        for field in &[Some(&self.name), Some(&self.message), self.payload.as_ref()] {
+            match *field {
+                Some(value) => out.push_str(&format!("{}:{}", value.len(), value)),
+                None => out.push('-'),
+            }
+        }
+    }
+
+    fn decode_list(encoded: &str) -> Option<Vec<FlashEntry>> {
+        // Splits a `{len}:{value}` field off of the front of `s`.
+        fn field(s: &str) -> Option<(Option<&str>, &str)> {
+            if s.starts_with('-') {
+                return Some((None, &s[1..]));
+            }
+
+            let colon = s.find(':')?;
+            let len: usize = s[..colon].parse().ok()?;
+            let rest = &s[colon + 1..];
+            Some((Some(rest.get(..len)?), rest.get(len..)?))
+        }
+
+        let bytes = base64::decode_config(encoded, base64::URL_SAFE_NO_PAD).ok()?;
+        let mut rest = ::std::str::from_utf8(&bytes).ok()?;
+        let mut entries = vec![];
+        while !rest.is_empty() {
+            let (name, r) = field(rest)?;
+            let (message, r) = field(r)?;
+            let (payload, r) = field(r)?;
+            entries.push(FlashEntry {
+                name: name?.to_string(),
+                message: message?.to_string(),
+                payload: payload.map(|p| p.to_string()),
+            });
+
+            rest = r;
+        }
+
+        Some(entries)
+    }
+}
+
 /// Sets a "flash" cookie that will be removed when it is accessed. The
 /// anologous request type is
 /// [FlashMessage](/rocket/request/type.FlashMessage.html).
@@ -34,6 +180,14 @@ const FLASH_COOKIE_NAME: &'static str = "_flash";
 /// [FlashMessage](/rocket/request/type.FlashMessage.html) type and the
 /// [name](#method.name) and [msg](#method.msg) methods.
 ///
+/// A single `Flash` can carry several messages: additional messages are added
+/// with [push](#method.push), and each message can carry a string payload, set
+/// with [with_payload](#method.with_payload). All of the messages are retrieved
+/// on the request side with [messages](#method.messages). Messages are encoded
+/// into the cookie safely, regardless of their contents. Since browsers drop
+/// overly large cookies, messages that would make the cookie exceed 4KiB are
+/// dropped with a warning.
+///
 /// # Response
 ///
 /// The `Responder` implementation for `Flash` sets the message cookie and then
@@ -86,8 +240,7 @@ const FLASH_COOKIE_NAME: &'static str = "_flash";
 /// receive the standard welcome message.
 #[derive(Debug)]
 pub struct Flash<R> {
-    name: String,
-    message: String,
+    messages: Vec<FlashEntry>,
     consumed: AtomicBool,
     inner: R,
 }
@@ -101,11 +254,13 @@ pub struct Flash<R> {
 /// there is a flash cookie present (set by the `Flash` `Responder`), a
 /// `FlashMessage` reuqest guard will succeed.
 ///
-/// The flash cookie is cleared if either the [`name()`] or [`msg()`] method is
-/// called. If neither method is called, the flash cookie is not cleared.
+/// The flash cookie is cleared if any of the [`name()`], [`msg()`], or
+/// [`messages()`] methods is called. If none of them is called, the flash
+/// cookie is not cleared.
 ///
 /// [`name()`]: /rocket/response.struct.Flash.html#method.name
 /// [`msg()`]: /rocket/response.struct.Flash.html#method.msg
+/// [`messages()`]: /rocket/response.struct.Flash.html#method.messages
 pub type FlashMessage<'a, 'r> = ::response::Flash<&'a Request<'r>>;
 
 impl<'r, R: Responder<'r>> Flash<R> {
@@ -125,13 +280,65 @@ impl<'r, R: Responder<'r>> Flash<R> {
     /// ```
     pub fn new<N: AsRef<str>, M: AsRef<str>>(res: R, name: N, msg: M) -> Flash<R> {
         Flash {
-            name: name.as_ref().to_string(),
-            message: msg.as_ref().to_string(),
+            messages: vec![FlashEntry::new(name.as_ref(), msg.as_ref())],
             consumed: AtomicBool::default(),
             inner: res,
         }
     }
 
+    /// Constructs an "info" `Flash` message with the given `responder` and
+    /// `msg`.
+    ///
+    /// # Examples
+    ///
+    /// Construct an "info" message with contents "Heads up." that redirects to
+    /// "/".
+    ///
+    /// ```rust
+    /// use rocket::response::{Redirect, Flash};
+    ///
+    /// # #[allow(unused_variables)]
+    /// let msg = Flash::info(Redirect::to("/"), "Heads up.");
+    /// ```
+    pub fn info<S: AsRef<str>>(responder: R, msg: S) -> Flash<R> {
+        Flash::new(responder, "info", msg)
+    }
+
+    /// Adds another message with the given `name` and `msg` to `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use rocket::response::{Redirect, Flash, FlashLevel};
+    ///
+    /// # #[allow(unused_variables)]
+    /// let msg = Flash::success(Redirect::to("/"), "Saved.")
+    ///     .push(FlashLevel::Warning.as_str(), "Your password expires soon.");
+    /// ```
+    pub fn push<N: AsRef<str>, M: AsRef<str>>(mut self, name: N, msg: M) -> Flash<R> {
+        self.messages.push(FlashEntry::new(name.as_ref(), msg.as_ref()));
+        self
+    }
+
+    /// Sets the payload of the most recently added message to `payload`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use rocket::response::{Redirect, Flash};
+    ///
+    /// # #[allow(unused_variables)]
+    /// let msg = Flash::success(Redirect::to("/"), "Created.")
+    ///     .with_payload(r#"{"id": 17}"#);
+    /// ```
+    pub fn with_payload<P: Into<String>>(mut self, payload: P) -> Flash<R> {
+        if let Some(last) = self.messages.last_mut() {
+            last.payload = Some(payload.into());
+        }
+
+        self
+    }
+
     /// Constructs a "success" `Flash` message with the given `responder` and
     /// `msg`.
     ///
@@ -187,7 +394,23 @@ impl<'r, R: Responder<'r>> Flash<R> {
     }
 
     fn cookie(&self) -> Cookie<'static> {
-        let content = format!("{}{}{}", self.name.len(), self.name, self.message);
+        // Add messages in order until the cookie would grow too large.
+        let mut encoded = String::new();
+        let mut content = String::new();
+        for (i, message) in self.messages.iter().enumerate() {
+            message.encode_into(&mut encoded);
+            let candidate = format!("{}{}", FLASH_LIST_PREFIX,
+                base64::encode_config(&encoded, base64::URL_SAFE_NO_PAD));
+
+            if candidate.len() > FLASH_COOKIE_MAX_SIZE {
+                warn_!("Flash: dropping {} message(s) exceeding the cookie size limit.",
+                       self.messages.len() - i);
+                break;
+            }
+
+            content = candidate;
+        }
+
         Cookie::build(FLASH_COOKIE_NAME, content)
             .max_age(Duration::minutes(5))
             .path("/")
@@ -201,19 +424,18 @@ impl<'r, R: Responder<'r>> Flash<R> {
 /// the response is the `Outcome` of the wrapped `Responder`.
 impl<'r, R: Responder<'r>> Responder<'r> for Flash<R> {
     fn respond_to(self, req: &Request) -> Result<Response<'r>, Status> {
-        trace_!("Flash: setting message: {}:{}", self.name, self.message);
+        trace_!("Flash: setting {} message(s).", self.messages.len());
         req.cookies().add(self.cookie());
         self.inner.respond_to(req)
     }
 }
 
 impl<'a, 'r> Flash<&'a Request<'r>> {
-    /// Constructs a new message with the given name and message for the given
-    /// request.
-    fn named(name: &str, msg: &str, req: &'a Request<'r>) -> Flash<&'a Request<'r>> {
+    /// Constructs a flash carrying `messages`, which must be non-empty, for the
+    /// given request.
+    fn with_messages(messages: Vec<FlashEntry>, req: &'a Request<'r>) -> Flash<&'a Request<'r>> {
         Flash {
-            name: name.to_string(),
-            message: msg.to_string(),
+            messages: messages,
             consumed: AtomicBool::new(false),
             inner: req,
         }
@@ -228,16 +450,22 @@ impl<'a, 'r> Flash<&'a Request<'r>> {
         }
     }
 
-    /// Returns the `name` of this message.
+    /// Returns the `name` of the first message.
     pub fn name(&self) -> &str {
         self.clear_cookie_if_needed();
-        &self.name
+        self.messages[0].name()
     }
 
-    /// Returns the `msg` contents of this message.
+    /// Returns the `msg` contents of the first message.
     pub fn msg(&self) -> &str {
         self.clear_cookie_if_needed();
-        &self.message
+        self.messages[0].msg()
+    }
+
+    /// Returns all of the messages, in the order they were added.
+    pub fn messages(&self) -> &[FlashEntry] {
+        self.clear_cookie_if_needed();
+        &self.messages
     }
 }
 
@@ -254,15 +482,26 @@ impl<'a, 'r> FromRequest<'a, 'r> for Flash<&'a Request<'r>> {
         req.cookies().get(FLASH_COOKIE_NAME).ok_or(()).and_then(|cookie| {
             trace_!("Flash: retrieving message: {:?}", cookie);
 
-            // Parse the flash message.
+            // Parse the list of messages or a legacy single message.
             let content = cookie.value();
+            if content.starts_with(FLASH_LIST_PREFIX) {
+                return match FlashEntry::decode_list(&content[1..]) {
+                    Some(ref messages) if messages.is_empty() => Err(()),
+                    Some(messages) => Ok(Flash::with_messages(messages, req)),
+                    None => Err(())
+                };
+            }
+
             let (len_str, kv) = match content.find(|c: char| !c.is_digit(10)) {
                 Some(i) => (&content[..i], &content[i..]),
                 None => (content, ""),
             };
 
             match len_str.parse::<usize>() {
-                Ok(i) if i <= kv.len() => Ok(Flash::named(&kv[..i], &kv[i..], req)),
+                Ok(i) if kv.get(..i).is_some() => {
+                    let message = FlashEntry::new(&kv[..i], &kv[i..]);
+                    Ok(Flash::with_messages(vec![message], req))
+                }
                 _ => Err(())
             }
         }).into_outcome(Status::BadRequest)
@@ -22,9 +22,11 @@
 mod responder;
 mod redirect;
 mod named_file;
+mod embedded;
 mod stream;
 mod response;
 mod failure;
+mod pool;
 
 pub(crate) mod flash;
 
@@ -34,11 +36,13 @@ pub mod status;
 pub use self::response::{Response, ResponseBuilder, Body, DEFAULT_CHUNK_SIZE};
 pub use self::responder::Responder;
 pub use self::redirect::Redirect;
-pub use self::flash::Flash;
+pub use self::flash::{Flash, FlashEntry, FlashLevel};
 pub use self::named_file::NamedFile;
+pub use self::embedded::{EmbeddedDir, EmbeddedFile};
 pub use self::stream::Stream;
 pub use self::failure::Failure;
 #[doc(inline)] pub use self::content::Content;
+pub(crate) use self::pool::BufferPool;
 
 /// Type alias for the `Result` of a `Responder::respond` call.
 pub type Result<'r> = ::std::result::Result<self::Response<'r>, ::http::Status>;
@@ -1,14 +1,35 @@
-use std::fs::File;
+use std::fs::{File, Metadata};
 use std::path::{Path, PathBuf};
-use std::io;
+use std::io::{self, Seek, SeekFrom, Read};
 use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+use std::time::UNIX_EPOCH;
+
+use time;
 
 use request::Request;
-use response::{self, Responder};
-use http::ContentType;
+use response::{self, Responder, Response, Body};
+use http::{ContentType, Method, Status};
+use http::hyper::header::{EntityTag, HttpDate};
 
 /// A file with an associated name; responds with the Content-Type based on the
 /// file extension.
+///
+/// # Validators and Ranges
+///
+/// Responses for a `NamedFile` carry an `ETag`, derived from the file's size
+/// and modification time, a `Last-Modified` header, and an `Accept-Ranges:
+/// bytes` header. A `GET` request with a `Range` header for a single byte
+/// range receives a `206 Partial Content` response with only those bytes, or
+/// a `416 Range Not Satisfiable` response if the range lies outside of the
+/// file. Requests for multiple ranges receive the entire file.
+///
+/// If the request also carries an `If-Range` header, the range is only served
+/// if the file hasn't changed: an entity tag must strongly match the current
+/// `ETag`, and a date must exactly equal the current `Last-Modified` date.
+/// Otherwise, the entire file is sent with a `200 OK` status, so that resumed
+/// downloads of a changed file restart cleanly instead of splicing together
+/// two different versions.
 #[derive(Debug)]
 pub struct NamedFile(PathBuf, File);
 
@@ -81,8 +102,13 @@ impl NamedFile {
 /// Content-Type than that implied by its extension, use a `File` directly.
 impl<'r> Responder<'r> for NamedFile {
     fn respond_to(self, req: &Request) -> response::Result<'r> {
-        let mut response = self.1.respond_to(req)?;
-        if let Some(ext) = self.0.extension() {
+        let NamedFile(path, file) = self;
+        let mut response = match file.metadata() {
+            Ok(metadata) => respond_with_validators(file, &metadata, req)?,
+            Err(_) => file.respond_to(req)?
+        };
+
+        if let Some(ext) = path.extension() {
             if let Some(ct) = ContentType::from_extension(&ext.to_string_lossy()) {
                 response.set_header(ct);
             }
@@ -92,6 +118,148 @@ impl<'r> Responder<'r> for NamedFile {
     }
 }
 
+/// The validators of a file's current contents.
+struct Validators {
+    len: u64,
+    etag: EntityTag,
+    modified: Option<HttpDate>,
+}
+
+impl Validators {
+    fn of(metadata: &Metadata) -> Validators {
+        let modified = metadata.modified().ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok());
+
+        let len = metadata.len();
+        let tag = match modified {
+            Some(ref m) => format!("{:x}-{:x}.{:x}", len, m.as_secs(), m.subsec_nanos()),
+            None => format!("{:x}", len)
+        };
+
+        Validators {
+            len: len,
+            etag: EntityTag::strong(tag),
+            modified: modified.map(|m| {
+                HttpDate(time::at_utc(time::Timespec::new(m.as_secs() as i64, 0)))
+            }),
+        }
+    }
+
+    /// Whether the `If-Range` header value `value` still describes the
+    /// current contents, which requires a strong comparison.
+    fn if_range_holds(&self, value: &str) -> bool {
+        let value = value.trim();
+        if value.starts_with('"') || value.starts_with("W/") {
+            return EntityTag::from_str(value).map_or(false, |tag| tag.strong_eq(&self.etag));
+        }
+
+        match (HttpDate::from_str(value), self.modified.as_ref()) {
+            (Ok(date), Some(modified)) => date.0.to_timespec() == modified.0.to_timespec(),
+            _ => false
+        }
+    }
+}
+
+/// A byte range requested by a `Range` header.
+enum RequestedRange {
+    /// Send the entire file.
+    Full,
+    /// Send the bytes in `[start, end]`.
+    Partial(u64, u64),
+    /// The range lies outside of the file.
+    Unsatisfiable,
+}
+
+/// Parses a single byte range from the value of a `Range` header for a file of
+/// `len` bytes. Malformed and multi-range values yield `Full`.
+fn parse_range(value: &str, len: u64) -> RequestedRange {
+    let spec = match value.trim().find('=') {
+        Some(i) if value.trim()[..i].trim().eq_ignore_ascii_case("bytes") => {
+            value.trim()[(i + 1)..].trim()
+        }
+        _ => return RequestedRange::Full
+    };
+
+    if spec.contains(',') {
+        return RequestedRange::Full;
+    }
+
+    let (first, last) = match spec.find('-') {
+        Some(i) => (spec[..i].trim(), spec[(i + 1)..].trim()),
+        None => return RequestedRange::Full
+    };
+
+    let (first, last) = match (first.parse::<u64>().ok(), last.parse::<u64>().ok()) {
+        // `bytes=-N`: the final N bytes.
+        (None, Some(n)) if first.is_empty() => match n {
+            0 => return RequestedRange::Unsatisfiable,
+            n => (len.saturating_sub(n), len.saturating_sub(1)),
+        },
+        // `bytes=N-`: from N to the end.
+        (Some(n), None) if last.is_empty() => (n, len.saturating_sub(1)),
+        (Some(first), Some(last)) if first <= last => {
+            (first, ::std::cmp::min(last, len.saturating_sub(1)))
+        }
+        _ => return RequestedRange::Full
+    };
+
+    if len == 0 || first >= len {
+        return RequestedRange::Unsatisfiable;
+    }
+
+    RequestedRange::Partial(first, last)
+}
+
+fn respond_with_validators<'r>(
+    mut file: File,
+    metadata: &Metadata,
+    req: &Request
+) -> response::Result<'r> {
+    let validators = Validators::of(metadata);
+    let range = match req.headers().get_one("Range") {
+        Some(value) if req.method() == Method::Get => {
+            let if_range = req.headers().get_one("If-Range");
+            match if_range.map_or(true, |value| validators.if_range_holds(value)) {
+                true => parse_range(value, validators.len),
+                false => RequestedRange::Full
+            }
+        }
+        _ => RequestedRange::Full
+    };
+
+    let mut builder = Response::build();
+    builder.raw_header("ETag", validators.etag.to_string())
+        .raw_header("Accept-Ranges", "bytes");
+
+    if let Some(ref modified) = validators.modified {
+        builder.raw_header("Last-Modified", modified.to_string());
+    }
+
+    match range {
+        RequestedRange::Full => {
+            builder.raw_body(Body::Sized(io::BufReader::new(file), validators.len));
+        }
+        RequestedRange::Partial(start, end) => {
+            if file.seek(SeekFrom::Start(start)).is_err() {
+                return Err(Status::InternalServerError);
+            }
+
+            let size = end - start + 1;
+            let body = io::BufReader::new(file.take(size));
+            let content_range = format!("bytes {}-{}/{}", start, end, validators.len);
+            builder.status(Status::PartialContent)
+                .raw_header("Content-Range", content_range)
+                .raw_body(Body::Sized(body, size));
+        }
+        RequestedRange::Unsatisfiable => {
+            builder.status(Status::RangeNotSatisfiable)
+                .raw_header("Content-Range", format!("bytes */{}", validators.len));
+        }
+    }
+
+    builder.ok()
+}
+
 impl Deref for NamedFile {
     type Target = File;
 
@@ -0,0 +1,99 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+
+/// The maximum number of idle buffers a pool retains.
+const MAX_IDLE_BUFFERS: usize = 64;
+
+/// The size, in bytes, of the largest buffer a pool retains. Larger buffers
+/// are freed when they're returned.
+const MAX_POOLED_SIZE: usize = 64 * 1024;
+
+/// A pool of byte buffers used to write out streamed response bodies, so that
+/// each response doesn't allocate a fresh buffer.
+pub(crate) struct BufferPool {
+    idle: Mutex<Vec<Vec<u8>>>,
+}
+
+/// A buffer borrowed from a `BufferPool`, returned to the pool when dropped.
+pub(crate) struct PooledBuffer<'p> {
+    pool: &'p BufferPool,
+    buffer: Vec<u8>,
+}
+
+impl BufferPool {
+    #[inline]
+    pub(crate) fn new() -> BufferPool {
+        BufferPool { idle: Mutex::new(Vec::new()) }
+    }
+
+    /// Returns a zeroed buffer of `size` bytes, reusing an idle one if any.
+    pub(crate) fn get(&self, size: usize) -> PooledBuffer {
+        let idle = self.idle.lock().expect("buffer pool lock").pop();
+        let mut buffer = idle.unwrap_or_else(Vec::new);
+        buffer.clear();
+        buffer.resize(size, 0);
+        PooledBuffer { pool: self, buffer: buffer }
+    }
+
+    /// The number of idle buffers in the pool.
+    #[cfg(test)]
+    fn idle(&self) -> usize {
+        self.idle.lock().expect("buffer pool lock").len()
+    }
+}
+
+impl<'p> Deref for PooledBuffer<'p> {
+    type Target = [u8];
+
+    #[inline(always)]
+    fn deref(&self) -> &[u8] {
+        &self.buffer
+    }
+}
+
+impl<'p> DerefMut for PooledBuffer<'p> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.buffer
+    }
+}
+
+impl<'p> Drop for PooledBuffer<'p> {
+    fn drop(&mut self) {
+        if self.buffer.capacity() > MAX_POOLED_SIZE {
+            return;
+        }
+
+        let mut idle = self.pool.idle.lock().expect("buffer pool lock");
+        if idle.len() < MAX_IDLE_BUFFERS {
+            idle.push(::std::mem::replace(&mut self.buffer, Vec::new()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BufferPool, MAX_POOLED_SIZE};
+
+    #[test]
+    fn test_buffers_are_reused() {
+        let pool = BufferPool::new();
+        {
+            let mut buffer = pool.get(16);
+            assert_eq!(buffer.len(), 16);
+            buffer[0] = 0xff;
+        }
+
+        assert_eq!(pool.idle(), 1);
+        let buffer = pool.get(8);
+        assert_eq!(&*buffer, &[0; 8]);
+        assert_eq!(pool.idle(), 0);
+    }
+
+    #[test]
+    fn test_large_buffers_are_freed() {
+        let pool = BufferPool::new();
+        drop(pool.get(MAX_POOLED_SIZE + 1));
+        assert_eq!(pool.idle(), 0);
+    }
+}
@@ -2,6 +2,7 @@ use std::{io, fmt, str};
 use std::borrow::Cow;
 
 use response::Responder;
+use data::Data;
 use http::{Header, HeaderMap, Status, ContentType, Cookie};
 
 /// The default size, in bytes, of a chunk for streamed responses.
@@ -563,6 +564,9 @@ pub struct Response<'r> {
     status: Option<Status>,
     headers: HeaderMap<'r>,
     body: Option<Body<Box<io::Read + 'r>>>,
+    // Whether each read of a chunked body is flushed to the client as soon as
+    // it's written, as is necessary for live streams like server-sent events.
+    flush_chunks: bool,
 }
 
 impl<'r> Response<'r> {
@@ -589,6 +593,7 @@ impl<'r> Response<'r> {
             status: None,
             headers: HeaderMap::new(),
             body: None,
+            flush_chunks: false,
         }
     }
 
@@ -623,6 +628,43 @@ impl<'r> Response<'r> {
         ResponseBuilder::new(other)
     }
 
+    /// Returns a `200 OK` response whose body is the body of the request in
+    /// `data`, as for echoing or proxying a request body.
+    ///
+    /// The body is streamed: it is read from the request while the response
+    /// is written, [`DEFAULT_CHUNK_SIZE`] bytes at a time, through buffers
+    /// that Rocket reuses across responses. No headers are set; in particular,
+    /// the `Content-Type` of the request isn't copied.
+    ///
+    /// [`DEFAULT_CHUNK_SIZE`]: /rocket/response/constant.DEFAULT_CHUNK_SIZE.html
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #![feature(plugin, decl_macro)]
+    /// # #![plugin(rocket_codegen)]
+    /// # extern crate rocket;
+    /// use rocket::{Data, Response};
+    /// use rocket::http::ContentType;
+    ///
+    /// #[post("/echo", data = "<data>")]
+    /// fn echo(content_type: Option<ContentType>, data: Data) -> Response<'static> {
+    ///     let mut response = Response::from_data(data);
+    ///     if let Some(content_type) = content_type {
+    ///         response.set_header(content_type);
+    ///     }
+    ///
+    ///     response
+    /// }
+    /// # fn main() {  }
+    /// ```
+    #[inline]
+    pub fn from_data(data: Data) -> Response<'r> {
+        let mut response = Response::new();
+        response.set_chunked_body(data.open(), DEFAULT_CHUNK_SIZE);
+        response
+    }
+
     /// Returns the status of `self`.
     ///
     /// # Example
@@ -973,6 +1015,18 @@ impl<'r> Response<'r> {
         self.body.take()
     }
 
+    // Sets whether each read of a chunked body is written and flushed to the
+    // client immediately instead of being buffered into full chunks.
+    #[inline(always)]
+    pub(crate) fn set_flush_chunks(&mut self, flush: bool) {
+        self.flush_chunks = flush;
+    }
+
+    #[inline(always)]
+    pub(crate) fn flushes_chunks(&self) -> bool {
+        self.flush_chunks
+    }
+
     // Makes the `Read`er in the body empty but leaves the size of the body if
     // it exists. Only meant to be used to handle HEAD requests automatically.
     #[inline(always)]
@@ -1122,6 +1176,7 @@ impl<'r> Response<'r> {
 
         if let Some(body) = other.body {
             self.body = Some(body);
+            self.flush_chunks = other.flush_chunks;
         }
 
         for (name, values) in other.headers.into_iter_raw() {
@@ -1171,6 +1226,7 @@ impl<'r> Response<'r> {
 
         if self.body.is_none() {
             self.body = other.body;
+            self.flush_chunks = other.flush_chunks;
         }
 
         for (name, mut values) in other.headers.into_iter_raw() {
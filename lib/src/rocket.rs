@@ -1,29 +1,42 @@
 use std::collections::HashMap;
 use std::str::from_utf8;
 use std::cmp::min;
-use std::io::{self, Write};
-use std::time::Duration;
-use std::mem;
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{mem, thread};
 
 use yansi::Paint;
 use state::Container;
 
-#[cfg(feature = "tls")]
-use hyper_sync_rustls::TlsServer;
+#[cfg(feature = "envelope")]
+use envelope::{Envelope, EnvelopeKey};
 
 use {logger, handler};
-use ext::ReadExt;
-use config::{self, Config, LoggedValue};
-use request::{Request, FormItems};
-use data::Data;
-use response::{Body, Response};
+use config::{self, Config, CookieLimits, CookieOverflow, LoggedValue};
+use request::{Request, FormItems, Host};
+use data::{Data, Checksum, ChecksumAlgorithm, NetStream};
+use response::{Body, Response, BufferPool};
 use router::{Router, Route};
 use catcher::{self, Catcher};
 use outcome::Outcome;
 use error::{Error, LaunchError, LaunchErrorKind};
-use fairing::{Fairing, Fairings};
-
-use http::{Method, Status, Header};
+use fairing::{Fairing, Fairings, AttachError};
+use metrics::{Metrics, Counter, Connections, ConnectionStats};
+use coalesce::Coalescer;
+use idempotency::{Idempotency, Lookup};
+use alert::ErrorHook;
+use features::Features;
+use versioning::Versioning;
+use admin::{Admin, Control};
+use summary::LaunchSummary;
+use tls::{TlsReloader, TlsReloadError};
+use dynamic::{DynamicRoutes, Matched};
+use compression;
+use listener;
+
+use http::{Cookie, Method, Status, StatusClass};
 use http::hyper::{self, header};
 use http::uri::Uri;
 
@@ -31,11 +44,134 @@ use http::uri::Uri;
 /// application.
 pub struct Rocket {
     pub(crate) config: Config,
-    router: Router,
+    pub(crate) router: Router,
     default_catchers: HashMap<u16, Catcher>,
-    catchers: HashMap<u16, Catcher>,
+    pub(crate) catchers: HashMap<u16, Catcher>,
     pub(crate) state: Container,
-    fairings: Fairings,
+    pub(crate) fairings: Fairings,
+    metrics: Metrics,
+    connections: Arc<Connections>,
+    coalescer: Coalescer,
+    buffers: BufferPool,
+    idempotency: Option<Idempotency>,
+    error_hook: Option<ErrorHook>,
+    #[cfg(feature = "envelope")]
+    envelope: Option<Envelope>,
+    admin: Option<Admin>,
+    maintenance: Arc<AtomicBool>,
+    tls: TlsReloader,
+    dynamic: Option<DynamicRoutes>,
+    warm_ups: Vec<(Method, String)>,
+    ready: Arc<AtomicBool>,
+    pub(crate) features: Features,
+    pub(crate) versioning: Option<Versioning>,
+    pub(crate) attach_error: Option<AttachError>,
+}
+
+// A streamed response body that fails once more than `remaining` bytes of
+// `inner` are read.
+struct LimitedBody<R> {
+    inner: R,
+    remaining: u64,
+}
+
+impl<R: io::Read> io::Read for LimitedBody<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Read one byte past the limit so we know whether it was exceeded.
+        let max = min(buf.len() as u64, self.remaining.saturating_add(1)) as usize;
+        let n = self.inner.read(&mut buf[..max])?;
+        if n as u64 > self.remaining {
+            error_!("Streamed response body exceeds the response limit.");
+            let msg = "response body exceeds the response limit";
+            return Err(io::Error::new(io::ErrorKind::Other, msg));
+        }
+
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
+
+// A streamed response body that fails once it is read past `deadline`,
+// counting the failure in `timed_out`.
+struct DeadlineBody<R> {
+    inner: R,
+    deadline: Instant,
+    timed_out: Counter,
+}
+
+impl<R: io::Read> io::Read for DeadlineBody<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if Instant::now() >= self.deadline {
+            error_!("Streamed response body exceeds the route's timeout.");
+            self.timed_out.incr();
+            let msg = "response body exceeds the route's timeout";
+            return Err(io::Error::new(io::ErrorKind::TimedOut, msg));
+        }
+
+        self.inner.read(buf)
+    }
+}
+
+// Sets the `Digest` and `Content-Digest` headers of `response` to the digest of
+// its body using `algorithm`. The body must be buffered to be digested, so only
+// sized bodies are digested; streamed bodies are left untouched. The buffered
+// body counts towards the `memory` limit of `request`.
+fn add_response_digest(algorithm: ChecksumAlgorithm, request: &Request, response: &mut Response) {
+    let mut bytes = vec![];
+    let result = match response.body() {
+        Some(Body::Sized(body, _)) => body.read_to_end(&mut bytes),
+        _ => return
+    };
+
+    if let Err(e) = result {
+        error_!("Failed to read response body for digest: {:?}.", e);
+        response.set_status(Status::InternalServerError);
+        response.set_sized_body(io::Cursor::new(Vec::new()));
+        return;
+    }
+
+    if request.reserve_memory(bytes.len() as u64).is_err() {
+        response.set_status(Status::InternalServerError);
+        response.set_sized_body(io::Cursor::new(Vec::new()));
+        return;
+    }
+
+    let checksum = Checksum::compute(algorithm, &bytes);
+    response.set_raw_header("Digest", checksum.to_string());
+    response.set_raw_header("Content-Digest", checksum.to_content_digest());
+    response.set_sized_body(io::Cursor::new(bytes));
+}
+
+// A redirect of a request that asked to be upgraded to `location`.
+fn upgrade_response<'r>(location: String) -> Response<'r> {
+    Response::build()
+        .status(Status::TemporaryRedirect)
+        .raw_header("Location", location)
+        .raw_header("Vary", "Upgrade-Insecure-Requests")
+        .finalize()
+}
+
+// The cookies `request` sets, less the oldest ones it set until the rest fit
+// `limits`. Removals are never dropped, so `None` is returned if dropping every
+// other cookie isn't enough or if the order the cookies were set isn't known.
+fn truncate_cookies(request: &Request, limits: CookieLimits) -> Option<Vec<Cookie<'static>>> {
+    let (mut cookies, removals): (Vec<_>, Vec<_>) = request.cookies().ordered_delta()?
+        .into_iter()
+        .partition(|&(_, removal)| !removal);
+
+    // `excess` drops from the front, so the removals go last.
+    let sizes: Vec<_> = cookies.iter().chain(&removals)
+        .map(|&(ref cookie, _)| cookie.to_string().len())
+        .collect();
+
+    let excess = limits.excess(&sizes);
+    if excess > cookies.len() {
+        return None;
+    }
+
+    warn_!("Dropping {} response cookie(s) exceeding the limits ({}).", excess, limits);
+    cookies.drain(..excess);
+    Some(cookies.into_iter().chain(removals).map(|(cookie, _)| cookie).collect())
 }
 
 #[doc(hidden)]
@@ -48,10 +184,29 @@ impl hyper::Handler for Rocket {
     fn handle<'h, 'k>(
         &self,
         hyp_req: hyper::Request<'h, 'k>,
-        res: hyper::FreshResponse<'h>,
+        mut res: hyper::FreshResponse<'h>,
     ) {
         // Get all of the information from Hyper.
-        let (h_addr, h_method, h_headers, h_uri, _, h_body) = hyp_req.deconstruct();
+        let (h_addr, h_method, h_headers, h_uri, h_version, h_body) = hyp_req.deconstruct();
+
+        // Track the connection until the response has been written out, and
+        // close it after the response if it has carried its last request.
+        let keep_alive = hyper::should_keep_alive(h_version, &h_headers);
+        let stream = NetStream::from_hyp(&**h_body.get_ref().get_ref());
+
+        // Count the next request's head against the header limits only once
+        // this request has been handled: the bytes read until then are body.
+        let _rearm = NetStream::rearm_head(&**h_body.get_ref().get_ref());
+        #[cfg(feature = "tls")]
+        let certificates = NetStream::peer_certificates(&**h_body.get_ref().get_ref());
+        #[cfg(feature = "tls")]
+        let alpn_protocol = NetStream::alpn_protocol(&**h_body.get_ref().get_ref());
+        #[cfg(feature = "tls")]
+        let tls_info = NetStream::tls_info(&**h_body.get_ref().get_ref());
+        let connection = self.connections.track(h_addr, stream, keep_alive);
+        if keep_alive && !connection.keep_alive() {
+            res.headers_mut().set(header::Connection::close());
+        }
 
         // Convert the Hyper request into a Rocket request.
         let req_res = Request::from_hyp(self, h_method, h_headers, h_uri, h_addr);
@@ -60,17 +215,38 @@ impl hyper::Handler for Rocket {
             Err(e) => {
                 error!("Bad incoming request: {}", e);
                 let dummy = Request::new(self, Method::Get, Uri::new("<unknown>"));
-                let r = self.handle_error(Status::BadRequest, &dummy);
+                let r = self.handle_early_error(Status::BadRequest, &dummy);
                 return self.issue_response(r, res);
             }
         };
 
-        // Retrieve the data from the hyper body.
-        let data = match Data::from_hyp(h_body) {
+        #[cfg(feature = "tls")] {
+            if let Some(certificates) = certificates {
+                req.set_peer_certificates(certificates);
+            }
+
+            if let Some(protocol) = alpn_protocol {
+                req.set_alpn_protocol(protocol);
+            }
+
+            if let Some(info) = tls_info {
+                req.set_shared_tls_info(info);
+            }
+        }
+
+        // Retrieve the data from the hyper body, bounding reads by the
+        // timeouts configured for the request's content type.
+        let (read_timeout, body_timeout) = {
+            let media_type = req.content_type().map(|ct| ct.media_type());
+            let limits = &self.config.limits;
+            (limits.get_read_timeout(media_type), limits.get_body_timeout(media_type))
+        };
+
+        let data = match Data::from_hyp(h_body, read_timeout, body_timeout) {
             Ok(data) => data,
             Err(reason) => {
                 error_!("Bad data in request: {}", reason);
-                let r = self.handle_error(Status::InternalServerError, &req);
+                let r = self.handle_early_error(Status::InternalServerError, &req);
                 return self.issue_response(r, res);
             }
         };
@@ -91,7 +267,8 @@ impl hyper::Handler for Rocket {
 #[cfg(not(feature = "tls"))]
 macro_rules! serve {
     ($rocket:expr, $addr:expr, |$server:ident, $proto:ident| $continue:expr) => ({
-        let ($proto, $server) = ("http://", hyper::Server::http($addr));
+        let listener = listener::http($addr, &$rocket.config, &$rocket.metrics);
+        let ($proto, $server) = ("http://", listener.map(hyper::Server::new));
         $continue
     })
 }
@@ -100,11 +277,17 @@ macro_rules! serve {
 macro_rules! serve {
     ($rocket:expr, $addr:expr, |$server:ident, $proto:ident| $continue:expr) => ({
         if let Some(tls) = $rocket.config.tls.clone() {
-            let tls = TlsServer::new(tls.certs, tls.key);
-            let ($proto, $server) = ("https://", hyper::Server::https($addr, tls));
+            let tls = match $rocket.tls.serve(&$rocket.config, tls, &$rocket.metrics) {
+                Ok(tls) => tls,
+                Err(e) => return LaunchError::new(LaunchErrorKind::TlsConfig(e))
+            };
+
+            let listener = listener::https($addr, &$rocket.config, &$rocket.metrics, tls);
+            let ($proto, $server) = ("https://", listener.map(hyper::Server::new));
             $continue
         } else {
-            let ($proto, $server) = ("http://", hyper::Server::http($addr));
+            let listener = listener::http($addr, &$rocket.config, &$rocket.metrics);
+            let ($proto, $server) = ("http://", listener.map(hyper::Server::new));
             $continue
         }
     })
@@ -125,6 +308,15 @@ impl Rocket {
         mut response: Response,
         mut hyp_res: hyper::FreshResponse,
     ) -> io::Result<()> {
+        // Responses are checked during dispatch, but the check is cheap enough
+        // to repeat here, where nothing can change the headers anymore.
+        if let Err(e) = response.headers().validate() {
+            *hyp_res.status_mut() = hyper::StatusCode::InternalServerError;
+            hyp_res.headers_mut().set(header::ContentLength(0));
+            hyp_res.start()?.end()?;
+            return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+        }
+
         *hyp_res.status_mut() = hyper::StatusCode::from_u16(response.status().code);
 
         for header in response.headers().iter() {
@@ -134,6 +326,7 @@ impl Rocket {
             hyp_res.headers_mut().append_raw(name, value);
         }
 
+        let flush_chunks = response.flushes_chunks();
         match response.body() {
             None => {
                 hyp_res.headers_mut().set(header::ContentLength(0));
@@ -153,12 +346,28 @@ impl Rocket {
                 }
 
                 // The buffer stores the current chunk being written out.
-                let mut buffer = vec![0; chunk_size as usize];
+                let mut buffer = self.buffers.get(chunk_size as usize);
                 let mut stream = hyp_res.start()?;
-                loop {
-                    match body.read_max(&mut buffer)? {
-                        0 => break,
-                        n => stream.write_all(&buffer[..n])?,
+                if !flush_chunks {
+                    loop {
+                        match body.read_max(&mut buffer)? {
+                            0 => break,
+                            n => stream.write_all(&buffer[..n])?,
+                        }
+                    }
+                } else {
+                    // Live streams, like server-sent events, are written out as
+                    // soon as any data is ready so that it reaches the client.
+                    loop {
+                        match body.read(&mut buffer) {
+                            Ok(0) => break,
+                            Ok(n) => {
+                                stream.write_all(&buffer[..n])?;
+                                stream.flush()?;
+                            }
+                            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                            Err(e) => return Err(e),
+                        }
                     }
                 }
 
@@ -191,6 +400,11 @@ impl Rocket {
                 }
             }
         }
+
+        // Determine the API version of the request, if versioning is enabled.
+        if let Some(ref versioning) = self.versioning {
+            versioning.negotiate(req, &self.router);
+        }
     }
 
     #[inline]
@@ -200,29 +414,321 @@ impl Rocket {
         data: Data
     ) -> Response<'r> {
         info!("{}:", request);
+        let start = Instant::now();
 
         // Do a bit of preprocessing before routing.
         self.preprocess_request(request, &data);
 
-        // Run the request fairings.
+        // Run the request fairings before any decisions are made about the
+        // request so that those decisions see any changes the fairings make.
+        let fairings_start = Instant::now();
         self.fairings.handle_request(request, &data);
+        request.update_timings(|t| t.fairings += fairings_start.elapsed());
+
+        // Turn every request away while in maintenance mode.
+        let maintenance = self.maintenance.load(Ordering::Acquire);
+
+        // Reject requests for hosts that aren't allowed, if any are configured.
+        let host_error = match self.config.allowed_hosts.is_empty() {
+            true => None,
+            false => Host::check(request, &self.config.allowed_hosts).err()
+        };
+
+        // Redirect plaintext requests that ask to be upgraded, if configured.
+        let upgrade = match maintenance {
+            true => None,
+            false => self.upgrade_location(request)
+        };
+
+        // Open the request's body if it's sealed in an envelope.
+        let (mut data, envelope) = match host_error.is_none() && upgrade.is_none() && !maintenance {
+            true => self.open_envelope(request, data),
+            false => (data, Ok(None))
+        };
+
+        let (envelope_key, envelope_error) = match envelope {
+            Ok(key) => (key, None),
+            Err(status) => (None, Some(status))
+        };
+
+        // Arrange to verify the body against its checksum, if requested. This
+        // happens before decompression: checksums cover the encoded body.
+        let checksum = match self.config.verify_checksums {
+            true => Checksum::expected(request.headers()).map(|c| data.verify_checksum(c)),
+            false => None
+        };
+
+        // Decompress the request's body if it's compressed, if configured.
+        let (data, decoding_error) = match self.config.compression {
+            Some(ref settings) if host_error.is_none() && upgrade.is_none() && !maintenance
+                && envelope_error.is_none() => {
+                let limit = self.config.limits.get("decompressed");
+                compression::decompress(settings, request, data, limit)
+            }
+            _ => (data, None)
+        };
+
+        // Look up the request's idempotency key, if idempotency is enabled.
+        let (data, lookup) = match self.idempotency {
+            Some(ref idempotency) if host_error.is_none() && upgrade.is_none() && !maintenance
+                && envelope_error.is_none() && decoding_error.is_none()
+                && !request.cookies_overflowed() => {
+                let limit = self.config.limits.get("idempotency");
+                idempotency.lookup(request, data, limit)
+            }
+            _ => (data, Lookup::None)
+        };
+
+        let lookup = match (host_error, envelope_error, decoding_error) {
+            _ if maintenance => {
+                info_!("Rejecting request in maintenance mode.");
+                Lookup::Reject(Status::ServiceUnavailable)
+            }
+            (Some(status), _, _) => {
+                error_!("Request for a host that isn't allowed.");
+                Lookup::Reject(status)
+            }
+            (None, Some(status), _) => {
+                error_!("Request envelope was rejected.");
+                Lookup::Reject(status)
+            }
+            (None, None, Some(status)) => {
+                error_!("Request body could not be decompressed.");
+                Lookup::Reject(status)
+            }
+            (None, None, None) if request.cookies_overflowed() => {
+                error_!("Request cookies exceed the configured limits.");
+                Lookup::Reject(Status::RequestHeaderFieldsTooLarge)
+            }
+            (None, None, None) => lookup
+        };
 
         // Remember if the request is a `HEAD` request for later body stripping.
         let was_head_request = request.method() == Method::Head;
 
-        // Route the request and run the user's handlers.
-        let mut response = self.route_and_process(request, data);
+        // Route the request and run the user's handlers unless the request is
+        // a retry whose response was recorded.
+        let (mut response, pending) = match (upgrade, lookup) {
+            (Some(location), _) => (upgrade_response(location), None),
+            (None, Lookup::None) => (self.route_and_process(request, data), None),
+            (None, Lookup::Record(p)) => (self.route_and_process(request, data), Some(p)),
+            (None, Lookup::Replay(recorded)) => (recorded.to_response(), None),
+            (None, Lookup::Reject(status)) => (self.handle_error(status, request), None),
+        };
+
+        // Enforce the route's or the configured limit on the response body.
+        self.limit_response_body(request, &mut response);
+
+        // Cut off a streamed response body once the route's timeout elapses.
+        if let Some(deadline) = request.deadline() {
+            match response.take_body() {
+                Some(Body::Chunked(body, chunk_size)) => {
+                    let body = DeadlineBody {
+                        inner: body,
+                        deadline: deadline,
+                        timed_out: self.metrics.counter("requests.timed_out"),
+                    };
+
+                    response.set_chunked_body(body, chunk_size);
+                }
+                Some(body) => response.set_raw_body(body),
+                None => {}
+            }
+        }
+
+        // Fail the request if the body that was read didn't match its checksum.
+        if checksum.map_or(false, |state| state.is_mismatch()) {
+            error_!("Request body does not match its checksum.");
+            response = self.handle_error(Status::UnprocessableEntity, request);
+        }
+
+        // Record the response for retries with the same idempotency key.
+        if let Some(pending) = pending {
+            pending.record(&mut response);
+        }
+
+        // Mark responses to requests for deprecated API versions.
+        if let Some(ref versioning) = self.versioning {
+            versioning.annotate(request, &mut response);
+        }
+
+        // Mark responses from deprecated routes.
+        if let Some(deprecation) = request.route().and_then(|r| r.deprecation.as_ref()) {
+            deprecation.annotate(&mut response);
+        }
 
-        // Add the 'rocket' server header to the response and run fairings.
+        // Add the configured default headers to the response and run fairings.
         // TODO: If removing Hyper, write out `Date` header too.
-        response.set_header(Header::new("Server", "Rocket"));
+        self.set_default_headers(&mut response);
+        let fairings_start = Instant::now();
         self.fairings.handle_response(request, &mut response);
+        request.update_timings(|t| t.fairings += fairings_start.elapsed());
+
+        // Compress the response body for clients that accept it, if configured.
+        compression::compress(request, &mut response);
+
+        // Seal the response body for clients that use envelopes.
+        self.seal_envelope(envelope_key, request, &mut response);
+
+        // Digest the final response body, if configured.
+        if let Some(algorithm) = self.config.response_digest {
+            add_response_digest(algorithm, request, &mut response);
+        }
+
+        // Remove or rewrite any headers the configuration deems sensitive.
+        self.config.header_policy.apply(&mut response);
+
+        // Never write a header that would split the response or inject others.
+        if let Err(e) = response.headers().validate() {
+            error_!("Response has an invalid header: {}.", e);
+            self.metrics.counter("responses.invalid_headers").incr();
+            response = self.handle_error(Status::InternalServerError, request);
+        }
 
         // Strip the body if this is a `HEAD` request.
         if was_head_request {
             response.strip_body();
         }
 
+        if request.memory_exceeded() {
+            self.metrics.counter("requests.memory_exceeded").incr();
+        }
+
+        request.update_timings(|t| t.total = start.elapsed());
+
+        // Alert the application to server errors, if it asked to be.
+        if let Some(ref hook) = self.error_hook {
+            hook.handle(request, &response);
+        }
+
+        response
+    }
+
+    /// Resolves the envelope key of the client making `request` and opens the
+    /// request's body if it's sealed, if envelopes are enabled.
+    #[cfg(feature = "envelope")]
+    fn open_envelope(&self, request: &mut Request, data: Data)
+        -> (Data, Result<Option<EnvelopeKey>, Status>)
+    {
+        match self.envelope {
+            Some(ref envelope) => {
+                let limit = self.config.limits.get("envelope");
+                envelope.open(request, data, limit)
+            }
+            None => (data, Ok(None))
+        }
+    }
+
+    #[cfg(not(feature = "envelope"))]
+    #[inline(always)]
+    fn open_envelope(&self, _: &mut Request, data: Data) -> (Data, Result<Option<()>, Status>) {
+        (data, Ok(None))
+    }
+
+    /// Seals the body of `response` with `key`, if there is one.
+    #[cfg(feature = "envelope")]
+    fn seal_envelope(&self, key: Option<EnvelopeKey>, request: &Request, response: &mut Response) {
+        if let (Some(envelope), Some(key)) = (self.envelope.as_ref(), key) {
+            envelope.seal(&key, request, response);
+        }
+    }
+
+    #[cfg(not(feature = "envelope"))]
+    #[inline(always)]
+    fn seal_envelope(&self, _: Option<()>, _: &Request, _: &mut Response) {  }
+
+    /// Sets the `Server` header in `response` to the configured server name, if
+    /// there is one, and the `Strict-Transport-Security` header to the
+    /// configured HSTS policy, if there is one and TLS is enabled.
+    fn set_default_headers(&self, response: &mut Response) {
+        if let Some(ref name) = self.config.server_name {
+            response.set_raw_header("Server", name.clone());
+        }
+
+        if let (Some(hsts), true) = (self.config.hsts, self.config.tls_enabled()) {
+            response.set_raw_header("Strict-Transport-Security", hsts.to_string());
+        }
+    }
+
+    /// Enforces the response body limit of the route that handled `request`,
+    /// or the configured `response` limit, on `response`. A sized body over
+    /// the limit turns the response into a `500`; a streamed body is cut off
+    /// with an error once it exceeds the limit.
+    fn limit_response_body<'r>(&self, request: &'r Request, response: &mut Response<'r>) {
+        let limit = request.route().and_then(|route| route.response_limit)
+            .or_else(|| self.config.limits.get("response"));
+
+        let limit = match limit {
+            Some(limit) => limit,
+            None => return
+        };
+
+        match response.take_body() {
+            Some(Body::Sized(_, size)) if size > limit => {
+                error_!("Response body of {} bytes exceeds the limit of {} bytes.", size, limit);
+                *response = self.handle_error(Status::InternalServerError, request);
+            }
+            Some(Body::Sized(body, size)) => response.set_raw_body(Body::Sized(body, size)),
+            Some(Body::Chunked(body, chunk_size)) => {
+                let body = LimitedBody { inner: body, remaining: limit };
+                response.set_chunked_body(body, chunk_size);
+            }
+            None => {}
+        }
+    }
+
+    /// Returns the HTTPS URI that `request` should be redirected to if it was
+    /// received over plaintext, asks to be upgraded, and upgrades are enabled.
+    /// Requests are never redirected by a plaintext server on port 443: the
+    /// redirect would target the server itself.
+    fn upgrade_location(&self, request: &Request) -> Option<String> {
+        let config = &self.config;
+        if !config.upgrade_insecure_requests || request.scheme() == "https" {
+            return None;
+        }
+
+        // Upgrading to the port the request was made on would loop, as would
+        // upgrading a request a TLS-terminating proxy received over HTTPS.
+        if config.port == config.https_port || self.forwarded_https(request) {
+            return None;
+        }
+
+        let upgrade = request.headers().get_one("Upgrade-Insecure-Requests");
+        if upgrade.map(|value| value.trim()) != Some("1") {
+            return None;
+        }
+
+        let host = Host::check(request, &[]).ok()?;
+        match config.https_port {
+            443 => Some(format!("https://{}{}", host.name(), request.uri())),
+            port => Some(format!("https://{}:{}{}", host.name(), port, request.uri())),
+        }
+    }
+
+    // Whether a trusted proxy reports, via the last `X-Forwarded-Proto` value,
+    // the one it set, that `request` was made over HTTPS.
+    fn forwarded_https(&self, request: &Request) -> bool {
+        let trusted = match request.remote() {
+            Some(remote) => self.config.trusted_proxies.iter()
+                .filter_map(|network| listener::IpNetwork::parse(network))
+                .any(|network| network.contains(remote.ip())),
+            None => false
+        };
+
+        let proto = request.headers().get("X-Forwarded-Proto")
+            .flat_map(|value| value.split(','))
+            .last()
+            .map(|proto| proto.trim());
+
+        trusted && proto.map_or(false, |proto| proto.eq_ignore_ascii_case("https"))
+    }
+
+    // Responds to a request that failed before it could be dispatched. These
+    // responses skip fairings but otherwise get the same headers as any other.
+    fn handle_early_error<'r>(&self, status: Status, req: &'r Request) -> Response<'r> {
+        let mut response = self.handle_error(status, req);
+        self.set_default_headers(&mut response);
+        self.config.header_policy.apply(&mut response);
         response
     }
 
@@ -234,8 +740,31 @@ impl Rocket {
     ) -> Response<'r> {
         match self.route(request, data) {
             Outcome::Success(mut response) => {
-                // A user's route responded! Set the cookies.
-                for cookie in request.cookies().delta() {
+                // A user's route responded! Set the cookies, within limits.
+                let mut cookies: Vec<_> = request.cookies().delta().cloned().collect();
+                let limits = self.config.cookie_limits;
+                let sizes: Vec<_> = cookies.iter().map(|c| c.to_string().len()).collect();
+                if limits.excess(&sizes) > 0 {
+                    match limits.overflow_policy() {
+                        CookieOverflow::Reject => {
+                            error_!("Response cookies exceed the limits ({}).", limits);
+                            return self.handle_error(Status::InternalServerError, request);
+                        }
+                        CookieOverflow::TruncateOldest => match truncate_cookies(request, limits) {
+                            Some(truncated) => cookies = truncated,
+                            None => {
+                                error_!("Response cookies exceed the limits ({}) and can't \
+                                        be truncated.", limits);
+                                return self.handle_error(Status::InternalServerError, request);
+                            }
+                        },
+                        CookieOverflow::Log => {
+                            warn_!("Response cookies exceed the limits ({}).", limits);
+                        }
+                    }
+                }
+
+                for cookie in cookies {
                     response.adjoin_header(cookie);
                 }
 
@@ -246,8 +775,11 @@ impl Rocket {
                 if request.method() == Method::Head {
                     info_!("Autohandling {} request.", Paint::white("HEAD"));
 
-                    // Dispatch the request again with Method `GET`.
+                    // Dispatch the request again with Method `GET`. Routes with
+                    // a `head_length` function answer from it once their
+                    // guards have succeeded instead of running the handler.
                     request._set_method(Method::Get);
+                    request.set_autohandled_head();
                     self.route_and_process(request, data)
                 } else {
                     // No match was found and it can't be autohandled. 404.
@@ -276,18 +808,63 @@ impl Rocket {
         mut data: Data,
     ) -> handler::Outcome<'r> {
         // Go through the list of matching routes until we fail or succeed.
-        let matches = self.router.route(request);
-        for route in matches {
-            // Retrieve and set the requests parameters.
+        // Dynamic routes are only tried once all of the mounted routes forward.
+        let routing_start = Instant::now();
+        let mut matches: Vec<_> = self.router.route(request).into_iter()
+            .map(Matched::Mounted)
+            .collect();
+
+        if let Some(ref dynamic) = self.dynamic {
+            matches.extend(dynamic.route(request).into_iter().map(Matched::Dynamic));
+        }
+
+        request.update_timings(|t| t.routing += routing_start.elapsed());
+        for matched in matches {
+            // Retrieve and set the requests parameters. The request keeps a
+            // dynamic route alive even if it's removed while it's handled.
+            let route = match matched {
+                Matched::Mounted(route) => {
+                    request.set_route(route);
+                    route
+                }
+                Matched::Dynamic(route) => request.set_dynamic_route(route),
+            };
+
             info_!("Matched: {}", route);
-            request.set_route(route);
 
-            // Dispatch the request to the handler.
-            let outcome = (route.handler)(request, data);
+            // Dispatch the request to the handler, sharing the execution with
+            // identical in-flight requests if the route is coalesced.
+            let handler_start = Instant::now();
+            request.set_deadline(route.timeout.map(|timeout| handler_start + timeout));
+            let outcome = if route.coalesce && request.method() == Method::Get {
+                self.coalescer.handle(route, request, data)
+            } else {
+                (route.handler)(request, data)
+            };
+
+            // Split the time into guards and handler if the handler marked the
+            // completion of its guards.
+            let guards_complete = request.take_guards_complete();
+            request.update_timings(|t| match guards_complete {
+                Some(complete) => {
+                    t.guards += complete.duration_since(handler_start);
+                    t.handler += complete.elapsed();
+                }
+                None => t.handler += handler_start.elapsed()
+            });
 
             // Check if the request processing completed or if the request needs
             // to be forwarded. If it does, continue the loop to try again.
             info_!("{} {}", Paint::white("Outcome:"), outcome);
+            if let Some(deadline) = request.deadline() {
+                if Instant::now() >= deadline {
+                    error_!("Handler exceeded the route's timeout.");
+                    self.metrics.counter("requests.timed_out").incr();
+                    request.set_deadline(None);
+                    return Outcome::Failure(Status::ServiceUnavailable);
+                }
+            }
+
             match outcome {
                 o@Outcome::Success(_) | o@Outcome::Failure(_) => return o,
                 Outcome::Forward(unused_data) => data = unused_data,
@@ -378,6 +955,57 @@ impl Rocket {
         Rocket::configured(config, log)
     }
 
+    /// Creates a new `Rocket` application using the configuration selected by
+    /// the program's command-line arguments.
+    ///
+    /// The arguments select the configuration file and environment and set
+    /// configuration parameters over those read from the file and from
+    /// environment variables: `--config <path>`, `--env <environment>`,
+    /// `--address <address>`, `--port <port>`, and `name=value` pairs. See
+    /// [`Args`](/rocket/config/struct.Args.html) for details. To combine the
+    /// arguments with other configuration in code, use `Args` directly.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ArgsError::Help` if `--help` is passed, an error describing
+    /// the first invalid argument if the arguments are invalid, and
+    /// `ArgsError::Config` if there is an error reading the configuration.
+    /// Nothing is printed: the application decides whether to print the
+    /// [`Args::usage()`](/rocket/config/struct.Args.html#method.usage) message
+    /// and exit.
+    ///
+    /// # Example
+    ///
+    /// Running the application below as `./app --env prod --port 80
+    /// workers=16` launches it in the production environment on port 80 with
+    /// 16 workers.
+    ///
+    /// ```rust
+    /// use std::{env, process};
+    /// use rocket::config::{Args, ArgsError};
+    ///
+    /// # if false {
+    /// let rocket = match rocket::Rocket::from_args() {
+    ///     Ok(rocket) => rocket,
+    ///     Err(ArgsError::Help) => {
+    ///         println!("{}", Args::usage(&env::args().next().unwrap()));
+    ///         process::exit(0)
+    ///     }
+    ///     Err(e) => {
+    ///         eprintln!("error: {}", e);
+    ///         process::exit(2)
+    ///     }
+    /// };
+    ///
+    /// rocket.launch();
+    /// # }
+    /// ```
+    #[inline]
+    pub fn from_args() -> Result<Rocket, config::ArgsError> {
+        let config = config::init_from_args(::std::env::args())?;
+        Ok(Rocket::configured(config, true))
+    }
+
     #[inline]
     fn configured(config: Config, log: bool) -> Rocket {
         if log {
@@ -394,11 +1022,51 @@ impl Rocket {
         launch_info_!("secret key: {}", Paint::white(&config.secret_key));
         launch_info_!("limits: {}", Paint::white(&config.limits));
 
+        match config.server_name {
+            Some(ref name) => launch_info_!("server name: {}", Paint::white(name)),
+            None => launch_info_!("server name: {}", Paint::white("omitted")),
+        }
+
+        if !config.allowed_hosts.is_empty() {
+            launch_info_!("allowed hosts: {}", Paint::white(config.allowed_hosts.join(", ")));
+        }
+
+        if !config.header_policy.is_empty() {
+            launch_info_!("header policy: {}", Paint::white(&config.header_policy));
+        }
+
         match config.keep_alive {
             Some(v) => launch_info_!("keep-alive: {}", Paint::white(format!("{}s", v))),
             None => launch_info_!("keep-alive: {}", Paint::white("disabled")),
         }
 
+        if let Some(max) = config.max_idle_connections {
+            launch_info_!("max idle connections: {}", Paint::white(max));
+        }
+
+        if let Some(max) = config.max_connections {
+            launch_info_!("max connections: {}", Paint::white(max));
+        }
+
+        if let Some(max) = config.max_requests_per_connection {
+            launch_info_!("max requests per connection: {}", Paint::white(max));
+        }
+
+        if let Some(backlog) = config.backlog {
+            launch_info_!("backlog: {}", Paint::white(backlog));
+        }
+
+        if let Some(max) = config.max_connections_per_ip {
+            launch_info_!("max connections per ip: {}", Paint::white(max));
+            if !config.connection_limit_exempt.is_empty() {
+                let exempt = config.connection_limit_exempt.join(", ");
+                launch_info_!("connection limit exempt: {}", Paint::white(exempt));
+            }
+        }
+
+        launch_info_!("max header size: {}", Paint::white(config.max_header_size));
+        launch_info_!("max header count: {}", Paint::white(config.max_header_count));
+
         let tls_configured = config.tls.is_some();
         if tls_configured && cfg!(feature = "tls") {
             launch_info_!("tls: {}", Paint::white("enabled"));
@@ -409,6 +1077,78 @@ impl Rocket {
             launch_info_!("tls: {}", Paint::white("disabled"));
         }
 
+        if let (true, Some(timeout)) = (config.tls_enabled(), config.tls_handshake_timeout) {
+            launch_info_!("tls handshake timeout: {}s", Paint::white(timeout));
+        }
+
+        if config.tls_enabled() && !config.tls_sni.is_empty() {
+            let hostnames: Vec<_> = config.tls_sni.iter().map(|&(ref name, _)| &**name).collect();
+            launch_info_!("tls sni: {}", Paint::white(hostnames.join(", ")));
+        }
+
+        if config.tls_enabled() && !config.tls_alpn.is_empty() {
+            launch_info_!("tls alpn: {}", Paint::white(config.tls_alpn.join(", ")));
+            if config.tls_alpn.iter().any(|protocol| !protocol.starts_with("http/1.")) {
+                warn_!("ALPN advertises protocols other than HTTP/1.x, which Rocket doesn't serve");
+            }
+        }
+
+        if config.tls_enabled() && !config.tls_versions.is_empty() {
+            launch_info_!("tls versions: {}", Paint::white(config.tls_versions.join(", ")));
+        }
+
+        if config.tls_enabled() && !config.tls_ciphersuites.is_empty() {
+            let ciphersuites = config.tls_ciphersuites.join(", ");
+            launch_info_!("tls ciphersuites: {}", Paint::white(ciphersuites));
+        }
+
+        if config.tls_enabled() && config.tls_ocsp.is_some() {
+            launch_info_!("ocsp stapling: {}", Paint::white("enabled"));
+            if let Some(secs) = config.tls_ocsp_refresh {
+                launch_info_!("ocsp refresh interval: {}s", Paint::white(secs));
+            }
+        }
+
+        if config.mutual_tls != config::MutualTls::Disabled {
+            match config.tls_enabled() {
+                true => launch_info_!("mutual tls: {}", Paint::white(config.mutual_tls)),
+                false => warn_!("mutual tls is configured, but tls is disabled"),
+            }
+        }
+
+        if let Some(algorithm) = config.response_digest {
+            launch_info_!("response digest: {}", Paint::white(algorithm.name()));
+        }
+
+        if config.cookie_limits != Default::default() {
+            launch_info_!("cookie limits: {}", Paint::white(config.cookie_limits));
+        }
+
+        if let Some(ref compression) = config.compression {
+            launch_info_!("compression: {}", Paint::white(compression));
+        }
+
+        if let Some(encoding) = config.text_encoding {
+            launch_info_!("text encoding: {}", Paint::white(encoding.name()));
+        }
+
+        if let Some(hsts) = config.hsts {
+            match config.tls_enabled() {
+                true => launch_info_!("hsts: {}", Paint::white(hsts)),
+                false => warn_!("hsts is configured, but tls is disabled"),
+            }
+        }
+
+        if config.upgrade_insecure_requests && !config.tls_enabled() {
+            match config.port == config.https_port {
+                true => warn_!("upgrade-insecure-requests: {} (serving plaintext on \
+                               the https port would cause a redirect loop)",
+                               Paint::white("disabled")),
+                false => launch_info_!("upgrade-insecure-requests: {} (to port {})",
+                                       Paint::white("enabled"), Paint::white(config.https_port)),
+            }
+        }
+
         if config.secret_key.is_generated() && config.environment.is_prod() {
             warn!("environment is 'production', but no `secret_key` is configured");
         }
@@ -420,6 +1160,21 @@ impl Rocket {
                           Paint::white(LoggedValue(value)));
         }
 
+        let mut features: Vec<_> = config.features.iter().collect();
+        features.sort();
+        for (name, enabled) in features {
+            let state = if *enabled { "enabled" } else { "disabled" };
+            launch_info_!("{} {}: {}", Paint::yellow("[feature]"),
+                          Paint::blue(name), Paint::white(state));
+        }
+
+        let metrics = Metrics::new();
+        let features = Features::new(config.features.clone());
+        let keep_alive = config.keep_alive.map(|s| Duration::from_secs(s as u64));
+        let max_idle = config.max_idle_connections.map(|n| n as usize);
+        let max_requests = config.max_requests_per_connection.map(|n| n as usize);
+        let connections = Connections::new(&metrics, keep_alive, max_idle, max_requests);
+
         Rocket {
             config: config,
             router: Router::new(),
@@ -427,6 +1182,23 @@ impl Rocket {
             catchers: catcher::defaults::get(),
             state: Container::new(),
             fairings: Fairings::new(),
+            metrics: metrics,
+            connections: Arc::new(connections),
+            coalescer: Coalescer::new(),
+            buffers: BufferPool::new(),
+            idempotency: None,
+            error_hook: None,
+            #[cfg(feature = "envelope")]
+            envelope: None,
+            admin: None,
+            maintenance: Arc::new(AtomicBool::new(false)),
+            tls: TlsReloader::new(),
+            dynamic: None,
+            warm_ups: vec![],
+            ready: Arc::new(AtomicBool::new(false)),
+            features: features,
+            versioning: None,
+            attach_error: None,
         }
     }
 
@@ -507,6 +1279,44 @@ impl Rocket {
         self
     }
 
+    /// Dedicates the mount point `base` to dynamic routes: routes that can be
+    /// added and removed while Rocket is running, even after launch, via the
+    /// handle returned by [`dynamic_routes()`](#method.dynamic_routes). See
+    /// the [`dynamic`](/rocket/dynamic/index.html) module for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `base` isn't a static, absolute path, as with
+    /// [`mount()`](#method.mount), or if a mount point for dynamic routes was
+    /// already set.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let rocket = rocket::ignite().mount_dynamic("/plugins");
+    /// assert_eq!(rocket.dynamic_routes().unwrap().base(), "/plugins");
+    /// ```
+    pub fn mount_dynamic(mut self, base: &str) -> Self {
+        info!("{}{} '{}' for dynamic routes.",
+              Paint::masked("🛰  "),
+              Paint::purple("Mounting"),
+              Paint::blue(base));
+
+        if base.contains('<') || !base.starts_with('/') {
+            error_!("Bad mount point: '{}'.", base);
+            error_!("Mount points must be static, absolute URIs: `/example`");
+            panic!("Bad mount point.")
+        }
+
+        if let Some(ref dynamic) = self.dynamic {
+            error_!("Dynamic routes are already mounted at '{}'.", dynamic.base());
+            panic!("Duplicate dynamic mount point.")
+        }
+
+        self.dynamic = Some(DynamicRoutes::new(base));
+        self
+    }
+
     /// Registers all of the catchers in the supplied vector.
     ///
     /// # Examples
@@ -633,13 +1443,228 @@ impl Rocket {
         self
     }
 
-    pub(crate) fn prelaunch_check(&self) -> Option<LaunchError> {
+    /// Fails the attach fairing that is currently running with `error`. The
+    /// result should be returned from the fairing's `on_attach` callback.
+    /// Launch will fail, reporting `error` along with the name of the fairing
+    /// and any other attach failures. See
+    /// [`AttachError`](/rocket/fairing/struct.AttachError.html) for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::fairing::{AdHoc, AttachError};
+    ///
+    /// let rocket = rocket::ignite()
+    ///     .attach(AdHoc::on_attach(|rocket| {
+    ///         if rocket.config().get_str("api_token").is_ok() {
+    ///             return Ok(rocket);
+    ///         }
+    ///
+    ///         rocket.fail_attach(AttachError::new("`api_token` is not configured"))
+    ///     }));
+    /// ```
+    #[inline]
+    pub fn fail_attach(mut self, error: AttachError) -> Result<Rocket, Rocket> {
+        self.attach_error = Some(error);
+        Err(self)
+    }
+
+    /// Enables handling of the `Idempotency-Key` header for `POST` requests,
+    /// recording and replaying responses as configured by `idempotency`. See
+    /// the [`idempotency`](/rocket/idempotency/index.html) module for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::idempotency::{Idempotency, MemoryStore};
+    ///
+    /// fn main() {
+    /// # if false { // We don't actually want to launch the server in an example.
+    ///     rocket::ignite()
+    ///         .idempotency(Idempotency::new(MemoryStore::new()))
+    ///         .launch();
+    /// # }
+    /// }
+    /// ```
+    #[inline]
+    pub fn idempotency(mut self, idempotency: Idempotency) -> Self {
+        self.idempotency = Some(idempotency);
+        self
+    }
+
+    /// Registers `hook` to be called with an alert for every response with a
+    /// `5xx` status, throttled per route. See the
+    /// [`alert`](/rocket/alert/index.html) module for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::alert::ErrorHook;
+    ///
+    /// fn main() {
+    /// # if false { // We don't actually want to launch the server in an example.
+    ///     rocket::ignite()
+    ///         .on_error_response(ErrorHook::new(|alert| eprintln!("{}", alert)))
+    ///         .launch();
+    /// # }
+    /// }
+    /// ```
+    #[inline]
+    pub fn on_error_response(mut self, hook: ErrorHook) -> Self {
+        self.error_hook = Some(hook);
+        self
+    }
+
+    /// Enables sealing of request and response bodies in envelopes as
+    /// configured by `envelope`. See the
+    /// [`envelope`](/rocket/envelope/index.html) module for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::envelope::{Envelope, KeyRing};
+    ///
+    /// fn main() {
+    /// # if false { // We don't actually want to launch the server in an example.
+    ///     rocket::ignite()
+    ///         .envelope(Envelope::new(KeyRing::new()))
+    ///         .launch();
+    /// # }
+    /// }
+    /// ```
+    #[cfg(feature = "envelope")]
+    #[inline]
+    pub fn envelope(mut self, envelope: Envelope) -> Self {
+        self.envelope = Some(envelope);
+        self
+    }
+
+    /// Enables the local admin channel as configured by `admin`. The channel
+    /// is opened when the application launches. See the
+    /// [`admin`](/rocket/admin/index.html) module for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::admin::Admin;
+    ///
+    /// fn main() {
+    /// # if false { // We don't actually want to launch the server in an example.
+    ///     rocket::ignite()
+    ///         .admin(Admin::loopback(8001, "s3cr3t"))
+    ///         .launch();
+    /// # }
+    /// }
+    /// ```
+    #[inline]
+    pub fn admin(mut self, admin: Admin) -> Self {
+        self.admin = Some(admin);
+        self
+    }
+
+    /// Registers a warm-up request: a `method` request to `uri` that is
+    /// dispatched, like a local request, after the launch fairings have run
+    /// but before the first connection is accepted. Warm-up requests run in
+    /// the order in which they were registered and can be used to prime
+    /// caches, connection pools, and lazily initialized values.
+    ///
+    /// Once all warm-up requests have completed, the application is
+    /// [ready](#method.is_ready). A warm-up request that fails with a server
+    /// error is logged but doesn't prevent the application from launching.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::http::Method;
+    ///
+    /// fn main() {
+    /// # if false { // We don't actually want to launch the server in an example.
+    ///     rocket::ignite()
+    ///         .warm_up(Method::Get, "/")
+    ///         .warm_up(Method::Get, "/search?q=rocket")
+    ///         .launch();
+    /// # }
+    /// }
+    /// ```
+    #[inline]
+    pub fn warm_up<U: Into<String>>(mut self, method: Method, uri: U) -> Self {
+        self.warm_ups.push((method, uri.into()));
+        self
+    }
+
+    /// Returns `true` once the application has run its
+    /// [warm-up requests](#method.warm_up) and is about to accept, or is
+    /// accepting, connections. A [`Client`] is ready once it has been
+    /// constructed.
+    ///
+    /// [`Client`]: /rocket/local/struct.Client.html
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    ///
+    /// let rocket = rocket::ignite();
+    /// assert!(!rocket.is_ready());
+    ///
+    /// let client = Client::new(rocket).unwrap();
+    /// assert!(client.rocket().is_ready());
+    /// ```
+    #[inline(always)]
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Acquire)
+    }
+
+    // Dispatches the warm-up requests, then marks `self` as ready.
+    pub(crate) fn warm(&self) {
+        if !self.warm_ups.is_empty() {
+            info!("{}{}:", Paint::masked("🔥  "), Paint::purple("Warming up"));
+        }
+
+        for &(method, ref uri) in &self.warm_ups {
+            let mut request = Request::new(self, method, uri.as_str());
+            let response = self.dispatch(&mut request, Data::local(vec![]));
+            match response.status().class() {
+                StatusClass::ServerError => {
+                    warn_!("{} {}: {}", method, uri, Paint::red(response.status()));
+                }
+                _ => info_!("{} {}: {}", method, uri, Paint::green(response.status())),
+            }
+        }
+
+        self.ready.store(true, Ordering::Release);
+    }
+
+    /// Enables API version negotiation as configured by `versioning`, routing
+    /// requests only to routes for the negotiated version. See the
+    /// [`versioning`](/rocket/versioning/index.html) module for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::versioning::Versioning;
+    ///
+    /// fn main() {
+    /// # if false { // We don't actually want to launch the server in an example.
+    ///     rocket::ignite()
+    ///         .versioning(Versioning::path_prefix().default_version("v1"))
+    ///         .launch();
+    /// # }
+    /// }
+    /// ```
+    #[inline]
+    pub fn versioning(mut self, versioning: Versioning) -> Self {
+        self.versioning = Some(versioning);
+        self
+    }
+
+    pub(crate) fn prelaunch_check(&mut self) -> Option<LaunchError> {
         let collisions = self.router.collisions();
         if !collisions.is_empty() {
             let owned = collisions.iter().map(|&(a, b)| (a.clone(), b.clone()));
             Some(LaunchError::new(LaunchErrorKind::Collision(owned.collect())))
-        } else if let Some(failures) = self.fairings.failures() {
-            Some(LaunchError::new(LaunchErrorKind::FailedFairings(failures.to_vec())))
+        } else if let Some(failures) = self.fairings.take_failures() {
+            Some(LaunchError::new(LaunchErrorKind::FailedFairings(failures)))
         } else {
             None
         }
@@ -654,9 +1679,14 @@ impl Rocket {
     /// If there is a problem starting the application, a [`LaunchError`] is
     /// returned. Note that a value of type `LaunchError` panics if dropped
     /// without first being inspected. See the [`LaunchError`] documentation for
-    /// more information.
+    /// more information. [`LaunchError::exit_code()`] maps the error to a
+    /// process exit code.
+    ///
+    /// Launching fails with a `TlsConfig` error if TLS is configured but the
+    /// `tls` feature is disabled.
     ///
     /// [`LaunchError`]: /rocket/error/struct.LaunchError.html
+    /// [`LaunchError::exit_code()`]: /rocket/error/struct.LaunchError.html#method.exit_code
     ///
     /// # Example
     ///
@@ -670,10 +1700,16 @@ impl Rocket {
             return error;
         }
 
+        // Refuse to serve plaintext when TLS was asked for but can't be used.
+        if self.config.tls.is_some() && !cfg!(feature = "tls") {
+            let msg = "tls is configured, but the tls feature is disabled";
+            return LaunchError::new(LaunchErrorKind::TlsConfig(msg.into()));
+        }
+
         self.fairings.pretty_print_counts();
 
         let full_addr = format!("{}:{}", self.config.address, self.config.port);
-        serve!(self, &full_addr, |server, proto| {
+        serve!(self, &full_addr, |server, _proto| {
             let mut server = match server {
                 Ok(server) => server,
                 Err(e) => return LaunchError::new(LaunchErrorKind::Bind(e)),
@@ -689,18 +1725,56 @@ impl Rocket {
             let timeout = self.config.keep_alive.map(|s| Duration::from_secs(s as u64));
             server.keep_alive(timeout);
 
+            // Periodically close connections that have idled past the timeout.
+            if timeout.is_some() {
+                let connections = self.connections.clone();
+                let reaper = thread::Builder::new().name("rocket-reaper".into());
+                let spawned = reaper.spawn(move || loop {
+                    thread::sleep(Duration::from_secs(1));
+                    connections.reap();
+                });
+
+                if let Err(e) = spawned {
+                    return LaunchError::from(e);
+                }
+            }
+
             // Freeze managed state for synchronization-free accesses later.
             self.state.freeze();
 
+            // Open the admin channel, if one is configured.
+            if let Some(admin) = self.admin.take() {
+                let address = admin.address().to_string();
+                let control = Control {
+                    routes: self.routes().map(|r| {
+                        let deprecated = if r.deprecation.is_some() { " deprecated" } else { "" };
+                        format!("{} {} {}{}", r.method, r.uri, r.rank, deprecated)
+                    }).collect(),
+                    workers: self.config.workers,
+                    connections: self.connections.clone(),
+                    maintenance: self.maintenance.clone(),
+                    ready: self.ready.clone(),
+                    tls: self.tls.clone(),
+                };
+
+                if let Err(e) = admin.start(control) {
+                    return LaunchError::from(e);
+                }
+
+                launch_info_!("admin: {}", Paint::white(address));
+            }
+
             // Run the launch fairings.
             self.fairings.handle_launch(&self);
 
-            let full_addr = format!("{}:{}", self.config.address, self.config.port);
-            launch_info!("{}{} {}{}",
+            // Prime the application before accepting any connections.
+            self.warm();
+
+            let summary = self.launch_summary();
+            launch_info!("{}{} {}",
                          Paint::masked("🚀  "),
                          Paint::white("Rocket has launched from"),
-                         Paint::white(proto).bold(),
-                         Paint::white(&full_addr).bold());
+                         Paint::white(summary.url()).bold());
 
             // Restore the log level back to what it originally was.
             logger::pop_max_level();
@@ -797,4 +1871,140 @@ impl Rocket {
     pub fn config(&self) -> &Config {
         &self.config
     }
+
+    /// Returns a structured description of this instance of Rocket: its
+    /// configuration highlights, bound address, routes, catchers, and
+    /// fairings. See the [`summary`](/rocket/summary/index.html) module
+    /// documentation for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #![feature(plugin, decl_macro)]
+    /// # #![plugin(rocket_codegen)]
+    /// # extern crate rocket;
+    /// #[get("/hello")]
+    /// fn hello() -> &'static str {
+    ///     "Hello, world!"
+    /// }
+    ///
+    /// fn main() {
+    ///     let rocket = rocket::ignite().mount("/", routes![hello]);
+    ///     let summary = rocket.launch_summary();
+    ///     assert_eq!(summary.routes[0].method, "GET");
+    ///     assert_eq!(summary.routes[0].uri, "/hello");
+    /// }
+    /// ```
+    pub fn launch_summary(&self) -> LaunchSummary {
+        LaunchSummary::new(self)
+    }
+
+    /// Returns the registry of runtime metrics for this instance of Rocket.
+    /// See the [`metrics`](/rocket/metrics/index.html) module documentation
+    /// for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let rocket = rocket::ignite();
+    /// for (name, value) in rocket.metrics().snapshot() {
+    ///     println!("{}: {}", name, value);
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Returns the registry of feature toggles for this instance of Rocket.
+    /// See the [`features`](/rocket/features/index.html) module documentation
+    /// for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let rocket = rocket::ignite();
+    /// rocket.features().set("beta_search", true);
+    /// assert!(rocket.features().is_enabled("beta_search"));
+    /// ```
+    #[inline(always)]
+    pub fn features(&self) -> &Features {
+        &self.features
+    }
+
+    /// Returns the number of open, idle, and active connections currently
+    /// being served by this instance of Rocket.
+    ///
+    /// Connections are only tracked while Rocket is serving requests over the
+    /// network; requests dispatched via [`local`](/rocket/local/index.html)
+    /// don't open connections. The same counts are available through
+    /// [`metrics()`](#method.metrics) as `connections.open`,
+    /// `connections.idle`, and `connections.active`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let rocket = rocket::ignite();
+    /// let stats = rocket.connection_stats();
+    /// assert_eq!(stats.open, 0);
+    /// assert_eq!(stats.open, stats.idle + stats.active);
+    /// ```
+    #[inline]
+    pub fn connection_stats(&self) -> ConnectionStats {
+        self.connections.stats()
+    }
+
+    /// Reloads the TLS certificates and private key from the paths they were
+    /// configured with. Connections accepted afterwards are served with the
+    /// reloaded certificates; established connections are unaffected. See the
+    /// [`tls`](/rocket/tls/index.html) module for details.
+    ///
+    /// Once launched, Rocket can no longer be accessed directly. Use
+    /// [`tls_reloader()`](#method.tls_reloader), for instance in a launch
+    /// fairing, to reload the certificates of a running instance.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Rocket isn't serving TLS, if the certificates were
+    /// configured from memory, or if the reloaded certificates or key can't be
+    /// read or used. On error, the current certificates remain in use.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::tls::TlsReloadError;
+    ///
+    /// let rocket = rocket::ignite();
+    /// match rocket.reload_tls() {
+    ///     Err(TlsReloadError::NotServing) => { /* not launched yet */ }
+    ///     _ => unreachable!("an unlaunched instance doesn't serve TLS"),
+    /// }
+    /// ```
+    #[inline]
+    pub fn reload_tls(&self) -> Result<(), TlsReloadError> {
+        self.tls.reload()
+    }
+
+    /// Returns a handle to the dynamic routes of this instance, which can be
+    /// used to add and remove routes even after launch, or `None` if no mount
+    /// point was dedicated to dynamic routes with
+    /// [`mount_dynamic()`](#method.mount_dynamic).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let rocket = rocket::ignite();
+    /// assert!(rocket.dynamic_routes().is_none());
+    /// ```
+    #[inline]
+    pub fn dynamic_routes(&self) -> Option<DynamicRoutes> {
+        self.dynamic.clone()
+    }
+
+    /// Returns a handle that reloads the TLS certificates of this instance,
+    /// even after it has launched. See [`reload_tls()`](#method.reload_tls).
+    #[inline]
+    pub fn tls_reloader(&self) -> TlsReloader {
+        self.tls.clone()
+    }
 }
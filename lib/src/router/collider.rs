@@ -1,7 +1,7 @@
 use super::Route;
 
-use http::uri::Uri;
-use http::MediaType;
+use http::uri::{Uri, Constraint};
+use http::{MediaType, RawStr};
 use request::Request;
 
 /// The Collider trait is used to determine if two items that can be routed on
@@ -37,24 +37,74 @@ impl<'a> Collider<str> for &'a str {
     }
 }
 
-// This _only_ checks the `path` component of the URI.
+// This _only_ checks the `path` component of the URI. When one path has more
+// segments than the other, the paths only collide if all of the additional
+// segments are optional.
 impl<'a, 'b> Collider<Uri<'b>> for Uri<'a> {
     fn collides_with(&self, other: &Uri<'b>) -> bool {
-        for (seg_a, seg_b) in self.segments().zip(other.segments()) {
-            if seg_a.ends_with("..>") || seg_b.ends_with("..>") {
-                return true;
-            }
-
-            if !seg_a.collides_with(seg_b) {
-                return false;
+        let (mut segs_a, mut segs_b) = (self.segments(), other.segments());
+        loop {
+            match (segs_a.next(), segs_b.next()) {
+                (Some(seg_a), Some(seg_b)) => {
+                    if seg_a.ends_with("..>") || seg_b.ends_with("..>") {
+                        return true;
+                    }
+
+                    if !seg_a.collides_with(seg_b) || constraints_disjoint(seg_a, seg_b) {
+                        return false;
+                    }
+                }
+                (Some(seg), None) => return is_optional(seg) && segs_a.all(is_optional),
+                (None, Some(seg)) => return is_optional(seg) && segs_b.all(is_optional),
+                (None, None) => return true
             }
         }
+    }
+}
 
-        if self.segment_count() != other.segment_count() {
-            return false;
-        }
+// Whether the route path segment `segment` is an optional dynamic segment,
+// such as `<name?>` or `<name?:int>`.
+fn is_optional(segment: &str) -> bool {
+    if !segment.starts_with('<') || !segment.ends_with('>') {
+        return false;
+    }
+
+    let (name, _) = Constraint::split_param(&segment[1..(segment.len() - 1)]);
+    name.ends_with('?')
+}
+
+// Whether two route path segments are dynamic segments that can't match the
+// same value because of their constraints, or one is a static segment that
+// doesn't satisfy the other's constraint.
+fn constraints_disjoint(seg_a: &str, seg_b: &str) -> bool {
+    match (Constraint::of_segment(seg_a), Constraint::of_segment(seg_b)) {
+        (Some(a), Some(b)) => a.is_disjoint(&b),
+        (Some(a), None) => !seg_b.contains('<') && !a.matches(seg_b),
+        (None, Some(b)) => !seg_a.contains('<') && !b.matches(seg_a),
+        (None, None) => false
+    }
+}
 
-        true
+// Whether every constrained segment of `route` is satisfied by the
+// corresponding segment of the request URI `uri`.
+fn constraints_satisfied(route: &Uri, uri: &Uri) -> bool {
+    if !route.path().contains(':') {
+        return true;
+    }
+
+    route.segments().zip(uri.segments()).all(|(route_seg, uri_seg)| {
+        Constraint::of_segment(route_seg).map_or(true, |constraint| {
+            constraint.matches(&RawStr::from_str(uri_seg).percent_decode_lossy())
+        })
+    })
+}
+
+// Two routes with different API versions never match the same request. An
+// unversioned route matches requests for any version.
+fn versions_collide(a: Option<&String>, b: Option<&String>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a == b,
+        _ => true
     }
 }
 
@@ -80,6 +130,7 @@ impl Collider for Route {
         self.method == b.method
             && self.rank == b.rank
             && self.uri.collides_with(&b.uri)
+            && versions_collide(self.version.as_ref(), b.version.as_ref())
             && match (self.format.as_ref(), b.format.as_ref()) {
                 (Some(mt_a), Some(mt_b)) => mt_a.collides_with(mt_b),
                 (Some(_), None) => true,
@@ -100,7 +151,9 @@ impl<'r> Collider<Request<'r>> for Route {
     fn collides_with(&self, req: &Request<'r>) -> bool {
         self.method == req.method()
             && self.uri.collides_with(req.uri())
+            && constraints_satisfied(&self.uri, req.uri())
             && self.uri.query().map_or(true, |_| req.uri().query().is_some())
+            && self.version.as_ref().map_or(true, |v| req.api_version() == Some(v.as_str()))
             && match self.format {
                 Some(ref mt_a) => match req.format() {
                     Some(ref mt_b) => mt_a.collides_with(mt_b),
@@ -300,6 +353,23 @@ mod tests {
         assert!(!s_s_collide("/", "/a"));
     }
 
+    #[test]
+    fn constrained_param_collisions() {
+        assert!(unranked_collide("/<a:int>", "/<b:int>"));
+        assert!(unranked_collide("/<a:int>", "/<b>"));
+        assert!(unranked_collide("/<a:int>", "/12"));
+        assert!(unranked_collide("/<a:[a-z-]+>", "/<b:alpha>"));
+        assert!(unranked_collide("/<a:hex>", "/<b:uint>"));
+        assert!(unranked_collide("/<a:[0-9]{2,4}>", "/<b:[0-9]{4}>"));
+        assert!(unranked_collide("/<a:[a-z]*>", "/<b:[0-9]*>"));
+
+        assert!(!unranked_collide("/<a:int>", "/<b:alpha>"));
+        assert!(!unranked_collide("/<a:int>", "/new"));
+        assert!(!unranked_collide("/users/<a:uint>", "/users/me"));
+        assert!(!unranked_collide("/<a:[0-9]{2}>", "/<b:[0-9]{4}>"));
+        assert!(!unranked_collide("/<a:uuid>/x", "/<b:[0-9]{1,8}>/x"));
+    }
+
     fn mt_mt_collide(mt1: &str, mt2: &str) -> bool {
         let mt_a = MediaType::from_str(mt1).expect(mt1);
         let mt_b = MediaType::from_str(mt2).expect(mt2);
@@ -340,6 +410,26 @@ mod tests {
         route_a.collides_with(&route_b)
     }
 
+    fn r_version_collide(v1: Option<&str>, v2: Option<&str>) -> bool {
+        let mut route_a = Route::new(Get, "/users", dummy_handler);
+        route_a.version = v1.map(|v| v.to_string());
+
+        let mut route_b = Route::new(Get, "/users", dummy_handler);
+        route_b.version = v2.map(|v| v.to_string());
+
+        route_a.collides_with(&route_b)
+    }
+
+    #[test]
+    fn test_route_version_collisions() {
+        assert!(r_version_collide(None, None));
+        assert!(r_version_collide(Some("v1"), None));
+        assert!(r_version_collide(None, Some("v2")));
+        assert!(r_version_collide(Some("v2"), Some("v2")));
+
+        assert!(!r_version_collide(Some("v1"), Some("v2")));
+    }
+
     #[test]
     fn test_route_content_type_colliions() {
         assert!(r_mt_mt_collide(Get, "application/json", Get, "application/json"));
@@ -447,4 +537,45 @@ mod tests {
         assert!(!req_route_path_collide("/?b=c", "/a/b?<c>"));
         assert!(!req_route_path_collide("/?b=c", "/a?<c>"));
     }
+
+    #[test]
+    fn optional_segment_collisions() {
+        assert!(unranked_collide("/a/<b?>", "/a"));
+        assert!(unranked_collide("/a/<b?>", "/a/c"));
+        assert!(unranked_collide("/a/<b?>/<c?>", "/a"));
+        assert!(unranked_collide("/a/<b?>/<c?>", "/a/<d>"));
+        assert!(unranked_collide("/a/<b?>", "/a/<c?>/<d?>"));
+        assert!(unranked_collide("/a/<b?:int>", "/a/1"));
+
+        assert!(!unranked_collide("/a/<b?>", "/a/c/d"));
+        assert!(!unranked_collide("/a/<b?>", "/<c>"));
+        assert!(!unranked_collide("/a/<b?:int>", "/a/c"));
+    }
+
+    #[test]
+    fn test_req_route_optional_collisions() {
+        assert!(req_route_path_collide("/archive", "/archive/<year?>/<month?>"));
+        assert!(req_route_path_collide("/archive/", "/archive/<year?>/<month?>"));
+        assert!(req_route_path_collide("/archive/2018", "/archive/<year?>/<month?>"));
+        assert!(req_route_path_collide("/archive/2018/3", "/archive/<year?>/<month?>"));
+        assert!(req_route_path_collide("/archive/2018?a=b", "/archive/<year?:int>"));
+
+        assert!(!req_route_path_collide("/archive/2018/3/1", "/archive/<year?>/<month?>"));
+        assert!(!req_route_path_collide("/archive/new", "/archive/<year?:int>"));
+        assert!(!req_route_path_collide("/", "/archive/<year?>"));
+    }
+
+    #[test]
+    fn test_req_route_constrained_collisions() {
+        assert!(req_route_path_collide("/users/12", "/users/<id:int>"));
+        assert!(req_route_path_collide("/users/-12", "/users/<id:int>"));
+        assert!(req_route_path_collide("/posts/a-b", "/posts/<slug:[a-z-]+>"));
+        assert!(req_route_path_collide("/posts/a%2Db", "/posts/<slug:[a-z-]+>"));
+        assert!(req_route_path_collide("/y/2018", "/y/<y:[0-9]{4}>"));
+
+        assert!(!req_route_path_collide("/users/bob", "/users/<id:int>"));
+        assert!(!req_route_path_collide("/users/1.5", "/users/<id:int>"));
+        assert!(!req_route_path_collide("/posts/A_b", "/posts/<slug:[a-z-]+>"));
+        assert!(!req_route_path_collide("/y/18", "/y/<y:[0-9]{4}>"));
+    }
 }
@@ -3,7 +3,7 @@ mod route;
 
 use std::collections::hash_map::HashMap;
 
-use self::collider::Collider;
+pub(crate) use self::collider::Collider;
 pub use self::route::Route;
 
 use request::Request;
@@ -12,17 +12,24 @@ use http::Method;
 // type Selector = (Method, usize);
 type Selector = Method;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Router {
     routes: HashMap<Selector, Vec<Route>>, // using 'selector' for now
+    versions: Vec<String>,
 }
 
 impl Router {
     pub fn new() -> Router {
-        Router { routes: HashMap::new() }
+        Router { routes: HashMap::new(), versions: vec![] }
     }
 
     pub fn add(&mut self, route: Route) {
+        if let Some(ref version) = route.version {
+            if !self.versions.contains(version) {
+                self.versions.push(version.clone());
+            }
+        }
+
         let selector = route.method;
         let entries = self.routes.entry(selector).or_insert_with(|| vec![]);
         let i = entries.binary_search_by_key(&route.rank, |r| r.rank).unwrap_or_else(|i| i);
@@ -64,6 +71,12 @@ impl Router {
         !self.collisions().is_empty()
     }
 
+    /// Returns `true` if some route serves the API version `version`.
+    #[inline]
+    pub fn has_version(&self, version: &str) -> bool {
+        self.versions.iter().any(|v| v == version)
+    }
+
     #[inline]
     pub fn routes<'a>(&'a self) -> impl Iterator<Item=&'a Route> + 'a {
         self.routes.values().flat_map(|v| v.iter())
@@ -442,4 +455,20 @@ mod test {
         assert!(match_params(&router, "/hello/sergio/age", &["sergio"]));
         assert!(match_params(&router, "/hello/you/age", &["you"]));
     }
+
+    #[test]
+    fn test_partial_segment_params() {
+        let router = router_with_routes(&["/report.<ext>"]);
+        assert!(match_params(&router, "/report.pdf", &["pdf"]));
+        assert!(match_params(&router, "/report.tar.gz", &["tar.gz"]));
+        assert!(!match_params(&router, "/report.", &[""]));
+
+        let router = router_with_routes(&["/v<version>/users"]);
+        assert!(match_params(&router, "/v2/users", &["2"]));
+        assert!(match_params(&router, "/v10/users", &["10"]));
+
+        let router = router_with_routes(&["/<name>.json"]);
+        assert!(match_params(&router, "/bob.json", &["bob"]));
+        assert!(!match_params(&router, "/bob.xml", &["bob"]));
+    }
 }
@@ -1,12 +1,14 @@
 use std::fmt;
 use std::convert::From;
+use std::time::Duration;
 
 use yansi::Color::*;
 
 use codegen::StaticRouteInfo;
-use handler::Handler;
+use handler::{Handler, HeadLength};
 use http::{Method, MediaType};
 use http::uri::Uri;
+use versioning::Deprecation;
 
 /// A route: a method, its handler, path, rank, and format/media type.
 pub struct Route {
@@ -25,6 +27,28 @@ pub struct Route {
     pub rank: isize,
     /// The media type this route matches against, if any.
     pub format: Option<MediaType>,
+    /// Whether concurrent, identical `GET` requests to this route share a
+    /// single handler execution. Only `200 OK` responses that don't set
+    /// cookies or forbid caching are shared, and only with requests that match
+    /// on the headers named in the response's `Vary` header.
+    pub coalesce: bool,
+    /// A function that computes the length of the body of a `GET` response
+    /// from this route so that automatically handled `HEAD` requests can be
+    /// answered without running the route's function, if any. Handlers
+    /// generated by `#[get]` call it once all of the route's guards succeed.
+    pub head_length: Option<HeadLength>,
+    /// The API version this route serves, if any. Versioned routes only match
+    /// requests that negotiate the same version.
+    pub version: Option<String>,
+    /// The maximum size, in bytes, of the body of a response from this route,
+    /// if any. Overrides the `response` limit in the configuration.
+    pub response_limit: Option<u64>,
+    /// The maximum time a request to this route may take, from the start of
+    /// the handler to the end of the response body, if any.
+    pub timeout: Option<Duration>,
+    /// The deprecation of this route, if it is deprecated. Responses from
+    /// deprecated routes carry deprecation headers.
+    pub deprecation: Option<Deprecation>,
 }
 
 #[inline(always)]
@@ -89,6 +113,12 @@ impl Route {
             base: Uri::from("/"),
             uri: uri,
             format: None,
+            coalesce: false,
+            head_length: None,
+            version: None,
+            response_limit: None,
+            timeout: None,
+            deprecation: None,
         }
     }
 
@@ -120,6 +150,12 @@ impl Route {
             uri: Uri::from(uri.as_ref().to_string()),
             rank: rank,
             format: None,
+            coalesce: false,
+            head_length: None,
+            version: None,
+            response_limit: None,
+            timeout: None,
+            deprecation: None,
         }
     }
 
@@ -195,8 +231,9 @@ impl Route {
         self.uri = Uri::from(uri.as_ref().to_string());
     }
 
-    // FIXME: Decide whether a component has to be fully variable or not. That
-    // is, whether you can have: /a<a>b/ or even /<a>:<b>/
+    // A segment may contain at most one parameter, which may be surrounded by
+    // static text, as in `/report.<ext>` or `/v<version>`, but `/<a>:<b>` is
+    // not allowed.
     // TODO: Don't return a Vec...take in an &mut [&'a str] (no alloc!)
     /// Given a URI, returns a vector of slices of that URI corresponding to the
     /// dynamic segments in this route.
@@ -211,9 +248,11 @@ impl Route {
             if route_seg.ends_with("..>") {
                 result.push((i, uri.path().len()));
                 break;
-            } else if route_seg.ends_with('>') {
-                let j = i + uri_seg.len();
-                result.push((i, j));
+            } else if let (Some(p), Some(q)) = (route_seg.find('<'), route_seg.rfind('>')) {
+                // Exclude any static prefix and suffix from the parameter.
+                let suffix_len = route_seg.len() - (q + 1);
+                let j = i + uri_seg.len().saturating_sub(suffix_len);
+                result.push((::std::cmp::min(i + p, j), j));
             }
         }
 
@@ -231,6 +270,12 @@ impl Clone for Route {
             base: self.base.clone(),
             uri: self.uri.clone(),
             format: self.format.clone(),
+            coalesce: self.coalesce,
+            head_length: self.head_length,
+            version: self.version.clone(),
+            response_limit: self.response_limit,
+            timeout: self.timeout,
+            deprecation: self.deprecation.clone(),
         }
     }
 }
@@ -247,6 +292,23 @@ impl fmt::Display for Route {
             write!(f, " {}", Yellow.paint(format))?;
         }
 
+        if self.coalesce {
+            write!(f, " {}", Yellow.paint("coalesced"))?;
+        }
+
+        if let Some(ref version) = self.version {
+            write!(f, " {}", Yellow.paint(version))?;
+        }
+
+        if let Some(timeout) = self.timeout {
+            let millis = timeout.as_secs() * 1000 + (timeout.subsec_nanos() / 1_000_000) as u64;
+            write!(f, " {}", Yellow.paint(format!("{}ms", millis)))?;
+        }
+
+        if self.deprecation.is_some() {
+            write!(f, " {}", Yellow.paint("deprecated"))?;
+        }
+
         if let Some(name) = self.name {
             write!(f, " {}{}{}",
                    Cyan.paint("("), Purple.paint(name), Cyan.paint(")"))?;
@@ -268,6 +330,19 @@ impl<'a> From<&'a StaticRouteInfo> for Route {
         let mut route = Route::new(info.method, info.path, info.handler);
         route.format = info.format.clone();
         route.name = Some(info.name);
+        route.coalesce = info.coalesce;
+        route.head_length = info.head_length;
+        route.version = info.version.map(|version| version.to_string());
+        route.response_limit = info.response_limit;
+        route.timeout = info.timeout.map(Duration::from_millis);
+        if info.deprecated_since.is_some() || info.sunset.is_some() || info.successor.is_some() {
+            route.deprecation = Some(Deprecation {
+                since: info.deprecated_since.map(|date| date.to_string()),
+                sunset: info.sunset.map(|date| date.to_string()),
+                successor: info.successor.map(|uri| uri.to_string()),
+            });
+        }
+
         if let Some(rank) = info.rank {
             route.rank = rank;
         }
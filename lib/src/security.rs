@@ -0,0 +1,287 @@
+//! Timing-safe comparison and secret handling utilities.
+//!
+//! Comparing secret values, such as signatures, MACs, or tokens, with `==`
+//! leaks, via timing, how many leading bytes of a guess are correct. The
+//! [`constant_time_eq()`] function compares byte slices in time independent of
+//! their contents and should be used for any comparison involving a secret.
+//!
+//! The [`Secret`] wrapper holds secret material, such as keys or passwords. Its
+//! `Debug` implementation never reveals the wrapped value, and the value is
+//! overwritten with zeroes when the `Secret` is dropped, reducing the time
+//! during which it resides in memory.
+//!
+//! Rocket uses these utilities internally when handling the configured secret
+//! key and when verifying checksums and idempotency fingerprints.
+//!
+//! # Derived Keys
+//!
+//! Subsystems that need a key, such as CSRF protection, signed tokens, or a
+//! session store, shouldn't use the configured `secret_key` directly: a key
+//! shared between two protocols lets a value produced by one be accepted by
+//! the other. Instead, [`Config::derived_key()`] derives an independent
+//! 256-bit key for each purpose from the secret key with HKDF-SHA256. Keys for
+//! different purposes are unrelated, and each is stable for as long as the
+//! secret key is.
+//!
+//! Purposes beginning with `rocket/` are reserved for Rocket's own subsystems.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rocket::security::{Secret, constant_time_eq};
+//!
+//! let token = Secret::new(String::from("s3cr3t-t0k3n"));
+//! assert_eq!(format!("{:?}", token), "Secret([REDACTED])");
+//!
+//! let provided = "s3cr3t-t0k3n";
+//! assert!(constant_time_eq(token.expose().as_bytes(), provided.as_bytes()));
+//! ```
+//!
+//! [`constant_time_eq()`]: /rocket/security/fn.constant_time_eq.html
+//! [`Secret`]: /rocket/security/struct.Secret.html
+//! [`Config::derived_key()`]: /rocket/config/struct.Config.html#method.derived_key
+
+use std::fmt;
+use std::ptr;
+use std::sync::atomic::{self, Ordering};
+
+use ring::{digest, hkdf, hmac};
+
+use http::Key;
+
+/// The HKDF salt of derived keys.
+const DERIVATION_SALT: &[u8] = b"rocket derived key";
+
+/// Returns `true` if `a` and `b` are equal, comparing in time that depends
+/// only on the lengths of `a` and `b`, not on their contents.
+///
+/// Slices of different lengths compare unequal immediately: the length of a
+/// secret is not considered secret.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::security::constant_time_eq;
+///
+/// assert!(constant_time_eq(b"signature", b"signature"));
+/// assert!(!constant_time_eq(b"signature", b"signaturf"));
+/// assert!(!constant_time_eq(b"signature", b"sig"));
+/// ```
+#[inline(never)]
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let difference = a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    unsafe { ptr::read_volatile(&difference) == 0 }
+}
+
+/// Derives the 256-bit key for `purpose` from `key`, the secret key, with
+/// HKDF-SHA256.
+pub(crate) fn derive_key(key: &Key, purpose: &str) -> Secret<[u8; 32]> {
+    let mut master = Secret::new(Vec::with_capacity(64));
+    master.expose_mut().extend_from_slice(key.signing());
+    master.expose_mut().extend_from_slice(key.encryption());
+    hkdf_sha256(DERIVATION_SALT, master.expose(), purpose.as_bytes())
+}
+
+fn hkdf_sha256(salt: &[u8], secret: &[u8], info: &[u8]) -> Secret<[u8; 32]> {
+    let salt = hmac::SigningKey::new(&digest::SHA256, salt);
+    let mut okm = Secret::new([0u8; 32]);
+    hkdf::extract_and_expand(&salt, secret, info, okm.expose_mut());
+    okm
+}
+
+/// Trait implemented by types whose contents can be overwritten with zeroes.
+///
+/// This trait is implemented for `Vec<u8>`, `String`, `Box<[u8]>`, and byte
+/// arrays of common key sizes. It is used by [`Secret`] to clear its value
+/// when dropped.
+///
+/// [`Secret`]: /rocket/security/struct.Secret.html
+pub trait Zeroize {
+    /// Overwrites the contents of `self` with zeroes.
+    fn zeroize(&mut self);
+}
+
+fn zero_bytes(bytes: &mut [u8]) {
+    for byte in bytes.iter_mut() {
+        unsafe { ptr::write_volatile(byte, 0) };
+    }
+
+    atomic::compiler_fence(Ordering::SeqCst);
+}
+
+/// Zeroes the contents and then clears the vector.
+impl Zeroize for Vec<u8> {
+    fn zeroize(&mut self) {
+        zero_bytes(&mut self[..]);
+        self.clear();
+    }
+}
+
+/// Zeroes the contents and then clears the string.
+impl Zeroize for String {
+    fn zeroize(&mut self) {
+        // Zeroes are valid UTF-8, so the string remains valid throughout.
+        unsafe { self.as_mut_vec().zeroize() }
+    }
+}
+
+impl Zeroize for Box<[u8]> {
+    fn zeroize(&mut self) {
+        zero_bytes(&mut self[..]);
+    }
+}
+
+macro_rules! impl_zeroize_for_arrays {
+    ($($n:expr),+) => ($(
+        impl Zeroize for [u8; $n] {
+            fn zeroize(&mut self) {
+                zero_bytes(&mut self[..]);
+            }
+        }
+    )+)
+}
+
+impl_zeroize_for_arrays!(16, 24, 32, 48, 64);
+
+/// A wrapper around secret material that redacts it in `Debug` output and
+/// zeroes it when dropped.
+///
+/// The wrapped value is accessible only via [`expose()`] and
+/// [`expose_mut()`], making every use of the secret explicit. Two secrets
+/// whose values are byte sequences compare in constant time.
+///
+/// Note that zeroing on drop can only clear the value's current storage:
+/// copies made by reallocating or cloning the value are not cleared.
+///
+/// [`expose()`]: #method.expose
+/// [`expose_mut()`]: #method.expose_mut
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::security::Secret;
+///
+/// let password = Secret::new(b"hunter2".to_vec());
+/// assert_eq!(format!("{:?}", password), "Secret([REDACTED])");
+/// assert_eq!(password.expose(), b"hunter2");
+/// assert_eq!(password, Secret::new(b"hunter2".to_vec()));
+/// ```
+pub struct Secret<T: Zeroize>(T);
+
+impl<T: Zeroize> Secret<T> {
+    /// Wraps `value` in a new `Secret`.
+    #[inline(always)]
+    pub fn new(value: T) -> Secret<T> {
+        Secret(value)
+    }
+
+    /// Returns a reference to the secret value.
+    #[inline(always)]
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    /// Returns a mutable reference to the secret value.
+    #[inline(always)]
+    pub fn expose_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Zeroize> From<T> for Secret<T> {
+    #[inline(always)]
+    fn from(value: T) -> Secret<T> {
+        Secret::new(value)
+    }
+}
+
+impl<T: Zeroize + Clone> Clone for Secret<T> {
+    #[inline(always)]
+    fn clone(&self) -> Secret<T> {
+        Secret(self.0.clone())
+    }
+}
+
+/// Compares the secret values in constant time.
+impl<T: Zeroize + AsRef<[u8]>> PartialEq for Secret<T> {
+    fn eq(&self, other: &Secret<T>) -> bool {
+        constant_time_eq(self.0.as_ref(), other.0.as_ref())
+    }
+}
+
+impl<T: Zeroize + AsRef<[u8]>> Eq for Secret<T> { }
+
+/// Never displays the secret value.
+impl<T: Zeroize> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Secret([REDACTED])")
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::Key;
+    use super::{Secret, Zeroize, constant_time_eq, derive_key, hkdf_sha256};
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"", b""));
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"bbc"));
+        assert!(!constant_time_eq(b"abc", b"abcd"));
+    }
+
+    #[test]
+    fn test_zeroize() {
+        let mut bytes = vec![1u8, 2, 3];
+        bytes.zeroize();
+        assert!(bytes.is_empty());
+
+        let mut string = String::from("secret");
+        string.zeroize();
+        assert!(string.is_empty());
+
+        let mut array = [7u8; 32];
+        array.zeroize();
+        assert_eq!(array, [0u8; 32]);
+    }
+
+    #[test]
+    fn test_secret_redacts_and_compares() {
+        let secret = Secret::new(String::from("password"));
+        assert_eq!(format!("{:?}", secret), "Secret([REDACTED])");
+        assert_eq!(secret.expose(), "password");
+        assert!(secret == Secret::new(String::from("password")));
+        assert!(secret != Secret::new(String::from("passw0rd")));
+    }
+
+    #[test]
+    fn test_hkdf_sha256() {
+        // RFC 5869, test case 1, truncated to 32 bytes.
+        let salt: Vec<u8> = (0..13).collect();
+        let info: Vec<u8> = (0xf0..0xfa).collect();
+        let okm = hkdf_sha256(&salt, &[0x0b; 22], &info);
+        let hex: String = okm.expose().iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(hex, "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf");
+    }
+
+    #[test]
+    fn test_derive_key() {
+        let key = Key::generate();
+        assert_eq!(derive_key(&key, "csrf"), derive_key(&key, "csrf"));
+        assert!(derive_key(&key, "csrf") != derive_key(&key, "sessions"));
+        assert!(derive_key(&key, "csrf") != derive_key(&Key::generate(), "csrf"));
+        assert!(derive_key(&key, "csrf").expose()[..] != key.encryption()[..]);
+    }
+}
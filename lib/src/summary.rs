@@ -0,0 +1,243 @@
+//! A structured description of a launched application.
+//!
+//! Rocket prints what it launches with: its configuration, routes, catchers,
+//! and fairings. The same information is available as data via
+//! [`Rocket::launch_summary()`], for instance to a launch fairing, so that
+//! deployment tooling can record exactly what launched. When the `json`
+//! feature is enabled, a [`LaunchSummary`] implements `Serialize` and can be
+//! written out as JSON.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rocket::fairing::AdHoc;
+//!
+//! # if false {
+//! rocket::ignite()
+//!     .attach(AdHoc::on_launch(|rocket| {
+//!         let summary = rocket.launch_summary();
+//!         println!("serving {} routes on port {}", summary.routes.len(), summary.port);
+//!     }))
+//!     .launch();
+//! # }
+//! ```
+//!
+//! [`Rocket::launch_summary()`]: /rocket/struct.Rocket.html#method.launch_summary
+//! [`LaunchSummary`]: /rocket/summary/struct.LaunchSummary.html
+
+use std::fmt;
+
+use yansi::Paint;
+
+use rocket::Rocket;
+use router::Route;
+use fairing::{Info, Kind};
+use versioning::Deprecation;
+
+#[cfg(feature = "json")] use serde::ser::{Serialize, Serializer, SerializeStruct};
+
+/// A description of an instance of `Rocket`: the configuration highlights,
+/// the address it serves on, and its routes, catchers, and fairings.
+///
+/// Once the application has launched, `port` is the port it is bound to,
+/// which differs from the configured port when the configured port is `0`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LaunchSummary {
+    /// The configuration environment, as in `development`.
+    pub environment: String,
+    /// The address the application is bound to.
+    pub address: String,
+    /// The port the application is bound to.
+    pub port: u16,
+    /// Whether requests are served over TLS.
+    pub tls: bool,
+    /// The number of worker threads.
+    pub workers: u16,
+    /// The log level, as in `normal`.
+    pub log_level: String,
+    /// The mounted routes, in the order in which they are matched.
+    pub routes: Vec<RouteSummary>,
+    /// The status codes with application-registered catchers, in ascending
+    /// order. Codes handled by Rocket's default catchers are not listed.
+    pub catchers: Vec<u16>,
+    /// The attached fairings, in the order in which they were attached.
+    pub fairings: Vec<FairingSummary>,
+}
+
+/// A description of a mounted route.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteSummary {
+    /// The name of the route's handler, if known.
+    pub name: Option<String>,
+    /// The method the route matches, as in `GET`.
+    pub method: String,
+    /// The URI the route matches, including its mount point.
+    pub uri: String,
+    /// The route's rank.
+    pub rank: isize,
+    /// The media type the route matches, if any.
+    pub format: Option<String>,
+    /// The route's deprecation, if it is deprecated.
+    pub deprecation: Option<Deprecation>,
+}
+
+/// A description of an attached fairing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FairingSummary {
+    /// The fairing's name.
+    pub name: String,
+    /// The kinds of callbacks the fairing receives: any of `attach`,
+    /// `launch`, `request`, and `response`.
+    pub kinds: Vec<&'static str>,
+}
+
+impl LaunchSummary {
+    pub(crate) fn new(rocket: &Rocket) -> LaunchSummary {
+        let config = rocket.config();
+        let mut catchers: Vec<u16> = rocket.catchers.values()
+            .filter(|catcher| !catcher.is_default())
+            .map(|catcher| catcher.code)
+            .collect();
+
+        catchers.sort();
+        LaunchSummary {
+            environment: config.environment.to_string(),
+            address: config.address.clone(),
+            port: config.port,
+            tls: config.tls_enabled(),
+            workers: config.workers,
+            log_level: config.log_level.to_string(),
+            routes: rocket.routes().map(RouteSummary::new).collect(),
+            catchers: catchers,
+            fairings: rocket.fairings.info().into_iter().map(FairingSummary::new).collect(),
+        }
+    }
+
+    /// Returns the URL of the application's root, as in
+    /// `https://localhost:8000`.
+    pub fn url(&self) -> String {
+        let scheme = if self.tls { "https" } else { "http" };
+        format!("{}://{}:{}", scheme, self.address, self.port)
+    }
+}
+
+impl RouteSummary {
+    fn new(route: &Route) -> RouteSummary {
+        RouteSummary {
+            name: route.name.map(|name| name.to_string()),
+            method: route.method.to_string(),
+            uri: route.uri.to_string(),
+            rank: route.rank,
+            format: route.format.as_ref().map(|format| format.to_string()),
+            deprecation: route.deprecation.clone(),
+        }
+    }
+}
+
+impl FairingSummary {
+    fn new(info: Info) -> FairingSummary {
+        let kinds = [
+            (Kind::Attach, "attach"),
+            (Kind::Launch, "launch"),
+            (Kind::Request, "request"),
+            (Kind::Response, "response"),
+        ];
+
+        FairingSummary {
+            name: info.name.to_string(),
+            kinds: kinds.iter()
+                .filter(|&&(kind, _)| info.kind.is(kind))
+                .map(|&(_, name)| name)
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for LaunchSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} ({}, {} workers)", Paint::white(self.url()).bold(),
+                 self.environment, self.workers)?;
+
+        for route in &self.routes {
+            write!(f, "    route: {} {} [{}]", Paint::green(&route.method),
+                   Paint::blue(&route.uri), route.rank)?;
+
+            if let Some(ref deprecation) = route.deprecation {
+                write!(f, " {}", Paint::yellow("deprecated"))?;
+                if let Some(ref sunset) = deprecation.sunset {
+                    write!(f, " (sunset {})", sunset)?;
+                }
+
+                if let Some(ref successor) = deprecation.successor {
+                    write!(f, " -> {}", successor)?;
+                }
+            }
+
+            writeln!(f)?;
+        }
+
+        for code in &self.catchers {
+            writeln!(f, "    catcher: {}", Paint::blue(code))?;
+        }
+
+        for fairing in &self.fairings {
+            writeln!(f, "    fairing: {} ({})", Paint::white(&fairing.name),
+                     fairing.kinds.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "json")]
+impl Serialize for LaunchSummary {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("LaunchSummary", 10)?;
+        state.serialize_field("environment", &self.environment)?;
+        state.serialize_field("address", &self.address)?;
+        state.serialize_field("port", &self.port)?;
+        state.serialize_field("tls", &self.tls)?;
+        state.serialize_field("url", &self.url())?;
+        state.serialize_field("workers", &self.workers)?;
+        state.serialize_field("log_level", &self.log_level)?;
+        state.serialize_field("routes", &self.routes)?;
+        state.serialize_field("catchers", &self.catchers)?;
+        state.serialize_field("fairings", &self.fairings)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "json")]
+impl Serialize for RouteSummary {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("RouteSummary", 6)?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("method", &self.method)?;
+        state.serialize_field("uri", &self.uri)?;
+        state.serialize_field("rank", &self.rank)?;
+        state.serialize_field("format", &self.format)?;
+        state.serialize_field("deprecation", &self.deprecation)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "json")]
+impl Serialize for Deprecation {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Deprecation", 3)?;
+        state.serialize_field("since", &self.since)?;
+        state.serialize_field("sunset", &self.sunset)?;
+        state.serialize_field("successor", &self.successor)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "json")]
+impl Serialize for FairingSummary {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("FairingSummary", 2)?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("kinds", &self.kinds)?;
+        state.end()
+    }
+}
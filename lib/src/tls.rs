@@ -0,0 +1,1129 @@
+//! TLS certificate reloading and client certificate authentication.
+//!
+//! # Reloading Certificates
+//!
+//! Certificates issued by ACME or other short-lived certificate authorities
+//! are renewed while the application runs. Rather than restarting Rocket to
+//! serve a renewed certificate, the certificates and private key can be
+//! reloaded from the paths they were configured with, via
+//! [`Rocket::reload_tls()`], a [`TlsReloader`] handle, or the admin channel's
+//! `reload-tls` command. New connections are served with the reloaded
+//! certificates as soon as they are loaded; connections that were already
+//! established keep their certificates until they close.
+//!
+//! Only the certificates and keys, including those selected by SNI, and the
+//! stapled OCSP response are reloaded. The certificate store and the mutual
+//! TLS policy remain as they were at launch. Certificates configured from
+//! memory, via `tls_bytes`, can't be reloaded.
+//!
+//! Reload the certificates once a day from a thread started at launch:
+//!
+//! ```rust
+//! use std::thread;
+//! use std::time::Duration;
+//! use rocket::fairing::AdHoc;
+//!
+//! # if false {
+//! rocket::ignite()
+//!     .attach(AdHoc::on_launch(|rocket| {
+//!         let reloader = rocket.tls_reloader();
+//!         thread::spawn(move || loop {
+//!             thread::sleep(Duration::from_secs(24 * 60 * 60));
+//!             if let Err(e) = reloader.reload() {
+//!                 eprintln!("failed to reload TLS certificates: {}", e);
+//!             }
+//!         });
+//!     }))
+//!     .launch();
+//! # }
+//! ```
+//!
+//! # OCSP Stapling
+//!
+//! When the `ocsp_response_path` key of the `tls` configuration table is set,
+//! the DER-encoded OCSP response in that file is stapled to handshakes that
+//! are served the default certificate, sparing clients a request to the
+//! issuer's OCSP responder. Clients require a stapled response for
+//! certificates with the OCSP Must-Staple extension.
+//!
+//! OCSP responses expire, usually within a week, so the file must be updated
+//! with a fresh response, as with `openssl ocsp -respout` run by a cron job.
+//! The response is reread whenever the certificates are reloaded and, when
+//! the `tls_ocsp_refresh` configuration parameter is set, every
+//! `tls_ocsp_refresh` seconds, or on demand via
+//! [`TlsReloader::refresh_ocsp()`]. A response that can't be reread is logged
+//! and the previous one remains stapled.
+//!
+//! # Handshakes
+//!
+//! New connections must complete the TLS handshake, and send the head of
+//! their first request, within the `tls_handshake_timeout` configuration
+//! parameter, 10 seconds by default, so that stalled handshakes don't hold a
+//! worker indefinitely. Failed handshakes are logged and counted in the
+//! [metrics](/rocket/metrics/) by cause:
+//!
+//!   * `tls.handshakes_failed.no_cert`: mutual TLS is required, but the client
+//!     presented no certificate
+//!   * `tls.handshakes_failed.bad_cert`: the client's certificate couldn't be
+//!     verified
+//!   * `tls.handshakes_failed.protocol`: the client doesn't support the
+//!     protocol versions or cipher suites Rocket does or violated the protocol
+//!   * `tls.handshakes_failed.timeout`: the handshake timed out
+//!   * `tls.handshakes_failed.other`: the handshake failed for any other
+//!     reason, such as the client closing the connection
+//!
+//! # Client Certificates
+//!
+//! When mutual TLS is enabled, via the `mutual_tls` configuration parameter,
+//! clients authenticate with a certificate verified against the configured
+//! certificate store. The [`MutualTlsUser`] request guard, available when the
+//! `tls` feature is enabled, retrieves the verified certificate chain of the
+//! client that sent a request and the common name of its certificate. Its
+//! [`certificate()`] method parses the rest of the leaf certificate into a
+//! [`ParsedCertificate`]: subject and issuer, alternative names, validity
+//! period, serial number, and fingerprints.
+//!
+//! To only accept specific clients, add the SHA-256 fingerprints of their
+//! certificates to the `mtls_allowed_fingerprints` configuration parameter and
+//! use the [`AllowedClient`] request guard, which fails with `403 Forbidden`
+//! for any other client. The response is produced by the `403` catcher, which
+//! can be replaced like any other. Other allow-lists, such as one stored in a
+//! database, can be implemented via a custom [`FingerprintPolicy`]:
+//!
+//! ```toml
+//! [global]
+//! mutual_tls = "required"
+//! mtls_allowed_fingerprints = [
+//!     "0bf5ad9e01f51f68cf44f2fdb0951bccf5157e0e7bc1487b84b3400bae928b8d"
+//! ]
+//! ```
+//!
+//! # Session Details
+//!
+//! The parameters a connection's handshake negotiated, as needed to audit the
+//! cryptography clients use, are available to handlers and guards via
+//! [`Request::tls_info()`]: the protocol version, the cipher suite, the
+//! hostname the client requested via SNI, and whether the client resumed an
+//! earlier session rather than performing a full handshake.
+//!
+//! [`Request::tls_info()`]: /rocket/struct.Request.html#method.tls_info
+//! [`Rocket::reload_tls()`]: /rocket/struct.Rocket.html#method.reload_tls
+//! [`TlsReloader`]: /rocket/tls/struct.TlsReloader.html
+//! [`TlsReloader::refresh_ocsp()`]: /rocket/tls/struct.TlsReloader.html#method.refresh_ocsp
+//! [`MutualTlsUser`]: /rocket/tls/struct.MutualTlsUser.html
+//! [`certificate()`]: /rocket/tls/struct.MutualTlsUser.html#method.certificate
+//! [`ParsedCertificate`]: /rocket/tls/struct.ParsedCertificate.html
+//! [`AllowedClient`]: /rocket/tls/struct.AllowedClient.html
+//! [`FingerprintPolicy`]: /rocket/tls/trait.FingerprintPolicy.html
+
+use std::fmt;
+use std::sync::{Arc, RwLock};
+#[cfg(feature = "tls")] use std::sync::Mutex;
+
+use config::ConfigError;
+
+#[cfg(feature = "tls")] use hyper_sync_rustls::TlsServer;
+#[cfg(feature = "tls")] use http::hyper;
+#[cfg(feature = "tls")] use http::hyper::net::{HttpStream, SslServer};
+#[cfg(feature = "tls")] use data::HttpsStream;
+#[cfg(feature = "tls")] use config::{self, Config};
+#[cfg(feature = "tls")] use http::Certificate;
+#[cfg(feature = "tls")] use request::{self, FromRequest, Request};
+#[cfg(feature = "tls")] use outcome::Outcome::*;
+#[cfg(feature = "tls")] use x509;
+#[cfg(feature = "tls")] use metrics::{Metrics, Counter};
+#[cfg(feature = "tls")] use std::time::Duration;
+#[cfg(feature = "tls")] use std::thread;
+#[cfg(feature = "tls")] use state::Storage;
+#[cfg(feature = "tls")] use std::marker::PhantomData;
+#[cfg(feature = "tls")] use std::ops::Deref;
+#[cfg(feature = "tls")] use http::Status;
+#[cfg(feature = "tls")] use std::cell::Cell;
+#[cfg(feature = "tls")] use rustls::{ServerSession, StoresServerSessions, ProducesTickets};
+
+#[cfg(feature = "tls")] pub use x509::{ParsedCertificate, DistinguishedName};
+
+/// The error returned when reloading TLS certificates fails. The certificates
+/// that were being served remain in use.
+#[derive(Debug)]
+pub enum TlsReloadError {
+    /// Rocket isn't serving over TLS: it hasn't launched, TLS isn't
+    /// configured, or the `tls` feature is disabled.
+    NotServing,
+    /// The certificates were configured from memory, so there are no files to
+    /// reload them from.
+    InMemory,
+    /// The certificates or private key couldn't be read or parsed.
+    Config(ConfigError),
+    /// The reloaded certificates and key couldn't be used to serve TLS.
+    Tls(String),
+}
+
+impl fmt::Display for TlsReloadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TlsReloadError::NotServing => write!(f, "TLS isn't being served"),
+            TlsReloadError::InMemory => write!(f, "TLS was configured from memory"),
+            TlsReloadError::Config(ref e) => write!(f, "{}", e),
+            TlsReloadError::Tls(ref e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// The TLS server currently in use and the configuration it was built from.
+#[cfg(feature = "tls")]
+struct Served {
+    config: Config,
+    server: TlsServer,
+}
+
+#[cfg(not(feature = "tls"))]
+struct Served;
+
+/// A handle that reloads the TLS certificates of a launched Rocket instance.
+///
+/// A `TlsReloader` is retrieved via [`Rocket::tls_reloader()`]. It is cheap to
+/// clone, and all clones reload the same instance. See the [module level
+/// documentation](/rocket/tls/index.html) for what is reloaded and when
+/// reloaded certificates take effect.
+///
+/// [`Rocket::tls_reloader()`]: /rocket/struct.Rocket.html#method.tls_reloader
+#[derive(Clone)]
+pub struct TlsReloader {
+    served: Arc<RwLock<Option<Served>>>,
+    /// Held while the served certificates are reread and replaced, so that a
+    /// reload and an OCSP refresh don't each replace the other's work. The
+    /// `served` lock is only held to read or swap the server, so handshakes
+    /// aren't blocked while files are reread.
+    #[cfg(feature = "tls")]
+    reloading: Arc<Mutex<()>>,
+}
+
+impl TlsReloader {
+    #[inline]
+    pub(crate) fn new() -> TlsReloader {
+        TlsReloader {
+            served: Arc::new(RwLock::new(None)),
+            #[cfg(feature = "tls")]
+            reloading: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Starts serving TLS with `tls` according to `config`, returning the
+    /// acceptor that wraps new connections with the current certificates and
+    /// counts failed handshakes in `metrics`. If an OCSP response is stapled
+    /// and `tls_ocsp_refresh` is set, a thread rereads it on that interval.
+    #[cfg(feature = "tls")]
+    pub(crate) fn serve(
+        &self,
+        config: &Config,
+        tls: config::TlsConfig,
+        metrics: &Metrics
+    ) -> Result<TlsAcceptor, String> {
+        let server = tls_server(config, tls)?;
+        let served = Served { config: config.clone(), server: server };
+        let _reloading = self.reloading.lock().expect("tls reload lock");
+        *self.served.write().expect("tls lock") = Some(served);
+        if let (Some(_), Some(secs)) = (config.tls_ocsp.as_ref(), config.tls_ocsp_refresh) {
+            let reloader = self.clone();
+            thread::spawn(move || loop {
+                thread::sleep(Duration::from_secs(secs as u64));
+                if let Err(e) = reloader.refresh_ocsp() {
+                    warn!("Failed to refresh the stapled OCSP response: {}", e);
+                }
+            });
+        }
+
+        Ok(TlsAcceptor {
+            reloader: self.clone(),
+            timeout: config.tls_handshake_timeout.map(|secs| Duration::from_secs(secs as u64)),
+            failures: HandshakeFailures::new(metrics),
+        })
+    }
+
+    /// Reloads the TLS certificates and private key, and the stapled OCSP
+    /// response if there is one, from the paths they were configured with.
+    /// Connections accepted after this method returns successfully are served
+    /// with the reloaded certificates. Reloads and OCSP refreshes, including
+    /// those of the `tls_ocsp_refresh` thread, take turns, so neither replaces
+    /// the other's certificates with older ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Rocket isn't serving TLS, if the certificates were
+    /// configured from memory, or if the reloaded certificates or key can't be
+    /// read or used. On error, the current certificates remain in use.
+    #[cfg(feature = "tls")]
+    pub fn reload(&self) -> Result<(), TlsReloadError> {
+        let _reloading = self.reloading.lock().expect("tls reload lock");
+        let mut config = match *self.served.read().expect("tls lock") {
+            Some(ref served) => served.config.clone(),
+            None => return Err(TlsReloadError::NotServing)
+        };
+
+        let paths = config.tls.as_ref().and_then(|tls| tls.paths.clone());
+        let (certs_path, key_path) = match paths {
+            Some(paths) => paths,
+            None => return Err(TlsReloadError::InMemory)
+        };
+
+        config.set_tls(&certs_path, &key_path).map_err(TlsReloadError::Config)?;
+        let sni: Vec<_> = config.tls_sni.iter()
+            .filter_map(|&(ref name, ref tls)| tls.paths.clone().map(|p| (name.clone(), p)))
+            .collect();
+
+        for (hostname, (certs_path, key_path)) in sni {
+            config.set_tls_sni(&hostname, &certs_path, &key_path)
+                .map_err(TlsReloadError::Config)?;
+        }
+
+        let ocsp_path = config.tls_ocsp.as_ref().map(|ocsp| ocsp.path.clone());
+        if let Some(ocsp_path) = ocsp_path {
+            config.set_tls_ocsp_response(&ocsp_path).map_err(TlsReloadError::Config)?;
+        }
+
+        let tls = config.tls.clone().expect("TLS configuration was just set");
+        let server = tls_server(&config, tls).map_err(TlsReloadError::Tls)?;
+        *self.served.write().expect("tls lock") = Some(Served { config: config, server: server });
+
+        info!("Reloaded TLS certificates from {}.", certs_path);
+        Ok(())
+    }
+
+    #[doc(hidden)]
+    #[cfg(not(feature = "tls"))]
+    pub fn reload(&self) -> Result<(), TlsReloadError> {
+        Err(TlsReloadError::NotServing)
+    }
+
+    /// Rereads the stapled OCSP response from the path it was configured with,
+    /// leaving the certificates as they are. Handshakes of connections
+    /// accepted after this method returns successfully staple the reread
+    /// response. If no OCSP response is configured, this method does nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Rocket isn't serving TLS or if the reread response
+    /// can't be read or used. On error, the current response remains stapled.
+    #[cfg(feature = "tls")]
+    pub fn refresh_ocsp(&self) -> Result<(), TlsReloadError> {
+        let _reloading = self.reloading.lock().expect("tls reload lock");
+        let mut config = match *self.served.read().expect("tls lock") {
+            Some(ref served) => served.config.clone(),
+            None => return Err(TlsReloadError::NotServing)
+        };
+
+        let ocsp_path = match config.tls_ocsp {
+            Some(ref ocsp) => ocsp.path.clone(),
+            None => return Ok(())
+        };
+
+        config.set_tls_ocsp_response(&ocsp_path).map_err(TlsReloadError::Config)?;
+        let tls = config.tls.clone().expect("OCSP responses require certificates");
+        let server = tls_server(&config, tls).map_err(TlsReloadError::Tls)?;
+        *self.served.write().expect("tls lock") = Some(Served { config: config, server: server });
+
+        info!("Refreshed the stapled OCSP response from {}.", ocsp_path);
+        Ok(())
+    }
+
+    #[doc(hidden)]
+    #[cfg(not(feature = "tls"))]
+    pub fn refresh_ocsp(&self) -> Result<(), TlsReloadError> {
+        Err(TlsReloadError::NotServing)
+    }
+}
+
+impl fmt::Debug for TlsReloader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let serving = self.served.read().map(|served| served.is_some()).unwrap_or(false);
+        f.debug_struct("TlsReloader").field("serving", &serving).finish()
+    }
+}
+
+/// The certificate chain a client presented during the TLS handshake, leaf
+/// certificate first. It's shared by every request on the client's
+/// connection, so the identity in it is parsed at most once per connection.
+#[cfg(feature = "tls")]
+pub(crate) struct PeerCertificates {
+    chain: Vec<Certificate>,
+    name: Storage<Option<String>>,
+    leaf: Storage<Option<ParsedCertificate>>,
+    fingerprint: Storage<[u8; 32]>,
+}
+
+#[cfg(feature = "tls")]
+impl PeerCertificates {
+    pub(crate) fn new(chain: Vec<Certificate>) -> PeerCertificates {
+        PeerCertificates {
+            chain: chain,
+            name: Storage::new(),
+            leaf: Storage::new(),
+            fingerprint: Storage::new(),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn chain(&self) -> &[Certificate] {
+        &self.chain
+    }
+
+    /// The common name of the subject of the leaf certificate, if any.
+    pub(crate) fn name(&self) -> Option<&str> {
+        let name = self.name.get_or_set(|| {
+            self.chain.first().and_then(|leaf| x509::common_name(&leaf.0))
+        });
+
+        name.as_ref().map(|name| &**name)
+    }
+
+    /// The parsed leaf certificate, if it could be parsed.
+    pub(crate) fn leaf(&self) -> Option<&ParsedCertificate> {
+        let leaf = self.leaf.get_or_set(|| {
+            self.chain.first().and_then(ParsedCertificate::parse)
+        });
+
+        leaf.as_ref()
+    }
+
+    /// The SHA-256 digest of the DER encoded leaf certificate. The chain must
+    /// not be empty.
+    pub(crate) fn fingerprint(&self) -> &[u8; 32] {
+        self.fingerprint.get_or_set(|| x509::sha256(&self.chain[0].0))
+    }
+}
+
+/// The parameters negotiated by the TLS handshake of the connection a request
+/// was received on, as returned by
+/// [`Request::tls_info()`](/rocket/struct.Request.html#method.tls_info). This
+/// type is only available when the `tls` feature is enabled.
+#[cfg(feature = "tls")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsInfo {
+    /// The negotiated protocol version, as in `TLSv1.2`.
+    pub protocol_version: String,
+    /// The negotiated cipher suite, as in
+    /// `TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256`.
+    pub cipher_suite: String,
+    /// The hostname the client requested via SNI, if it requested one.
+    pub sni_hostname: Option<String>,
+    /// Whether the client resumed an earlier session, via a session ID or a
+    /// session ticket, instead of performing a full handshake.
+    pub resumed: bool,
+}
+
+#[cfg(feature = "tls")]
+impl TlsInfo {
+    /// Reads the parameters of `session`, whose handshake must have been the
+    /// last to complete on this thread. Returns `None` if the handshake hasn't
+    /// completed.
+    pub(crate) fn read(session: &ServerSession) -> Option<TlsInfo> {
+        use rustls::Session;
+
+        let version = session.get_protocol_version()?;
+        let suite = session.get_negotiated_ciphersuite()?;
+        Some(TlsInfo {
+            protocol_version: format!("{:?}", version).replace('_', "."),
+            cipher_suite: format!("{:?}", suite.suite),
+            sni_hostname: session.get_sni_hostname().map(|name| name.to_string()),
+            resumed: RESUMED.with(|resumed| resumed.replace(false)),
+        })
+    }
+}
+
+// Whether the last handshake on this thread resumed a session. rustls doesn't
+// report resumption, so it's recorded when the session cache or ticketer
+// returns a session. A connection's handshake completes on the worker thread
+// that accepted it, before the connection's first request is read, and so
+// before its `TlsInfo` is.
+#[cfg(feature = "tls")]
+thread_local!(static RESUMED: Cell<bool> = Cell::new(false));
+
+/// Records the sessions returned by the wrapped session cache or ticketer as
+/// resumed in `RESUMED`.
+#[cfg(feature = "tls")]
+struct TrackResumption<T: ?Sized>(Arc<T>);
+
+#[cfg(feature = "tls")]
+impl<T: ?Sized> TrackResumption<T> {
+    fn record(session: Option<Vec<u8>>) -> Option<Vec<u8>> {
+        if session.is_some() {
+            RESUMED.with(|resumed| resumed.set(true));
+        }
+
+        session
+    }
+}
+
+#[cfg(feature = "tls")]
+impl<T: ?Sized + StoresServerSessions> StoresServerSessions for TrackResumption<T> {
+    fn generate(&self) -> ::rustls::internal::msgs::handshake::SessionID {
+        self.0.generate()
+    }
+
+    fn put(&self, key: Vec<u8>, value: Vec<u8>) -> bool {
+        self.0.put(key, value)
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        Self::record(self.0.get(key))
+    }
+}
+
+#[cfg(feature = "tls")]
+impl<T: ?Sized + ProducesTickets> ProducesTickets for TrackResumption<T> {
+    fn enabled(&self) -> bool {
+        self.0.enabled()
+    }
+
+    fn get_lifetime(&self) -> u32 {
+        self.0.get_lifetime()
+    }
+
+    fn encrypt(&self, plain: &[u8]) -> Option<Vec<u8>> {
+        self.0.encrypt(plain)
+    }
+
+    fn decrypt(&self, cipher: &[u8]) -> Option<Vec<u8>> {
+        Self::record(self.0.decrypt(cipher))
+    }
+}
+
+/// A request guard that retrieves the certificate chain the client presented
+/// during the TLS handshake. This type is only available when the `tls`
+/// feature is enabled.
+///
+/// The guard forwards when the request was sent over plaintext or the client
+/// presented no certificate, as is allowed when the `mutual_tls` policy is
+/// `optional`. The chain has been verified against the configured certificate
+/// store, except in requests dispatched via [`local`](/rocket/local/), where
+/// it is whatever the test set.
+///
+/// The chain is read from the TLS session, and the identity in it parsed, once
+/// per connection; every request on a kept-alive connection shares them.
+///
+/// # Example
+///
+/// ```rust
+/// # #![feature(plugin, decl_macro)]
+/// # #![plugin(rocket_codegen)]
+/// # extern crate rocket;
+/// use rocket::tls::MutualTlsUser;
+///
+/// #[get("/whoami")]
+/// fn whoami(user: MutualTlsUser) -> String {
+///     match user.name() {
+///         Some(name) => format!("Hello, {}!", name),
+///         None => format!("Hello, {} byte certificate!", user.der().len()),
+///     }
+/// }
+/// # fn main() { }
+/// ```
+#[cfg(feature = "tls")]
+#[derive(Clone)]
+pub struct MutualTlsUser {
+    peer: Arc<PeerCertificates>,
+}
+
+#[cfg(feature = "tls")]
+impl MutualTlsUser {
+    /// Returns the common name (CN) of the subject of the client's leaf
+    /// certificate, if it has one.
+    #[inline]
+    pub fn name(&self) -> Option<&str> {
+        self.peer.name()
+    }
+
+    /// Returns the client's leaf certificate, parsed, or `None` if it couldn't
+    /// be parsed. The certificate is parsed at most once per connection.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #![feature(plugin, decl_macro)]
+    /// # #![plugin(rocket_codegen)]
+    /// # extern crate rocket;
+    /// use rocket::tls::MutualTlsUser;
+    ///
+    /// #[get("/whoami")]
+    /// fn whoami(user: MutualTlsUser) -> Option<String> {
+    ///     let certificate = user.certificate()?;
+    ///     Some(format!("{}, issued by {}", certificate.subject(), certificate.issuer()))
+    /// }
+    /// # fn main() { }
+    /// ```
+    #[inline]
+    pub fn certificate(&self) -> Option<&ParsedCertificate> {
+        self.peer.leaf()
+    }
+
+    /// Returns the SHA-256 digest of the DER encoding of the client's leaf
+    /// certificate, as in the `mtls_allowed_fingerprints` configuration
+    /// parameter. Unlike [`certificate()`](#method.certificate), this is
+    /// available even if the certificate couldn't be parsed.
+    #[inline]
+    pub fn fingerprint(&self) -> &[u8; 32] {
+        self.peer.fingerprint()
+    }
+
+    /// Returns the DER encoding of the client's leaf certificate.
+    #[inline]
+    pub fn der(&self) -> &[u8] {
+        &self.peer.chain()[0].0
+    }
+
+    /// Returns the certificate chain the client presented, leaf certificate
+    /// first.
+    #[inline]
+    pub fn certificates(&self) -> &[Certificate] {
+        self.peer.chain()
+    }
+}
+
+#[cfg(feature = "tls")]
+impl fmt::Debug for MutualTlsUser {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MutualTlsUser")
+            .field("name", &self.name())
+            .field("certificates", &self.certificates())
+            .finish()
+    }
+}
+
+#[cfg(feature = "tls")]
+impl<'a, 'r> FromRequest<'a, 'r> for MutualTlsUser {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        match request.peer_certificates() {
+            Some(peer) if !peer.chain().is_empty() => Success(MutualTlsUser { peer: peer.clone() }),
+            _ => Forward(())
+        }
+    }
+}
+
+/// Trait implemented by types that decide which clients an
+/// [`AllowedClient`](/rocket/tls/struct.AllowedClient.html) guard accepts,
+/// by the SHA-256 fingerprint of their certificates. This trait is only
+/// available when the `tls` feature is enabled.
+///
+/// # Example
+///
+/// Accept the clients whose fingerprints are in a managed allow-list:
+///
+/// ```rust
+/// # #![feature(plugin, decl_macro)]
+/// # #![plugin(rocket_codegen)]
+/// # extern crate rocket;
+/// use std::collections::HashSet;
+///
+/// use rocket::{Request, State};
+/// use rocket::tls::{AllowedClient, FingerprintPolicy};
+///
+/// struct Deployers(HashSet<[u8; 32]>);
+///
+/// impl FingerprintPolicy for Deployers {
+///     fn allows(request: &Request, fingerprint: &[u8; 32]) -> bool {
+///         request.guard::<State<Deployers>>().succeeded()
+///             .map(|deployers| deployers.0.contains(fingerprint))
+///             .unwrap_or(false)
+///     }
+/// }
+///
+/// #[post("/deploy")]
+/// fn deploy(client: AllowedClient<Deployers>) -> String {
+///     format!("Deploying for {}.", client.name().unwrap_or("an unnamed client"))
+/// }
+/// # fn main() { }
+/// ```
+#[cfg(feature = "tls")]
+pub trait FingerprintPolicy {
+    /// Returns `true` if the client whose leaf certificate has the SHA-256
+    /// fingerprint `fingerprint` may send `request`.
+    fn allows(request: &Request, fingerprint: &[u8; 32]) -> bool;
+}
+
+/// The fingerprints in the `mtls_allowed_fingerprints` configuration
+/// parameter. The default policy of an
+/// [`AllowedClient`](/rocket/tls/struct.AllowedClient.html) guard.
+#[cfg(feature = "tls")]
+pub struct ConfiguredFingerprints;
+
+#[cfg(feature = "tls")]
+impl FingerprintPolicy for ConfiguredFingerprints {
+    fn allows(request: &Request, fingerprint: &[u8; 32]) -> bool {
+        request.config().mtls_allowed_fingerprints.contains(fingerprint)
+    }
+}
+
+/// The reason an [`AllowedClient`](/rocket/tls/struct.AllowedClient.html)
+/// guard failed. The status is `403 Forbidden` either way.
+#[cfg(feature = "tls")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllowedClientError {
+    /// The client presented no certificate.
+    Missing,
+    /// The client's certificate isn't allowed by the policy.
+    Forbidden,
+}
+
+/// A request guard for clients whose certificates are allowed by the
+/// [`FingerprintPolicy`] `P`. This type is only available when the `tls`
+/// feature is enabled.
+///
+/// With the default policy, [`ConfiguredFingerprints`], clients are allowed
+/// if the SHA-256 fingerprint of their leaf certificate is in the
+/// `mtls_allowed_fingerprints` configuration parameter. The guard fails with
+/// `403 Forbidden`, rendered by the `403` catcher, if the client presented no
+/// certificate or isn't allowed; unlike [`MutualTlsUser`], it never forwards.
+/// The allowed client's [`MutualTlsUser`] is available via `Deref`.
+///
+/// # Example
+///
+/// ```rust
+/// # #![feature(plugin, decl_macro)]
+/// # #![plugin(rocket_codegen)]
+/// # extern crate rocket;
+/// use rocket::Request;
+/// use rocket::tls::AllowedClient;
+///
+/// #[get("/admin")]
+/// fn admin(client: AllowedClient) -> String {
+///     format!("Welcome, {}!", client.name().unwrap_or("administrator"))
+/// }
+///
+/// #[catch(403)]
+/// fn forbidden(_: &Request) -> &'static str {
+///     "Your certificate isn't allowed here."
+/// }
+///
+/// fn main() {
+/// # if false {
+///     rocket::ignite()
+///         .mount("/", routes![admin])
+///         .catch(catchers![forbidden])
+///         .launch();
+/// # }
+/// }
+/// ```
+///
+/// [`FingerprintPolicy`]: /rocket/tls/trait.FingerprintPolicy.html
+/// [`ConfiguredFingerprints`]: /rocket/tls/struct.ConfiguredFingerprints.html
+/// [`MutualTlsUser`]: /rocket/tls/struct.MutualTlsUser.html
+#[cfg(feature = "tls")]
+pub struct AllowedClient<P: FingerprintPolicy = ConfiguredFingerprints> {
+    user: MutualTlsUser,
+    _policy: PhantomData<P>,
+}
+
+#[cfg(feature = "tls")]
+impl<P: FingerprintPolicy> AllowedClient<P> {
+    /// Returns the allowed client's `MutualTlsUser`.
+    #[inline(always)]
+    pub fn into_inner(self) -> MutualTlsUser {
+        self.user
+    }
+}
+
+#[cfg(feature = "tls")]
+impl<P: FingerprintPolicy> Deref for AllowedClient<P> {
+    type Target = MutualTlsUser;
+
+    #[inline(always)]
+    fn deref(&self) -> &MutualTlsUser {
+        &self.user
+    }
+}
+
+#[cfg(feature = "tls")]
+impl<P: FingerprintPolicy> fmt::Debug for AllowedClient<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("AllowedClient").field(&self.user).finish()
+    }
+}
+
+#[cfg(feature = "tls")]
+impl<'a, 'r, P: FingerprintPolicy> FromRequest<'a, 'r> for AllowedClient<P> {
+    type Error = AllowedClientError;
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        let user = match request.guard::<MutualTlsUser>() {
+            Success(user) => user,
+            _ => return Failure((Status::Forbidden, AllowedClientError::Missing))
+        };
+
+        if !P::allows(request, user.fingerprint()) {
+            let hex: Vec<_> = user.fingerprint().iter().map(|b| format!("{:02x}", b)).collect();
+            info_!("Client certificate {} isn't allowed.", hex.join(":"));
+            return Failure((Status::Forbidden, AllowedClientError::Forbidden));
+        }
+
+        Success(AllowedClient { user: user, _policy: PhantomData })
+    }
+}
+
+/// Wraps new connections with the TLS server currently in use, bounding the
+/// handshake by the configured timeout.
+#[cfg(feature = "tls")]
+#[derive(Clone)]
+pub(crate) struct TlsAcceptor {
+    reloader: TlsReloader,
+    timeout: Option<Duration>,
+    failures: HandshakeFailures,
+}
+
+#[cfg(feature = "tls")]
+impl SslServer for TlsAcceptor {
+    type Stream = HttpsStream;
+
+    fn wrap_server(&self, mut stream: HttpStream) -> hyper::Result<HttpsStream> {
+        use http::hyper::net::NetworkStream;
+
+        // Clone the server so the lock isn't held during the handshake.
+        let server = match *self.reloader.served.read().expect("tls lock") {
+            Some(ref served) => served.server.clone(),
+            None => unreachable!("an acceptor is only created once TLS is served")
+        };
+
+        // Hyper resets the read timeout once the first request's head is read.
+        stream.set_read_timeout(self.timeout)?;
+        stream.set_write_timeout(self.timeout)?;
+        let peer = stream.peer_addr().ok().map(|addr| addr.to_string());
+        RESUMED.with(|resumed| resumed.set(false));
+        server.wrap_server(stream).map_err(|e| {
+            let failure = HandshakeFailure::of(&e);
+            self.failures.count(failure);
+            warn_!("TLS handshake with {} failed ({}): {}",
+                   peer.as_ref().map_or("client", |peer| &**peer), failure.name(), e);
+            e
+        })
+    }
+}
+
+/// The cause of a failed TLS handshake.
+#[cfg(feature = "tls")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum HandshakeFailure {
+    NoCert,
+    BadCert,
+    Protocol,
+    Timeout,
+    Other,
+}
+
+#[cfg(feature = "tls")]
+impl HandshakeFailure {
+    fn of(error: &hyper::Error) -> HandshakeFailure {
+        use std::io::ErrorKind::{TimedOut, WouldBlock};
+        use rustls::TLSError;
+
+        let tls_error = match *error {
+            hyper::Error::Io(ref e) if e.kind() == TimedOut || e.kind() == WouldBlock => {
+                return HandshakeFailure::Timeout;
+            }
+            hyper::Error::Io(ref e) => e.get_ref().and_then(|e| e.downcast_ref::<TLSError>()),
+            hyper::Error::Ssl(ref e) => e.downcast_ref::<TLSError>(),
+            _ => None
+        };
+
+        match tls_error {
+            Some(&TLSError::NoCertificatesPresented) => HandshakeFailure::NoCert,
+            Some(&TLSError::WebPKIError(_)) => HandshakeFailure::BadCert,
+            Some(&TLSError::PeerIncompatibleError(_))
+                | Some(&TLSError::PeerMisbehavedError(_))
+                | Some(&TLSError::InappropriateMessage { .. })
+                | Some(&TLSError::InappropriateHandshakeMessage { .. })
+                | Some(&TLSError::CorruptMessage)
+                | Some(&TLSError::CorruptMessagePayload(_))
+                | Some(&TLSError::AlertReceived(_)) => HandshakeFailure::Protocol,
+            _ => HandshakeFailure::Other
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            HandshakeFailure::NoCert => "no_cert",
+            HandshakeFailure::BadCert => "bad_cert",
+            HandshakeFailure::Protocol => "protocol",
+            HandshakeFailure::Timeout => "timeout",
+            HandshakeFailure::Other => "other",
+        }
+    }
+}
+
+/// The counters of failed TLS handshakes, one for each cause.
+#[cfg(feature = "tls")]
+#[derive(Clone)]
+struct HandshakeFailures {
+    no_cert: Counter,
+    bad_cert: Counter,
+    protocol: Counter,
+    timeout: Counter,
+    other: Counter,
+}
+
+#[cfg(feature = "tls")]
+impl HandshakeFailures {
+    fn new(metrics: &Metrics) -> HandshakeFailures {
+        HandshakeFailures {
+            no_cert: metrics.counter("tls.handshakes_failed.no_cert"),
+            bad_cert: metrics.counter("tls.handshakes_failed.bad_cert"),
+            protocol: metrics.counter("tls.handshakes_failed.protocol"),
+            timeout: metrics.counter("tls.handshakes_failed.timeout"),
+            other: metrics.counter("tls.handshakes_failed.other"),
+        }
+    }
+
+    fn count(&self, failure: HandshakeFailure) {
+        match failure {
+            HandshakeFailure::NoCert => self.no_cert.incr(),
+            HandshakeFailure::BadCert => self.bad_cert.incr(),
+            HandshakeFailure::Protocol => self.protocol.incr(),
+            HandshakeFailure::Timeout => self.timeout.incr(),
+            HandshakeFailure::Other => self.other.incr(),
+        }
+    }
+}
+
+/// Returns the TLS server for `tls`, requesting client certificates and
+/// verifying them against the configured certificate store according to the
+/// mutual TLS policy. When SNI certificates are configured, the certificate is
+/// selected by the hostname the client requests, with `tls` as the default.
+/// The configured OCSP response, if any, is stapled to the default, and the
+/// configured ALPN protocols are advertised.
+#[cfg(feature = "tls")]
+fn tls_server(config: &Config, tls: config::TlsConfig) -> Result<TlsServer, String> {
+    use rustls::{self, RootCertStore, ClientCertVerifier};
+    use config::MutualTls;
+
+    let stapled = config.tls_ocsp.as_ref().map(|ocsp| ocsp.der.clone());
+    let single_cert = config.tls_sni.is_empty() && stapled.is_none();
+    let roots = match (config.mutual_tls, config.cert_store.as_ref()) {
+        (MutualTls::Disabled, _) => None,
+        (_, None) => return Err("mutual TLS is enabled, but there is no cert_store".into()),
+        (_, Some(store)) => {
+            let mut roots = RootCertStore::empty();
+            for cert in &store.0 {
+                roots.add(cert).map_err(|e| format!("invalid cert_store certificate: {:?}", e))?;
+            }
+
+            Some(roots)
+        }
+    };
+
+    let verifier: Arc<ClientCertVerifier> = match (config.mutual_tls, roots) {
+        (_, None) => rustls::NoClientAuth::new(),
+        (MutualTls::Required, Some(roots)) => rustls::AllowAnyAuthenticatedClient::new(roots),
+        (_, Some(roots)) => rustls::AllowAnyAnonymousOrAuthenticatedClient::new(roots),
+    };
+
+    let mut server_config = rustls::ServerConfig::new(verifier);
+    let cache = rustls::ServerSessionMemoryCache::new(1024);
+    server_config.set_persistence(Arc::new(TrackResumption(cache)));
+    server_config.ticketer = Arc::new(TrackResumption(rustls::Ticketer::new()));
+    server_config.set_protocols(&config.tls_alpn);
+    restrict_versions(&mut server_config, &config.tls_versions, &config.tls_ciphersuites)?;
+    if single_cert {
+        server_config.set_single_cert(tls.certs, tls.key);
+    } else {
+        server_config.cert_resolver = Arc::new(SniResolver::new(tls, stapled, &config.tls_sni)?);
+    }
+
+    Ok(TlsServer { cfg: Arc::new(server_config) })
+}
+
+/// Restricts the protocol versions and cipher suites `server_config` may
+/// negotiate to those named in `versions` and `ciphersuites`, unless they're
+/// empty. At least one of the cipher suites must be usable with one of the
+/// versions.
+#[cfg(feature = "tls")]
+fn restrict_versions(
+    server_config: &mut ::rustls::ServerConfig,
+    versions: &[String],
+    ciphersuites: &[String]
+) -> Result<(), String> {
+    use rustls::{ProtocolVersion, ALL_CIPHERSUITES};
+
+    if !versions.is_empty() {
+        server_config.versions = versions.iter()
+            .map(|version| match &**version {
+                "TLSv1.2" => Ok(ProtocolVersion::TLSv1_2),
+                "TLSv1.3" => Ok(ProtocolVersion::TLSv1_3),
+                _ => Err(format!("unsupported TLS version: {}", version))
+            })
+            .collect::<Result<_, _>>()?;
+    }
+
+    if !ciphersuites.is_empty() {
+        server_config.ciphersuites = ciphersuites.iter()
+            .map(|name| {
+                ALL_CIPHERSUITES.iter()
+                    .find(|suite| format!("{:?}", suite.suite) == *name)
+                    .map(|suite| *suite)
+                    .ok_or_else(|| format!("unsupported TLS cipher suite: {}", name))
+            })
+            .collect::<Result<_, _>>()?;
+    }
+
+    // The `TLS13_` suites are only usable with TLS 1.3, and the rest only with
+    // TLS 1.2.
+    let usable = server_config.ciphersuites.iter().any(|suite| {
+        let tls13 = format!("{:?}", suite.suite).starts_with("TLS13_");
+        server_config.versions.iter().any(|v| (*v == ProtocolVersion::TLSv1_3) == tls13)
+    });
+
+    if !usable {
+        return Err("none of the TLS cipher suites are usable with the TLS versions".into());
+    }
+
+    Ok(())
+}
+
+/// Selects the certificate for a connection by the hostname the client
+/// requests via SNI, falling back to the default certificate when the client
+/// requests no hostname or one without its own certificate. Only the default
+/// certificate is stapled with an OCSP response.
+#[cfg(feature = "tls")]
+struct SniResolver {
+    by_name: ::rustls::ResolvesServerCertUsingSNI,
+    default: ::rustls::sign::CertifiedKey,
+}
+
+#[cfg(feature = "tls")]
+impl SniResolver {
+    fn new(
+        default: config::TlsConfig,
+        ocsp: Option<Vec<u8>>,
+        sni: &[(String, config::TlsConfig)]
+    ) -> Result<SniResolver, String> {
+        let mut by_name = ::rustls::ResolvesServerCertUsingSNI::new();
+        for &(ref hostname, ref tls) in sni {
+            let key = certified_key(tls.certs.clone(), &tls.key)
+                .map_err(|e| format!("{} for {}", e, hostname))?;
+
+            by_name.add(hostname, key)
+                .map_err(|e| format!("invalid certificate for {}: {:?}", hostname, e))?;
+        }
+
+        let mut default = certified_key(default.certs, &default.key)?;
+        default.ocsp = ocsp;
+        Ok(SniResolver { by_name: by_name, default: default })
+    }
+}
+
+#[cfg(feature = "tls")]
+impl ::rustls::ResolvesServerCert for SniResolver {
+    fn resolve(
+        &self,
+        server_name: Option<::webpki::DNSNameRef>,
+        sigschemes: &[::rustls::SignatureScheme]
+    ) -> Option<::rustls::sign::CertifiedKey> {
+        self.by_name.resolve(server_name, sigschemes).or_else(|| Some(self.default.clone()))
+    }
+}
+
+/// Pairs `certs` with a signing key for `key`.
+#[cfg(feature = "tls")]
+fn certified_key(
+    certs: Vec<::rustls::Certificate>,
+    key: &::rustls::PrivateKey
+) -> Result<::rustls::sign::CertifiedKey, String> {
+    use rustls::sign::{CertifiedKey, RSASigningKey, SigningKey};
+
+    let key = RSASigningKey::new(key).map_err(|_| "invalid private key".to_string())?;
+    let key: Box<SigningKey> = Box::new(key);
+    Ok(CertifiedKey::new(certs, Arc::new(key)))
+}
+
+#[cfg(all(test, feature = "tls"))]
+mod tests {
+    use std::io;
+
+    use rustls::{self, TLSError, ProducesTickets};
+    use http::{hyper, Certificate};
+    use super::{HandshakeFailure, PeerCertificates, TlsReloader, TlsReloadError};
+    use super::{TrackResumption, RESUMED, restrict_versions};
+
+    fn io_error(e: TLSError) -> hyper::Error {
+        hyper::Error::Io(io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    #[test]
+    fn test_handshake_failure_causes() {
+        let timeout = hyper::Error::Io(io::Error::new(io::ErrorKind::WouldBlock, "timeout"));
+        assert_eq!(HandshakeFailure::of(&timeout), HandshakeFailure::Timeout);
+
+        let no_cert = io_error(TLSError::NoCertificatesPresented);
+        assert_eq!(HandshakeFailure::of(&no_cert), HandshakeFailure::NoCert);
+
+        let protocol = io_error(TLSError::PeerIncompatibleError("no versions".into()));
+        assert_eq!(HandshakeFailure::of(&protocol), HandshakeFailure::Protocol);
+
+        let ssl = hyper::Error::Ssl(Box::new(TLSError::CorruptMessage));
+        assert_eq!(HandshakeFailure::of(&ssl), HandshakeFailure::Protocol);
+
+        let reset = hyper::Error::Io(io::Error::new(io::ErrorKind::ConnectionReset, "reset"));
+        assert_eq!(HandshakeFailure::of(&reset), HandshakeFailure::Other);
+
+        let decrypt = io_error(TLSError::DecryptError);
+        assert_eq!(HandshakeFailure::of(&decrypt), HandshakeFailure::Other);
+    }
+
+    #[test]
+    fn test_peer_certificates_parse_once() {
+        let peer = PeerCertificates::new(vec![Certificate(vec![0x30, 0x00])]);
+        assert_eq!(peer.name(), None);
+        assert!(peer.name.try_get().is_some());
+        assert_eq!(peer.leaf(), None);
+        assert!(peer.leaf.try_get().is_some());
+
+        let fingerprint = *peer.fingerprint();
+        assert_eq!(peer.fingerprint.try_get(), Some(&fingerprint));
+
+        let peer = PeerCertificates::new(vec![]);
+        assert_eq!(peer.name(), None);
+        assert_eq!(peer.chain().len(), 0);
+    }
+
+    #[test]
+    fn test_refresh_ocsp_requires_serving() {
+        match TlsReloader::new().refresh_ocsp() {
+            Err(TlsReloadError::NotServing) => {}
+            other => panic!("refreshed OCSP without serving: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_resumed_tickets_are_recorded() {
+        let ticketer = TrackResumption(rustls::Ticketer::new());
+        let ticket = ticketer.encrypt(b"session").unwrap();
+        RESUMED.with(|resumed| resumed.set(false));
+
+        assert_eq!(ticketer.decrypt(b"not a ticket"), None);
+        assert!(!RESUMED.with(|resumed| resumed.get()));
+
+        assert_eq!(ticketer.decrypt(&ticket), Some(b"session".to_vec()));
+        assert!(RESUMED.with(|resumed| resumed.get()));
+    }
+
+    #[test]
+    fn test_restrict_versions() {
+        let strings = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        let mut config = rustls::ServerConfig::new(rustls::NoClientAuth::new());
+        let all_suites = config.ciphersuites.len();
+        assert!(restrict_versions(&mut config, &[], &[]).is_ok());
+        assert_eq!(config.ciphersuites.len(), all_suites);
+
+        let suites = strings(&["TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384",
+                               "TLS13_AES_128_GCM_SHA256"]);
+        assert!(restrict_versions(&mut config, &strings(&["TLSv1.2"]), &suites).is_ok());
+        assert_eq!(config.versions, vec![rustls::ProtocolVersion::TLSv1_2]);
+        assert_eq!(config.ciphersuites.len(), 2);
+
+        let mut config = rustls::ServerConfig::new(rustls::NoClientAuth::new());
+        let tls13 = strings(&["TLS13_AES_128_GCM_SHA256"]);
+        assert!(restrict_versions(&mut config, &strings(&["TLSv1.2"]), &tls13).is_err());
+        assert!(restrict_versions(&mut config, &strings(&["TLSv1.1"]), &[]).is_err());
+        assert!(restrict_versions(&mut config, &[], &strings(&["RC4"])).is_err());
+    }
+}
@@ -0,0 +1,289 @@
+//! API versioning: routing requests to the routes for a negotiated version.
+//!
+//! Routes declare the API version they serve with the `version` route
+//! parameter, as in `#[get("/users", version = "v2")]`. Routes for different
+//! versions of the same endpoint don't collide. When versioning is enabled via
+//! [`Rocket::versioning()`], Rocket determines the version each request asks
+//! for in one of three ways:
+//!
+//!   * **path prefix**: the first segment of the path names the version, as in
+//!     `/v2/users`. The segment is removed before routing, so the route above
+//!     matches `/v2/users`. Only segments naming the version of some mounted
+//!     route are treated as versions.
+//!   * **header**: a request header, such as `Api-Version: v2`, names the
+//!     version.
+//!   * **query**: a query parameter, such as `?api-version=v2`, names the
+//!     version.
+//!
+//! A request that doesn't name a version gets the default version, if one is
+//! configured. Versioned routes only match requests for their version;
+//! unversioned routes match requests for any version, including none. The
+//! negotiated version is available via
+//! [`Request::api_version()`](/rocket/struct.Request.html#method.api_version).
+//!
+//! Versions can be marked as deprecated, optionally with a sunset date.
+//! Responses to requests for a deprecated version carry a `Deprecation: true`
+//! header and, if there is a sunset date, a `Sunset` header.
+//!
+//! # Deprecated Routes
+//!
+//! Individual routes can be deprecated as well, with the `deprecated_since`,
+//! `sunset`, and `successor` route parameters:
+//!
+//! ```rust,ignore
+//! #[get("/users", deprecated_since = "2018-06-01", sunset = "2019-01-01",
+//!       successor = "/v2/users")]
+//! ```
+//!
+//! Dates are given as `YYYY-MM-DD` and sent as HTTP dates at midnight UTC.
+//! Responses from a deprecated route carry a [`Deprecation`] header with the
+//! date the route was deprecated, or `true` if there is none, a `Sunset`
+//! header if there is a sunset date, and a `Link` header with a relation type
+//! of `successor-version` if there is a successor. Deprecations are listed in
+//! the [launch summary](/rocket/summary/index.html).
+//!
+//! [`Deprecation`]: /rocket/versioning/struct.Deprecation.html
+//!
+//! # Example
+//!
+//! ```rust
+//! # #![feature(plugin, decl_macro)]
+//! # #![plugin(rocket_codegen)]
+//! # extern crate rocket;
+//! use rocket::versioning::Versioning;
+//!
+//! #[get("/users", version = "v1")]
+//! fn users_v1() -> &'static str { "[\"bob\"]" }
+//!
+//! #[get("/users", version = "v2")]
+//! fn users_v2() -> &'static str { "{\"users\": [\"bob\"]}" }
+//!
+//! fn main() {
+//! # if false {
+//!     let versioning = Versioning::header("Api-Version")
+//!         .default_version("v2")
+//!         .sunset("v1", "Sat, 01 Jun 2019 00:00:00 GMT");
+//!
+//!     rocket::ignite()
+//!         .mount("/", routes![users_v1, users_v2])
+//!         .versioning(versioning)
+//!         .launch();
+//! # }
+//! }
+//! ```
+//!
+//! [`Rocket::versioning()`]: /rocket/struct.Rocket.html#method.versioning
+
+use time;
+
+use http::hyper::header::HttpDate;
+use request::{Request, FormItems};
+use response::Response;
+use router::Router;
+
+/// How the API version of a request is determined.
+#[derive(Debug, Clone, PartialEq)]
+enum Negotiation {
+    PathPrefix,
+    Header(String),
+    Query(String),
+}
+
+/// The API version negotiated for a request, cached in the request.
+pub(crate) struct NegotiatedVersion(pub(crate) Option<String>);
+
+/// Configuration for API version negotiation.
+///
+/// See the [module level documentation](/rocket/versioning/index.html) for
+/// details and an example.
+#[derive(Debug, Clone)]
+pub struct Versioning {
+    negotiation: Negotiation,
+    default: Option<String>,
+    deprecated: Vec<(String, Option<String>)>,
+}
+
+impl Versioning {
+    fn new(negotiation: Negotiation) -> Versioning {
+        Versioning { negotiation: negotiation, default: None, deprecated: vec![] }
+    }
+
+    /// Determine the version from the first segment of the request's path,
+    /// as in `/v2/users`.
+    pub fn path_prefix() -> Versioning {
+        Versioning::new(Negotiation::PathPrefix)
+    }
+
+    /// Determine the version from the value of the request header `name`.
+    pub fn header<S: Into<String>>(name: S) -> Versioning {
+        Versioning::new(Negotiation::Header(name.into()))
+    }
+
+    /// Determine the version from the value of the query parameter `param`.
+    pub fn query<S: Into<String>>(param: S) -> Versioning {
+        Versioning::new(Negotiation::Query(param.into()))
+    }
+
+    /// Sets the version used for requests that don't name one.
+    pub fn default_version<S: Into<String>>(mut self, version: S) -> Self {
+        self.default = Some(version.into());
+        self
+    }
+
+    /// Marks `version` as deprecated.
+    pub fn deprecate<S: Into<String>>(self, version: S) -> Self {
+        self.mark_deprecated(version.into(), None)
+    }
+
+    /// Marks `version` as deprecated and to be removed on the HTTP date
+    /// `date`, such as `"Sat, 01 Jun 2019 00:00:00 GMT"`.
+    pub fn sunset<S: Into<String>, D: Into<String>>(self, version: S, date: D) -> Self {
+        self.mark_deprecated(version.into(), Some(date.into()))
+    }
+
+    fn mark_deprecated(mut self, version: String, sunset: Option<String>) -> Self {
+        self.deprecated.retain(|&(ref v, _)| *v != version);
+        self.deprecated.push((version, sunset));
+        self
+    }
+
+    /// Determines the version of `request`, removing the version prefix from
+    /// the path if the version is negotiated by path prefix.
+    pub(crate) fn negotiate(&self, request: &mut Request, router: &Router) {
+        let requested = match self.negotiation {
+            Negotiation::PathPrefix => {
+                let stripped = {
+                    let (uri, path) = (request.uri(), request.uri().path());
+                    match uri.segments().next() {
+                        Some(segment) if path[1..].starts_with(segment)
+                                && router.has_version(segment) => {
+                            let rest = &path[(segment.len() + 1)..];
+                            let rest = if rest.is_empty() { "/" } else { rest };
+                            let new_uri = match uri.query() {
+                                Some(query) => format!("{}?{}", rest, query),
+                                None => rest.to_string()
+                            };
+
+                            Some((segment.to_string(), new_uri))
+                        }
+                        _ => None
+                    }
+                };
+
+                stripped.map(|(version, uri)| {
+                    request.set_uri(uri);
+                    version
+                })
+            }
+            Negotiation::Header(ref name) => {
+                match request.headers().get_one(name).map(|value| value.trim()) {
+                    Some(value) if !value.is_empty() => Some(value.to_string()),
+                    _ => None
+                }
+            }
+            Negotiation::Query(ref param) => {
+                request.uri().query().and_then(|query| {
+                    FormItems::from(query)
+                        .find(|&(key, _)| key.as_str() == param.as_str())
+                        .and_then(|(_, value)| value.url_decode().ok())
+                })
+            }
+        };
+
+        let version = requested.or_else(|| self.default.clone());
+        request.local_cache(|| NegotiatedVersion(version));
+    }
+
+    /// Adds deprecation headers to `response` if the version of `request` is
+    /// deprecated.
+    pub(crate) fn annotate(&self, request: &Request, response: &mut Response) {
+        let version = match request.api_version() {
+            Some(version) => version,
+            None => return
+        };
+
+        let deprecation = self.deprecated.iter().find(|&&(ref v, _)| v == version);
+        if let Some(&(_, ref sunset)) = deprecation {
+            response.set_raw_header("Deprecation", "true");
+            if let Some(ref date) = *sunset {
+                response.set_raw_header("Sunset", date.clone());
+            }
+        }
+    }
+}
+
+/// The deprecation of a route: when it was deprecated, when it will be
+/// removed, and what replaces it.
+///
+/// Any route with a `Deprecation` is deprecated, even if none of its fields
+/// are set. Dates are either `YYYY-MM-DD` dates or HTTP dates.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::{Request, Route, Data};
+/// use rocket::handler::Outcome;
+/// use rocket::http::Method;
+/// use rocket::versioning::Deprecation;
+///
+/// fn handler<'r>(request: &'r Request, _data: Data) -> Outcome<'r> {
+///     Outcome::from(request, "[\"bob\"]")
+/// }
+///
+/// let mut users = Route::new(Method::Get, "/users", handler);
+/// users.deprecation = Some(Deprecation {
+///     since: Some("2018-06-01".into()),
+///     sunset: Some("2019-01-01".into()),
+///     successor: Some("/v2/users".into()),
+/// });
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Deprecation {
+    /// The date the route was deprecated, if known.
+    pub since: Option<String>,
+    /// The date the route will be removed, if known.
+    pub sunset: Option<String>,
+    /// The URI of the route that replaces this one, if any.
+    pub successor: Option<String>,
+}
+
+impl Deprecation {
+    /// Adds the `Deprecation`, `Sunset`, and successor `Link` headers for
+    /// this deprecation to `response`.
+    pub(crate) fn annotate(&self, response: &mut Response) {
+        match self.since {
+            Some(ref date) => response.set_raw_header("Deprecation", http_date(date)),
+            None => response.set_raw_header("Deprecation", "true"),
+        };
+
+        if let Some(ref date) = self.sunset {
+            response.set_raw_header("Sunset", http_date(date));
+        }
+
+        if let Some(ref uri) = self.successor {
+            let link = format!("<{}>; rel=\"successor-version\"", uri);
+            response.adjoin_raw_header("Link", link);
+        }
+    }
+}
+
+/// Converts a `YYYY-MM-DD` date to an HTTP date at midnight UTC. Any other
+/// value, such as a date that's already an HTTP date, is returned as is.
+fn http_date(date: &str) -> String {
+    match time::strptime(date, "%Y-%m-%d") {
+        Ok(tm) => HttpDate(time::at_utc(tm.to_timespec())).to_string(),
+        Err(_) => date.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::http_date;
+
+    #[test]
+    fn test_http_date() {
+        assert_eq!(http_date("2025-01-01"), "Wed, 01 Jan 2025 00:00:00 GMT");
+        assert_eq!(http_date("2019-06-01"), "Sat, 01 Jun 2019 00:00:00 GMT");
+        assert_eq!(http_date("Sat, 01 Jun 2019 00:00:00 GMT"), "Sat, 01 Jun 2019 00:00:00 GMT");
+    }
+}
@@ -0,0 +1,637 @@
+//! A minimal DER reader for the parts of X.509 certificates Rocket inspects.
+//!
+//! Certificates are verified by rustls before they reach Rocket, so this
+//! reader doesn't validate them; it only extracts fields, returning `None` for
+//! anything it doesn't understand.
+
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use sha2::{Sha256, Digest};
+
+use http::Certificate;
+
+const SEQUENCE: u8 = 0x30;
+const SET: u8 = 0x31;
+const INTEGER: u8 = 0x02;
+const OID: u8 = 0x06;
+const OCTET_STRING: u8 = 0x04;
+const BOOLEAN: u8 = 0x01;
+const UTC_TIME: u8 = 0x17;
+const GENERALIZED_TIME: u8 = 0x18;
+const VERSION: u8 = 0xA0;
+const EXTENSIONS: u8 = 0xA3;
+
+/// The object identifier of the subject alternative name extension.
+const SUBJECT_ALT_NAME: &[u8] = &[0x55, 0x1D, 0x11];
+
+/// The tags of the `rfc822Name` and `dNSName` alternative names.
+const SAN_EMAIL: u8 = 0x81;
+const SAN_DNS: u8 = 0x82;
+
+/// The short names of well-known attributes of distinguished names, by
+/// object identifier.
+const ATTRIBUTE_NAMES: &[(&[u8], &str)] = &[
+    (&[0x55, 0x04, 0x03], "CN"),
+    (&[0x55, 0x04, 0x05], "serialNumber"),
+    (&[0x55, 0x04, 0x06], "C"),
+    (&[0x55, 0x04, 0x07], "L"),
+    (&[0x55, 0x04, 0x08], "ST"),
+    (&[0x55, 0x04, 0x0A], "O"),
+    (&[0x55, 0x04, 0x0B], "OU"),
+    (&[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x01], "emailAddress"),
+    (&[0x09, 0x92, 0x26, 0x89, 0x93, 0xF2, 0x2C, 0x64, 0x01, 0x01], "UID"),
+    (&[0x09, 0x92, 0x26, 0x89, 0x93, 0xF2, 0x2C, 0x64, 0x01, 0x19], "DC"),
+];
+
+/// Reads DER encoded values, tag and contents, in order.
+#[derive(Debug, Clone, Copy)]
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the tag and contents of the next value.
+    fn next(&mut self) -> Option<(u8, &'a [u8])> {
+        let (&tag, rest) = self.0.split_first()?;
+        let (&first, mut rest) = rest.split_first()?;
+        let len = match first {
+            0...0x7F => first as usize,
+            0x81...0x84 => {
+                let n = (first & 0x7F) as usize;
+                if rest.len() < n {
+                    return None;
+                }
+
+                let len = rest[..n].iter().fold(0, |len, &b| (len << 8) | b as usize);
+                rest = &rest[n..];
+                len
+            }
+            _ => return None
+        };
+
+        if rest.len() < len {
+            return None;
+        }
+
+        self.0 = &rest[len..];
+        Some((tag, &rest[..len]))
+    }
+
+    /// Returns the whole encoding, tag and length included, of the next value.
+    fn next_raw(&mut self) -> Option<&'a [u8]> {
+        let start = self.0;
+        self.next()?;
+        Some(&start[..(start.len() - self.0.len())])
+    }
+
+    /// Returns the contents of the next value if it has tag `tag`.
+    fn expect(&mut self, tag: u8) -> Option<&'a [u8]> {
+        match self.next()? {
+            (actual, contents) if actual == tag => Some(contents),
+            _ => None
+        }
+    }
+
+    /// Skips the next value if it has tag `tag`.
+    fn skip_if(&mut self, tag: u8) {
+        if self.0.first() == Some(&tag) {
+            self.next();
+        }
+    }
+}
+
+/// Returns the contents of the `tbsCertificate` of the certificate `der`,
+/// positioned after the optional version.
+fn tbs_certificate(der: &[u8]) -> Option<Reader> {
+    let certificate = Reader(der).expect(SEQUENCE)?;
+    let mut tbs = Reader(Reader(certificate).expect(SEQUENCE)?);
+    tbs.skip_if(VERSION);
+    Some(tbs)
+}
+
+/// Returns the subject of the certificate `der`, a DER encoded `Name`.
+fn subject(der: &[u8]) -> Option<&[u8]> {
+    let mut tbs = tbs_certificate(der)?;
+    for _ in 0..4 {
+        // The serial number, signature algorithm, issuer, and validity.
+        tbs.next()?;
+    }
+
+    tbs.expect(SEQUENCE)
+}
+
+/// Returns the common name of the subject of the certificate `der`, if it
+/// has one. Unlike [`ParsedCertificate::parse()`], this only requires the
+/// fields preceding the subject to be well-formed.
+pub(crate) fn common_name(der: &[u8]) -> Option<String> {
+    let subject = DistinguishedName::parse(subject(der)?)?;
+    subject.common_name().map(|name| name.to_string())
+}
+
+/// A distinguished name, such as the subject or issuer of a certificate: a
+/// sequence of attributes, like `CN=alice` or `O=Rocket`.
+///
+/// Well-known attributes are named by their short names: `CN`, `O`, `OU`, `C`,
+/// `ST`, `L`, `serialNumber`, `emailAddress`, `UID`, and `DC`. Others are named
+/// by their object identifiers in dotted notation, as in `2.5.4.12`.
+/// Attributes whose values aren't strings are omitted.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DistinguishedName {
+    attributes: Vec<(String, String)>,
+}
+
+impl DistinguishedName {
+    fn parse(name: &[u8]) -> Option<DistinguishedName> {
+        let mut attributes = vec![];
+        let mut rdns = Reader(name);
+        while !rdns.is_empty() {
+            let mut set = Reader(rdns.expect(SET)?);
+            while !set.is_empty() {
+                let mut attribute = Reader(set.expect(SEQUENCE)?);
+                let oid = attribute.expect(OID)?;
+                let (tag, value) = attribute.next()?;
+                if let Some(value) = string(tag, value) {
+                    attributes.push((attribute_name(oid)?, value));
+                }
+            }
+        }
+
+        Some(DistinguishedName { attributes: attributes })
+    }
+
+    /// Returns the value of the first attribute named `name`, if there is one.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// use rocket::tls::DistinguishedName;
+    ///
+    /// # fn check(subject: &DistinguishedName) {
+    /// if subject.get("O") == Some("Rocket") {
+    ///     /* the subject is in the Rocket organization */
+    /// }
+    /// # }
+    /// ```
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.attributes.iter()
+            .find(|&&(ref attr, _)| attr == name)
+            .map(|&(_, ref value)| &**value)
+    }
+
+    /// Returns the value of the first common name (`CN`) attribute, if there
+    /// is one.
+    #[inline]
+    pub fn common_name(&self) -> Option<&str> {
+        self.get("CN")
+    }
+
+    /// Returns the attributes as `(name, value)` pairs in the order in which
+    /// they appear in the certificate.
+    #[inline]
+    pub fn attributes(&self) -> &[(String, String)] {
+        &self.attributes
+    }
+}
+
+/// Formats the attributes in the order in which they appear, as in
+/// `O=Rocket, CN=alice`.
+impl fmt::Display for DistinguishedName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, &(ref name, ref value)) in self.attributes.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+
+            write!(f, "{}={}", name, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The fields of an X.509 certificate that matter to authorization decisions.
+///
+/// A `ParsedCertificate` is usually retrieved via
+/// [`MutualTlsUser::certificate()`], which parses the client's leaf
+/// certificate once per connection, but any certificate can be parsed via
+/// [`ParsedCertificate::parse()`](#method.parse). This type is only available
+/// when the `tls` feature is enabled.
+///
+/// # Example
+///
+/// Authorize clients by the DNS names in their certificates:
+///
+/// ```rust
+/// # #![feature(plugin, decl_macro)]
+/// # #![plugin(rocket_codegen)]
+/// # extern crate rocket;
+/// use rocket::tls::MutualTlsUser;
+/// use rocket::http::Status;
+///
+/// #[get("/deploy")]
+/// fn deploy(user: MutualTlsUser) -> Result<&'static str, Status> {
+///     let certificate = user.certificate().ok_or(Status::Forbidden)?;
+///     match certificate.dns_names().iter().any(|name| name == "ci.example.com") {
+///         true => Ok("deploying"),
+///         false => Err(Status::Forbidden)
+///     }
+/// }
+/// # fn main() { }
+/// ```
+///
+/// [`MutualTlsUser::certificate()`]: /rocket/tls/struct.MutualTlsUser.html#method.certificate
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedCertificate {
+    subject: DistinguishedName,
+    issuer: DistinguishedName,
+    serial: Vec<u8>,
+    not_before: SystemTime,
+    not_after: SystemTime,
+    dns_names: Vec<String>,
+    emails: Vec<String>,
+    fingerprint: [u8; 32],
+    key_fingerprint: [u8; 32],
+}
+
+impl ParsedCertificate {
+    /// Parses the DER encoded `certificate`. Returns `None` if `certificate`
+    /// isn't an X.509 certificate or uses encodings this parser doesn't
+    /// understand.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::http::Certificate;
+    /// use rocket::tls::ParsedCertificate;
+    ///
+    /// assert!(ParsedCertificate::parse(&Certificate(vec![0x30, 0x00])).is_none());
+    /// ```
+    pub fn parse(certificate: &Certificate) -> Option<ParsedCertificate> {
+        let der = &certificate.0;
+        let mut tbs = tbs_certificate(der)?;
+
+        let serial = tbs.expect(INTEGER)?;
+        tbs.expect(SEQUENCE)?;
+        let issuer = DistinguishedName::parse(tbs.expect(SEQUENCE)?)?;
+        let mut validity = Reader(tbs.expect(SEQUENCE)?);
+        let (not_before, not_after) = (time(&mut validity)?, time(&mut validity)?);
+        let subject = DistinguishedName::parse(tbs.expect(SEQUENCE)?)?;
+        let key = tbs.next_raw()?;
+
+        // The issuer and subject unique IDs precede the extensions.
+        let (mut dns_names, mut emails) = (vec![], vec![]);
+        while let Some((tag, contents)) = tbs.next() {
+            if tag == EXTENSIONS {
+                alt_names(contents, &mut dns_names, &mut emails)?;
+            }
+        }
+
+        // Integers are padded with a zero byte when the high bit is set.
+        let serial = match serial.split_first() {
+            Some((&0, rest)) if !rest.is_empty() => rest,
+            _ => serial
+        };
+
+        Some(ParsedCertificate {
+            subject, issuer, not_before, not_after, dns_names, emails,
+            serial: serial.to_vec(),
+            fingerprint: sha256(der),
+            key_fingerprint: sha256(key),
+        })
+    }
+
+    /// Returns the subject: who the certificate identifies.
+    #[inline]
+    pub fn subject(&self) -> &DistinguishedName {
+        &self.subject
+    }
+
+    /// Returns the issuer: who signed the certificate.
+    #[inline]
+    pub fn issuer(&self) -> &DistinguishedName {
+        &self.issuer
+    }
+
+    /// Returns the serial number as big-endian bytes.
+    #[inline]
+    pub fn serial(&self) -> &[u8] {
+        &self.serial
+    }
+
+    /// Returns the time before which the certificate isn't valid.
+    #[inline]
+    pub fn not_before(&self) -> SystemTime {
+        self.not_before
+    }
+
+    /// Returns the time after which the certificate isn't valid.
+    #[inline]
+    pub fn not_after(&self) -> SystemTime {
+        self.not_after
+    }
+
+    /// Returns `true` if `time` is within the certificate's validity period.
+    #[inline]
+    pub fn is_valid_at(&self, time: SystemTime) -> bool {
+        self.not_before <= time && time <= self.not_after
+    }
+
+    /// Returns the DNS names in the subject alternative name extension.
+    #[inline]
+    pub fn dns_names(&self) -> &[String] {
+        &self.dns_names
+    }
+
+    /// Returns the email addresses in the subject alternative name extension.
+    #[inline]
+    pub fn emails(&self) -> &[String] {
+        &self.emails
+    }
+
+    /// Returns the SHA-256 digest of the DER encoded certificate.
+    #[inline]
+    pub fn fingerprint(&self) -> &[u8; 32] {
+        &self.fingerprint
+    }
+
+    /// Returns the SHA-256 digest of the DER encoded subject public key info:
+    /// a fingerprint of the key that stays the same when a certificate for
+    /// the same key is reissued.
+    #[inline]
+    pub fn key_fingerprint(&self) -> &[u8; 32] {
+        &self.key_fingerprint
+    }
+}
+
+/// Collects the DNS names and email addresses in the subject alternative name
+/// extension of the extensions `extensions`.
+fn alt_names(extensions: &[u8], dns: &mut Vec<String>, emails: &mut Vec<String>) -> Option<()> {
+    let mut extensions = Reader(Reader(extensions).expect(SEQUENCE)?);
+    while !extensions.is_empty() {
+        let mut extension = Reader(extensions.expect(SEQUENCE)?);
+        if extension.expect(OID)? != SUBJECT_ALT_NAME {
+            continue;
+        }
+
+        extension.skip_if(BOOLEAN);
+        let value = extension.expect(OCTET_STRING)?;
+        let mut names = Reader(Reader(value).expect(SEQUENCE)?);
+        while let Some((tag, name)) = names.next() {
+            match tag {
+                SAN_DNS => dns.push(String::from_utf8(name.to_vec()).ok()?),
+                SAN_EMAIL => emails.push(String::from_utf8(name.to_vec()).ok()?),
+                _ => continue
+            }
+        }
+    }
+
+    Some(())
+}
+
+/// Returns the name of the attribute with object identifier `oid`.
+fn attribute_name(oid: &[u8]) -> Option<String> {
+    match ATTRIBUTE_NAMES.iter().find(|&&(id, _)| id == oid) {
+        Some(&(_, name)) => Some(name.to_string()),
+        None => dotted(oid)
+    }
+}
+
+/// Formats the object identifier `oid` in dotted notation.
+fn dotted(oid: &[u8]) -> Option<String> {
+    let (mut arcs, mut arc) = (vec![], 0u64);
+    for &byte in oid {
+        arc = arc.checked_mul(128)? | (byte & 0x7F) as u64;
+        if byte & 0x80 == 0 {
+            arcs.push(arc);
+            arc = 0;
+        }
+    }
+
+    let first = *arcs.first()?;
+    let (x, y) = match first {
+        0...39 => (0, first),
+        40...79 => (1, first - 40),
+        _ => (2, first - 80)
+    };
+
+    let rest: Vec<_> = arcs[1..].iter().map(|arc| arc.to_string()).collect();
+    match rest.is_empty() {
+        true => Some(format!("{}.{}", x, y)),
+        false => Some(format!("{}.{}.{}", x, y, rest.join(".")))
+    }
+}
+
+/// Decodes the DER string `value` with tag `tag`.
+fn string(tag: u8, value: &[u8]) -> Option<String> {
+    match tag {
+        // UTF8String, PrintableString, TeletexString, IA5String.
+        0x0C | 0x13 | 0x14 | 0x16 => String::from_utf8(value.to_vec()).ok(),
+        // BMPString, UTF-16 big endian.
+        0x1E if value.len() % 2 == 0 => {
+            let units: Vec<u16> = value.chunks(2)
+                .map(|pair| (pair[0] as u16) << 8 | pair[1] as u16)
+                .collect();
+
+            String::from_utf16(&units).ok()
+        }
+        _ => None
+    }
+}
+
+/// Reads the next `UTCTime` or `GeneralizedTime` from `reader`. Both must be
+/// in UTC with seconds, as RFC 5280 requires.
+fn time(reader: &mut Reader) -> Option<SystemTime> {
+    let (tag, value) = reader.next()?;
+    let value = ::std::str::from_utf8(value).ok()?;
+    if !value.ends_with('Z') || !value[..(value.len() - 1)].bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let digits = |range: ::std::ops::Range<usize>| value[range].parse::<i64>().ok();
+    let (year, rest) = match (tag, value.len()) {
+        (UTC_TIME, 13) => match digits(0..2)? {
+            year if year >= 50 => (1900 + year, 2),
+            year => (2000 + year, 2)
+        },
+        (GENERALIZED_TIME, 15) => (digits(0..4)?, 4),
+        _ => return None
+    };
+
+    let (month, day) = (digits(rest..(rest + 2))?, digits((rest + 2)..(rest + 4))?);
+    let hour = digits((rest + 4)..(rest + 6))?;
+    let minute = digits((rest + 6)..(rest + 8))?;
+    let second = digits((rest + 8)..(rest + 10))?;
+    if month < 1 || month > 12 || day < 1 || day > 31 || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    // Days since the epoch of the proleptic Gregorian date, after Howard
+    // Hinnant's `days_from_civil`.
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = (if y >= 0 { y } else { y - 399 }) / 400;
+    let yoe = y - era * 400;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+
+    let secs = days * 86400 + hour * 3600 + minute * 60 + second;
+    match secs >= 0 {
+        true => Some(UNIX_EPOCH + Duration::from_secs(secs as u64)),
+        false => Some(UNIX_EPOCH - Duration::from_secs(-secs as u64)),
+    }
+}
+
+pub(crate) fn sha256(bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::default();
+    hasher.input(bytes);
+
+    let mut digest = [0; 32];
+    digest.copy_from_slice(&hasher.result());
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use base64;
+    use http::Certificate;
+    use super::{ParsedCertificate, DistinguishedName, Reader, common_name, dotted, time};
+
+    const ALICE: &str = include_str!("../tests/mtls/alice.pem");
+
+    fn alice() -> Certificate {
+        let body: String = ALICE.lines().filter(|line| !line.starts_with("-----")).collect();
+        Certificate(base64::decode(&body).unwrap())
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    // A certificate with subject `name` and empty placeholders for every other
+    // field: only the structure matters here.
+    fn certificate(name: &[u8]) -> Vec<u8> {
+        let mut tbs = vec![0xA0, 0x03, 0x02, 0x01, 0x02, 0x02, 0x01, 0x01, 0x30, 0x00, 0x30, 0x00,
+                           0x30, 0x00];
+        tbs.push(0x30);
+        tbs.push(name.len() as u8);
+        tbs.extend_from_slice(name);
+        tbs.extend_from_slice(&[0x30, 0x00]);
+
+        let mut certificate = vec![0x30, tbs.len() as u8];
+        certificate.extend(tbs);
+        certificate.extend_from_slice(&[0x30, 0x00, 0x03, 0x01, 0x00]);
+
+        let mut der = vec![0x30, certificate.len() as u8];
+        der.extend(certificate);
+        der
+    }
+
+    #[test]
+    fn test_reader() {
+        let mut reader = Reader(&[0x02, 0x01, 0x05, 0x04, 0x81, 0x02, 0xAB, 0xCD]);
+        assert_eq!(reader.next(), Some((0x02, &[0x05][..])));
+        assert_eq!(reader.expect(0x04), Some(&[0xAB, 0xCD][..]));
+        assert!(reader.is_empty());
+
+        let mut reader = Reader(&[0x02, 0x01, 0x05, 0x04, 0x81, 0x02, 0xAB, 0xCD]);
+        assert_eq!(reader.next_raw(), Some(&[0x02, 0x01, 0x05][..]));
+        assert_eq!(reader.next_raw(), Some(&[0x04, 0x81, 0x02, 0xAB, 0xCD][..]));
+        assert!(reader.is_empty());
+
+        assert_eq!(Reader(&[0x02, 0x02, 0x05]).next(), None);
+        assert_eq!(Reader(&[0x02, 0x85, 0, 0, 0, 0, 1, 0]).next(), None);
+        assert_eq!(Reader(&[0x02, 0x01, 0x05]).expect(0x04), None);
+    }
+
+    #[test]
+    fn test_common_name() {
+        let cn = [0x31, 0x0E, 0x30, 0x0C, 0x06, 0x03, 0x55, 0x04, 0x03,
+                  0x0C, 0x05, b'a', b'l', b'i', b'c', b'e'];
+        let org = [0x31, 0x0F, 0x30, 0x0D, 0x06, 0x03, 0x55, 0x04, 0x0A,
+                   0x13, 0x06, b'R', b'o', b'c', b'k', b'e', b't'];
+
+        let name: Vec<u8> = org.iter().chain(cn.iter()).cloned().collect();
+        assert_eq!(common_name(&certificate(&name)), Some("alice".into()));
+        assert_eq!(common_name(&certificate(&org)), None);
+        assert_eq!(common_name(&[0x30, 0x00]), None);
+        assert_eq!(common_name(&[]), None);
+
+        let bmp = [0x31, 0x0E, 0x30, 0x0C, 0x06, 0x03, 0x55, 0x04, 0x03,
+                   0x1E, 0x05, 0x00, b'b', 0x00, b'o', 0x00];
+        assert_eq!(common_name(&certificate(&bmp)), None);
+
+        // The placeholders make the certificate unparseable as a whole.
+        assert_eq!(ParsedCertificate::parse(&Certificate(certificate(&name))), None);
+    }
+
+    #[test]
+    fn test_parse_certificate() {
+        let certificate = ParsedCertificate::parse(&alice()).unwrap();
+        assert_eq!(certificate.subject().to_string(), "O=Rocket, CN=alice");
+        assert_eq!(certificate.subject().common_name(), Some("alice"));
+        assert_eq!(certificate.issuer().get("O"), Some("Rocket"));
+        assert_eq!(certificate.issuer().get("OU"), None);
+        assert_eq!(hex(certificate.serial()), "4945a8a2534ba1c95e201f068b8ae482a73bf99c");
+        assert_eq!(certificate.not_before(), UNIX_EPOCH + Duration::from_secs(1792181147));
+        assert_eq!(certificate.not_after(), UNIX_EPOCH + Duration::from_secs(4945781147));
+        assert!(certificate.is_valid_at(UNIX_EPOCH + Duration::from_secs(2000000000)));
+        assert!(!certificate.is_valid_at(UNIX_EPOCH));
+        assert_eq!(certificate.dns_names(), &["alice.example.com".to_string()]);
+        assert_eq!(certificate.emails(), &["alice@example.com".to_string()]);
+        assert_eq!(hex(certificate.fingerprint()),
+                   "0bf5ad9e01f51f68cf44f2fdb0951bccf5157e0e7bc1487b84b3400bae928b8d");
+        assert_eq!(hex(certificate.key_fingerprint()),
+                   "50e0fa89986303cfef5c8b2946c0ca20e203fe73cb280d89c44d23f766dad915");
+
+        assert_eq!(ParsedCertificate::parse(&Certificate(vec![0x30, 0x00])), None);
+        assert_eq!(ParsedCertificate::parse(&Certificate(vec![])), None);
+
+        let mut truncated = alice();
+        truncated.0.truncate(200);
+        assert_eq!(ParsedCertificate::parse(&truncated), None);
+    }
+
+    #[test]
+    fn test_distinguished_names() {
+        let name = [0x31, 0x0E, 0x30, 0x0C, 0x06, 0x03, 0x55, 0x04, 0x03,
+                    0x0C, 0x05, b'a', b'l', b'i', b'c', b'e',
+                    0x31, 0x0C, 0x30, 0x0A, 0x06, 0x03, 0x55, 0x04, 0x0C,
+                    0x13, 0x03, b'C', b'E', b'O',
+                    0x31, 0x0A, 0x30, 0x08, 0x06, 0x03, 0x55, 0x04, 0x0A,
+                    0x02, 0x01, 0x01];
+
+        let name = DistinguishedName::parse(&name).unwrap();
+        assert_eq!(name.to_string(), "CN=alice, 2.5.4.12=CEO");
+        assert_eq!(name.get("2.5.4.12"), Some("CEO"));
+        assert_eq!(name.attributes().len(), 2);
+
+        assert_eq!(DistinguishedName::parse(&[]), Some(DistinguishedName::default()));
+        assert_eq!(DistinguishedName::parse(&[0x31, 0x02, 0x30]), None);
+        assert_eq!(dotted(&[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D]), Some("1.2.840.113549".into()));
+    }
+
+    #[test]
+    fn test_times() {
+        let parse = |tag: u8, value: &str| {
+            let mut der = vec![tag, value.len() as u8];
+            der.extend_from_slice(value.as_bytes());
+            time(&mut Reader(&der))
+        };
+
+        let secs = |secs: u64| Some(UNIX_EPOCH + Duration::from_secs(secs));
+        assert_eq!(parse(0x17, "700101000000Z"), secs(0));
+        assert_eq!(parse(0x17, "491231235959Z"), secs(2524607999));
+        assert_eq!(parse(0x18, "20000229120000Z"), secs(951825600));
+        assert_eq!(parse(0x18, "19691231235959Z"), Some(UNIX_EPOCH - Duration::from_secs(1)));
+        assert_eq!(parse(0x17, "7001010000Z"), None);
+        assert_eq!(parse(0x17, "700101000000+0100"), None);
+        assert_eq!(parse(0x18, "20001301000000Z"), None);
+        assert_eq!(parse(0x04, "700101000000Z"), None);
+    }
+}
@@ -0,0 +1,95 @@
+#![cfg(feature = "tls")]
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::Request;
+use rocket::tls::{AllowedClient, FingerprintPolicy};
+
+struct Nobody;
+
+impl FingerprintPolicy for Nobody {
+    fn allows(_: &Request, _: &[u8; 32]) -> bool {
+        false
+    }
+}
+
+#[get("/")]
+fn configured(client: AllowedClient) -> String {
+    format!("hello, {}", client.name().unwrap_or("unnamed"))
+}
+
+#[get("/", rank = 2)]
+fn anonymous() -> &'static str {
+    "anonymous"
+}
+
+#[get("/nobody")]
+fn nobody(_client: AllowedClient<Nobody>) -> &'static str {
+    "unreachable"
+}
+
+#[catch(403)]
+fn forbidden(_: &Request) -> &'static str {
+    "not on the list"
+}
+
+mod allowed_client_tests {
+    use rocket;
+    use rocket::config::{Config, Environment};
+    use rocket::local::Client;
+    use rocket::http::Status;
+
+    const ALICE: &str = "0B:F5:AD:9E:01:F5:1F:68:CF:44:F2:FD:B0:95:1B:CC:\
+                         F5:15:7E:0E:7B:C1:48:7B:84:B3:40:0B:AE:92:8B:8D";
+
+    fn rocket_with_fingerprints(fingerprints: &[&str]) -> rocket::Rocket {
+        let config = fingerprints.iter()
+            .fold(Config::build(Environment::Development), |config, fingerprint| {
+                config.mtls_allowed_fingerprint(*fingerprint)
+            })
+            .unwrap();
+
+        let routes = routes![super::configured, super::anonymous, super::nobody];
+        rocket::custom(config, false)
+            .mount("/", routes)
+            .catch(catchers![super::forbidden])
+    }
+
+    #[test]
+    fn allowed_fingerprints_pass() {
+        let client = Client::new(rocket_with_fingerprints(&[ALICE])).unwrap();
+        let request = client.get("/").certificate_file("tests/mtls/alice.pem").unwrap();
+        assert_eq!(request.dispatch().body_string(), Some("hello, alice".into()));
+    }
+
+    #[test]
+    fn other_fingerprints_are_forbidden() {
+        let client = Client::new(rocket_with_fingerprints(&[ALICE])).unwrap();
+        let mut response = client.get("/").certificate_file("tests/mtls/no_cn.pem")
+            .unwrap()
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Forbidden);
+        assert_eq!(response.body_string(), Some("not on the list".into()));
+
+        let client = Client::new(rocket_with_fingerprints(&[])).unwrap();
+        let request = client.get("/").certificate_file("tests/mtls/alice.pem").unwrap();
+        assert_eq!(request.dispatch().status(), Status::Forbidden);
+    }
+
+    #[test]
+    fn missing_certificates_are_forbidden() {
+        let client = Client::new(rocket_with_fingerprints(&[ALICE])).unwrap();
+        let response = client.get("/").dispatch();
+        assert_eq!(response.status(), Status::Forbidden);
+    }
+
+    #[test]
+    fn custom_policies_are_consulted() {
+        let client = Client::new(rocket_with_fingerprints(&[ALICE])).unwrap();
+        let request = client.get("/nobody").certificate_file("tests/mtls/alice.pem").unwrap();
+        assert_eq!(request.dispatch().status(), Status::Forbidden);
+    }
+}
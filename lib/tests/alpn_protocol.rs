@@ -0,0 +1,52 @@
+#![cfg(feature = "tls")]
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::Request;
+use rocket::response::Redirect;
+
+struct Alpn(String);
+
+impl<'a, 'r> rocket::request::FromRequest<'a, 'r> for Alpn {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> rocket::request::Outcome<Self, ()> {
+        let protocol = request.alpn_protocol().unwrap_or("none").to_string();
+        rocket::Outcome::Success(Alpn(protocol))
+    }
+}
+
+#[get("/")]
+fn protocol(alpn: Alpn) -> String {
+    alpn.0
+}
+
+#[get("/moved")]
+fn moved() -> Redirect {
+    Redirect::to("/")
+}
+
+mod alpn_protocol_tests {
+    use rocket;
+    use rocket::local::Client;
+
+    #[test]
+    fn negotiated_protocol_is_exposed() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::protocol])).unwrap();
+        let mut response = client.get("/").dispatch();
+        assert_eq!(response.body_string(), Some("none".into()));
+
+        let mut response = client.get("/").alpn_protocol("http/1.1").dispatch();
+        assert_eq!(response.body_string(), Some("http/1.1".into()));
+    }
+
+    #[test]
+    fn negotiated_protocol_follows_redirects() {
+        let rocket = rocket::ignite().mount("/", routes![super::protocol, super::moved]);
+        let client = Client::new(rocket).unwrap().follow_redirects(true);
+        let mut response = client.get("/moved").alpn_protocol("http/1.1").dispatch();
+        assert_eq!(response.body_string(), Some("http/1.1".into()));
+    }
+}
@@ -0,0 +1,121 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::api_key::{ApiKey, Scope};
+
+struct Admin;
+
+impl Scope for Admin {
+    const NAME: &'static str = "admin";
+}
+
+#[get("/status")]
+fn status(key: ApiKey) -> String {
+    format!("hello {}", key.name())
+}
+
+#[post("/shutdown")]
+fn shutdown(key: ApiKey<Admin>) -> String {
+    format!("bye {}", key.name())
+}
+
+mod api_key_tests {
+    use std::collections::HashMap;
+
+    use rocket;
+    use rocket::config::{Config, Environment, Value};
+    use rocket::api_key::{ApiKeys, StaticKeys};
+    use rocket::local::Client;
+    use rocket::http::{Header, Status};
+
+    const CI_KEY: &str = "KOIoMzuxnqt0nxvBnX3R6Y6UmXUKAtns";
+    const OPS_KEY: &str = "yvP3yDsw8hOxuMB0OsYJgovK5fhEqf1p";
+
+    fn rocket() -> rocket::Rocket {
+        let keys = StaticKeys::new()
+            .key("ci", CI_KEY, &["read"])
+            .key("ops", OPS_KEY, &["read", "admin"]);
+
+        rocket::ignite()
+            .manage(ApiKeys::new(keys))
+            .mount("/", routes![super::status, super::shutdown])
+    }
+
+    fn bearer(key: &str) -> Header<'static> {
+        Header::new("Authorization", format!("Bearer {}", key))
+    }
+
+    #[test]
+    fn missing_and_unknown_keys_are_unauthorized() {
+        let client = Client::new(rocket()).unwrap();
+        assert_eq!(client.get("/status").dispatch().status(), Status::Unauthorized);
+
+        let response = client.get("/status").header(bearer("guess")).dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+    }
+
+    #[test]
+    fn valid_keys_are_accepted() {
+        let client = Client::new(rocket()).unwrap();
+        let mut response = client.get("/status").header(bearer(CI_KEY)).dispatch();
+        assert_eq!(response.body_string(), Some("hello ci".into()));
+
+        let mut response = client.get("/status")
+            .header(Header::new("X-API-Key", OPS_KEY))
+            .dispatch();
+
+        assert_eq!(response.body_string(), Some("hello ops".into()));
+    }
+
+    #[test]
+    fn keys_without_scope_are_forbidden() {
+        let client = Client::new(rocket()).unwrap();
+        let response = client.post("/shutdown").header(bearer(CI_KEY)).dispatch();
+        assert_eq!(response.status(), Status::Forbidden);
+
+        let mut response = client.post("/shutdown").header(bearer(OPS_KEY)).dispatch();
+        assert_eq!(response.body_string(), Some("bye ops".into()));
+    }
+
+    #[test]
+    fn unmanaged_keys_are_an_error() {
+        let rocket = rocket::ignite().mount("/", routes![super::status]);
+        let client = Client::new(rocket).unwrap();
+        let response = client.get("/status").header(bearer(CI_KEY)).dispatch();
+        assert_eq!(response.status(), Status::InternalServerError);
+    }
+
+    #[test]
+    fn keys_are_read_from_config() {
+        let mut ops = HashMap::new();
+        ops.insert("key".to_string(), Value::from(OPS_KEY));
+        ops.insert("scopes".to_string(), Value::from(vec!["*"]));
+
+        let mut keys = HashMap::new();
+        keys.insert("ops".to_string(), ops);
+
+        let config = Config::build(Environment::Development)
+            .extra("api_keys", keys)
+            .unwrap();
+
+        let rocket = rocket::custom(config, false)
+            .attach(ApiKeys::fairing())
+            .mount("/", routes![super::shutdown]);
+
+        let client = Client::new(rocket).unwrap();
+        let mut response = client.post("/shutdown").header(bearer(OPS_KEY)).dispatch();
+        assert_eq!(response.body_string(), Some("bye ops".into()));
+    }
+
+    #[test]
+    fn malformed_config_aborts_launch() {
+        let config = Config::build(Environment::Development)
+            .extra("api_keys", vec!["oops"])
+            .unwrap();
+
+        let rocket = rocket::custom(config, false).attach(ApiKeys::fairing());
+        assert!(Client::new(rocket).is_err());
+    }
+}
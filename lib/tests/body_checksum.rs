@@ -0,0 +1,107 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use std::io::Read;
+
+use rocket::Data;
+
+#[post("/", data = "<data>")]
+fn index(data: Data) -> String {
+    let mut stream = data.open();
+    let mut body = String::new();
+    match stream.read_to_string(&mut body) {
+        Ok(_) => match stream.checksum() {
+            Some(checksum) => format!("{}: {}", body, checksum),
+            None => body
+        },
+        Err(_) => "mismatch".into()
+    }
+}
+
+#[post("/prefix", data = "<data>")]
+fn prefix(data: Data) -> String {
+    let mut prefix = String::new();
+    data.open().take(2).read_to_string(&mut prefix).expect("read prefix");
+    prefix
+}
+
+mod body_checksum_tests {
+    use rocket;
+    use rocket::config::{Environment, Config};
+    use rocket::local::Client;
+    use rocket::http::{Status, Header};
+
+    const HELLO_MD5: &str = "XUFAKrxLKna5cZ2REBfFkg==";
+    const HELLO_SHA256: &str = "LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ=";
+
+    fn rocket_verifying_checksums(verify: bool) -> rocket::Rocket {
+        let config = Config::build(Environment::Development)
+            .verify_checksums(verify)
+            .unwrap();
+
+        rocket::custom(config, true).mount("/", routes![super::index, super::prefix])
+    }
+
+    #[test]
+    fn content_md5_matches() {
+        let client = Client::new(rocket_verifying_checksums(true)).unwrap();
+        let mut response = client.post("/")
+            .header(Header::new("Content-MD5", HELLO_MD5))
+            .body("hello")
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some(format!("hello: MD5={}", HELLO_MD5)));
+    }
+
+    #[test]
+    fn digest_prefers_sha256() {
+        let client = Client::new(rocket_verifying_checksums(true)).unwrap();
+        let digest = format!("MD5={}, SHA-256={}", HELLO_MD5, HELLO_SHA256);
+        let mut response = client.post("/")
+            .header(Header::new("Digest", digest))
+            .body("hello")
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        let expected = format!("hello: SHA-256={}", HELLO_SHA256);
+        assert_eq!(response.body_string(), Some(expected));
+    }
+
+    #[test]
+    fn mismatch_is_unprocessable() {
+        let client = Client::new(rocket_verifying_checksums(true)).unwrap();
+        let response = client.post("/")
+            .header(Header::new("Content-MD5", HELLO_MD5))
+            .body("goodbye")
+            .dispatch();
+
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+
+    #[test]
+    fn verification_is_opt_in() {
+        let client = Client::new(rocket_verifying_checksums(false)).unwrap();
+        let mut response = client.post("/")
+            .header(Header::new("Content-MD5", HELLO_MD5))
+            .body("goodbye")
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some("goodbye".into()));
+    }
+
+    #[test]
+    fn partially_read_body_is_not_verified() {
+        let client = Client::new(rocket_verifying_checksums(true)).unwrap();
+        let mut response = client.post("/prefix")
+            .header(Header::new("Content-MD5", HELLO_MD5))
+            .body("goodbye")
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some("go".into()));
+    }
+}
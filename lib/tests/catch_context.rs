@@ -0,0 +1,77 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::Outcome;
+use rocket::http::Status;
+use rocket::request::{self, Request, FromRequest, CatchContext};
+
+#[derive(Debug)]
+enum AuthError {
+    Missing,
+}
+
+struct User;
+
+impl<'a, 'r> FromRequest<'a, 'r> for User {
+    type Error = AuthError;
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<User, AuthError> {
+        match request.headers().get_one("Authorization") {
+            Some(_) => Outcome::Success(User),
+            None => Outcome::Failure((Status::Unauthorized, AuthError::Missing))
+        }
+    }
+}
+
+#[get("/private")]
+fn private(_user: User) -> &'static str {
+    "secret"
+}
+
+#[catch(401)]
+fn unauthorized(context: CatchContext) -> String {
+    let route = context.route().and_then(|route| route.name).unwrap_or("none");
+    match context.guard_failure() {
+        Some(failure) => format!("{} {} {} {:?}", route, failure.kind, failure.name, failure.error),
+        None => format!("{} no guard", route)
+    }
+}
+
+#[catch(404)]
+fn not_found(req: &Request, context: CatchContext) -> String {
+    format!("{} {}", req.uri(), context.route().is_none() && context.guard_failure().is_none())
+}
+
+mod catch_context_tests {
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::{Header, Status};
+
+    #[test]
+    fn failed_guards_are_passed_to_catchers() {
+        let rocket = rocket::ignite()
+            .mount("/", routes![super::private])
+            .catch(catchers![super::unauthorized]);
+
+        let client = Client::new(rocket).unwrap();
+        let mut response = client.get("/private").dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        assert_eq!(response.body_string(),
+                   Some("private request guard _user Some(\"Missing\")".into()));
+
+        let mut response = client.get("/private")
+            .header(Header::new("Authorization", "yes"))
+            .dispatch();
+        assert_eq!(response.body_string(), Some("secret".into()));
+    }
+
+    #[test]
+    fn unmatched_requests_have_no_route() {
+        let client = Client::new(rocket::ignite().catch(catchers![super::not_found])).unwrap();
+        let mut response = client.get("/nowhere").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        assert_eq!(response.body_string(), Some("/nowhere true".into()));
+    }
+}
@@ -0,0 +1,63 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rocket::State;
+
+struct Calls(AtomicUsize);
+
+#[get("/report", coalesce = true)]
+fn report(calls: State<Calls>) -> String {
+    format!("report #{}", calls.0.fetch_add(1, Ordering::SeqCst) + 1)
+}
+
+#[get("/plain")]
+fn plain() -> &'static str {
+    "plain"
+}
+
+mod coalesced_routes_tests {
+    use super::*;
+    use rocket::local::Client;
+    use rocket::http::Status;
+
+    fn rocket() -> rocket::Rocket {
+        rocket::ignite()
+            .mount("/", routes![report, plain])
+            .manage(Calls(AtomicUsize::new(0)))
+    }
+
+    #[test]
+    fn coalesce_flag_is_set() {
+        let rocket = rocket();
+        for route in rocket.routes() {
+            match route.uri.path() {
+                "/report" => assert!(route.coalesce),
+                "/plain" => assert!(!route.coalesce),
+                _ => unreachable!("only /report, /plain are expected")
+            }
+        }
+    }
+
+    #[test]
+    fn sequential_requests_run_handler() {
+        let client = Client::new(rocket()).unwrap();
+        let mut response = client.get("/report").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some("report #1".into()));
+
+        let mut response = client.get("/report").dispatch();
+        assert_eq!(response.body_string(), Some("report #2".into()));
+    }
+
+    #[test]
+    fn coalesced_head_has_no_body() {
+        let client = Client::new(rocket()).unwrap();
+        let mut response = client.head("/report").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some("".into()));
+    }
+}
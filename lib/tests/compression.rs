@@ -0,0 +1,128 @@
+#![cfg(feature = "gzip")]
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+#[get("/")]
+fn index() -> String {
+    "Hello, compression! ".repeat(100)
+}
+
+#[get("/small")]
+fn small() -> &'static str {
+    "Hello!"
+}
+
+#[post("/echo", data = "<body>")]
+fn echo(body: String) -> String {
+    body
+}
+
+mod compression_tests {
+    use rocket;
+    use rocket::config::{Config, Environment, CompressionConfig};
+    use rocket::local::Client;
+    use rocket::data::{Checksum, ChecksumAlgorithm};
+    use rocket::http::{Header, Status};
+
+    fn rocket_verifying_checksums(verify: bool) -> rocket::Rocket {
+        let compression = CompressionConfig::default().brotli(None).zstd(None);
+        let config = Config::build(Environment::Development)
+            .compression(compression)
+            .verify_checksums(verify)
+            .unwrap();
+
+        rocket::custom(config, true).mount("/", routes![super::index, super::small, super::echo])
+    }
+
+    #[test]
+    fn responses_are_compressed_for_clients_that_accept_it() {
+        let client = Client::new(rocket_verifying_checksums(false)).unwrap();
+        let mut response = client.get("/")
+            .header(Header::new("Accept-Encoding", "deflate, gzip;q=0.8"))
+            .dispatch();
+
+        assert_eq!(response.headers().get_one("Content-Encoding"), Some("gzip"));
+        assert_eq!(response.headers().get_one("Vary"), Some("Accept-Encoding"));
+
+        let body = response.body_bytes().unwrap();
+        assert_eq!(&body[..2], &[0x1f, 0x8b]);
+        assert!(body.len() < 2000);
+    }
+
+    #[test]
+    fn responses_are_not_compressed_otherwise() {
+        let client = Client::new(rocket_verifying_checksums(false)).unwrap();
+        let response = client.get("/").dispatch();
+        assert!(!response.headers().contains("Content-Encoding"));
+
+        let response = client.get("/")
+            .header(Header::new("Accept-Encoding", "gzip;q=0.5, identity"))
+            .dispatch();
+
+        assert!(!response.headers().contains("Content-Encoding"));
+        assert_eq!(response.headers().get_one("Vary"), Some("Accept-Encoding"));
+
+        let response = client.get("/small")
+            .header(Header::new("Accept-Encoding", "gzip"))
+            .dispatch();
+
+        assert!(!response.headers().contains("Content-Encoding"));
+    }
+
+    #[test]
+    fn compressed_request_bodies_are_decompressed() {
+        let client = Client::new(rocket_verifying_checksums(false)).unwrap();
+        let compressed = client.get("/")
+            .header(Header::new("Accept-Encoding", "gzip"))
+            .dispatch()
+            .body_bytes()
+            .unwrap();
+
+        let mut response = client.post("/echo")
+            .header(Header::new("Content-Encoding", "gzip"))
+            .body(compressed)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some("Hello, compression! ".repeat(100)));
+
+        let response = client.post("/echo")
+            .header(Header::new("Content-Encoding", "gzip"))
+            .body("not gzip")
+            .dispatch();
+
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[test]
+    fn checksums_cover_the_compressed_body() {
+        let client = Client::new(rocket_verifying_checksums(true)).unwrap();
+        let compressed = client.get("/")
+            .header(Header::new("Accept-Encoding", "gzip"))
+            .dispatch()
+            .body_bytes()
+            .unwrap();
+
+        let md5 = Checksum::compute(ChecksumAlgorithm::Md5, &compressed).to_base64();
+        let mut response = client.post("/echo")
+            .header(Header::new("Content-Encoding", "gzip"))
+            .header(Header::new("Content-MD5", md5))
+            .body(compressed.clone())
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some("Hello, compression! ".repeat(100)));
+
+        let decompressed = "Hello, compression! ".repeat(100);
+        let md5 = Checksum::compute(ChecksumAlgorithm::Md5, decompressed.as_bytes()).to_base64();
+        let response = client.post("/echo")
+            .header(Header::new("Content-Encoding", "gzip"))
+            .header(Header::new("Content-MD5", md5))
+            .body(compressed)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+}
@@ -0,0 +1,83 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::http::{Cookie, Cookies};
+
+#[get("/")]
+fn index(mut cookies: Cookies) -> &'static str {
+    cookies.add(Cookie::new("b", "2"));
+    cookies.add(Cookie::new("a", "1"));
+    cookies.add(Cookie::new("c", "3"));
+    "cookies"
+}
+
+#[get("/logout")]
+fn logout(mut cookies: Cookies) -> &'static str {
+    cookies.remove(Cookie::named("a"));
+    cookies.add(Cookie::new("c", "3"));
+    cookies.add(Cookie::new("d", "4"));
+    "logout"
+}
+
+#[get("/clear")]
+fn clear(mut cookies: Cookies) -> &'static str {
+    cookies.remove(Cookie::named("a"));
+    cookies.remove(Cookie::named("b"));
+    "clear"
+}
+
+mod cookie_limits_tests {
+    use rocket;
+    use rocket::config::{Config, Environment, CookieLimits, CookieOverflow};
+    use rocket::local::Client;
+    use rocket::http::{Cookie, Status};
+
+    fn cookie_names(overflow: CookieOverflow, path: &str) -> (Status, Vec<String>) {
+        let config = Config::build(Environment::Development)
+            .cookie_limits(CookieLimits::new(2, 80).overflow(overflow))
+            .unwrap();
+
+        let routes = routes![super::index, super::logout, super::clear];
+        let client = Client::new(rocket::custom(config, true).mount("/", routes)).unwrap();
+        let cookies = vec![Cookie::new("a", "1"), Cookie::new("b", "2")];
+        let response = client.get(path).cookies(cookies).dispatch();
+        let mut names: Vec<_> = response.headers().get("Set-Cookie")
+            .map(|value| value.split('=').next().unwrap().to_string())
+            .collect();
+
+        names.sort();
+        (response.status(), names)
+    }
+
+    #[test]
+    fn log_policy_emits_all_cookies() {
+        let expected: Vec<String> = vec!["a".into(), "b".into(), "c".into()];
+        assert_eq!(cookie_names(CookieOverflow::Log, "/"), (Status::Ok, expected));
+    }
+
+    #[test]
+    fn truncate_policy_drops_oldest_cookies() {
+        let expected: Vec<String> = vec!["a".into(), "c".into()];
+        assert_eq!(cookie_names(CookieOverflow::TruncateOldest, "/"), (Status::Ok, expected));
+    }
+
+    #[test]
+    fn truncate_policy_keeps_removals() {
+        let expected: Vec<String> = vec!["a".into(), "d".into()];
+        let names = cookie_names(CookieOverflow::TruncateOldest, "/logout");
+        assert_eq!(names, (Status::Ok, expected));
+
+        let (status, names) = cookie_names(CookieOverflow::TruncateOldest, "/clear");
+        assert_eq!(status, Status::InternalServerError);
+        assert!(names.is_empty());
+    }
+
+    #[test]
+    fn reject_policy_fails_response() {
+        let (status, names) = cookie_names(CookieOverflow::Reject, "/");
+        assert_eq!(status, Status::InternalServerError);
+        assert!(names.is_empty());
+    }
+}
@@ -0,0 +1,91 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::response::content::Html;
+
+#[get("/page")]
+fn page() -> Html<&'static str> {
+    Html("<html><body>page</body></html>")
+}
+
+#[get("/text")]
+fn text() -> &'static str {
+    "</body>"
+}
+
+mod dev_reload_tests {
+    use rocket;
+    use rocket::config::{Environment, Config};
+    use rocket::dev::{DevReload, ReloadHandle};
+    use rocket::local::Client;
+    use rocket::http::{Status, ContentType};
+
+    fn rocket_in(environment: Environment, fairing: DevReload) -> rocket::Rocket {
+        let config = Config::build(environment).unwrap();
+        rocket::custom(config, true)
+            .mount("/", routes![super::page, super::text])
+            .attach(fairing)
+    }
+
+    #[test]
+    fn html_responses_reload_in_development() {
+        let client = Client::new(rocket_in(Environment::Development, DevReload::new())).unwrap();
+        assert!(client.rocket().state::<ReloadHandle>().is_some());
+
+        let body = client.get("/page").dispatch().body_string().unwrap();
+        assert!(body.starts_with("<html><body>page<script>"));
+        assert!(body.contains("/rocket/live-reload"));
+        assert!(body.ends_with("</script></body></html>"));
+
+        let mut response = client.get("/text").dispatch();
+        assert_eq!(response.body_string(), Some("</body>".into()));
+
+        let response = client.get("/rocket/live-reload").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::new("text", "event-stream")));
+    }
+
+    #[test]
+    fn live_reload_can_be_disabled() {
+        let fairing = DevReload::new().live_reload(false);
+        let client = Client::new(rocket_in(Environment::Development, fairing)).unwrap();
+        assert!(client.rocket().state::<ReloadHandle>().is_some());
+
+        let mut response = client.get("/page").dispatch();
+        assert_eq!(response.body_string(), Some("<html><body>page</body></html>".into()));
+
+        let response = client.get("/rocket/live-reload").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[test]
+    fn inactive_outside_of_development() {
+        let client = Client::new(rocket_in(Environment::Staging, DevReload::new())).unwrap();
+        assert!(client.rocket().state::<ReloadHandle>().is_none());
+
+        let mut response = client.get("/page").dispatch();
+        assert_eq!(response.body_string(), Some("<html><body>page</body></html>".into()));
+
+        let response = client.get("/rocket/live-reload").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[test]
+    fn callbacks_are_notified() {
+        use std::path::PathBuf;
+        use std::sync::{Arc, Mutex};
+
+        let seen = Arc::new(Mutex::new(vec![]));
+        let recorder = seen.clone();
+        let fairing = DevReload::new().on_change(move |paths| {
+            recorder.lock().unwrap().extend(paths.iter().cloned());
+        });
+
+        let handle = fairing.handle();
+        handle.notify(&[PathBuf::from("templates/index.html.hbs")]);
+        assert_eq!(handle.generation(), 1);
+        assert_eq!(*seen.lock().unwrap(), vec![PathBuf::from("templates/index.html.hbs")]);
+    }
+}
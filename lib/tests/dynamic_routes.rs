@@ -0,0 +1,84 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::{Request, Data, State};
+use rocket::handler::Outcome;
+use rocket::http::Method;
+use rocket::dynamic::DynamicRoutes;
+
+#[get("/static")]
+fn mounted() -> &'static str {
+    "mounted"
+}
+
+fn dynamic<'r>(req: &'r Request, _: Data) -> Outcome<'r> {
+    Outcome::from(req, format!("dynamic {}", req.uri()))
+}
+
+fn once<'r>(req: &'r Request, _: Data) -> Outcome<'r> {
+    let routes = req.guard::<State<DynamicRoutes>>().unwrap();
+    routes.remove(Method::Get, "/once");
+    let uri = req.route().map(|route| route.uri.to_string());
+    Outcome::from(req, format!("once {}", uri.unwrap_or_default()))
+}
+
+mod dynamic_routes_tests {
+    use super::*;
+    use rocket::Route;
+    use rocket::local::Client;
+    use rocket::http::{Method, Status};
+    use rocket::dynamic::DynamicRouteError;
+
+    #[test]
+    fn routes_can_be_added_and_removed_after_ignition() {
+        let rocket = rocket::ignite()
+            .mount("/ext", routes![mounted])
+            .mount_dynamic("/ext");
+
+        let client = Client::new(rocket).unwrap();
+        let routes = client.rocket().dynamic_routes().unwrap();
+        assert_eq!(client.get("/ext/hello").dispatch().status(), Status::NotFound);
+
+        routes.add(Route::new(Method::Get, "/hello", dynamic)).unwrap();
+        let mut response = client.get("/ext/hello").dispatch();
+        assert_eq!(response.body_string(), Some("dynamic /ext/hello".into()));
+
+        routes.add(Route::ranked(-3, Method::Get, "/<any>", dynamic)).unwrap_err();
+        routes.add(Route::new(Method::Get, "/<any>", dynamic)).unwrap();
+
+        // Mounted routes take precedence over dynamic routes.
+        let mut response = client.get("/ext/static").dispatch();
+        assert_eq!(response.body_string(), Some("mounted".into()));
+
+        assert!(routes.remove(Method::Get, "/hello").is_some());
+        assert!(routes.remove(Method::Get, "/hello").is_none());
+        let mut response = client.get("/ext/hello").dispatch();
+        assert_eq!(response.body_string(), Some("dynamic /ext/hello".into()));
+        assert_eq!(routes.routes().len(), 1);
+    }
+
+    #[test]
+    fn colliding_dynamic_routes_are_rejected() {
+        let rocket = rocket::ignite().mount_dynamic("/");
+        let routes = rocket.dynamic_routes().unwrap();
+        routes.add(Route::new(Method::Post, "/a", dynamic)).unwrap();
+        match routes.add(Route::new(Method::Post, "/a", dynamic)) {
+            Err(DynamicRouteError::Collision(route)) => assert_eq!(route.uri.path(), "/a"),
+            Ok(_) => panic!("expected a collision")
+        }
+    }
+
+    #[test]
+    fn removed_routes_outlive_their_requests() {
+        let rocket = rocket::ignite().mount_dynamic("/ext");
+        let routes = rocket.dynamic_routes().unwrap();
+        routes.add(Route::new(Method::Get, "/once", once)).unwrap();
+
+        let client = Client::new(rocket.manage(routes)).unwrap();
+        let mut response = client.get("/ext/once").dispatch();
+        assert_eq!(response.body_string(), Some("once /ext/once".into()));
+        assert_eq!(client.get("/ext/once").dispatch().status(), Status::NotFound);
+    }
+}
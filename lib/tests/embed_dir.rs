@@ -0,0 +1,71 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use std::path::PathBuf;
+use rocket::response::{EmbeddedDir, EmbeddedFile};
+
+static ASSETS: EmbeddedDir = embed_dir!("tests/embedded", gzip);
+
+#[get("/<path..>")]
+fn assets(path: PathBuf) -> Option<&'static EmbeddedFile> {
+    ASSETS.get(path)
+}
+
+mod embed_dir_tests {
+    use super::*;
+    use rocket::local::Client;
+    use rocket::http::{ContentType, Header, Status};
+
+    #[test]
+    fn files_are_embedded() {
+        let paths: Vec<_> = ASSETS.files().iter().map(|file| file.path()).collect();
+        assert_eq!(paths, vec!["css/style.css", "index.html"]);
+        assert!(ASSETS.get("../embedded/index.html").is_none());
+
+        let index = ASSETS.get("index.html").unwrap();
+        assert_eq!(index.bytes(), &include_bytes!("embedded/index.html")[..]);
+    }
+
+    #[test]
+    fn files_are_served_with_validators() {
+        let client = Client::new(rocket::ignite().mount("/", routes![assets])).unwrap();
+        let mut response = client.get("/index.html").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::HTML));
+        assert_eq!(response.body_bytes(), Some(include_bytes!("embedded/index.html").to_vec()));
+
+        let etag = response.headers().get_one("ETag").unwrap().to_string();
+        let response = client.get("/index.html")
+            .header(Header::new("If-None-Match", etag.clone()))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::NotModified);
+        assert_eq!(response.headers().get_one("ETag"), Some(&*etag));
+
+        let response = client.get("/index.html")
+            .header(Header::new("If-Match", "\"stale\""))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::PreconditionFailed);
+        assert_eq!(client.get("/missing.html").dispatch().status(), Status::NotFound);
+    }
+
+    #[test]
+    fn compressed_copies_are_negotiated() {
+        let client = Client::new(rocket::ignite().mount("/", routes![assets])).unwrap();
+        let plain = client.get("/css/style.css").dispatch();
+        assert_eq!(plain.headers().get_one("Content-Encoding"), None);
+        assert_eq!(plain.headers().get_one("Vary"), Some("Accept-Encoding"));
+
+        let mut gzipped = client.get("/css/style.css")
+            .header(Header::new("Accept-Encoding", "gzip"))
+            .dispatch();
+
+        assert_eq!(gzipped.headers().get_one("Content-Encoding"), Some("gzip"));
+        assert_ne!(gzipped.headers().get_one("ETag"), plain.headers().get_one("ETag"));
+        let size = gzipped.body_bytes().unwrap().len();
+        assert!(size < include_bytes!("embedded/css/style.css").len());
+    }
+}
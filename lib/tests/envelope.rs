@@ -0,0 +1,196 @@
+#![cfg(feature = "envelope")]
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+#[post("/echo", data = "<body>")]
+fn echo(body: String) -> String {
+    format!("echo: {}", body)
+}
+
+#[get("/public")]
+fn public() -> &'static str {
+    "public"
+}
+
+#[get("/text")]
+fn text() -> String {
+    "Hello, envelope! ".repeat(100)
+}
+
+mod envelope_tests {
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::{Header, Status};
+    use rocket::envelope::{Envelope, EnvelopeKey, KeyRing};
+
+    fn key() -> EnvelopeKey {
+        EnvelopeKey::new([42; 32])
+    }
+
+    fn rocket_with_envelope(required: bool) -> rocket::Rocket {
+        let envelope = Envelope::new(KeyRing::new().key("c1", key())).required(required);
+        rocket::ignite()
+            .envelope(envelope)
+            .mount("/", routes![super::echo, super::public, super::text])
+    }
+
+    #[test]
+    fn sealed_bodies_are_opened_and_responses_sealed() {
+        let client = Client::new(rocket_with_envelope(false)).unwrap();
+        let sealed = key().seal(b"request POST /echo", b"secret");
+        let mut response = client.post("/echo")
+            .header(Header::new("Envelope-Key-Id", "c1"))
+            .header(Header::new("Content-Encoding", "aes256gcm"))
+            .body(sealed)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.headers().get_one("Content-Encoding"), Some("aes256gcm"));
+
+        let body = response.body_bytes().unwrap();
+        assert!(key().open(b"request POST /echo", &body).is_none());
+        let opened = key().open(b"response POST /echo", &body).unwrap();
+        assert_eq!(opened, b"echo: secret".to_vec());
+    }
+
+    #[test]
+    fn tampered_or_misdirected_bodies_are_rejected() {
+        let client = Client::new(rocket_with_envelope(false)).unwrap();
+        let mut sealed = key().seal(b"request POST /echo", b"secret");
+        *sealed.last_mut().unwrap() ^= 1;
+        let response = client.post("/echo")
+            .header(Header::new("Envelope-Key-Id", "c1"))
+            .header(Header::new("Content-Encoding", "aes256gcm"))
+            .body(sealed)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let sealed = key().seal(b"request POST /elsewhere", b"secret");
+        let response = client.post("/echo")
+            .header(Header::new("Envelope-Key-Id", "c1"))
+            .header(Header::new("Content-Encoding", "aes256gcm"))
+            .body(sealed)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[test]
+    fn clients_without_keys() {
+        let client = Client::new(rocket_with_envelope(false)).unwrap();
+        let mut response = client.get("/public").dispatch();
+        assert_eq!(response.body_string(), Some("public".into()));
+        assert!(response.headers().get_one("Content-Encoding").is_none());
+
+        let client = Client::new(rocket_with_envelope(true)).unwrap();
+        let response = client.get("/public").dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        let response = client.get("/public")
+            .header(Header::new("Envelope-Key-Id", "unknown"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Unauthorized);
+    }
+
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn compressed_bodies_keep_their_coding() {
+        use rocket::config::{Config, Environment, CompressionConfig};
+
+        let compression = CompressionConfig::default().brotli(None).zstd(None);
+        let config = Config::build(Environment::Development)
+            .compression(compression)
+            .unwrap();
+
+        let rocket = rocket::custom(config, true)
+            .envelope(Envelope::new(KeyRing::new().key("c1", key())))
+            .mount("/", routes![super::echo, super::text]);
+
+        let client = Client::new(rocket).unwrap();
+        let compressed = client.get("/text")
+            .header(Header::new("Accept-Encoding", "gzip"))
+            .dispatch()
+            .body_bytes()
+            .unwrap();
+
+        // The request body is opened, then decompressed.
+        let sealed = key().seal(b"request POST /echo", &compressed);
+        let mut response = client.post("/echo")
+            .header(Header::new("Envelope-Key-Id", "c1"))
+            .header(Header::new("Content-Encoding", "gzip, aes256gcm"))
+            .header(Header::new("Accept-Encoding", "gzip"))
+            .body(sealed)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.headers().get_one("Content-Encoding"), Some("gzip, aes256gcm"));
+
+        // The response body is compressed, then sealed.
+        let body = response.body_bytes().unwrap();
+        let opened = key().open(b"response POST /echo", &body).unwrap();
+        assert_eq!(&opened[..2], &[0x1f, 0x8b]);
+
+        let mut response = client.post("/echo")
+            .header(Header::new("Content-Encoding", "gzip"))
+            .body(opened)
+            .dispatch();
+
+        let echoed = format!("echo: echo: {}", "Hello, envelope! ".repeat(100));
+        assert_eq!(response.body_string(), Some(echoed));
+
+        // A body can't be opened if it was encoded after it was sealed.
+        let sealed = key().seal(b"request POST /echo", b"secret");
+        let response = client.post("/echo")
+            .header(Header::new("Envelope-Key-Id", "c1"))
+            .header(Header::new("Content-Encoding", "aes256gcm, gzip"))
+            .body(sealed)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[test]
+    #[cfg(feature = "tls")]
+    fn keys_are_derived_from_client_certificates() {
+        use rocket::http::Certificate;
+        use rocket::envelope::ClientCertificateKeys;
+
+        let rocket = rocket::ignite()
+            .envelope(Envelope::new(ClientCertificateKeys).required(true))
+            .mount("/", routes![super::echo]);
+
+        let client = Client::new(rocket).unwrap();
+        let alice = Certificate(vec![1, 2, 3]);
+        let key = ClientCertificateKeys::key_for(client.rocket().config(), &alice);
+        let sealed = key.seal(b"request POST /echo", b"secret");
+        let mut response = client.post("/echo")
+            .certificates(vec![alice])
+            .header(Header::new("Content-Encoding", "aes256gcm"))
+            .body(sealed.clone())
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        let body = response.body_bytes().unwrap();
+        assert_eq!(key.open(b"response POST /echo", &body), Some(b"echo: secret".to_vec()));
+
+        // Another client's key is different; a client without a certificate has none.
+        let response = client.post("/echo")
+            .certificates(vec![Certificate(vec![4, 5, 6])])
+            .header(Header::new("Content-Encoding", "aes256gcm"))
+            .body(sealed.clone())
+            .dispatch();
+
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let response = client.post("/echo")
+            .header(Header::new("Content-Encoding", "aes256gcm"))
+            .body(sealed)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Unauthorized);
+    }
+}
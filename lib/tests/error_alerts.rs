@@ -0,0 +1,110 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::Request;
+use rocket::request::{self, FromRequest};
+use rocket::http::Status;
+use rocket::Outcome::*;
+
+#[derive(Debug)]
+struct DatabaseDown;
+
+struct Database;
+
+impl<'a, 'r> FromRequest<'a, 'r> for Database {
+    type Error = DatabaseDown;
+
+    fn from_request(_: &'a Request<'r>) -> request::Outcome<Self, DatabaseDown> {
+        Failure((Status::ServiceUnavailable, DatabaseDown))
+    }
+}
+
+#[get("/ok")]
+fn ok() -> &'static str {
+    "ok"
+}
+
+#[get("/fail")]
+fn fail() -> Result<(), Status> {
+    Err(Status::InternalServerError)
+}
+
+#[get("/users")]
+fn users(_db: Database) -> &'static str {
+    "unreachable"
+}
+
+mod error_alerts_tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::Status;
+    use rocket::alert::{ErrorHook, ErrorAlert};
+
+    fn rocket_with_alerts(max: u32) -> (rocket::Rocket, Arc<Mutex<Vec<ErrorAlert>>>) {
+        let alerts = Arc::new(Mutex::new(vec![]));
+        let sink = alerts.clone();
+        let hook = ErrorHook::new(move |alert| sink.lock().unwrap().push(alert.clone()))
+            .throttle(max, Duration::from_secs(60));
+
+        let rocket = rocket::ignite()
+            .mount("/", routes![super::ok, super::fail, super::users])
+            .on_error_response(hook);
+
+        (rocket, alerts)
+    }
+
+    #[test]
+    fn server_errors_are_alerted() {
+        let (rocket, alerts) = rocket_with_alerts(10);
+        let client = Client::new(rocket).unwrap();
+        assert_eq!(client.get("/ok").dispatch().status(), Status::Ok);
+        assert_eq!(client.get("/missing").dispatch().status(), Status::NotFound);
+        assert!(alerts.lock().unwrap().is_empty());
+
+        assert_eq!(client.get("/fail").dispatch().status(), Status::InternalServerError);
+        let alerts = alerts.lock().unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].status, Status::InternalServerError);
+        assert_eq!(alerts[0].uri, "/fail");
+        assert_eq!(alerts[0].route, Some("GET /fail".into()));
+        assert_eq!(alerts[0].route_name, Some("fail"));
+        assert!(alerts[0].error_chain.is_empty());
+        assert_eq!(alerts[0].suppressed, 0);
+    }
+
+    #[test]
+    fn guard_failures_are_in_the_error_chain() {
+        let (rocket, alerts) = rocket_with_alerts(10);
+        let client = Client::new(rocket).unwrap();
+        assert_eq!(client.get("/users").dispatch().status(), Status::ServiceUnavailable);
+
+        let alerts = alerts.lock().unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].error_chain.len(), 2);
+        assert!(alerts[0].error_chain[0].contains("_db: Database"));
+        assert_eq!(alerts[0].error_chain[1], "DatabaseDown");
+        assert!(alerts[0].to_string().contains("DatabaseDown"));
+    }
+
+    #[test]
+    fn alerts_are_throttled_per_route() {
+        let (rocket, alerts) = rocket_with_alerts(1);
+        let client = Client::new(rocket).unwrap();
+        for _ in 0..3 {
+            client.get("/fail").dispatch();
+            client.get("/users").dispatch();
+        }
+
+        assert_eq!(alerts.lock().unwrap().len(), 2);
+        let routes: Vec<_> = alerts.lock().unwrap().iter()
+            .map(|alert| alert.route.clone().unwrap())
+            .collect();
+
+        assert_eq!(routes, vec!["GET /fail", "GET /users"]);
+    }
+}
@@ -0,0 +1,76 @@
+extern crate rocket;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use rocket::Rocket;
+use rocket::fairing::{Fairing, Info, Kind, AttachError};
+
+#[derive(Default)]
+struct Flaky {
+    cleaned_up: AtomicBool,
+}
+
+impl Fairing for Flaky {
+    fn info(&self) -> Info {
+        Info { name: "Flaky", kind: Kind::Attach }
+    }
+
+    fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        rocket.fail_attach(AttachError::new("pool init failed").with_cause("timed out"))
+    }
+
+    fn on_attach_failure(&self, _: &Rocket, error: &AttachError) {
+        assert_eq!(error.fairing(), "Flaky");
+        self.cleaned_up.store(true, Ordering::Release);
+    }
+}
+
+mod fairing_attach_errors_tests {
+    use super::*;
+    use rocket::error::LaunchErrorKind;
+    use rocket::fairing::AdHoc;
+    use rocket::local::Client;
+
+    #[test]
+    fn attach_failures_are_aggregated() {
+        let flaky = Arc::new(Flaky::default());
+        let rocket = rocket::ignite()
+            .attach(flaky.clone())
+            .attach(AdHoc::on_attach(|rocket| Ok(rocket)))
+            .attach(AdHoc::on_attach(|rocket| Err(rocket)));
+
+        assert!(flaky.cleaned_up.load(Ordering::Acquire));
+
+        let error = Client::new(rocket).err().expect("launch should fail");
+        assert_eq!(error.exit_code(), 75);
+        match *error.kind() {
+            LaunchErrorKind::FailedFairings(ref failures) => {
+                assert_eq!(failures.len(), 2);
+                assert_eq!(failures[0].to_string(), "Flaky: pool init failed (timed out)");
+                assert_eq!(failures[1].fairing(), "AdHoc::Attach");
+                assert_eq!(failures[1].message(), "attach callback failed");
+            }
+            ref kind => panic!("unexpected launch error: {}", kind)
+        }
+    }
+
+    #[test]
+    fn nested_attach_failures_are_kept() {
+        let rocket = rocket::ignite()
+            .attach(AdHoc::on_attach(|rocket| {
+                Ok(rocket.attach(AdHoc::on_attach(|rocket| {
+                    rocket.fail_attach(AttachError::new("nested"))
+                })))
+            }));
+
+        let error = Client::new(rocket).err().expect("launch should fail");
+        match *error.kind() {
+            LaunchErrorKind::FailedFairings(ref failures) => {
+                assert_eq!(failures.len(), 1);
+                assert_eq!(failures[0].message(), "nested");
+            }
+            ref kind => panic!("unexpected launch error: {}", kind)
+        }
+    }
+}
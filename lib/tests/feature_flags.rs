@@ -0,0 +1,79 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::features::{Feature, Features, Flag};
+
+struct NewCheckout;
+
+impl Feature for NewCheckout {
+    const NAME: &'static str = "new_checkout";
+}
+
+#[get("/checkout")]
+fn new_checkout(_flag: Flag<NewCheckout>) -> &'static str {
+    "new"
+}
+
+#[get("/checkout", rank = 2)]
+fn old_checkout() -> &'static str {
+    "old"
+}
+
+#[get("/search")]
+fn search(features: &Features) -> &'static str {
+    match features.is_enabled("beta_search") {
+        true => "beta",
+        false => "stable"
+    }
+}
+
+mod feature_flags_tests {
+    use rocket;
+    use rocket::config::{Environment, Config};
+    use rocket::local::Client;
+
+    fn body(client: &Client, path: &str) -> Option<String> {
+        client.get(path).dispatch().body_string()
+    }
+
+    #[test]
+    fn flag_guard_follows_config() {
+        for &(enabled, expected) in &[(true, "new"), (false, "old")] {
+            let config = Config::build(Environment::Development)
+                .feature("new_checkout", enabled)
+                .unwrap();
+
+            let routes = routes![super::new_checkout, super::old_checkout];
+            let client = Client::new(rocket::custom(config, true).mount("/", routes)).unwrap();
+            assert_eq!(body(&client, "/checkout"), Some(expected.into()));
+        }
+    }
+
+    #[test]
+    fn runtime_overrides() {
+        let config = Config::build(Environment::Development)
+            .feature("new_checkout", false)
+            .unwrap();
+
+        let routes = routes![super::new_checkout, super::old_checkout, super::search];
+        let client = Client::new(rocket::custom(config, true).mount("/", routes)).unwrap();
+        assert_eq!(body(&client, "/search"), Some("stable".into()));
+
+        client.rocket().features().set("new_checkout", true);
+        client.rocket().features().set("beta_search", true);
+        assert_eq!(body(&client, "/checkout"), Some("new".into()));
+        assert_eq!(body(&client, "/search"), Some("beta".into()));
+
+        assert!(client.rocket().features().reset("new_checkout"));
+        assert!(!client.rocket().features().reset("new_checkout"));
+        assert_eq!(body(&client, "/checkout"), Some("old".into()));
+
+        let snapshot = client.rocket().features().snapshot();
+        assert_eq!(snapshot, vec![
+            ("beta_search".to_string(), true),
+            ("new_checkout".to_string(), false)
+        ]);
+    }
+}
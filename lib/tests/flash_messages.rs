@@ -0,0 +1,61 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::response::{Flash, FlashLevel};
+use rocket::request::FlashMessage;
+
+#[post("/")]
+fn set() -> Flash<&'static str> {
+    Flash::success("Saved.", "Saved; really, \"saved\".")
+        .with_payload("{\"id\": 17}")
+        .push(FlashLevel::Warning.as_str(), "Almost out of space.")
+}
+
+#[get("/")]
+fn get(flash: Option<FlashMessage>) -> String {
+    let flash = match flash {
+        Some(flash) => flash,
+        None => return "none".into()
+    };
+
+    flash.messages().iter()
+        .map(|m| format!("{:?}|{}|{}", m.level(), m.msg(), m.payload().unwrap_or("-")))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+mod flash_messages_tests {
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::{Status, Cookie};
+
+    #[test]
+    fn multiple_messages_round_trip() {
+        let rocket = rocket::ignite().mount("/", routes![super::set, super::get]);
+        let client = Client::new(rocket).unwrap();
+
+        // The client tracks cookies, so the flash cookie is sent back.
+        let response = client.post("/").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+
+        let mut response = client.get("/").dispatch();
+
+        let expected = "Some(Success)|Saved; really, \"saved\".|{\"id\": 17}\n\
+                        Some(Warning)|Almost out of space.|-";
+        assert_eq!(response.body_string(), Some(expected.into()));
+    }
+
+    #[test]
+    fn legacy_cookie_is_understood() {
+        let rocket = rocket::ignite().mount("/", routes![super::set, super::get]);
+        let client = Client::new(rocket).unwrap();
+
+        let mut response = client.get("/")
+            .cookie(Cookie::new("_flash", "5errorOops"))
+            .dispatch();
+
+        assert_eq!(response.body_string(), Some("Some(Error)|Oops|-".into()));
+    }
+}
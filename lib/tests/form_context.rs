@@ -0,0 +1,62 @@
+#![feature(plugin, decl_macro, custom_derive)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::request::Contextual;
+
+#[derive(FromForm)]
+struct Signup {
+    name: String,
+    age: u8,
+    email: String,
+}
+
+#[post("/", data = "<form>")]
+fn signup(form: Contextual<Signup>) -> String {
+    let context = form.context();
+    match form.get() {
+        Some(signup) => format!("ok: {} {} {}", signup.name, signup.age, signup.email),
+        None => {
+            let mut errors: Vec<_> = context.errors().iter()
+                .map(|e| format!("{:?}:{}", e.kind(), e.name()))
+                .collect();
+
+            errors.sort();
+            format!("name={} age={} | {}",
+                    context.value("name").unwrap_or("-"),
+                    context.value("age").unwrap_or("-"),
+                    errors.join(","))
+        }
+    }
+}
+
+mod form_context_tests {
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::{Status, ContentType};
+
+    fn submit(body: &str) -> (Status, Option<String>) {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::signup])).unwrap();
+        let mut response = client.post("/")
+            .header(ContentType::Form)
+            .body(body)
+            .dispatch();
+
+        (response.status(), response.body_string())
+    }
+
+    #[test]
+    fn valid_submission_parses() {
+        let (status, body) = submit("name=Bob&age=30&email=bob%40example.com&extra=1");
+        assert_eq!(status, Status::Ok);
+        assert_eq!(body, Some("ok: Bob 30 bob@example.com".into()));
+    }
+
+    #[test]
+    fn invalid_submission_retains_values_and_errors() {
+        let (status, body) = submit("name=Bob+Smith&age=ten");
+        assert_eq!(status, Status::Ok);
+        assert_eq!(body, Some("name=Bob Smith age=ten | Invalid:age,Missing:email".into()));
+    }
+}
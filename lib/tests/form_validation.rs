@@ -0,0 +1,78 @@
+#![feature(plugin, decl_macro, custom_derive)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::request::{Form, Contextual};
+
+#[derive(FromForm)]
+struct Signup {
+    #[validate(length(min = 3, max = 8))]
+    username: String,
+    #[validate(email)]
+    email: String,
+    #[validate(range(min = 13, max = 130))]
+    age: u8,
+    #[validate(length(max = 5))]
+    nickname: Option<String>,
+}
+
+#[post("/strict", data = "<form>")]
+fn strict(form: Form<Signup>) -> String {
+    form.into_inner().username
+}
+
+#[post("/context", data = "<form>")]
+fn context(form: Contextual<Signup>) -> String {
+    let mut errors: Vec<_> = form.context().errors().iter()
+        .map(|e| e.to_string())
+        .collect();
+
+    errors.sort();
+    errors.join("\n")
+}
+
+mod form_validation_tests {
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::{Status, ContentType};
+
+    #[test]
+    fn valid_form_passes() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::strict])).unwrap();
+        let mut response = client.post("/strict")
+            .header(ContentType::Form)
+            .body("username=bob&email=bob%40example.com&age=30")
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some("bob".into()));
+    }
+
+    #[test]
+    fn invalid_form_is_unprocessable() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::strict])).unwrap();
+        let response = client.post("/strict")
+            .header(ContentType::Form)
+            .body("username=bo&email=bob%40example.com&age=30")
+            .dispatch();
+
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+
+    #[test]
+    fn errors_are_aggregated() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::context])).unwrap();
+        let mut response = client.post("/context")
+            .header(ContentType::Form)
+            .body("username=robertson&email=bob&age=7&nickname=bobster")
+            .dispatch();
+
+        let expected = "'age' must be at least 13.\n\
+                        'email' must be an email address.\n\
+                        'nickname' must be at most 5 characters long.\n\
+                        'username' must be at most 8 characters long.";
+
+        assert_eq!(response.body_string(), Some(expected.into()));
+    }
+}
@@ -0,0 +1,116 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rocket::{Request, State, Outcome};
+use rocket::request::{self, FromRequest};
+use rocket::http::Status;
+
+const REPORT_LEN: usize = 4096;
+
+struct Renders(AtomicUsize);
+
+fn report_len(_: &Request) -> Option<u64> {
+    Some(REPORT_LEN as u64)
+}
+
+fn unknown_len(_: &Request) -> Option<u64> {
+    None
+}
+
+#[get("/report", format = "text/plain", head_length = "report_len")]
+fn report(renders: State<Renders>) -> String {
+    renders.0.fetch_add(1, Ordering::SeqCst);
+    "x".repeat(REPORT_LEN)
+}
+
+#[get("/other", head_length = "unknown_len")]
+fn other(renders: State<Renders>) -> &'static str {
+    renders.0.fetch_add(1, Ordering::SeqCst);
+    "other"
+}
+
+struct Admin;
+
+impl<'a, 'r> FromRequest<'a, 'r> for Admin {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Admin, ()> {
+        match request.headers().get_one("Authorization") {
+            Some("admin") => Outcome::Success(Admin),
+            _ => Outcome::Failure((Status::Unauthorized, ()))
+        }
+    }
+}
+
+#[get("/admin/report", head_length = "report_len")]
+fn admin_report(_admin: Admin, renders: State<Renders>) -> String {
+    renders.0.fetch_add(1, Ordering::SeqCst);
+    "x".repeat(REPORT_LEN)
+}
+
+mod head_length_tests {
+    use super::*;
+    use rocket::local::Client;
+    use rocket::response::Body;
+    use rocket::http::{Status, ContentType, Accept, Header};
+
+    fn rocket() -> rocket::Rocket {
+        rocket::ignite()
+            .manage(Renders(AtomicUsize::new(0)))
+            .mount("/", routes![report, other, admin_report])
+    }
+
+    fn renders(client: &Client) -> usize {
+        client.rocket().state::<Renders>().unwrap().0.load(Ordering::SeqCst)
+    }
+
+    #[test]
+    fn head_answered_from_metadata() {
+        let client = Client::new(rocket()).unwrap();
+        let mut response = client.head("/report").header(Accept::Plain).dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::Plain));
+        let size = match response.body() {
+            Some(Body::Sized(_, n)) => Some(n),
+            _ => None
+        };
+
+        assert_eq!(size, Some(REPORT_LEN as u64));
+        assert_eq!(renders(&client), 0);
+    }
+
+    #[test]
+    fn head_falls_back_to_handler() {
+        let client = Client::new(rocket()).unwrap();
+        let mut response = client.head("/other").dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some("".into()));
+        assert_eq!(renders(&client), 1);
+    }
+
+    #[test]
+    fn head_runs_guards_first() {
+        let client = Client::new(rocket()).unwrap();
+        let response = client.head("/admin/report").dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        let mut response = client.head("/admin/report")
+            .header(Header::new("Authorization", "admin"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        let size = match response.body() {
+            Some(Body::Sized(_, n)) => Some(n),
+            _ => None
+        };
+
+        assert_eq!(size, Some(REPORT_LEN as u64));
+        assert_eq!(renders(&client), 0);
+    }
+}
@@ -0,0 +1,85 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::State;
+use rocket::response::Response;
+
+struct Injected(String, String);
+
+#[get("/")]
+fn inject(injected: State<Injected>) -> Response<'static> {
+    Response::build()
+        .raw_header(injected.0.clone(), injected.1.clone())
+        .finalize()
+}
+
+mod header_injection_tests {
+    use std::fs;
+    use std::path::Path;
+
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::{Header, Status};
+
+    fn rocket_injecting(name: &str, value: &str) -> rocket::Rocket {
+        let injected = super::Injected(name.to_string(), value.to_string());
+        rocket::ignite().mount("/", routes![super::inject]).manage(injected)
+    }
+
+    #[test]
+    fn valid_headers_are_written() {
+        let client = Client::new(rocket_injecting("X-Custom", "a\tb; c=\"d\"")).unwrap();
+        let response = client.get("/").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.headers().get_one("X-Custom"), Some("a\tb; c=\"d\""));
+        assert_eq!(client.rocket().metrics().get("responses.invalid_headers"), None);
+    }
+
+    #[test]
+    fn split_responses_are_replaced_with_errors() {
+        let rocket = rocket_injecting("Location", "/home\r\nSet-Cookie: session=evil");
+        let client = Client::new(rocket).unwrap();
+        let response = client.get("/").dispatch();
+        assert_eq!(response.status(), Status::InternalServerError);
+        assert!(!response.headers().contains("Location"));
+        assert!(!response.headers().contains("Set-Cookie"));
+        assert_eq!(client.rocket().metrics().get("responses.invalid_headers"), Some(1));
+    }
+
+    #[test]
+    fn invalid_names_are_replaced_with_errors() {
+        let client = Client::new(rocket_injecting("X-Evil\r\nSet-Cookie", "value")).unwrap();
+        let response = client.get("/").dispatch();
+        assert_eq!(response.status(), Status::InternalServerError);
+        assert!(!response.headers().contains("Set-Cookie"));
+    }
+
+    // Replays the seeds of the `header_serialization` fuzz target: a header is
+    // written if and only if it's valid, and then exactly as it was set.
+    #[test]
+    fn fuzz_corpus_is_handled() {
+        let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus");
+        for entry in fs::read_dir(corpus.join("header_serialization")).unwrap() {
+            let data = fs::read(entry.unwrap().path()).unwrap();
+            let input = String::from_utf8_lossy(&data);
+            let (name, value) = match input.find(':') {
+                Some(i) => (&input[..i], &input[(i + 1)..]),
+                None => (&*input, "")
+            };
+
+            let client = Client::new(rocket_injecting(name, value)).unwrap();
+            let response = client.get("/").dispatch();
+            match Header::try_new(name, value) {
+                Ok(_) => {
+                    assert_eq!(response.status(), Status::Ok, "{:?}", input);
+                    assert_eq!(response.headers().get_one(name), Some(value), "{:?}", input);
+                }
+                Err(_) => {
+                    assert_eq!(response.status(), Status::InternalServerError, "{:?}", input);
+                }
+            }
+        }
+    }
+}
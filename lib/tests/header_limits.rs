@@ -0,0 +1,94 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+#[post("/echo", data = "<body>")]
+fn echo(body: String) -> String {
+    body.len().to_string()
+}
+
+mod header_limits_tests {
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::thread;
+    use std::time::Duration;
+
+    use rocket;
+    use rocket::config::{Config, Environment};
+
+    const PORT: u16 = 48537;
+
+    fn connect() -> TcpStream {
+        let config = Config::build(Environment::Development)
+            .address("127.0.0.1")
+            .port(PORT)
+            .max_header_size(1024)
+            .max_header_count(8)
+            .unwrap();
+
+        let rocket = rocket::custom(config, false).mount("/", routes![super::echo]);
+        thread::spawn(move || rocket.launch());
+        for _ in 0..50 {
+            if let Ok(stream) = TcpStream::connect(("127.0.0.1", PORT)) {
+                stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+                return stream;
+            }
+
+            thread::sleep(Duration::from_millis(100));
+        }
+
+        panic!("the server didn't start");
+    }
+
+    // Reads one response with a small body and returns its status line and
+    // body.
+    fn read_response(stream: &mut TcpStream) -> (String, String) {
+        let mut response = vec![];
+        let mut buf = [0; 1024];
+        loop {
+            let n = stream.read(&mut buf).unwrap();
+            response.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&response).into_owned();
+            if let Some(i) = text.find("\r\n\r\n") {
+                let length = text[..i].lines()
+                    .find(|line| line.to_lowercase().starts_with("content-length:"))
+                    .map_or(0, |line| line[15..].trim().parse::<usize>().unwrap());
+
+                if n == 0 || text.len() >= i + 4 + length {
+                    let status = text.lines().next().unwrap().to_string();
+                    return (status, text[(i + 4)..].to_string());
+                }
+            } else if n == 0 {
+                panic!("connection closed mid-response: {:?}", text);
+            }
+        }
+    }
+
+    #[test]
+    fn bodies_are_not_counted_but_the_next_head_is() {
+        let mut stream = connect();
+
+        // A pretty-printed body larger than `max_header_size`, with many more
+        // lines than `max_header_count`.
+        let body = "  \"key\": \"value\",\n".repeat(200);
+        write!(stream, "POST /echo HTTP/1.1\r\nHost: localhost\r\n\
+               Content-Type: text/plain\r\nContent-Length: {}\r\n\r\n{}",
+               body.len(), body).unwrap();
+
+        let (status, length) = read_response(&mut stream);
+        assert!(status.starts_with("HTTP/1.1 200"), "{}", status);
+        assert_eq!(length, body.len().to_string());
+
+        // The next request on the connection has too many headers.
+        let mut request = "GET /echo HTTP/1.1\r\nHost: localhost\r\n".to_string();
+        for i in 0..10 {
+            request.push_str(&format!("X-Header-{}: {}\r\n", i, i));
+        }
+
+        stream.write_all(request.as_bytes()).unwrap();
+        stream.write_all(b"\r\n").unwrap();
+        let (status, _) = read_response(&mut stream);
+        assert!(status.starts_with("HTTP/1.1 431"), "{}", status);
+    }
+}
@@ -0,0 +1,81 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::Response;
+use rocket::http::{Status, ContentType};
+
+#[get("/")]
+fn index() -> Response<'static> {
+    Response::build()
+        .header(ContentType::Plain)
+        .raw_header("X-Powered-By", "Rust")
+        .raw_header("X-Debug-Trace", "index.rs:12")
+        .raw_header("Cache-Control", "no-cache")
+        .finalize()
+}
+
+#[catch(404)]
+fn not_found() -> Response<'static> {
+    Response::build()
+        .status(Status::NotFound)
+        .raw_header("X-Debug-Route", "none matched")
+        .finalize()
+}
+
+mod header_policy_tests {
+    use rocket;
+    use rocket::config::{Environment, Config, HeaderPolicy};
+    use rocket::local::Client;
+
+    fn rocket_with_policy(policy: HeaderPolicy) -> rocket::Rocket {
+        let config = Config::build(Environment::Development)
+            .header_policy(policy)
+            .unwrap();
+
+        rocket::custom(config, true)
+            .mount("/", routes![super::index])
+            .catch(catchers![super::not_found])
+    }
+
+    #[test]
+    fn default_policy_keeps_headers() {
+        let client = Client::new(rocket_with_policy(HeaderPolicy::new())).unwrap();
+        let response = client.get("/").dispatch();
+        assert_eq!(response.headers().get_one("Server"), Some("Rocket"));
+        assert_eq!(response.headers().get_one("X-Powered-By"), Some("Rust"));
+        assert!(response.headers().get_one("X-Debug-Trace").is_some());
+    }
+
+    #[test]
+    fn denied_headers_are_removed() {
+        let policy = HeaderPolicy::new()
+            .deny("x-powered-by")
+            .deny("X-Debug-*")
+            .rewrite("Server", "web");
+
+        let client = Client::new(rocket_with_policy(policy)).unwrap();
+        let response = client.get("/").dispatch();
+        assert_eq!(response.headers().get_one("Server"), Some("web"));
+        assert_eq!(response.headers().get_one("Cache-Control"), Some("no-cache"));
+        assert!(response.headers().get_one("X-Powered-By").is_none());
+        assert!(response.headers().get_one("X-Debug-Trace").is_none());
+
+        let response = client.get("/missing").dispatch();
+        assert_eq!(response.headers().get_one("Server"), Some("web"));
+        assert!(response.headers().get_one("X-Debug-Route").is_none());
+    }
+
+    #[test]
+    fn only_allowed_headers_are_kept() {
+        let policy = HeaderPolicy::new().allow("Cache-Control");
+        let client = Client::new(rocket_with_policy(policy)).unwrap();
+        let response = client.get("/").dispatch();
+        assert_eq!(response.headers().get_one("Cache-Control"), Some("no-cache"));
+        assert!(response.headers().get_one("Content-Type").is_some());
+        assert!(response.headers().get_one("Server").is_none());
+        assert!(response.headers().get_one("X-Powered-By").is_none());
+        assert!(response.headers().get_one("X-Debug-Trace").is_none());
+    }
+}
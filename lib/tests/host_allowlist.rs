@@ -0,0 +1,76 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::request::Host;
+
+#[get("/")]
+fn index(host: Host) -> String {
+    host.to_string()
+}
+
+mod host_allowlist_tests {
+    use rocket;
+    use rocket::config::{Environment, Config};
+    use rocket::local::Client;
+    use rocket::http::{Status, Header};
+
+    fn rocket_with_allowed_hosts(hosts: &[&str]) -> rocket::Rocket {
+        let config = hosts.iter()
+            .fold(Config::build(Environment::Development), |config, host| {
+                config.allowed_host(*host)
+            })
+            .unwrap();
+
+        rocket::custom(config, true).mount("/", routes![super::index])
+    }
+
+    fn status(client: &Client, host: Option<&'static str>) -> Status {
+        let mut request = client.get("/");
+        if let Some(host) = host {
+            request.add_header(Header::new("Host", host));
+        }
+
+        request.dispatch().status()
+    }
+
+    #[test]
+    fn any_host_without_allowlist() {
+        let client = Client::new(rocket_with_allowed_hosts(&[])).unwrap();
+        assert_eq!(status(&client, Some("evil.com")), Status::Ok);
+
+        let mut response = client.get("/").header(Header::new("Host", "a.b:80")).dispatch();
+        assert_eq!(response.body_string(), Some("a.b:80".into()));
+
+        // The guard itself still requires a well-formed host.
+        assert_eq!(status(&client, None), Status::BadRequest);
+        assert_eq!(status(&client, Some("a b")), Status::BadRequest);
+    }
+
+    #[test]
+    fn allowlisted_hosts() {
+        let hosts = ["example.com", "*.example.com", "localhost:8000", "[::1]"];
+        let client = Client::new(rocket_with_allowed_hosts(&hosts)).unwrap();
+        assert_eq!(status(&client, Some("example.com")), Status::Ok);
+        assert_eq!(status(&client, Some("EXAMPLE.com:443")), Status::Ok);
+        assert_eq!(status(&client, Some("api.example.com")), Status::Ok);
+        assert_eq!(status(&client, Some("localhost:8000")), Status::Ok);
+        assert_eq!(status(&client, Some("[::1]:8000")), Status::Ok);
+
+        assert_eq!(status(&client, Some("evil.com")), Status::MisdirectedRequest);
+        assert_eq!(status(&client, Some("example.com.evil.com")), Status::MisdirectedRequest);
+        assert_eq!(status(&client, Some("localhost:8001")), Status::MisdirectedRequest);
+        assert_eq!(status(&client, Some("localhost")), Status::MisdirectedRequest);
+        assert_eq!(status(&client, Some("exa mple.com")), Status::BadRequest);
+        assert_eq!(status(&client, None), Status::BadRequest);
+    }
+
+    #[test]
+    fn unbracketed_ipv6_is_malformed() {
+        let client = Client::new(rocket_with_allowed_hosts(&["example.com"])).unwrap();
+        assert_eq!(status(&client, Some("::1")), Status::BadRequest);
+        assert_eq!(status(&client, Some("fe80::1:8000")), Status::BadRequest);
+        assert_eq!(status(&client, Some("[fe80::1]:8000")), Status::MisdirectedRequest);
+    }
+}
@@ -0,0 +1,166 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rocket::State;
+
+struct Charges(AtomicUsize);
+
+#[post("/charge", data = "<amount>")]
+fn charge(amount: String, charges: State<Charges>) -> String {
+    let n = charges.0.fetch_add(1, Ordering::SeqCst) + 1;
+    format!("charge #{}: {}", n, amount)
+}
+
+#[post("/slow_charge", data = "<amount>")]
+fn slow_charge(amount: String, charges: State<Charges>) -> String {
+    ::std::thread::sleep(::std::time::Duration::from_millis(20));
+    charge(amount, charges)
+}
+
+mod idempotency_key_tests {
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::*;
+    use rocket::local::Client;
+    use rocket::config::{Config, Environment, Limits};
+    use rocket::http::{Status, Header};
+    use rocket::idempotency::{Idempotency, MemoryStore};
+
+    fn rocket() -> rocket::Rocket {
+        rocket::ignite()
+            .idempotency(Idempotency::new(MemoryStore::new()))
+            .manage(Charges(AtomicUsize::new(0)))
+            .mount("/", routes![charge, slow_charge])
+    }
+
+    #[test]
+    fn retries_are_replayed() {
+        let client = Client::new(rocket()).unwrap();
+        let mut first = client.post("/charge")
+            .header(Header::new("Idempotency-Key", "abc"))
+            .body("10")
+            .dispatch();
+
+        assert_eq!(first.status(), Status::Ok);
+        assert_eq!(first.body_string(), Some("charge #1: 10".into()));
+        assert!(first.headers().get_one("Idempotent-Replayed").is_none());
+
+        let mut retry = client.post("/charge")
+            .header(Header::new("Idempotency-Key", "abc"))
+            .body("10")
+            .dispatch();
+
+        assert_eq!(retry.status(), Status::Ok);
+        assert_eq!(retry.body_string(), Some("charge #1: 10".into()));
+        assert_eq!(retry.headers().get_one("Idempotent-Replayed"), Some("true"));
+    }
+
+    #[test]
+    fn key_reuse_with_different_body_is_rejected() {
+        let client = Client::new(rocket()).unwrap();
+        client.post("/charge")
+            .header(Header::new("Idempotency-Key", "xyz"))
+            .body("10")
+            .dispatch();
+
+        let response = client.post("/charge")
+            .header(Header::new("Idempotency-Key", "xyz"))
+            .body("20")
+            .dispatch();
+
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+
+    #[test]
+    fn requests_without_key_are_not_recorded() {
+        let client = Client::new(rocket()).unwrap();
+        let mut first = client.post("/charge").body("5").dispatch();
+        let mut second = client.post("/charge").body("5").dispatch();
+
+        assert_eq!(first.body_string(), Some("charge #1: 5".into()));
+        assert_eq!(second.body_string(), Some("charge #2: 5".into()));
+    }
+
+    #[test]
+    fn keys_are_scoped_to_the_client() {
+        let client = Client::new(rocket()).unwrap();
+        let request = |authorization: &str, remote: &str| client.post("/charge")
+            .header(Header::new("Idempotency-Key", "shared"))
+            .header(Header::new("Authorization", authorization.to_string()))
+            .remote(remote.parse().unwrap())
+            .body("10")
+            .dispatch()
+            .body_string();
+
+        assert_eq!(request("Bearer alice", "1.1.1.1:80"), Some("charge #1: 10".into()));
+        assert_eq!(request("Bearer mallory", "1.1.1.1:80"), Some("charge #2: 10".into()));
+        assert_eq!(request("Bearer alice", "2.2.2.2:80"), Some("charge #1: 10".into()));
+
+        let anonymous = |remote: &str| client.post("/charge")
+            .header(Header::new("Idempotency-Key", "shared"))
+            .remote(remote.parse().unwrap())
+            .body("10")
+            .dispatch()
+            .body_string();
+
+        assert_eq!(anonymous("3.3.3.3:80"), Some("charge #3: 10".into()));
+        assert_eq!(anonymous("4.4.4.4:80"), Some("charge #4: 10".into()));
+        assert_eq!(anonymous("3.3.3.3:80"), Some("charge #3: 10".into()));
+    }
+
+    #[test]
+    fn concurrent_requests_run_the_handler_once() {
+        let client = Arc::new(Client::new(rocket()).unwrap());
+        let handles: Vec<_> = (0..8).map(|_| {
+            let client = client.clone();
+            thread::spawn(move || {
+                client.post("/slow_charge")
+                    .header(Header::new("Idempotency-Key", "concurrent"))
+                    .body("10")
+                    .dispatch_owned()
+            })
+        }).collect();
+
+        for handle in handles {
+            let response = handle.join().unwrap();
+            match response.status() {
+                Status::Ok => assert_eq!(response.body_string(), Some("charge #1: 10".into())),
+                status => assert_eq!(status, Status::Conflict),
+            }
+        }
+
+        let charges = client.rocket().state::<Charges>().unwrap();
+        assert_eq!(charges.0.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn responses_over_the_limit_are_not_recorded() {
+        let config = Config::build(Environment::Development)
+            .limits(Limits::new().limit("idempotency", 12))
+            .unwrap();
+
+        let rocket = rocket::custom(config, true)
+            .idempotency(Idempotency::new(MemoryStore::new()))
+            .manage(Charges(AtomicUsize::new(0)))
+            .mount("/", routes![charge]);
+
+        let client = Client::new(rocket).unwrap();
+        let request = || client.post("/charge")
+            .header(Header::new("Idempotency-Key", "large"))
+            .body("10")
+            .dispatch();
+
+        let mut first = request();
+        assert_eq!(first.status(), Status::Ok);
+        assert_eq!(first.body_string(), Some("charge #1: 10".into()));
+
+        let mut retry = request();
+        assert_eq!(retry.body_string(), Some("charge #2: 10".into()));
+        assert!(retry.headers().get_one("Idempotent-Replayed").is_none());
+    }
+}
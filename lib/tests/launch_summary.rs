@@ -0,0 +1,58 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+#[get("/")]
+fn index() -> &'static str {
+    "index"
+}
+
+#[post("/items", format = "application/json", data = "<body>")]
+fn create(body: String) -> String {
+    body
+}
+
+#[catch(404)]
+fn not_found() -> &'static str {
+    "not found"
+}
+
+mod launch_summary_tests {
+    use rocket;
+    use rocket::config::{Config, Environment};
+    use rocket::fairing::AdHoc;
+
+    fn rocket() -> rocket::Rocket {
+        let config = Config::build(Environment::Staging)
+            .address("127.0.0.1")
+            .port(8123)
+            .workers(3)
+            .unwrap();
+
+        rocket::custom(config, false)
+            .mount("/api", routes![super::index, super::create])
+            .catch(catchers![super::not_found])
+            .attach(AdHoc::on_response(|_, _| ()))
+    }
+
+    #[test]
+    fn summary_describes_rocket() {
+        let summary = rocket().launch_summary();
+        assert_eq!(summary.environment, "staging");
+        assert_eq!(summary.url(), "http://127.0.0.1:8123");
+        assert_eq!(summary.workers, 3);
+        assert!(!summary.tls);
+        assert_eq!(summary.catchers, vec![404]);
+
+        let routes: Vec<_> = summary.routes.iter()
+            .map(|r| (r.method.as_str(), r.uri.as_str(), r.format.clone()))
+            .collect();
+
+        assert!(routes.contains(&("GET", "/api", None)));
+        assert!(routes.contains(&("POST", "/api/items", Some("application/json".into()))));
+
+        assert_eq!(summary.fairings.len(), 1);
+        assert_eq!(summary.fairings[0].kinds, vec!["response"]);
+    }
+}
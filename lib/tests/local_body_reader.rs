@@ -0,0 +1,58 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use std::io;
+
+use rocket::Data;
+
+#[post("/count", data = "<data>")]
+fn count(data: Data) -> io::Result<String> {
+    data.stream_to(&mut io::sink()).map(|n| n.to_string())
+}
+
+#[post("/echo", data = "<body>")]
+fn echo(body: String) -> String {
+    body
+}
+
+mod local_body_reader_tests {
+    use std::io::{self, Read, Cursor};
+
+    use rocket;
+    use rocket::local::Client;
+
+    #[test]
+    fn large_streamed_body() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::count])).unwrap();
+        let size = 64 * 1024 * 1024;
+        let mut response = client.post("/count")
+            .body_reader(io::repeat(b'a').take(size))
+            .dispatch();
+
+        assert_eq!(response.body_string(), Some(size.to_string()));
+    }
+
+    #[test]
+    fn streamed_body_is_read_to_eof() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::echo])).unwrap();
+        let mut response = client.post("/echo")
+            .body_reader(Cursor::new("hello, world"))
+            .dispatch();
+
+        assert_eq!(response.body_string(), Some("hello, world".into()));
+
+        let mut request = client.post("/echo").body_reader(Cursor::new("streamed"));
+        request.set_body("buffered");
+        assert_eq!(request.dispatch().body_string(), Some("buffered".into()));
+    }
+
+    #[test]
+    fn mut_dispatch_consumes_streamed_body() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::echo])).unwrap();
+        let mut request = client.post("/echo").body_reader(Cursor::new("once"));
+        assert_eq!(request.mut_dispatch().body_string(), Some("once".into()));
+        assert_eq!(request.mut_dispatch().body_string(), Some("".into()));
+    }
+}
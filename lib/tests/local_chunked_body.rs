@@ -0,0 +1,66 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use std::io::Read;
+
+use rocket::{Request, Data};
+use rocket::request::{self, FromRequest};
+use rocket::outcome::Outcome;
+
+struct Chunked(bool);
+
+impl<'a, 'r> FromRequest<'a, 'r> for Chunked {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        let encoding = request.headers().get_one("Transfer-Encoding");
+        Outcome::Success(Chunked(encoding == Some("chunked")))
+    }
+}
+
+#[post("/upload", data = "<data>")]
+fn upload(chunked: Chunked, data: Data) -> String {
+    let mut body = String::new();
+    data.open().read_to_string(&mut body).unwrap();
+    let kind = if chunked.0 { "chunked" } else { "sized" };
+    format!("{}:{}", kind, body)
+}
+
+mod local_chunked_body_tests {
+    use rocket;
+    use rocket::local::Client;
+
+    #[test]
+    fn chunked_bodies_are_decoded() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::upload])).unwrap();
+        let mut response = client.post("/upload")
+            .chunked_body(vec!["Hello", "", ", ", "world!"])
+            .dispatch();
+
+        assert_eq!(response.body_string(), Some("chunked:Hello, world!".into()));
+
+        let mut response = client.post("/upload").body("Hello, world!").dispatch();
+        assert_eq!(response.body_string(), Some("sized:Hello, world!".into()));
+    }
+
+    #[test]
+    fn large_chunked_bodies_are_read_fully() {
+        let chunks: Vec<String> = (0..100).map(|i| format!("{:03}-", i)).collect();
+        let expected = format!("chunked:{}", chunks.concat());
+
+        let client = Client::new(rocket::ignite().mount("/", routes![super::upload])).unwrap();
+        let req = client.post("/upload").chunked_body(&chunks);
+        assert_eq!(req.cloned_dispatch().body_string(), Some(expected.clone()));
+        assert_eq!(req.dispatch().body_string(), Some(expected));
+    }
+
+    #[test]
+    fn empty_chunked_bodies() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::upload])).unwrap();
+        let chunks: Vec<&str> = vec![];
+        let mut response = client.post("/upload").chunked_body(chunks).dispatch();
+        assert_eq!(response.body_string(), Some("chunked:".into()));
+    }
+}
@@ -0,0 +1,91 @@
+#![cfg(feature = "tls")]
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::Outcome::*;
+use rocket::request::{self, FromRequest, Request};
+
+struct Chain(Vec<Vec<u8>>);
+
+impl<'a, 'r> FromRequest<'a, 'r> for Chain {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        match request.client_certificates() {
+            Some(certs) => Success(Chain(certs.iter().map(|c| c.0.clone()).collect())),
+            None => Forward(())
+        }
+    }
+}
+
+#[get("/")]
+fn chain(chain: Chain) -> String {
+    let certs: Vec<_> = chain.0.iter().map(|der| format!("{:?}", der)).collect();
+    certs.join(" ")
+}
+
+#[get("/", rank = 2)]
+fn anonymous() -> &'static str {
+    "anonymous"
+}
+
+mod local_client_certificates_tests {
+    use std::env;
+    use std::fs::File;
+    use std::io::{ErrorKind, Write};
+
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::Certificate;
+
+    #[test]
+    fn test_no_certificate() {
+        let rocket = rocket::ignite().mount("/", routes![super::chain, super::anonymous]);
+        let client = Client::new(rocket).unwrap();
+        let mut response = client.get("/").dispatch();
+        assert_eq!(response.body_string(), Some("anonymous".into()));
+    }
+
+    #[test]
+    fn test_single_certificate() {
+        let rocket = rocket::ignite().mount("/", routes![super::chain, super::anonymous]);
+        let client = Client::new(rocket).unwrap();
+        let mut response = client.get("/").certificate(Certificate(vec![1, 2])).dispatch();
+        assert_eq!(response.body_string(), Some("[1, 2]".into()));
+    }
+
+    #[test]
+    fn test_certificate_chain() {
+        let rocket = rocket::ignite().mount("/", routes![super::chain, super::anonymous]);
+        let client = Client::new(rocket).unwrap();
+        let chain = vec![Certificate(vec![1]), Certificate(vec![2]), Certificate(vec![3])];
+        let mut response = client.get("/").certificates(chain).dispatch();
+        assert_eq!(response.body_string(), Some("[1] [2] [3]".into()));
+    }
+
+    #[test]
+    fn test_certificate_file() {
+        let path = env::temp_dir().join("rocket-local-client-chain.pem");
+        let mut file = File::create(&path).unwrap();
+        file.write_all(b"-----BEGIN CERTIFICATE-----\nAQI=\n-----END CERTIFICATE-----\n\
+                         -----BEGIN CERTIFICATE-----\nAwQ=\n-----END CERTIFICATE-----\n")
+            .unwrap();
+
+        let rocket = rocket::ignite().mount("/", routes![super::chain, super::anonymous]);
+        let client = Client::new(rocket).unwrap();
+        let request = client.get("/").certificate_file(&path).unwrap();
+        let mut response = request.dispatch();
+        assert_eq!(response.body_string(), Some("[1, 2] [3, 4]".into()));
+
+        let empty = env::temp_dir().join("rocket-local-client-empty.pem");
+        File::create(&empty).unwrap();
+        let error = client.get("/").certificate_file(&empty).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+        let missing = env::temp_dir().join("rocket-local-client-missing.pem");
+        let error = client.get("/").certificate_file(&missing).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::NotFound);
+    }
+}
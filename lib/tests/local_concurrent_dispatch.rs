@@ -0,0 +1,64 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rocket::State;
+
+struct Hits(AtomicUsize);
+
+#[get("/")]
+fn hit(hits: State<Hits>) -> String {
+    hits.0.fetch_add(1, Ordering::SeqCst).to_string()
+}
+
+mod local_concurrent_dispatch_tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::Status;
+    use super::Hits;
+
+    #[test]
+    fn dispatch_from_many_threads() {
+        let rocket = rocket::ignite()
+            .mount("/", routes![super::hit])
+            .manage(Hits(AtomicUsize::new(0)));
+
+        let client = Arc::new(Client::new(rocket).unwrap());
+        let handles: Vec<_> = (0..8).map(|_| {
+            let client = client.clone();
+            thread::spawn(move || {
+                (0..25).map(|_| client.get("/").dispatch_owned()).collect::<Vec<_>>()
+            })
+        }).collect();
+
+        let mut seen = vec![];
+        for handle in handles {
+            for response in handle.join().unwrap() {
+                assert_eq!(response.status(), Status::Ok);
+                seen.push(response.body_string().unwrap().parse::<usize>().unwrap());
+            }
+        }
+
+        seen.sort();
+        assert_eq!(seen, (0..200).collect::<Vec<_>>());
+
+        let hits = client.rocket().state::<Hits>().unwrap();
+        assert_eq!(hits.0.load(Ordering::SeqCst), 200);
+    }
+
+    #[test]
+    fn owned_response_keeps_headers() {
+        let client = Client::new(rocket::ignite()).unwrap();
+        let response = client.get("/missing").dispatch_owned();
+        let response = thread::spawn(move || response).join().unwrap();
+        assert_eq!(response.status(), Status::NotFound);
+        assert!(response.headers().get_one("Content-Type").unwrap().contains("text/html"));
+    }
+}
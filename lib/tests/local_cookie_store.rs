@@ -0,0 +1,84 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::http::{Cookie, Cookies};
+
+#[post("/login")]
+fn login(mut cookies: Cookies) {
+    cookies.add(Cookie::new("session", "abc"));
+    cookies.add(Cookie::new("theme", "dark"));
+}
+
+#[get("/whoami")]
+fn whoami(cookies: Cookies) -> String {
+    cookies.get("session").map(|c| c.value().to_string()).unwrap_or_default()
+}
+
+mod local_cookie_store_tests {
+    use rocket;
+    use rocket::local::Client;
+
+    fn rocket() -> rocket::Rocket {
+        rocket::ignite().mount("/", routes![super::login, super::whoami])
+    }
+
+    #[test]
+    fn session_continues_in_new_client() {
+        let client = Client::new(rocket()).unwrap();
+        client.post("/login").dispatch();
+        let store = client.export_cookies();
+        assert_eq!(store, "session=abc\ntheme=dark\n");
+
+        let other = Client::new(rocket()).unwrap();
+        assert_eq!(other.get("/whoami").dispatch().body_string(), Some("".into()));
+
+        other.import_cookies(&store).unwrap();
+        assert_eq!(other.get("/whoami").dispatch().body_string(), Some("abc".into()));
+        assert_eq!(other.export_cookies(), store);
+    }
+
+    #[test]
+    fn invalid_store_imports_nothing() {
+        let client = Client::new(rocket()).unwrap();
+        let err = client.import_cookies("session=abc\n=oops\n").unwrap_err();
+        assert!(err.starts_with("line 2"));
+        assert_eq!(client.export_cookies(), "");
+    }
+
+    #[test]
+    fn expired_cookies_are_skipped() {
+        let client = Client::new(rocket()).unwrap();
+        client.import_cookies("session=old; Expires=Wed, 21 Oct 2015 07:28:00 GMT").unwrap();
+        assert_eq!(client.export_cookies(), "");
+    }
+
+    #[test]
+    fn jar_can_be_seeded_inspected_and_cleared() {
+        use rocket::http::Cookie;
+
+        let client = Client::new(rocket()).unwrap();
+        client.add_cookie(Cookie::new("session", "seeded")).unwrap();
+        assert_eq!(client.get("/whoami").dispatch().body_string(), Some("seeded".into()));
+
+        client.post("/login").dispatch();
+        let names: Vec<_> = client.cookies().iter().map(|c| c.name().to_string()).collect();
+        assert_eq!(names, vec!["session", "theme"]);
+        assert_eq!(client.cookies()[0].value(), "abc");
+
+        client.clear_cookies();
+        assert!(client.cookies().is_empty());
+        assert_eq!(client.get("/whoami").dispatch().body_string(), Some("".into()));
+    }
+
+    #[test]
+    fn untracked_client_has_no_jar() {
+        use rocket::http::Cookie;
+
+        let client = Client::untracked(rocket()).unwrap();
+        assert!(client.add_cookie(Cookie::new("session", "abc")).is_err());
+        client.post("/login").dispatch();
+        assert!(client.cookies().is_empty());
+    }
+}
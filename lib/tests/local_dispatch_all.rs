@@ -0,0 +1,71 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::http::{Cookie, Cookies, Status};
+
+#[post("/login")]
+fn login(mut cookies: Cookies) {
+    cookies.add(Cookie::new("session", "abc"));
+}
+
+#[post("/logout")]
+fn logout(mut cookies: Cookies) {
+    cookies.remove(Cookie::named("session"));
+}
+
+#[get("/whoami")]
+fn whoami(cookies: Cookies) -> Result<String, Status> {
+    cookies.get("session").map(|c| c.value().to_string()).ok_or(Status::Unauthorized)
+}
+
+mod local_dispatch_all_tests {
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::{Cookie, Status};
+
+    #[test]
+    fn responses_are_returned_in_order() {
+        let routes = routes![super::login, super::logout, super::whoami];
+        let client = Client::new(rocket::ignite().mount("/", routes)).unwrap();
+        let responses = client.dispatch_all(vec![
+            client.get("/whoami"),
+            client.post("/login"),
+            client.get("/whoami"),
+            client.post("/logout"),
+            client.get("/whoami"),
+            client.get("/missing"),
+        ]);
+
+        let statuses: Vec<_> = responses.iter().map(|r| r.status()).collect();
+        assert_eq!(statuses, vec![
+            Status::Unauthorized, Status::Ok, Status::Ok,
+            Status::Ok, Status::Unauthorized, Status::NotFound,
+        ]);
+
+        assert!(client.cookies().is_empty());
+    }
+
+    #[test]
+    fn explicit_cookies_are_kept() {
+        let routes = routes![super::login, super::logout, super::whoami];
+        let client = Client::new(rocket::ignite().mount("/", routes)).unwrap();
+        let mut responses = client.dispatch_all(vec![
+            client.post("/login"),
+            client.get("/whoami").cookie(Cookie::new("session", "explicit")),
+            client.get("/whoami"),
+        ]);
+
+        assert_eq!(responses[1].body_string(), Some("explicit".into()));
+        assert_eq!(responses[2].body_string(), Some("abc".into()));
+    }
+
+    #[test]
+    #[should_panic(expected = "another client")]
+    fn requests_from_other_clients_are_rejected() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::whoami])).unwrap();
+        let other = Client::new(rocket::ignite().mount("/", routes![super::whoami])).unwrap();
+        client.dispatch_all(vec![other.get("/whoami")]);
+    }
+}
@@ -0,0 +1,61 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::http::ContentType;
+
+#[post("/", data = "<body>")]
+fn echo(content_type: &ContentType, body: String) -> String {
+    let boundary = content_type.params()
+        .find(|&(key, _)| key == "boundary")
+        .map(|(_, value)| value)
+        .unwrap_or("");
+
+    body.replace(boundary, "BOUNDARY")
+}
+
+mod local_multipart_tests {
+    use rocket;
+    use rocket::local::{Client, Multipart};
+    use rocket::http::ContentType;
+
+    #[test]
+    fn multipart_body_is_encoded() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::echo])).unwrap();
+        let form = Multipart::new()
+            .text("title", "Holiday")
+            .file("photo", "beach \"1\".txt", ContentType::Plain, "sand");
+
+        let mut response = client.post("/").multipart(form).dispatch();
+        assert_eq!(response.body_string().unwrap(), "--BOUNDARY\r\n\
+            Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+            Holiday\r\n\
+            --BOUNDARY\r\n\
+            Content-Disposition: form-data; name=\"photo\"; filename=\"beach %221%22.txt\"\r\n\
+            Content-Type: text/plain; charset=utf-8\r\n\r\n\
+            sand\r\n\
+            --BOUNDARY--\r\n");
+    }
+
+    #[test]
+    fn multipart_sets_content_type() {
+        let client = Client::new(rocket::ignite()).unwrap();
+        let request = client.post("/")
+            .header(ContentType::JSON)
+            .multipart(Multipart::new().text("a", "b"));
+
+        let content_type = request.inner().content_type().unwrap();
+        assert!(content_type.is_form_data());
+        assert_eq!(request.inner().headers().get("Content-Type").count(), 1);
+    }
+
+    #[test]
+    fn boundary_avoids_part_contents() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::echo])).unwrap();
+        let form = Multipart::new().text("a", "RocketLocalBoundary00000000");
+        let mut response = client.post("/").multipart(form).dispatch();
+        let body = response.body_string().unwrap();
+        assert!(body.contains("RocketLocalBoundary00000000"));
+    }
+}
@@ -0,0 +1,94 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::http::Cookies;
+
+#[post("/echo", data = "<body>")]
+fn echo(body: String, cookies: Cookies) -> String {
+    match cookies.get("user") {
+        Some(user) => format!("{}: {}", user.value(), body),
+        None => body
+    }
+}
+
+#[get("/version")]
+fn old_version() -> &'static str {
+    "1"
+}
+
+#[get("/version")]
+fn new_version() -> &'static str {
+    "2"
+}
+
+mod local_record_replay_tests {
+    use rocket::{self, Rocket};
+    use rocket::local::{Client, Recording};
+    use rocket::http::{ContentType, Cookie, Status};
+
+    fn old() -> Rocket {
+        rocket::ignite().mount("/", routes![super::echo, super::old_version])
+    }
+
+    fn new() -> Rocket {
+        rocket::ignite().mount("/", routes![super::echo, super::new_version])
+    }
+
+    fn record() -> String {
+        let client = Client::new(old()).unwrap().record(true);
+        client.post("/echo")
+            .header(ContentType::Plain)
+            .cookie(Cookie::new("user", "bob"))
+            .body("hi")
+            .dispatch();
+
+        client.get("/version").dispatch();
+        client.get("/missing").dispatch();
+        client.take_recording().to_string()
+    }
+
+    #[test]
+    fn recording_round_trips() {
+        let log = record();
+        let recording = Recording::parse(&log).unwrap();
+        assert_eq!(recording.len(), 3);
+        assert_eq!(recording.to_string(), log);
+
+        let exchange = &recording.exchanges()[0];
+        assert_eq!(exchange.uri(), "/echo");
+        assert_eq!(exchange.body(), b"hi");
+        assert_eq!(exchange.headers().get_one("Cookie"), Some("user=bob"));
+        assert_eq!(exchange.response().body_string(), Some("bob: hi".into()));
+        assert_eq!(recording.exchanges()[2].response().status(), Status::NotFound);
+    }
+
+    #[test]
+    fn replay_against_same_build_matches() {
+        let recording = Recording::parse(&record()).unwrap();
+        let client = Client::untracked(old()).unwrap();
+        assert!(client.replay(&recording).is_empty());
+    }
+
+    #[test]
+    fn replay_against_new_build_reports_divergences() {
+        let recording = Recording::parse(&record()).unwrap();
+        let client = Client::untracked(new()).unwrap();
+        let divergences = client.replay(&recording);
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].index, 1);
+        assert_eq!(divergences[0].uri, "/version");
+        assert!(divergences[0].body_differs);
+        assert_eq!(divergences[0].to_string(),
+                   "exchange 1 (GET /version): the response body differs");
+    }
+
+    #[test]
+    fn malformed_recordings_are_rejected() {
+        assert!(Recording::parse("> GET /\n").is_err());
+        assert!(Recording::parse("> FETCH /\n< 200\n").is_err());
+        assert!(Recording::parse("> GET /\n< 200\n> H A: b\n").is_err());
+        assert!(Recording::parse("> GET /\n< 200\n< B ???\n").is_err());
+    }
+}
@@ -0,0 +1,80 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::response::Redirect;
+use rocket::http::{Cookie, Cookies};
+
+#[post("/login")]
+fn login(mut cookies: Cookies) -> Redirect {
+    cookies.add(Cookie::new("session", "1"));
+    Redirect::to("/home")
+}
+
+#[get("/home")]
+fn home(cookies: Cookies) -> String {
+    match cookies.get("session") {
+        Some(cookie) => format!("session {}", cookie.value()),
+        None => "no session".into()
+    }
+}
+
+#[post("/temporary")]
+fn temporary() -> Redirect {
+    Redirect::temporary("echo")
+}
+
+#[post("/echo", data = "<body>")]
+fn echo(body: String) -> String {
+    body
+}
+
+#[get("/loop")]
+fn redirect_loop() -> Redirect {
+    Redirect::to("http://localhost:8000/loop")
+}
+
+mod local_redirects_tests {
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::Status;
+
+    fn rocket() -> rocket::Rocket {
+        rocket::ignite().mount("/", routes![super::login, super::home, super::temporary,
+                                            super::echo, super::redirect_loop])
+    }
+
+    #[test]
+    fn redirects_are_not_followed_by_default() {
+        let client = Client::new(rocket()).unwrap();
+        let response = client.post("/login").dispatch();
+        assert_eq!(response.status(), Status::SeeOther);
+    }
+
+    #[test]
+    fn see_other_becomes_get_and_keeps_cookies() {
+        let client = Client::untracked(rocket()).unwrap().follow_redirects(true);
+        let mut response = client.post("/login").body("ignored").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some("session 1".into()));
+    }
+
+    #[test]
+    fn temporary_redirect_keeps_method_and_body() {
+        let client = Client::new(rocket()).unwrap().follow_redirects(true);
+        let mut response = client.post("/temporary").body("payload").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some("payload".into()));
+    }
+
+    #[test]
+    fn hop_limit_returns_last_redirect() {
+        let client = Client::new(rocket()).unwrap()
+            .follow_redirects(true)
+            .max_redirects(3);
+
+        let response = client.get("/loop").dispatch();
+        assert_eq!(response.status(), Status::SeeOther);
+    }
+}
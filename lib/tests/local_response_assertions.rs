@@ -0,0 +1,64 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::response::{Stream, content};
+
+#[get("/")]
+fn index() -> content::Json<&'static str> {
+    content::Json(r#"{ "greeting": "hello" }"#)
+}
+
+#[get("/stream")]
+fn stream() -> Stream<&'static [u8]> {
+    Stream::chunked(&b"streamed body"[..], 4)
+}
+
+mod local_response_assertions_tests {
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::Status;
+
+    #[test]
+    fn passing_assertions_chain() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::index])).unwrap();
+        let mut response = client.get("/").dispatch();
+        response.assert_status(Status::Ok)
+            .assert_header("content-type", "application/json")
+            .assert_body_contains("hello")
+            .assert_body(r#"{ "greeting": "hello" }"#);
+
+        // Body assertions leave the body in place.
+        assert_eq!(response.body_string(), Some(r#"{ "greeting": "hello" }"#.into()));
+    }
+
+    #[test]
+    fn chunked_bodies_stay_chunked() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::stream])).unwrap();
+        let mut response = client.get("/stream").dispatch();
+        response.assert_body("streamed body");
+        assert!(response.body().map(|b| b.is_chunked()).unwrap_or(false));
+    }
+
+    #[test]
+    #[should_panic(expected = "expected status `404 Not Found`, found `200 OK`")]
+    fn failing_status_assertion() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::index])).unwrap();
+        client.get("/").dispatch().assert_status(Status::NotFound);
+    }
+
+    #[test]
+    #[should_panic(expected = "{ \"greeting\": \"hello\" }")]
+    fn failure_message_includes_response() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::index])).unwrap();
+        client.get("/").dispatch().assert_body_contains("goodbye");
+    }
+
+    #[test]
+    #[should_panic(expected = "expected header `X-Missing: 1`")]
+    fn failing_header_assertion() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::index])).unwrap();
+        client.get("/").dispatch().assert_header("X-Missing", "1");
+    }
+}
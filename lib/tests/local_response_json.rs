@@ -0,0 +1,56 @@
+#![cfg(feature = "json")]
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::response::content;
+
+#[get("/json")]
+fn json() -> content::Json<&'static str> {
+    content::Json(r#"{ "apples": 3, "pears": 5 }"#)
+}
+
+#[get("/problem")]
+fn problem() -> content::Content<&'static str> {
+    let problem = rocket::http::ContentType::new("application", "problem+json");
+    content::Content(problem, r#"{ "apples": 1 }"#)
+}
+
+#[get("/plain")]
+fn plain() -> &'static str {
+    r#"{ "apples": 3 }"#
+}
+
+mod local_response_json_tests {
+    use std::collections::HashMap;
+
+    use rocket;
+    use rocket::local::Client;
+
+    type Counts = HashMap<String, usize>;
+
+    #[test]
+    fn json_bodies_are_deserialized() {
+        let routes = routes![super::json, super::problem];
+        let client = Client::new(rocket::ignite().mount("/", routes)).unwrap();
+        let mut response = client.get("/json").dispatch();
+        let counts: Counts = response.json().expect("JSON body");
+        assert_eq!(counts["pears"], 5);
+
+        // The body remains readable.
+        assert!(response.body_string().unwrap().contains("pears"));
+
+        let counts: Counts = client.get("/problem").dispatch().json().expect("JSON body");
+        assert_eq!(counts["apples"], 1);
+    }
+
+    #[test]
+    fn non_json_responses_are_rejected() {
+        let routes = routes![super::json, super::plain];
+        let client = Client::new(rocket::ignite().mount("/", routes)).unwrap();
+        assert!(client.get("/plain").dispatch().json::<Counts>().is_none());
+        assert!(client.get("/json").dispatch().json::<Vec<String>>().is_none());
+        assert!(client.get("/missing").dispatch().json::<Counts>().is_none());
+    }
+}
@@ -0,0 +1,63 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use std::thread;
+use std::time::Duration;
+
+use rocket::Request;
+use rocket::request::{self, FromRequest};
+use rocket::outcome::Outcome;
+
+struct SlowGuard;
+
+impl<'a, 'r> FromRequest<'a, 'r> for SlowGuard {
+    type Error = ();
+
+    fn from_request(_: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        thread::sleep(Duration::from_millis(40));
+        Outcome::Success(SlowGuard)
+    }
+}
+
+#[get("/slow")]
+fn slow(_guard: SlowGuard) -> &'static str {
+    thread::sleep(Duration::from_millis(20));
+    "slow"
+}
+
+mod local_response_timings_tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use rocket;
+    use rocket::fairing::AdHoc;
+    use rocket::local::Client;
+
+    #[test]
+    fn phases_are_timed() {
+        let rocket = rocket::ignite()
+            .attach(AdHoc::on_request(|_, _| thread::sleep(Duration::from_millis(10))))
+            .mount("/", routes![super::slow]);
+
+        let client = Client::new(rocket).unwrap();
+        let response = client.get("/slow").dispatch();
+        let timings = response.timings();
+
+        assert!(timings.guards >= Duration::from_millis(40));
+        assert!(timings.handler >= Duration::from_millis(20));
+        assert!(timings.fairings >= Duration::from_millis(10));
+        assert!(timings.total >= timings.guards + timings.handler + timings.fairings);
+        assert_eq!(response.time(), timings.total);
+    }
+
+    #[test]
+    fn unrouted_requests_are_timed() {
+        let client = Client::new(rocket::ignite()).unwrap();
+        let timings = client.get("/missing").dispatch().timings();
+        assert_eq!(timings.guards, Duration::from_secs(0));
+        assert_eq!(timings.handler, Duration::from_secs(0));
+        assert!(timings.total >= timings.routing);
+    }
+}
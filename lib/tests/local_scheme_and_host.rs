@@ -0,0 +1,82 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::Outcome;
+use rocket::request::{self, FromRequest, Request, Host};
+use rocket::response::Redirect;
+
+struct BaseUrl(String);
+
+impl<'a, 'r> FromRequest<'a, 'r> for BaseUrl {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        let host = request.headers().get_one("Host").unwrap_or("localhost");
+        Outcome::Success(BaseUrl(format!("{}://{}", request.scheme(), host)))
+    }
+}
+
+#[get("/url")]
+fn url(base: BaseUrl) -> String {
+    format!("{}/url", base.0)
+}
+
+#[get("/host")]
+fn host(host: Host) -> String {
+    format!("{} {:?}", host.name(), host.port())
+}
+
+#[get("/secure")]
+fn secure() -> Redirect {
+    Redirect::to("https://secure.example.com/url")
+}
+
+mod local_scheme_and_host_tests {
+    use rocket;
+    use rocket::local::Client;
+
+    #[test]
+    fn defaults_to_plain_http() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::url])).unwrap();
+        let mut response = client.get("/url").dispatch();
+        assert_eq!(response.body_string(), Some("http://localhost/url".into()));
+    }
+
+    #[test]
+    fn scheme_and_host_are_overridden() {
+        let routes = routes![super::url, super::host];
+        let client = Client::new(rocket::ignite().mount("/", routes)).unwrap();
+        let mut response = client.get("/url")
+            .scheme("https")
+            .host("example.com:8443")
+            .dispatch();
+
+        assert_eq!(response.body_string(), Some("https://example.com:8443/url".into()));
+
+        let mut response = client.get("/host").host("example.com:8443").dispatch();
+        assert_eq!(response.body_string(), Some("example.com Some(8443)".into()));
+    }
+
+    #[test]
+    fn host_replaces_existing_header() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::host])).unwrap();
+        let req = client.get("/host")
+            .header(rocket::http::Header::new("Host", "old.example.com"))
+            .host("new.example.com");
+
+        assert_eq!(req.inner().headers().get("Host").count(), 1);
+        assert_eq!(req.dispatch().body_string(), Some("new.example.com None".into()));
+    }
+
+    #[test]
+    fn absolute_redirects_switch_scheme_and_host() {
+        let routes = routes![super::url, super::secure];
+        let client = Client::new(rocket::ignite().mount("/", routes)).unwrap()
+            .follow_redirects(true);
+
+        let mut response = client.get("/secure").host("example.com").dispatch();
+        assert_eq!(response.body_string(), Some("https://secure.example.com/url".into()));
+    }
+}
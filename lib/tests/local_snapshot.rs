@@ -0,0 +1,61 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::response::Response;
+use rocket::http::{ContentType, Header};
+use std::io::Cursor;
+
+#[get("/")]
+fn index() -> Response<'static> {
+    Response::build()
+        .header(ContentType::HTML)
+        .header(Header::new("X-Request-Id", "6f1c"))
+        .header(Header::new("Date", "Tue, 02 Oct 2018 10:00:00 GMT"))
+        .header(Header::new("Cache-Control", "no-cache"))
+        .sized_body(Cursor::new("<h1>Hi!</h1>"))
+        .finalize()
+}
+
+#[get("/bytes")]
+fn bytes() -> Vec<u8> {
+    vec![0xff, 0x00, 0xfe]
+}
+
+mod local_snapshot_tests {
+    use rocket;
+    use rocket::local::Client;
+
+    #[test]
+    fn snapshots_sort_headers_and_redact_volatile_ones() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::index])).unwrap();
+        let snapshot = client.get("/").dispatch().into_snapshot().redact("x-request-id");
+        assert_eq!(snapshot.to_string(), "200 OK\n\
+                                          Cache-Control: no-cache\n\
+                                          Content-Type: text/html; charset=utf-8\n\
+                                          Date: [redacted]\n\
+                                          Server: Rocket\n\
+                                          X-Request-Id: [redacted]\n\
+                                          \n\
+                                          <h1>Hi!</h1>");
+
+        let snapshot = snapshot.reveal("Date");
+        assert_eq!(snapshot.header("date"), Some("Tue, 02 Oct 2018 10:00:00 GMT"));
+    }
+
+    #[test]
+    fn snapshots_are_stable_across_dispatches() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::index])).unwrap();
+        let first = client.get("/").dispatch().into_snapshot();
+        let second = client.get("/").dispatch().into_snapshot();
+        assert_eq!(first.to_string(), second.to_string());
+    }
+
+    #[test]
+    fn binary_bodies_are_base64_encoded() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::bytes])).unwrap();
+        let snapshot = client.get("/bytes").dispatch().into_snapshot();
+        assert!(snapshot.to_string().ends_with("\n\n[3 bytes, base64]\n/wD+"));
+    }
+}
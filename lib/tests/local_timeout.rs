@@ -0,0 +1,80 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use std::thread;
+use std::time::Duration;
+
+use rocket::http::{Cookie, Cookies};
+use rocket::response::Redirect;
+
+#[get("/fast")]
+fn fast(mut cookies: Cookies) -> &'static str {
+    cookies.add(Cookie::new("visited", "yes"));
+    "done"
+}
+
+#[get("/slow")]
+fn slow() -> &'static str {
+    thread::sleep(Duration::from_secs(30));
+    "too late"
+}
+
+#[post("/echo", data = "<body>")]
+fn echo(body: String) -> String {
+    body
+}
+
+#[get("/moved")]
+fn moved() -> Redirect {
+    Redirect::to("/slow")
+}
+
+mod local_timeout_tests {
+    use std::time::{Duration, Instant};
+
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::Status;
+
+    #[test]
+    fn responses_within_the_deadline_are_returned() {
+        let routes = routes![super::fast, super::echo];
+        let client = Client::new(rocket::ignite().mount("/", routes)).unwrap();
+        let mut response = client.get("/fast").timeout(Duration::from_secs(10)).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some("done".into()));
+        assert!(response.cookies().iter().any(|c| c.name() == "visited"));
+        assert!(client.export_cookies().contains("visited=yes"));
+
+        let mut response = client.post("/echo")
+            .body("hello")
+            .timeout(Duration::from_secs(10))
+            .dispatch();
+
+        assert_eq!(response.body_string(), Some("hello".into()));
+    }
+
+    #[test]
+    fn handlers_over_the_deadline_time_out() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::slow])).unwrap();
+        let start = Instant::now();
+        let mut response = client.get("/slow").timeout(Duration::from_millis(100)).dispatch();
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert_eq!(response.status(), Status::ServiceUnavailable);
+
+        let body = response.body_string().unwrap();
+        assert!(body.contains("/slow") && body.contains("timed out"));
+    }
+
+    #[test]
+    fn redirects_inherit_the_timeout() {
+        let routes = routes![super::slow, super::moved];
+        let client = Client::new(rocket::ignite().mount("/", routes)).unwrap()
+            .follow_redirects(true);
+
+        let response = client.get("/moved").timeout(Duration::from_millis(100)).dispatch();
+        assert_eq!(response.status(), Status::ServiceUnavailable);
+    }
+}
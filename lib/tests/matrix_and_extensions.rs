@@ -0,0 +1,75 @@
+#![feature(plugin, decl_macro, custom_derive)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::http::ContentType;
+use rocket::request::{Matrix, WithExtension};
+use rocket::response::content::Content;
+
+#[get("/map/<view>/tiles")]
+fn tiles(view: Matrix<String>) -> String {
+    let zoom: u8 = view.get_as("zoom").and_then(|r| r.ok()).unwrap_or(1);
+    let debug = view.get("debug").is_some();
+    format!("{} zoom={} debug={}", view.value(), zoom, debug)
+}
+
+#[get("/report.<ext>")]
+fn report(ext: String) -> String {
+    format!("report as {}", ext)
+}
+
+#[get("/v<version>/status")]
+fn status(version: u8) -> String {
+    format!("v{} ok", version)
+}
+
+#[get("/files/<file>")]
+fn file(file: WithExtension<String>) -> Content<String> {
+    let content_type = file.content_type().unwrap_or(ContentType::Plain);
+    Content(content_type, file.into_inner())
+}
+
+mod matrix_and_extensions_tests {
+    use super::*;
+    use rocket::local::Client;
+    use rocket::http::Status;
+
+    fn assert_body(client: &Client, path: &str, expected: &str) {
+        let mut response = client.get(path).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some(expected.into()));
+    }
+
+    #[test]
+    fn matrix_params() {
+        let client = Client::new(rocket::ignite().mount("/", routes![tiles])).unwrap();
+        assert_body(&client, "/map/road/tiles", "road zoom=1 debug=false");
+        assert_body(&client, "/map/road;zoom=5/tiles", "road zoom=5 debug=false");
+        assert_body(&client, "/map/sat;debug;zoom=12/tiles", "sat zoom=12 debug=true");
+        assert_body(&client, "/map/sat;zoom=high/tiles", "sat zoom=1 debug=false");
+    }
+
+    #[test]
+    fn partial_segments() {
+        let client = Client::new(rocket::ignite().mount("/", routes![report, status])).unwrap();
+        assert_body(&client, "/report.pdf", "report as pdf");
+        assert_body(&client, "/report.csv", "report as csv");
+        assert_body(&client, "/v2/status", "v2 ok");
+        assert_eq!(client.get("/report").dispatch().status(), Status::NotFound);
+        assert_eq!(client.get("/report.").dispatch().status(), Status::NotFound);
+        assert_eq!(client.get("/vx/status").dispatch().status(), Status::NotFound);
+    }
+
+    #[test]
+    fn extensions() {
+        let client = Client::new(rocket::ignite().mount("/", routes![file])).unwrap();
+        let mut response = client.get("/files/data.json").dispatch();
+        assert_eq!(response.content_type(), Some(ContentType::JSON));
+        assert_eq!(response.body_string(), Some("data".into()));
+
+        let mut response = client.get("/files/README").dispatch();
+        assert_eq!(response.content_type(), Some(ContentType::Plain));
+        assert_eq!(response.body_string(), Some("README".into()));
+    }
+}
@@ -0,0 +1,49 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+#[post("/echo", data = "<body>")]
+fn echo(body: String) -> String {
+    body
+}
+
+mod memory_limit_tests {
+    use rocket;
+    use rocket::config::{Config, Environment, Limits};
+    use rocket::local::Client;
+    use rocket::http::Status;
+
+    fn rocket_with_memory_limit(limit: u64) -> rocket::Rocket {
+        let config = Config::build(Environment::Development)
+            .limits(Limits::new().limit("memory", limit))
+            .unwrap();
+
+        rocket::custom(config, true).mount("/", routes![super::echo])
+    }
+
+    #[test]
+    fn bodies_within_the_limit_are_read() {
+        let client = Client::new(rocket_with_memory_limit(16)).unwrap();
+        let mut response = client.post("/echo").body("sixteen bytes!!!").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some("sixteen bytes!!!".into()));
+    }
+
+    #[test]
+    fn bodies_over_the_limit_are_rejected() {
+        let client = Client::new(rocket_with_memory_limit(16)).unwrap();
+        let response = client.post("/echo").body("seventeen bytes!!").dispatch();
+        assert_eq!(response.status(), Status::PayloadTooLarge);
+        assert_eq!(client.rocket().metrics().get("requests.memory_exceeded"), Some(1));
+    }
+
+    #[test]
+    fn bodies_are_unbounded_without_a_limit() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::echo])).unwrap();
+        let body = "x".repeat(64 * 1024);
+        let mut response = client.post("/echo").body(&body).dispatch();
+        assert_eq!(response.body_string(), Some(body));
+        assert_eq!(client.rocket().metrics().get("requests.memory_exceeded"), None);
+    }
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "tls")]
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::tls::MutualTlsUser;
+
+#[get("/")]
+fn user(user: MutualTlsUser) -> String {
+    format!("{} ({} certs)", user.name().unwrap_or("unnamed"), user.certificates().len())
+}
+
+#[get("/certificate")]
+fn certificate(user: MutualTlsUser) -> Option<String> {
+    let certificate = user.certificate()?;
+    Some(format!("{} by {}: {:?}", certificate.subject(), certificate.issuer(),
+                 certificate.dns_names()))
+}
+
+#[get("/", rank = 2)]
+fn anonymous() -> &'static str {
+    "anonymous"
+}
+
+mod mutual_tls_user_tests {
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::{Certificate, Status};
+
+    #[test]
+    fn test_plaintext_forwards() {
+        let routes = routes![super::user, super::certificate, super::anonymous];
+        let client = Client::new(rocket::ignite().mount("/", routes)).unwrap();
+        let mut response = client.get("/").dispatch();
+        assert_eq!(response.body_string(), Some("anonymous".into()));
+
+        let mut response = client.get("/").certificates(vec![]).dispatch();
+        assert_eq!(response.body_string(), Some("anonymous".into()));
+    }
+
+    #[test]
+    fn test_common_name() {
+        let routes = routes![super::user, super::certificate, super::anonymous];
+        let client = Client::new(rocket::ignite().mount("/", routes)).unwrap();
+        let request = client.get("/").certificate_file("tests/mtls/alice.pem").unwrap();
+        assert_eq!(request.dispatch().body_string(), Some("alice (1 certs)".into()));
+
+        let request = client.get("/").certificate_file("tests/mtls/no_cn.pem").unwrap();
+        assert_eq!(request.dispatch().body_string(), Some("unnamed (1 certs)".into()));
+    }
+
+    #[test]
+    fn test_unparseable_certificate() {
+        let routes = routes![super::user, super::certificate, super::anonymous];
+        let client = Client::new(rocket::ignite().mount("/", routes)).unwrap();
+        let chain = vec![Certificate(vec![1, 2]), Certificate(vec![3])];
+        let mut response = client.get("/").certificates(chain.clone()).dispatch();
+        assert_eq!(response.body_string(), Some("unnamed (2 certs)".into()));
+
+        let response = client.get("/certificate").certificates(chain).dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[test]
+    fn test_parsed_certificate() {
+        let routes = routes![super::user, super::certificate, super::anonymous];
+        let client = Client::new(rocket::ignite().mount("/", routes)).unwrap();
+        let request = client.get("/certificate").certificate_file("tests/mtls/alice.pem").unwrap();
+        let expected = "O=Rocket, CN=alice by O=Rocket, CN=alice: [\"alice.example.com\"]";
+        assert_eq!(request.dispatch().body_string(), Some(expected.into()));
+    }
+}
@@ -0,0 +1,123 @@
+#![feature(plugin, decl_macro, custom_derive)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use std::env;
+use std::path::PathBuf;
+
+use rocket::State;
+use rocket::response::NamedFile;
+
+struct FilePath(PathBuf);
+
+#[get("/file")]
+fn file(path: State<FilePath>) -> Option<NamedFile> {
+    NamedFile::open(&path.0).ok()
+}
+
+mod named_file_ranges_tests {
+    use super::*;
+    use std::fs;
+    use rocket::local::Client;
+    use rocket::http::{Status, Header};
+
+    const CONTENTS: &'static str = "0123456789abcdefghij";
+
+    fn rocket_serving(name: &str, contents: &str) -> (rocket::Rocket, PathBuf) {
+        let path = env::temp_dir().join(format!("rocket-ranges-{}.txt", name));
+        fs::write(&path, contents).unwrap();
+
+        let rocket = rocket::ignite()
+            .manage(FilePath(path.clone()))
+            .mount("/", routes![file]);
+
+        (rocket, path)
+    }
+
+    #[test]
+    fn full_response_has_validators() {
+        let (rocket, path) = rocket_serving("full", CONTENTS);
+        let client = Client::new(rocket).unwrap();
+        let mut response = client.get("/file").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert!(response.headers().get_one("ETag").is_some());
+        assert!(response.headers().get_one("Last-Modified").is_some());
+        assert_eq!(response.headers().get_one("Accept-Ranges"), Some("bytes"));
+        assert_eq!(response.body_string(), Some(CONTENTS.into()));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn byte_ranges() {
+        let (rocket, path) = rocket_serving("ranges", CONTENTS);
+        let client = Client::new(rocket).unwrap();
+        let expectations = [
+            ("bytes=0-3", "0123", "bytes 0-3/20"),
+            ("bytes=10-", "abcdefghij", "bytes 10-19/20"),
+            ("bytes=-5", "fghij", "bytes 15-19/20"),
+            ("bytes=18-100", "ij", "bytes 18-19/20"),
+        ];
+
+        for &(range, body, content_range) in &expectations {
+            let mut response = client.get("/file").header(Header::new("Range", range)).dispatch();
+            assert_eq!(response.status(), Status::PartialContent);
+            assert_eq!(response.headers().get_one("Content-Range"), Some(content_range));
+            assert_eq!(response.body_string(), Some(body.into()));
+        }
+
+        let response = client.get("/file").header(Header::new("Range", "bytes=20-")).dispatch();
+        assert_eq!(response.status(), Status::RangeNotSatisfiable);
+        assert_eq!(response.headers().get_one("Content-Range"), Some("bytes */20"));
+
+        let mut response = client.get("/file")
+            .header(Header::new("Range", "bytes=0-1,4-5"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some(CONTENTS.into()));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn if_range_restarts_changed_files() {
+        let (rocket, path) = rocket_serving("if-range", CONTENTS);
+        let client = Client::new(rocket).unwrap();
+        let response = client.get("/file").dispatch();
+        let etag = response.headers().get_one("ETag").unwrap().to_string();
+        let modified = response.headers().get_one("Last-Modified").unwrap().to_string();
+
+        // The file is unchanged: the range is honored.
+        for validator in &[&etag, &modified] {
+            let mut response = client.get("/file")
+                .header(Header::new("Range", "bytes=10-"))
+                .header(Header::new("If-Range", validator.to_string()))
+                .dispatch();
+
+            assert_eq!(response.status(), Status::PartialContent);
+            assert_eq!(response.body_string(), Some("abcdefghij".into()));
+        }
+
+        // The file changed: the whole file is sent.
+        fs::write(&path, "a new, longer version of the file").unwrap();
+        let mut response = client.get("/file")
+            .header(Header::new("Range", "bytes=10-"))
+            .header(Header::new("If-Range", etag.clone()))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some("a new, longer version of the file".into()));
+
+        // Weak tags never satisfy `If-Range`.
+        let response = client.get("/file").dispatch();
+        let etag = response.headers().get_one("ETag").unwrap().to_string();
+        let mut response = client.get("/file")
+            .header(Header::new("Range", "bytes=10-"))
+            .header(Header::new("If-Range", format!("W/{}", etag)))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert!(response.body_string().is_some());
+        fs::remove_file(path).unwrap();
+    }
+}
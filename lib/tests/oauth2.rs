@@ -0,0 +1,157 @@
+#![cfg(feature = "oauth2")]
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+extern crate base64;
+
+use rocket::State;
+use rocket::http::Cookies;
+use rocket::response::Redirect;
+use rocket::oauth2::{OAuth2, OidcUser, TokenResponse};
+
+#[get("/login")]
+fn login(oauth: State<OAuth2>, mut cookies: Cookies) -> Redirect {
+    oauth.login(&mut cookies)
+}
+
+#[get("/callback")]
+fn callback(token: TokenResponse) -> String {
+    token.access_token().to_string()
+}
+
+#[get("/me")]
+fn me(user: OidcUser) -> String {
+    format!("{} <{}>", user.subject(), user.email().unwrap_or(""))
+}
+
+#[get("/me", rank = 2)]
+fn anonymous() -> &'static str {
+    "anonymous"
+}
+
+mod oauth2_tests {
+    use std::sync::{Arc, Mutex};
+
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::Status;
+    use rocket::oauth2::{OAuth2, OAuthConfig, Transport};
+    use base64;
+
+    const ISSUER: &str = "https://accounts.example.com";
+
+    /// Answers token requests with an ID token for the last login's nonce.
+    struct MockProvider {
+        nonce: Arc<Mutex<String>>,
+    }
+
+    impl Transport for MockProvider {
+        fn post_form(&self, url: &str, form: &[(&str, &str)]) -> Result<Vec<u8>, String> {
+            assert_eq!(url, "https://accounts.example.com/token");
+            assert!(form.contains(&("grant_type", "authorization_code")));
+            assert!(form.contains(&("code", "the-code")));
+            assert!(form.iter().any(|&(k, v)| k == "code_verifier" && v.len() == 43));
+
+            let claims = format!(r#"{{"iss":"{}","aud":"rocket-app","sub":"alice",
+                "email":"alice@example.com","exp":4102444800,"nonce":"{}"}}"#,
+                ISSUER, self.nonce.lock().unwrap());
+
+            let payload = base64::encode_config(claims.as_bytes(), base64::URL_SAFE_NO_PAD);
+            let body = format!(r#"{{"access_token":"at-123","token_type":"Bearer",
+                "expires_in":3600,"id_token":"e30.{}.sig"}}"#, payload);
+
+            Ok(body.into_bytes())
+        }
+    }
+
+    fn rocket_with_provider(nonce: Arc<Mutex<String>>) -> rocket::Rocket {
+        let mut config = OAuthConfig::new("rocket-app",
+            "https://accounts.example.com/authorize",
+            "https://accounts.example.com/token",
+            "http://localhost:8000/callback");
+
+        config.issuer = Some(ISSUER.into());
+        let oauth = OAuth2::new(config).transport(MockProvider { nonce: nonce });
+        rocket::ignite()
+            .manage(oauth)
+            .mount("/", routes![super::login, super::callback, super::me, super::anonymous])
+    }
+
+    fn query_param<'a>(location: &'a str, name: &str) -> &'a str {
+        let query = location.splitn(2, '?').nth(1).expect("query");
+        query.split('&')
+            .filter_map(|pair| {
+                let mut kv = pair.splitn(2, '=');
+                match (kv.next(), kv.next()) {
+                    (Some(k), Some(v)) if k == name => Some(v),
+                    _ => None
+                }
+            })
+            .next()
+            .expect("parameter")
+    }
+
+    #[test]
+    fn login_redirects_to_provider() {
+        let nonce = Arc::new(Mutex::new(String::new()));
+        let client = Client::new(rocket_with_provider(nonce)).unwrap();
+        let response = client.get("/login").dispatch();
+        assert_eq!(response.status(), Status::SeeOther);
+
+        let location = response.headers().get_one("Location").unwrap();
+        assert!(location.starts_with("https://accounts.example.com/authorize?"));
+        assert_eq!(query_param(location, "response_type"), "code");
+        assert_eq!(query_param(location, "client_id"), "rocket-app");
+        assert_eq!(query_param(location, "scope"), "openid");
+        assert_eq!(query_param(location, "code_challenge_method"), "S256");
+        assert_eq!(query_param(location, "state").len(), 43);
+        assert_eq!(query_param(location, "nonce").len(), 43);
+    }
+
+    #[test]
+    fn callback_completes_login() {
+        let nonce = Arc::new(Mutex::new(String::new()));
+        let client = Client::new(rocket_with_provider(nonce.clone())).unwrap();
+        assert_eq!(client.get("/me").dispatch().body_string(), Some("anonymous".into()));
+
+        let response = client.get("/login").dispatch();
+        let location = response.headers().get_one("Location").unwrap();
+        *nonce.lock().unwrap() = query_param(location, "nonce").to_string();
+
+        let callback = format!("/callback?code=the-code&state={}", query_param(location, "state"));
+        let mut response = client.get(callback).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some("at-123".into()));
+
+        let mut response = client.get("/me").dispatch();
+        assert_eq!(response.body_string(), Some("alice <alice@example.com>".into()));
+    }
+
+    #[test]
+    fn callback_rejects_state_mismatch_and_replay() {
+        let nonce = Arc::new(Mutex::new(String::new()));
+        let client = Client::new(rocket_with_provider(nonce.clone())).unwrap();
+
+        let response = client.get("/callback?code=the-code&state=forged").dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+
+        client.get("/login").dispatch();
+        let response = client.get("/callback?code=the-code&state=forged").dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+
+        // The failed callback consumed the login.
+        assert_eq!(client.get("/me").dispatch().body_string(), Some("anonymous".into()));
+    }
+
+    #[test]
+    fn callback_rejects_wrong_nonce() {
+        let nonce = Arc::new(Mutex::new(String::from("not-the-nonce")));
+        let client = Client::new(rocket_with_provider(nonce)).unwrap();
+
+        let response = client.get("/login").dispatch();
+        let location = response.headers().get_one("Location").unwrap().to_string();
+        let callback = format!("/callback?code=the-code&state={}", query_param(&location, "state"));
+        assert_eq!(client.get(callback).dispatch().status(), Status::BadRequest);
+    }
+}
@@ -0,0 +1,49 @@
+#![feature(plugin, decl_macro, custom_derive)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+#[get("/archive/<year?>/<month?>")]
+fn archive(year: Option<u16>, month: Option<u8>) -> String {
+    match (year, month) {
+        (Some(year), Some(month)) => format!("{}-{}", year, month),
+        (Some(year), None) => format!("{}", year),
+        _ => "all".into()
+    }
+}
+
+#[get("/list/<page?:uint>")]
+#[default(page = "1")]
+fn list(page: usize) -> String {
+    format!("page {}", page)
+}
+
+mod optional_segments_tests {
+    use super::*;
+    use rocket::local::Client;
+    use rocket::http::Status;
+
+    fn assert_body(client: &Client, path: &str, expected: &str) {
+        let mut response = client.get(path).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some(expected.into()));
+    }
+
+    #[test]
+    fn missing_optional_segments_are_none() {
+        let client = Client::new(rocket::ignite().mount("/", routes![archive])).unwrap();
+        assert_body(&client, "/archive", "all");
+        assert_body(&client, "/archive/", "all");
+        assert_body(&client, "/archive/2018", "2018");
+        assert_body(&client, "/archive/2018/3", "2018-3");
+        assert_eq!(client.get("/archive/2018/3/1").dispatch().status(), Status::NotFound);
+    }
+
+    #[test]
+    fn missing_optional_segments_use_default() {
+        let client = Client::new(rocket::ignite().mount("/", routes![list])).unwrap();
+        assert_body(&client, "/list", "page 1");
+        assert_body(&client, "/list/7", "page 7");
+        assert_eq!(client.get("/list/seven").dispatch().status(), Status::NotFound);
+    }
+}
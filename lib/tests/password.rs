@@ -0,0 +1,148 @@
+#![cfg(feature = "password")]
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use std::net::SocketAddr;
+
+use rocket::State;
+use rocket::password::{LoginThrottle, Throttled};
+
+#[post("/login/<user>/<ok>")]
+fn login(user: String, ok: bool, remote: SocketAddr, throttle: State<LoginThrottle>)
+    -> Result<&'static str, Throttled>
+{
+    throttle.check(&user, remote.ip())?;
+    match ok {
+        true => throttle.record_success(&user, remote.ip()),
+        false => throttle.record_failure(&user, remote.ip()),
+    }
+
+    Ok("attempted")
+}
+
+mod password_tests {
+    use std::thread;
+    use std::net::IpAddr;
+    use std::time::Duration;
+
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::Status;
+    use rocket::password::{self, LoginThrottle};
+
+    #[test]
+    fn hashes_verify_and_differ() {
+        let a = password::hash("correct horse").unwrap();
+        let b = password::hash("correct horse").unwrap();
+        assert_ne!(a, b);
+        assert!(password::verify("correct horse", &a));
+        assert!(password::verify("correct horse", &b));
+        assert!(!password::verify("battery staple", &a));
+        assert!(!password::needs_rehash(&a));
+    }
+
+    #[test]
+    fn bcrypt_hashes_verify_and_need_rehash() {
+        let hash = password::hash_bcrypt("correct horse", 4).unwrap();
+        assert!(password::verify("correct horse", &hash));
+        assert!(!password::verify("battery staple", &hash));
+        assert!(password::needs_rehash(&hash));
+    }
+
+    #[test]
+    fn malformed_hashes_never_verify() {
+        assert!(!password::verify("", ""));
+        assert!(!password::verify("password", "password"));
+        assert!(!password::verify("password", "$argon2id$garbage"));
+        assert!(!password::verify("password", "$2b$garbage"));
+    }
+
+    #[test]
+    fn throttle_window_expires() {
+        let throttle = LoginThrottle::new(2, Duration::from_millis(200));
+        let ip = "192.0.2.1".parse().unwrap();
+        let other_ip = "192.0.2.2".parse().unwrap();
+
+        throttle.record_failure("alice", ip);
+        throttle.record_failure("alice", ip);
+        let retry = throttle.check("alice", ip).unwrap_err().retry_after();
+        assert!(retry <= Duration::from_millis(200));
+        assert!(throttle.check("alice", other_ip).is_ok());
+        assert!(throttle.check("bob", ip).is_ok());
+
+        thread::sleep(Duration::from_millis(250));
+        assert!(throttle.check("alice", ip).is_ok());
+    }
+
+    #[test]
+    fn successful_login_clears_failures() {
+        let throttle = LoginThrottle::new(2, Duration::from_secs(60));
+        let ip = "2001:db8::1".parse().unwrap();
+        throttle.record_failure("alice", ip);
+        throttle.record_success("alice", ip);
+        throttle.record_failure("alice", ip);
+        assert!(throttle.check("alice", ip).is_ok());
+    }
+
+    #[test]
+    fn accounts_are_throttled_across_addresses() {
+        let throttle = LoginThrottle::new(2, Duration::from_secs(60)).max_account_failures(3);
+        for i in 1..4 {
+            let ip = format!("192.0.2.{}", i).parse().unwrap();
+            assert!(throttle.check("alice", ip).is_ok());
+            throttle.record_failure("alice", ip);
+        }
+
+        assert!(throttle.check("alice", "192.0.2.100".parse().unwrap()).is_err());
+        assert!(throttle.check("bob", "192.0.2.1".parse().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn addresses_are_throttled_across_accounts() {
+        let throttle = LoginThrottle::new(2, Duration::from_secs(60)).max_ip_failures(3);
+        let ip = "192.0.2.1".parse().unwrap();
+        for account in &["alice", "bob", "carol"] {
+            assert!(throttle.check(account, ip).is_ok());
+            throttle.record_failure(account, ip);
+        }
+
+        assert!(throttle.check("dave", ip).is_err());
+        assert!(throttle.check("dave", "192.0.2.2".parse().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn oldest_failures_are_evicted_at_capacity() {
+        let throttle = LoginThrottle::new(1, Duration::from_secs(60)).capacity(8);
+        let ip = |i: usize| format!("10.0.{}.{}", i / 256, i % 256).parse::<IpAddr>().unwrap();
+        for i in 0..1000 {
+            throttle.record_failure(&format!("user{}", i), ip(i));
+        }
+
+        assert!(throttle.check("user999", ip(999)).is_err());
+        assert!(throttle.check("user0", ip(0)).is_ok());
+    }
+
+    #[test]
+    fn throttled_logins_are_refused() {
+        let rocket = rocket::ignite()
+            .manage(LoginThrottle::new(3, Duration::from_secs(60)))
+            .mount("/", routes![super::login]);
+
+        let client = Client::new(rocket).unwrap();
+        let remote = "198.51.100.4:4000".parse().unwrap();
+        for _ in 0..3 {
+            let response = client.post("/login/alice/false").remote(remote).dispatch();
+            assert_eq!(response.status(), Status::Ok);
+        }
+
+        let response = client.post("/login/alice/true").remote(remote).dispatch();
+        assert_eq!(response.status(), Status::TooManyRequests);
+        let retry: u64 = response.headers().get_one("Retry-After").unwrap().parse().unwrap();
+        assert!(retry > 0 && retry <= 60);
+
+        let response = client.post("/login/bob/true").remote(remote).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+    }
+}
@@ -0,0 +1,33 @@
+#![cfg(feature = "plugins")]
+
+extern crate rocket;
+
+mod plugin_loader_tests {
+    use rocket;
+    use rocket::error::LaunchErrorKind;
+    use rocket::local::Client;
+    use rocket::plugin::PluginLoader;
+
+    #[test]
+    fn missing_plugins_fail_launch() {
+        let loader = PluginLoader::new().load("/nonexistent/libmissing.so");
+        let rocket = rocket::ignite().attach(loader);
+
+        let error = Client::new(rocket).err().expect("launch should fail");
+        match *error.kind() {
+            LaunchErrorKind::FailedFairings(ref failures) => {
+                assert_eq!(failures.len(), 1);
+                assert_eq!(failures[0].fairing(), "Plugin Loader");
+                assert_eq!(failures[0].message(),
+                    "failed to load plugin /nonexistent/libmissing.so");
+            }
+            ref kind => panic!("unexpected launch error: {}", kind)
+        }
+    }
+
+    #[test]
+    fn no_plugins_is_a_no_op() {
+        let rocket = rocket::ignite().attach(PluginLoader::new());
+        assert!(Client::new(rocket).is_ok());
+    }
+}
@@ -0,0 +1,75 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::request::{Precondition, PreconditionError};
+
+#[put("/doc", data = "<doc>")]
+fn update(doc: String, pre: Precondition) -> Result<String, PreconditionError> {
+    pre.require()?;
+    pre.evaluate(Some("v2"), None)?;
+    Ok(doc)
+}
+
+mod precondition_tests {
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::{Status, Header};
+
+    #[test]
+    fn unconditional_write_is_rejected() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::update])).unwrap();
+        let response = client.put("/doc").body("new").dispatch();
+        assert_eq!(response.status(), Status::PreconditionRequired);
+    }
+
+    #[test]
+    fn matching_etag_succeeds() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::update])).unwrap();
+        let mut response = client.put("/doc")
+            .header(Header::new("If-Match", "\"v1\", \"v2\""))
+            .body("new")
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some("new".into()));
+
+        let response = client.put("/doc")
+            .header(Header::new("If-Match", "*"))
+            .body("new")
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    #[test]
+    fn stale_etag_fails() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::update])).unwrap();
+        let response = client.put("/doc")
+            .header(Header::new("If-Match", "\"v1\""))
+            .body("new")
+            .dispatch();
+
+        assert_eq!(response.status(), Status::PreconditionFailed);
+
+        // `If-Match` uses the strong comparison: weak tags never match.
+        let response = client.put("/doc")
+            .header(Header::new("If-Match", "W/\"v2\""))
+            .body("new")
+            .dispatch();
+
+        assert_eq!(response.status(), Status::PreconditionFailed);
+    }
+
+    #[test]
+    fn if_none_match_any_fails_when_resource_exists() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::update])).unwrap();
+        let response = client.put("/doc")
+            .header(Header::new("If-None-Match", "*"))
+            .body("new")
+            .dispatch();
+
+        assert_eq!(response.status(), Status::PreconditionFailed);
+    }
+}
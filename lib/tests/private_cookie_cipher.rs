@@ -0,0 +1,86 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::http::{Cookie, Cookies};
+
+#[get("/")]
+fn read(mut cookies: Cookies) -> Option<String> {
+    cookies.get_private("session").map(|cookie| cookie.value().to_string())
+}
+
+#[post("/")]
+fn write(mut cookies: Cookies) {
+    cookies.add_private(Cookie::new("session", "alice"));
+}
+
+mod private_cookie_cipher_tests {
+    use rocket;
+    use rocket::config::{Config, Environment};
+    use rocket::local::{Client, LocalResponse};
+    use rocket::http::{Cookie, CookieCipher, Status};
+
+    const KEY: &str = "TpUiXK2d/v5DFxJnWL12suJKPExKR8h9zd/o+E7SU+0=";
+
+    fn rocket_with_cipher(cipher: CookieCipher) -> rocket::Rocket {
+        let config = Config::build(Environment::Development)
+            .secret_key(KEY)
+            .private_cookie_cipher(cipher)
+            .unwrap();
+
+        rocket::custom(config, false).mount("/", routes![super::read, super::write])
+    }
+
+    fn session(response: &LocalResponse) -> Option<String> {
+        let header = response.headers().get_one("Set-Cookie")?;
+        Cookie::parse_encoded(header).ok().map(|cookie| cookie.value().to_string())
+    }
+
+    #[test]
+    fn cookies_are_versioned_by_cipher() {
+        for &(cipher, version) in &[(CookieCipher::Aes256Gcm, "1."),
+                                    (CookieCipher::ChaCha20Poly1305, "2.")] {
+            let client = Client::untracked(rocket_with_cipher(cipher)).unwrap();
+            let sealed = session(&client.post("/").dispatch()).unwrap();
+            assert!(sealed.starts_with(version));
+            assert!(!sealed.contains("alice"));
+
+            let mut response = client.get("/").cookie(Cookie::new("session", sealed)).dispatch();
+            assert_eq!(response.body_string(), Some("alice".into()));
+            assert_eq!(session(&response), None);
+        }
+    }
+
+    #[test]
+    fn other_ciphers_are_upgraded_on_read() {
+        let client = Client::untracked(rocket_with_cipher(CookieCipher::Aes256Gcm)).unwrap();
+        let sealed = session(&client.post("/").dispatch()).unwrap();
+
+        let client = Client::untracked(rocket_with_cipher(CookieCipher::ChaCha20Poly1305)).unwrap();
+        let mut response = client.get("/").cookie(Cookie::new("session", sealed)).dispatch();
+        assert_eq!(response.body_string(), Some("alice".into()));
+
+        let upgraded = session(&response).unwrap();
+        assert!(upgraded.starts_with("2."));
+
+        let mut response = client.get("/").cookie(Cookie::new("session", upgraded)).dispatch();
+        assert_eq!(response.body_string(), Some("alice".into()));
+        assert_eq!(session(&response), None);
+    }
+
+    #[test]
+    fn tampered_cookies_are_rejected() {
+        let client = Client::untracked(rocket_with_cipher(CookieCipher::ChaCha20Poly1305)).unwrap();
+        let sealed = session(&client.post("/").dispatch()).unwrap();
+
+        // Claiming another version doesn't get the value opened with AES.
+        let relabeled = format!("1.{}", &sealed[2..]);
+        let response = client.get("/").cookie(Cookie::new("session", relabeled)).dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+
+        let truncated = sealed[..(sealed.len() - 4)].to_string();
+        let response = client.get("/").cookie(Cookie::new("session", truncated)).dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+    }
+}
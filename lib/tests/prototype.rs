@@ -0,0 +1,79 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rocket::State;
+
+#[derive(Default)]
+struct Counter(AtomicUsize);
+
+#[get("/count")]
+fn count(counter: State<Counter>) -> String {
+    (counter.0.fetch_add(1, Ordering::Relaxed) + 1).to_string()
+}
+
+#[catch(404)]
+fn not_found() -> &'static str {
+    "nothing here"
+}
+
+mod prototype_tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use rocket;
+    use rocket::config::{Environment, Config};
+    use rocket::local::Prototype;
+    use rocket::http::Status;
+
+    fn prototype() -> Prototype {
+        let config = Config::build(Environment::Staging).server_name("Proto").unwrap();
+        let rocket = rocket::custom(config, false)
+            .mount("/", routes![super::count])
+            .catch(catchers![super::not_found]);
+
+        Prototype::new(rocket).on_instance(|rocket| rocket.manage(super::Counter::default()))
+    }
+
+    #[test]
+    fn instances_have_fresh_state() {
+        let prototype = prototype();
+        let first = prototype.client().unwrap();
+        let second = prototype.clone().client().unwrap();
+
+        assert_eq!(first.get("/count").dispatch().body_string(), Some("1".into()));
+        assert_eq!(first.get("/count").dispatch().body_string(), Some("2".into()));
+        assert_eq!(second.get("/count").dispatch().body_string(), Some("1".into()));
+    }
+
+    #[test]
+    fn instances_keep_config_routes_and_catchers() {
+        let client = prototype().untracked_client().unwrap();
+        assert_eq!(client.rocket().routes().count(), 1);
+
+        let mut response = client.get("/missing").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        assert_eq!(response.headers().get_one("Server"), Some("Proto"));
+        assert_eq!(response.body_string(), Some("nothing here".into()));
+    }
+
+    #[test]
+    fn constructors_run_per_instance() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        let prototype = prototype().on_instance(move |rocket| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            rocket
+        });
+
+        assert_eq!(runs.load(Ordering::Relaxed), 0);
+        for _ in 0..3 {
+            prototype.rocket();
+        }
+
+        assert_eq!(runs.load(Ordering::Relaxed), 3);
+    }
+}
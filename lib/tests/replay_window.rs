@@ -0,0 +1,113 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::replay::{Fresh, ReplayWindow};
+
+struct Deliveries;
+
+impl ReplayWindow for Deliveries {
+    const SECONDS: u64 = 60;
+    const NONCE_HEADER: &'static str = "X-Delivery-Id";
+    const TIMESTAMP_HEADER: &'static str = "X-Delivery-Time";
+}
+
+#[post("/signed")]
+fn signed(fresh: Fresh) -> String {
+    fresh.nonce().to_string()
+}
+
+#[post("/webhook")]
+fn webhook(fresh: Fresh<Deliveries>) -> String {
+    fresh.nonce().to_string()
+}
+
+mod replay_window_tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::{Header, Status};
+    use rocket::replay::ReplayCache;
+
+    fn rocket_with_cache() -> rocket::Rocket {
+        rocket::ignite()
+            .manage(ReplayCache::default())
+            .mount("/", routes![super::signed, super::webhook])
+    }
+
+    fn now() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+    }
+
+    fn signed(client: &Client, nonce: &str, timestamp: u64) -> Status {
+        client.post("/signed")
+            .header(Header::new("X-Nonce", nonce.to_string()))
+            .header(Header::new("X-Timestamp", timestamp.to_string()))
+            .dispatch()
+            .status()
+    }
+
+    #[test]
+    fn fresh_requests_are_accepted_once() {
+        let client = Client::new(rocket_with_cache()).unwrap();
+        let mut response = client.post("/signed")
+            .header(Header::new("X-Nonce", "a1b2c3"))
+            .header(Header::new("X-Timestamp", now().to_string()))
+            .dispatch();
+
+        assert_eq!(response.body_string(), Some("a1b2c3".into()));
+        assert_eq!(signed(&client, "a1b2c3", now()), Status::Unauthorized);
+        assert_eq!(signed(&client, "d4e5f6", now()), Status::Ok);
+    }
+
+    #[test]
+    fn stale_and_future_timestamps_are_rejected() {
+        let client = Client::new(rocket_with_cache()).unwrap();
+        assert_eq!(signed(&client, "old", now() - 301), Status::Unauthorized);
+        assert_eq!(signed(&client, "future", now() + 301), Status::Unauthorized);
+        assert_eq!(signed(&client, "skewed", now() + 30), Status::Ok);
+    }
+
+    #[test]
+    fn missing_and_malformed_values_are_bad_requests() {
+        let client = Client::new(rocket_with_cache()).unwrap();
+        let response = client.post("/signed").header(Header::new("X-Nonce", "abc")).dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let response = client.post("/signed")
+            .header(Header::new("X-Nonce", "abc"))
+            .header(Header::new("X-Timestamp", "yesterday"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::BadRequest);
+        assert_eq!(signed(&client, "", now()), Status::BadRequest);
+        assert_eq!(signed(&client, &"n".repeat(257), now()), Status::BadRequest);
+    }
+
+    #[test]
+    fn windows_are_configured_per_route() {
+        let client = Client::new(rocket_with_cache()).unwrap();
+        let deliver = |id: &str, timestamp: u64| {
+            client.post("/webhook")
+                .header(Header::new("X-Delivery-Id", id.to_string()))
+                .header(Header::new("X-Delivery-Time", timestamp.to_string()))
+                .dispatch()
+                .status()
+        };
+
+        assert_eq!(deliver("7", now()), Status::Ok);
+        assert_eq!(deliver("7", now()), Status::Unauthorized);
+        assert_eq!(deliver("8", now() - 120), Status::Unauthorized);
+
+        // Accepted by the default five minute window.
+        assert_eq!(signed(&client, "8", now() - 120), Status::Ok);
+    }
+
+    #[test]
+    fn unmanaged_cache_is_an_error() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::signed])).unwrap();
+        assert_eq!(signed(&client, "a1b2c3", now()), Status::InternalServerError);
+    }
+}
@@ -0,0 +1,74 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use std::io::Cursor;
+
+use rocket::response::Stream;
+
+#[get("/")]
+fn index() -> &'static str {
+    "hello"
+}
+
+#[get("/stream")]
+fn stream() -> Stream<Cursor<&'static [u8]>> {
+    Stream::from(Cursor::new(&b"hello"[..]))
+}
+
+mod response_digest_tests {
+    use rocket;
+    use rocket::config::{Environment, Config};
+    use rocket::data::ChecksumAlgorithm;
+    use rocket::local::Client;
+
+    #[test]
+    fn sized_bodies_are_digested() {
+        let config = Config::build(Environment::Development)
+            .response_digest(Some(ChecksumAlgorithm::Sha256))
+            .unwrap();
+
+        let rocket = rocket::custom(config, true).mount("/", routes![super::index]);
+        let client = Client::new(rocket).unwrap();
+
+        let mut response = client.get("/").dispatch();
+        let digest = "LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ=";
+        assert_eq!(response.headers().get_one("Digest"), Some(&*format!("SHA-256={}", digest)));
+        assert_eq!(response.headers().get_one("Content-Digest"),
+                   Some(&*format!("sha-256=:{}:", digest)));
+        assert_eq!(response.body_string(), Some("hello".into()));
+
+        let config = Config::build(Environment::Development)
+            .response_digest(Some(ChecksumAlgorithm::Md5))
+            .unwrap();
+
+        let rocket = rocket::custom(config, true).mount("/", routes![super::index]);
+        let client = Client::new(rocket).unwrap();
+
+        let response = client.head("/").dispatch();
+        assert_eq!(response.headers().get_one("Digest"), Some("MD5=XUFAKrxLKna5cZ2REBfFkg=="));
+    }
+
+    #[test]
+    fn streamed_and_undigested_bodies() {
+        let config = Config::build(Environment::Development)
+            .response_digest(Some(ChecksumAlgorithm::Sha256))
+            .unwrap();
+
+        let rocket = rocket::custom(config, true).mount("/", routes![super::stream]);
+        let client = Client::new(rocket).unwrap();
+
+        let mut response = client.get("/stream").dispatch();
+        assert!(response.headers().get_one("Digest").is_none());
+        assert_eq!(response.body_string(), Some("hello".into()));
+
+        let config = Config::build(Environment::Development).unwrap();
+        let rocket = rocket::custom(config, true).mount("/", routes![super::index]);
+        let client = Client::new(rocket).unwrap();
+
+        let response = client.get("/").dispatch();
+        assert!(response.headers().get_one("Digest").is_none());
+        assert!(response.headers().get_one("Content-Digest").is_none());
+    }
+}
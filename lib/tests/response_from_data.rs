@@ -0,0 +1,45 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::{Data, Response};
+use rocket::http::ContentType;
+
+#[post("/echo", data = "<data>")]
+fn echo(content_type: Option<ContentType>, data: Data) -> Response<'static> {
+    let mut response = Response::from_data(data);
+    if let Some(content_type) = content_type {
+        response.set_header(content_type);
+    }
+
+    response
+}
+
+mod response_from_data_tests {
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::{ContentType, Status};
+
+    #[test]
+    fn request_bodies_are_piped_into_responses() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::echo])).unwrap();
+        let body: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+        let mut response = client.post("/echo")
+            .header(ContentType::Binary)
+            .body(&body)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::Binary));
+        assert_eq!(response.body_bytes(), Some(body));
+    }
+
+    #[test]
+    fn empty_request_bodies_yield_empty_responses() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::echo])).unwrap();
+        let mut response = client.post("/echo").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_bytes(), Some(vec![]));
+    }
+}
@@ -0,0 +1,71 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use std::io::Cursor;
+
+use rocket::response::Stream;
+
+#[get("/small")]
+fn small() -> &'static str {
+    "tiny"
+}
+
+#[get("/large")]
+fn large() -> String {
+    "x".repeat(64)
+}
+
+#[get("/large/allowed", response_limit = 128)]
+fn large_allowed() -> String {
+    "x".repeat(64)
+}
+
+#[get("/stream")]
+fn stream() -> Stream<Cursor<Vec<u8>>> {
+    Stream::chunked(Cursor::new(vec![b'x'; 64]), 4)
+}
+
+mod response_limits_tests {
+    use rocket;
+    use rocket::config::{Environment, Config, Limits};
+    use rocket::local::Client;
+    use rocket::http::Status;
+
+    fn rocket() -> rocket::Rocket {
+        let config = Config::build(Environment::Development)
+            .limits(Limits::new().limit("response", 16))
+            .unwrap();
+
+        let routes = routes![super::small, super::large, super::large_allowed, super::stream];
+        rocket::custom(config, true).mount("/", routes)
+    }
+
+    #[test]
+    fn sized_bodies_are_limited() {
+        let client = Client::new(rocket()).unwrap();
+        let mut response = client.get("/small").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some("tiny".into()));
+
+        let response = client.get("/large").dispatch();
+        assert_eq!(response.status(), Status::InternalServerError);
+    }
+
+    #[test]
+    fn route_limit_overrides_config() {
+        let client = Client::new(rocket()).unwrap();
+        let mut response = client.get("/large/allowed").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some("x".repeat(64)));
+    }
+
+    #[test]
+    fn streamed_bodies_are_cut_off() {
+        let client = Client::new(rocket()).unwrap();
+        let mut response = client.get("/stream").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert!(response.body_string().is_none());
+    }
+}
@@ -0,0 +1,72 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+#[get("/v1/users", deprecated_since = "2018-06-01", sunset = "2025-01-01",
+      successor = "/v2/users")]
+fn users_v1() -> &'static str {
+    "[\"bob\"]"
+}
+
+#[get("/v1/items", sunset = "2019-06-01")]
+fn items_v1() -> &'static str {
+    "[]"
+}
+
+#[get("/v2/users")]
+fn users_v2() -> &'static str {
+    "{\"users\": [\"bob\"]}"
+}
+
+mod route_deprecation_tests {
+    use rocket;
+    use rocket::local::Client;
+    use rocket::versioning::Deprecation;
+
+    fn rocket() -> rocket::Rocket {
+        rocket::ignite().mount("/", routes![super::users_v1, super::items_v1, super::users_v2])
+    }
+
+    #[test]
+    fn deprecated_routes_send_deprecation_headers() {
+        let client = Client::new(rocket()).unwrap();
+        let response = client.get("/v1/users").dispatch();
+        let headers = response.headers();
+        assert_eq!(headers.get_one("Deprecation"), Some("Fri, 01 Jun 2018 00:00:00 GMT"));
+        assert_eq!(headers.get_one("Sunset"), Some("Wed, 01 Jan 2025 00:00:00 GMT"));
+        assert_eq!(headers.get_one("Link"), Some("</v2/users>; rel=\"successor-version\""));
+
+        let response = client.get("/v1/items").dispatch();
+        let headers = response.headers();
+        assert_eq!(headers.get_one("Deprecation"), Some("true"));
+        assert_eq!(headers.get_one("Sunset"), Some("Sat, 01 Jun 2019 00:00:00 GMT"));
+        assert!(!headers.contains("Link"));
+    }
+
+    #[test]
+    fn current_routes_send_no_deprecation_headers() {
+        let client = Client::new(rocket()).unwrap();
+        let response = client.get("/v2/users").dispatch();
+        assert!(!response.headers().contains("Deprecation"));
+        assert!(!response.headers().contains("Sunset"));
+        assert!(!response.headers().contains("Link"));
+    }
+
+    #[test]
+    fn deprecations_are_summarized() {
+        let summary = rocket().launch_summary();
+        let deprecation = |uri: &str| {
+            summary.routes.iter().find(|r| r.uri == uri).unwrap().deprecation.clone()
+        };
+
+        assert_eq!(deprecation("/v1/users"), Some(Deprecation {
+            since: Some("2018-06-01".into()),
+            sunset: Some("2025-01-01".into()),
+            successor: Some("/v2/users".into()),
+        }));
+
+        assert_eq!(deprecation("/v2/users"), None);
+        assert!(summary.to_string().contains("(sunset 2019-06-01)"));
+    }
+}
@@ -0,0 +1,87 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use std::io::{self, Read};
+use std::thread;
+use std::time::Duration;
+
+use rocket::request::CancelToken;
+use rocket::response::Stream;
+
+// A reader that takes 10ms to produce each byte.
+struct Slow(usize);
+
+impl Read for Slow {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.0 == 0 || buf.is_empty() {
+            return Ok(0);
+        }
+
+        thread::sleep(Duration::from_millis(10));
+        self.0 -= 1;
+        buf[0] = b'x';
+        Ok(1)
+    }
+}
+
+#[get("/fast", timeout = 10000)]
+fn fast(token: CancelToken) -> String {
+    format!("{} {}", token.is_cancelled(), token.remaining().is_some())
+}
+
+#[get("/slow", timeout = 20)]
+fn slow(token: CancelToken) -> &'static str {
+    while !token.is_cancelled() {
+        thread::sleep(Duration::from_millis(5));
+    }
+
+    "too late"
+}
+
+#[get("/stream", timeout = 50)]
+fn stream() -> Stream<Slow> {
+    Stream::chunked(Slow(100), 1)
+}
+
+#[get("/unbounded")]
+fn unbounded(token: CancelToken) -> String {
+    format!("{} {:?}", token.is_cancelled(), token.remaining())
+}
+
+mod route_timeouts_tests {
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::Status;
+
+    #[test]
+    fn handlers_within_the_timeout_respond() {
+        let routes = routes![super::fast, super::unbounded];
+        let client = Client::new(rocket::ignite().mount("/", routes)).unwrap();
+        let mut response = client.get("/fast").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some("false true".into()));
+
+        let mut response = client.get("/unbounded").dispatch();
+        assert_eq!(response.body_string(), Some("false None".into()));
+        assert_eq!(client.rocket().metrics().get("requests.timed_out"), None);
+    }
+
+    #[test]
+    fn handlers_past_the_timeout_fail() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::slow])).unwrap();
+        let response = client.get("/slow").dispatch();
+        assert_eq!(response.status(), Status::ServiceUnavailable);
+        assert_eq!(client.rocket().metrics().get("requests.timed_out"), Some(1));
+    }
+
+    #[test]
+    fn streamed_bodies_are_cut_off_at_the_timeout() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::stream])).unwrap();
+        let mut response = client.get("/stream").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_bytes(), None);
+        assert_eq!(client.rocket().metrics().get("requests.timed_out"), Some(1));
+    }
+}
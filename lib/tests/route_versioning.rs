@@ -0,0 +1,96 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::Request;
+use rocket::request::{FromRequest, Outcome};
+
+struct ApiVersion(Option<String>);
+
+impl<'a, 'r> FromRequest<'a, 'r> for ApiVersion {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> Outcome<Self, ()> {
+        Outcome::Success(ApiVersion(request.api_version().map(|v| v.to_string())))
+    }
+}
+
+#[get("/users", version = "v1")]
+fn users_v1() -> &'static str {
+    "users v1"
+}
+
+#[get("/users", version = "v2")]
+fn users_v2() -> &'static str {
+    "users v2"
+}
+
+#[get("/status")]
+fn status(version: ApiVersion) -> String {
+    format!("status {}", version.0.unwrap_or("none".into()))
+}
+
+mod route_versioning_tests {
+    use rocket;
+    use rocket::local::Client;
+    use rocket::http::{Status, Header};
+    use rocket::versioning::Versioning;
+
+    fn rocket_with_versioning(versioning: Versioning) -> rocket::Rocket {
+        rocket::ignite()
+            .mount("/", routes![super::users_v1, super::users_v2, super::status])
+            .versioning(versioning)
+    }
+
+    fn body(client: &Client, path: &str) -> Option<String> {
+        client.get(path).dispatch().body_string()
+    }
+
+    #[test]
+    fn path_prefix_negotiation() {
+        let client = Client::new(rocket_with_versioning(Versioning::path_prefix())).unwrap();
+        assert_eq!(body(&client, "/v1/users"), Some("users v1".into()));
+        assert_eq!(body(&client, "/v2/users"), Some("users v2".into()));
+        assert_eq!(body(&client, "/v2/status"), Some("status v2".into()));
+        assert_eq!(body(&client, "/status"), Some("status none".into()));
+        assert_eq!(client.get("/users").dispatch().status(), Status::NotFound);
+        assert_eq!(client.get("/v3/users").dispatch().status(), Status::NotFound);
+    }
+
+    #[test]
+    fn header_negotiation_with_default() {
+        let versioning = Versioning::header("Api-Version").default_version("v2");
+        let client = Client::new(rocket_with_versioning(versioning)).unwrap();
+        assert_eq!(body(&client, "/users"), Some("users v2".into()));
+
+        let mut response = client.get("/users")
+            .header(Header::new("Api-Version", "v1"))
+            .dispatch();
+
+        assert_eq!(response.body_string(), Some("users v1".into()));
+    }
+
+    #[test]
+    fn query_negotiation() {
+        let client = Client::new(rocket_with_versioning(Versioning::query("api-version"))).unwrap();
+        assert_eq!(body(&client, "/users?api-version=v1"), Some("users v1".into()));
+        assert_eq!(body(&client, "/users?a=b&api-version=v2"), Some("users v2".into()));
+        assert_eq!(client.get("/users").dispatch().status(), Status::NotFound);
+    }
+
+    #[test]
+    fn deprecated_versions() {
+        let date = "Sat, 01 Jun 2019 00:00:00 GMT";
+        let versioning = Versioning::path_prefix().sunset("v1", date);
+        let client = Client::new(rocket_with_versioning(versioning)).unwrap();
+
+        let response = client.get("/v1/users").dispatch();
+        assert_eq!(response.headers().get_one("Deprecation"), Some("true"));
+        assert_eq!(response.headers().get_one("Sunset"), Some(date));
+
+        let response = client.get("/v2/users").dispatch();
+        assert!(response.headers().get_one("Deprecation").is_none());
+        assert!(response.headers().get_one("Sunset").is_none());
+    }
+}
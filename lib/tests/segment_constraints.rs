@@ -0,0 +1,68 @@
+#![feature(plugin, decl_macro, custom_derive)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+#[get("/users/<id:int>")]
+fn user_by_id(id: i8) -> String {
+    format!("id: {}", id)
+}
+
+#[get("/users/<name:alpha>")]
+fn user_by_name(name: String) -> String {
+    format!("name: {}", name)
+}
+
+#[get("/posts/<year:[0-9]{4}>/<slug:[a-z0-9-]+>")]
+fn post(year: u16, slug: String) -> String {
+    format!("{}: {}", year, slug)
+}
+
+#[get("/posts/<rest>/<slug>", rank = 2)]
+fn fallback(rest: String, slug: String) -> String {
+    format!("fallback: {}/{}", rest, slug)
+}
+
+mod segment_constraints_tests {
+    use super::*;
+    use rocket::local::Client;
+    use rocket::http::Status;
+
+    fn assert_body(client: &Client, path: &str, expected: &str) {
+        let mut response = client.get(path).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some(expected.into()));
+    }
+
+    #[test]
+    fn disjoint_constraints_select_route() {
+        let routes = routes![user_by_id, user_by_name];
+        let client = Client::new(rocket::ignite().mount("/", routes)).unwrap();
+        assert_body(&client, "/users/10", "id: 10");
+        assert_body(&client, "/users/-7", "id: -7");
+        assert_body(&client, "/users/bob", "name: bob");
+    }
+
+    #[test]
+    fn unmatched_constraints_are_not_found() {
+        let routes = routes![user_by_id, user_by_name];
+        let client = Client::new(rocket::ignite().mount("/", routes)).unwrap();
+        assert_eq!(client.get("/users/bob_1").dispatch().status(), Status::NotFound);
+        assert_eq!(client.get("/users/1.5").dispatch().status(), Status::NotFound);
+    }
+
+    #[test]
+    fn constrained_parse_failure_is_bad_request() {
+        let routes = routes![user_by_id, user_by_name];
+        let client = Client::new(rocket::ignite().mount("/", routes)).unwrap();
+        assert_eq!(client.get("/users/1000").dispatch().status(), Status::BadRequest);
+    }
+
+    #[test]
+    fn class_constraints() {
+        let client = Client::new(rocket::ignite().mount("/", routes![post, fallback])).unwrap();
+        assert_body(&client, "/posts/2018/hello-world", "2018: hello-world");
+        assert_body(&client, "/posts/18/hello-world", "fallback: 18/hello-world");
+        assert_body(&client, "/posts/2018/Hello", "fallback: 2018/Hello");
+    }
+}
@@ -0,0 +1,64 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use std::io::Cursor;
+
+use rocket::response::Stream;
+
+#[get("/")]
+fn index() -> &'static str {
+    "Hello, world!"
+}
+
+#[get("/stream")]
+fn stream() -> Stream<Cursor<&'static str>> {
+    Stream::chunked(Cursor::new("Hello, stream!"), 4)
+}
+
+mod server_name_tests {
+    use rocket;
+    use rocket::config::{Environment, Config};
+    use rocket::local::Client;
+    use rocket::http::Status;
+
+    #[test]
+    fn default_server_name() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::index])).unwrap();
+        let response = client.get("/").dispatch();
+        assert_eq!(response.headers().get_one("Server"), Some("Rocket"));
+    }
+
+    #[test]
+    fn custom_server_name() {
+        let config = Config::build(Environment::Development)
+            .server_name(Some("MyApp"))
+            .unwrap();
+
+        let rocket = rocket::custom(config, true).mount("/", routes![super::index, super::stream]);
+        let client = Client::new(rocket).unwrap();
+        for path in &["/", "/stream", "/missing"] {
+            let response = client.get(*path).dispatch();
+            let servers: Vec<_> = response.headers().get("Server").collect();
+            assert_eq!(servers, vec!["MyApp"]);
+        }
+
+        let response = client.get("/missing").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[test]
+    fn omitted_server_name() {
+        let config = Config::build(Environment::Development)
+            .server_name(None)
+            .unwrap();
+
+        let rocket = rocket::custom(config, true).mount("/", routes![super::index, super::stream]);
+        let client = Client::new(rocket).unwrap();
+        for path in &["/", "/stream", "/missing"] {
+            let response = client.get(*path).dispatch();
+            assert!(response.headers().get_one("Server").is_none());
+        }
+    }
+}
@@ -0,0 +1,105 @@
+#![feature(plugin, decl_macro, custom_derive)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::request::Form;
+
+#[derive(FromForm)]
+struct Simple {
+    value: String
+}
+
+#[post("/string", data = "<body>")]
+fn string(body: String) -> String {
+    body
+}
+
+#[post("/form", data = "<form>")]
+fn form(form: Form<Simple>) -> String {
+    form.into_inner().value
+}
+
+mod text_encoding_tests {
+    use rocket;
+    use rocket::config::{Environment, Config};
+    use rocket::data::TextEncoding;
+    use rocket::local::Client;
+    use rocket::http::{Status, ContentType};
+
+    fn rocket_with_encoding(encoding: Option<TextEncoding>) -> rocket::Rocket {
+        let config = Config::build(Environment::Development)
+            .text_encoding(encoding)
+            .unwrap();
+
+        rocket::custom(config, true).mount("/", routes![super::string, super::form])
+    }
+
+    fn latin1(subtype: &str) -> ContentType {
+        ContentType::with_params("text", subtype, ("charset", "ISO-8859-1"))
+    }
+
+    #[test]
+    fn valid_utf8_is_accepted() {
+        for encoding in vec![None, Some(TextEncoding::Strict), Some(TextEncoding::Lossy)] {
+            let client = Client::new(rocket_with_encoding(encoding)).unwrap();
+            let mut response = client.post("/string")
+                .header(ContentType::with_params("text", "plain", ("charset", "UTF-8")))
+                .body("héllo")
+                .dispatch();
+
+            assert_eq!(response.body_string(), Some("héllo".into()));
+
+            let mut response = client.post("/form")
+                .header(ContentType::Form)
+                .body("value=h%C3%A9llo")
+                .dispatch();
+
+            assert_eq!(response.body_string(), Some("héllo".into()));
+        }
+    }
+
+    #[test]
+    fn invalid_utf8_is_rejected() {
+        for encoding in vec![None, Some(TextEncoding::Strict)] {
+            let client = Client::new(rocket_with_encoding(encoding)).unwrap();
+            let response = client.post("/string").body(b"h\xe9llo").dispatch();
+            assert_eq!(response.status(), Status::BadRequest);
+
+            let response = client.post("/form")
+                .header(ContentType::Form)
+                .body(b"value=h\xe9llo")
+                .dispatch();
+
+            assert_eq!(response.status(), Status::BadRequest);
+        }
+    }
+
+    #[test]
+    fn invalid_utf8_is_replaced_when_lossy() {
+        let client = Client::new(rocket_with_encoding(Some(TextEncoding::Lossy))).unwrap();
+        let mut response = client.post("/string").body(b"h\xe9llo").dispatch();
+        assert_eq!(response.body_string(), Some("h\u{FFFD}llo".into()));
+    }
+
+    #[test]
+    fn other_charsets_are_unsupported() {
+        let client = Client::new(rocket_with_encoding(Some(TextEncoding::Lossy))).unwrap();
+        let response = client.post("/string").header(latin1("plain")).body(b"h\xe9llo").dispatch();
+        assert_eq!(response.status(), Status::UnsupportedMediaType);
+
+        let mut response = client.post("/string")
+            .header(ContentType::with_params("text", "plain", ("charset", "us-ascii")))
+            .body("hello")
+            .dispatch();
+
+        assert_eq!(response.body_string(), Some("hello".into()));
+    }
+
+    #[test]
+    fn charset_is_ignored_by_default() {
+        let client = Client::new(rocket_with_encoding(None)).unwrap();
+        let mut response = client.post("/string").header(latin1("plain")).body("hello").dispatch();
+        assert_eq!(response.body_string(), Some("hello".into()));
+    }
+}
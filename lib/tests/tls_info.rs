@@ -0,0 +1,69 @@
+#![cfg(feature = "tls")]
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use rocket::Request;
+use rocket::response::Redirect;
+
+struct Session(String);
+
+impl<'a, 'r> rocket::request::FromRequest<'a, 'r> for Session {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> rocket::request::Outcome<Self, ()> {
+        let session = match request.tls_info() {
+            Some(tls) => format!("{} {} {} {}", tls.protocol_version, tls.cipher_suite,
+                                 tls.sni_hostname.as_ref().map_or("-", |name| &**name),
+                                 tls.resumed),
+            None => "plaintext".to_string()
+        };
+
+        rocket::Outcome::Success(Session(session))
+    }
+}
+
+#[get("/")]
+fn session(session: Session) -> String {
+    session.0
+}
+
+#[get("/moved")]
+fn moved() -> Redirect {
+    Redirect::to("/")
+}
+
+mod tls_info_tests {
+    use rocket;
+    use rocket::local::Client;
+    use rocket::tls::TlsInfo;
+
+    fn info() -> TlsInfo {
+        TlsInfo {
+            protocol_version: "TLSv1.2".into(),
+            cipher_suite: "TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384".into(),
+            sni_hostname: Some("api.rocket.rs".into()),
+            resumed: true,
+        }
+    }
+
+    #[test]
+    fn session_details_are_exposed() {
+        let client = Client::new(rocket::ignite().mount("/", routes![super::session])).unwrap();
+        let mut response = client.get("/").dispatch();
+        assert_eq!(response.body_string(), Some("plaintext".into()));
+
+        let mut response = client.get("/").tls_info(info()).dispatch();
+        assert_eq!(response.body_string(),
+                   Some("TLSv1.2 TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384 api.rocket.rs true".into()));
+    }
+
+    #[test]
+    fn session_details_follow_redirects() {
+        let rocket = rocket::ignite().mount("/", routes![super::session, super::moved]);
+        let client = Client::new(rocket).unwrap().follow_redirects(true);
+        let mut response = client.get("/moved").tls_info(info()).dispatch();
+        assert!(response.body_string().unwrap().starts_with("TLSv1.2 "));
+    }
+}
@@ -0,0 +1,119 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+#[get("/")]
+fn index() -> &'static str {
+    "index"
+}
+
+mod upgrade_insecure_tests {
+    use rocket;
+    use rocket::config::{Environment, Config, ConfigBuilder, Hsts};
+    use rocket::local::Client;
+    use rocket::http::{Status, Header};
+
+    fn rocket_upgrading(upgrade: bool, port: u16) -> rocket::Rocket {
+        rocket_from(Config::build(Environment::Development)
+            .port(port)
+            .hsts(Hsts::preload())
+            .upgrade_insecure_requests(upgrade))
+    }
+
+    fn rocket_from(builder: ConfigBuilder) -> rocket::Rocket {
+        rocket::custom(builder.unwrap(), true).mount("/", routes![super::index])
+    }
+
+    #[test]
+    fn upgrade_requests_are_redirected() {
+        let client = Client::new(rocket_upgrading(true, 8000)).unwrap();
+        let response = client.get("/?a=b")
+            .header(Header::new("Host", "example.com:8000"))
+            .header(Header::new("Upgrade-Insecure-Requests", "1"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::TemporaryRedirect);
+        assert_eq!(response.headers().get_one("Location"), Some("https://example.com/?a=b"));
+        assert_eq!(response.headers().get_one("Vary"), Some("Upgrade-Insecure-Requests"));
+
+        // HSTS is only ever sent over TLS.
+        assert!(response.headers().get_one("Strict-Transport-Security").is_none());
+    }
+
+    #[test]
+    fn other_requests_are_not_redirected() {
+        let client = Client::new(rocket_upgrading(true, 8000)).unwrap();
+        let mut response = client.get("/")
+            .header(Header::new("Host", "example.com"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some("index".into()));
+
+        let response = client.get("/")
+            .header(Header::new("Upgrade-Insecure-Requests", "1"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    #[test]
+    fn upgrades_disabled() {
+        for &(upgrade, port) in &[(false, 8000), (true, 443)] {
+            let client = Client::new(rocket_upgrading(upgrade, port)).unwrap();
+            let response = client.get("/")
+                .header(Header::new("Host", "example.com"))
+                .header(Header::new("Upgrade-Insecure-Requests", "1"))
+                .dispatch();
+
+            assert_eq!(response.status(), Status::Ok);
+        }
+    }
+
+    #[test]
+    fn upgrades_keep_the_https_port() {
+        let builder = Config::build(Environment::Development)
+            .port(8000)
+            .https_port(8443)
+            .upgrade_insecure_requests(true);
+
+        let client = Client::new(rocket_from(builder)).unwrap();
+
+        let response = client.get("/?a=b")
+            .header(Header::new("Host", "example.com:8000"))
+            .header(Header::new("Upgrade-Insecure-Requests", "1"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::TemporaryRedirect);
+        assert_eq!(response.headers().get_one("Location"), Some("https://example.com:8443/?a=b"));
+    }
+
+    #[test]
+    fn requests_trusted_proxies_received_over_https_are_not_upgraded() {
+        let builder = Config::build(Environment::Development)
+            .port(8000)
+            .upgrade_insecure_requests(true)
+            .trusted_proxy("10.0.0.0/8");
+
+        let client = Client::new(rocket_from(builder)).unwrap();
+
+        let request = |remote: &str, proto: &str| client.get("/")
+            .remote(remote.parse().unwrap())
+            .header(Header::new("Host", "example.com"))
+            .header(Header::new("Upgrade-Insecure-Requests", "1"))
+            .header(Header::new("X-Forwarded-Proto", proto.to_string()))
+            .dispatch()
+            .status();
+
+        // A trusted proxy that terminated TLS: the request was made over HTTPS.
+        assert_eq!(request("10.1.2.3:4000", "https"), Status::Ok);
+
+        // The last value is the one the trusted proxy set.
+        assert_eq!(request("10.1.2.3:4000", "https, http"), Status::TemporaryRedirect);
+        assert_eq!(request("10.1.2.3:4000", "http"), Status::TemporaryRedirect);
+
+        // Anyone else can't claim the request was made over HTTPS.
+        assert_eq!(request("8.8.8.8:4000", "https"), Status::TemporaryRedirect);
+    }
+}
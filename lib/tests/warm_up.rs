@@ -0,0 +1,53 @@
+#![feature(plugin, decl_macro)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+
+use std::sync::Mutex;
+
+use rocket::State;
+
+struct Log(Mutex<Vec<String>>);
+
+#[get("/prime?<q>")]
+fn prime(q: String, log: State<Log>) -> &'static str {
+    log.0.lock().unwrap().push(q);
+    "primed"
+}
+
+#[post("/fail")]
+fn fail() -> Result<(), ()> {
+    Err(())
+}
+
+mod warm_up_tests {
+    use super::Log;
+    use std::sync::Mutex;
+
+    use rocket;
+    use rocket::http::Method;
+    use rocket::local::Client;
+
+    #[test]
+    fn warm_ups_run_in_order_before_ready() {
+        let rocket = rocket::ignite()
+            .mount("/", routes![super::prime, super::fail])
+            .manage(Log(Mutex::new(vec![])))
+            .warm_up(Method::Get, "/prime?q=templates")
+            .warm_up(Method::Post, "/fail")
+            .warm_up(Method::Get, "/prime?q=pool");
+
+        assert!(!rocket.is_ready());
+        let client = Client::new(rocket).unwrap();
+        assert!(client.rocket().is_ready());
+
+        let log = client.rocket().state::<Log>().unwrap();
+        assert_eq!(*log.0.lock().unwrap(), vec!["templates", "pool"]);
+    }
+
+    #[test]
+    fn ready_without_warm_ups() {
+        let client = Client::new(rocket::ignite()).unwrap();
+        assert!(client.rocket().is_ready());
+    }
+}